@@ -39,6 +39,7 @@ let lastGenomeJson = null;
 let tick = 0;
 let tpsCounter = 0;
 let lastTpsUpdate = performance.now();
+let lastFrame = null;
 const tickElem = document.getElementById('tickCount');
 const tpsElem = document.getElementById('tpsCount');
 // Stats display elements
@@ -214,7 +215,7 @@ function updateStats() {
   document.getElementById('lootCount').textContent = `Loot: ${sim.lootCount()}`;
 }
 
-function draw() {
+function draw(alpha = 1) {
   ctx.clearRect(0,0,canvas.width,canvas.height);
   // Debug: dump raw agent buffer for first agent (manual read)
   {
@@ -245,8 +246,19 @@ function draw() {
   }
   const xOffsets = isToroidal ? [-W, 0, W] : [0];
   const yOffsets = isToroidal ? [-H, 0, H] : [0];
+  // Draw gravity wells beneath everything: attractors blue, repulsors red
+  const wells = sim.wellsData();
+  for (let i = 0; i < wells.length; i += 4) {
+    ctx.fillStyle = wells[i+2] >= 0 ? 'rgba(80,120,255,0.12)' : 'rgba(255,80,80,0.12)';
+    for (const [xx, yy] of getPositions(wells[i], wells[i+1])) {
+      ctx.beginPath();
+      ctx.arc(xx, yy, wells[i+3], 0, 2*Math.PI);
+      ctx.fill();
+    }
+  }
   // HP & Shield bar parameters
-  const t = 3, g = 3, R = 4;
+  // ship hull drawn at its hitbox radius (a point hitbox still gets a visible marker)
+  const t = 3, g = 3, R = sim.agentRadius() > 0 ? sim.agentRadius() : 4;
   const maxHealth = 100;
   const maxShield = sim.maxShield();
   const ptr = sim.agentsPtr() >>> 2;
@@ -254,13 +266,25 @@ function draw() {
   // compute ring radii so they sit outside the ship hull
   const healthRadius = R + t/2 + g;
   const shieldRadius = healthRadius + t + g;
+  // positions one tick back, to interpolate between fixed ticks
+  const prev = sim.prevAgentsData();
+  const lerp = prev.length === len;
   for (let i = ptr; i < ptr + len; i += 6) {
-    const x = mem[i], y = mem[i+1], teamId = mem[i+2]|0, health = mem[i+3], shield = mem[i+4];
+    let x = mem[i], y = mem[i+1];
+    const teamId = mem[i+2]|0, health = mem[i+3], shield = mem[i+4];
     if (health <= 0) continue;
+    if (lerp) {
+      const px = prev[i - ptr], py = prev[i - ptr + 1];
+      // skip interpolation across a wrap-around edge
+      if (Math.abs(x - px) < W / 2 && Math.abs(y - py) < H / 2) {
+        x = px + (x - px) * alpha;
+        y = py + (y - py) * alpha;
+      }
+    }
     for (const [xx, yy] of getPositions(x, y)) {
       ctx.fillStyle = hexToRgba(TEAM_COLORS[teamId], Math.max(health/100,0));
       ctx.beginPath();
-      ctx.arc(xx, yy, 4, 0, 2*Math.PI);
+      ctx.arc(xx, yy, R, 0, 2*Math.PI);
       ctx.fill();
       drawRing(ctx, xx, yy, shieldRadius, t, shield / maxShield,
                'rgba(255,0,0,0.5)', '#00ffff');
@@ -293,16 +317,34 @@ function draw() {
                'rgba(128,128,128,0.2)', 'rgba(192,192,192,0.8)');
     }
   }
+  // Draw projectiles
+  const bullets = sim.bullets_data();
+  const br = sim.bulletRadius();
+  ctx.fillStyle = 'rgba(255,255,255,0.9)';
+  for (let i = 0; i < bullets.length; i += 4) {
+    for (const [xx, yy] of getPositions(bullets[i], bullets[i+1])) {
+      ctx.fillRect(xx - br, yy - br, 2 * br, 2 * br);
+    }
+  }
+  // Draw area-of-effect blasts
+  const effects = sim.effects_data();
+  ctx.strokeStyle = 'rgba(255,160,0,0.8)';
+  for (let i = 0; i < effects.length; i += 3) {
+    for (const [xx, yy] of getPositions(effects[i], effects[i+1])) {
+      ctx.beginPath();
+      ctx.arc(xx, yy, effects[i+2], 0, 2*Math.PI);
+      ctx.stroke();
+    }
+  }
   // Draw hitscan vectors
-  const hitsPtr = sim.hitsPtr() >>> 2;
-  const hitsLen = sim.hitsLen();
+  const hits = sim.hits_data();
   ctx.strokeStyle = 'rgba(255,0,0,0.5)';
   for (let dx of xOffsets) {
     for (let dy of yOffsets) {
       ctx.beginPath();
-      for (let i = hitsPtr; i < hitsPtr + hitsLen; i += 4) {
-        const x1 = mem[i] + dx, y1 = mem[i+1] + dy;
-        const x2 = mem[i+2] + dx, y2 = mem[i+3] + dy;
+      for (let i = 0; i < hits.length; i += 4) {
+        const x1 = hits[i] + dx, y1 = hits[i+1] + dy;
+        const x2 = hits[i+2] + dx, y2 = hits[i+3] + dy;
         ctx.moveTo(x1, y1);
         ctx.lineTo(x2, y2);
       }
@@ -311,21 +353,26 @@ function draw() {
   }
 }
 
-function loop() {
+function loop(now) {
+  // fixed-timestep simulation: real time decides how many ticks run, not the refresh rate
+  const dt = lastFrame === null ? 0 : now - lastFrame;
+  lastFrame = now;
   if (!paused) {
+    const before = sim.tickCount();
+    let alpha;
     // Debug: catch WASM step panics
     try {
-      sim.step();
+      alpha = sim.advance(dt);
     } catch(e) {
-      console.error("WASM step() panic:", e);
+      console.error("WASM advance() panic:", e);
       paused = true;
       return;
     }
-    draw(); updateStats();
+    draw(alpha); updateStats();
     // update diagnostics
-    tick++;
-    tpsCounter++;
-    const now = performance.now();
+    const ran = sim.tickCount() - before;
+    tick += ran;
+    tpsCounter += ran;
     if (now - lastTpsUpdate >= 1000) {
       tpsElem.textContent = `TPS: ${tpsCounter}`;
       tpsCounter = 0;
@@ -357,7 +404,12 @@ async function initSim(genomeJson) {
     }
     const genomeObj = parsed.genome ? parsed.genome : parsed;
     const genomeStr = JSON.stringify(genomeObj);
-    sim = Simulation.new_champ_vs_naive(canvas.width, canvas.height, o, y, g, b, genomeStr);
+    try {
+      sim = Simulation.new_champ_vs_naive(canvas.width, canvas.height, o, y, g, b, genomeStr);
+    } catch (e) {
+      console.error("Invalid champion, falling back to NN vs naive:", e);
+      sim = Simulation.new_nn_vs_naive(canvas.width, canvas.height, o, y, g, b);
+    }
   } else {
     console.log("   → new_nn_vs_naive");
     sim = Simulation.new_nn_vs_naive(canvas.width, canvas.height, o, y, g, b);
@@ -0,0 +1,183 @@
+//! Micro-benchmarks for the hot loops: `Simulation::step` at several agent
+//! counts, `scan`, `Genome::feed_forward`, and `Genome::crossover`.
+//!
+//! `cargo bench --bench hot_loops [filter]`. Each benchmark is warmed up, then
+//! timed over many samples; the report gives the mean with its 95% interval
+//! and compares against the previous run's results (kept under `target/`),
+//! flagging changes that are both larger than the noise and above 2%.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::hint::black_box;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use rand::{rngs::StdRng, SeedableRng};
+use sim_core::config::Config;
+use sim_core::neat::config::EvolutionConfig;
+use sim_core::neat::genome::Genome;
+use sim_core::neat::innovation::InnovationTracker;
+use sim_core::Simulation;
+
+const WARM_UP: Duration = Duration::from_millis(300);
+const SAMPLE_TARGET: Duration = Duration::from_millis(5);
+const SAMPLES: usize = 60;
+/// Relative change below which a difference is reported as noise
+const NOISE_THRESHOLD: f64 = 0.02;
+
+/// Mean and spread of one benchmark's per-iteration times, in nanoseconds
+#[derive(Debug, Clone, Copy)]
+struct Estimate {
+    samples: usize,
+    mean: f64,
+    std_dev: f64,
+}
+
+impl Estimate {
+    fn from_samples(per_iter: &[f64]) -> Self {
+        let n = per_iter.len() as f64;
+        let mean = per_iter.iter().sum::<f64>() / n;
+        let var = per_iter.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0).max(1.0);
+        Estimate { samples: per_iter.len(), mean, std_dev: var.sqrt() }
+    }
+
+    fn std_err(&self) -> f64 {
+        self.std_dev / (self.samples as f64).sqrt()
+    }
+
+    /// Welch's t statistic against `before`
+    fn t_against(&self, before: &Estimate) -> f64 {
+        let se = (self.std_err().powi(2) + before.std_err().powi(2)).sqrt();
+        if se > 0.0 { (self.mean - before.mean) / se } else { 0.0 }
+    }
+}
+
+struct Runner {
+    filter: Option<String>,
+    baseline_path: PathBuf,
+    baseline: BTreeMap<String, Estimate>,
+    results: BTreeMap<String, Estimate>,
+}
+
+impl Runner {
+    fn from_args() -> Self {
+        // cargo bench passes `--bench`; anything else not a flag is a name filter
+        let filter = std::env::args().skip(1).find(|a| !a.starts_with("--"));
+        let target = std::env::var("CARGO_TARGET_DIR").unwrap_or_else(|_| "target".to_string());
+        let baseline_path = PathBuf::from(target).join("bench-baselines").join("hot_loops.tsv");
+        let baseline = fs::read_to_string(&baseline_path).unwrap_or_default().lines().filter_map(|line| {
+            let mut cols = line.split('\t');
+            let name = cols.next()?.to_string();
+            let mut num = || cols.next()?.parse::<f64>().ok();
+            Some((name, Estimate { samples: num()? as usize, mean: num()?, std_dev: num()? }))
+        }).collect();
+        Runner { filter, baseline_path, baseline, results: BTreeMap::new() }
+    }
+
+    /// Time `routine` on state from `setup`, which is rebuilt (untimed) for every sample
+    fn bench<S>(&mut self, name: &str, mut setup: impl FnMut() -> S, mut routine: impl FnMut(&mut S)) {
+        if self.filter.as_ref().is_some_and(|f| !name.contains(f.as_str())) {
+            return;
+        }
+        // warm up and size samples so each takes about SAMPLE_TARGET
+        let mut state = setup();
+        let (mut iters, start) = (0u64, Instant::now());
+        while start.elapsed() < WARM_UP {
+            routine(&mut state);
+            iters += 1;
+        }
+        let per_iter = start.elapsed().as_secs_f64() / iters as f64;
+        let iters = ((SAMPLE_TARGET.as_secs_f64() / per_iter) as u64).max(1);
+        let samples: Vec<f64> = (0..SAMPLES).map(|_| {
+            let mut state = setup();
+            let start = Instant::now();
+            for _ in 0..iters {
+                routine(&mut state);
+            }
+            start.elapsed().as_nanos() as f64 / iters as f64
+        }).collect();
+        let est = Estimate::from_samples(&samples);
+        let ci = 1.96 * est.std_err();
+        println!("{:<24} time: [{} {} {}]", name, fmt_ns(est.mean - ci), fmt_ns(est.mean), fmt_ns(est.mean + ci));
+        if let Some(before) = self.baseline.get(name) {
+            let change = est.mean / before.mean - 1.0;
+            let verdict = match est.t_against(before) {
+                _ if change.abs() < NOISE_THRESHOLD => "no change",
+                t if t > 2.0 => "REGRESSED",
+                t if t < -2.0 => "improved",
+                _ => "within noise",
+            };
+            println!("{:<24} change: {:+.2}% ({})", "", change * 100.0, verdict);
+        }
+        self.results.insert(name.to_string(), est);
+    }
+
+    /// Save this run's estimates as the baseline for the next
+    fn finish(self) {
+        let mut all = self.baseline;
+        all.extend(self.results);
+        let text: String = all.iter()
+            .map(|(name, e)| format!("{}\t{}\t{}\t{}\n", name, e.samples, e.mean, e.std_dev))
+            .collect();
+        if let Some(dir) = self.baseline_path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        if let Err(e) = fs::write(&self.baseline_path, text) {
+            eprintln!("could not save baseline {}: {}", self.baseline_path.display(), e);
+        }
+    }
+}
+
+fn fmt_ns(ns: f64) -> String {
+    match ns {
+        ns if ns >= 1e6 => format!("{:.3} ms", ns / 1e6),
+        ns if ns >= 1e3 => format!("{:.3} µs", ns / 1e3),
+        ns => format!("{:.1} ns", ns),
+    }
+}
+
+/// A genome grown by a few rounds of mutation, so it has hidden structure
+fn grown_genome(seed: u64, evo_cfg: &EvolutionConfig, tracker: &mut InnovationTracker) -> Genome {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut g = Genome::new();
+    g.initialize(&Config::default(), evo_cfg, &mut rng);
+    tracker.observe(&g);
+    for _ in 0..20 {
+        g.mutate(evo_cfg, tracker, &mut rng);
+    }
+    g
+}
+
+fn main() {
+    let mut runner = Runner::from_args();
+
+    // 100 steps from spawn, split evenly over the four quadrant teams
+    for agents in [4, 16, 64] {
+        let per_team = agents / 4;
+        runner.bench(
+            &format!("step/agents={}", agents),
+            || Simulation::new_seeded(1000, 1000, per_team, per_team, per_team, per_team, 7),
+            |sim| for _ in 0..100 { sim.step() },
+        );
+    }
+
+    let cfg = Config::default();
+    runner.bench(
+        "scan/agents=64",
+        || Simulation::new_seeded(1000, 1000, 16, 16, 16, 16, 7),
+        |sim| { black_box(sim.scan(black_box(0), cfg.nearest_k_enemies, cfg.scan_max_dist)); },
+    );
+
+    let evo_cfg = EvolutionConfig::default();
+    let mut tracker = InnovationTracker::new();
+    let (a, mut b) = (grown_genome(1, &evo_cfg, &mut tracker), grown_genome(2, &evo_cfg, &mut tracker));
+    b.fitness = 1.0;
+    let input = vec![0.25f32; a.input_size()];
+    runner.bench("feed_forward", || (), |_| { black_box(a.feed_forward(black_box(&input))); });
+    runner.bench(
+        "crossover",
+        || StdRng::seed_from_u64(3),
+        |rng| { black_box(Genome::crossover(&a, &b, &evo_cfg, rng)); },
+    );
+
+    runner.finish();
+}
@@ -4,4 +4,9 @@ fn main() {
         .out_dir("src/onnx_generated")
         .compile_protos(&["proto/onnx/onnx.proto"], &["proto"])
         .expect("ONNX proto compilation failed");
+    // gRPC inference client and server stubs
+    #[cfg(feature = "grpc")]
+    tonic_build::configure()
+        .compile(&["proto/inference/inference.proto"], &["proto"])
+        .expect("inference proto compilation failed");
 }
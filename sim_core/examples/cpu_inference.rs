@@ -10,7 +10,7 @@ fn main() {
     // Create a population and initialize its first genome
     let mut pop = Population::new(&evo_cfg);
     let mut genome = pop.genomes[0].clone();
-    genome.initialize(&sim_cfg, &evo_cfg);
+    genome.initialize(&sim_cfg, &evo_cfg, &mut rand::thread_rng());
     // Prepare a dummy input of appropriate size
     let input_size = genome.input_size();
     let dummy_input = vec![0.5_f32; input_size];
@@ -11,9 +11,9 @@ fn main() {
     // Initialize a population and ensure its first genome is built
     let pop = Population::new(&evo_cfg);
     let mut genome = pop.genomes[0].clone();
-    genome.initialize(&sim_cfg, &evo_cfg);
+    genome.initialize(&sim_cfg, &evo_cfg, &mut rand::thread_rng());
     // Export to ONNX bytes
-    let bytes = export_genome(&genome);
+    let bytes = export_genome(&genome).expect("Failed to export ONNX model");
     // Write the ONNX model next to the Python service
     fs::write("../python_onnx_service/model.onnx", bytes)
         .expect("Failed to write ONNX model to python_onnx_service/model.onnx");
@@ -1,6 +1,9 @@
 use crate::domain::{WorldView, Agent, Action, Vec2, Weapon};
 use crate::config::{Config, DistanceMode};
-use crate::brain::Brain;
+use crate::brain::{Brain, TeamBrain, Teammate};
+use std::collections::VecDeque;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 
 // AI state machine states
 enum AgentState {
@@ -10,15 +13,86 @@ enum AgentState {
     Looting { wreck: usize },
 }
 
+/// How hard a `NaiveAgent` plays. The default is its full strength; `at`
+/// ramps from `EASY` up to it.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Difficulty {
+    /// Ticks between seeing the world and acting on it
+    pub reaction_delay: u32,
+    /// Chance (0-1) that a shot is fumbled and the tick wasted
+    pub aim_error: f32,
+    /// Multiplier on thrust
+    pub speed_scale: f32,
+    /// Health ratio below which it flees
+    pub flee_ratio: f32,
+    /// Health ratio above which it re-engages
+    pub engage_ratio: f32,
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        let cfg = Config::default();
+        Difficulty {
+            reaction_delay: 0,
+            aim_error: 0.0,
+            speed_scale: 1.0,
+            flee_ratio: cfg.health_flee_ratio,
+            engage_ratio: cfg.health_engage_ratio,
+        }
+    }
+}
+
+impl Difficulty {
+    /// Bottom of the ramp: slow to react, misses half its shots, and runs early
+    pub const EASY: Difficulty = Difficulty {
+        reaction_delay: 8,
+        aim_error: 0.5,
+        speed_scale: 0.5,
+        flee_ratio: 0.5,
+        engage_ratio: 0.8,
+    };
+
+    /// Level `level` of `max_level`, linearly from `EASY` (0) to full strength (`max_level`)
+    pub fn at(level: usize, max_level: usize) -> Self {
+        let t = if max_level == 0 { 1.0 } else { level.min(max_level) as f32 / max_level as f32 };
+        let (easy, full) = (Difficulty::EASY, Difficulty::default());
+        let lerp = |a: f32, b: f32| a * (1.0 - t) + b * t;
+        Difficulty {
+            reaction_delay: lerp(easy.reaction_delay as f32, full.reaction_delay as f32).round() as u32,
+            aim_error: lerp(easy.aim_error, full.aim_error),
+            speed_scale: lerp(easy.speed_scale, full.speed_scale),
+            flee_ratio: lerp(easy.flee_ratio, full.flee_ratio),
+            engage_ratio: lerp(easy.engage_ratio, full.engage_ratio),
+        }
+    }
+}
+
 pub struct NaiveAgent {
     pub speed: f32,
     pub attack_damage: f32,
     pub state: AgentState,
+    pub difficulty: Difficulty,
+    /// Decided actions waiting out the reaction delay
+    pending: VecDeque<Action>,
+    /// Fixed-seed, so matches stay reproducible
+    rng: StdRng,
 }
 
 impl NaiveAgent {
     pub fn new(speed: f32, attack_damage: f32) -> Self {
-        NaiveAgent { speed, attack_damage, state: AgentState::Idle }
+        NaiveAgent::with_difficulty(speed, attack_damage, Difficulty::default())
+    }
+
+    pub fn with_difficulty(speed: f32, attack_damage: f32, difficulty: Difficulty) -> Self {
+        NaiveAgent {
+            speed,
+            attack_damage,
+            state: AgentState::Idle,
+            difficulty,
+            pending: VecDeque::new(),
+            rng: StdRng::seed_from_u64(0x4e41_4956),
+        }
     }
 
     /// Update AI state based on view & config
@@ -126,7 +200,7 @@ impl NaiveAgent {
                 if let Some((j, _)) = view.positions.iter().enumerate()
                     .filter(|(j,_)| *j != view.self_idx && view.healths[*j] > 0.0 && view.teams[*j] != view.self_team)
                     .map(|(j,p)| (j, view.dist2(*p, cfg)))
-                    .min_by(|a,b| a.1.partial_cmp(&b.1).unwrap()) {
+                    .min_by(|a,b| a.1.total_cmp(&b.1)) {
                     let p = view.positions[j];
                     let delta = view.delta(p, cfg);
                     let dist = delta.length().max(1e-6);
@@ -157,9 +231,23 @@ impl NaiveAgent {
 
 impl Agent for NaiveAgent {
     fn think(&mut self, view: &WorldView) -> Action {
-        let cfg = Config::default();
+        let d = self.difficulty;
+        let cfg = Config { health_flee_ratio: d.flee_ratio, health_engage_ratio: d.engage_ratio, ..Config::default() };
         self.update_state(view, &cfg);
-        self.decide_action(view, &cfg)
+        let action = match self.decide_action(view, &cfg) {
+            Action::Thrust(v) => Action::Thrust(Vec2 { x: v.x * d.speed_scale, y: v.y * d.speed_scale }),
+            Action::Fire { .. } if d.aim_error > 0.0 && self.rng.gen::<f32>() < d.aim_error => Action::Idle,
+            other => other,
+        };
+        if d.reaction_delay == 0 {
+            return action;
+        }
+        self.pending.push_back(action);
+        if self.pending.len() > d.reaction_delay as usize {
+            self.pending.pop_front().unwrap_or(Action::Idle)
+        } else {
+            Action::Idle
+        }
     }
 }
 
@@ -200,6 +288,36 @@ impl Brain for NNAgent {
     }
 }
 
+/// Centralized baseline: the whole team chases and fires on one target, the
+/// living enemy with the least health
+pub struct FocusFireTeam {
+    pub speed: f32,
+    pub attack_damage: f32,
+}
+
+impl TeamBrain for FocusFireTeam {
+    fn think_team(&mut self, _team: u32, members: &[Teammate]) -> Vec<Action> {
+        let Some(first) = members.first() else { return Vec::new() };
+        let v = &first.view;
+        let target = (0..v.positions.len())
+            .filter(|&j| v.teams[j] != v.self_team && v.healths[j] > 0.0)
+            .min_by(|&a, &b| v.healths[a].total_cmp(&v.healths[b]));
+        let cfg = Config::default();
+        members.iter().map(|m| match target {
+            None => Action::Idle,
+            Some(t) => {
+                let delta = m.view.delta(m.view.positions[t], &cfg);
+                if delta.length() <= m.view.attack_range {
+                    Action::Fire { weapon: Weapon::Laser { damage: self.attack_damage, range: m.view.attack_range } }
+                } else {
+                    let dir = delta.normalize();
+                    Action::Thrust(Vec2 { x: dir.x * self.speed, y: dir.y * self.speed })
+                }
+            }
+        }).collect()
+    }
+}
+
 // Unified distance helpers based on config
 impl<'a> WorldView<'a> {
     pub fn delta(&self, pos: Vec2, cfg: &Config) -> Vec2 {
@@ -532,4 +650,38 @@ mod tests {
             panic!("Expected Thrust action, got {:?}", action);
         }
     }
+
+    #[test]
+    fn difficulty_delays_and_fumbles_shots() {
+        let positions = vec![Vec2 { x: 0.0, y: 0.0 }, Vec2 { x: 10.0, y: 0.0 }];
+        let (teams, healths, shields) = (vec![0, 1], vec![100.0, 100.0], vec![0.0, 0.0]);
+        let view = WorldView {
+            self_idx: 0,
+            self_pos: positions[0],
+            self_team: 0,
+            self_health: healths[0],
+            self_shield: shields[0],
+            positions: &positions,
+            teams: &teams,
+            healths: &healths,
+            shields: &shields,
+            wreck_positions: &[],
+            wreck_pools: &[],
+            world_width: 1000.0,
+            world_height: 1000.0,
+            attack_range: Config::default().attack_range,
+            sep_range: Config::default().sep_range,
+        };
+        let slow = Difficulty { reaction_delay: 2, ..Difficulty::default() };
+        let mut agent = NaiveAgent::with_difficulty(1.0, 1.0, slow);
+        let actions: Vec<Action> = (0..3).map(|_| agent.think(&view)).collect();
+        assert!(matches!(actions[..], [Action::Idle, Action::Idle, Action::Fire { .. }]));
+
+        let blind = Difficulty { aim_error: 1.0, ..Difficulty::default() };
+        assert!(matches!(NaiveAgent::with_difficulty(1.0, 1.0, blind).think(&view), Action::Idle));
+
+        assert_eq!(Difficulty::at(0, 4), Difficulty::EASY);
+        assert_eq!(Difficulty::at(4, 4), Difficulty::default());
+        assert_eq!(Difficulty::at(2, 4).reaction_delay, 4);
+    }
 }
@@ -4,4 +4,24 @@ use crate::domain::{Action, WorldView};
 pub trait Brain {
     /// Decide action based on full world view and sensor inputs
     fn think(&mut self, view: &WorldView, inputs: &[f32]) -> Action;
+
+    /// Clear any memory carried between ticks; called when the agent joins a match
+    fn reset(&mut self) {}
+}
+
+/// One living teammate as a `TeamBrain` sees it: the same view and sensor
+/// inputs its own `Brain` would have been given
+pub struct Teammate<'a> {
+    pub view: WorldView<'a>,
+    pub inputs: Vec<f32>,
+}
+
+/// Centralized controller deciding for a whole team each tick, in place of
+/// the members' own brains (see `Simulation::set_team_brain`)
+pub trait TeamBrain {
+    /// One action per teammate, in the order given; missing actions are `Idle`
+    fn think_team(&mut self, team: u32, members: &[Teammate]) -> Vec<Action>;
+
+    /// Clear any memory carried between ticks; called when the brain is installed
+    fn reset(&mut self) {}
 }
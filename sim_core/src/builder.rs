@@ -0,0 +1,265 @@
+//! Builder for simulations with explicit per-agent spawn placement.
+
+use crate::{math, Simulation, Config, Brain, Vec2, DistanceMode, ShipClass};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::f32::consts::TAU;
+
+/// Attempts per agent before random placement gives up on min separation
+const MAX_PLACEMENT_ATTEMPTS: usize = 200;
+
+/// One agent to spawn: position, team, optional ship class, and decision maker
+pub struct SpawnSpec {
+    pub pos: Vec2,
+    pub team: u32,
+    pub class: Option<ShipClass>,
+    pub brain: Box<dyn Brain>,
+}
+
+/// Collects config, seed, and spawn specs, then builds a `Simulation`.
+///
+/// Random layouts draw from the builder's RNG as they are added, so call
+/// `seed()` before any `random_spread()` for reproducible placement.
+pub struct SimulationBuilder {
+    width: u32,
+    height: u32,
+    config: Config,
+    seed: u64,
+    rng: StdRng,
+    spawns: Vec<SpawnSpec>,
+}
+
+impl SimulationBuilder {
+    /// Start an empty builder for a `width` x `height` map with default config
+    pub fn new(width: u32, height: u32) -> Self {
+        SimulationBuilder {
+            width,
+            height,
+            config: Config::default(),
+            seed: 0,
+            rng: StdRng::seed_from_u64(0),
+            spawns: Vec::new(),
+        }
+    }
+
+    /// Use a custom simulation config
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Seed both placement randomness and the simulation RNG
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    /// Spawn a single agent at an explicit position
+    pub fn agent(mut self, pos: Vec2, team: u32, brain: Box<dyn Brain>) -> Self {
+        self.spawns.push(SpawnSpec { pos, team, class: None, brain });
+        self
+    }
+
+    /// Add a single agent with a ship class
+    pub fn agent_with_class(mut self, pos: Vec2, team: u32, class: ShipClass, brain: Box<dyn Brain>) -> Self {
+        self.spawns.push(SpawnSpec { pos, team, class: Some(class), brain });
+        self
+    }
+
+    /// Spawn agents evenly spaced on a circle around `center`
+    pub fn ring(mut self, center: Vec2, radius: f32, team: u32, brains: Vec<Box<dyn Brain>>) -> Self {
+        let positions = ring_positions(center, radius, brains.len());
+        self.push_all(positions, team, brains);
+        self
+    }
+
+    /// Spawn agents evenly spaced from `start` to `end` (inclusive)
+    pub fn line(mut self, start: Vec2, end: Vec2, team: u32, brains: Vec<Box<dyn Brain>>) -> Self {
+        let positions = line_positions(start, end, brains.len());
+        self.push_all(positions, team, brains);
+        self
+    }
+
+    /// Spawn agents at random positions at least `min_sep` from every agent placed so far
+    pub fn random_spread(mut self, min_sep: f32, team: u32, brains: Vec<Box<dyn Brain>>) -> Self {
+        let existing: Vec<Vec2> = self.spawns.iter().map(|s| s.pos).collect();
+        let positions = random_positions(
+            &mut self.rng, self.width as f32, self.height as f32,
+            brains.len(), min_sep, &existing, self.config.distance_mode,
+        );
+        self.push_all(positions, team, brains);
+        self
+    }
+
+    /// Spawn `counts[t]` agents for every team t, scattered within `cluster_radius`
+    /// of that team's point on `team_centers`. `factory` builds a brain per team id.
+    pub fn team_clusters<F>(mut self, counts: &[u32], cluster_radius: f32, factory: F) -> Self
+    where
+        F: Fn(u32) -> Box<dyn Brain>,
+    {
+        let centers = team_centers(self.width as f32, self.height as f32, counts.len());
+        for (t, (&count, center)) in counts.iter().zip(centers).enumerate() {
+            for _ in 0..count {
+                let a = self.rng.gen::<f32>() * TAU;
+                let r = cluster_radius * self.rng.gen::<f32>().sqrt();
+                let pos = Vec2 { x: center.x + r * math::cos(a), y: center.y + r * math::sin(a) };
+                self.spawns.push(SpawnSpec { pos, team: t as u32, class: None, brain: factory(t as u32) });
+            }
+        }
+        self
+    }
+
+    fn push_all(&mut self, positions: Vec<Vec2>, team: u32, brains: Vec<Box<dyn Brain>>) {
+        for (pos, brain) in positions.into_iter().zip(brains) {
+            self.spawns.push(SpawnSpec { pos, team, class: None, brain });
+        }
+    }
+
+    /// Build the simulation; positions are wrapped (toroidal) or clamped (euclidean) onto the map
+    pub fn build(self) -> Simulation {
+        let w = self.width as f32;
+        let h = self.height as f32;
+        let mut sim = Simulation::empty(self.width, self.height);
+        sim.config = self.config;
+        sim.reseed(self.seed);
+        sim.agents_data.reserve(self.spawns.len() * crate::AGENT_STRIDE);
+        for spec in self.spawns {
+            let pos = match sim.config.distance_mode {
+                DistanceMode::Toroidal => spec.pos.wrap(w, h),
+                DistanceMode::Euclidean => Vec2 { x: spec.pos.x.clamp(0.0, w), y: spec.pos.y.clamp(0.0, h) },
+            };
+            sim.push_agent_with_class(pos, spec.team, spec.class, spec.brain);
+        }
+        sim
+    }
+}
+
+/// `n` points evenly spaced on a circle, starting at angle 0
+pub fn ring_positions(center: Vec2, radius: f32, n: usize) -> Vec<Vec2> {
+    (0..n)
+        .map(|i| {
+            let a = TAU * i as f32 / n as f32;
+            Vec2 { x: center.x + radius * math::cos(a), y: center.y + radius * math::sin(a) }
+        })
+        .collect()
+}
+
+/// Home points for `n` teams, evenly spaced on a ring around the map center
+pub fn team_centers(w: f32, h: f32, n: usize) -> Vec<Vec2> {
+    let center = Vec2 { x: w * 0.5, y: h * 0.5 };
+    if n <= 1 {
+        return vec![center; n];
+    }
+    ring_positions(center, w.min(h) * 0.35, n)
+}
+
+/// `n` points evenly spaced along a segment; a single point sits at the midpoint
+pub fn line_positions(start: Vec2, end: Vec2, n: usize) -> Vec<Vec2> {
+    if n == 1 {
+        return vec![Vec2 { x: (start.x + end.x) * 0.5, y: (start.y + end.y) * 0.5 }];
+    }
+    (0..n)
+        .map(|i| {
+            let t = i as f32 / (n - 1) as f32;
+            Vec2 { x: start.x + (end.x - start.x) * t, y: start.y + (end.y - start.y) * t }
+        })
+        .collect()
+}
+
+/// `n` uniform random points at least `min_sep` apart from each other and from `existing`.
+/// Falls back to the last candidate if no valid spot is found after a bounded number of tries.
+pub fn random_positions<R: Rng>(
+    rng: &mut R,
+    w: f32,
+    h: f32,
+    n: usize,
+    min_sep: f32,
+    existing: &[Vec2],
+    mode: DistanceMode,
+) -> Vec<Vec2> {
+    let min2 = min_sep * min_sep;
+    let dist2 = |a: Vec2, b: Vec2| match mode {
+        DistanceMode::Toroidal => a.torus_dist2(b, w, h),
+        DistanceMode::Euclidean => (a.x - b.x) * (a.x - b.x) + (a.y - b.y) * (a.y - b.y),
+    };
+    let mut placed: Vec<Vec2> = existing.to_vec();
+    let mut out = Vec::with_capacity(n);
+    for _ in 0..n {
+        let mut candidate = Vec2 { x: 0.0, y: 0.0 };
+        for _ in 0..MAX_PLACEMENT_ATTEMPTS {
+            candidate = Vec2 { x: rng.gen::<f32>() * w, y: rng.gen::<f32>() * h };
+            if placed.iter().all(|&p| dist2(p, candidate) >= min2) {
+                break;
+            }
+        }
+        placed.push(candidate);
+        out.push(candidate);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::{NaiveAgent, NaiveBrain};
+    use crate::{AGENT_STRIDE, IDX_X, IDX_Y, IDX_TEAM};
+
+    fn brains(n: usize) -> Vec<Box<dyn Brain>> {
+        (0..n).map(|_| Box::new(NaiveBrain(NaiveAgent::new(1.0, 1.0))) as Box<dyn Brain>).collect()
+    }
+
+    #[test]
+    fn explicit_agents_keep_position_and_team() {
+        let sim = SimulationBuilder::new(100, 100)
+            .agent(Vec2 { x: 10.0, y: 20.0 }, 0, brains(1).pop().unwrap())
+            .agent(Vec2 { x: 80.0, y: 70.0 }, 3, brains(1).pop().unwrap())
+            .build();
+        assert_eq!(sim.agents_data.len(), 2 * AGENT_STRIDE);
+        assert_eq!(sim.agents_data[IDX_X], 10.0);
+        assert_eq!(sim.agents_data[IDX_Y], 20.0);
+        assert_eq!(sim.agents_data[AGENT_STRIDE + IDX_TEAM], 3.0);
+        assert_eq!(sim.agents_impl.len(), 2);
+    }
+
+    #[test]
+    fn ring_positions_are_on_circle() {
+        let c = Vec2 { x: 50.0, y: 50.0 };
+        for p in ring_positions(c, 10.0, 6) {
+            let d = Vec2 { x: p.x - c.x, y: p.y - c.y }.length();
+            assert!((d - 10.0).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn team_clusters_assign_every_team() {
+        let sim = SimulationBuilder::new(500, 500)
+            .team_clusters(&[1, 2, 3, 4, 5], 20.0, |_| brains(1).pop().unwrap())
+            .build();
+        assert_eq!(sim.agent_count(), 15);
+        assert_eq!(sim.team_alive_counts(), vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn line_positions_hit_endpoints() {
+        let pts = line_positions(Vec2 { x: 0.0, y: 0.0 }, Vec2 { x: 10.0, y: 0.0 }, 3);
+        assert_eq!(pts[0].x, 0.0);
+        assert_eq!(pts[1].x, 5.0);
+        assert_eq!(pts[2].x, 10.0);
+    }
+
+    #[test]
+    fn random_spread_respects_min_separation_and_seed() {
+        let build = || SimulationBuilder::new(1000, 1000).seed(9).random_spread(50.0, 0, brains(8)).build();
+        let a = build();
+        let b = build();
+        assert_eq!(a.agents_data, b.agents_data);
+        let n = a.agents_data.len() / AGENT_STRIDE;
+        for i in 0..n {
+            for j in (i + 1)..n {
+                let dx = a.agents_data[i * AGENT_STRIDE + IDX_X] - a.agents_data[j * AGENT_STRIDE + IDX_X];
+                let dy = a.agents_data[i * AGENT_STRIDE + IDX_Y] - a.agents_data[j * AGENT_STRIDE + IDX_Y];
+                assert!(dx * dx + dy * dy >= 50.0 * 50.0);
+            }
+        }
+    }
+}
@@ -8,6 +8,7 @@ pub fn run(sim: &mut Simulation) {
     let h = sim.height as f32;
     let agent_count = sim.agents_data.len() / AGENT_STRIDE;
     let mut new_bullets = Vec::with_capacity(sim.bullets_data.len());
+    let reach = sim.config.agent_radius + sim.config.bullet_radius;
 
     for chunk in sim.bullets_data.chunks(4) {
         let mut x = chunk[0];
@@ -21,7 +22,7 @@ pub fn run(sim: &mut Simulation) {
         let wrapped = Vec2 { x, y }.wrap(w, h);
         x = wrapped.x;
         y = wrapped.y;
-        // collision detection radius = 1.0
+        // collision: bullet and hull circles overlap
         let mut hit = false;
         for idx in 0..agent_count {
             let base = idx * AGENT_STRIDE;
@@ -29,7 +30,7 @@ pub fn run(sim: &mut Simulation) {
             if health > 0.0 {
                 let dx = sim.agents_data[base + IDX_X] - x;
                 let dy = sim.agents_data[base + IDX_Y] - y;
-                if dx*dx + dy*dy <= 1.0 {
+                if dx*dx + dy*dy <= reach * reach {
                     sim.agents_data[base + IDX_HEALTH] -= damage;
                     hit = true;
                     break;
@@ -45,3 +46,23 @@ pub fn run(sim: &mut Simulation) {
     }
     sim.bullets_data = new_bullets;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bullets_hit_within_combined_radii() {
+        let mut sim = Simulation::new(100, 100, 0, 0, 0, 0);
+        sim.agents_data = vec![50.0, 50.0, 0.0, 100.0, 0.0, 0.0];
+        let fire = |sim: &mut Simulation| {
+            sim.bullets_data = vec![53.0, 50.0, 5.0, 10.0];
+            run(sim);
+            sim.bullets_data.is_empty()
+        };
+        assert!(!fire(&mut sim));
+        sim.config.agent_radius = 2.0;
+        assert!(fire(&mut sim));
+        assert_eq!(sim.agents_data[IDX_HEALTH], 95.0);
+    }
+}
@@ -1,79 +1,205 @@
 use crate::Simulation;
 use crate::{AGENT_STRIDE, IDX_X, IDX_Y, IDX_TEAM, IDX_HEALTH, IDX_SHIELD, IDX_LAST_HIT};
-use crate::domain::{Action, Weapon};
+use crate::domain::{Action, Vec2, Weapon};
+use crate::facing::Side;
+use crate::status::{Status, WEAKEN_FACTOR};
+
+/// Offset from `from` to `to`, across the map edge in toroidal mode
+fn offset(sim: &Simulation, from: Vec2, to: Vec2) -> Vec2 {
+    if sim.is_toroidal() {
+        from.torus_delta(to, sim.width as f32, sim.height as f32)
+    } else {
+        Vec2 { x: to.x - from.x, y: to.y - from.y }
+    }
+}
+
+fn position(sim: &Simulation, id: usize) -> Vec2 {
+    let base = id * AGENT_STRIDE;
+    Vec2 { x: sim.agents_data[base + IDX_X], y: sim.agents_data[base + IDX_Y] }
+}
+
+/// Nearest living enemy of `id` within `reach`, with its distance
+fn nearest_enemy(sim: &Simulation, id: usize, reach: f32) -> Option<(usize, f32)> {
+    let team = sim.agents_data[id * AGENT_STRIDE + IDX_TEAM] as u32;
+    let me = position(sim, id);
+    (0..sim.agents_data.len() / AGENT_STRIDE)
+        .filter(|&j| {
+            let b = j * AGENT_STRIDE;
+            j != id && sim.agents_data[b + IDX_HEALTH] > 0.0 && sim.agents_data[b + IDX_TEAM] as u32 != team
+        })
+        .map(|j| (j, offset(sim, me, position(sim, j)).length()))
+        .filter(|&(_, d)| d <= reach)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+/// Damage agent `ti` with a shot from `from`: shield absorbs first (with
+/// directional shields, the facing it strikes), the rest comes off health;
+/// a killing blow leaves a wreck
+fn apply_damage(sim: &mut Simulation, ti: usize, damage: f32, from: Vec2) {
+    let tb = ti * AGENT_STRIDE;
+    // record hit time and apply damage to shield first
+    let tick = sim.tick_count;
+    sim.agents_data[tb + IDX_LAST_HIT] = tick as f32;
+    let side = if sim.config.directional_shields {
+        let toward = offset(sim, position(sim, ti), from);
+        Some(sim.agent_facing(ti).side_of(toward))
+    } else {
+        None
+    };
+    let spill = if side == Some(Side::Rear) {
+        let max = sim.agent_max_shield(ti);
+        let facing = sim.facing_mut(ti);
+        facing.rear_hit = tick;
+        let absorbed = facing.rear_shield(max).min(damage);
+        facing.rear_deficit += absorbed;
+        damage - absorbed
+    } else {
+        if side.is_some() {
+            sim.facing_mut(ti).front_hit = tick;
+        }
+        let sh = &mut sim.agents_data[tb + IDX_SHIELD];
+        let absorbed = sh.min(damage);
+        *sh -= absorbed;
+        damage - absorbed
+    };
+    let was_alive = sim.agents_data[tb + IDX_HEALTH] > 0.0;
+    sim.agents_data[tb + IDX_HEALTH] -= spill;
+    // If this shot killed the target, spawn a wreck
+    if was_alive && sim.agents_data[tb + IDX_HEALTH] <= 0.0 {
+        let px = sim.agents_data[tb + IDX_X];
+        let py = sim.agents_data[tb + IDX_Y];
+        let init = sim.config.health_max * sim.config.loot_init_ratio;
+        sim.wrecks_data.extend(&[px, py, init]);
+    }
+}
 
 /// Execute the combat phase (fire resolution) outside of Simulation.
 pub fn run(sim: &mut Simulation) {
     let agent_count = sim.agents_data.len() / AGENT_STRIDE;
-    for (&id, action) in sim.commands.iter() {
-        if let Action::Fire { ref weapon } = action {
-            match weapon {
-                // hitscan: find nearest living enemy within weapon.range
-                Weapon::Laser { damage, range } => {
-                    let base_i = id * AGENT_STRIDE;
-                    let sx = sim.agents_data[base_i + IDX_X];
-                    let sy = sim.agents_data[base_i + IDX_Y];
-                    let shooter_team = sim.agents_data[base_i + IDX_TEAM] as usize;
-                    let mut closest = None;
-                    let mut dmin = f32::MAX;
-                    for j in 0..agent_count {
-                        let basej = j * AGENT_STRIDE;
-                        let h2 = sim.agents_data[basej + IDX_HEALTH];
-                        let t2 = sim.agents_data[basej + IDX_TEAM] as usize;
-                        if j != id && h2 > 0.0 && t2 != shooter_team {
-                            let dx = sim.agents_data[basej + IDX_X] - sx;
-                            let dy = sim.agents_data[basej + IDX_Y] - sy;
-                            let dist2 = dx * dx + dy * dy;
-                            if dist2 < dmin {
-                                dmin = dist2;
-                                closest = Some(j);
-                            }
-                        }
+    // damage needs `sim` mutably, so walk the commands out of it
+    let commands = std::mem::take(&mut sim.commands);
+    for (&id, action) in commands.iter() {
+        // ship classes can only fire from their loadout, one slot at a time
+        let (weapon, slot) = match (action, sim.agent_class(id)) {
+            (Action::Fire { weapon: requested }, None) => (requested.clone(), None),
+            (Action::FireSlot { .. }, None) => (sim.default_weapon(id), None),
+            (Action::Fire { weapon: requested }, Some(class)) => match class.resolve_slot(requested) {
+                Some(i) => (class.slots[i].weapon.clone(), Some(i)),
+                None => continue,
+            },
+            (Action::FireSlot { slot }, Some(class)) => match class.select_slot(*slot) {
+                Some(i) => (class.slots[i].weapon.clone(), Some(i)),
+                None => continue,
+            },
+            _ => continue,
+        };
+        // bullets carry no shooter to credit hits to, so missiles go untallied
+        let tallied = !matches!(weapon, Weapon::Missile { .. });
+        if tallied {
+            sim.shot_tally_mut(id).trigger_pulls += 1;
+        }
+        if let Some(i) = slot {
+            let ready = sim.agent_class(id).is_some_and(|c| sim.agent_slots(id).ready(i, &c.slots[i], sim.tick_count));
+            if !ready {
+                continue;
+            }
+        }
+        // a weakened shooter deals reduced damage with any weapon
+        // Every weapon goes off whether or not anything is in reach: a shot
+        // at nothing still counts as a miss and spends the slot
+        let weaken = if sim.agent_status(id).has(Status::Weakened) { WEAKEN_FACTOR } else { 1.0 };
+        let (fired, hit) = match &weapon {
+            // hitscan: find nearest living enemy within weapon.range
+            Weapon::Laser { damage, range } => {
+                let shooter_team = sim.agents_data[id * AGENT_STRIDE + IDX_TEAM] as u32;
+                // per-team overrides cap range and scale damage
+                let team_cfg = sim.config.team_override(shooter_team);
+                let range = team_cfg.and_then(|o| o.attack_range).map_or(*range, |r| range.min(r));
+                let damage = *damage * sim.config.team_damage_scale(shooter_team) * weaken;
+                // the target's hull only has to touch the end of the beam
+                let reach = range + sim.config.agent_radius;
+                if let Some((ti, d)) = nearest_enemy(sim, id, reach) {
+                    // weaker at long range, measured to the target's hull
+                    let t = if range > 0.0 { (d - sim.config.agent_radius).max(0.0) / range } else { 0.0 };
+                    let damage = damage * sim.config.laser_falloff.scale(t, sim.config.laser_falloff_floor);
+                    let (from, to) = (position(sim, id), position(sim, ti));
+                    sim.hits_data.extend(&[from.x, from.y, to.x, to.y]);
+                    apply_damage(sim, ti, damage, from);
+                    sim.fire_count += 1;
+                    (true, true)
+                } else {
+                    (true, false)
+                }
+            }
+            Weapon::Missile { damage, speed: _, ttl: _ } => {
+                // spawn simple bullet: push pos x,y and damage
+                let base = id * AGENT_STRIDE;
+                let x = sim.agents_data[base + IDX_X];
+                let y = sim.agents_data[base + IDX_Y];
+                sim.bullets_data.push(x);
+                sim.bullets_data.push(y);
+                sim.bullets_data.push(*damage * sim.config.team_damage_scale(sim.agents_data[base + IDX_TEAM] as u32) * weaken);
+                sim.bullets_data.push(0.0);
+                (true, false)
+            }
+            Weapon::Burst { damage, radius, range } => 'burst: {
+                let shooter_team = sim.agents_data[id * AGENT_STRIDE + IDX_TEAM] as u32;
+                let team_cfg = sim.config.team_override(shooter_team);
+                let range = team_cfg.and_then(|o| o.attack_range).map_or(*range, |r| range.min(r));
+                let damage = *damage * sim.config.team_damage_scale(shooter_team);
+                let damage = damage * weaken;
+                // detonate on the nearest living enemy in reach
+                let Some((target, _)) = nearest_enemy(sim, id, range + sim.config.agent_radius) else { break 'burst (true, false) };
+                let center = position(sim, target);
+                let blast = *radius + sim.config.agent_radius;
+                let mut hit = false;
+                for j in 0..agent_count {
+                    if j == id || sim.agents_data[j * AGENT_STRIDE + IDX_HEALTH] <= 0.0 {
+                        continue;
                     }
-                    if let Some(ti) = closest {
-                        if dmin <= range * range {
-                            let tb = ti * AGENT_STRIDE;
-                            sim.hits_data.push(sx);
-                            sim.hits_data.push(sy);
-                            sim.hits_data.push(sim.agents_data[tb + IDX_X]);
-                            sim.hits_data.push(sim.agents_data[tb + IDX_Y]);
-                            // record hit time and apply damage to shield first
-                            sim.agents_data[tb + IDX_LAST_HIT] = sim.tick_count as f32;
-                            let sh = &mut sim.agents_data[tb + IDX_SHIELD];
-                            let spill = if *sh >= *damage {
-                                *sh -= *damage;
-                                0.0
-                            } else {
-                                let rem = *damage - *sh;
-                                *sh = 0.0;
-                                rem
-                            };
-                            sim.agents_data[tb + IDX_HEALTH] -= spill;
-                            // If this shot killed the target, spawn a wreck
-                            if sim.agents_data[tb + IDX_HEALTH] <= 0.0 {
-                                let px = sim.agents_data[tb + IDX_X];
-                                let py = sim.agents_data[tb + IDX_Y];
-                                let init = sim.config.health_max * sim.config.loot_init_ratio;
-                                sim.wrecks_data.extend(&[px, py, init]);
-                            }
-                            sim.fire_count += 1;
+                    let d = offset(sim, center, position(sim, j)).length();
+                    if d <= blast && blast > 0.0 {
+                        let dealt = damage * (1.0 - d / blast);
+                        apply_damage(sim, j, dealt, center);
+                        if sim.agents_data[j * AGENT_STRIDE + IDX_TEAM] as u32 == shooter_team {
+                            sim.shot_tally_mut(id).friendly_damage += dealt;
+                        } else {
+                            hit = true;
                         }
                     }
                 }
-                Weapon::Missile { damage, speed: _, ttl: _ } => {
-                    // spawn simple bullet: push pos x,y and damage
-                    let base = id * AGENT_STRIDE;
-                    let x = sim.agents_data[base + IDX_X];
-                    let y = sim.agents_data[base + IDX_Y];
-                    sim.bullets_data.push(x);
-                    sim.bullets_data.push(y);
-                    sim.bullets_data.push(*damage);
-                    sim.bullets_data.push(0.0);
-                }
-                _ => {}
+                sim.effects_data.extend(&[center.x, center.y, *radius]);
+                sim.fire_count += 1;
+                (true, hit)
+            }
+            Weapon::Emp { status, ticks, range } => 'emp: {
+                let shooter_team = sim.agents_data[id * AGENT_STRIDE + IDX_TEAM] as u32;
+                let range = sim.config.team_override(shooter_team)
+                    .and_then(|o| o.attack_range)
+                    .map_or(*range, |r| range.min(r));
+                let Some((target, _)) = nearest_enemy(sim, id, range + sim.config.agent_radius) else { break 'emp (true, false) };
+                let (from, to) = (position(sim, id), position(sim, target));
+                sim.hits_data.extend(&[from.x, from.y, to.x, to.y]);
+                sim.inflict(target, *status, *ticks);
+                sim.fire_count += 1;
+                (true, true)
+            }
+        };
+        if fired && tallied {
+            let tally = sim.shot_tally_mut(id);
+            tally.shots += 1;
+            tally.hits += hit as u32;
+        }
+        // only shots that went off use up the slot
+        if let (true, Some(i)) = (fired, slot) {
+            let tick = sim.tick_count;
+            let loaded = sim.agent_class(id).map(|c| c.slots[i].clone());
+            if let Some(loaded) = loaded {
+                sim.slot_state_mut(id).fire(i, &loaded, tick);
             }
         }
     }
+    sim.commands = commands;
 }
 
 // Unit tests for combat phase
@@ -127,6 +253,160 @@ mod tests {
         assert_eq!(sim.hits_data.len(), 4);
     }
 
+    #[test]
+    fn team_override_scales_damage_and_caps_range() {
+        let mut sim = make_sim(&[(0.0, 0.0, 0, 100.0), (3.0, 4.0, 1, 100.0)]);
+        sim.config.team_overrides.push(crate::config::TeamOverrides {
+            team: 0, damage_scale: Some(2.0), ..Default::default()
+        });
+        sim.commands.insert(0, Action::Fire { weapon: Weapon::Laser { damage: 5.0, range: 10.0 } });
+        run(&mut sim);
+        assert_eq!(sim.agents_data[AGENT_STRIDE + IDX_SHIELD], sim.config.max_shield - 10.0);
+
+        let mut sim = make_sim(&[(0.0, 0.0, 0, 100.0), (3.0, 4.0, 1, 100.0)]);
+        sim.config.team_overrides.push(crate::config::TeamOverrides {
+            team: 0, attack_range: Some(4.0), ..Default::default()
+        });
+        sim.commands.insert(0, Action::Fire { weapon: Weapon::Laser { damage: 5.0, range: 10.0 } });
+        run(&mut sim);
+        assert_eq!(sim.fire_count, 0);
+    }
+
+    #[test]
+    fn class_loadout_overrides_requested_weapon() {
+        use crate::ship::ShipClass;
+        let mut sim = make_sim(&[(0.0, 0.0, 0, 100.0), (3.0, 4.0, 1, 100.0)]);
+        sim.agent_classes = vec![
+            Some(ShipClass::new("sniper", 100.0, 50.0, 0.04, vec![Weapon::Laser { damage: 7.0, range: 10.0 }])),
+            None,
+        ];
+        // requested range is too short, but the loadout laser reaches
+        sim.commands.insert(0, Action::Fire { weapon: Weapon::Laser { damage: 1.0, range: 1.0 } });
+        run(&mut sim);
+        assert_eq!(sim.agents_data[AGENT_STRIDE + IDX_SHIELD], sim.config.max_shield - 7.0);
+    }
+
+    #[test]
+    fn selected_slot_waits_for_cooldown_and_ammo() {
+        use crate::ship::{ShipClass, WeaponSlot};
+        let mut sim = make_sim(&[(0.0, 0.0, 0, 100.0), (3.0, 4.0, 1, 100.0)]);
+        sim.agent_classes = vec![
+            Some(ShipClass::with_slots("duo", 100.0, 50.0, 0.04, vec![
+                WeaponSlot::new(Weapon::Laser { damage: 1.0, range: 10.0 }),
+                WeaponSlot::new(Weapon::Laser { damage: 4.0, range: 10.0 }).with_cooldown(1).with_ammo(2),
+            ])),
+            None,
+        ];
+        let shield = |sim: &Simulation| sim.agents_data[AGENT_STRIDE + IDX_SHIELD];
+        let max = sim.config.max_shield;
+        let fire = |sim: &mut Simulation, slot: usize| {
+            sim.commands.insert(0, Action::FireSlot { slot });
+            run(sim);
+            sim.tick_count += 1;
+        };
+        fire(&mut sim, 5);
+        assert_eq!(shield(&sim), max - 4.0);
+        // cooling down: nothing fires, while the free slot still can
+        fire(&mut sim, 1);
+        assert_eq!(shield(&sim), max - 4.0);
+        fire(&mut sim, 0);
+        assert_eq!(shield(&sim), max - 5.0);
+        fire(&mut sim, 1);
+        fire(&mut sim, 1);
+        assert_eq!(shield(&sim), max - 9.0);
+        assert_eq!(sim.agent_slots(0).ammo_left(1, &sim.agent_class(0).unwrap().slots[1]), Some(0));
+        // pulls into a cooldown are wasted
+        let tally = sim.agent_shots(0);
+        assert_eq!((tally.trigger_pulls, tally.shots, tally.hits), (5, 3, 3));
+    }
+
+    #[test]
+    fn agent_radius_extends_laser_reach() {
+        let mut sim = make_sim(&[(0.0, 0.0, 0, 100.0), (6.0, 8.0, 1, 100.0)]);
+        sim.commands.insert(0, Action::Fire { weapon: Weapon::Laser { damage: 5.0, range: 9.0 } });
+        run(&mut sim);
+        assert_eq!(sim.fire_count, 0);
+        sim.config.agent_radius = 1.0;
+        run(&mut sim);
+        assert_eq!(sim.fire_count, 1);
+    }
+
+    #[test]
+    fn burst_falls_off_and_wraps() {
+        // shooter at the left edge; enemies across the wrap and a teammate next to them
+        let mut sim = make_sim(&[(2.0, 50.0, 0, 100.0), (95.0, 50.0, 1, 100.0), (90.0, 50.0, 0, 100.0), (50.0, 50.0, 1, 100.0)]);
+        sim.config.distance_mode = crate::config::DistanceMode::Toroidal;
+        sim.commands.insert(0, Action::Fire { weapon: Weapon::Burst { damage: 10.0, radius: 10.0, range: 20.0 } });
+        run(&mut sim);
+        let shield = |i: usize| sim.agents_data[i * AGENT_STRIDE + IDX_SHIELD];
+        let max = sim.config.max_shield;
+        assert_eq!(shield(0), max);
+        assert_eq!(shield(1), max - 10.0);
+        assert!((shield(2) - (max - 5.0)).abs() < 1e-4);
+        assert_eq!(shield(3), max);
+        assert_eq!(sim.effects_data, vec![95.0, 50.0, 10.0]);
+        assert_eq!(sim.fire_count, 1);
+        assert!((sim.agent_shots(0).friendly_damage - 5.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn emp_disrupts_and_weakened_shots_hurt_less() {
+        let mut sim = make_sim(&[(0.0, 0.0, 0, 100.0), (3.0, 4.0, 1, 100.0)]);
+        sim.commands.insert(1, Action::Fire { weapon: Weapon::Emp { status: Status::Weakened, ticks: 3, range: 10.0 } });
+        run(&mut sim);
+        assert!(sim.agent_status(0).has(Status::Weakened));
+        assert_eq!(sim.agents_data[IDX_SHIELD], sim.config.max_shield);
+        assert_eq!(sim.hits_data.len(), 4);
+
+        sim.commands.clear();
+        sim.commands.insert(0, Action::Fire { weapon: Weapon::Laser { damage: 6.0, range: 10.0 } });
+        run(&mut sim);
+        assert_eq!(sim.agents_data[AGENT_STRIDE + IDX_SHIELD], sim.config.max_shield - 3.0);
+    }
+
+    #[test]
+    fn directional_shields_take_hits_by_facing() {
+        let mut sim = make_sim(&[(0.0, 0.0, 0, 100.0), (10.0, 0.0, 1, 100.0)]);
+        sim.config.directional_shields = true;
+        let max = sim.config.max_shield;
+        // target heads away from the shooter: the shot lands on its rear
+        sim.facing_mut(1).steer(Vec2 { x: 1.0, y: 0.0 });
+        sim.commands.insert(0, Action::Fire { weapon: Weapon::Laser { damage: 60.0, range: 20.0 } });
+        run(&mut sim);
+        assert_eq!(sim.agents_data[AGENT_STRIDE + IDX_SHIELD], max);
+        assert_eq!(sim.agent_facing(1).rear_shield(max), 0.0);
+        assert_eq!(sim.agents_data[AGENT_STRIDE + IDX_HEALTH], 100.0 - (60.0 - max));
+        // turned to face it, the front absorbs instead
+        sim.facing_mut(1).steer(Vec2 { x: -1.0, y: 0.0 });
+        run(&mut sim);
+        assert_eq!(sim.agents_data[AGENT_STRIDE + IDX_SHIELD], 0.0);
+    }
+
+    #[test]
+    fn laser_falloff_weakens_long_shots() {
+        use crate::config::Falloff;
+        assert_eq!(Falloff::None.scale(1.0, 0.25), 1.0);
+        assert_eq!(Falloff::Linear.scale(0.5, 0.0), 0.5);
+        assert_eq!(Falloff::Quadratic.scale(0.5, 0.0), 0.75);
+        assert_eq!(Falloff::Linear.scale(2.0, 0.25), 0.25);
+
+        let mut sim = make_sim(&[(0.0, 0.0, 0, 100.0), (3.0, 4.0, 1, 100.0)]);
+        sim.config.laser_falloff = Falloff::Linear;
+        sim.config.laser_falloff_floor = 0.0;
+        sim.commands.insert(0, Action::Fire { weapon: Weapon::Laser { damage: 8.0, range: 10.0 } });
+        run(&mut sim);
+        assert_eq!(sim.agents_data[AGENT_STRIDE + IDX_SHIELD], sim.config.max_shield - 4.0);
+
+        // across the wrap seam the target is 5 away, not 95
+        let mut sim = make_sim(&[(2.0, 50.0, 0, 100.0), (97.0, 50.0, 1, 100.0)]);
+        sim.config.distance_mode = crate::config::DistanceMode::Toroidal;
+        sim.config.laser_falloff = Falloff::Linear;
+        sim.config.laser_falloff_floor = 0.0;
+        sim.commands.insert(0, Action::Fire { weapon: Weapon::Laser { damage: 8.0, range: 10.0 } });
+        run(&mut sim);
+        assert_eq!(sim.agents_data[AGENT_STRIDE + IDX_SHIELD], sim.config.max_shield - 4.0);
+    }
+
     #[test]
     fn no_hit_out_of_range() {
         let mut sim = make_sim(&[(0.0, 0.0, 0, 100.0), (100.0, 100.0, 1, 100.0)]);
@@ -137,4 +417,42 @@ mod tests {
         assert_eq!(sim.fire_count, 0);
         assert!(sim.hits_data.is_empty());
     }
+
+    #[test]
+    fn lasers_fired_at_nothing_lower_accuracy() {
+        let mut sim = make_sim(&[(0.0, 0.0, 0, 100.0), (50.0, 50.0, 1, 100.0)]);
+        let laser = Action::Fire { weapon: Weapon::Laser { damage: 5.0, range: 10.0 } };
+        sim.commands.insert(0, laser.clone());
+        run(&mut sim);
+        sim.agents_data[AGENT_STRIDE + IDX_X] = 6.0;
+        sim.agents_data[AGENT_STRIDE + IDX_Y] = 8.0;
+        run(&mut sim);
+        // missiles are not tallied
+        sim.commands.insert(0, Action::Fire { weapon: Weapon::Missile { damage: 5.0, speed: 1.0, ttl: 10 } });
+        run(&mut sim);
+        let tally = sim.agent_shots(0);
+        assert_eq!((tally.trigger_pulls, tally.shots, tally.hits), (2, 2, 1));
+        assert_eq!(tally.accuracy(), 0.5);
+    }
+
+    #[test]
+    fn every_weapon_spends_a_shot_on_nothing() {
+        use crate::ship::{ShipClass, WeaponSlot};
+        let weapons = [
+            Weapon::Laser { damage: 5.0, range: 10.0 },
+            Weapon::Burst { damage: 5.0, radius: 5.0, range: 10.0 },
+            Weapon::Emp { status: Status::Weakened, ticks: 3, range: 10.0 },
+        ];
+        for weapon in weapons {
+            let mut sim = make_sim(&[(0.0, 0.0, 0, 100.0), (50.0, 50.0, 1, 100.0)]);
+            let slot = WeaponSlot::new(weapon.clone()).with_ammo(1);
+            sim.agent_classes = vec![Some(ShipClass::with_slots("one", 100.0, 50.0, 0.04, vec![slot.clone()])), None];
+            sim.commands.insert(0, Action::FireSlot { slot: 0 });
+            run(&mut sim);
+            let tally = sim.agent_shots(0);
+            assert_eq!((tally.trigger_pulls, tally.shots, tally.hits), (1, 1, 0), "{:?}", weapon);
+            assert_eq!(sim.agent_slots(0).ammo_left(0, &slot), Some(0), "{:?}", weapon);
+            assert_eq!(sim.agents_data[AGENT_STRIDE + IDX_HEALTH], 100.0, "{:?}", weapon);
+        }
+    }
 }
@@ -1,7 +1,18 @@
 //! Simulation configuration parameters.
 
+use serde::{Serialize, Deserialize};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use crate::gravity::GravityWell;
+use crate::mapgen::MapGen;
+
+/// Every network output; `weapon` is only produced with `Config::weapon_select`
+const ACTION_LAYOUT: [&str; 4] = ["vx", "vy", "fire", "weapon"];
+
 /// Centralized simulation constants for tuning and modularity.
-#[derive(Clone)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
     /// Repulsion distance for separation behavior.
     pub sep_range: f32,
@@ -9,10 +20,25 @@ pub struct Config {
     pub sep_strength: f32,
     /// Maximum distance at which lasers can hit.
     pub attack_range: f32,
-    /// Friction factor applied to velocity each tick.
+    /// Hull radius of every agent; lasers reach targets whose hull is within
+    /// range, and bullets hit within `agent_radius + bullet_radius`.
+    pub agent_radius: f32,
+    /// Radius of a bullet for collision with agents.
+    pub bullet_radius: f32,
+    /// How laser damage drops with distance to the target.
+    pub laser_falloff: Falloff,
+    /// Fraction of laser damage still dealt at maximum range under a falloff.
+    pub laser_falloff_floor: f32,
+    /// Fraction of its velocity an agent keeps from one tick to the next.
     pub friction: f32,
     /// Maximum speed (units per tick).
     pub max_speed: f32,
+    /// Maximum thrust, i.e. velocity change per tick (units per tick²).
+    pub max_accel: f32,
+    /// Pre-`max_accel` motion: thrust scaled by `friction` is the whole
+    /// velocity and nothing carries over between ticks. Champion bundles
+    /// from before the inertial model load with it set (see `bundle`).
+    pub legacy_motion: bool,
     /// View range for Fog of War (units).
     pub view_range: f32,
     /// Ticks without damage before shield regen starts.
@@ -41,41 +67,150 @@ pub struct Config {
     pub scan_rays: usize,
     /// Maximum distance for sensor scan (units)
     pub scan_max_dist: f32,
+    /// Which spatial sensors `Simulation::scan` produces
+    pub sensor_mode: SensorMode,
     /// Number of nearest enemies to include in sensor vector.
     pub nearest_k_enemies: usize,
     /// Number of nearest allies to include in sensor vector.
     pub nearest_k_allies: usize,
     /// Number of nearest wrecks to include in sensor vector.
     pub nearest_k_wrecks: usize,
-    /// Enable GPU inference via ONNXRuntime (unused)
+    /// Side of the egocentric influence grid in cells (odd, so the agent sits in the middle one)
+    pub influence_cells: usize,
+    /// Side of one influence grid cell (units)
+    pub influence_cell_size: f32,
+    /// Run `NeatBrain` inference through onnxruntime, preferring the CUDA
+    /// provider (needs the `onnxruntime` feature; see `NeatBrain::for_config`)
     pub use_onnx_gpu: bool,
+    /// Run `NeatBrain` inference through tract, a pure-Rust ONNX runtime that
+    /// also works in the browser (needs the `tract` feature)
+    pub use_tract: bool,
     /// Enable Python service
     pub use_python_service: bool,
     /// Python service URL
     pub python_service_url: Option<String>,
+    /// Protocol spoken to the inference service at `python_service_url`
+    pub inference_backend: InferenceBackend,
     /// Batch size for Python inference service
     pub batch_size: usize,
     /// Curriculum: current difficulty level (0 = easiest)
     pub difficulty_level: usize,
     /// Maximum allowed difficulty level
     pub max_difficulty: usize,
+    /// Per-team capability overrides (asymmetric scenarios)
+    pub team_overrides: Vec<TeamOverrides>,
+    /// Append own ship-class stats (speed, hull, shield, range) to sensors
+    pub class_sensors: bool,
+    /// Split shields into front and rear facings by heading (adds heading
+    /// and rear-shield sensors)
+    pub directional_shields: bool,
+    /// Append own active statuses (slowed, weakened, disrupted) to sensors
+    pub status_sensors: bool,
+    /// Point attractors/repulsors acting on agents during movement
+    pub gravity_wells: Vec<GravityWell>,
+    /// Procedural obstacles, wrecks and spawn rings generated per match seed
+    pub map_gen: MapGen,
+    /// Append the net well pull and the offset to the nearest well to sensors
+    pub gravity_sensors: bool,
+    /// Add a `weapon` network output that selects the loadout slot to fire
+    pub weapon_select: bool,
+    /// Side of a pheromone grid cell (units); 0 disables the grid and its sensors
+    pub pheromone_cell: f32,
+    /// Pheromone each living agent deposits on its cell per tick
+    pub pheromone_deposit: f32,
+    /// Fraction of every pheromone cell lost per tick
+    pub pheromone_decay: f32,
+    /// Ticks per second of simulated time for `Simulation::advance`
+    pub tick_rate_hz: f32,
+}
+
+/// Optional per-team replacements for global parameters; `None` keeps the global value
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TeamOverrides {
+    /// Team id these overrides apply to
+    pub team: u32,
+    /// Maximum speed for this team's agents
+    pub max_speed: Option<f32>,
+    /// Cap on laser range for this team's shots
+    pub attack_range: Option<f32>,
+    /// Spawn and loot-cap health for this team's agents
+    pub health_max: Option<f32>,
+    /// Multiplier applied to this team's weapon damage
+    pub damage_scale: Option<f32>,
+}
+
+/// Wire protocol for remote inference
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InferenceBackend {
+    /// JSON over HTTP (`POST /infer`)
+    #[default]
+    Http,
+    /// gRPC per `proto/inference/inference.proto` (needs the `grpc` feature)
+    Grpc,
+}
+
+/// Spatial sensors in the agent's input vector
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SensorMode {
+    /// Nearest-K enemy, ally, and wreck lists
+    #[default]
+    NearestK,
+    /// Egocentric grid of enemy HP, ally HP, and wreck value per cell
+    Influence,
+    /// Nearest-K lists followed by the influence grid
+    Both,
 }
 
 /// Selects distance calculation mode for AI
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum DistanceMode {
     Euclidean,
     Toroidal,
 }
 
+/// Weapon damage falloff over distance
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Falloff {
+    /// Full damage out to maximum range
+    #[default]
+    None,
+    /// Damage falls in a straight line from full (point blank) to `floor` (max range)
+    Linear,
+    /// Like `Linear` over the squared distance: gentle up close, steep near max range
+    Quadratic,
+}
+
+impl Falloff {
+    /// Damage multiplier at `t` = distance / range, clamped to [0, 1]
+    pub fn scale(self, t: f32, floor: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Falloff::None => 1.0,
+            Falloff::Linear => 1.0 - (1.0 - floor) * t,
+            Falloff::Quadratic => 1.0 - (1.0 - floor) * t * t,
+        }
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Config {
             sep_range:         10.0,
             sep_strength:      0.5,
             attack_range:      50.0,
+            agent_radius:      0.0,
+            bullet_radius:     1.0,
+            laser_falloff:     Falloff::None,
+            laser_falloff_floor: 0.25,
             friction:          0.98,
             max_speed:         0.04,
+            max_accel:         0.01,
+            legacy_motion:     false,
             view_range:        f32::MAX,
             shield_regen_delay:30,
             shield_regen_rate: 1.0,
@@ -90,16 +225,389 @@ impl Default for Config {
             distance_mode: DistanceMode::Euclidean,
             scan_rays: 32,
             scan_max_dist: 1000.0,
+            sensor_mode: SensorMode::NearestK,
             nearest_k_enemies: 8,
             nearest_k_allies: 4,
             nearest_k_wrecks: 4,
+            influence_cells: 5,
+            influence_cell_size: 50.0,
             use_onnx_gpu: false,
+            use_tract: false,
             use_python_service: false,
             python_service_url: None,
+            inference_backend: InferenceBackend::Http,
             batch_size: 1,
             difficulty_level: 0,
             max_difficulty: 5,
+            team_overrides: Vec::new(),
+            class_sensors: false,
+            directional_shields: false,
+            status_sensors: false,
+            gravity_wells: Vec::new(),
+            map_gen: MapGen::default(),
+            gravity_sensors: false,
+            weapon_select: false,
+            pheromone_cell: 0.0,
+            pheromone_deposit: 1.0,
+            pheromone_decay: 0.05,
+            tick_rate_hz: 60.0,
+        }
+    }
+}
+
+/// Errors from loading or saving a `Config`
+#[derive(Debug)]
+pub enum ConfigError {
+    /// Reading or writing the config file failed
+    Io(std::io::Error),
+    /// The file contents were not valid TOML for `Config`
+    Parse(String),
+    /// A parameter violates an invariant
+    Invalid { field: &'static str, reason: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "config io error: {}", e),
+            ConfigError::Parse(msg) => write!(f, "config parse error: {}", msg),
+            ConfigError::Invalid { field, reason } => write!(f, "invalid config `{}`: {}", field, reason),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<std::io::Error> for ConfigError {
+    fn from(e: std::io::Error) -> Self { ConfigError::Io(e) }
+}
+
+impl Config {
+    /// Start building a validated config from defaults
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder { cfg: Config::default(), expected_inputs: None }
+    }
+
+    /// Length of the sensor vector produced by `Simulation::scan`
+    pub fn sensor_len(&self) -> usize {
+        self.sensor_layout().iter().map(|(_, slots, features)| slots * features.len()).sum()
+    }
+
+    /// Nearest-K list lengths (enemies, allies, wrecks) actually sensed; all
+    /// zero when `sensor_mode` is `Influence`
+    pub fn sensed_nearest_k(&self) -> (usize, usize, usize) {
+        match self.sensor_mode {
+            SensorMode::Influence => (0, 0, 0),
+            _ => (self.nearest_k_enemies, self.nearest_k_allies, self.nearest_k_wrecks),
+        }
+    }
+
+    /// Cells in the influence grid (0 unless `sensor_mode` includes it)
+    pub fn influence_grid_cells(&self) -> usize {
+        match self.sensor_mode {
+            SensorMode::NearestK => 0,
+            _ => self.influence_cells * self.influence_cells,
+        }
+    }
+
+    /// Sensor groups in `Simulation::scan` order: name, slot count, per-slot features
+    pub fn sensor_layout(&self) -> Vec<(&'static str, usize, &'static [&'static str])> {
+        let (enemies, allies, wrecks) = self.sensed_nearest_k();
+        vec![
+            ("self", 1, &["hp", "shield"]),
+            ("enemies", enemies, &["dx", "dy", "hp", "shield"]),
+            ("allies", allies, &["dx", "dy", "hp", "shield"]),
+            ("wrecks", wrecks, &["dx", "dy", "pool"]),
+            ("influence", self.influence_grid_cells(), &["enemy_hp", "ally_hp", "wreck_pool"]),
+            ("class", usize::from(self.class_sensors), &["speed", "hull", "shield", "range"]),
+            ("facing", usize::from(self.directional_shields), &["heading_x", "heading_y", "rear_shield"]),
+            ("status", usize::from(self.status_sensors), &["slowed", "weakened", "disrupted"]),
+            ("gravity", usize::from(self.gravity_sensors), &["pull_x", "pull_y", "dx", "dy"]),
+            ("pheromone", usize::from(self.pheromones_enabled()), &["here", "n", "e", "s", "w"]),
+        ]
+    }
+
+    /// Network outputs a brain produces, in order
+    pub fn action_layout(&self) -> &'static [&'static str] {
+        &ACTION_LAYOUT[..3 + usize::from(self.weapon_select)]
+    }
+
+    /// True when the pheromone grid is active
+    pub fn pheromones_enabled(&self) -> bool {
+        self.pheromone_cell > 0.0
+    }
+
+    /// Overrides registered for `team`, if any
+    pub fn team_override(&self, team: u32) -> Option<&TeamOverrides> {
+        self.team_overrides.iter().find(|o| o.team == team)
+    }
+
+    /// Effective max speed for `team`
+    pub fn team_max_speed(&self, team: u32) -> f32 {
+        self.team_override(team).and_then(|o| o.max_speed).unwrap_or(self.max_speed)
+    }
+
+    /// Effective attack range for `team`
+    pub fn team_attack_range(&self, team: u32) -> f32 {
+        self.team_override(team).and_then(|o| o.attack_range).unwrap_or(self.attack_range)
+    }
+
+    /// Effective max health for `team`
+    pub fn team_health_max(&self, team: u32) -> f32 {
+        self.team_override(team).and_then(|o| o.health_max).unwrap_or(self.health_max)
+    }
+
+    /// Weapon damage multiplier for `team` (1.0 without override)
+    pub fn team_damage_scale(&self, team: u32) -> f32 {
+        self.team_override(team).and_then(|o| o.damage_scale).unwrap_or(1.0)
+    }
+
+    /// Check parameter invariants; bad values otherwise silently produce nonsense matches
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        fn invalid(field: &'static str, reason: String) -> Result<(), ConfigError> {
+            Err(ConfigError::Invalid { field, reason })
+        }
+        let non_negative = [
+            ("sep_range", self.sep_range),
+            ("sep_strength", self.sep_strength),
+            ("attack_range", self.attack_range),
+            ("view_range", self.view_range),
+            ("shield_regen_rate", self.shield_regen_rate),
+            ("max_shield", self.max_shield),
+            ("loot_range", self.loot_range),
+            ("loot_fixed", self.loot_fixed),
+            ("loot_init_ratio", self.loot_init_ratio),
+            ("scan_max_dist", self.scan_max_dist),
+            ("agent_radius", self.agent_radius),
+            ("bullet_radius", self.bullet_radius),
+            ("pheromone_cell", self.pheromone_cell),
+            ("pheromone_deposit", self.pheromone_deposit),
+        ];
+        for (field, v) in non_negative {
+            if v.is_nan() || v < 0.0 {
+                return invalid(field, format!("must be >= 0, got {}", v));
+            }
+        }
+        let unit = [
+            ("friction", self.friction),
+            ("loot_fraction", self.loot_fraction),
+            ("health_flee_ratio", self.health_flee_ratio),
+            ("health_engage_ratio", self.health_engage_ratio),
+            ("pheromone_decay", self.pheromone_decay),
+            ("laser_falloff_floor", self.laser_falloff_floor),
+        ];
+        for (field, v) in unit {
+            if !(0.0..=1.0).contains(&v) {
+                return invalid(field, format!("must be in [0,1], got {}", v));
+            }
         }
+        if self.max_speed.is_nan() || self.max_speed <= 0.0 {
+            return invalid("max_speed", format!("must be > 0, got {}", self.max_speed));
+        }
+        if self.max_accel.is_nan() || self.max_accel <= 0.0 {
+            return invalid("max_accel", format!("must be > 0, got {}", self.max_accel));
+        }
+        if self.tick_rate_hz.is_nan() || self.tick_rate_hz <= 0.0 {
+            return invalid("tick_rate_hz", format!("must be > 0, got {}", self.tick_rate_hz));
+        }
+        if self.health_max.is_nan() || self.health_max <= 0.0 {
+            return invalid("health_max", format!("must be > 0, got {}", self.health_max));
+        }
+        if self.health_flee_ratio > self.health_engage_ratio {
+            return invalid("health_flee_ratio", format!(
+                "flee ratio {} exceeds engage ratio {}", self.health_flee_ratio, self.health_engage_ratio));
+        }
+        if self.influence_cell_size.is_nan() || self.influence_cell_size <= 0.0 {
+            return invalid("influence_cell_size", format!("must be > 0, got {}", self.influence_cell_size));
+        }
+        if self.influence_cells.is_multiple_of(2) {
+            return invalid("influence_cells", format!("must be odd, got {}", self.influence_cells));
+        }
+        if self.batch_size == 0 {
+            return invalid("batch_size", "must be >= 1".to_string());
+        }
+        if self.difficulty_level > self.max_difficulty {
+            return invalid("difficulty_level", format!(
+                "level {} exceeds max_difficulty {}", self.difficulty_level, self.max_difficulty));
+        }
+        for o in &self.team_overrides {
+            let positive = [
+                ("team_overrides.max_speed", o.max_speed),
+                ("team_overrides.health_max", o.health_max),
+            ];
+            for (field, v) in positive {
+                if let Some(v) = v {
+                    if v.is_nan() || v <= 0.0 {
+                        return invalid(field, format!("team {}: must be > 0, got {}", o.team, v));
+                    }
+                }
+            }
+            let non_negative = [
+                ("team_overrides.attack_range", o.attack_range),
+                ("team_overrides.damage_scale", o.damage_scale),
+            ];
+            for (field, v) in non_negative {
+                if let Some(v) = v {
+                    if v.is_nan() || v < 0.0 {
+                        return invalid(field, format!("team {}: must be >= 0, got {}", o.team, v));
+                    }
+                }
+            }
+        }
+        if self.team_overrides.iter().enumerate()
+            .any(|(i, o)| self.team_overrides[..i].iter().any(|p| p.team == o.team))
+        {
+            return invalid("team_overrides", "duplicate team id".to_string());
+        }
+        for (i, w) in self.gravity_wells.iter().enumerate() {
+            if !w.strength.is_finite() {
+                return invalid("gravity_wells.strength", format!("well {}: must be finite, got {}", i, w.strength));
+            }
+            if w.radius.is_nan() || w.radius < 0.0 {
+                return invalid("gravity_wells.radius", format!("well {}: must be >= 0, got {}", i, w.radius));
+            }
+        }
+        let m = &self.map_gen;
+        let map_non_negative = [
+            ("map_gen.obstacle_radius", m.obstacle_radius),
+            ("map_gen.cluster_spread", m.cluster_spread),
+            ("map_gen.wreck_spread", m.wreck_spread),
+        ];
+        for (field, v) in map_non_negative {
+            if v.is_nan() || v < 0.0 {
+                return invalid(field, format!("must be >= 0, got {}", v));
+            }
+        }
+        if !m.obstacle_strength.is_finite() {
+            return invalid("map_gen.obstacle_strength", format!("must be finite, got {}", m.obstacle_strength));
+        }
+        if !(0.0..=1.0).contains(&m.spawn_ring) {
+            return invalid("map_gen.spawn_ring", format!("must be in [0,1], got {}", m.spawn_ring));
+        }
+        if self.use_python_service && self.python_service_url.is_none() {
+            return invalid("python_service_url", "required when use_python_service is set".to_string());
+        }
+        Ok(())
+    }
+
+    /// Parse a config from TOML text; missing keys fall back to defaults
+    pub fn from_toml_str(s: &str) -> Result<Config, ConfigError> {
+        let cfg: Config = toml::from_str(s).map_err(|e| ConfigError::Parse(e.to_string()))?;
+        cfg.validate()?;
+        Ok(cfg)
+    }
+
+    /// Load a config from a TOML file
+    pub fn from_toml_file<P: AsRef<Path>>(path: P) -> Result<Config, ConfigError> {
+        let text = fs::read_to_string(path)?;
+        Config::from_toml_str(&text)
+    }
+
+    /// Serialize this config to TOML text
+    pub fn to_toml(&self) -> Result<String, ConfigError> {
+        toml::to_string_pretty(self).map_err(|e| ConfigError::Parse(e.to_string()))
+    }
+
+    /// Write this config to a TOML file
+    pub fn to_toml_file<P: AsRef<Path>>(&self, path: P) -> Result<(), ConfigError> {
+        fs::write(path, self.to_toml()?)?;
+        Ok(())
+    }
+}
+
+/// Builder for `Config` whose `build()` enforces `Config::validate`
+#[derive(Clone, Debug)]
+pub struct ConfigBuilder {
+    cfg: Config,
+    /// Genome input count the sensor layout must match, if any
+    expected_inputs: Option<usize>,
+}
+
+macro_rules! builder_setters {
+    ($($field:ident: $ty:ty),* $(,)?) => {
+        $(
+            pub fn $field(mut self, v: $ty) -> Self {
+                self.cfg.$field = v;
+                self
+            }
+        )*
+    };
+}
+
+impl ConfigBuilder {
+    builder_setters! {
+        sep_range: f32,
+        sep_strength: f32,
+        attack_range: f32,
+        agent_radius: f32,
+        bullet_radius: f32,
+        laser_falloff: Falloff,
+        laser_falloff_floor: f32,
+        friction: f32,
+        max_speed: f32,
+        max_accel: f32,
+        legacy_motion: bool,
+        view_range: f32,
+        shield_regen_delay: u32,
+        shield_regen_rate: f32,
+        max_shield: f32,
+        health_max: f32,
+        loot_range: f32,
+        loot_fixed: f32,
+        loot_fraction: f32,
+        loot_init_ratio: f32,
+        health_flee_ratio: f32,
+        health_engage_ratio: f32,
+        distance_mode: DistanceMode,
+        scan_rays: usize,
+        scan_max_dist: f32,
+        sensor_mode: SensorMode,
+        nearest_k_enemies: usize,
+        nearest_k_allies: usize,
+        nearest_k_wrecks: usize,
+        influence_cells: usize,
+        influence_cell_size: f32,
+        use_onnx_gpu: bool,
+        use_tract: bool,
+        use_python_service: bool,
+        python_service_url: Option<String>,
+        inference_backend: InferenceBackend,
+        batch_size: usize,
+        difficulty_level: usize,
+        max_difficulty: usize,
+        team_overrides: Vec<TeamOverrides>,
+        class_sensors: bool,
+        directional_shields: bool,
+        status_sensors: bool,
+        gravity_wells: Vec<GravityWell>,
+        map_gen: MapGen,
+        gravity_sensors: bool,
+        weapon_select: bool,
+        pheromone_cell: f32,
+        pheromone_deposit: f32,
+        pheromone_decay: f32,
+        tick_rate_hz: f32,
+    }
+
+    /// Require the sensor vector length to equal a genome's input count
+    pub fn genome_inputs(mut self, n: usize) -> Self {
+        self.expected_inputs = Some(n);
+        self
+    }
+
+    /// Validate and return the config
+    pub fn build(self) -> Result<Config, ConfigError> {
+        self.cfg.validate()?;
+        if let Some(n) = self.expected_inputs {
+            let len = self.cfg.sensor_len();
+            if len != n {
+                return Err(ConfigError::Invalid {
+                    field: "genome_inputs",
+                    reason: format!("sensor length {} does not match genome inputs {}", len, n),
+                });
+            }
+        }
+        Ok(self.cfg)
     }
 }
 
@@ -150,3 +658,92 @@ impl Default for EvolutionConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toml_roundtrip_preserves_values() {
+        let cfg = Config {
+            attack_range: 42.0,
+            distance_mode: DistanceMode::Toroidal,
+            python_service_url: Some("http://localhost:8000".to_string()),
+            inference_backend: InferenceBackend::Grpc,
+            ..Default::default()
+        };
+        let text = cfg.to_toml().unwrap();
+        let back = Config::from_toml_str(&text).unwrap();
+        assert_eq!(back.attack_range, 42.0);
+        assert_eq!(back.distance_mode, DistanceMode::Toroidal);
+        assert_eq!(back.python_service_url.as_deref(), Some("http://localhost:8000"));
+        assert_eq!(back.inference_backend, InferenceBackend::Grpc);
+        assert!(!back.legacy_motion);
+    }
+
+    #[test]
+    fn partial_toml_uses_defaults() {
+        let cfg = Config::from_toml_str("max_speed = 2.5\ndistance_mode = \"euclidean\"\n").unwrap();
+        assert_eq!(cfg.max_speed, 2.5);
+        assert_eq!(cfg.nearest_k_enemies, Config::default().nearest_k_enemies);
+        assert!(!cfg.legacy_motion);
+    }
+
+    #[test]
+    fn builder_accepts_defaults() {
+        let cfg = Config::builder().attack_range(30.0).build().unwrap();
+        assert_eq!(cfg.attack_range, 30.0);
+    }
+
+    #[test]
+    fn builder_rejects_out_of_range_values() {
+        let err = Config::builder().loot_fraction(1.5).build().unwrap_err();
+        assert!(matches!(err, ConfigError::Invalid { field: "loot_fraction", .. }));
+        let err = Config::builder().max_speed(0.0).build().unwrap_err();
+        assert!(matches!(err, ConfigError::Invalid { field: "max_speed", .. }));
+    }
+
+    #[test]
+    fn builder_checks_genome_inputs() {
+        let len = Config::default().sensor_len();
+        assert!(Config::builder().genome_inputs(len).build().is_ok());
+        let err = Config::builder().nearest_k_enemies(4).genome_inputs(len).build().unwrap_err();
+        assert!(matches!(err, ConfigError::Invalid { field: "genome_inputs", .. }));
+    }
+
+    #[test]
+    fn team_overrides_fall_back_to_globals() {
+        let cfg = Config::builder()
+            .team_overrides(vec![TeamOverrides { team: 1, max_speed: Some(0.5), damage_scale: Some(2.0), ..Default::default() }])
+            .build()
+            .unwrap();
+        assert_eq!(cfg.team_max_speed(1), 0.5);
+        assert_eq!(cfg.team_max_speed(0), cfg.max_speed);
+        assert_eq!(cfg.team_damage_scale(1), 2.0);
+        assert_eq!(cfg.team_damage_scale(0), 1.0);
+        let text = cfg.to_toml().unwrap();
+        assert_eq!(Config::from_toml_str(&text).unwrap().team_overrides, cfg.team_overrides);
+    }
+
+    #[test]
+    fn gravity_wells_load_from_toml() {
+        let cfg = Config::from_toml_str(
+            "gravity_sensors = true\n[[gravity_wells]]\nx = 10.0\ny = 20.0\nstrength = -0.01\nradius = 50.0\n",
+        ).unwrap();
+        assert_eq!(cfg.gravity_wells, vec![GravityWell { x: 10.0, y: 20.0, strength: -0.01, radius: 50.0 }]);
+        assert_eq!(cfg.sensor_len(), Config::default().sensor_len() + 4);
+        let err = Config::from_toml_str("[[gravity_wells]]\nradius = -1.0\n").unwrap_err();
+        assert!(matches!(err, ConfigError::Invalid { field: "gravity_wells.radius", .. }));
+    }
+
+    #[test]
+    fn duplicate_team_overrides_rejected() {
+        let o = TeamOverrides { team: 2, ..Default::default() };
+        assert!(Config::builder().team_overrides(vec![o.clone(), o]).build().is_err());
+    }
+
+    #[test]
+    fn bad_toml_is_an_error() {
+        assert!(matches!(Config::from_toml_str("max_speed = \"fast\""), Err(ConfigError::Parse(_))));
+    }
+}
@@ -1,6 +1,8 @@
 // Domain types for simulation core
+use serde::{Serialize, Deserialize};
+use crate::status::Status;
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Vec2 {
     pub x: f32,
     pub y: f32,
@@ -42,6 +44,7 @@ impl Vec2 {
     }
 }
 
+/// Named ids for the classic four-team layout; any u32 is a valid team id
 #[derive(Debug, Copy, Clone)]
 pub enum Team {
     Orange,
@@ -50,16 +53,75 @@ pub enum Team {
     Blue,
 }
 
-#[derive(Debug, Clone)]
+impl From<Team> for u32 {
+    fn from(t: Team) -> u32 { t as u32 }
+}
+
+/// Display colors for the first teams (orange, yellow, green, blue match the web palette)
+const TEAM_PALETTE: [[u8; 3]; 7] = [
+    [0xFF, 0xA5, 0x00],
+    [0xFF, 0xFF, 0x00],
+    [0x00, 0xFF, 0x00],
+    [0x00, 0x00, 0xFF],
+    [0xFF, 0x00, 0x00],
+    [0x4B, 0x00, 0x82],
+    [0xEE, 0x82, 0xEE],
+];
+
+/// RGB display color for a team; ids past the palette get golden-angle spaced hues
+pub fn team_color(team: u32) -> [u8; 3] {
+    if let Some(c) = TEAM_PALETTE.get(team as usize) {
+        return *c;
+    }
+    let hue = (team as f32 * 137.508) % 360.0;
+    // HSV -> RGB with s=0.8, v=0.95
+    let (s, v) = (0.8f32, 0.95f32);
+    let c = v * s;
+    let x = c * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+    let m = v - c;
+    let (r, g, b) = match (hue / 60.0) as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    [((r + m) * 255.0) as u8, ((g + m) * 255.0) as u8, ((b + m) * 255.0) as u8]
+}
+
+/// Team color as a `#rrggbb` string
+pub fn team_color_hex(team: u32) -> String {
+    let [r, g, b] = team_color(team);
+    format!("#{:02X}{:02X}{:02X}", r, g, b)
+}
+
+/// JSON form: `{"kind":"laser","damage":..,"range":..}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
 pub enum Weapon {
     Laser   { damage: f32, range: f32 },
     Missile { damage: f32, speed: f32, ttl: u32 },
+    /// Flak/plasma burst: detonates on the nearest enemy within `range` and
+    /// damages every other agent (teammates included) within `radius` of it,
+    /// `damage` at the center falling linearly to zero at the edge
+    Burst   { damage: f32, radius: f32, range: f32 },
+    /// Hitscan pulse that deals no damage but inflicts `status` on the
+    /// nearest enemy within `range` for `ticks` ticks
+    Emp     { status: Status, ticks: u32, range: f32 },
 }
 
-#[derive(Debug, Clone)]
+/// JSON form: `{"type":"thrust","x":..,"y":..}`, `{"type":"fire","weapon":{..}}`,
+/// `{"type":"fire_slot","slot":..}`, `{"type":"loot"}`, `{"type":"idle"}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
 pub enum Action {
     Thrust(Vec2),           // acceleration vector
     Fire  { weapon: Weapon },
+    /// Fire loadout slot `slot` (clamped to the loadout); agents without a
+    /// class fire their default weapon
+    #[serde(rename = "fire_slot")]
+    FireSlot { slot: usize },
     Loot,                   // scavenge from corpse
     Idle,                   // no-op
 }
@@ -68,12 +130,12 @@ pub enum Action {
 pub struct WorldView<'a> {
     pub self_idx: usize,
     pub self_pos: Vec2,
-    pub self_team: usize,
+    pub self_team: u32,
     pub self_health: f32,
     /// Current shield buffer level
     pub self_shield: f32,
     pub positions: &'a [Vec2],
-    pub teams: &'a [usize],
+    pub teams: &'a [u32],
     pub healths: &'a [f32],
     /// Shield levels for all agents
     pub shields: &'a [f32],
@@ -109,6 +171,19 @@ mod tests {
         assert_eq!(r.y, 1.0);
     }
 
+    #[test]
+    fn action_json_round_trip() {
+        let a: Action = serde_json::from_str(r#"{"type":"thrust","x":1.5,"y":-2.0}"#).unwrap();
+        assert!(matches!(a, Action::Thrust(v) if v.x == 1.5 && v.y == -2.0));
+        let a: Action = serde_json::from_str(r#"{"type":"fire","weapon":{"kind":"laser","damage":2.0,"range":30.0}}"#).unwrap();
+        assert!(matches!(a, Action::Fire { weapon: Weapon::Laser { range, .. } } if range == 30.0));
+        let a: Action = serde_json::from_str(r#"{"type":"fire_slot","slot":2}"#).unwrap();
+        assert!(matches!(a, Action::FireSlot { slot: 2 }));
+        let text = serde_json::to_string(&Action::Loot).unwrap();
+        assert_eq!(text, r#"{"type":"loot"}"#);
+        assert!(serde_json::from_str::<Action>(r#"{"type":"warp"}"#).is_err());
+    }
+
     #[test]
     fn action_variants_compile() {
         let _ = Action::Idle;
@@ -134,6 +209,14 @@ mod tests {
         assert_eq!(d2, 20.0);
     }
 
+    #[test]
+    fn team_colors_match_palette_and_extend() {
+        assert_eq!(team_color_hex(Team::Orange.into()), "#FFA500");
+        assert_eq!(team_color_hex(Team::Blue.into()), "#0000FF");
+        // ids beyond the fixed palette still get distinct colors
+        assert_ne!(team_color(8), team_color(9));
+    }
+
     #[test]
     fn torus_delta_wrap_forward() {
         let a = Vec2 { x: 995.0, y: 0.0 };
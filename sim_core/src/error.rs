@@ -0,0 +1,57 @@
+//! `SimError`: the crate-wide error, wrapping each module's own error type
+//! (config, snapshots, replays, and everything under `neat`).
+
+use std::fmt;
+use crate::config::ConfigError;
+use crate::neat::error::NeatError;
+use crate::replay::ReplayError;
+use crate::snapshot::SnapshotError;
+
+#[derive(Debug)]
+pub enum SimError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Config(ConfigError),
+    Snapshot(SnapshotError),
+    Replay(ReplayError),
+    Neat(NeatError),
+}
+
+impl fmt::Display for SimError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimError::Io(e) => write!(f, "I/O error: {}", e),
+            SimError::Json(e) => write!(f, "JSON error: {}", e),
+            SimError::Config(e) => write!(f, "{}", e),
+            SimError::Snapshot(e) => write!(f, "{}", e),
+            SimError::Replay(e) => write!(f, "{}", e),
+            SimError::Neat(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for SimError {}
+
+impl From<std::io::Error> for SimError {
+    fn from(e: std::io::Error) -> Self { SimError::Io(e) }
+}
+
+impl From<serde_json::Error> for SimError {
+    fn from(e: serde_json::Error) -> Self { SimError::Json(e) }
+}
+
+impl From<ConfigError> for SimError {
+    fn from(e: ConfigError) -> Self { SimError::Config(e) }
+}
+
+impl From<SnapshotError> for SimError {
+    fn from(e: SnapshotError) -> Self { SimError::Snapshot(e) }
+}
+
+impl From<ReplayError> for SimError {
+    fn from(e: ReplayError) -> Self { SimError::Replay(e) }
+}
+
+impl From<NeatError> for SimError {
+    fn from(e: NeatError) -> Self { SimError::Neat(e) }
+}
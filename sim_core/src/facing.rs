@@ -0,0 +1,74 @@
+//! Directional shields: with `Config::directional_shields` set, each agent
+//! carries a front and a rear shield. The front is the `IDX_SHIELD` slot of
+//! the agent buffer; the rear and the agent's heading live here. Hits are
+//! assigned to a facing by the angle between the heading and the direction
+//! the shot came from, and each facing regenerates on its own timer.
+
+use serde::{Deserialize, Serialize};
+use crate::domain::Vec2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Front,
+    Rear,
+}
+
+/// Heading and rear-shield state of one agent
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Facing {
+    /// Unit vector of the last thrust direction
+    pub heading: Vec2,
+    /// Rear shield missing from full (0 = full), so new agents start charged
+    pub rear_deficit: f32,
+    /// Tick the front facing was last hit
+    pub front_hit: u32,
+    /// Tick the rear facing was last hit
+    pub rear_hit: u32,
+}
+
+impl Default for Facing {
+    fn default() -> Self {
+        Facing { heading: Vec2 { x: 1.0, y: 0.0 }, rear_deficit: 0.0, front_hit: 0, rear_hit: 0 }
+    }
+}
+
+impl Facing {
+    /// Turn toward `thrust`; a zero thrust keeps the heading
+    pub fn steer(&mut self, thrust: Vec2) {
+        if thrust.x != 0.0 || thrust.y != 0.0 {
+            self.heading = thrust.normalize();
+        }
+    }
+
+    /// Facing struck by a shot arriving from `toward_shooter` (target to shooter);
+    /// the front covers the half-plane ahead, ties included
+    pub fn side_of(&self, toward_shooter: Vec2) -> Side {
+        if self.heading.x * toward_shooter.x + self.heading.y * toward_shooter.y >= 0.0 {
+            Side::Front
+        } else {
+            Side::Rear
+        }
+    }
+
+    /// Rear shield given the per-facing capacity `max`
+    pub fn rear_shield(&self, max: f32) -> f32 {
+        (max - self.rear_deficit).max(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hits_from_behind_strike_the_rear() {
+        let mut f = Facing::default();
+        f.steer(Vec2 { x: 0.0, y: -2.0 });
+        assert_eq!(f.side_of(Vec2 { x: 0.5, y: -1.0 }), Side::Front);
+        assert_eq!(f.side_of(Vec2 { x: 0.5, y: 1.0 }), Side::Rear);
+        f.steer(Vec2 { x: 0.0, y: 0.0 });
+        assert_eq!(f.heading.y, -1.0);
+        f.rear_deficit = 60.0;
+        assert_eq!(f.rear_shield(50.0), 0.0);
+    }
+}
@@ -0,0 +1,64 @@
+//! Gravity wells: fixed point attractors and repulsors listed in
+//! `Config::gravity_wells`, plus the obstacles `mapgen` lays out. Each pulls
+//! agents within its radius toward its center (or pushes them away),
+//! strongest at the center and fading to nothing at the edge. The movement phase adds the pull to every agent's
+//! velocity, and `Config::gravity_sensors` lets agents sense it.
+
+use serde::{Deserialize, Serialize};
+use crate::domain::Vec2;
+
+/// Values per well in `Simulation::wells_data`: x, y, strength, radius
+pub const WELL_STRIDE: usize = 4;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GravityWell {
+    pub x: f32,
+    pub y: f32,
+    /// Velocity change per tick at the center; negative repels
+    pub strength: f32,
+    /// Distance beyond which the well has no effect
+    pub radius: f32,
+}
+
+impl GravityWell {
+    pub fn center(&self) -> Vec2 {
+        Vec2 { x: self.x, y: self.y }
+    }
+
+    /// Acceleration on an agent whose offset to the center is `to_center`
+    pub fn pull(&self, to_center: Vec2) -> Vec2 {
+        let d = to_center.length();
+        if d <= 0.0 || d >= self.radius {
+            return Vec2 { x: 0.0, y: 0.0 };
+        }
+        let a = self.strength * (1.0 - d / self.radius) / d;
+        Vec2 { x: to_center.x * a, y: to_center.y * a }
+    }
+}
+
+/// Summed pull of `wells` on an agent, given its offset to a point
+pub fn field<'a>(wells: impl IntoIterator<Item = &'a GravityWell>, offset_to: impl Fn(Vec2) -> Vec2) -> Vec2 {
+    wells.into_iter().fold(Vec2 { x: 0.0, y: 0.0 }, |acc, w| {
+        let p = w.pull(offset_to(w.center()));
+        Vec2 { x: acc.x + p.x, y: acc.y + p.y }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pull_fades_to_the_edge_and_repulsors_push() {
+        let well = GravityWell { x: 0.0, y: 0.0, strength: 2.0, radius: 10.0 };
+        let p = well.pull(Vec2 { x: -5.0, y: 0.0 });
+        assert!((p.x + 1.0).abs() < 1e-6 && p.y == 0.0);
+        assert_eq!(well.pull(Vec2 { x: 10.0, y: 0.0 }), Vec2 { x: 0.0, y: 0.0 });
+        assert_eq!(well.pull(Vec2 { x: 0.0, y: 0.0 }), Vec2 { x: 0.0, y: 0.0 });
+
+        let repulsor = GravityWell { strength: -2.0, ..well.clone() };
+        let both = field(&[well, repulsor], |c| Vec2 { x: c.x - 5.0, y: c.y });
+        assert_eq!(both, Vec2 { x: 0.0, y: 0.0 });
+    }
+}
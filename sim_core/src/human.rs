@@ -0,0 +1,182 @@
+//! Human-controlled agent for `neat_train play`: the player's latest command
+//! sits in a shared `Controls` slot that `HumanBrain` reads every tick, so any
+//! input source (stdin, a socket) can steer it from another thread. Also a
+//! text view of a frame for playing in a terminal.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex};
+use crate::brain::Brain;
+use crate::domain::{Action, Vec2, Weapon, WorldView};
+use crate::replay::ReplayFrame;
+use crate::{AGENT_STRIDE, IDX_HEALTH, IDX_SHIELD, IDX_TEAM, IDX_X, IDX_Y, WRECK_STRIDE};
+
+/// Team markers in `ascii_frame`, by team id
+const TEAM_MARKS: [char; 4] = ['o', 'x', '+', '*'];
+
+/// What the player is holding down: a direction plus fire or loot
+#[derive(Debug, Clone, Copy)]
+pub struct Command {
+    pub thrust: Vec2,
+    pub fire: bool,
+    pub loot: bool,
+}
+
+impl Default for Command {
+    fn default() -> Self {
+        Command { thrust: Vec2 { x: 0.0, y: 0.0 }, fire: false, loot: false }
+    }
+}
+
+impl Command {
+    /// Parse a line of keys: `w`/`a`/`s`/`d` thrust up/left/down/right (combine
+    /// for diagonals), `f` fires, `l` loots, and an empty line or `.` stops.
+    /// None if the line has any other key.
+    pub fn parse(line: &str) -> Option<Command> {
+        let mut cmd = Command::default();
+        for key in line.trim().chars() {
+            match key.to_ascii_lowercase() {
+                'w' => cmd.thrust.y -= 1.0,
+                's' => cmd.thrust.y += 1.0,
+                'a' => cmd.thrust.x -= 1.0,
+                'd' => cmd.thrust.x += 1.0,
+                'f' => cmd.fire = true,
+                'l' => cmd.loot = true,
+                '.' | ' ' => {}
+                _ => return None,
+            }
+        }
+        Some(cmd)
+    }
+}
+
+/// Shared handle to the command a `HumanBrain` acts on
+#[derive(Debug, Clone, Default)]
+pub struct Controls(Arc<Mutex<Command>>);
+
+impl Controls {
+    pub fn set(&self, cmd: Command) {
+        *self.0.lock().unwrap() = cmd;
+    }
+
+    pub fn get(&self) -> Command {
+        *self.0.lock().unwrap()
+    }
+}
+
+/// Repeats the player's current command every tick. Fire only shoots with an
+/// enemy in laser range, as `NeatBrain` does; otherwise the ship keeps moving.
+pub struct HumanBrain {
+    controls: Controls,
+}
+
+impl HumanBrain {
+    pub fn new(controls: Controls) -> Self {
+        HumanBrain { controls }
+    }
+}
+
+impl Brain for HumanBrain {
+    fn think(&mut self, view: &WorldView, _inputs: &[f32]) -> Action {
+        let cmd = self.controls.get();
+        if cmd.fire {
+            let in_range = view.positions.iter().enumerate().any(|(i, &pos)| {
+                i != view.self_idx && view.healths[i] > 0.0 && view.teams[i] != view.self_team
+                    && view.self_pos.torus_delta(pos, view.world_width, view.world_height).length() <= view.attack_range
+            });
+            if in_range {
+                return Action::Fire { weapon: Weapon::Laser { damage: 1.0, range: view.attack_range } };
+            }
+        }
+        if cmd.loot {
+            return Action::Loot;
+        }
+        if cmd.thrust.x != 0.0 || cmd.thrust.y != 0.0 {
+            return Action::Thrust(cmd.thrust);
+        }
+        Action::Idle
+    }
+}
+
+/// Ships alive and total health per team
+pub fn standings(frame: &ReplayFrame) -> BTreeMap<u32, (usize, f32)> {
+    let mut teams = BTreeMap::new();
+    for a in frame.agents.chunks(AGENT_STRIDE) {
+        let entry = teams.entry(a[IDX_TEAM] as u32).or_insert((0, 0.0));
+        if a[IDX_HEALTH] > 0.0 {
+            entry.0 += 1;
+            entry.1 += a[IDX_HEALTH];
+        }
+    }
+    teams
+}
+
+/// `frame` drawn on a `cols`×`rows` character grid: `@` is `player`, other
+/// ships are their team's mark, `%` a wreck with loot left
+pub fn ascii_frame(frame: &ReplayFrame, world_width: u32, world_height: u32, cols: usize, rows: usize, player: usize) -> String {
+    let mut grid = vec![vec!['·'; cols]; rows];
+    let mut plot = |x: f32, y: f32, c: char| {
+        let col = ((x / world_width as f32 * cols as f32) as usize).min(cols - 1);
+        let row = ((y / world_height as f32 * rows as f32) as usize).min(rows - 1);
+        grid[row][col] = c;
+    };
+    for w in frame.wrecks.chunks(WRECK_STRIDE).filter(|w| w[2] > 0.0) {
+        plot(w[0], w[1], '%');
+    }
+    for (i, a) in frame.agents.chunks(AGENT_STRIDE).enumerate() {
+        if a[IDX_HEALTH] > 0.0 && i != player {
+            plot(a[IDX_X], a[IDX_Y], TEAM_MARKS[a[IDX_TEAM] as usize % TEAM_MARKS.len()]);
+        }
+    }
+    let me = frame.agents.chunks(AGENT_STRIDE).nth(player);
+    if let Some(a) = me.filter(|a| a[IDX_HEALTH] > 0.0) {
+        plot(a[IDX_X], a[IDX_Y], '@');
+    }
+    let mut out = String::new();
+    for row in &grid {
+        out.extend(row.iter());
+        out.push('\n');
+    }
+    let _ = write!(out, "tick {}", frame.tick);
+    if let Some(a) = me {
+        let _ = write!(out, " · you: hp {:.0} shield {:.0}", a[IDX_HEALTH].max(0.0), a[IDX_SHIELD]);
+    }
+    for (team, (alive, health)) in standings(frame) {
+        let _ = write!(out, " · team {} '{}': {} alive, hp {:.0}", team, TEAM_MARKS[team as usize % TEAM_MARKS.len()], alive, health);
+    }
+    out.push('\n');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_held_keys() {
+        let cmd = Command::parse("wd f").unwrap();
+        assert_eq!((cmd.thrust.x, cmd.thrust.y, cmd.fire, cmd.loot), (1.0, -1.0, true, false));
+        assert!(Command::parse("").is_some_and(|c| !c.fire && c.thrust.x == 0.0));
+        assert!(Command::parse("L").unwrap().loot);
+        assert!(Command::parse("wq").is_none());
+    }
+
+    #[test]
+    fn draws_player_over_teams() {
+        let frame = ReplayFrame {
+            tick: 7,
+            agents: vec![10.0, 10.0, 0.0, 80.0, 5.0, 0.0, 90.0, 90.0, 1.0, 100.0, 0.0, 0.0],
+            wrecks: vec![50.0, 50.0, 3.0],
+            bullets: Vec::new(),
+            hits: Vec::new(),
+            effects: Vec::new(),
+        };
+        let text = ascii_frame(&frame, 100, 100, 10, 5, 0);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "·@········");
+        assert_eq!(lines[2], "·····%····");
+        assert_eq!(lines[4], "·········x");
+        assert!(lines[5].starts_with("tick 7 · you: hp 80 shield 5"));
+        assert_eq!(standings(&frame)[&1], (1, 100.0));
+    }
+}
@@ -0,0 +1,87 @@
+//! Brain backed by a JavaScript callback (WASM only).
+//!
+//! The callback is invoked as `callback(inputs, view)` where `inputs` is the
+//! sensor vector (`Float32Array`) and `view` a plain object summarizing the
+//! agent's situation. It returns an action object in the same JSON shape as
+//! `Action` (e.g. `{type: "thrust", x: 0, y: 1}`); anything unparseable or a
+//! thrown exception yields `Idle`.
+
+use js_sys::{Float32Array, Function, JSON};
+use serde::Serialize;
+use wasm_bindgen::JsValue;
+
+use crate::brain::Brain;
+use crate::domain::{Action, Vec2, WorldView};
+
+pub struct JsBrain {
+    callback: Function,
+}
+
+impl JsBrain {
+    pub fn new(callback: Function) -> Self {
+        JsBrain { callback }
+    }
+}
+
+/// Per-agent world summary handed to the JS callback
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ViewSummary {
+    self_idx: usize,
+    self_pos: Vec2,
+    self_team: u32,
+    self_health: f32,
+    self_shield: f32,
+    world_width: f32,
+    world_height: f32,
+    attack_range: f32,
+    sep_range: f32,
+    /// Living agents on other teams
+    enemies_alive: usize,
+    /// Living agents on this team, excluding self
+    allies_alive: usize,
+    /// Wrecks with loot remaining
+    wrecks: usize,
+}
+
+impl ViewSummary {
+    fn from_view(view: &WorldView) -> Self {
+        let living = |same_team: bool| {
+            (0..view.positions.len())
+                .filter(|&i| i != view.self_idx && view.healths[i] > 0.0)
+                .filter(|&i| (view.teams[i] == view.self_team) == same_team)
+                .count()
+        };
+        ViewSummary {
+            self_idx: view.self_idx,
+            self_pos: view.self_pos,
+            self_team: view.self_team,
+            self_health: view.self_health,
+            self_shield: view.self_shield,
+            world_width: view.world_width,
+            world_height: view.world_height,
+            attack_range: view.attack_range,
+            sep_range: view.sep_range,
+            enemies_alive: living(false),
+            allies_alive: living(true),
+            wrecks: view.wreck_pools.iter().filter(|&&p| p > 0.0).count(),
+        }
+    }
+}
+
+impl Brain for JsBrain {
+    fn think(&mut self, view: &WorldView, inputs: &[f32]) -> Action {
+        let summary = serde_json::to_string(&ViewSummary::from_view(view))
+            .ok()
+            .and_then(|s| JSON::parse(&s).ok())
+            .unwrap_or(JsValue::NULL);
+        let inputs = Float32Array::from(inputs);
+        self.callback
+            .call2(&JsValue::NULL, &inputs, &summary)
+            .ok()
+            .and_then(|ret| JSON::stringify(&ret).ok())
+            .and_then(|s| s.as_string())
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or(Action::Idle)
+    }
+}
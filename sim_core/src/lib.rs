@@ -6,31 +6,53 @@
 use js_sys::Math;
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
-use std::collections::HashMap;
-
-#[cfg(target_arch = "wasm32")]
-fn random_coef() -> f32 {
-    Math::random() as f32
-}
-#[cfg(not(target_arch = "wasm32"))]
-fn random_coef() -> f32 {
-    0.5
-}
+use std::collections::BTreeMap;
+use rand::{rngs::StdRng, SeedableRng};
 
 pub mod domain;
-pub use domain::{Action, Vec2, WorldView};
+pub use domain::{Action, Vec2, WorldView, team_color, team_color_hex};
 
 pub mod config;
 pub use config::Config;
-pub use config::DistanceMode;
+pub mod error;
+pub use error::SimError;
+pub mod builder;
+pub use builder::SimulationBuilder;
+pub use config::{ConfigError, DistanceMode, Falloff, SensorMode, TeamOverrides};
+pub mod ship;
+pub use ship::{ShipClass, WeaponSlot};
+pub mod replay;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod render;
+use replay::ReplayRecorder;
+pub mod state;
+pub use state::SimState;
+pub mod snapshot;
+pub mod pheromone;
+pub mod status;
+pub mod facing;
+pub mod gravity;
+pub mod mapgen;
+pub mod shots;
+use facing::Facing;
+use status::{Status, StatusSet};
+use ship::SlotState;
+use shots::ShotTally;
+pub mod math;
+use pheromone::PheromoneGrid;
+use snapshot::{Snapshot, SnapshotError, SnapshotRing};
 
 mod movement;
 mod combat;
 mod bullet;
 mod loot;
 pub mod ai;
+pub mod human;
+pub mod scripted;
+#[cfg(feature = "scripting")]
+pub mod script;
 mod brain;
-pub use brain::Brain;
+pub use brain::{Brain, TeamBrain, Teammate};
 pub mod neat;
 pub mod onnx_generated;
 
@@ -49,6 +71,8 @@ const IDX_SHIELD: usize = 4;
 const IDX_LAST_HIT: usize = 5;
 /// Number of floats per wreck record in the flat buffer
 const WRECK_STRIDE: usize = 3;
+/// Number of floats per effect record: [x, y, radius]
+pub const EFFECT_STRIDE: usize = 3;
 /// Offsets into a wreck record
 const IDX_WRECK_X: usize    = 0;
 const IDX_WRECK_Y: usize    = 1;
@@ -60,7 +84,8 @@ pub struct Simulation {
     agents_data: Vec<f32>,
     bullets_data: Vec<f32>,
     wrecks_data: Vec<f32>,
-    commands: HashMap<usize, Action>,
+    /// Pending commands keyed by agent id (ordered so phases resolve deterministically)
+    commands: BTreeMap<usize, Action>,
     thrust_count: u32,
     fire_count: u32,
     idle_count: u32,
@@ -69,32 +94,98 @@ pub struct Simulation {
     tick_count: u32,
     /// hitscan segments: [x1,y1,x2,y2,...]
     hits_data: Vec<f32>,
+    /// this tick's blasts: [x,y,radius,...]
+    effects_data: Vec<f32>,
     /// Simulation configuration parameters
     config: Config,
     /// Agent implementations for decision making
     agents_impl: Vec<Box<dyn Brain>>,
+    /// Centralized controllers by team; their members' own brains are skipped
+    team_brains: BTreeMap<u32, Box<dyn TeamBrain>>,
+    /// Optional ship class per agent id; `None` follows the global config
+    agent_classes: Vec<Option<ShipClass>>,
+    /// Timed statuses per agent id (agents past the end have none)
+    statuses: Vec<StatusSet>,
+    /// Heading and rear shield per agent id (agents past the end have the default)
+    facings: Vec<Facing>,
+    /// Loadout cooldowns and ammo per agent id (agents past the end are ready and full)
+    slot_states: Vec<SlotState>,
+    /// Carried-over velocity per agent id (agents past the end are at rest)
+    velocities: Vec<Vec2>,
+    /// Gunnery record per agent id (agents past the end have fired nothing)
+    shot_tallies: Vec<ShotTally>,
+    /// Seed the simulation RNG was last initialized with
+    seed: u64,
+    /// Simulation-owned RNG (spawn jitter etc.), reproducible from `seed`
+    rng: StdRng,
+    /// Frame buffer while recording a replay
+    recorder: Option<ReplayRecorder>,
+    /// Automatic snapshots for rewind, when enabled
+    snapshots: Option<SnapshotRing>,
+    /// Stigmergy grid, created on the first tick with `pheromone_cell` set
+    pheromones: Option<PheromoneGrid>,
+    /// Repulsor wells laid out by `generate_map`, on top of the configured ones
+    obstacles: Vec<gravity::GravityWell>,
+    /// Real time `advance` has received but not yet simulated
+    accumulator_ms: f32,
+    /// Agent state before the last tick `advance` ran, for interpolation
+    prev_agents_data: Vec<f32>,
+}
+
+/// FNV-1a over 32-bit words, fed by `Simulation::state_hash`
+pub(crate) struct StateHasher(u64);
+
+impl StateHasher {
+    fn new() -> Self {
+        StateHasher(0xcbf2_9ce4_8422_2325)
+    }
+
+    pub(crate) fn word(&mut self, w: u32) {
+        for b in w.to_le_bytes() {
+            self.0 ^= b as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    /// Length-prefixed, so adjacent buffers cannot run into each other
+    pub(crate) fn floats(&mut self, values: &[f32]) {
+        self.word(values.len() as u32);
+        for v in values {
+            self.word(v.to_bits());
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Ticks `advance` runs per call at most; time beyond that is dropped so a
+/// stalled tab does not fast-forward the match when it resumes
+pub const MAX_CATCH_UP_TICKS: u32 = 8;
+
+/// Result of `Simulation::advance`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Advance {
+    /// Ticks simulated
+    pub ticks: u32,
+    /// How far real time has moved past the last tick, as a fraction of a
+    /// tick: draw `prev_agents_data` lerped toward `agents_data` by this
+    pub alpha: f32,
 }
 
 impl Simulation {
     /// Constructor for a new simulation
     pub fn new(width: u32, height: u32, orange: u32, yellow: u32, green: u32, blue: u32) -> Simulation {
+        Simulation::new_seeded(width, height, orange, yellow, green, blue, default_seed())
+    }
+
+    /// Like `new`, with spawn placement drawn from an explicit seed
+    pub fn new_seeded(width: u32, height: u32, orange: u32, yellow: u32, green: u32, blue: u32, seed: u64) -> Simulation {
         // init empty state
-        let mut sim = Simulation {
-            width,
-            height,
-            agents_data: Vec::with_capacity(((orange + yellow + green + blue) * AGENT_STRIDE as u32) as usize),
-            bullets_data: Vec::new(),
-            wrecks_data: Vec::new(),
-            commands: HashMap::new(),
-            thrust_count: 0,
-            fire_count: 0,
-            idle_count: 0,
-            loot_count: 0,
-            tick_count: 0,
-            hits_data: Vec::new(),
-            config: Config::default(),
-            agents_impl: Vec::new(),
-        };
+        let mut sim = Simulation::empty(width, height);
+        sim.reseed(seed);
+        sim.agents_data.reserve(((orange + yellow + green + blue) * AGENT_STRIDE as u32) as usize);
         sim.spawn_quadrants(
             [orange, yellow, green, blue],
             &[|| Box::new(NaiveBrain(NaiveAgent::new(1.2, 0.8)))],
@@ -110,8 +201,9 @@ impl Simulation {
         self.fire_count = 0;
         self.idle_count = 0;
         self.loot_count = 0;
-        // clear previous hits
+        // clear previous hits and blasts
         self.hits_data.clear();
+        self.effects_data.clear();
         // advance global tick
         self.tick_count += 1;
 
@@ -121,6 +213,18 @@ impl Simulation {
             // Skip dead agents
             let health = self.agents_data[idx * AGENT_STRIDE + IDX_HEALTH];
             if health <= 0.0 { continue; }
+            // Commands queued externally (push_command) override the brain this tick
+            if let Some(action) = self.commands.get(&idx) {
+                match action {
+                    Action::Thrust(_) => self.thrust_count += 1,
+                    Action::Idle => self.idle_count += 1,
+                    Action::Loot => self.loot_count += 1,
+                    Action::Fire { .. } | Action::FireSlot { .. } => self.fire_count += 1,
+                }
+                continue;
+            }
+            // Team-controlled agents are decided together below
+            if self.team_brains.contains_key(&self.agent_team(idx)) { continue; }
             // Build full WorldView
             let (positions, teams, healths, shields, wreck_positions, wreck_pools, w, h) = self.build_global_view();
             let view = WorldView {
@@ -137,20 +241,16 @@ impl Simulation {
                 wreck_pools: &wreck_pools,
                 world_width: w,
                 world_height: h,
-                attack_range: self.config.attack_range,
+                attack_range: self.agent_attack_range(idx),
                 sep_range: self.config.sep_range,
             };
             // Sensor-based decision
             let inputs = self.scan(idx, self.config.scan_rays, self.config.scan_max_dist);
             let action = self.agents_impl[idx].think(&view, &inputs);
-            self.commands.insert(idx, action.clone());
-            match action {
-                Action::Thrust(_) => self.thrust_count += 1,
-                Action::Idle => self.idle_count += 1,
-                Action::Loot => self.loot_count += 1,
-                Action::Fire { .. } => self.fire_count += 1,
-            }
+            self.tally(&action);
+            self.commands.insert(idx, action);
         }
+        self.think_teams();
 
         // Phase 3: Movement System
         movement::run(self);
@@ -164,19 +264,120 @@ impl Simulation {
         // Phase 6: Loot System
         loot::run(self);
 
-        // Shield regeneration pass: regen if no hit recently
+        // Phase 7: Pheromone trails
+        self.update_pheromones();
+
+        // Shield regeneration pass: regen if no hit recently and not disrupted
         let agent_count = self.agents_data.len() / AGENT_STRIDE;
         for idx in 0..agent_count {
+            if self.agent_status(idx).has(Status::Disrupted) {
+                continue;
+            }
             let base = idx * AGENT_STRIDE;
-            let last = self.agents_data[base + IDX_LAST_HIT] as u32;
-            if self.tick_count.saturating_sub(last) >= self.config.shield_regen_delay {
+            let (regen, delay, tick) = (self.config.shield_regen_rate, self.config.shield_regen_delay, self.tick_count);
+            // directional shields: front and rear recover on their own timers
+            let last = if self.config.directional_shields {
+                let f = self.agent_facing(idx);
+                if tick.saturating_sub(f.rear_hit) >= delay && f.rear_deficit > 0.0 {
+                    self.facing_mut(idx).rear_deficit = (f.rear_deficit - regen).max(0.0);
+                }
+                f.front_hit
+            } else {
+                self.agents_data[base + IDX_LAST_HIT] as u32
+            };
+            if tick.saturating_sub(last) >= delay {
+                let cap = self.agent_max_shield(idx);
                 let sh = &mut self.agents_data[base + IDX_SHIELD];
-                *sh = (*sh + self.config.shield_regen_rate).min(self.config.max_shield);
+                *sh = (*sh + regen).min(cap);
             }
         }
 
+        // Status timers run down
+        for set in &mut self.statuses {
+            set.tick();
+        }
+
         // Ready for next tick
         self.commands.clear();
+
+        if let Some(mut rec) = self.recorder.take() {
+            rec.record(self);
+            self.recorder = Some(rec);
+        }
+        if let Some(mut ring) = self.snapshots.take() {
+            ring.observe(self);
+            self.snapshots = Some(ring);
+        }
+    }
+
+    /// Capture the current world state
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot::capture(self)
+    }
+
+    /// Restore world state from a snapshot taken with the same agents;
+    /// pending commands are dropped
+    pub fn restore(&mut self, snap: &Snapshot) -> Result<(), SnapshotError> {
+        if snap.agent_count() != self.agent_count() {
+            return Err(SnapshotError::AgentCount { expected: self.agent_count(), found: snap.agent_count() });
+        }
+        self.tick_count = snap.tick;
+        self.agents_data.clone_from(&snap.agents);
+        self.bullets_data.clone_from(&snap.bullets);
+        self.wrecks_data.clone_from(&snap.wrecks);
+        self.hits_data.clone_from(&snap.hits);
+        self.effects_data.clear();
+        self.statuses.clone_from(&snap.statuses);
+        self.facings.clone_from(&snap.facings);
+        self.slot_states.clone_from(&snap.slot_states);
+        self.velocities.clone_from(&snap.velocities);
+        self.shot_tallies.clone_from(&snap.shot_tallies);
+        self.pheromones.clone_from(&snap.pheromones);
+        self.obstacles.clone_from(&snap.obstacles);
+        [self.thrust_count, self.fire_count, self.idle_count, self.loot_count] = snap.counters;
+        self.commands.clear();
+        Ok(())
+    }
+
+    /// Keep the last `capacity` snapshots, taken every `interval` ticks
+    pub fn enable_auto_snapshots(&mut self, capacity: usize, interval: u32) {
+        let mut ring = SnapshotRing::new(capacity, interval);
+        ring.observe(self);
+        self.snapshots = Some(ring);
+    }
+
+    pub fn disable_auto_snapshots(&mut self) {
+        self.snapshots = None;
+    }
+
+    /// Ticks available to rewind to, oldest first
+    pub fn snapshot_ticks(&self) -> Vec<u32> {
+        self.snapshots.as_ref().map_or_else(Vec::new, |r| r.ticks())
+    }
+
+    /// Restore the latest automatic snapshot at or before `tick`, dropping newer
+    /// ones; returns the tick actually restored
+    pub fn rewind_to(&mut self, tick: u32) -> Result<u32, SnapshotError> {
+        let snap = self.snapshots.as_mut()
+            .and_then(|r| r.rewind_to(tick))
+            .ok_or(SnapshotError::NoSnapshot(tick))?;
+        self.restore(&snap)?;
+        Ok(snap.tick)
+    }
+
+    /// Start buffering one replay frame per tick (discards any previous recording)
+    pub fn start_recording(&mut self) {
+        self.recorder = Some(ReplayRecorder::new());
+    }
+
+    /// Stop recording and hand back the buffered frames
+    pub fn stop_recording(&mut self) -> Option<ReplayRecorder> {
+        self.recorder.take()
+    }
+
+    /// Frames buffered so far, if recording
+    pub fn recording(&self) -> Option<&ReplayRecorder> {
+        self.recorder.as_ref()
     }
 
     /// Pointer and length accessors for flat state arrays
@@ -193,6 +394,90 @@ impl Simulation {
     pub fn hits_ptr(&self) -> *const f32 { self.hits_data.as_ptr() }
     /// Length of hits_data array
     pub fn hits_len(&self) -> usize { self.hits_data.len() }
+    /// This tick's blasts: [x,y,radius,...]
+    pub fn effects_data(&self) -> &[f32] { &self.effects_data }
+
+    /// Reset the simulation RNG to a known seed
+    pub fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    /// Seed the simulation RNG was last initialized with
+    pub fn seed(&self) -> u64 { self.seed }
+
+    /// Lay out this match's procedural map (`Config::map_gen`) from `seed`:
+    /// obstacles replace any earlier generated ones (the configured wells are
+    /// left alone), wrecks are added, and with a spawn ring each team's agents
+    /// spread around their team's point, `sep_range` out. Does nothing when
+    /// map generation is off.
+    pub fn generate_map(&mut self, seed: u64) {
+        if !self.config.map_gen.enabled() {
+            return;
+        }
+        let teams = self.team_count() as usize;
+        let map = self.config.map_gen.generate(self.width, self.height, teams, seed);
+        self.obstacles = map.obstacles;
+        let pool = self.config.health_max * self.config.loot_init_ratio;
+        for p in map.wrecks {
+            self.wrecks_data.extend(&[p.x, p.y, pool]);
+        }
+        let (w, h) = (self.width as f32, self.height as f32);
+        for (t, &p) in map.spawns.iter().enumerate() {
+            let members: Vec<usize> = (0..self.agent_count()).filter(|&id| self.agent_team(id) as usize == t).collect();
+            let radius = if members.len() > 1 { self.config.sep_range } else { 0.0 };
+            for (k, &id) in members.iter().enumerate() {
+                let a = std::f32::consts::TAU * k as f32 / members.len() as f32;
+                let at = Vec2 { x: p.x + math::cos(a) * radius, y: p.y + math::sin(a) * radius }.wrap(w, h);
+                self.agents_data[id * AGENT_STRIDE + IDX_X] = at.x;
+                self.agents_data[id * AGENT_STRIDE + IDX_Y] = at.y;
+            }
+        }
+    }
+
+    /// Configured gravity wells followed by generated obstacles
+    pub fn wells(&self) -> impl Iterator<Item = &gravity::GravityWell> {
+        self.config.gravity_wells.iter().chain(&self.obstacles)
+    }
+
+    /// Current tick number
+    pub fn tick_count(&self) -> u32 { self.tick_count }
+
+    /// Stable FNV-1a hash of the dynamic state (tick, agents, bullets,
+    /// wrecks, statuses, facings, slot states, velocities, pheromones).
+    /// Used to verify that two runs of the same match evolve identically.
+    pub fn state_hash(&self) -> u64 {
+        let mut h = StateHasher::new();
+        h.word(self.tick_count);
+        for buf in [&self.agents_data, &self.bullets_data, &self.wrecks_data] {
+            h.floats(buf);
+        }
+        h.word(self.statuses.len() as u32);
+        for set in &self.statuses {
+            h.word(set.bits() as u32);
+            for status in Status::ALL {
+                h.word(set.remaining(status));
+            }
+        }
+        h.word(self.facings.len() as u32);
+        for f in &self.facings {
+            h.floats(&[f.heading.x, f.heading.y, f.rear_deficit]);
+            h.word(f.front_hit);
+            h.word(f.rear_hit);
+        }
+        h.word(self.slot_states.len() as u32);
+        for slots in &self.slot_states {
+            slots.hash_into(&mut h);
+        }
+        h.word(self.velocities.len() as u32);
+        for v in &self.velocities {
+            h.floats(&[v.x, v.y]);
+        }
+        if let Some(grid) = &self.pheromones {
+            grid.hash_into(&mut h);
+        }
+        h.finish()
+    }
 
     /// Load pretrained neural network weights (if any)
     pub fn load_weights(&mut self, _data: &[u8]) {
@@ -242,6 +527,113 @@ impl Simulation {
     }
 }
 
+// Runtime tuning: each setter validates the resulting Config and leaves the
+// simulation untouched on error.
+impl Simulation {
+    /// Read-only access to the live configuration
+    pub fn config(&self) -> &Config { &self.config }
+
+    /// Apply `f` to a copy of the config and commit it only if it validates
+    fn try_update_config(&mut self, f: impl FnOnce(&mut Config)) -> Result<(), ConfigError> {
+        let mut cfg = self.config.clone();
+        f(&mut cfg);
+        cfg.validate()?;
+        self.config = cfg;
+        Ok(())
+    }
+
+    /// Validated config edit; agents' health and shields are clamped to any lowered caps
+    pub fn update_config(&mut self, f: impl FnOnce(&mut Config)) -> Result<(), ConfigError> {
+        self.try_update_config(f)?;
+        for id in 0..self.agent_count() {
+            let (hp_cap, sh_cap) = (self.agent_health_max(id), self.agent_max_shield(id));
+            let base = id * AGENT_STRIDE;
+            self.agents_data[base + IDX_HEALTH] = self.agents_data[base + IDX_HEALTH].min(hp_cap);
+            self.agents_data[base + IDX_SHIELD] = self.agents_data[base + IDX_SHIELD].min(sh_cap);
+        }
+        Ok(())
+    }
+
+    /// Replace the whole config (validated, with the same clamping as `update_config`)
+    pub fn set_config(&mut self, cfg: Config) -> Result<(), ConfigError> {
+        self.update_config(|c| *c = cfg)
+    }
+
+    /// Current config as pretty-printed JSON
+    pub fn config_json(&self) -> String {
+        serde_json::to_string_pretty(&self.config).expect("config serializes")
+    }
+
+    /// Set laser targeting radius
+    pub fn set_attack_range(&mut self, range: f32) -> Result<(), ConfigError> {
+        self.try_update_config(|c| c.attack_range = range)
+    }
+
+    /// Set maximum speed (units per tick)
+    pub fn set_max_speed(&mut self, speed: f32) -> Result<(), ConfigError> {
+        self.try_update_config(|c| c.max_speed = speed)
+    }
+
+    /// Set the fraction of velocity kept per tick
+    pub fn set_friction(&mut self, friction: f32) -> Result<(), ConfigError> {
+        self.try_update_config(|c| c.friction = friction)
+    }
+
+    /// Replace the gravity wells
+    pub fn set_gravity_wells(&mut self, wells: Vec<gravity::GravityWell>) -> Result<(), ConfigError> {
+        self.try_update_config(|c| c.gravity_wells = wells)
+    }
+
+    /// Wells and obstacles flattened as [x, y, strength, radius] (`gravity::WELL_STRIDE`), for rendering
+    pub fn wells_data(&self) -> Vec<f32> {
+        self.wells().flat_map(|w| [w.x, w.y, w.strength, w.radius]).collect()
+    }
+
+    /// Summed well pull on an agent at `pos`
+    pub fn gravity_at(&self, pos: Vec2) -> Vec2 {
+        let (w, h) = (self.width as f32, self.height as f32);
+        gravity::field(self.wells(), |c| {
+            if self.is_toroidal() { pos.torus_delta(c, w, h) } else { Vec2 { x: c.x - pos.x, y: c.y - pos.y } }
+        })
+    }
+
+    /// Set separation radius and repulsion strength
+    pub fn set_separation(&mut self, range: f32, strength: f32) -> Result<(), ConfigError> {
+        self.try_update_config(|c| {
+            c.sep_range = range;
+            c.sep_strength = strength;
+        })
+    }
+
+    /// Set shield capacity and regen behavior; current shields are clamped to the new max
+    pub fn set_shield_params(&mut self, max_shield: f32, regen_delay: u32, regen_rate: f32) -> Result<(), ConfigError> {
+        self.update_config(|c| {
+            c.max_shield = max_shield;
+            c.shield_regen_delay = regen_delay;
+            c.shield_regen_rate = regen_rate;
+        })
+    }
+
+    /// Set pheromone cell size (0 disables), per-tick deposit, and decay fraction.
+    /// Toggling the grid changes the sensor length.
+    pub fn set_pheromone_params(&mut self, cell: f32, deposit: f32, decay: f32) -> Result<(), ConfigError> {
+        self.try_update_config(|c| {
+            c.pheromone_cell = cell;
+            c.pheromone_deposit = deposit;
+            c.pheromone_decay = decay;
+        })
+    }
+
+    /// Set loot range, flat gain, and pool fraction per tick
+    pub fn set_loot_params(&mut self, range: f32, fixed: f32, fraction: f32) -> Result<(), ConfigError> {
+        self.try_update_config(|c| {
+            c.loot_range = range;
+            c.loot_fixed = fixed;
+            c.loot_fraction = fraction;
+        })
+    }
+}
+
 impl Simulation {
     /// Create an empty Simulation without agents
     pub fn empty(width: u32, height: u32) -> Simulation {
@@ -251,15 +643,31 @@ impl Simulation {
             agents_data: Vec::new(),
             bullets_data: Vec::new(),
             wrecks_data: Vec::new(),
-            commands: HashMap::new(),
+            commands: BTreeMap::new(),
             thrust_count: 0,
             fire_count: 0,
             idle_count: 0,
             loot_count: 0,
             tick_count: 0,
             hits_data: Vec::new(),
+            effects_data: Vec::new(),
             config: Config::default(),
             agents_impl: Vec::new(),
+            team_brains: BTreeMap::new(),
+            agent_classes: Vec::new(),
+            statuses: Vec::new(),
+            facings: Vec::new(),
+            slot_states: Vec::new(),
+            velocities: Vec::new(),
+            shot_tallies: Vec::new(),
+            seed: default_seed(),
+            rng: StdRng::seed_from_u64(default_seed()),
+            recorder: None,
+            snapshots: None,
+            pheromones: None,
+            obstacles: Vec::new(),
+            accumulator_ms: 0.0,
+            prev_agents_data: Vec::new(),
         }
     }
 
@@ -270,37 +678,54 @@ impl Simulation {
         config: Config,
         agents: Vec<(Box<dyn Brain>, u32)>,
     ) -> Simulation {
-        let mut sim = Simulation {
-            width,
-            height,
-            agents_data: Vec::new(),
-            bullets_data: Vec::new(),
-            wrecks_data: Vec::new(),
-            commands: HashMap::new(),
-            thrust_count: 0,
-            fire_count: 0,
-            idle_count: 0,
-            loot_count: 0,
-            tick_count: 0,
-            hits_data: Vec::new(),
-            config,
-            agents_impl: Vec::new(),
-        };
+        let mut sim = Simulation::empty(width, height);
+        sim.config = config;
         // Reserve capacity for flat agent state
         sim.agents_data.reserve(agents.len() * AGENT_STRIDE);
         // Populate agents_data and agents_impl boxes
+        let center = Vec2 { x: width as f32 * 0.5, y: height as f32 * 0.5 };
         for (brain, team) in agents {
-            let x = width as f32 * 0.5;
-            let y = height as f32 * 0.5;
-            let health = sim.config.health_max;
-            let shield = sim.config.max_shield;
-            let last_hit = sim.tick_count as f32;
-            sim.agents_data.extend_from_slice(&[x, y, team as f32, health, shield, last_hit]);
-            sim.agents_impl.push(brain);
+            sim.push_agent(center, team, brain);
         }
         sim
     }
 
+    /// Append one agent at full health/shield and register its brain
+    pub(crate) fn push_agent(&mut self, pos: Vec2, team: u32, brain: Box<dyn Brain>) {
+        self.push_agent_with_class(pos, team, None, brain);
+    }
+
+    /// Append one agent; a class sets its spawn health/shield instead of the config
+    pub(crate) fn push_agent_with_class(&mut self, pos: Vec2, team: u32, class: Option<ShipClass>, mut brain: Box<dyn Brain>) {
+        brain.reset();
+        let (health, shield) = match &class {
+            Some(c) => (c.hull, c.shield),
+            None => (self.config.team_health_max(team), self.config.max_shield),
+        };
+        let last_hit = self.tick_count as f32;
+        let id = self.agent_count();
+        self.agents_data.extend_from_slice(&[pos.x, pos.y, team as f32, health, shield, last_hit]);
+        self.agents_impl.push(brain);
+        if self.agent_classes.len() < id {
+            self.agent_classes.resize(id, None);
+        }
+        self.agent_classes.insert(id, class);
+    }
+
+    /// N-team free-for-all: `counts[t]` naive agents for team t, clustered on a ring
+    pub fn new_teams(width: u32, height: u32, counts: &[u32]) -> Simulation {
+        Simulation::new_teams_seeded(width, height, counts, default_seed())
+    }
+
+    /// Like `new_teams`, with cluster placement drawn from an explicit seed
+    pub fn new_teams_seeded(width: u32, height: u32, counts: &[u32], seed: u64) -> Simulation {
+        let radius = width.min(height) as f32 * 0.1;
+        SimulationBuilder::new(width, height)
+            .seed(seed)
+            .team_clusters(counts, radius, |_| naive_factory())
+            .build()
+    }
+
     /// Head-to-head NN vs Naive duel constructor
     pub fn new_nn_vs_naive(
         width: u32, height: u32,
@@ -318,18 +743,375 @@ impl Simulation {
 }
 
 impl Simulation {
-    /// Enqueue or overwrite a command for a ship this tick
+    /// Enqueue or overwrite a command for a ship this tick; it replaces the
+    /// ship's brain decision on the next `step`
     pub fn push_command(&mut self, actor_id: usize, action: Action) {
         self.commands.insert(actor_id, action);
     }
 
+    /// Weapon fired on behalf of agent `id` when no weapon is specified:
+    /// its class default, else a laser at the agent's attack range
+    pub fn default_weapon(&self, id: usize) -> domain::Weapon {
+        self.agent_class(id)
+            .and_then(|c| c.weapons().next().cloned())
+            .unwrap_or(domain::Weapon::Laser {
+                damage: ship::DEFAULT_LASER_DAMAGE,
+                range: self.agent_attack_range(id),
+            })
+    }
+
+    /// Let `brain` decide for every agent on `team` at once; the members' own
+    /// brains are skipped until it is cleared
+    pub fn set_team_brain(&mut self, team: u32, mut brain: Box<dyn TeamBrain>) {
+        brain.reset();
+        self.team_brains.insert(team, brain);
+    }
+
+    /// Hand `team` back to its members' own brains, returning its controller
+    pub fn clear_team_brain(&mut self, team: u32) -> Option<Box<dyn TeamBrain>> {
+        self.team_brains.remove(&team)
+    }
+
     /// Register an agent for decision making
     pub fn register_agent(&mut self, agent: Box<dyn Brain>) {
         self.agents_impl.push(agent);
     }
 
+    /// Ship class assigned to agent `id`, if any
+    pub fn agent_class(&self, id: usize) -> Option<&ShipClass> {
+        self.agent_classes.get(id).and_then(|c| c.as_ref())
+    }
+
+    /// Timed statuses currently on agent `id`
+    pub fn agent_status(&self, id: usize) -> StatusSet {
+        self.statuses.get(id).copied().unwrap_or_default()
+    }
+
+    /// Heading and rear shield of agent `id`
+    pub fn agent_facing(&self, id: usize) -> Facing {
+        self.facings.get(id).copied().unwrap_or_default()
+    }
+
+    pub(crate) fn facing_mut(&mut self, id: usize) -> &mut Facing {
+        if self.facings.len() <= id {
+            self.facings.resize(id + 1, Facing::default());
+        }
+        &mut self.facings[id]
+    }
+
+    /// Cooldowns and ammo of agent `id`'s loadout slots
+    pub fn agent_slots(&self, id: usize) -> SlotState {
+        self.slot_states.get(id).copied().unwrap_or_default()
+    }
+
+    pub(crate) fn slot_state_mut(&mut self, id: usize) -> &mut SlotState {
+        if self.slot_states.len() <= id {
+            self.slot_states.resize(id + 1, SlotState::default());
+        }
+        &mut self.slot_states[id]
+    }
+
+    /// Velocity agent `id` carries into the next tick
+    pub fn agent_velocity(&self, id: usize) -> Vec2 {
+        self.velocities.get(id).copied().unwrap_or(Vec2 { x: 0.0, y: 0.0 })
+    }
+
+    pub(crate) fn set_velocity(&mut self, id: usize, v: Vec2) {
+        if self.velocities.len() <= id {
+            self.velocities.resize(id + 1, Vec2 { x: 0.0, y: 0.0 });
+        }
+        self.velocities[id] = v;
+    }
+
+    /// Trigger pulls, shots and hits of agent `id` so far
+    pub fn agent_shots(&self, id: usize) -> ShotTally {
+        self.shot_tallies.get(id).copied().unwrap_or_default()
+    }
+
+    /// Summed gunnery record of every agent on `team`
+    pub fn team_shots(&self, team: u32) -> ShotTally {
+        let mut total = ShotTally::default();
+        for id in 0..self.agent_count() {
+            if self.agent_team(id) == team {
+                total.add(&self.agent_shots(id));
+            }
+        }
+        total
+    }
+
+    pub(crate) fn shot_tally_mut(&mut self, id: usize) -> &mut ShotTally {
+        if self.shot_tallies.len() <= id {
+            self.shot_tallies.resize(id + 1, ShotTally::default());
+        }
+        &mut self.shot_tallies[id]
+    }
+
+    /// Inflict `status` on agent `id` for `ticks` ticks
+    pub fn inflict(&mut self, id: usize, status: Status, ticks: u32) {
+        if self.statuses.len() <= id {
+            self.statuses.resize(id + 1, StatusSet::default());
+        }
+        self.statuses[id].apply(status, ticks);
+    }
+
+    fn agent_team(&self, id: usize) -> u32 {
+        self.agents_data[id * AGENT_STRIDE + IDX_TEAM] as u32
+    }
+
+    /// Effective max speed for agent `id` (class, else team/global config)
+    pub(crate) fn agent_max_speed(&self, id: usize) -> f32 {
+        self.agent_class(id).map_or_else(|| self.config.team_max_speed(self.agent_team(id)), |c| c.speed)
+    }
+
+    /// Effective max health for agent `id`
+    pub(crate) fn agent_health_max(&self, id: usize) -> f32 {
+        self.agent_class(id).map_or_else(|| self.config.team_health_max(self.agent_team(id)), |c| c.hull)
+    }
+
+    /// Effective max shield for agent `id`
+    pub(crate) fn agent_max_shield(&self, id: usize) -> f32 {
+        self.agent_class(id).map_or(self.config.max_shield, |c| c.shield)
+    }
+
+    /// Effective attack range for agent `id` (class laser range, else team/global config)
+    pub(crate) fn agent_attack_range(&self, id: usize) -> f32 {
+        self.agent_class(id)
+            .and_then(|c| c.laser_range())
+            .unwrap_or_else(|| self.config.team_attack_range(self.agent_team(id)))
+    }
+
+    /// Number of agent records (alive or dead)
+    pub fn agent_count(&self) -> usize {
+        self.agents_data.len() / AGENT_STRIDE
+    }
+
+    /// One past the highest team id present (0 with no agents)
+    pub fn team_count(&self) -> u32 {
+        self.agents_data.chunks(AGENT_STRIDE)
+            .map(|ch| ch[IDX_TEAM] as u32 + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Living agents per team id, indexed 0..team_count()
+    pub fn team_alive_counts(&self) -> Vec<u32> {
+        let mut counts = vec![0; self.team_count() as usize];
+        for ch in self.agents_data.chunks(AGENT_STRIDE) {
+            if ch[IDX_HEALTH] > 0.0 {
+                counts[ch[IDX_TEAM] as usize] += 1;
+            }
+        }
+        counts
+    }
+
+    /// True once at most one team has living agents
+    pub fn is_decided(&self) -> bool {
+        self.team_alive_counts().iter().filter(|&&c| c > 0).count() <= 1
+    }
+
+    /// Advance up to `n` ticks, optionally stopping once the match is decided.
+    /// Hit segments from every tick in the batch are kept for rendering; the
+    /// per-tick action counters reflect the last tick only. Returns ticks run.
+    pub fn step_n(&mut self, n: u32, stop_when_decided: bool) -> u32 {
+        self.step_n_with(n, stop_when_decided, |_| true)
+    }
+
+    /// Like `step_n`, calling `on_tick(sim)` after each tick; returning false stops early
+    pub fn step_n_with<F>(&mut self, n: u32, stop_when_decided: bool, mut on_tick: F) -> u32
+    where
+        F: FnMut(&Simulation) -> bool,
+    {
+        let (mut hits, mut effects) = (Vec::new(), Vec::new());
+        let mut ran = 0;
+        while ran < n {
+            if stop_when_decided && self.is_decided() {
+                break;
+            }
+            self.step();
+            hits.extend_from_slice(&self.hits_data);
+            effects.extend_from_slice(&self.effects_data);
+            ran += 1;
+            if !on_tick(self) {
+                break;
+            }
+        }
+        if ran > 0 {
+            self.hits_data = hits;
+            self.effects_data = effects;
+        }
+        ran
+    }
+
+    /// Real-time entry point: add `dt_ms` of wall-clock time and run as many
+    /// whole ticks of `1000 / tick_rate_hz` ms as it covers (at most
+    /// `MAX_CATCH_UP_TICKS`), carrying the remainder to the next call. Hits
+    /// and blasts from every tick run are kept, as with `step_n`.
+    pub fn advance(&mut self, dt_ms: f32) -> Advance {
+        let tick_ms = 1000.0 / self.config.tick_rate_hz;
+        self.accumulator_ms += dt_ms.max(0.0);
+        let ticks = ((self.accumulator_ms / tick_ms) as u32).min(MAX_CATCH_UP_TICKS);
+        self.accumulator_ms = if ticks == MAX_CATCH_UP_TICKS {
+            self.accumulator_ms % tick_ms
+        } else {
+            self.accumulator_ms - ticks as f32 * tick_ms
+        };
+        if ticks > 0 {
+            self.step_n(ticks - 1, false);
+            let mut hits = std::mem::take(&mut self.hits_data);
+            let mut effects = std::mem::take(&mut self.effects_data);
+            self.prev_agents_data.clone_from(&self.agents_data);
+            self.step();
+            hits.extend_from_slice(&self.hits_data);
+            effects.extend_from_slice(&self.effects_data);
+            self.hits_data = hits;
+            self.effects_data = effects;
+        } else if self.prev_agents_data.len() != self.agents_data.len() {
+            // nothing to interpolate from yet (first call, or agents spawned)
+            self.prev_agents_data.clone_from(&self.agents_data);
+        }
+        Advance { ticks, alpha: (self.accumulator_ms / tick_ms).clamp(0.0, 1.0) }
+    }
+
+    /// Agent state as of the tick before the last one `advance` ran
+    pub fn prev_agents_data(&self) -> &[f32] {
+        &self.prev_agents_data
+    }
+
+    /// Spawn a new agent mid-run at full health/shield; returns its id
+    pub fn spawn_agent(&mut self, pos: Vec2, team: u32, brain: Box<dyn Brain>) -> usize {
+        self.spawn_agent_with_class(pos, team, None, brain)
+    }
+
+    /// Like `spawn_agent`, with an optional ship class (stats and weapon loadout)
+    pub fn spawn_agent_with_class(&mut self, pos: Vec2, team: u32, class: Option<ShipClass>, brain: Box<dyn Brain>) -> usize {
+        let w = self.width as f32;
+        let h = self.height as f32;
+        let pos = match self.config.distance_mode {
+            DistanceMode::Toroidal => pos.wrap(w, h),
+            DistanceMode::Euclidean => Vec2 { x: pos.x.clamp(0.0, w), y: pos.y.clamp(0.0, h) },
+        };
+        let id = self.agent_count();
+        self.push_agent_with_class(pos, team, class, brain);
+        id
+    }
+
+    /// Remove agent `id`, returning its brain. Agents after it shift down by one
+    /// id; pending commands are re-keyed to match and the removed agent's is dropped.
+    pub fn remove_agent(&mut self, id: usize) -> Option<Box<dyn Brain>> {
+        if id >= self.agent_count() || id >= self.agents_impl.len() {
+            return None;
+        }
+        let base = id * AGENT_STRIDE;
+        self.agents_data.drain(base..base + AGENT_STRIDE);
+        let brain = self.agents_impl.remove(id);
+        if id < self.agent_classes.len() {
+            self.agent_classes.remove(id);
+        }
+        if id < self.statuses.len() {
+            self.statuses.remove(id);
+        }
+        if id < self.facings.len() {
+            self.facings.remove(id);
+        }
+        if id < self.slot_states.len() {
+            self.slot_states.remove(id);
+        }
+        if id < self.velocities.len() {
+            self.velocities.remove(id);
+        }
+        if id < self.shot_tallies.len() {
+            self.shot_tallies.remove(id);
+        }
+        let old = std::mem::take(&mut self.commands);
+        self.commands = old.into_iter()
+            .filter(|(aid, _)| *aid != id)
+            .map(|(aid, a)| if aid > id { (aid - 1, a) } else { (aid, a) })
+            .collect();
+        Some(brain)
+    }
+
+    /// Decay the pheromone grid, then have every living agent deposit on its cell
+    fn update_pheromones(&mut self) {
+        if !self.config.pheromones_enabled() {
+            self.pheromones = None;
+            return;
+        }
+        let cell = self.config.pheromone_cell;
+        let (w, h) = (self.width, self.height);
+        if self.pheromones.as_ref().is_none_or(|g| g.cell_size() != cell.max(1.0)) {
+            self.pheromones = Some(PheromoneGrid::new(w, h, cell));
+        }
+        let Some(grid) = self.pheromones.as_mut() else { return };
+        grid.decay(1.0 - self.config.pheromone_decay);
+        for ch in self.agents_data.chunks(AGENT_STRIDE) {
+            if ch[IDX_HEALTH] > 0.0 {
+                grid.deposit(ch[IDX_TEAM] as u32, Vec2 { x: ch[IDX_X], y: ch[IDX_Y] }, self.config.pheromone_deposit);
+            }
+        }
+    }
+
+    /// The pheromone grid, once a tick has run with `pheromone_cell` set
+    pub fn pheromones(&self) -> Option<&PheromoneGrid> {
+        self.pheromones.as_ref()
+    }
+
+    /// Count `action` in this tick's command totals
+    fn tally(&mut self, action: &Action) {
+        match action {
+            Action::Thrust(_) => self.thrust_count += 1,
+            Action::Idle => self.idle_count += 1,
+            Action::Loot => self.loot_count += 1,
+            Action::Fire { .. } | Action::FireSlot { .. } => self.fire_count += 1,
+        }
+    }
+
+    /// Decision phase for team-controlled teams: each controller gets all its
+    /// living members without an externally queued command at once
+    fn think_teams(&mut self) {
+        if self.team_brains.is_empty() {
+            return;
+        }
+        let mut brains = std::mem::take(&mut self.team_brains);
+        let (positions, teams, healths, shields, wreck_positions, wreck_pools, w, h) = self.build_global_view();
+        for (&team, brain) in brains.iter_mut() {
+            let ids: Vec<usize> = (0..positions.len().min(self.agents_impl.len()))
+                .filter(|&i| teams[i] == team && healths[i] > 0.0 && !self.commands.contains_key(&i))
+                .collect();
+            if ids.is_empty() {
+                continue;
+            }
+            let members: Vec<Teammate> = ids.iter().map(|&idx| Teammate {
+                view: WorldView {
+                    self_idx: idx,
+                    self_pos: positions[idx],
+                    self_team: team,
+                    self_health: healths[idx],
+                    self_shield: shields[idx],
+                    positions: &positions,
+                    teams: &teams,
+                    healths: &healths,
+                    shields: &shields,
+                    wreck_positions: &wreck_positions,
+                    wreck_pools: &wreck_pools,
+                    world_width: w,
+                    world_height: h,
+                    attack_range: self.agent_attack_range(idx),
+                    sep_range: self.config.sep_range,
+                },
+                inputs: self.scan(idx, self.config.scan_rays, self.config.scan_max_dist),
+            }).collect();
+            let mut actions = brain.think_team(team, &members).into_iter();
+            for idx in ids {
+                let action = actions.next().unwrap_or(Action::Idle);
+                self.tally(&action);
+                self.commands.insert(idx, action);
+            }
+        }
+        self.team_brains = brains;
+    }
+
     /// Flatten agents_data buffers into read-only vectors (positions, teams, healths, shields)
-    fn build_global_view(&self) -> (Vec<Vec2>, Vec<usize>, Vec<f32>, Vec<f32>, Vec<Vec2>, Vec<f32>, f32, f32) {
+    fn build_global_view(&self) -> (Vec<Vec2>, Vec<u32>, Vec<f32>, Vec<f32>, Vec<Vec2>, Vec<f32>, f32, f32) {
         let count = self.agents_data.len() / AGENT_STRIDE;
         let mut positions = Vec::with_capacity(count);
         let mut teams = Vec::with_capacity(count);
@@ -338,7 +1120,7 @@ impl Simulation {
         for i in 0..count {
             let base = i * AGENT_STRIDE;
             positions.push(Vec2 { x: self.agents_data[base + IDX_X], y: self.agents_data[base + IDX_Y] });
-            teams.push(self.agents_data[base + IDX_TEAM] as usize);
+            teams.push(self.agents_data[base + IDX_TEAM] as u32);
             healths.push(self.agents_data[base + IDX_HEALTH]);
             shields.push(self.agents_data[base + IDX_SHIELD]);
         }
@@ -357,18 +1139,18 @@ impl Simulation {
         (positions, teams, healths, shields, wreck_positions, wreck_pools, self.width as f32, self.height as f32)
     }
 
-    /// Sensor: nearest-K encoding of self stats, enemies, allies, wrecks
+    /// Sensor: self stats, then per `sensor_mode` the nearest-K enemies,
+    /// allies, and wrecks and/or the influence grid, then the optional class
+    /// and pheromone groups (see `Config::sensor_layout`)
     pub fn scan(&self, agent_idx: usize, _rays: usize, _max_dist: f32) -> Vec<f32> {
         let cfg = &self.config;
         let (positions, teams, healths, shields, wreck_positions, wreck_pools, w, h) = self.build_global_view();
         let self_team = teams[agent_idx];
         let self_pos = positions[agent_idx];
         // normalize self stats
-        let self_hp = healths[agent_idx] / cfg.health_max;
-        let self_sh = shields[agent_idx] / cfg.max_shield;
-        let mut out = Vec::with_capacity(
-            2 + 4*cfg.nearest_k_enemies + 4*cfg.nearest_k_allies + 3*cfg.nearest_k_wrecks
-        );
+        let self_hp = healths[agent_idx] / self.agent_health_max(agent_idx);
+        let self_sh = shields[agent_idx] / self.agent_max_shield(agent_idx);
+        let mut out = Vec::with_capacity(cfg.sensor_len());
         out.push(self_hp);
         out.push(self_sh);
         // distance squared helper
@@ -389,20 +1171,21 @@ impl Simulation {
                 DistanceMode::Toroidal => self_pos.torus_delta(pos, w, h),
             }
         };
+        let (k_enemies, k_allies, k_wrecks) = cfg.sensed_nearest_k();
         // Nearest enemies
         let mut enemies: Vec<_> = positions.iter().cloned().enumerate()
             .filter(|&(i,_p)| i != agent_idx && healths[i] > 0.0 && teams[i] != self_team)
             .map(|(i,p)| (dist2(p), i))
             .collect();
-        enemies.sort_by(|a,b| a.0.partial_cmp(&b.0).unwrap());
-        for &(_, i) in enemies.iter().take(cfg.nearest_k_enemies) {
+        enemies.sort_by(|a,b| a.0.total_cmp(&b.0));
+        for &(_, i) in enemies.iter().take(k_enemies) {
             let d = delta(positions[i]);
             out.push(d.x / (w/2.0));
             out.push(d.y / (h/2.0));
-            out.push(healths[i] / cfg.health_max);
-            out.push(shields[i] / cfg.max_shield);
+            out.push(healths[i] / self.agent_health_max(i));
+            out.push(shields[i] / self.agent_max_shield(i));
         }
-        for _ in enemies.len()..cfg.nearest_k_enemies {
+        for _ in enemies.len()..k_enemies {
             out.extend(&[0.0; 4]);
         }
         // Nearest allies
@@ -410,15 +1193,15 @@ impl Simulation {
             .filter(|&(i,_p)| i != agent_idx && healths[i] > 0.0 && teams[i] == self_team)
             .map(|(i,p)| (dist2(p), i))
             .collect();
-        allies.sort_by(|a,b| a.0.partial_cmp(&b.0).unwrap());
-        for &(_, i) in allies.iter().take(cfg.nearest_k_allies) {
+        allies.sort_by(|a,b| a.0.total_cmp(&b.0));
+        for &(_, i) in allies.iter().take(k_allies) {
             let d = delta(positions[i]);
             out.push(d.x / (w/2.0));
             out.push(d.y / (h/2.0));
-            out.push(healths[i] / cfg.health_max);
-            out.push(shields[i] / cfg.max_shield);
+            out.push(healths[i] / self.agent_health_max(i));
+            out.push(shields[i] / self.agent_max_shield(i));
         }
-        for _ in allies.len()..cfg.nearest_k_allies {
+        for _ in allies.len()..k_allies {
             out.extend(&[0.0; 4]);
         }
         // Nearest wrecks
@@ -427,19 +1210,87 @@ impl Simulation {
             .filter(|&(i,_p)| wreck_pools[i] > 0.0)
             .map(|(i,p)| (dist2(p), i))
             .collect();
-        wrecks.sort_by(|a,b| a.0.partial_cmp(&b.0).unwrap());
-        for &(_, i) in wrecks.iter().take(cfg.nearest_k_wrecks) {
+        wrecks.sort_by(|a,b| a.0.total_cmp(&b.0));
+        for &(_, i) in wrecks.iter().take(k_wrecks) {
             let d = delta(wreck_positions[i]);
             out.push(d.x / (w/2.0));
             out.push(d.y / (h/2.0));
             out.push(wreck_pools[i] / max_wpool);
         }
-        for _ in wrecks.len()..cfg.nearest_k_wrecks {
+        for _ in wrecks.len()..k_wrecks {
             out.extend(&[0.0; 3]);
         }
+        // Influence grid centred on us: enemy HP, ally HP (in full ships), wreck pool per cell
+        let n = cfg.influence_cells;
+        if cfg.influence_grid_cells() > 0 {
+            let mut grid = vec![0.0f32; n * n * 3];
+            let cell_of = |pos: Vec2| -> Option<usize> {
+                let d = delta(pos);
+                let half = n as f32 / 2.0;
+                let col = (d.x / cfg.influence_cell_size + half).floor();
+                let row = (d.y / cfg.influence_cell_size + half).floor();
+                let inside = (0.0..n as f32).contains(&col) && (0.0..n as f32).contains(&row);
+                inside.then(|| row as usize * n + col as usize)
+            };
+            for (i, &p) in positions.iter().enumerate() {
+                if i == agent_idx || healths[i] <= 0.0 { continue; }
+                if let Some(c) = cell_of(p) {
+                    let feature = if teams[i] == self_team { 1 } else { 0 };
+                    grid[c * 3 + feature] += healths[i] / self.agent_health_max(i);
+                }
+            }
+            for (i, &p) in wreck_positions.iter().enumerate() {
+                if let Some(c) = cell_of(p) {
+                    grid[c * 3 + 2] += wreck_pools[i] / max_wpool;
+                }
+            }
+            out.extend(grid);
+        }
+        // Own ship class stats
+        if cfg.class_sensors {
+            let features = match self.agent_class(agent_idx) {
+                Some(c) => c.sensor_features(cfg),
+                None => ShipClass::from_config(cfg, self_team).sensor_features(cfg),
+            };
+            out.extend(&features);
+        }
+        // Own heading and rear shield fraction
+        if cfg.directional_shields {
+            let f = self.agent_facing(agent_idx);
+            let max = self.agent_max_shield(agent_idx);
+            let rear = if max > 0.0 { f.rear_shield(max) / max } else { 0.0 };
+            out.extend(&[f.heading.x, f.heading.y, rear]);
+        }
+        // Own statuses: 1.0 while active
+        if cfg.status_sensors {
+            let set = self.agent_status(agent_idx);
+            out.extend(Status::ALL.iter().map(|&s| if set.has(s) { 1.0 } else { 0.0 }));
+        }
+        // Net well pull (relative to full thrust) and the nearest well's offset
+        if cfg.gravity_sensors {
+            let pull = self.gravity_at(self_pos);
+            let nearest = self.wells()
+                .map(|well| delta(well.center()))
+                .min_by(|a, b| a.length().total_cmp(&b.length()))
+                .map_or(Vec2 { x: 0.0, y: 0.0 }, |d| Vec2 { x: d.x / (w / 2.0), y: d.y / (h / 2.0) });
+            out.extend(&[pull.x / cfg.max_accel, pull.y / cfg.max_accel, nearest.x, nearest.y]);
+        }
+        // Own team's pheromone around us, 1.0 = one agent's steady-state trail
+        if cfg.pheromones_enabled() {
+            let sensed = self.pheromones.as_ref()
+                .map_or([0.0; pheromone::SENSED_CELLS], |g| g.sense(self_team, self_pos));
+            let full = cfg.pheromone_deposit / cfg.pheromone_decay.max(0.01);
+            out.extend(sensed.iter().map(|v| if full > 0.0 { (v / full).min(1.0) } else { 0.0 }));
+        }
         out
     }
 
+    /// Uniform [0,1) coefficient for spawn placement (from the seeded RNG)
+    fn random_coef(&mut self) -> f32 {
+        use rand::Rng;
+        self.rng.gen::<f32>()
+    }
+
     fn spawn_quadrants(
         &mut self,
         counts: [u32;4],                     // [orange,yellow,green,blue]
@@ -450,19 +1301,13 @@ impl Simulation {
         let half_h = self.height as f32 / 2.0;
         for (q, &count) in counts.iter().enumerate() {
             for _ in 0..count {
-                let rx = random_coef();
+                let rx = self.random_coef();
                 let x = if q % 2 == 0 { rx * half_w } else { half_w + rx * half_w };
-                let ry = random_coef();
+                let ry = self.random_coef();
                 let y = if q < 2 { ry * half_h } else { half_h + ry * half_h };
-                self.agents_data.push(x);
-                self.agents_data.push(y);
-                self.agents_data.push(q as f32);
-                self.agents_data.push(100.0);
-                self.agents_data.push(self.config.max_shield);
-                self.agents_data.push(0.0);
                 let idx = assignment[q];
                 let brain = factories[idx]();
-                self.register_agent(brain);
+                self.push_agent(Vec2 { x, y }, q as u32, brain);
             }
         }
     }
@@ -473,6 +1318,21 @@ impl Simulation {
 mod tests {
     use super::*;
 
+    #[test]
+    fn advance_runs_whole_ticks_and_carries_the_rest() {
+        let mut sim = Simulation::new_seeded(200, 200, 1, 1, 0, 0, 3);
+        let tick_ms = 1000.0 / sim.config.tick_rate_hz;
+        let first = sim.advance(tick_ms * 2.5);
+        assert_eq!(first.ticks, 2);
+        assert!((first.alpha - 0.5).abs() < 1e-3);
+        assert_eq!(sim.prev_agents_data().len(), sim.agents_data.len());
+        assert_eq!(sim.advance(tick_ms * 0.6).ticks, 1);
+        assert_eq!(sim.tick_count(), 3);
+        // a long stall runs a bounded catch-up instead of the whole backlog
+        assert_eq!(sim.advance(tick_ms * 100.0).ticks, MAX_CATCH_UP_TICKS);
+        assert!(sim.advance(0.0).alpha < 1.0);
+    }
+
     #[test]
     fn it_works() {
         let sim = Simulation::new(10, 10, 0, 0, 0, 0);
@@ -509,6 +1369,246 @@ mod tests {
         assert_eq!(sim.agents_data[IDX_SHIELD], 20.0);
     }
 
+    #[test]
+    fn runtime_setters_validate() {
+        let mut sim = Simulation::new(10, 10, 0, 0, 0, 0);
+        assert!(sim.set_attack_range(25.0).is_ok());
+        assert_eq!(sim.attack_range(), 25.0);
+        assert!(sim.set_max_speed(-1.0).is_err());
+        assert_eq!(sim.config().max_speed, Config::default().max_speed);
+    }
+
+    #[test]
+    fn shrinking_max_shield_clamps_agents() {
+        let mut sim = Simulation::new(10, 10, 0, 0, 0, 0);
+        sim.agents_data.extend(&[0.0, 0.0, 0.0, 100.0, 50.0, 0.0]);
+        sim.set_shield_params(20.0, 10, 1.0).unwrap();
+        assert_eq!(sim.agents_data[IDX_SHIELD], 20.0);
+    }
+
+    #[test]
+    fn spawn_and_remove_keep_indices_consistent() {
+        let mut sim = Simulation::new(100, 100, 0, 0, 0, 0);
+        let a = sim.spawn_agent(Vec2 { x: 10.0, y: 10.0 }, 0, naive_factory());
+        let b = sim.spawn_agent(Vec2 { x: 20.0, y: 20.0 }, 1, naive_factory());
+        let c = sim.spawn_agent(Vec2 { x: 30.0, y: 30.0 }, 2, naive_factory());
+        assert_eq!((a, b, c), (0, 1, 2));
+        sim.push_command(0, Action::Idle);
+        sim.push_command(1, Action::Loot);
+        sim.push_command(2, Action::Idle);
+        assert!(sim.remove_agent(1).is_some());
+        assert_eq!(sim.agent_count(), 2);
+        assert_eq!(sim.agents_impl.len(), 2);
+        // former agent 2 is now agent 1
+        assert_eq!(sim.agents_data[AGENT_STRIDE + IDX_TEAM], 2.0);
+        assert!(matches!(sim.commands.get(&1), Some(Action::Idle)));
+        assert!(!sim.commands.contains_key(&2));
+        assert!(sim.remove_agent(5).is_none());
+        sim.step();
+    }
+
+    #[test]
+    fn team_brain_decides_for_its_members() {
+        use std::{cell::Cell, rc::Rc};
+        struct Count(Rc<Cell<usize>>);
+        impl TeamBrain for Count {
+            fn think_team(&mut self, _team: u32, members: &[Teammate]) -> Vec<Action> {
+                self.0.set(members.len());
+                vec![Action::Loot]
+            }
+        }
+        let seen = Rc::new(Cell::new(0));
+        let mut sim = Simulation::new_seeded(400, 400, 3, 2, 0, 0, 1);
+        sim.set_team_brain(0, Box::new(Count(seen.clone())));
+        sim.push_command(1, Action::Idle);
+        sim.step();
+        // the externally commanded member is left out
+        assert_eq!(seen.get(), 2);
+        assert_eq!(sim.loot_count, 1);
+        assert!(sim.clear_team_brain(0).is_some());
+
+        sim.set_team_brain(1, Box::new(ai::FocusFireTeam { speed: 1.2, attack_damage: 0.8 }));
+        sim.step();
+        assert_eq!(sim.thrust_count + sim.fire_count + sim.idle_count + sim.loot_count, 5);
+    }
+
+    #[test]
+    fn pheromone_sensors_follow_the_trail() {
+        let mut sim = Simulation::empty(100, 100);
+        let a = sim.spawn_agent(Vec2 { x: 15.0, y: 15.0 }, 0, naive_factory());
+        sim.spawn_agent(Vec2 { x: 85.0, y: 85.0 }, 1, naive_factory());
+        let plain = sim.config.sensor_len();
+        sim.set_pheromone_params(10.0, 1.0, 0.5).unwrap();
+        assert_eq!(sim.config.sensor_len(), plain + pheromone::SENSED_CELLS);
+        assert!(sim.scan(a, 0, 0.0)[plain..].iter().all(|&v| v == 0.0));
+        sim.push_command(0, Action::Idle);
+        sim.push_command(1, Action::Idle);
+        sim.step();
+        // one deposit of 1 against a steady state of 1 / 0.5
+        let v = sim.scan(a, 0, 0.0);
+        assert_eq!(v.len(), sim.config.sensor_len());
+        assert_eq!(v[plain], 0.5);
+        assert_eq!(sim.pheromones().unwrap().layer(1).iter().sum::<f32>(), 1.0);
+        sim.set_pheromone_params(0.0, 1.0, 0.5).unwrap();
+        sim.step();
+        assert!(sim.pheromones().is_none());
+    }
+
+    #[test]
+    fn influence_grid_sums_hp_around_the_agent() {
+        let mut sim = Simulation::empty(400, 400);
+        sim.config.sensor_mode = SensorMode::Influence;
+        sim.config.influence_cells = 3;
+        sim.config.influence_cell_size = 10.0;
+        let me = sim.spawn_agent(Vec2 { x: 200.0, y: 200.0 }, 0, naive_factory());
+        sim.spawn_agent(Vec2 { x: 212.0, y: 200.0 }, 1, naive_factory());
+        sim.spawn_agent(Vec2 { x: 213.0, y: 201.0 }, 1, naive_factory());
+        sim.spawn_agent(Vec2 { x: 190.0, y: 191.0 }, 0, naive_factory());
+        sim.spawn_agent(Vec2 { x: 300.0, y: 300.0 }, 1, naive_factory());
+        let v = sim.scan(me, 0, 0.0);
+        assert_eq!(v.len(), sim.config.sensor_len());
+        assert_eq!(v.len(), 2 + 9 * 3);
+        let cell = |row: usize, col: usize| &v[2 + (row * 3 + col) * 3..][..3];
+        // two enemies east of centre, one ally north-west, the far enemy unseen
+        assert_eq!(cell(1, 2), &[2.0, 0.0, 0.0]);
+        assert_eq!(cell(0, 0), &[0.0, 1.0, 0.0]);
+        assert_eq!(v[2..].iter().sum::<f32>(), 3.0);
+
+        sim.config.sensor_mode = SensorMode::Both;
+        let v = sim.scan(me, 0, 0.0);
+        assert_eq!(v.len(), sim.config.sensor_len());
+        let (ke, ka, kw) = sim.config.sensed_nearest_k();
+        assert_eq!(v.len(), 2 + 4 * ke + 4 * ka + 3 * kw + 27);
+    }
+
+    #[test]
+    fn eight_team_free_for_all() {
+        let mut sim = Simulation::new_teams(400, 400, &[2; 8]);
+        assert_eq!(sim.team_count(), 8);
+        assert_eq!(sim.team_alive_counts(), vec![2; 8]);
+        sim.step();
+    }
+
+    #[test]
+    fn team_health_override_applies_at_spawn() {
+        let mut sim = Simulation::empty(100, 100);
+        sim.config.team_overrides.push(TeamOverrides { team: 1, health_max: Some(250.0), ..Default::default() });
+        sim.spawn_agent(Vec2 { x: 1.0, y: 1.0 }, 0, naive_factory());
+        sim.spawn_agent(Vec2 { x: 2.0, y: 2.0 }, 1, naive_factory());
+        assert_eq!(sim.agents_data[IDX_HEALTH], sim.config.health_max);
+        assert_eq!(sim.agents_data[AGENT_STRIDE + IDX_HEALTH], 250.0);
+    }
+
+    #[test]
+    fn generated_map_places_teams_and_terrain() {
+        let map_gen = mapgen::MapGen { obstacle_clusters: 1, wreck_fields: 1, spawn_ring: 0.5, ..Default::default() };
+        let cfg = Config { map_gen, ..Config::default() };
+        let agents: Vec<(Box<dyn Brain>, u32)> = (0..4).map(|i| (naive_factory(), i % 2)).collect();
+        let mut sim = Simulation::with_brains(200, 200, cfg, agents);
+        sim.generate_map(5);
+        sim.generate_map(6);
+        assert!(sim.config.gravity_wells.is_empty());
+        assert_eq!(sim.wells().count(), 3);
+        assert_eq!(sim.wrecks_data.len(), 8 * WRECK_STRIDE);
+        let pos = |id: usize| Vec2 { x: sim.agents_data[id * AGENT_STRIDE + IDX_X], y: sim.agents_data[id * AGENT_STRIDE + IDX_Y] };
+        // teammates sit on opposite sides of their spawn point, sep_range out
+        let apart = Vec2 { x: pos(2).x - pos(0).x, y: pos(2).y - pos(0).y }.length();
+        assert!((apart - 2.0 * sim.config.sep_range).abs() < 1e-3, "{}", apart);
+        let spawn = |a: Vec2, b: Vec2| Vec2 { x: (a.x + b.x) / 2.0, y: (a.y + b.y) / 2.0 };
+        assert_ne!(spawn(pos(0), pos(2)), spawn(pos(1), pos(3)));
+    }
+
+    #[test]
+    fn mixed_fleet_classes_apply_stats_and_sensors() {
+        let mut sim = Simulation::empty(100, 100);
+        sim.config.class_sensors = true;
+        let tank = sim.spawn_agent_with_class(Vec2 { x: 10.0, y: 10.0 }, 0, Some(ShipClass::tank()), naive_factory());
+        let plain = sim.spawn_agent(Vec2 { x: 90.0, y: 90.0 }, 1, naive_factory());
+        assert_eq!(sim.agents_data[tank * AGENT_STRIDE + IDX_HEALTH], 200.0);
+        assert_eq!(sim.agent_max_speed(tank), ShipClass::tank().speed);
+        assert_eq!(sim.agent_max_speed(plain), sim.config.max_speed);
+        let v = sim.scan(tank, 0, 0.0);
+        assert_eq!(v.len(), sim.config.sensor_len());
+        assert_eq!(v[0], 1.0);
+        assert_eq!(&v[v.len() - 4..], &ShipClass::tank().sensor_features(&sim.config));
+        // removal keeps the class aligned with the remaining agent
+        sim.remove_agent(tank);
+        assert!(sim.agent_class(0).is_none());
+    }
+
+    #[test]
+    fn pushed_command_overrides_brain() {
+        let mut sim = Simulation::empty(100, 100);
+        sim.spawn_agent(Vec2 { x: 10.0, y: 10.0 }, 0, naive_factory());
+        sim.spawn_agent(Vec2 { x: 90.0, y: 90.0 }, 1, naive_factory());
+        sim.push_command(0, Action::Thrust(Vec2 { x: 0.0, y: 1.0 }));
+        sim.push_command(1, Action::Idle);
+        sim.step();
+        assert_eq!(sim.idle_count, 1);
+        assert_eq!(sim.thrust_count, 1);
+        assert!(sim.agents_data[IDX_Y] > 10.0);
+        assert_eq!(sim.agents_data[IDX_X], 10.0);
+    }
+
+    #[test]
+    fn step_n_runs_batch_and_stops_when_decided() {
+        let mut sim = Simulation::new(100, 100, 2, 2, 2, 2);
+        assert_eq!(sim.step_n(10, false), 10);
+        assert_eq!(sim.tick_count(), 10);
+        assert_eq!(sim.step_n_with(10, false, |s| s.tick_count() < 13), 3);
+
+        let mut lone = Simulation::empty(100, 100);
+        lone.spawn_agent(Vec2 { x: 50.0, y: 50.0 }, 0, naive_factory());
+        assert_eq!(lone.step_n(5, true), 0);
+        assert_eq!(lone.step_n(5, false), 5);
+    }
+
+    #[test]
+    fn seeded_constructors_reproduce_spawns() {
+        let a = Simulation::new_seeded(200, 200, 3, 3, 3, 3, 42);
+        let b = Simulation::new_seeded(200, 200, 3, 3, 3, 3, 42);
+        let c = Simulation::new_seeded(200, 200, 3, 3, 3, 3, 43);
+        assert_eq!(a.state_hash(), b.state_hash());
+        assert_ne!(a.state_hash(), c.state_hash());
+        assert_eq!(a.seed(), 42);
+        let t1 = Simulation::new_teams_seeded(200, 200, &[2, 2, 2], 7);
+        let t2 = Simulation::new_teams_seeded(200, 200, &[2, 2, 2], 7);
+        assert_eq!(t1.state_hash(), t2.state_hash());
+    }
+
+    #[test]
+    fn state_hash_covers_side_state() {
+        let mut sim = Simulation::new_seeded(200, 200, 2, 2, 0, 0, 1);
+        let changed = |sim: &mut Simulation, edit: &dyn Fn(&mut Simulation)| {
+            let before = sim.state_hash();
+            edit(sim);
+            sim.state_hash() != before
+        };
+        sim.config.pheromone_cell = 20.0;
+        sim.step();
+        // edits in place, so the lazily grown buffers keep their lengths
+        sim.inflict(1, Status::Weakened, 3);
+        assert!(changed(&mut sim, &|s| s.inflict(1, Status::Weakened, 5)));
+        sim.facing_mut(1);
+        assert!(changed(&mut sim, &|s| s.facing_mut(1).steer(Vec2 { x: 0.0, y: 1.0 })));
+        sim.slot_state_mut(1);
+        let slot = WeaponSlot::new(domain::Weapon::Laser { damage: 1.0, range: 10.0 });
+        assert!(changed(&mut sim, &|s| s.slot_state_mut(1).fire(0, &slot, 0)));
+        sim.set_velocity(1, Vec2 { x: 0.0, y: 0.0 });
+        assert!(changed(&mut sim, &|s| s.set_velocity(1, Vec2 { x: 0.5, y: 0.0 })));
+        assert!(changed(&mut sim, &|s| s.pheromones.as_mut().unwrap().deposit(0, Vec2 { x: 5.0, y: 5.0 }, 1.0)));
+    }
+
+    #[test]
+    fn update_config_clamps_health_and_dumps_json() {
+        let mut sim = Simulation::new(100, 100, 1, 1, 0, 0);
+        sim.update_config(|c| c.health_max = 40.0).unwrap();
+        assert!(sim.agents_data.chunks(AGENT_STRIDE).all(|a| a[IDX_HEALTH] <= 40.0));
+        assert!(sim.update_config(|c| c.loot_fraction = 2.0).is_err());
+        let dumped: Config = serde_json::from_str(&sim.config_json()).unwrap();
+        assert_eq!(dumped.health_max, 40.0);
+    }
+
     /// No shield regen before delay expires
     #[test]
     fn shield_no_regen_before_delay() {
@@ -641,6 +1741,51 @@ mod wasm_bindgen_tests {
         let arr = sim.agents_data();
         assert!(arr.length() > 0, "agents_data should not be empty");
     }
+
+    #[wasm_bindgen_test]
+    fn test_bullets_and_hits_copies_match_lengths() {
+        let mut sim = WasmSimulation::new(64, 64, 2, 2, 2, 2);
+        for _ in 0..50 { sim.step(); }
+        assert_eq!(sim.bullets_data().length() as usize, sim.bullets_len());
+        assert_eq!(sim.hits_data().length() as usize, sim.hits_len());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_seeded_construction_is_reproducible() {
+        let a = WasmSimulation::new_seeded(100, 100, 2, 2, 2, 2, 99);
+        let b = WasmSimulation::new_seeded(100, 100, 2, 2, 2, 2, 99);
+        assert_eq!(a.agents_data().to_vec(), b.agents_data().to_vec());
+        assert_eq!(a.seed(), 99);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_state_object_has_agents() {
+        let sim = WasmSimulation::new(64, 64, 1, 1, 1, 1);
+        let state: wasm_bindgen::JsValue = sim.state().unwrap().into();
+        let agents = js_sys::Reflect::get(&state, &"agents".into()).unwrap();
+        assert_eq!(js_sys::Array::from(&agents).length(), 4);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_js_brain_drives_agent() {
+        let mut sim = WasmSimulation::new(100, 100, 1, 0, 0, 1);
+        let cb = js_sys::Function::new_with_args("inputs, view", "return { type: 'thrust', x: 0, y: 1 };");
+        sim.set_js_brain(0, cb).unwrap();
+        let y0 = sim.agents_data().get_index(1);
+        sim.step();
+        assert!(sim.agents_data().get_index(1) > y0);
+    }
+}
+
+/// Seed for constructors without an explicit one: fixed natively, random in the
+/// browser (read it back with `seed()` to reproduce a run)
+#[cfg(target_arch = "wasm32")]
+fn default_seed() -> u64 {
+    (Math::random() * u32::MAX as f64) as u64
+}
+#[cfg(not(target_arch = "wasm32"))]
+fn default_seed() -> u64 {
+    0
 }
 
 fn nn_factory() -> Box<dyn Brain> { Box::new(NNAgent) }
@@ -651,3 +1796,5 @@ fn naive_factory() -> Box<dyn Brain> { Box::new(NaiveBrain(NaiveAgent::new(1.2,
 mod wasm_bindings;
 #[cfg(target_arch = "wasm32")]
 pub use wasm_bindings::WasmSimulation;
+#[cfg(target_arch = "wasm32")]
+pub mod js_brain;
@@ -11,6 +11,7 @@ pub fn run(sim: &mut Simulation) {
             let px = sim.agents_data[aid * AGENT_STRIDE + IDX_X];
             let py = sim.agents_data[aid * AGENT_STRIDE + IDX_Y];
             let ship = Vec2 { x: px, y: py };
+            let cap = sim.agent_health_max(aid);
             let mut best = None;
             let mut best_d2 = f32::MAX;
             let wd = &mut sim.wrecks_data;
@@ -32,7 +33,7 @@ pub fn run(sim: &mut Simulation) {
                 let actual = gain.min(*pool);
                 *pool -= actual;
                 let hslot = aid * AGENT_STRIDE + IDX_HEALTH;
-                sim.agents_data[hslot] = (sim.agents_data[hslot] + actual).min(sim.config.health_max);
+                sim.agents_data[hslot] = (sim.agents_data[hslot] + actual).min(cap);
                 sim.loot_count += 1;
                 if *pool <= 0.0 {
                     wd.drain(idx0..idx0 + WRECK_STRIDE);
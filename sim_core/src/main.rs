@@ -1,9 +1,27 @@
 use sim_core::config::Config;
-use sim_core::neat::config::{EvolutionConfig, FitnessFn};
+use sim_core::neat::config::{Encoding, EvolutionConfig, FitnessFn, ScenarioAggregate, SelectionStrategy, TieBreak};
+use sim_core::neat::fitness::FitnessExpr;
+use sim_core::neat::hyperneat::phenotype;
+use sim_core::neat::coevolution::CoEvolution;
+use sim_core::neat::curriculum::Curriculum;
+#[cfg(feature = "dashboard")]
+use sim_core::neat::dashboard::{Dashboard, GenerationStats};
+use sim_core::neat::eval::HeadToHead;
+use sim_core::neat::experiment::Experiment;
+use sim_core::neat::sweep::{self, SweepSpec};
+use sim_core::neat::tournament::{self, Format};
+use sim_core::neat::report::Report;
+#[cfg(feature = "db")]
+use sim_core::neat::results_db::ResultsDb;
+use sim_core::replay;
+use sim_core::render::{Canvas, RenderError};
+use sim_core::neat::metrics;
+use sim_core::neat::islands::Archipelago;
 use sim_core::neat::population::Population;
-use sim_core::neat::runner::{PHYS_TIME_NS, PHYS_COUNT, MATCH_TIME_NS, MATCH_COUNT, MatchStats};
+use sim_core::neat::server;
+use sim_core::neat::prometheus;
+use sim_core::neat::runner::MatchStats;
 use sim_core::neat::runner::run_match_record;
-use sim_core::neat::runner::run_match;
 use sim_core::Brain;
 use sim_core::neat::brain::NeatBrain;
 use std::env;
@@ -13,33 +31,66 @@ use num_cpus;
 use rayon::ThreadPoolBuilder;
 use rayon::prelude::*;
 use std::sync::atomic::Ordering;
-use sim_core::neat::brain::{INFER_TIME_NS, INFER_COUNT, HTTP_TIME_NS, REMOTE_INFER_NS};
-use clap::{Parser, Subcommand, Args};
+use sim_core::neat::brain::REMOTE_FALLBACKS;
+use sim_core::neat::telemetry::{Counters, GenerationRecord, LogFormat, LogSubscriber};
+use tracing::{debug, info, info_span, warn, Level};
+use clap::{Parser, Subcommand, Args, CommandFactory, FromArgMatches};
+use clap::parser::ValueSource;
 use clap::ArgAction;
+use clap::builder::PossibleValuesParser;
 use sim_core::neat::genome::Genome;
+use sim_core::neat::bundle::{trained_with_legacy_motion, BundleError, ChampionBundle, Provenance};
+use sim_core::neat::champion::load_genome;
+use sim_core::neat::validate;
+use sim_core::neat::bench;
+use sim_core::human::{self, Controls, HumanBrain};
+use sim_core::scripted;
+#[cfg(feature = "scripting")]
+use sim_core::script::{Program, ScriptBrain};
+use sim_core::replay::ReplayFrame;
+use sim_core::Simulation;
+use sim_core::neat::map_elites::MapElites;
 use sim_core::domain::{WorldView, Vec2};
 use reqwest::blocking::Client;
 use serde_json::json;
-use sim_core::neat::onnx_exporter::export_genome;
+use sim_core::neat::onnx_exporter::export_champion;
 use serde_json;
 use sim_core::ai::{NaiveAgent, NaiveBrain};
-use std::collections::HashMap;
-use indicatif::ParallelProgressIterator;
+use indicatif::ProgressBar;
+#[cfg(feature = "dashboard")]
+use console::Term;
 use std::collections::VecDeque;
 use clap::ValueEnum;
 use chrono::Utc;
 use rand::{rngs::StdRng, Rng, SeedableRng};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::net::SocketAddr;
 use serde::{Serialize, Deserialize};
 
 /// neat_train CLI with `bench`, `train`, and `tournament` subcommands
 #[derive(Parser, Debug)]
 #[clap(author, version, about)]
 struct Cli {
+    /// diagnostics format: human-readable text or JSON lines
+    #[clap(long, value_enum, global = true, default_value_t = LogFormatArg::Text)]
+    log_format: LogFormatArg,
+    /// most verbose diagnostics level: error, warn, info, debug, or trace
+    #[clap(long, global = true, default_value_t = Level::INFO)]
+    log_level: Level,
+    /// write diagnostics to this file instead of stderr
+    #[clap(long, global = true)]
+    log_file: Option<String>,
     #[clap(subcommand)]
     command: Command,
 }
 
+/// Available diagnostics formats
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum LogFormatArg {
+    Text,
+    Json,
+}
+
 #[derive(Subcommand, Debug)]
 enum Command {
     /// Run inference benchmarks (CPU or Python/ONNX service)
@@ -50,11 +101,217 @@ enum Command {
     Tournament(TournamentOpts),
     /// Full 2v2 pipeline: train, tournament, and replay
     Pipeline(PipelineOpts),
+    /// Build a MAP-Elites archive: one champion per behavior cell
+    MapElites(MapElitesOpts),
+    /// Strip dead structure (disabled connections, unreachable hidden nodes) from a champion file
+    Simplify(SimplifyOpts),
+    /// Serve a champion over the Python inference service's HTTP API
+    Serve(ServeOpts),
+    /// Compare two champions head-to-head over mirrored seeded matches
+    Eval(EvalOpts),
+    /// Summarize a recorded match: health curves, kills, and loot
+    Replay(ReplayOpts),
+    /// Draw a replay as PNG frames or an animated GIF
+    Render(RenderOpts),
+    /// Train a grid or random search of hyperparameters and rank the results
+    Sweep(SweepOpts),
+    /// Convert champion files (or a directory of them) to ONNX models
+    Export(ExportOpts),
+    /// Check champion files for structural problems, sensor-size mismatches, and missing metadata
+    Validate(ValidateOpts),
+    /// Fly one ship yourself from the keyboard against a champion
+    Play(PlayOpts),
+}
+
+/// Options for the `play` subcommand
+#[derive(Args, Debug)]
+struct PlayOpts {
+    /// champion file flying the opposing team (and your teammates)
+    champion: String,
+    /// ships per team, you included
+    #[clap(long, default_value_t = 1)]
+    team_size: u32,
+    /// sim config to play under (default: `experiment.toml` beside the champion, else defaults)
+    #[clap(long, value_name = "FILE")]
+    config: Option<String>,
+    /// simulation ticks per second
+    #[clap(long, default_value_t = 10.0)]
+    tps: f32,
+    /// advance one tick per line entered instead of in real time
+    #[clap(long, action=ArgAction::SetTrue)]
+    step: bool,
+    #[clap(long, default_value_t = 2000)]
+    max_ticks: usize,
+    #[clap(long, default_value_t = 0)]
+    seed: u64,
+    /// map size in characters
+    #[clap(long, default_value_t = 80)]
+    cols: usize,
+    #[clap(long, default_value_t = 30)]
+    rows: usize,
+}
+
+/// Options for the `validate` subcommand
+#[derive(Args, Debug)]
+struct ValidateOpts {
+    /// champion file (json, .gz, or .onnx), or a directory whose champion files are all checked
+    path: String,
+    /// sim config the champions should play under: an experiment file (its `[sim]` table)
+    /// or a plain sim config TOML (default: `experiment.toml` beside each champion, else defaults)
+    #[clap(long, value_name = "FILE")]
+    config: Option<String>,
+}
+
+/// Options for the `export` subcommand
+#[derive(Args, Debug)]
+struct ExportOpts {
+    /// champion file, or a directory whose champion files are all exported
+    #[clap(long = "in", value_name = "PATH")]
+    input: String,
+    /// output model, or output directory for a directory input
+    /// (default: next to each champion, with an `.onnx` extension)
+    #[clap(long = "out", value_name = "PATH")]
+    output: Option<String>,
+}
+
+/// Options for the `sweep` subcommand
+#[derive(Args, Debug)]
+struct SweepOpts {
+    /// TOML sweep spec: search mode and budget, base `[sim]`/`[evolution]`, swept `[params]`
+    spec: String,
+    /// write the ranked results to this CSV
+    #[clap(long)]
+    csv: Option<String>,
+}
+
+/// Options for the `render` subcommand
+#[derive(Args, Debug)]
+struct RenderOpts {
+    /// replay file (JSONL, optionally gzipped)
+    file: String,
+    /// output: a `.gif` file, or a directory for numbered PNG frames
+    /// (encode those to MP4 with e.g. `ffmpeg -i frame_%05d.png`)
+    #[clap(long)]
+    out: String,
+    /// world size the replay was recorded in
+    #[clap(long, default_value_t = 1000)]
+    width: u32,
+    #[clap(long, default_value_t = 1000)]
+    height: u32,
+    /// longest image side in pixels
+    #[clap(long, default_value_t = 512)]
+    size: u32,
+    /// draw every n-th frame
+    #[clap(long, default_value_t = 1)]
+    stride: usize,
+    /// GIF frame delay in hundredths of a second
+    #[clap(long, default_value_t = 4)]
+    delay: u16,
+}
+
+/// Options for the `replay` subcommand
+#[derive(Args, Debug)]
+struct ReplayOpts {
+    /// replay file (JSONL, optionally gzipped)
+    file: String,
+    /// ticks between printed health samples (0 = about ten samples)
+    #[clap(long, default_value_t = 0)]
+    every: usize,
+    /// also print every loot event
+    #[clap(long, action=ArgAction::SetTrue)]
+    loot_events: bool,
+    /// write per-tick health, kills, and loot to this CSV
+    #[clap(long)]
+    csv: Option<String>,
+}
+
+/// Options for the `eval` subcommand
+#[derive(Args, Debug)]
+struct EvalOpts {
+    /// first champion file
+    #[clap(long)]
+    a: String,
+    /// second champion file
+    #[clap(long)]
+    b: String,
+    /// seeds to play when `--seeds` is not given (seeds 0..N); each seed is
+    /// played twice with sides swapped
+    #[clap(long, default_value_t = 100)]
+    matches: usize,
+    /// explicit match seeds, comma-separated
+    #[clap(long, value_delimiter = ',')]
+    seeds: Vec<u64>,
+    /// copies of each champion per team
+    #[clap(long, default_value_t = 4)]
+    team_size: usize,
+    #[clap(long, default_value_t = 200)]
+    max_ticks: usize,
+    /// record the games and update both ratings in this sqlite results database
+    #[cfg(feature = "db")]
+    #[clap(long)]
+    db: Option<String>,
+}
+
+/// Options for the `serve` subcommand
+#[derive(Args, Debug)]
+struct ServeOpts {
+    /// champion file (JSON, gzipped JSON, or exported `.onnx`)
+    model: String,
+    #[clap(long, default_value = "127.0.0.1")]
+    host: String,
+    #[clap(long, default_value_t = 8000)]
+    port: u16,
+}
+
+/// Options for the `simplify` subcommand
+#[derive(Args, Debug)]
+struct SimplifyOpts {
+    /// champion JSON file (bare, wrapped, or gzipped)
+    input: String,
+    /// output path (defaults to `<input stem>_simplified.json` next to the input)
+    #[clap(long)]
+    output: Option<String>,
+}
+
+/// Options for the `map-elites` subcommand
+#[derive(Args, Debug)]
+struct MapElitesOpts {
+    /// iterations (offspring batches) to run
+    #[clap(long, default_value_t = 50)]
+    generations: usize,
+    /// offspring evaluated per iteration
+    #[clap(long, default_value_t = 20)]
+    batch: usize,
+    /// bins per behavior axis (aggression, looting, mobility)
+    #[clap(long, default_value_t = 8)]
+    bins: usize,
+    #[clap(long, default_value_t = 2)]
+    num_teams: usize,
+    #[clap(long, default_value_t = 1)]
+    team_size: usize,
+    #[clap(long, default_value_t = 200)]
+    max_ticks: usize,
+    #[clap(long, default_value_t = num_cpus::get())]
+    workers: usize,
+    /// output directory name under out/ (timestamp if omitted)
+    #[clap(long)]
+    run_id: Option<String>,
 }
 
 /// Options for the `bench` subcommand
 #[derive(Args, Debug)]
 struct BenchOpts {
+    /// what to measure: genome inference, or a synthetic physics, match, or scan workload
+    #[clap(long, value_enum, default_value_t = BenchTarget::Inference)]
+    target: BenchTarget,
+    /// agents in the synthetic workloads
+    #[clap(long, default_value_t = 32)]
+    agents: usize,
+    /// ticks per synthetic workload (per match for `match`, which plays `--runs` matches)
+    #[clap(long, default_value_t = 1000)]
+    ticks: usize,
+    #[clap(long, default_value_t = 0)]
+    seed: u64,
     #[clap(long, default_value = "cpu")]
     device: String,
     #[clap(long, default_value_t = 10)]
@@ -71,14 +328,21 @@ struct BenchOpts {
     duration: Option<u64>,
     #[clap(long, action=ArgAction::SetTrue)]
     bench_verbose: bool,
-    /// Export initial genome ONNX to path and exit
-    #[clap(long)]
-    export_model: Option<String>,
 }
 
 /// Options for the `train` subcommand
-#[derive(Args, Debug)]
+#[derive(Args, Debug, Serialize)]
+#[clap(args_override_self = true)]
 struct TrainOpts {
+    /// TOML experiment file (`[train]` options, `[sim]` and `[evolution]` settings);
+    /// flags given on the command line override it
+    #[clap(long, value_name = "FILE")]
+    #[serde(skip)]
+    config: Option<String>,
+    /// flags set explicitly (on the command line or in the experiment's `[train]` table)
+    #[clap(skip)]
+    #[serde(skip)]
+    given: Vec<String>,
     #[clap(long, default_value = "cpu")]
     device: String,
     #[clap(long, default_value_t = num_cpus::get().saturating_sub(1))]
@@ -92,7 +356,10 @@ struct TrainOpts {
     /// snapshot every N generations
     #[clap(long, default_value_t = 5)]
     snapshot_interval: usize,
-    /// number of generations between difficulty increases
+    /// JSON curriculum (stages with overrides and promotion criteria) replacing the difficulty ramp
+    #[clap(long)]
+    curriculum: Option<String>,
+    /// minimum generations between difficulty increases
     #[clap(long, default_value_t = 10)]
     difficulty_interval: usize,
     /// threshold of avg_naive to bump difficulty
@@ -101,6 +368,15 @@ struct TrainOpts {
     /// verbose per-match logs during training
     #[clap(long = "train-verbose", action=ArgAction::SetTrue, default_value_t = false)]
     verbose: bool,
+    /// serve Prometheus metrics on `http://ADDR/metrics` while training
+    #[clap(long, value_name = "ADDR")]
+    #[serde(skip)]
+    metrics_addr: Option<SocketAddr>,
+    /// live full-screen dashboard (fitness curves, species, timing, champion)
+    /// instead of the per-generation log
+    #[cfg(feature = "dashboard")]
+    #[clap(long, action=ArgAction::SetTrue)]
+    dashboard: bool,
     /// generations without improvement before triggering recovery
     #[clap(long, default_value_t = 20)]
     stagnation_window: usize,
@@ -110,42 +386,196 @@ struct TrainOpts {
     /// scale factor to multiply mutation rates during recovery
     #[clap(long, default_value_t = 2.0)]
     mutation_scale: f32,
+    /// allow recurrent connections (agents keep per-node memory across ticks)
+    #[clap(long, action=ArgAction::SetTrue, default_value_t = false)]
+    #[serde(skip)]
+    recurrent: bool,
+    /// generations a species may go without improving before it is culled
+    #[clap(long, default_value_t = 15)]
+    #[serde(skip)]
+    species_stagnation: usize,
+    /// co-evolve separate red and blue populations, each scored only against the other
+    #[clap(long)]
+    coevolve: bool,
+    /// rival opponents each genome meets per generation with --coevolve
+    #[clap(long, default_value_t = 5)]
+    #[serde(skip)]
+    coevo_opponents: usize,
+    /// evolve this many sub-populations in parallel, migrating top genomes between them
+    #[clap(long, default_value_t = 1)]
+    #[serde(skip)]
+    islands: usize,
+    /// generations between island migrations
+    #[clap(long, default_value_t = 10)]
+    #[serde(skip)]
+    migration_interval: usize,
+    /// genomes each island sends to the next one per migration
+    #[clap(long, default_value_t = 2)]
+    #[serde(skip)]
+    migrants: usize,
+    /// fraction of evaluation matches played against the hall-of-fame
+    #[clap(long, default_value_t = 0.1, value_parser = probability)]
+    #[serde(skip)]
+    hof_match_rate: f32,
+    /// play every evaluation match from both sides and score each pair together
+    #[clap(long, action=ArgAction::SetTrue, default_value_t = false)]
+    #[serde(skip)]
+    mirror_matches: bool,
+    /// end matches once one side's health is below this fraction of the other's (0 = never)
+    #[clap(long, default_value_t = 0.0)]
+    #[serde(skip)]
+    mercy_ratio: f32,
+    /// end matches after this many ticks without damage (0 = never)
+    #[clap(long, default_value_t = 0)]
+    #[serde(skip)]
+    stall_ticks: usize,
+    /// How matches still undecided at max-ticks are settled
+    #[clap(long, value_enum, default_value_t = TieBreakArg::Health)]
+    #[serde(skip)]
+    tie_break: TieBreakArg,
+    /// Tie-break leads at or below this count as a draw
+    #[clap(long, default_value_t = 1e-3)]
+    #[serde(skip)]
+    draw_margin: f32,
+    /// How fitness over the `[[evolution.scenarios]]` suite is combined
+    #[clap(long, value_enum, default_value_t = ScenarioAggregateArg::Mean)]
+    #[serde(skip)]
+    scenario_aggregate: ScenarioAggregateArg,
+    /// How parents are ranked: scalar fitness or NSGA-II Pareto fronts
+    #[clap(long, value_enum, default_value_t = SelectionArg::Tournament)]
+    #[serde(skip)]
+    selection: SelectionArg,
+    /// Genome encoding: direct networks or HyperNEAT CPPNs over a sensor substrate
+    #[clap(long, value_enum, default_value_t = EncodingArg::Direct)]
+    #[serde(skip)]
+    encoding: EncodingArg,
+    /// hidden nodes in the HyperNEAT substrate
+    #[clap(long, default_value_t = 8)]
+    #[serde(skip)]
+    substrate_hidden: usize,
+    /// scripted opponent the per-genome baseline fitness is measured against
+    #[clap(long, default_value = "naive", value_parser = PossibleValuesParser::new(scripted::NAMES))]
+    #[serde(skip)]
+    baseline: String,
     /// Which fitness function to use
     #[clap(long, value_enum, default_value_t = FitnessFnArg::HealthPlusDamage)]
+    #[serde(skip)]
     fitness_fn: FitnessFnArg,
+    /// Fitness expression over match stats, e.g. "health + 2 * damage - 0.1 * longest_idle"; overrides --fitness-fn
+    #[clap(long, value_name = "EXPR", value_parser = FitnessExpr::parse)]
+    #[serde(skip)]
+    fitness_expr: Option<FitnessExpr>,
     /// Weight for time-to-win bonus (only for time-based fitness)
     #[clap(long, default_value_t = 0.1)]
+    #[serde(skip)]
     time_bonus_weight: f32,
     /// Weight for health in fitness
     #[clap(long, default_value_t = 1.0)]
+    #[serde(skip)]
     w_health: f32,
     /// Weight for damage in fitness
     #[clap(long, default_value_t = 1.0)]
+    #[serde(skip)]
     w_damage: f32,
     /// Weight for kills in fitness
     #[clap(long, default_value_t = 0.5)]
+    #[serde(skip)]
     w_kills: f32,
     /// Weight for salvage actions in fitness
     #[clap(long, default_value_t = 0.0)]
+    #[serde(skip)]
     w_salvage: f32,
     /// Weight for exploration (thrust) actions in fitness
     #[clap(long, default_value_t = 0.0)]
+    #[serde(skip)]
     w_explore: f32,
+    /// Weight for hit rate (hits per shot) in fitness
+    #[clap(long, default_value_t = 0.0)]
+    #[serde(skip)]
+    w_accuracy: f32,
+    /// Weight for damage dealt per shot in fitness
+    #[clap(long, default_value_t = 0.0)]
+    #[serde(skip)]
+    w_damage_per_shot: f32,
+    /// Weight for the fraction of trigger pulls that went off (not wasted on cooldowns or empty slots)
+    #[clap(long, default_value_t = 0.0)]
+    #[serde(skip)]
+    w_fire_efficiency: f32,
+    /// Penalty per point of damage dealt to teammates
+    #[clap(long, default_value_t = 0.0)]
+    #[serde(skip)]
+    w_friendly_fire: f32,
+    /// Penalty per tick of the longest run an agent stood still
+    #[clap(long, default_value_t = 0.0)]
+    #[serde(skip)]
+    w_idle: f32,
     /// Optional override for run ID
     #[clap(long)]
+    #[serde(skip)]
     run_id: Option<String>,
-    /// Random seed for scenario randomization
+    /// continue from a population checkpoint (`out/<run>/population.json`, written every generation)
+    #[clap(long, value_name = "CHECKPOINT")]
+    #[serde(skip)]
+    resume: Option<String>,
+    /// Random seed for scenario randomization and the whole evolutionary run (reproducible training)
     #[clap(long)]
+    #[serde(skip)]
     random_seed: Option<u64>,
     /// Max variation for map dimensions (±)
     #[clap(long, default_value_t = 0)]
     map_var: u32,
     /// number of agents per team
     #[clap(long, default_value_t = 2)]
+    #[serde(skip)]
     team_size: usize,
     /// number of teams in each match
     #[clap(long, default_value_t = 2)]
+    #[serde(skip)]
     num_teams: usize,
+    /// genomes per generation
+    #[clap(long, default_value_t = 10)]
+    #[serde(skip)]
+    pop_size: usize,
+    /// opponents sampled per genome per generation
+    #[clap(long, default_value_t = 2)]
+    #[serde(skip)]
+    tournament_k: usize,
+    /// tick limit per match
+    #[clap(long, default_value_t = 200)]
+    #[serde(skip)]
+    max_ticks: usize,
+    /// map width (before --map-var randomization)
+    #[clap(long, default_value_t = 1000)]
+    #[serde(skip)]
+    map_width: u32,
+    /// map height (before --map-var randomization)
+    #[clap(long, default_value_t = 1000)]
+    #[serde(skip)]
+    map_height: u32,
+    /// hall-of-fame capacity
+    #[clap(long, default_value_t = 5)]
+    #[serde(skip)]
+    hof_size: usize,
+    /// probability of an add-node mutation per offspring
+    #[clap(long, default_value_t = 0.3, value_parser = probability)]
+    #[serde(skip)]
+    add_node_rate: f32,
+    /// probability of an add-connection mutation per offspring
+    #[clap(long, default_value_t = 0.5, value_parser = probability)]
+    #[serde(skip)]
+    add_conn_rate: f32,
+    /// per-connection probability of mutating its weight
+    #[clap(long, default_value_t = 0.8, value_parser = probability)]
+    #[serde(skip)]
+    weight_rate: f32,
+    /// std-dev of the gaussian weight perturbation
+    #[clap(long, default_value_t = 0.1)]
+    #[serde(skip)]
+    weight_sigma: f32,
+    /// probability an offspring is bred by crossover rather than cloning
+    #[clap(long, default_value_t = 0.75, value_parser = probability)]
+    #[serde(skip)]
+    crossover_rate: f32,
 }
 
 /// Options for the `tournament` subcommand
@@ -163,6 +593,41 @@ struct TournamentOpts {
     /// include naive agent in tournament for Elo ranking
     #[clap(long = "tournament-include-naive", action=ArgAction::SetTrue, default_value_t = false)]
     include_naive: bool,
+    /// scripted opponents to enter alongside the champions, comma-separated
+    #[clap(long, value_delimiter = ',', value_parser = PossibleValuesParser::new(scripted::NAMES))]
+    scripted: Vec<String>,
+    /// script opponent files (see `sim_core::script`), comma-separated
+    #[cfg(feature = "scripting")]
+    #[clap(long, value_delimiter = ',')]
+    script: Vec<String>,
+    /// pairing format: round-robin, Swiss, or single/double elimination
+    #[clap(long, value_enum, default_value_t = FormatArg::Rr)]
+    format: FormatArg,
+    /// Swiss rounds (default: log2 of the field, rounded up)
+    #[clap(long)]
+    rounds: Option<usize>,
+    /// seeds per pairing, each played twice with the teams swapped
+    #[clap(long, default_value_t = 1)]
+    games: usize,
+    /// play under the legacy motion model, entering only champions trained
+    /// under it (plain JSON champions from before bundles)
+    #[clap(long, action=ArgAction::SetTrue)]
+    legacy_motion: bool,
+    /// record every game in this sqlite results database and rate the
+    /// entrants from their stored ratings
+    #[cfg(feature = "db")]
+    #[clap(long)]
+    db: Option<String>,
+}
+
+/// Available tournament formats
+#[derive(ValueEnum, Clone, Copy, Debug)]
+#[clap(rename_all = "kebab-case")]
+enum FormatArg {
+    Rr,
+    Swiss,
+    Bracket,
+    DoubleBracket,
 }
 
 /// Options for the `pipeline` subcommand (train → tournament → replay)
@@ -175,23 +640,67 @@ struct PipelineOpts {
 }
 
 /// Available fitness function types
-#[clap(rename_all = "kebab-case")]
 #[derive(ValueEnum, Clone, Debug)]
+#[clap(rename_all = "kebab-case")]
 enum FitnessFnArg {
     HealthPlusDamage,
     HealthPlusDamageTime,
     HealthDamageSalvage,
     HealthDamageExplore,
     HealthDamageTimeSalvageExplore,
+    Novelty,
+    Hybrid,
+}
+
+/// `bench` targets
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+#[clap(rename_all = "kebab-case")]
+enum BenchTarget {
+    Inference,
+    Physics,
+    Match,
+    Scan,
+}
+
+/// Available selection strategies
+#[derive(ValueEnum, Clone, Debug)]
+#[clap(rename_all = "kebab-case")]
+enum SelectionArg {
+    Tournament,
+    Nsga2,
+}
+
+/// Available tie-break rules
+#[derive(ValueEnum, Clone, Copy, Debug)]
+#[clap(rename_all = "kebab-case")]
+enum TieBreakArg {
+    Health,
+    Damage,
+    Survivors,
+    None,
+}
+
+/// Ways to combine per-scenario fitness
+#[derive(ValueEnum, Clone, Copy, Debug)]
+#[clap(rename_all = "kebab-case")]
+enum ScenarioAggregateArg {
+    Mean,
+    Worst,
+}
+
+/// Available genome encodings
+#[derive(ValueEnum, Clone, Debug)]
+#[clap(rename_all = "kebab-case")]
+enum EncodingArg {
+    Direct,
+    HyperNeat,
 }
 
 /// Run CPU or MPS inference bench and exit
 fn bench_inference(sim_cfg: &Config, evo_cfg: &EvolutionConfig, runs: usize, batch: bool, verbose: bool) {
     let mut genome = Genome::new();
-    genome.initialize(sim_cfg, evo_cfg);
-    let input_len = 2 + sim_cfg.nearest_k_enemies * 4
-                  + sim_cfg.nearest_k_allies * 4
-                  + sim_cfg.nearest_k_wrecks * 3;
+    genome.initialize(sim_cfg, evo_cfg, &mut rand::thread_rng());
+    let input_len = sim_cfg.sensor_len();
     let input_row = vec![0.0f32; input_len];
     let mut total_ns: u128 = 0;
     if sim_cfg.use_python_service {
@@ -241,33 +750,538 @@ fn bench_inference(sim_cfg: &Config, evo_cfg: &EvolutionConfig, runs: usize, bat
     }
 }
 
+#[global_allocator]
+static ALLOC: bench::CountingAlloc = bench::CountingAlloc;
+
 fn main() {
-    let cli = Cli::parse();
+    let mut args: Vec<String> = env::args().collect();
+    // an experiment's `[train]` options go ahead of the command line's, which override them
+    if let Some(path) = experiment_path(&Cli::parse_from(&args).command) {
+        let exp = Experiment::from_toml_file(&path)
+            .unwrap_or_else(|e| panic!("Failed to load {}: {}", path, e));
+        // right after the subcommand, in case global flags come first
+        let at = args.iter().position(|a| a == "train" || a == "pipeline").map_or(2, |i| i + 1);
+        args.splice(at..at, experiment_args(&exp.train));
+    }
+    let matches = Cli::command().get_matches_from(&args);
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    if let (Command::Train(opts) | Command::Pipeline(PipelineOpts { train: opts, .. }), Some((_, sub)))
+        = (&mut cli.command, matches.subcommand())
+    {
+        opts.given = sub.ids()
+            .filter(|id| sub.value_source(id.as_str()) == Some(ValueSource::CommandLine))
+            .map(|id| id.to_string())
+            .collect();
+    }
+    init_logging(&cli);
     match cli.command {
         Command::Bench(opts) => run_bench(&opts),
         Command::Train(opts) => { let _ = run_train(&opts); },
         Command::Tournament(opts) => run_tournament(&opts),
         Command::Pipeline(opts) => run_pipeline(&opts),
+        Command::MapElites(opts) => run_map_elites(&opts),
+        Command::Simplify(opts) => run_simplify(&opts),
+        Command::Serve(opts) => run_serve(&opts),
+        Command::Eval(opts) => run_eval(&opts),
+        Command::Replay(opts) => run_replay(&opts),
+        Command::Render(opts) => run_render(&opts),
+        Command::Sweep(opts) => run_sweep(&opts),
+        Command::Export(opts) => run_export(&opts),
+        Command::Validate(opts) => run_validate(&opts),
+        Command::Play(opts) => run_play(&opts),
+    }
+}
+
+/// Install the diagnostics subscriber the global `--log-*` flags describe
+fn init_logging(cli: &Cli) {
+    let out: Box<dyn std::io::Write + Send> = match &cli.log_file {
+        Some(path) => Box::new(fs::File::create(path).unwrap_or_else(|e| panic!("Failed to create {}: {}", path, e))),
+        None => Box::new(std::io::stderr()),
+    };
+    let format = match cli.log_format {
+        LogFormatArg::Text => LogFormat::Text,
+        LogFormatArg::Json => LogFormat::Json,
+    };
+    // only problems may interrupt the dashboard on the terminal
+    #[cfg(feature = "dashboard")]
+    let dashboard = matches!(&cli.command, Command::Train(opts) | Command::Pipeline(PipelineOpts { train: opts, .. }) if opts.dashboard);
+    #[cfg(not(feature = "dashboard"))]
+    let dashboard = false;
+    let level = if dashboard && cli.log_file.is_none() { cli.log_level.min(Level::WARN) } else { cli.log_level };
+    LogSubscriber::new(format, level, out).init().expect("Failed to install log subscriber");
+}
+
+/// Experiment file named by `--config`, for the commands that train
+fn experiment_path(command: &Command) -> Option<String> {
+    match command {
+        Command::Train(opts) | Command::Pipeline(PipelineOpts { train: opts, .. }) => opts.config.clone(),
+        _ => None,
+    }
+}
+
+/// Flags equivalent to an experiment's `[train]` table
+fn experiment_args(train: &toml::Table) -> Vec<String> {
+    let cli = Cli::command();
+    let train_cmd = cli.find_subcommand("train").unwrap();
+    let mut args = Vec::new();
+    for (key, value) in train {
+        // keyed by field name or flag name, with either separator
+        let long = key.replace('_', "-");
+        let flag = train_cmd.get_arguments()
+            .filter_map(|a| Some((a.get_id().as_str().replace('_', "-"), a.get_long()?)))
+            .find(|(id, l)| *id == long || *l == long)
+            .map(|(_, l)| format!("--{}", l))
+            .unwrap_or_else(|| panic!("Unknown [train] option `{}`", key));
+        match value {
+            toml::Value::Boolean(true) => args.push(flag),
+            toml::Value::Boolean(false) => {}
+            toml::Value::String(s) => args.push(format!("{}={}", flag, s)),
+            v => args.push(format!("{}={}", flag, v)),
+        }
+    }
+    args
+}
+
+/// Train every configuration of a sweep spec and print them ranked
+fn run_sweep(opts: &SweepOpts) {
+    let spec = SweepSpec::from_toml_file(&opts.spec)
+        .unwrap_or_else(|e| panic!("Failed to load {}: {}", opts.spec, e));
+    let count = spec.configurations().unwrap_or_else(|e| panic!("{}", e)).len();
+    println!("Sweeping {} configurations, {} generations each", count, spec.generations);
+    let start = Instant::now();
+    let trials = spec.run().unwrap_or_else(|e| panic!("{}", e));
+    println!("Done in {:.1}s\n", start.elapsed().as_secs_f32());
+    println!("{:>4}  {:>9}  {:>9}  params", "rank", "score", "fitness");
+    for (i, t) in trials.iter().enumerate() {
+        let params: Vec<String> = t.params.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+        println!("{:>4}  {:>9.2}  {:>9.2}  {}", i + 1, t.score, t.best_fitness, params.join(" "));
+    }
+    if let Some(path) = &opts.csv {
+        let file = fs::File::create(path).unwrap_or_else(|e| panic!("Failed to create {}: {}", path, e));
+        sweep::write_csv(std::io::BufWriter::new(file), &trials).expect("write sweep CSV");
+        println!("Wrote {}", path);
+    }
+}
+
+/// Render a replay to a GIF or a directory of PNG frames
+fn run_render(opts: &RenderOpts) {
+    let frames = replay::load(&opts.file)
+        .unwrap_or_else(|e| panic!("Failed to load {}: {}", opts.file, e));
+    let frames: Vec<_> = frames.into_iter().step_by(opts.stride.max(1)).collect();
+    let canvas = Canvas::fit(opts.width, opts.height, opts.size);
+    let result = if opts.out.ends_with(".gif") {
+        fs::File::create(&opts.out)
+            .map_err(RenderError::from)
+            .and_then(|f| canvas.write_gif(&frames, std::io::BufWriter::new(f), opts.delay))
+    } else {
+        canvas.write_pngs(&frames, &opts.out).map(|_| ())
+    };
+    result.unwrap_or_else(|e| panic!("Failed to render {}: {}", opts.out, e));
+    let (w, h) = canvas.size();
+    println!("Rendered {} frames at {}x{} to {}", frames.len(), w, h, opts.out);
+}
+
+/// Print what happened in a replay file
+fn run_replay(opts: &ReplayOpts) {
+    let frames = replay::load(&opts.file)
+        .unwrap_or_else(|e| panic!("Failed to load {}: {}", opts.file, e));
+    if frames.is_empty() {
+        println!("{}: empty replay", opts.file);
+        return;
+    }
+    let summary = replay::analyze(&frames);
+    let teams: Vec<u32> = summary.health.keys().copied().collect();
+    println!("{}: {} frames, ticks {}..={}", opts.file, frames.len(), summary.ticks[0], summary.ticks[summary.ticks.len() - 1]);
+    println!("\nHealth by team:");
+    println!("{:>6} {}", "tick", teams.iter().map(|t| format!("{:>9}", format!("team {}", t))).collect::<String>());
+    let every = if opts.every > 0 { opts.every } else { frames.len().div_ceil(10) };
+    let last = frames.len() - 1;
+    for i in (0..frames.len()).filter(|&i| i % every == 0 || i == last) {
+        let row: String = summary.health.values().map(|h| format!("{:>9.1}", h[i])).collect();
+        println!("{:>6} {}", summary.ticks[i], row);
+    }
+    println!("\nKills:");
+    if summary.kills.is_empty() {
+        println!("  none");
+    }
+    for k in &summary.kills {
+        println!("  tick {:>5}: agent {} (team {}) destroyed", k.tick, k.agent, k.team);
+    }
+    println!("\nLoot:");
+    for &t in &teams {
+        let events: Vec<_> = summary.loot.iter().filter(|l| l.team == t).collect();
+        let total = events.iter().fold(0.0, |acc, l| acc + l.amount);
+        println!("  team {}: {} events, {:.1} health recovered", t, events.len(), total);
+    }
+    if opts.loot_events {
+        for l in &summary.loot {
+            println!("  tick {:>5}: agent {} (team {}) +{:.2}", l.tick, l.agent, l.team, l.amount);
+        }
+    }
+    println!("\nFinal:");
+    let survivors = summary.survivors(&frames);
+    for &t in &teams {
+        println!("  team {}: {} alive, {:.1} health", t, survivors[&t], summary.health[&t][last]);
+    }
+    if let Some(path) = &opts.csv {
+        let file = fs::File::create(path).unwrap_or_else(|e| panic!("Failed to create {}: {}", path, e));
+        summary.write_csv(std::io::BufWriter::new(file)).expect("write replay CSV");
+        println!("Wrote {}", path);
+    }
+}
+
+/// Play two champions against each other and report who is stronger
+fn run_eval(opts: &EvalOpts) {
+    let load = |path: &str| load_genome(path).unwrap_or_else(|e| panic!("Failed to load {}: {}", path, e));
+    let (a, b) = (load(&opts.a), load(&opts.b));
+    let sim_cfg = Config { use_python_service: false, python_service_url: None, ..Default::default() };
+    let evo_cfg = EvolutionConfig { num_teams: 2, team_size: opts.team_size, max_ticks: opts.max_ticks, ..Default::default() };
+    let seeds: Vec<u64> = if opts.seeds.is_empty() { (0..opts.matches as u64).collect() } else { opts.seeds.clone() };
+    let h2h = HeadToHead::play(&sim_cfg, &evo_cfg, &a, &b, &seeds);
+    let (wins, draws, losses) = h2h.record();
+    let (dmg_a, dmg_b) = h2h.mean_damage();
+    let p = h2h.p_value();
+    println!("A: {}\nB: {}", opts.a, opts.b);
+    println!("{} games ({} seeds × 2 sides), {}v{}, {} ticks", h2h.games.len(), seeds.len(), opts.team_size, opts.team_size, opts.max_ticks);
+    println!("A wins {} / draws {} / losses {}", wins, draws, losses);
+    println!("Mean damage per game: A {:.1}, B {:.1}", dmg_a, dmg_b);
+    let verdict = if p >= 0.05 {
+        "no significant difference"
+    } else if wins > losses {
+        "A is stronger"
+    } else {
+        "B is stronger"
+    };
+    println!("Sign test p = {:.4} ({})", p, verdict);
+    #[cfg(feature = "db")]
+    if let Some(path) = &opts.db {
+        let record = ResultsDb::open(path).and_then(|mut db| {
+            db.record("eval", &sim_cfg, &evo_cfg, 32.0, [(opts.a.as_str(), opts.b.as_str(), &h2h)])?;
+            Ok((db.rating(&opts.a)?, db.rating(&opts.b)?))
+        });
+        match record {
+            Ok((Some(a), Some(b))) => println!("Stored ratings: A {:.1} ({} games), B {:.1} ({} games)", a.elo, a.games, b.elo, b.games),
+            Ok(_) => {}
+            Err(e) => eprintln!("Failed to record results in {}: {}", path, e),
+        }
+    }
+}
+
+/// Serve a champion on `/infer` and `/infer_batch` until interrupted
+fn run_serve(opts: &ServeOpts) {
+    let genome = load_genome(&opts.model)
+        .unwrap_or_else(|e| panic!("Failed to load {}: {}", opts.model, e));
+    let addr: SocketAddr = format!("{}:{}", opts.host, opts.port).parse()
+        .unwrap_or_else(|e| panic!("Invalid address {}:{}: {}", opts.host, opts.port, e));
+    println!("Serving {} ({} inputs → {} outputs) on http://{}", opts.model, genome.input_size(), genome.output_size(), addr);
+    tokio::runtime::Runtime::new().expect("start runtime")
+        .block_on(server::serve(genome, addr))
+        .unwrap_or_else(|e| panic!("Server on {} failed: {}", addr, e));
+}
+
+/// Prune a champion and write it as a bare genome JSON
+fn run_simplify(opts: &SimplifyOpts) {
+    let mut genome = load_genome(&opts.input)
+        .unwrap_or_else(|e| panic!("Failed to load {}: {}", opts.input, e));
+    // a bundle stays a bundle, so the simplified champion keeps its config
+    let bundle = fs::read(&opts.input).ok().and_then(|bytes| ChampionBundle::from_bytes(&bytes).ok().flatten());
+    let (nodes, conns) = (genome.nodes.len(), genome.conns.len());
+    genome.prune();
+    let output = opts.output.clone().unwrap_or_else(|| {
+        let path = Path::new(&opts.input);
+        let stem = path.file_name().and_then(|s| s.to_str()).unwrap_or("champion");
+        let stem = stem.trim_end_matches(".gz").trim_end_matches(".json");
+        path.with_file_name(format!("{}_simplified.json", stem)).to_string_lossy().into_owned()
+    });
+    let json = match bundle {
+        Some(bundle) => serde_json::to_string(&ChampionBundle { genome: genome.clone(), ..bundle }).unwrap(),
+        None => serde_json::to_string(&genome).unwrap(),
+    };
+    fs::write(&output, json).expect("write simplified champion");
+    println!("{}: nodes {} → {}, conns {} → {}; wrote {}",
+             opts.input, nodes, genome.nodes.len(), conns, genome.conns.len(), output);
+}
+
+/// Write each champion as a self-describing ONNX model
+fn run_export(opts: &ExportOpts) {
+    let input = Path::new(&opts.input);
+    let jobs: Vec<(PathBuf, PathBuf)> = if input.is_dir() {
+        let mut champs: Vec<PathBuf> = fs::read_dir(input)
+            .unwrap_or_else(|e| panic!("Failed to read {}: {}", opts.input, e))
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|p| matches!(p.extension().and_then(|e| e.to_str()), Some("json") | Some("gz")))
+            .collect();
+        champs.sort();
+        let out_dir = opts.output.as_ref().map_or(input, Path::new);
+        fs::create_dir_all(out_dir).expect("create export directory");
+        champs.into_iter().map(|p| {
+            let out = out_dir.join(format!("{}.onnx", champion_stem(&p)));
+            (p, out)
+        }).collect()
+    } else {
+        let out = opts.output.as_ref().map_or_else(
+            || input.with_file_name(format!("{}.onnx", champion_stem(input))),
+            PathBuf::from,
+        );
+        vec![(input.to_path_buf(), out)]
+    };
+    let mut exported = 0;
+    for (path, out) in &jobs {
+        // sensor layout metadata follows the run's own sim config when its experiment file is alongside
+        let sim_cfg = run_sim_config(path);
+        let generation = champion_stem(path).strip_prefix("champion_gen_").and_then(|g| g.parse().ok()).unwrap_or(0);
+        let bytes = load_genome(path)
+            .map_err(|e| e.to_string())
+            .and_then(|g| export_champion(&g, generation, &sim_cfg).map_err(|e| e.to_string()));
+        match bytes {
+            Ok(bytes) => {
+                fs::write(out, bytes).unwrap_or_else(|e| panic!("Failed to write {}: {}", out.display(), e));
+                println!("{} → {}", path.display(), out.display());
+                exported += 1;
+            }
+            Err(e) => eprintln!("Skipping {}: {}", path.display(), e),
+        }
+    }
+    println!("Exported {} of {} champions", exported, jobs.len());
+}
+
+/// Check every champion under `opts.path`; exits non-zero if any cannot be played
+fn run_validate(opts: &ValidateOpts) {
+    let input = Path::new(&opts.path);
+    let files: Vec<PathBuf> = if input.is_dir() {
+        let mut champs: Vec<PathBuf> = fs::read_dir(input)
+            .unwrap_or_else(|e| panic!("Failed to read {}: {}", opts.path, e))
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|p| matches!(p.extension().and_then(|e| e.to_str()), Some("json") | Some("gz") | Some("onnx")))
+            .filter(|p| p.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with("champion")))
+            .collect();
+        champs.sort();
+        champs
+    } else {
+        vec![input.to_path_buf()]
+    };
+    let given = opts.config.as_ref().map(|path| {
+        sim_config_file(Path::new(path)).unwrap_or_else(|e| {
+            eprintln!("Failed to read {}: {}", path, e);
+            std::process::exit(2);
+        })
+    });
+    let mut failed = 0;
+    for path in &files {
+        let sim_cfg = given.clone().unwrap_or_else(|| run_sim_config(path));
+        let findings = match fs::read(path) {
+            Ok(bytes) => validate::check_bytes(&bytes, &sim_cfg),
+            Err(e) => {
+                eprintln!("{}: error: {}; check the path", path.display(), e);
+                failed += 1;
+                continue;
+            }
+        };
+        if findings.is_empty() {
+            println!("{}: ok", path.display());
+        }
+        for f in &findings {
+            println!("{}: {}", path.display(), f);
+        }
+        if validate::has_errors(&findings) {
+            failed += 1;
+        }
+    }
+    println!("{} of {} champions valid", files.len() - failed, files.len());
+    if failed > 0 || files.is_empty() {
+        std::process::exit(1);
+    }
+}
+
+/// Play a match with agent 0 steered by lines of keys on stdin (see `human::Command::parse`)
+fn run_play(opts: &PlayOpts) {
+    let genome = load_genome(&opts.champion).unwrap_or_else(|e| panic!("Failed to load {}: {}", opts.champion, e));
+    let sim_cfg = match &opts.config {
+        Some(path) => sim_config_file(Path::new(path)).unwrap_or_else(|e| panic!("Failed to read {}: {}", path, e)),
+        None => run_sim_config(Path::new(&opts.champion)),
+    };
+    // the champion flies under the motion model it was trained with
+    let legacy = sim_cfg.legacy_motion || fs::read(&opts.champion).is_ok_and(|b| trained_with_legacy_motion(&b));
+    let sim_cfg = Config { use_python_service: false, python_service_url: None, batch_size: 1, legacy_motion: legacy, ..sim_cfg };
+    if let Some(f) = validate::check_genome(&genome, &sim_cfg).into_iter().find(|f| f.severity == validate::Severity::Error) {
+        eprintln!("{}: {}", opts.champion, f);
+        std::process::exit(1);
+    }
+    let evo_cfg = EvolutionConfig::default();
+    let controls = Controls::default();
+    let mut agents: Vec<(Box<dyn Brain>, u32)> = vec![(Box::new(HumanBrain::new(controls.clone())), 0)];
+    for team in [0, 1] {
+        let ships = if team == 0 { opts.team_size.saturating_sub(1) } else { opts.team_size };
+        for _ in 0..ships {
+            agents.push((Box::new(NeatBrain::for_config(genome.clone(), &sim_cfg)), team));
+        }
+    }
+    let mut sim = Simulation::with_brains(evo_cfg.map_width, evo_cfg.map_height, sim_cfg, agents);
+    sim.reseed(opts.seed);
+
+    // stdin lines arrive on a channel; None means quit
+    let (tx, rx) = std::sync::mpsc::channel::<Option<human::Command>>();
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lines() {
+            let Ok(line) = line else { break };
+            let cmd = match line.trim() {
+                "q" | "quit" => None,
+                keys => match human::Command::parse(keys) {
+                    Some(cmd) => Some(cmd),
+                    None => continue,
+                },
+            };
+            if tx.send(cmd).is_err() || cmd.is_none() {
+                return;
+            }
+        }
+        let _ = tx.send(None);
+    });
+
+    let tick_time = Duration::from_secs_f32(1.0 / opts.tps.max(0.1));
+    let help = "keys + Enter: w/a/s/d move (combine for diagonals), f fire, l loot, empty line stop, q quit";
+    let mut frame = ReplayFrame::capture(&sim);
+    let mut quit = false;
+    while !quit && frame.tick < opts.max_ticks {
+        // clear the screen and home the cursor before each frame
+        print!("\x1b[2J\x1b[H{}", human::ascii_frame(&frame, evo_cfg.map_width, evo_cfg.map_height, opts.cols, opts.rows, 0));
+        println!("{}", help);
+        let standings = human::standings(&frame);
+        if standings.values().filter(|(alive, _)| *alive > 0).count() < 2 {
+            break;
+        }
+        let started = Instant::now();
+        if opts.step {
+            match rx.recv() {
+                Ok(Some(cmd)) => controls.set(cmd),
+                _ => quit = true,
+            }
+        } else {
+            for cmd in rx.try_iter() {
+                match cmd {
+                    Some(cmd) => controls.set(cmd),
+                    None => quit = true,
+                }
+            }
+        }
+        sim.step();
+        frame = ReplayFrame::capture(&sim);
+        if !opts.step {
+            std::thread::sleep(tick_time.saturating_sub(started.elapsed()));
+        }
+    }
+    let standings = human::standings(&frame);
+    let (yours, theirs) = (standings.get(&0).copied().unwrap_or_default(), standings.get(&1).copied().unwrap_or_default());
+    let result = match (yours.0 > 0, theirs.0 > 0) {
+        _ if quit => "Quit",
+        (true, false) => "You win",
+        (false, true) => "The champion wins",
+        _ if yours.1 > theirs.1 => "Time: you lead on health",
+        _ if yours.1 < theirs.1 => "Time: the champion leads on health",
+        _ => "Draw",
+    };
+    println!("{} after {} ticks (your team hp {:.0}, champion hp {:.0})", result, frame.tick, yours.1, theirs.1);
+}
+
+/// The sim config a champion was trained under: the one bundled with it,
+/// else its run's `experiment.toml` if it is alongside, else defaults; plain
+/// JSON champions predate the inertial motion model and keep the legacy one
+fn run_sim_config(champion: &Path) -> Config {
+    let bytes = fs::read(champion).unwrap_or_default();
+    if let Ok(Some(bundle)) = ChampionBundle::from_bytes(&bytes) {
+        return bundle.sim_config;
+    }
+    let exp_path = champion.with_file_name("experiment.toml");
+    let cfg = if exp_path.exists() {
+        Experiment::from_toml_file(&exp_path)
+            .and_then(|exp| exp.sim_config(&Config::default()))
+            .unwrap_or_else(|e| panic!("Failed to read {}: {}", exp_path.display(), e))
+    } else {
+        Config::default()
+    };
+    Config { legacy_motion: cfg.legacy_motion || trained_with_legacy_motion(&bytes), ..cfg }
+}
+
+/// Sim config from an experiment file's `[sim]` table or from a plain sim config TOML
+fn sim_config_file(path: &Path) -> Result<Config, String> {
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let value: toml::Value = toml::from_str(&text).map_err(|e| e.to_string())?;
+    if ["sim", "evolution", "train"].iter().any(|k| value.get(k).is_some()) {
+        Experiment::from_toml_str(&text)
+            .and_then(|exp| exp.sim_config(&Config::default()))
+            .map_err(|e| e.to_string())
+    } else {
+        Config::from_toml_str(&text).map_err(|e| e.to_string())
+    }
+}
+
+/// File name without the `.json`/`.json.gz` extension
+fn champion_stem(path: &Path) -> String {
+    let name = path.file_name().and_then(|s| s.to_str()).unwrap_or("champion");
+    name.trim_end_matches(".gz").trim_end_matches(".json").to_string()
+}
+
+/// Run MAP-Elites and write every elite to out/<run>/elites/
+fn run_map_elites(opts: &MapElitesOpts) {
+    ThreadPoolBuilder::new().num_threads(opts.workers).build_global().unwrap();
+    let id = opts.run_id.clone()
+        .unwrap_or_else(|| format!("{}-map-elites", Utc::now().format("%Y%m%d_%H%M%S")));
+    let elite_dir = format!("out/{}/elites", id);
+    fs::create_dir_all(&elite_dir).unwrap();
+    let sim_cfg = Config { use_python_service: false, python_service_url: None, ..Default::default() };
+    let evo_cfg = EvolutionConfig {
+        pop_size: opts.batch,
+        num_teams: opts.num_teams,
+        team_size: opts.team_size,
+        max_ticks: opts.max_ticks,
+        tournament_k: 2,
+        ..Default::default()
+    };
+    let mut me = MapElites::new(opts.bins);
+    let start = Instant::now();
+    for gen in 0..opts.generations {
+        let kept = me.step(&sim_cfg, &evo_cfg);
+        let best = me.archive.best().map_or(0.0, |g| g.fitness);
+        println!("[{:.2}s] Gen {}: kept = {}, elites = {}, coverage = {:.1}%, best = {:.2}",
+                 start.elapsed().as_secs_f32(), gen, kept, me.archive.len(), me.archive.coverage() * 100.0, best);
+    }
+    for (cell, genome) in me.archive.elites() {
+        let path = format!("{}/elite_{}_{}_{}.json", elite_dir, cell[0], cell[1], cell[2]);
+        let net = phenotype(genome, &sim_cfg, &evo_cfg);
+        let provenance = Provenance {
+            run_id: Some(id.clone()),
+            generation: opts.generations,
+            fitness: net.fitness,
+            fitness_naive: net.fitness_naive,
+            created: Utc::now().format("%Y%m%d_%H%M%S").to_string(),
+        };
+        let bundle = ChampionBundle::new(net, &sim_cfg, &evo_cfg, provenance, serde_json::Value::Null);
+        fs::write(&path, serde_json::to_string(&bundle).unwrap()).expect("write elite");
     }
+    println!("Wrote {} elites to {}", me.archive.len(), elite_dir);
 }
 
 /// Run the inference benchmark
 fn run_bench(opts: &BenchOpts) {
+    let target = match opts.target {
+        BenchTarget::Inference => None,
+        BenchTarget::Physics => Some(bench::Target::Physics),
+        BenchTarget::Match => Some(bench::Target::Match),
+        BenchTarget::Scan => Some(bench::Target::Scan),
+    };
+    if let Some(target) = target {
+        let sim_cfg = Config { use_python_service: false, python_service_url: None, batch_size: 1, ..Default::default() };
+        let workload = bench::Workload { agents: opts.agents, ticks: opts.ticks, matches: opts.runs, seed: opts.seed };
+        println!("{}", bench::run(target, &sim_cfg, workload));
+        return;
+    }
     // reuse existing bench_inference logic with opts
     let mut sim_cfg = Config::default();
     sim_cfg.use_python_service = opts.device == "mps";
     sim_cfg.python_service_url = if opts.device == "mps" { Some(opts.python_service_url.clone()) } else { None };
     sim_cfg.batch_size = opts.batch_size;
-    let mut evo_cfg = EvolutionConfig::default();
+    let evo_cfg = EvolutionConfig::default();
     ThreadPoolBuilder::new().num_threads(opts.workers).build_global().unwrap();
-    if let Some(path) = &opts.export_model {
-        let mut genome = Genome::new();
-        genome.initialize(&sim_cfg, &evo_cfg);
-        let bytes = export_genome(&genome);
-        fs::write(path, bytes).expect("Failed to write ONNX model");
-        println!("Exported ONNX model to {}", path);
-        return;
-    }
     // If duration mode, run until wall-clock >= duration
     if let Some(secs) = opts.duration {
         let dur = Duration::from_secs(secs);
@@ -293,168 +1307,377 @@ fn run_bench(opts: &BenchOpts) {
     }
 }
 
+/// Copy the evolution settings of `opts` into `evo_cfg`, for the flags `given` accepts
+fn apply_train_opts(evo_cfg: &mut EvolutionConfig, opts: &TrainOpts, given: impl Fn(&str) -> bool) {
+    macro_rules! copy {
+        ($($flag:ident => $field:ident),* $(,)?) => {
+            $(if given(stringify!($flag)) { evo_cfg.$field = opts.$flag; })*
+        };
+    }
+    copy!(
+        pop_size => pop_size,
+        tournament_k => tournament_k,
+        max_ticks => max_ticks,
+        map_width => map_width,
+        map_height => map_height,
+        hof_size => hof_size,
+        hof_match_rate => hof_match_rate,
+        mirror_matches => mirror_matches,
+        draw_margin => draw_margin,
+        mercy_ratio => mercy_ratio,
+        stall_ticks => stall_ticks,
+        add_node_rate => mutation_add_node_rate,
+        add_conn_rate => mutation_add_conn_rate,
+        weight_rate => mutation_weight_rate,
+        weight_sigma => mutation_weight_sigma,
+        crossover_rate => crossover_rate,
+        num_teams => num_teams,
+        team_size => team_size,
+        species_stagnation => species_stagnation_limit,
+        recurrent => allow_recurrent,
+        random_seed => seed,
+        substrate_hidden => substrate_hidden,
+        islands => islands,
+        migration_interval => migration_interval,
+        migrants => migrants,
+        coevo_opponents => coevo_opponents,
+        time_bonus_weight => time_bonus_weight,
+        w_health => w_health,
+        w_damage => w_damage,
+        w_kills => w_kills,
+        w_salvage => w_salvage,
+        w_explore => w_explore,
+        w_accuracy => w_accuracy,
+        w_damage_per_shot => w_damage_per_shot,
+        w_fire_efficiency => w_fire_efficiency,
+        w_friendly_fire => w_friendly_fire,
+        w_idle => w_idle,
+    );
+    if given("baseline") {
+        evo_cfg.baseline = opts.baseline.clone();
+    }
+    if given("selection") {
+        evo_cfg.selection = match opts.selection {
+            SelectionArg::Tournament => SelectionStrategy::Tournament,
+            SelectionArg::Nsga2 => SelectionStrategy::Nsga2,
+        };
+    }
+    if given("tie_break") {
+        evo_cfg.tie_break = match opts.tie_break {
+            TieBreakArg::Health => TieBreak::Health,
+            TieBreakArg::Damage => TieBreak::Damage,
+            TieBreakArg::Survivors => TieBreak::Survivors,
+            TieBreakArg::None => TieBreak::None,
+        };
+    }
+    if given("scenario_aggregate") {
+        evo_cfg.scenario_aggregate = match opts.scenario_aggregate {
+            ScenarioAggregateArg::Mean => ScenarioAggregate::Mean,
+            ScenarioAggregateArg::Worst => ScenarioAggregate::Worst,
+        };
+    }
+    if given("encoding") {
+        evo_cfg.encoding = match opts.encoding {
+            EncodingArg::Direct => Encoding::Direct,
+            EncodingArg::HyperNeat => Encoding::HyperNeat,
+        };
+    }
+    if given("fitness_fn") {
+        evo_cfg.fitness_fn = match opts.fitness_fn {
+            FitnessFnArg::HealthPlusDamage => FitnessFn::HealthPlusDamage,
+            FitnessFnArg::HealthPlusDamageTime => FitnessFn::HealthPlusDamageTime,
+            FitnessFnArg::HealthDamageSalvage => FitnessFn::HealthDamageSalvage,
+            FitnessFnArg::HealthDamageExplore => FitnessFn::HealthDamageExplore,
+            FitnessFnArg::HealthDamageTimeSalvageExplore => FitnessFn::HealthDamageTimeSalvageExplore,
+            FitnessFnArg::Novelty => FitnessFn::Novelty,
+            FitnessFnArg::Hybrid => FitnessFn::Hybrid,
+        };
+    }
+    if let (true, Some(expr)) = (given("fitness_expr"), &opts.fitness_expr) {
+        evo_cfg.fitness_fn = FitnessFn::Expr(expr.clone());
+    }
+}
+
+/// Parse a probability flag, rejecting values outside [0, 1]
+fn probability(s: &str) -> Result<f32, String> {
+    let p: f32 = s.parse().map_err(|e| format!("{}", e))?;
+    if (0.0..=1.0).contains(&p) { Ok(p) } else { Err(format!("{} is not within [0, 1]", p)) }
+}
+
+/// Settings of a training run: the flags' values, overlaid by the `--config`
+/// experiment's `[sim]` and `[evolution]` sections, overlaid by the flags set explicitly
+fn resolve_train_config(opts: &TrainOpts) -> (Config, EvolutionConfig) {
+    let mut sim_cfg = Config { use_python_service: false, python_service_url: None, ..Config::default() };
+    let mut evo_cfg = EvolutionConfig::default();
+    apply_train_opts(&mut evo_cfg, opts, |_| true);
+    if let Some(path) = &opts.config {
+        let exp = Experiment::from_toml_file(path)
+            .unwrap_or_else(|e| panic!("Failed to load {}: {}", path, e));
+        sim_cfg = exp.sim_config(&sim_cfg).unwrap_or_else(|e| panic!("Bad [sim] in {}: {}", path, e));
+        evo_cfg = exp.evolution_config(&evo_cfg).unwrap_or_else(|e| panic!("Bad [evolution] in {}: {}", path, e));
+        apply_train_opts(&mut evo_cfg, opts, |flag| opts.given.iter().any(|g| g == flag));
+    }
+    evo_cfg.validate().unwrap_or_else(|e| panic!("Bad evolution settings: {}", e));
+    (sim_cfg, evo_cfg)
+}
+
+/// Island-model training loop (`train --islands N` with N > 1): per-island
+/// progress each generation, champion and genealogy written at the end
+fn run_islands(opts: &TrainOpts, out_dir: &str, sim_cfg: &Config, evo_cfg: EvolutionConfig) {
+    let max_gens = opts.runs.unwrap_or(usize::MAX);
+    let mut arch = Archipelago::new(&evo_cfg);
+    let start = Instant::now();
+    while arch.generation < max_gens && opts.duration.is_none_or(|s| start.elapsed() < Duration::from_secs(s)) {
+        arch.evaluate(sim_cfg, &evo_cfg);
+        let summary: Vec<String> = arch.islands.iter().map(|p| {
+            let best = p.genomes.first().map_or(0.0, |g| g.fitness);
+            let avg = p.genomes.iter().map(|g| g.fitness).sum::<f32>() / p.genomes.len().max(1) as f32;
+            format!("{:.2}/{:.2}", best, avg)
+        }).collect();
+        println!("[{:.2}s] Gen {}: island best/avg = [{}]",
+                 start.elapsed().as_secs_f32(), arch.generation, summary.join(", "));
+        if arch.generation + 1 < max_gens {
+            arch.reproduce(&evo_cfg);
+        } else {
+            break;
+        }
+    }
+    if let Some(champ) = arch.champion() {
+        let champ = phenotype(champ, sim_cfg, &evo_cfg);
+        let provenance = Provenance {
+            run_id: out_dir.strip_prefix("out/").map(str::to_string),
+            generation: arch.generation,
+            fitness: champ.fitness,
+            fitness_naive: champ.fitness_naive,
+            created: Utc::now().format("%Y%m%d_%H%M%S").to_string(),
+        };
+        let bundle = ChampionBundle::new(champ.clone(), sim_cfg, &evo_cfg, provenance, serde_json::Value::Null);
+        fs::write(format!("{}/champion_latest.json", out_dir), serde_json::to_string(&bundle).unwrap()).unwrap();
+        fs::write(format!("{}/genealogy.dot", out_dir), arch.genealogy.to_dot(champ.id)).unwrap();
+        println!("Champion fitness {:.2} → {}/champion_latest.json", champ.fitness, out_dir);
+    }
+    fs::write(format!("{}/genealogy.json", out_dir), serde_json::to_string(&arch.genealogy).unwrap()).unwrap();
+}
+
+/// Red-vs-blue co-evolution loop (`train --coevolve`): both sides' progress
+/// each generation, one champion per side written at the end
+fn run_coevolution(opts: &TrainOpts, out_dir: &str, sim_cfg: &Config, evo_cfg: EvolutionConfig) {
+    let max_gens = opts.runs.unwrap_or(usize::MAX);
+    let mut coevo = CoEvolution::new(&evo_cfg);
+    let start = Instant::now();
+    while coevo.generation() < max_gens && opts.duration.is_none_or(|s| start.elapsed() < Duration::from_secs(s)) {
+        coevo.evaluate(sim_cfg, &evo_cfg);
+        let best = |p: &Population| p.genomes.first().map_or(0.0, |g| g.fitness);
+        println!("[{:.2}s] Gen {}: red best = {:.2}, blue best = {:.2}",
+                 start.elapsed().as_secs_f32(), coevo.generation(), best(&coevo.red), best(&coevo.blue));
+        if coevo.generation() + 1 < max_gens {
+            coevo.reproduce(&evo_cfg);
+        } else {
+            break;
+        }
+    }
+    for (name, side) in [("red", &coevo.red), ("blue", &coevo.blue)] {
+        if let Some(champ) = side.hof.first() {
+            let champ = phenotype(champ, sim_cfg, &evo_cfg);
+            let path = format!("{}/champion_{}.json", out_dir, name);
+            let provenance = Provenance {
+                run_id: out_dir.strip_prefix("out/").map(str::to_string),
+                generation: coevo.generation(),
+                fitness: champ.fitness,
+                fitness_naive: champ.fitness_naive,
+                created: Utc::now().format("%Y%m%d_%H%M%S").to_string(),
+            };
+            let fitness = champ.fitness;
+            let bundle = ChampionBundle::new(champ, sim_cfg, &evo_cfg, provenance, serde_json::Value::Null);
+            fs::write(&path, serde_json::to_string(&bundle).unwrap()).unwrap();
+            println!("{} champion fitness {:.2} → {}", name, fitness, path);
+        }
+    }
+}
+
 /// Run the NEAT training loop with snapshots and status logs
 fn run_train(opts: &TrainOpts) -> String {
-    println!("[debug][run_train] workers flag = {}", opts.workers);
     ThreadPoolBuilder::new().num_threads(opts.workers).build_global().unwrap();
-    println!("[debug][run_train] rayon threadpool size = {}", rayon::current_num_threads());
+    debug!(workers = opts.workers, threads = rayon::current_num_threads(), "rayon thread pool ready");
     fs::create_dir_all("out").unwrap();
+    let (mut sim_cfg, mut evo_cfg) = resolve_train_config(opts);
     // generate run-specific ID and create output directory
     let ts = Utc::now().format("%Y%m%d_%H%M%S").to_string();
-    let fn_name = opts.fitness_fn.to_possible_value().unwrap().get_name().to_string();
+    let fn_name = serde_json::to_value(&evo_cfg.fitness_fn).unwrap();
     let id = opts.run_id.clone().unwrap_or_else(|| format!(
         "{}-fn-{}-h{:.1}-d{:.1}-k{:.1}-s{:.1}-e{:.1}",
-        ts, fn_name, opts.w_health, opts.w_damage, opts.w_kills, opts.w_salvage, opts.w_explore
+        ts, fn_name.as_str().unwrap_or("custom"),
+        evo_cfg.w_health, evo_cfg.w_damage, evo_cfg.w_kills, evo_cfg.w_salvage, evo_cfg.w_explore
     ));
     println!("Run ID: {}", id);
     let out_dir = format!("out/{}", id);
     fs::create_dir_all(&out_dir).unwrap();
-    let mut sim_cfg = Config::default();
-    sim_cfg.use_python_service = false;
-    sim_cfg.python_service_url = None;
-    let mut evo_cfg = EvolutionConfig::default();
-    evo_cfg.pop_size = 10;
-    evo_cfg.tournament_k = 2;
-    evo_cfg.max_ticks = 200;
-    evo_cfg.num_teams = opts.num_teams;
-    evo_cfg.team_size = opts.team_size;
+    // everything needed to rerun with `train --config out/<run>/experiment.toml`
+    let experiment_path = format!("{}/experiment.toml", out_dir);
+    Experiment::resolved(opts, &sim_cfg, &evo_cfg)
+        .and_then(|exp| exp.to_toml_file(&experiment_path))
+        .unwrap_or_else(|e| eprintln!("Failed to write {}: {}", experiment_path, e));
+    if let Some(addr) = opts.metrics_addr {
+        prometheus::spawn(addr).unwrap_or_else(|e| panic!("Failed to serve metrics on {}: {}", addr, e));
+        info!(%addr, "serving Prometheus metrics on /metrics");
+    }
+    if opts.coevolve {
+        run_coevolution(opts, &out_dir, &sim_cfg, evo_cfg);
+        return id;
+    }
+    if evo_cfg.islands > 1 {
+        run_islands(opts, &out_dir, &sim_cfg, evo_cfg);
+        return id;
+    }
     // upper bound on generations (usize::MAX if unlimited)
     let max_gens = opts.runs.unwrap_or(usize::MAX);
-    let mut population = Population::new(&evo_cfg);
+    let mut population = match &opts.resume {
+        Some(path) => {
+            let pop = Population::load(path)
+                .unwrap_or_else(|e| panic!("Failed to resume from {}: {}", path, e));
+            println!("Resuming from {} at generation {}", path, pop.generation);
+            pop
+        }
+        None => Population::new(&evo_cfg),
+    };
+    let checkpoint_path = format!("{}/population.json", out_dir);
+    fs::create_dir_all(format!("{}/metrics", out_dir)).unwrap();
     let start = Instant::now();
-    let mut gen = 0;
-    // Track base sensor range for difficulty adjustments
-    let base_scan_max_dist = sim_cfg.scan_max_dist;
+    let mut gen = population.generation;
+    // a resumed checkpoint carries its own curriculum
+    if population.curriculum.is_none() {
+        population.curriculum = Some(match &opts.curriculum {
+            Some(path) => serde_json::from_str(&fs::read_to_string(path).expect("Failed to read curriculum"))
+                .expect("Failed to parse curriculum"),
+            None => Curriculum::difficulty_ramp(
+                sim_cfg.scan_max_dist, sim_cfg.max_difficulty, opts.difficulty_interval, opts.difficulty_threshold,
+            ),
+        });
+    }
+    // stage overrides are reapplied to the base settings after each promotion
+    let base_sim_cfg = sim_cfg.clone();
     // keep original mutation rates for auto-recovery
     let orig_node_rate = evo_cfg.mutation_add_node_rate;
     let orig_conn_rate = evo_cfg.mutation_add_conn_rate;
     let mut recovery_active = false;
     let mut best_history: VecDeque<f32> = VecDeque::new();
     // RNG for scenario randomization
-    let mut rng = match opts.random_seed {
+    let mut rng = match evo_cfg.seed {
         Some(s) => StdRng::seed_from_u64(s),
         None => StdRng::from_entropy(),
     };
     let base_map_w = evo_cfg.map_width;
     let base_map_h = evo_cfg.map_height;
+    // with --dashboard the per-generation log is dropped and notable events are shown on screen
+    #[cfg(feature = "dashboard")]
+    let mut dashboard = opts.dashboard.then(|| (Dashboard::new(&id), Term::stdout()));
+    #[cfg(feature = "dashboard")]
+    let live = dashboard.is_some();
+    #[cfg(not(feature = "dashboard"))]
+    let live = false;
+    macro_rules! log {
+        ($($arg:tt)*) => { if !live { println!($($arg)*); } };
+    }
+    macro_rules! note {
+        ($($arg:tt)*) => {
+            #[cfg(feature = "dashboard")]
+            match dashboard.as_mut() {
+                Some((d, _)) => d.event(format!($($arg)*)),
+                None => println!($($arg)*),
+            }
+            #[cfg(not(feature = "dashboard"))]
+            println!($($arg)*);
+        };
+    }
     // run until generation or time limit
+    let mut run_counters = Counters::default();
+    let generations_path = format!("{}/metrics/generations.jsonl", out_dir);
     while gen < max_gens && (opts.duration.map_or(true, |s| start.elapsed() < Duration::from_secs(s))) {
+        let gen_start = Instant::now();
+        let _gen_span = info_span!("generation", generation = gen).entered();
         // scenario randomization per generation
         if opts.map_var > 0 {
             let delta_w = rng.gen_range(-(opts.map_var as i32)..=(opts.map_var as i32));
             let delta_h = rng.gen_range(-(opts.map_var as i32)..=(opts.map_var as i32));
             evo_cfg.map_width = (base_map_w as i32 + delta_w).max(1) as u32;
             evo_cfg.map_height = (base_map_h as i32 + delta_h).max(1) as u32;
-            println!("[{:.2}s] randomized map size → {}x{}", start.elapsed().as_secs_f32(), evo_cfg.map_width, evo_cfg.map_height);
+            note!("[{:.2}s] randomized map size → {}x{}", start.elapsed().as_secs_f32(), evo_cfg.map_width, evo_cfg.map_height);
         }
         // reset instrumentation counters
-        PHYS_TIME_NS.store(0, Ordering::Relaxed);
-        PHYS_COUNT.store(0, Ordering::Relaxed);
-        MATCH_TIME_NS.store(0, Ordering::Relaxed);
-        MATCH_COUNT.store(0, Ordering::Relaxed);
-        INFER_TIME_NS.store(0, Ordering::Relaxed);
-        INFER_COUNT.store(0, Ordering::Relaxed);
-        HTTP_TIME_NS.store(0, Ordering::Relaxed);
-        REMOTE_INFER_NS.store(0, Ordering::Relaxed);
+        run_counters.add(&Counters::take());
         // Timestamped generation header
-        println!("[{}][{:.2}s] --- Generation {} ({}v{}) ---", id, start.elapsed().as_secs_f32(), gen, evo_cfg.num_teams, evo_cfg.team_size);
+        log!("[{}][{:.2}s] --- Generation {} ({}v{}) ---", id, start.elapsed().as_secs_f32(), gen, evo_cfg.num_teams, evo_cfg.team_size);
         let eval_start = Instant::now();
         // Evaluate and log stats
         population.evaluate(&sim_cfg, &evo_cfg);
         let eval_dur = eval_start.elapsed();
-        println!(" Evaluation took: {:?}", eval_dur);
-        // performance instrumentation
-        let phys_ns = PHYS_TIME_NS.load(Ordering::Relaxed);
-        let phys_ct = PHYS_COUNT.load(Ordering::Relaxed);
-        let match_ns = MATCH_TIME_NS.load(Ordering::Relaxed);
-        let match_ct = MATCH_COUNT.load(Ordering::Relaxed);
-        let infer_ns = INFER_TIME_NS.load(Ordering::Relaxed);
-        let infer_ct = INFER_COUNT.load(Ordering::Relaxed);
-        let http_ns = HTTP_TIME_NS.load(Ordering::Relaxed);
-        let remote_ns = REMOTE_INFER_NS.load(Ordering::Relaxed);
-        println!(
-            "  Perf: sim avg = {:.2}µs/tick ({} ticks); match avg = {:.2}ms/match ({} matches)",
-            phys_ns as f64 / phys_ct as f64 / 1e3, phys_ct,
-            match_ns as f64 / match_ct as f64 / 1e6, match_ct,
-        );
-        println!(
-            "        infer avg = {:.2}µs ({}); http total = {:.2}ms; remote infer = {:.2}ms",
-            infer_ns as f64 / infer_ct as f64 / 1e3, infer_ct,
-            http_ns as f64 / 1e6, remote_ns as f64 / 1e6,
-        );
+        log!(" Evaluation took: {:?}", eval_dur);
+        let metrics_path = format!("{}/metrics/gen_{:04}.csv", out_dir, gen);
+        fs::File::create(&metrics_path)
+            .and_then(|f| metrics::write_csv(std::io::BufWriter::new(f), gen, &population.genomes))
+            .unwrap_or_else(|e| eprintln!("Failed to write {}: {}", metrics_path, e));
+        // performance instrumentation of the evaluation
+        let counters = Counters::take();
+        run_counters.add(&counters);
+        log_counters(&counters, "evaluation perf");
         let fitnesses: Vec<f32> = population.genomes.iter().map(|g| g.fitness).collect();
         let best = *fitnesses.iter().max_by(|a, b| a.partial_cmp(b).unwrap()).unwrap();
         let avg = fitnesses.iter().sum::<f32>() / fitnesses.len() as f32;
         let naive_vals: Vec<f32> = population.genomes.iter().map(|g| g.fitness_naive).collect();
         let best_naive = *naive_vals.iter().max_by(|a, b| a.partial_cmp(b).unwrap()).unwrap();
         let avg_naive = naive_vals.iter().sum::<f32>() / naive_vals.len() as f32;
-        println!(
+        log!(
             "Gen {}: best = {:.2}, avg = {:.2}, naive_best = {:.2}, avg_naive = {:.2}",
             gen, best, avg, best_naive, avg_naive
         );
-        println!("=== Profiling Summary ===");
-        println!(
-            "Inference: {:.2} ms total over {} calls", infer_ns as f64 / 1e6, infer_ct
-        );
-        println!(
-            "Physics:   {:.2} ms total over {} steps", phys_ns as f64 / 1e6, phys_ct
-        );
-        println!(
-            "HTTP:      {:.2} ms total", http_ns as f64 / 1e6
-        );
-        println!(
-            "Remote:    {:.2} ms total", remote_ns as f64 / 1e6
-        );
-        // Bump difficulty if threshold & interval reached
-        if gen > 0
-           && gen % opts.difficulty_interval == 0
-           && avg_naive >= opts.difficulty_threshold
-           && sim_cfg.difficulty_level < sim_cfg.max_difficulty
-        {
-            sim_cfg.difficulty_level += 1;
-            // Shrink sensor range by 10% per level
-            sim_cfg.scan_max_dist = base_scan_max_dist * (1.0 - sim_cfg.difficulty_level as f32 * 0.1);
-            println!("[{:.2}s] ↑ Difficulty → level {}, scan_max_dist={:.2}",
-                     start.elapsed().as_secs_f32(), sim_cfg.difficulty_level, sim_cfg.scan_max_dist);
+        log!("Species ({}):", population.species.len());
+        for line in population.species_summary(&evo_cfg) {
+            log!("{}", line);
+        }
+        let record = GenerationRecord {
+            generation: gen,
+            elapsed_s: start.elapsed().as_secs_f32(),
+            eval_s: eval_dur.as_secs_f32(),
+            best, avg, best_naive, avg_naive,
+            species: population.species.len(),
+            counters,
+        };
+        fs::OpenOptions::new().create(true).append(true).open(&generations_path)
+            .and_then(|f| record.write_line(f))
+            .unwrap_or_else(|e| warn!(path = %generations_path, error = %e, "failed to append generation metrics"));
+        // `evaluate` may have promoted the curriculum
+        if let Some(c) = &population.curriculum {
+            if c.stage != sim_cfg.difficulty_level {
+                sim_cfg = base_sim_cfg.clone();
+                c.apply(&mut sim_cfg, &mut evo_cfg.clone());
+                note!("[{:.2}s] ↑ Difficulty → level {}, scan_max_dist={:.2}",
+                         start.elapsed().as_secs_f32(), sim_cfg.difficulty_level, sim_cfg.scan_max_dist);
+            }
         }
         // Hall of Fame
-        println!("Hall of Fame (top {}):", evo_cfg.hof_size);
+        log!("Hall of Fame (top {}):", evo_cfg.hof_size);
         for (i, g) in population.hof.iter().enumerate() {
-            println!("  HoF {}: {:.2}", i, g.fitness);
+            log!("  HoF {}: {:.2}", i, g.fitness);
         }
         // Replay champion vs second-best
         if population.hof.len() > 1 {
-            let champ = population.hof[0].clone();
-            let opp = population.hof[1].clone();
+            let champ = phenotype(&population.hof[0], &sim_cfg, &evo_cfg);
+            let opp = phenotype(&population.hof[1], &sim_cfg, &evo_cfg);
             let agents: Vec<(Box<dyn Brain>, u32)> = vec![
-                (Box::new(NeatBrain::new(
-                    champ.clone(),
-                    sim_cfg.batch_size,
-                    sim_cfg.python_service_url.clone().unwrap_or_default(),
-                )) as Box<dyn Brain>, 0),
-                (Box::new(NeatBrain::new(
-                    opp.clone(),
-                    sim_cfg.batch_size,
-                    sim_cfg.python_service_url.clone().unwrap_or_default(),
-                )) as Box<dyn Brain>, 1),
+                (Box::new(NeatBrain::for_config(champ.clone(), &sim_cfg)) as Box<dyn Brain>, 0),
+                (Box::new(NeatBrain::for_config(opp.clone(), &sim_cfg)) as Box<dyn Brain>, 1),
             ];
             let path = format!("{}/champ_replay.jsonl", out_dir);
-            let stats = run_match_record(&path, &sim_cfg, &evo_cfg, agents);
-            println!("  Replay: ticks = {}, health = {:.2}", stats.ticks, stats.subject_team_health);
+            match run_match_record(&path, &sim_cfg, &evo_cfg, agents, evo_cfg.seed.unwrap_or(0)) {
+                Ok(stats) => log!("  Replay: ticks = {}, health = {:.2}", stats.ticks, stats.subject_team_health),
+                Err(e) => warn!(path = %path, error = %e, "failed to record champion replay"),
+            }
         }
         // Snapshot champion weights for continued use
         {
-            let champ = population.hof[0].clone();
+            let champ = phenotype(&population.hof[0], &sim_cfg, &evo_cfg);
             let timestamp = Utc::now().format("%Y%m%d_%H%M%S").to_string();
             let total_duration = start.elapsed().as_secs_f32();
-            let phys_ns = PHYS_TIME_NS.load(Ordering::Relaxed);
-            let phys_ct = PHYS_COUNT.load(Ordering::Relaxed);
-            let match_ns = MATCH_TIME_NS.load(Ordering::Relaxed);
-            let match_ct = MATCH_COUNT.load(Ordering::Relaxed);
-            let infer_ns = INFER_TIME_NS.load(Ordering::Relaxed);
-            let infer_ct = INFER_COUNT.load(Ordering::Relaxed);
-            let http_ns = HTTP_TIME_NS.load(Ordering::Relaxed);
-            let remote_ns = REMOTE_INFER_NS.load(Ordering::Relaxed);
             let metadata = json!({
                 "timestamp": timestamp,
                 "duration_s": total_duration,
@@ -465,14 +1688,19 @@ fn run_train(opts: &TrainOpts) -> String {
                     "runs": opts.runs,
                     "duration_limit_s": opts.duration,
                     "snapshot_interval": opts.snapshot_interval,
-                    "fitness_fn": format!("{:?}", opts.fitness_fn),
-                    "time_bonus_weight": opts.time_bonus_weight,
-                    "w_health": opts.w_health,
-                    "w_damage": opts.w_damage,
-                    "w_kills": opts.w_kills,
-                    "w_salvage": opts.w_salvage,
-                    "w_explore": opts.w_explore,
-                    "random_seed": opts.random_seed,
+                    "fitness_fn": evo_cfg.fitness_fn,
+                    "time_bonus_weight": evo_cfg.time_bonus_weight,
+                    "w_health": evo_cfg.w_health,
+                    "w_damage": evo_cfg.w_damage,
+                    "w_kills": evo_cfg.w_kills,
+                    "w_salvage": evo_cfg.w_salvage,
+                    "w_explore": evo_cfg.w_explore,
+                    "w_accuracy": evo_cfg.w_accuracy,
+                    "w_damage_per_shot": evo_cfg.w_damage_per_shot,
+                    "w_fire_efficiency": evo_cfg.w_fire_efficiency,
+                    "w_friendly_fire": evo_cfg.w_friendly_fire,
+                    "w_idle": evo_cfg.w_idle,
+                    "random_seed": evo_cfg.seed,
                     "map_var": opts.map_var,
                     "run_id": opts.run_id
                 },
@@ -494,7 +1722,15 @@ fn run_train(opts: &TrainOpts) -> String {
                     "max_ticks": evo_cfg.max_ticks,
                     "num_teams": evo_cfg.num_teams,
                     "team_size": evo_cfg.team_size,
-                    "hof_size": evo_cfg.hof_size
+                    "hof_size": evo_cfg.hof_size,
+                    "hof_match_rate": evo_cfg.hof_match_rate,
+                    "mirror_matches": evo_cfg.mirror_matches,
+                    "tie_break": evo_cfg.tie_break,
+                    "draw_margin": evo_cfg.draw_margin,
+                    "mercy_ratio": evo_cfg.mercy_ratio,
+                    "stall_ticks": evo_cfg.stall_ticks,
+                    "scenarios": evo_cfg.scenarios,
+                    "scenario_aggregate": evo_cfg.scenario_aggregate
                 },
                 "fitness_weights": {
                     "health": evo_cfg.w_health,
@@ -502,32 +1738,47 @@ fn run_train(opts: &TrainOpts) -> String {
                     "kills": evo_cfg.w_kills,
                     "salvage": evo_cfg.w_salvage,
                     "explore": evo_cfg.w_explore,
+                    "accuracy": evo_cfg.w_accuracy,
+                    "damage_per_shot": evo_cfg.w_damage_per_shot,
+                    "fire_efficiency": evo_cfg.w_fire_efficiency,
+                    "friendly_fire": evo_cfg.w_friendly_fire,
+                    "idle": evo_cfg.w_idle,
                     "time_bonus": evo_cfg.time_bonus_weight
                 },
                 "instrumentation": {
-                    "sim_avg_us": phys_ns as f64 / phys_ct as f64 / 1e3,
-                    "match_avg_ms": match_ns as f64 / match_ct as f64 / 1e6,
-                    "infer_avg_us": infer_ns as f64 / infer_ct as f64 / 1e3,
-                    "http_total_ms": http_ns as f64 / 1e6,
-                    "remote_infer_ms": remote_ns as f64 / 1e6
+                    "sim_avg_us": counters.sim_avg_us(),
+                    "match_avg_ms": counters.match_avg_ms(),
+                    "infer_avg_us": counters.infer_avg_us(),
+                    "http_total_ms": counters.http_ns as f64 / 1e6,
+                    "remote_infer_ms": counters.remote_infer_ns as f64 / 1e6
                 },
                 // champion's baseline performance against NaiveAgent
                 "champion_fitness_naive": champ.fitness_naive
             });
-            let output = json!({
-                "metadata": metadata,
-                "genome": champ
-            });
-            let json_str = serde_json::to_string_pretty(&output).unwrap();
+            let provenance = Provenance {
+                run_id: Some(id.clone()),
+                generation: gen,
+                fitness: champ.fitness,
+                fitness_naive: champ.fitness_naive,
+                created: timestamp,
+            };
+            let bundle = ChampionBundle::new(champ, &sim_cfg, &evo_cfg, provenance, metadata);
+            let json_str = serde_json::to_string_pretty(&bundle).unwrap();
             fs::write(format!("{}/champion_latest.json", out_dir), &json_str).expect("Failed to write champion_latest");
             fs::write(format!("{}/champion_gen_{:03}.json", out_dir, gen), &json_str)
                 .expect("Failed to write champion_gen file");
         }
         if gen % opts.snapshot_interval == 0 || gen + 1 == max_gens {
-            let champ = &population.hof[0];
-            let json = serde_json::to_string(champ).unwrap();
-            fs::write(format!("{}/champion_gen_{:03}.json", out_dir, gen), &json).unwrap();
-            fs::write(format!("{}/champion_latest.json", out_dir), &json).unwrap();
+            let champ = phenotype(&population.hof[0], &sim_cfg, &evo_cfg);
+            // self-describing ONNX copy (recurrent champions cannot be exported)
+            match export_champion(&champ, gen, &sim_cfg) {
+                Ok(bytes) => fs::write(format!("{}/champion_latest.onnx", out_dir), bytes).unwrap(),
+                Err(e) if opts.verbose => eprintln!("champion not exported to ONNX: {}", e),
+                Err(_) => {}
+            }
+            // family tree of the whole run, and a DOT graph of the champion's ancestry
+            fs::write(format!("{}/genealogy.json", out_dir), serde_json::to_string(&population.genealogy).unwrap()).unwrap();
+            fs::write(format!("{}/genealogy.dot", out_dir), population.genealogy.to_dot(champ.id)).unwrap();
             if opts.verbose {
                 eprintln!("[{:.1}s] ▶ snapshot champion → {}/champion_gen_{:03}.json", start.elapsed().as_secs_f32(), out_dir, gen);
             }
@@ -540,7 +1791,7 @@ fn run_train(opts: &TrainOpts) -> String {
         if best_history.len() == opts.stagnation_window
             && best_history.iter().all(|&v| (v - best_history[0]).abs() < f32::EPSILON)
         {
-            println!("No improvement in {} gens; injecting {} random genomes and scaling mutation x{:.2}",
+            note!("No improvement in {} gens; injecting {} random genomes and scaling mutation x{:.2}",
                      opts.stagnation_window, opts.inject_count, opts.mutation_scale);
             evo_cfg.mutation_add_node_rate = orig_node_rate * opts.mutation_scale;
             evo_cfg.mutation_add_conn_rate = orig_conn_rate * opts.mutation_scale;
@@ -559,35 +1810,34 @@ fn run_train(opts: &TrainOpts) -> String {
                 evo_cfg.mutation_add_conn_rate = orig_conn_rate;
                 recovery_active = false;
             }
+            if let Err(e) = population.save(&checkpoint_path) {
+                eprintln!("Failed to write checkpoint {}: {}", checkpoint_path, e);
+            }
+        }
+        prometheus::GENERATION_SECONDS.observe_ns(gen_start.elapsed().as_nanos() as u64);
+        prometheus::GENERATION.set(gen as f64);
+        prometheus::BEST_FITNESS.set(best as f64);
+        #[cfg(feature = "dashboard")]
+        if let Some((d, term)) = dashboard.as_mut() {
+            d.push(GenerationStats {
+                generation: gen, best, avg, best_naive,
+                species: population.species.len(),
+                eval_secs: eval_dur.as_secs_f32(),
+                gen_secs: gen_start.elapsed().as_secs_f32(),
+            });
+            d.set_champion(&population.hof[0]);
+            let _ = term.clear_screen();
+            let _ = term.write_str(&d.render(term.size().1 as usize));
         }
-        // apply selected fitness function and weight
-        evo_cfg.fitness_fn = match opts.fitness_fn {
-            FitnessFnArg::HealthPlusDamage => FitnessFn::HealthPlusDamage,
-            FitnessFnArg::HealthPlusDamageTime => FitnessFn::HealthPlusDamageTime,
-            FitnessFnArg::HealthDamageSalvage => FitnessFn::HealthDamageSalvage,
-            FitnessFnArg::HealthDamageExplore => FitnessFn::HealthDamageExplore,
-            FitnessFnArg::HealthDamageTimeSalvageExplore => FitnessFn::HealthDamageTimeSalvageExplore,
-        };
-        evo_cfg.time_bonus_weight = opts.time_bonus_weight;
-        evo_cfg.w_health = opts.w_health;
-        evo_cfg.w_damage = opts.w_damage;
-        evo_cfg.w_kills = opts.w_kills;
-        evo_cfg.w_salvage = opts.w_salvage;
-        evo_cfg.w_explore = opts.w_explore;
         gen += 1;
     }
-    // Print cumulative profiling results
-    let infer_time = INFER_TIME_NS.load(Ordering::Relaxed);
-    let infer_count = INFER_COUNT.load(Ordering::Relaxed);
-    let phys_time = PHYS_TIME_NS.load(Ordering::Relaxed);
-    let phys_count = PHYS_COUNT.load(Ordering::Relaxed);
-    println!("=== Profiling Summary ===");
-    println!("Inference: {:.2} ms total over {} calls", infer_time as f64 / 1e6, infer_count);
-    println!("Physics:   {:.2} ms total over {} steps", phys_time as f64 / 1e6, phys_count);
-    let http_time = HTTP_TIME_NS.load(Ordering::Relaxed);
-    let remote_time = REMOTE_INFER_NS.load(Ordering::Relaxed);
-    println!("HTTP:      {:.2} ms total", http_time as f64 / 1e6);
-    println!("Remote:    {:.2} ms total", remote_time as f64 / 1e6);
+    // cumulative profiling results
+    run_counters.add(&Counters::take());
+    log_counters(&run_counters, "training perf");
+    let fallbacks = REMOTE_FALLBACKS.load(Ordering::Relaxed);
+    if fallbacks > 0 {
+        warn!(brains = fallbacks, "brains fell back to CPU after inference service errors");
+    }
     println!("Trained {} gens in {:.1}s → {:.2} gens/sec", gen, start.elapsed().as_secs_f32(), gen as f32 / start.elapsed().as_secs_f32());
     // return run ID
     id
@@ -596,12 +1846,7 @@ fn run_train(opts: &TrainOpts) -> String {
 /// Run a round-robin tournament among all champions, compute and dump Elo ratings
 fn run_tournament(opts: &TournamentOpts) {
     // reset profiling counters
-    PHYS_TIME_NS.store(0, Ordering::Relaxed);
-    PHYS_COUNT.store(0, Ordering::Relaxed);
-    INFER_TIME_NS.store(0, Ordering::Relaxed);
-    INFER_COUNT.store(0, Ordering::Relaxed);
-    HTTP_TIME_NS.store(0, Ordering::Relaxed);
-    REMOTE_INFER_NS.store(0, Ordering::Relaxed);
+    Counters::take();
     // configure Rayon thread pool to cpu_count-1 threads
     let threads = num_cpus::get().saturating_sub(1).max(1);
     ThreadPoolBuilder::new().num_threads(threads).build_global().expect("Failed to build global thread pool");
@@ -612,34 +1857,48 @@ fn run_tournament(opts: &TournamentOpts) {
     sim_cfg.use_python_service = false;
     sim_cfg.batch_size = 1;
     sim_cfg.python_service_url = None;
+    sim_cfg.legacy_motion = opts.legacy_motion;
     let mut evo_cfg = EvolutionConfig::default();
     evo_cfg.num_teams = 2;
     evo_cfg.team_size = 4;
     evo_cfg.max_ticks = 200;
     // Gather participants: try explicit files first, then dir; skip invalid
     let mut participants: Vec<(String, Option<Genome>)> = Vec::new();
+    // a champion that cannot play under sim_cfg is skipped like one that fails to parse
+    let load = |path: &Path| -> Result<Genome, String> {
+        let g = load_genome(path).map_err(|e| e.to_string())?;
+        let trained_legacy = fs::read(path).is_ok_and(|b| trained_with_legacy_motion(&b));
+        if trained_legacy != sim_cfg.legacy_motion {
+            let flag = if trained_legacy { "with" } else { "without" };
+            return Err(format!("{}; enter it {} --legacy-motion", BundleError::MotionMismatch { trained_legacy }, flag));
+        }
+        match validate::check_genome(&g, &sim_cfg).into_iter().find(|f| f.severity == validate::Severity::Error) {
+            Some(f) => Err(f.message),
+            None => Ok(g),
+        }
+    };
     if !opts.pop_files.is_empty() {
         for file in &opts.pop_files {
-            let p = Path::new(file);
-            match fs::read_to_string(p) {
-                Ok(data) => match serde_json::from_str::<Genome>(&data) {
-                    Ok(g) => participants.push((file.clone(), Some(g))),
-                    Err(e) => eprintln!("Skipping {}: invalid genome JSON: {}", file, e),
-                },
-                Err(e) => eprintln!("Skipping {}: failed to read file: {}", file, e),
+            match load(Path::new(file)) {
+                Ok(g) => participants.push((file.clone(), Some(g))),
+                Err(e) => eprintln!("Skipping {}: {}", file, e),
             }
         }
     } else {
         let champs: Vec<(String, Genome)> = fs::read_dir(&opts.pop_path).unwrap()
             .filter_map(|entry| {
                 let path = entry.ok()?.path();
-                if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                if !matches!(path.extension().and_then(|e| e.to_str()), Some("json") | Some("gz")) {
                     return None;
                 }
                 let fname = path.file_name()?.to_string_lossy().to_string();
-                let data = fs::read_to_string(&path).ok()?;
-                let g: Genome = serde_json::from_str(&data).ok()?;
-                Some((fname, g))
+                match load(&path) {
+                    Ok(g) => Some((fname, g)),
+                    Err(e) => {
+                        eprintln!("Skipping {}: {} (run `neat_train validate {}` for details)", fname, e, path.display());
+                        None
+                    }
+                }
             })
             .collect();
         if champs.is_empty() {
@@ -656,101 +1915,135 @@ fn run_tournament(opts: &TournamentOpts) {
         return;
     }
     // Include naive agent if requested
-    if opts.include_naive {
+    if opts.include_naive && !opts.scripted.iter().any(|s| s == "naive") {
         participants.push(("Naive".to_string(), None));
     }
+    for name in &opts.scripted {
+        participants.push((name.clone(), None));
+    }
+    // each script compiles once here and every match shares the program
+    #[cfg(feature = "scripting")]
+    let mut scripts: std::collections::HashMap<String, std::sync::Arc<Program>> = Default::default();
+    #[cfg(feature = "scripting")]
+    for path in &opts.script {
+        match Program::read(path) {
+            Ok(program) => {
+                scripts.insert(path.clone(), std::sync::Arc::new(program));
+                participants.push((path.clone(), None));
+            }
+            Err(e) => eprintln!("Skipping {}: {}", path, e),
+        }
+    }
     let total = participants.len();
-    // Initialize Elo ratings at 1200, use full names for pop-files
-    let mut ratings: HashMap<String, f32> = participants.iter().map(|(name, _)| {
-        let key = if !opts.pop_files.is_empty() {
+    // full names for pop-files
+    let names: Vec<String> = participants.iter().map(|(name, _)| {
+        if !opts.pop_files.is_empty() {
             name.clone()
         } else {
             format!("{}/{}", opts.pop_path, name)
-        };
-        (key, 1200.0)
+        }
     }).collect();
     let k_factor = 32.0;
-    // Generate all unique pairs (i < j)
-    let pairs: Vec<(usize, usize)> = (0..total)
-        .flat_map(|i| ((i+1)..total).map(move |j| (i, j)))
-        .collect();
-    // Run matches in parallel and collect outcomes
-    let total_pairs = pairs.len() as u64;
-    println!("Running {} matchups…", total_pairs);
-    let outcomes = pairs.into_par_iter()
-        .progress_count(total_pairs)
-        .map(|(i, j)| {
-            // spawn 4v4 match: 4 copies per side
-            let mut agents: Vec<(Box<dyn Brain>, u32)> = Vec::with_capacity((evo_cfg.team_size * 2) as usize);
-            for _ in 0..evo_cfg.team_size {
-                let bi: Box<dyn Brain> = if let Some(ref gi) = participants[i].1 {
-                    Box::new(NeatBrain::new(gi.clone(), sim_cfg.batch_size, sim_cfg.python_service_url.clone().unwrap_or_default())) as Box<dyn Brain>
-                } else {
-                    Box::new(NaiveBrain(NaiveAgent::new(sim_cfg.max_speed, 10.0)))
-                };
-                agents.push((bi, 0));
-            }
-            for _ in 0..evo_cfg.team_size {
-                let bj: Box<dyn Brain> = if let Some(ref gj) = participants[j].1 {
-                    Box::new(NeatBrain::new(gj.clone(), sim_cfg.batch_size, sim_cfg.python_service_url.clone().unwrap_or_default())) as Box<dyn Brain>
-                } else {
-                    Box::new(NaiveBrain(NaiveAgent::new(sim_cfg.max_speed, 10.0)))
-                };
-                agents.push((bj, 1));
+    let format = match opts.format {
+        FormatArg::Rr => Format::RoundRobin,
+        FormatArg::Swiss => Format::Swiss { rounds: opts.rounds.unwrap_or_else(|| Format::swiss_rounds(total)) },
+        FormatArg::Bracket => Format::Elimination { lives: 1 },
+        FormatArg::DoubleBracket => Format::Elimination { lives: 2 },
+    };
+    // Run each round's matches in parallel and collect outcomes
+    let max_games = format.games(total) as u64;
+    println!("Running up to {} matchups ({:?})…", max_games, format);
+    let bar = ProgressBar::new(max_games);
+    let seeds: Vec<u64> = (0..opts.games.max(1) as u64).collect();
+    let outcomes = format.run(total, |i, j| {
+        // team_size copies per side; champion-less entries are scripted opponents by name
+        let brain = |p: usize| {
+            let ((name, entry), sim_cfg) = (&participants[p], &sim_cfg);
+            #[cfg(feature = "scripting")]
+            let scripts = &scripts;
+            move || -> Box<dyn Brain> {
+                match entry {
+                    Some(g) => Box::new(NeatBrain::for_config(g.clone(), sim_cfg)),
+                    None if name == "Naive" => Box::new(NaiveBrain(NaiveAgent::new(sim_cfg.max_speed, 10.0))),
+                    #[cfg(feature = "scripting")]
+                    None if scripts.contains_key(name) => Box::new(ScriptBrain::from_program(scripts[name].clone())),
+                    None => scripted::by_name(name).expect("scripted opponent names are checked by clap"),
+                }
             }
-            let stats = run_match(&sim_cfg, &evo_cfg, agents);
-            let win_i = stats.subject_team_health > 0.0;
-            (i, j, win_i)
-        }).collect::<Vec<_>>();
-    println!(); // newline after progress bar
-    // Sequentially update Elo ratings
-    for (i, j, win_i) in outcomes {
-        let pi = if !opts.pop_files.is_empty() {
-            participants[i].0.clone()
-        } else {
-            format!("{}/{}", opts.pop_path, participants[i].0)
         };
-        let pj = if !opts.pop_files.is_empty() {
-            participants[j].0.clone()
-        } else {
-            format!("{}/{}", opts.pop_path, participants[j].0)
-        };
-        let ri = *ratings.get(&pi).unwrap();
-        let rj = *ratings.get(&pj).unwrap();
-        let expected_i = 1.0 / (1.0 + 10f32.powf((rj - ri) / 400.0));
-        let expected_j = 1.0 / (1.0 + 10f32.powf((ri - rj) / 400.0));
-        let score_i = if win_i { 1.0 } else { 0.0 };
-        let score_j = 1.0 - score_i;
-        *ratings.get_mut(&pi).unwrap() += k_factor * (score_i - expected_i);
-        *ratings.get_mut(&pj).unwrap() += k_factor * (score_j - expected_j);
+        let h2h = HeadToHead::play_brains(&sim_cfg, &evo_cfg, &brain(i), &brain(j), &seeds);
+        bar.inc(1);
+        h2h
+    });
+    bar.finish();
+    println!(); // newline after progress bar
+    // Elo from each pairing's aggregate score, in the order pairings were played
+    let ratings = tournament::elo(total, &outcomes, k_factor);
+    let intervals = tournament::elo_intervals(total, &outcomes, k_factor, 500, 0);
+    let mut ranked: Vec<usize> = (0..total).collect();
+    ranked.sort_by(|&x, &y| ratings[y].total_cmp(&ratings[x]));
+    println!("Top ratings (95% bootstrap interval):");
+    for &p in ranked.iter().take(10) {
+        println!("  {:>7.1} [{:>7.1}, {:>7.1}]  {}", ratings[p], intervals[p].0, intervals[p].1, names[p]);
     }
     // Write Elo ratings to JSON
     let elo_path = format!("{}/elo_ratings.json", opts.pop_path);
-    let out_list: Vec<_> = ratings.iter()
-        .map(|(path, &elo)| json!({ "path": path, "elo": elo }))
+    let out_list: Vec<_> = ranked.iter()
+        .map(|&p| json!({ "path": names[p], "elo": ratings[p], "elo_low": intervals[p].0, "elo_high": intervals[p].1 }))
         .collect();
     fs::write(&elo_path, serde_json::to_string_pretty(&out_list).unwrap())
         .expect("Failed to write elo_ratings.json");
     println!("Wrote Elo ratings to {}", elo_path);
-    // Profiling summary
-    let phys_ns = PHYS_TIME_NS.load(Ordering::Relaxed);
-    let phys_count = PHYS_COUNT.load(Ordering::Relaxed);
-    if phys_count > 0 {
-        println!("Physics steps: {}, avg step time: {:.3} ms", phys_count, phys_ns as f64 / phys_count as f64 / 1e6);
-    }
-    let infer_ns = INFER_TIME_NS.load(Ordering::Relaxed);
-    let infer_count = INFER_COUNT.load(Ordering::Relaxed);
-    if infer_count > 0 {
-        println!("Inference calls: {}, avg inference time: {:.3} ms", infer_count, infer_ns as f64 / infer_count as f64 / 1e6);
-    }
-    let http_ns = HTTP_TIME_NS.load(Ordering::Relaxed);
-    if http_ns > 0 {
-        println!("HTTP overhead total: {:.3} ms", http_ns as f64 / 1e6);
+    // head-to-head matrix, per-champion stats, and shareable reports next to the ratings
+    let report = Report::new(&names, &outcomes, &ratings, &intervals);
+    if report.entrants.len() <= 20 {
+        println!("Head-to-head (row's score vs column, ranked as above):\n{}", report.matrix_table());
     }
-    let remote_ns = REMOTE_INFER_NS.load(Ordering::Relaxed);
-    if remote_ns > 0 {
-        println!("Remote inference total: {:.3} ms", remote_ns as f64 / 1e6);
+    let path = |file: &str| format!("{}/{}", opts.pop_path, file);
+    fs::File::create(path("h2h_matrix.csv"))
+        .and_then(|f| report.write_matrix_csv(std::io::BufWriter::new(f)))
+        .expect("Failed to write h2h_matrix.csv");
+    fs::File::create(path("champion_stats.csv"))
+        .and_then(|f| report.write_stats_csv(std::io::BufWriter::new(f)))
+        .expect("Failed to write champion_stats.csv");
+    fs::write(path("tournament_report.md"), report.to_markdown()).expect("Failed to write tournament_report.md");
+    fs::write(path("tournament_report.html"), report.to_html()).expect("Failed to write tournament_report.html");
+    println!("Wrote h2h_matrix.csv, champion_stats.csv, and tournament_report.{{md,html}} to {}", opts.pop_path);
+    #[cfg(feature = "db")]
+    if let Some(path) = &opts.db {
+        let pairings = outcomes.iter().map(|o| (names[o.a].as_str(), names[o.b].as_str(), &o.result));
+        let record = ResultsDb::open(path).and_then(|mut db| {
+            db.record("tournament", &sim_cfg, &evo_cfg, k_factor, pairings)?;
+            names.iter().map(|n| db.rating(n)).collect::<Result<Vec<_>, _>>()
+        });
+        match record {
+            Ok(stored) => {
+                let mut stored: Vec<_> = stored.into_iter().flatten().collect();
+                stored.sort_by(|x, y| y.elo.total_cmp(&x.elo));
+                println!("Stored ratings across runs ({}):", path);
+                for r in stored.iter().take(10) {
+                    println!("  {:>7.1} {:>6} games  {}", r.elo, r.games, r.player);
+                }
+            }
+            Err(e) => eprintln!("Failed to record results in {}: {}", path, e),
+        }
     }
+    log_counters(&Counters::take(), "tournament perf");
+}
+
+/// Log a profiling counter snapshot as one structured event
+fn log_counters(counters: &Counters, message: &str) {
+    info!(
+        sim_ticks = counters.sim_ticks,
+        sim_avg_us = counters.sim_avg_us(),
+        matches = counters.matches,
+        match_avg_ms = counters.match_avg_ms(),
+        infer_calls = counters.infer_calls,
+        infer_avg_us = counters.infer_avg_us(),
+        http_ms = counters.http_ns as f64 / 1e6,
+        remote_infer_ms = counters.remote_infer_ns as f64 / 1e6,
+        "{}", message,
+    );
 }
 
 /// Full pipeline: train, tournament, then replay info
@@ -762,8 +2055,7 @@ fn run_pipeline(opts: &PipelineOpts) {
     let tour_opts = TournamentOpts {
         pop_path: format!("out/{}", run_id),
         pop_files: Vec::new(),
-        verbose: opts.tour.verbose,
-        include_naive: opts.tour.include_naive,
+        ..opts.tour.clone()
     };
     run_tournament(&tour_opts);
     // 3) Summarize replay path
@@ -0,0 +1,125 @@
+//! Seeded procedural maps: obstacle clusters, wreck fields and team spawn
+//! rings, laid out from the density knobs in `Config::map_gen`. Obstacles
+//! are short-range repulsor wells (see `gravity`), so agents sense and
+//! steer around them like any other well. The same seed and knobs always
+//! give the same map, so a match seed fixes its terrain.
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use crate::domain::Vec2;
+use crate::gravity::GravityWell;
+use crate::math;
+
+/// Map generation knobs; the defaults generate nothing
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MapGen {
+    /// Number of obstacle clusters (0 = none)
+    pub obstacle_clusters: usize,
+    /// Obstacles per cluster
+    pub obstacles_per_cluster: usize,
+    /// Radius of each obstacle's push
+    pub obstacle_radius: f32,
+    /// Push at an obstacle's center (velocity change per tick)
+    pub obstacle_strength: f32,
+    /// Max distance of a cluster's obstacles from its center
+    pub cluster_spread: f32,
+    /// Number of wreck fields (0 = none)
+    pub wreck_fields: usize,
+    /// Wrecks per field
+    pub wrecks_per_field: usize,
+    /// Max distance of a field's wrecks from its center
+    pub wreck_spread: f32,
+    /// Team spawn ring radius as a fraction of half the shorter map side
+    /// (0 = every agent starts at the map center)
+    pub spawn_ring: f32,
+}
+
+impl Default for MapGen {
+    fn default() -> Self {
+        MapGen {
+            obstacle_clusters: 0,
+            obstacles_per_cluster: 3,
+            obstacle_radius: 20.0,
+            obstacle_strength: 0.02,
+            cluster_spread: 40.0,
+            wreck_fields: 0,
+            wrecks_per_field: 4,
+            wreck_spread: 25.0,
+            spawn_ring: 0.0,
+        }
+    }
+}
+
+/// One generated layout
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Map {
+    /// Obstacles, as repulsor wells
+    pub obstacles: Vec<GravityWell>,
+    /// Wreck positions
+    pub wrecks: Vec<Vec2>,
+    /// Spawn point per team, in team order (empty without a spawn ring)
+    pub spawns: Vec<Vec2>,
+}
+
+impl MapGen {
+    /// Whether any knob generates something
+    pub fn enabled(&self) -> bool {
+        self.obstacle_clusters > 0 || self.wreck_fields > 0 || self.spawn_ring > 0.0
+    }
+
+    /// Lay out a `width` x `height` map for `teams` teams from `seed`
+    pub fn generate(&self, width: u32, height: u32, teams: usize, seed: u64) -> Map {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let (w, h) = (width as f32, height as f32);
+        let anywhere = |rng: &mut StdRng| Vec2 { x: rng.gen::<f32>() * w, y: rng.gen::<f32>() * h };
+        let around = |rng: &mut StdRng, c: Vec2, spread: f32| {
+            let (a, r) = (rng.gen::<f32>() * std::f32::consts::TAU, rng.gen::<f32>().sqrt() * spread);
+            Vec2 { x: c.x + math::cos(a) * r, y: c.y + math::sin(a) * r }.wrap(w, h)
+        };
+        let mut map = Map::default();
+        for _ in 0..self.obstacle_clusters {
+            let c = anywhere(&mut rng);
+            for _ in 0..self.obstacles_per_cluster {
+                let p = around(&mut rng, c, self.cluster_spread);
+                map.obstacles.push(GravityWell { x: p.x, y: p.y, strength: -self.obstacle_strength, radius: self.obstacle_radius });
+            }
+        }
+        for _ in 0..self.wreck_fields {
+            let c = anywhere(&mut rng);
+            for _ in 0..self.wrecks_per_field {
+                map.wrecks.push(around(&mut rng, c, self.wreck_spread));
+            }
+        }
+        if self.spawn_ring > 0.0 && teams > 0 {
+            let radius = self.spawn_ring * w.min(h) / 2.0;
+            let turn = rng.gen::<f32>() * std::f32::consts::TAU;
+            for t in 0..teams {
+                let a = turn + std::f32::consts::TAU * t as f32 / teams as f32;
+                map.spawns.push(Vec2 { x: w / 2.0 + math::cos(a) * radius, y: h / 2.0 + math::sin(a) * radius });
+            }
+        }
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_map() {
+        let mapgen = MapGen { obstacle_clusters: 2, wreck_fields: 1, spawn_ring: 0.5, ..MapGen::default() };
+        let map = mapgen.generate(400, 200, 2, 9);
+        assert_eq!(map, mapgen.generate(400, 200, 2, 9));
+        assert_ne!(map, mapgen.generate(400, 200, 2, 10));
+        assert_eq!(map.obstacles.len(), 6);
+        assert!(map.obstacles.iter().all(|o| o.strength < 0.0 && (0.0..=400.0).contains(&o.x) && (0.0..=200.0).contains(&o.y)));
+        assert_eq!(map.wrecks.len(), 4);
+        // teams face each other across the center on a ring of radius 50
+        let (a, b) = (map.spawns[0], map.spawns[1]);
+        assert!((a.x + b.x - 400.0).abs() < 1e-3 && (a.y + b.y - 200.0).abs() < 1e-3);
+        assert!((Vec2 { x: a.x - 200.0, y: a.y - 100.0 }.length() - 50.0).abs() < 1e-3);
+        assert!(!MapGen::default().enabled());
+    }
+}
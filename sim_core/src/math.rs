@@ -0,0 +1,43 @@
+//! Transcendental functions on the simulation path (activations, spawn
+//! placement). Platform libms differ in the last bits of `exp`, `sin` and
+//! friends, so by default wasm32 and x86_64 replays can drift apart. With
+//! the `deterministic` feature these come from the pure-Rust `libm` crate
+//! instead and are bit-identical on every target. Arithmetic and `sqrt` are
+//! IEEE-exact either way and stay as they are.
+
+/// Whether this build uses the portable implementations
+pub const DETERMINISTIC: bool = cfg!(feature = "deterministic");
+
+#[cfg(feature = "deterministic")]
+mod imp {
+    pub fn exp(x: f32) -> f32 { libm::expf(x) }
+    pub fn tanh(x: f32) -> f32 { libm::tanhf(x) }
+    pub fn sin(x: f32) -> f32 { libm::sinf(x) }
+    pub fn cos(x: f32) -> f32 { libm::cosf(x) }
+}
+
+#[cfg(not(feature = "deterministic"))]
+mod imp {
+    pub fn exp(x: f32) -> f32 { x.exp() }
+    pub fn tanh(x: f32) -> f32 { x.tanh() }
+    pub fn sin(x: f32) -> f32 { x.sin() }
+    pub fn cos(x: f32) -> f32 { x.cos() }
+}
+
+pub use imp::{cos, exp, sin, tanh};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn agrees_with_std() {
+        for i in -40..=40 {
+            let x = i as f32 * 0.173;
+            assert!((exp(x) - x.exp()).abs() <= 1e-6 * x.exp().max(1.0));
+            assert!((tanh(x) - x.tanh()).abs() <= 1e-6);
+            assert!((sin(x) - x.sin()).abs() <= 1e-6);
+            assert!((cos(x) - x.cos()).abs() <= 1e-6);
+        }
+    }
+}
@@ -1,39 +1,150 @@
 use crate::Simulation;
-use crate::{AGENT_STRIDE, IDX_X, IDX_Y};
+use crate::{AGENT_STRIDE, IDX_X, IDX_Y, IDX_HEALTH};
 use crate::domain::{Action, Vec2};
 use crate::config::DistanceMode;
+use crate::status::{Status, SLOW_FACTOR};
+
+/// `v` scaled down to at most `max` long
+fn cap(v: Vec2, max: f32) -> Vec2 {
+    let len2 = v.x * v.x + v.y * v.y;
+    if len2 > max * max {
+        let factor = max / len2.sqrt();
+        Vec2 { x: v.x * factor, y: v.y * factor }
+    } else {
+        v
+    }
+}
 
 /// Execute the movement phase (thrust integration) outside of Simulation.
+///
+/// Each living agent keeps `friction` of last tick's velocity and adds its
+/// thrust, capped at `max_accel`, and the pull of any gravity wells; the sum
+/// is capped at its max speed. Agents that do not thrust coast. Under
+/// `legacy_motion` only thrusting agents move, at their thrust scaled by
+/// `friction` plus the pull.
 pub fn run(sim: &mut Simulation) {
     let w = sim.width as f32;
     let h = sim.height as f32;
     let friction = sim.config.friction;
-    let max_speed = sim.config.max_speed;
-
-    for (&id, action) in sim.commands.iter() {
-        if let Action::Thrust(v) = action {
-            let base = id * AGENT_STRIDE;
-            let x = sim.agents_data[base + IDX_X];
-            let y = sim.agents_data[base + IDX_Y];
-
-            // apply friction to thrust and clamp max speed
-            let mut vx = v.x * friction;
-            let mut vy = v.y * friction;
-            let speed2 = vx * vx + vy * vy;
-            if speed2 > max_speed * max_speed {
-                let factor = max_speed / speed2.sqrt();
-                vx *= factor;
-                vy *= factor;
+    let legacy = sim.config.legacy_motion;
+
+    let commands = std::mem::take(&mut sim.commands);
+    for id in 0..sim.agents_data.len() / AGENT_STRIDE {
+        let thrust = match commands.get(&id) {
+            Some(Action::Thrust(v)) => Some(*v),
+            _ => None,
+        };
+        let base = id * AGENT_STRIDE;
+        let x = sim.agents_data[base + IDX_X];
+        let y = sim.agents_data[base + IDX_Y];
+        let v = if legacy {
+            let Some(t) = thrust else { continue };
+            Vec2 { x: t.x * friction, y: t.y * friction }
+        } else {
+            if sim.agents_data[base + IDX_HEALTH] <= 0.0 {
+                continue;
             }
+            let prev = sim.agent_velocity(id);
+            let accel = thrust.map_or(Vec2 { x: 0.0, y: 0.0 }, |t| cap(t, sim.config.max_accel));
+            Vec2 { x: prev.x * friction + accel.x, y: prev.y * friction + accel.y }
+        };
+        let pull = sim.gravity_at(Vec2 { x, y });
+        let v = Vec2 { x: v.x + pull.x, y: v.y + pull.y };
+        if let Some(t) = thrust {
+            sim.facing_mut(id).steer(t);
+        }
+        let mut max_speed = sim.agent_max_speed(id);
+        if sim.agent_status(id).has(Status::Slowed) {
+            max_speed *= SLOW_FACTOR;
+        }
+        let mut v = cap(v, max_speed);
 
-            // integrate velocity and wrap (toroidal) or clamp (euclidean)
-            let newpos = Vec2 { x: x + vx, y: y + vy };
-            let moved = match sim.config.distance_mode {
-                DistanceMode::Toroidal  => newpos.wrap(w, h),
-                DistanceMode::Euclidean => Vec2 { x: newpos.x.clamp(0.0, w), y: newpos.y.clamp(0.0, h) },
-            };
-            sim.agents_data[base + IDX_X] = moved.x;
-            sim.agents_data[base + IDX_Y] = moved.y;
+        // integrate velocity and wrap (toroidal) or clamp (euclidean)
+        let newpos = Vec2 { x: x + v.x, y: y + v.y };
+        let moved = match sim.config.distance_mode {
+            DistanceMode::Toroidal  => newpos.wrap(w, h),
+            DistanceMode::Euclidean => {
+                let clamped = Vec2 { x: newpos.x.clamp(0.0, w), y: newpos.y.clamp(0.0, h) };
+                // walls stop motion into them
+                if clamped.x != newpos.x {
+                    v.x = 0.0;
+                }
+                if clamped.y != newpos.y {
+                    v.y = 0.0;
+                }
+                clamped
+            }
+        };
+        sim.agents_data[base + IDX_X] = moved.x;
+        sim.agents_data[base + IDX_Y] = moved.y;
+        if !legacy {
+            sim.set_velocity(id, v);
         }
     }
+    sim.commands = commands;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TeamOverrides;
+
+    #[test]
+    fn team_speed_override_caps_thrust() {
+        let mut sim = Simulation::empty(100, 100);
+        sim.config.max_speed = 1.0;
+        sim.config.friction = 1.0;
+        sim.config.max_accel = 10.0;
+        sim.config.team_overrides.push(TeamOverrides { team: 1, max_speed: Some(0.25), ..Default::default() });
+        sim.agents_data.extend(&[10.0, 10.0, 0.0, 100.0, 0.0, 0.0, 10.0, 10.0, 1.0, 100.0, 0.0, 0.0]);
+        sim.commands.insert(0, Action::Thrust(Vec2 { x: 5.0, y: 0.0 }));
+        sim.commands.insert(1, Action::Thrust(Vec2 { x: 5.0, y: 0.0 }));
+        run(&mut sim);
+        assert!((sim.agents_data[IDX_X] - 11.0).abs() < 1e-5);
+        assert!((sim.agents_data[AGENT_STRIDE + IDX_X] - 10.25).abs() < 1e-5);
+
+        sim.inflict(0, Status::Slowed, 5);
+        sim.commands.remove(&1);
+        run(&mut sim);
+        assert!((sim.agents_data[IDX_X] - 11.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn velocity_builds_up_and_coasts_under_friction() {
+        let mut sim = Simulation::empty(100, 100);
+        sim.config.max_speed = 1.0;
+        sim.config.max_accel = 0.25;
+        sim.config.friction = 0.5;
+        sim.agents_data.extend(&[10.0, 10.0, 0.0, 100.0, 0.0, 0.0]);
+        // thrust is capped at max_accel, and builds on what friction leaves
+        sim.commands.insert(0, Action::Thrust(Vec2 { x: 5.0, y: 0.0 }));
+        run(&mut sim);
+        assert!((sim.agent_velocity(0).x - 0.25).abs() < 1e-6);
+        run(&mut sim);
+        assert!((sim.agent_velocity(0).x - 0.375).abs() < 1e-6);
+        // no thrust: the agent coasts and slows
+        sim.commands.clear();
+        run(&mut sim);
+        assert!((sim.agent_velocity(0).x - 0.1875).abs() < 1e-6);
+        assert!((sim.agents_data[IDX_X] - 10.8125).abs() < 1e-5);
+
+        // the old model moves only while thrusting, at thrust * friction
+        sim.config.legacy_motion = true;
+        run(&mut sim);
+        assert!((sim.agents_data[IDX_X] - 10.8125).abs() < 1e-5);
+        sim.commands.insert(0, Action::Thrust(Vec2 { x: 0.5, y: 0.0 }));
+        run(&mut sim);
+        assert!((sim.agents_data[IDX_X] - 11.0625).abs() < 1e-5);
+    }
+
+    #[test]
+    fn gravity_wells_pull_idle_agents() {
+        let mut sim = Simulation::empty(100, 100);
+        sim.config.max_speed = 1.0;
+        sim.config.gravity_wells.push(crate::gravity::GravityWell { x: 20.0, y: 10.0, strength: 0.2, radius: 20.0 });
+        sim.agents_data.extend(&[10.0, 10.0, 0.0, 100.0, 0.0, 0.0]);
+        run(&mut sim);
+        assert!((sim.agents_data[IDX_X] - 10.1).abs() < 1e-5);
+        assert_eq!(sim.agents_data[IDX_Y], 10.0);
+    }
 }
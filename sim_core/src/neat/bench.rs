@@ -0,0 +1,239 @@
+//! Synthetic core-loop workloads for `neat_train bench --target`: physics
+//! steps, whole matches, and sensor scans over N agents for M ticks, each
+//! reported as latency percentiles plus heap allocations per operation.
+//! Allocations are only counted when the binary installs `CountingAlloc`
+//! as its global allocator.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+use crate::brain::Brain;
+use crate::domain::{Action, Vec2, Weapon, WorldView};
+use crate::{Config, Simulation};
+use super::brain::NeatBrain;
+use super::genome::Genome;
+use super::runner::{run_match_seeded, EvolutionConfig};
+
+static ALLOCS: AtomicU64 = AtomicU64::new(0);
+static ALLOC_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// System allocator that counts allocations and bytes requested
+pub struct CountingAlloc;
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCS.fetch_add(1, Ordering::Relaxed);
+        ALLOC_BYTES.fetch_add(layout.size() as u64, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCS.fetch_add(1, Ordering::Relaxed);
+        ALLOC_BYTES.fetch_add(new_size as u64, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+/// Allocations and bytes allocated so far (zero without `CountingAlloc`)
+pub fn allocations() -> (u64, u64) {
+    (ALLOCS.load(Ordering::Relaxed), ALLOC_BYTES.load(Ordering::Relaxed))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// `Simulation::step` with cheap scripted brains
+    Physics,
+    /// Whole matches between freshly initialized genomes
+    Match,
+    /// `Simulation::scan` for every agent
+    Scan,
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Target::Physics => "physics",
+            Target::Match => "match",
+            Target::Scan => "scan",
+        })
+    }
+}
+
+/// Workload size
+#[derive(Debug, Clone, Copy)]
+pub struct Workload {
+    pub agents: usize,
+    pub ticks: usize,
+    /// Matches played (`Target::Match` only)
+    pub matches: usize,
+    pub seed: u64,
+}
+
+/// Timings and allocations of one workload; one sample per operation
+/// (a tick, a match, or a scan of every agent)
+#[derive(Debug, Clone)]
+pub struct BenchReport {
+    pub target: Target,
+    pub workload: Workload,
+    /// Sorted ascending
+    pub samples_ns: Vec<u64>,
+    pub allocs: u64,
+    pub alloc_bytes: u64,
+}
+
+impl BenchReport {
+    fn new(target: Target, workload: Workload, mut samples_ns: Vec<u64>, before: (u64, u64)) -> Self {
+        let after = allocations();
+        samples_ns.sort_unstable();
+        BenchReport { target, workload, samples_ns, allocs: after.0 - before.0, alloc_bytes: after.1 - before.1 }
+    }
+
+    /// Nearest-rank percentile, `p` in [0, 100]
+    pub fn percentile(&self, p: f64) -> u64 {
+        if self.samples_ns.is_empty() {
+            return 0;
+        }
+        let rank = ((p / 100.0) * self.samples_ns.len() as f64).ceil() as usize;
+        self.samples_ns[rank.clamp(1, self.samples_ns.len()) - 1]
+    }
+
+    pub fn mean_ns(&self) -> f64 {
+        self.samples_ns.iter().sum::<u64>() as f64 / self.samples_ns.len().max(1) as f64
+    }
+
+    pub fn allocs_per_op(&self) -> f64 {
+        self.allocs as f64 / self.samples_ns.len().max(1) as f64
+    }
+
+    pub fn bytes_per_op(&self) -> f64 {
+        self.alloc_bytes as f64 / self.samples_ns.len().max(1) as f64
+    }
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let us = |ns: u64| ns as f64 / 1e3;
+        writeln!(
+            f, "{}: {} agents, {} ticks, {} samples",
+            self.target, self.workload.agents, self.workload.ticks, self.samples_ns.len(),
+        )?;
+        writeln!(
+            f, "  latency µs: p50 {:.1}  p90 {:.1}  p99 {:.1}  max {:.1}  mean {:.1}",
+            us(self.percentile(50.0)), us(self.percentile(90.0)), us(self.percentile(99.0)),
+            us(self.percentile(100.0)), self.mean_ns() / 1e3,
+        )?;
+        write!(f, "  allocations: {:.1}/op, {:.0} bytes/op", self.allocs_per_op(), self.bytes_per_op())
+    }
+}
+
+/// Circles and fires whenever an enemy is in range, so a step exercises
+/// movement and combat without the cost of a network
+struct Scripted {
+    heading: f32,
+}
+
+impl Brain for Scripted {
+    fn think(&mut self, view: &WorldView, _inputs: &[f32]) -> Action {
+        self.heading += 0.05;
+        let enemy_in_range = view.positions.iter().enumerate().any(|(i, &p)| {
+            view.teams[i] != view.self_team && view.healths[i] > 0.0
+                && view.self_pos.torus_delta(p, view.world_width, view.world_height).length() <= view.attack_range
+        });
+        if enemy_in_range {
+            Action::Fire { weapon: Weapon::Laser { damage: 1.0, range: view.attack_range } }
+        } else {
+            Action::Thrust(Vec2 { x: self.heading.cos(), y: self.heading.sin() })
+        }
+    }
+}
+
+/// Two teams splitting `agents`, as evolution would field them
+fn evo_config(workload: &Workload) -> EvolutionConfig {
+    EvolutionConfig {
+        num_teams: 2,
+        team_size: (workload.agents / 2).max(1),
+        max_ticks: workload.ticks,
+        early_exit: false,
+        ..Default::default()
+    }
+}
+
+fn scripted_sim(sim_cfg: &Config, workload: &Workload) -> Simulation {
+    let evo_cfg = evo_config(workload);
+    let agents: Vec<(Box<dyn Brain>, u32)> = (0..workload.agents)
+        .map(|i| (Box::new(Scripted { heading: i as f32 }) as Box<dyn Brain>, (i % 2) as u32))
+        .collect();
+    let mut sim = Simulation::with_brains(evo_cfg.map_width, evo_cfg.map_height, sim_cfg.clone(), agents);
+    sim.reseed(workload.seed);
+    sim
+}
+
+/// Run `target` over `workload` on the calling thread
+pub fn run(target: Target, sim_cfg: &Config, workload: Workload) -> BenchReport {
+    match target {
+        Target::Physics => {
+            let mut sim = scripted_sim(sim_cfg, &workload);
+            let mut samples = Vec::with_capacity(workload.ticks);
+            let before = allocations();
+            for _ in 0..workload.ticks {
+                let start = Instant::now();
+                sim.step();
+                samples.push(start.elapsed().as_nanos() as u64);
+            }
+            BenchReport::new(target, workload, samples, before)
+        }
+        Target::Scan => {
+            let mut sim = scripted_sim(sim_cfg, &workload);
+            let mut samples = Vec::with_capacity(workload.ticks);
+            let before = allocations();
+            for _ in 0..workload.ticks {
+                let start = Instant::now();
+                for i in 0..workload.agents {
+                    std::hint::black_box(sim.scan(i, sim_cfg.nearest_k_enemies, sim_cfg.scan_max_dist));
+                }
+                samples.push(start.elapsed().as_nanos() as u64);
+                sim.step();
+            }
+            BenchReport::new(target, workload, samples, before)
+        }
+        Target::Match => {
+            let evo_cfg = evo_config(&workload);
+            let mut rng = <rand::rngs::StdRng as rand::SeedableRng>::seed_from_u64(workload.seed);
+            let mut genome = Genome::new();
+            genome.initialize(sim_cfg, &evo_cfg, &mut rng);
+            let mut samples = Vec::with_capacity(workload.matches);
+            let before = allocations();
+            for m in 0..workload.matches {
+                let agents: Vec<(Box<dyn Brain>, u32)> = (0..workload.agents)
+                    .map(|i| (Box::new(NeatBrain::for_config(genome.clone(), sim_cfg)) as Box<dyn Brain>, (i % 2) as u32))
+                    .collect();
+                let start = Instant::now();
+                std::hint::black_box(run_match_seeded(sim_cfg, &evo_cfg, agents, workload.seed + m as u64));
+                samples.push(start.elapsed().as_nanos() as u64);
+            }
+            BenchReport::new(target, workload, samples, before)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_one_per_operation() {
+        let sim_cfg = Config::default();
+        let workload = Workload { agents: 4, ticks: 20, matches: 2, seed: 1 };
+        let physics = run(Target::Physics, &sim_cfg, workload);
+        assert_eq!(physics.samples_ns.len(), 20);
+        assert!(physics.percentile(50.0) <= physics.percentile(99.0));
+        assert_eq!(physics.percentile(100.0), *physics.samples_ns.last().unwrap());
+        assert_eq!(run(Target::Match, &sim_cfg, workload).samples_ns.len(), 2);
+        assert!(run(Target::Scan, &sim_cfg, workload).to_string().starts_with("scan: 4 agents, 20 ticks, 20 samples"));
+    }
+}
@@ -1,22 +1,40 @@
 use crate::brain::Brain;
+use crate::config::{Config, InferenceBackend};
 use crate::domain::{WorldView, Action, Vec2, Weapon};
+use crate::ship::slot_for_output;
+use super::bundle::{BundleError, ChampionBundle};
 use super::genome::Genome;
+#[cfg(all(feature = "onnxruntime", not(target_arch = "wasm32")))]
+use super::onnx_runtime::OrtModel;
+#[cfg(feature = "tract")]
+use super::onnx_tract::TractModel;
+use std::collections::HashMap;
 use std::time::Instant;
 use std::sync::atomic::{AtomicU64, Ordering};
 use serde::{Serialize, Deserialize};
 
 #[cfg(not(target_arch = "wasm32"))]
 use reqwest::blocking::Client;
+#[cfg(not(target_arch = "wasm32"))]
+use std::{fmt, sync::OnceLock, time::Duration};
 
 /// Adapter wrapping a Genome under the Brain trait
 #[derive(Clone)]
 pub struct NeatBrain {
     genome: Genome,
+    /// Node activations from the previous tick, read by recurrent connections
+    state: HashMap<usize, f32>,
     buffer: Vec<Vec<f32>>,
     batch_size: usize,
-    #[cfg(not(target_arch = "wasm32"))]
-    client: Client,
     url: String,
+    /// Protocol spoken to the service at `url`
+    backend: InferenceBackend,
+    /// onnxruntime session for the genome, when that backend is selected
+    #[cfg(all(feature = "onnxruntime", not(target_arch = "wasm32")))]
+    ort: Option<std::sync::Arc<std::sync::Mutex<OrtModel>>>,
+    /// tract plan for the genome, when that backend is selected
+    #[cfg(feature = "tract")]
+    tract: Option<std::sync::Arc<TractModel>>,
 }
 
 /// Cumulative inference time and count for profiling
@@ -24,6 +42,8 @@ pub static INFER_TIME_NS: AtomicU64 = AtomicU64::new(0);
 pub static INFER_COUNT: AtomicU64 = AtomicU64::new(0);
 pub static HTTP_TIME_NS: AtomicU64 = AtomicU64::new(0);
 pub static REMOTE_INFER_NS: AtomicU64 = AtomicU64::new(0);
+/// Brains that gave up on the inference service and fell back to CPU
+pub static REMOTE_FALLBACKS: AtomicU64 = AtomicU64::new(0);
 
 #[derive(Serialize)]
 struct InferenceRequest {
@@ -40,80 +60,249 @@ impl NeatBrain {
     pub fn new(genome: Genome, batch_size: usize, url: String) -> Self {
         NeatBrain {
             genome,
+            state: HashMap::new(),
             buffer: Vec::new(),
             batch_size,
-            #[cfg(not(target_arch = "wasm32"))]
-            client: Client::new(),
             url,
+            backend: InferenceBackend::Http,
+            #[cfg(all(feature = "onnxruntime", not(target_arch = "wasm32")))]
+            ort: None,
+            #[cfg(feature = "tract")]
+            tract: None,
         }
     }
-}
 
-impl Brain for NeatBrain {
-    fn think(&mut self, view: &WorldView, inputs: &[f32]) -> Action {
-        // Choose inference path: Python service, ONNX, or CPU
-        let outputs: Vec<f32>;
-        #[cfg(not(target_arch = "wasm32"))]
-        if !self.url.is_empty() {
-            // Remote inference per call
-            let start_http = Instant::now();
-            let req = InferenceRequest { inputs: vec![inputs.to_vec()] };
-            let endpoint = format!("{}/infer", self.url);
-            eprintln!("[NeatBrain] POST to {} with payload: {:?}", endpoint, req.inputs);
-            let response = self.client.post(&endpoint)
-                .json(&req)
-                .send()
-                .unwrap_or_else(|e| panic!("HTTP POST failed to {}: {}", endpoint, e));
-            eprintln!("[NeatBrain] Received status: {}", response.status());
-            let resp: InferenceResponse = response.json()
-                .unwrap_or_else(|e| panic!("JSON parse failed from {}: {}", endpoint, e));
-            let http_ns = start_http.elapsed().as_nanos() as u64;
-            HTTP_TIME_NS.fetch_add(http_ns, Ordering::Relaxed);
-            let remote_ns = (resp.duration_ms * 1e6) as u64;
-            REMOTE_INFER_NS.fetch_add(remote_ns, Ordering::Relaxed);
-            let outputs = resp.outputs.into_iter().next().unwrap();
-            // Decode outputs to Action
-            if outputs.len() >= 3 {
-                let vx = outputs[0];
-                let vy = outputs[1];
-                let thrust = Vec2 { x: vx, y: vy };
-                if outputs[2] > 0.5 {
-                    // safety override: only fire if enemy within attack_range
-                    let mut min_dist = f32::MAX;
-                    for (i, &pos) in view.positions.iter().enumerate() {
-                        if i == view.self_idx || view.healths[i] <= 0.0 || view.teams[i] == view.self_team {
-                            continue;
-                        }
-                        let delta = view.self_pos.torus_delta(pos, view.world_width, view.world_height);
-                        let dist = delta.length();
-                        if dist < min_dist { min_dist = dist; }
-                    }
-                    if min_dist <= view.attack_range {
-                        return Action::Fire { weapon: Weapon::Laser { damage: 1.0, range: view.attack_range } };
-                    } else {
-                        return Action::Thrust(thrust);
-                    }
+    /// Brain using the inference path `sim_cfg` selects: the Python service
+    /// (over `inference_backend`) when a URL is set, onnxruntime when `use_onnx_gpu` is set (feature
+    /// `onnxruntime`), tract when `use_tract` is set (feature `tract`),
+    /// otherwise CPU `activate`. Backends that cannot load the genome fall
+    /// back to CPU.
+    pub fn for_config(genome: Genome, sim_cfg: &Config) -> Self {
+        #[allow(unused_mut)]
+        let mut brain = NeatBrain::new(genome, sim_cfg.batch_size, sim_cfg.python_service_url.clone().unwrap_or_default());
+        brain.backend = sim_cfg.inference_backend;
+        #[cfg(all(feature = "onnxruntime", not(target_arch = "wasm32")))]
+        if sim_cfg.use_onnx_gpu {
+            brain.ort = match OrtModel::from_genome(&brain.genome, true) {
+                Ok(model) => Some(std::sync::Arc::new(std::sync::Mutex::new(model))),
+                Err(e) => {
+                    tracing::warn!(error = %e, "onnxruntime unavailable, using CPU");
+                    None
+                }
+            };
+        }
+        #[cfg(feature = "tract")]
+        if sim_cfg.use_tract {
+            brain.tract = match TractModel::from_genome(&brain.genome) {
+                Ok(model) => Some(std::sync::Arc::new(model)),
+                Err(e) => {
+                    tracing::warn!(error = %e, "tract unavailable, using CPU");
+                    None
+                }
+            };
+        }
+        brain
+    }
+
+    /// Brain for a bundled champion, refusing one trained on sensors other
+    /// than those `sim_cfg` produces
+    pub fn from_bundle(bundle: &ChampionBundle, sim_cfg: &Config) -> Result<Self, BundleError> {
+        bundle.check(sim_cfg)?;
+        Ok(NeatBrain::for_config(bundle.genome.clone(), sim_cfg))
+    }
+
+    /// Local forward pass: an attached onnxruntime or tract model, else `activate`
+    fn forward(&mut self, inputs: &[f32]) -> Vec<f32> {
+        #[cfg(all(feature = "onnxruntime", not(target_arch = "wasm32")))]
+        if let Some(model) = &self.ort {
+            let result = model.lock().unwrap().run(&[inputs.to_vec()]);
+            match result {
+                Ok(mut rows) => return rows.remove(0),
+                Err(e) => {
+                    tracing::warn!(error = %e, "onnxruntime failed, using CPU");
+                    self.ort = None;
                 }
-                return Action::Thrust(thrust);
             }
-            return Action::Idle;
         }
-        // CPU-only inference with timing on native
+        #[cfg(feature = "tract")]
+        if let Some(model) = &self.tract {
+            match model.run(&[inputs.to_vec()]) {
+                Ok(mut rows) => return rows.remove(0),
+                Err(e) => {
+                    tracing::warn!(error = %e, "tract failed, using CPU");
+                    self.tract = None;
+                }
+            }
+        }
+        self.genome.activate(inputs, &mut self.state)
+    }
+
+    /// `forward` with inference timing (timed on native only)
+    fn timed_forward(&mut self, inputs: &[f32]) -> Vec<f32> {
+        #[cfg(not(target_arch = "wasm32"))]
+        let infer_start = Instant::now();
+        let outputs = self.forward(inputs);
         #[cfg(not(target_arch = "wasm32"))]
         {
-            let infer_start = Instant::now();
-            outputs = self.genome.feed_forward(inputs);
             let infer_ns = infer_start.elapsed().as_nanos() as u64;
             INFER_TIME_NS.fetch_add(infer_ns, Ordering::Relaxed);
-            INFER_COUNT.fetch_add(1, Ordering::Relaxed);
+            super::prometheus::INFER_SECONDS.observe_ns(infer_ns);
         }
-        // WebAssembly inference without timing
-        #[cfg(target_arch = "wasm32")]
-        {
-            outputs = self.genome.feed_forward(inputs);
-            INFER_COUNT.fetch_add(1, Ordering::Relaxed);
+        INFER_COUNT.fetch_add(1, Ordering::Relaxed);
+        outputs
+    }
+
+    /// Send `inputs` to the inference service, retrying transient failures
+    #[cfg(not(target_arch = "wasm32"))]
+    fn remote_infer(&self, inputs: &[f32]) -> Result<Vec<f32>, InferenceError> {
+        let req = InferenceRequest { inputs: vec![inputs.to_vec()] };
+        let mut attempt = 0;
+        loop {
+            let start_http = Instant::now();
+            match self.call_service(&req) {
+                Ok(resp) => {
+                    HTTP_TIME_NS.fetch_add(start_http.elapsed().as_nanos() as u64, Ordering::Relaxed);
+                    REMOTE_INFER_NS.fetch_add((resp.duration_ms * 1e6) as u64, Ordering::Relaxed);
+                    return resp.outputs.into_iter().next().ok_or(InferenceError::Empty);
+                }
+                Err(e) if attempt < HTTP_RETRIES && e.is_transient() => {
+                    attempt += 1;
+                    std::thread::sleep(HTTP_BACKOFF * attempt);
+                }
+                Err(e) => return Err(e),
+            }
         }
-        // If we get at least 3 outputs: [vx, vy, fire_score]
+    }
+
+    /// One request to the service over the configured protocol
+    #[cfg(not(target_arch = "wasm32"))]
+    fn call_service(&self, req: &InferenceRequest) -> Result<InferenceResponse, InferenceError> {
+        match self.backend {
+            InferenceBackend::Http => post_infer(&format!("{}/infer", self.url), req),
+            #[cfg(feature = "grpc")]
+            InferenceBackend::Grpc => {
+                let (outputs, duration_ms) = super::grpc_client::infer(&self.url, &req.inputs, false)?;
+                Ok(InferenceResponse { outputs, duration_ms })
+            }
+            #[cfg(not(feature = "grpc"))]
+            InferenceBackend::Grpc => Err(InferenceError::Unsupported("gRPC (build with feature `grpc`)")),
+        }
+    }
+}
+
+/// Retries after the first failed request to the inference service
+#[cfg(not(target_arch = "wasm32"))]
+const HTTP_RETRIES: u32 = 2;
+/// Wait before the n-th retry is n times this
+#[cfg(not(target_arch = "wasm32"))]
+const HTTP_BACKOFF: Duration = Duration::from_millis(50);
+
+/// Failure of a call to the Python inference service
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+pub enum InferenceError {
+    /// Connection, timeout, or body decoding failure
+    Http(reqwest::Error),
+    /// The service answered with a non-success status
+    Status(reqwest::StatusCode),
+    /// gRPC transport or call failure
+    #[cfg(feature = "grpc")]
+    Grpc(super::grpc_client::GrpcError),
+    /// The response held no output rows
+    Empty,
+    /// The configured protocol is not compiled in
+    Unsupported(&'static str),
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl InferenceError {
+    /// Worth retrying: timeouts, connection errors, and 5xx responses
+    fn is_transient(&self) -> bool {
+        match self {
+            InferenceError::Http(e) => e.is_timeout() || e.is_connect(),
+            InferenceError::Status(status) => status.is_server_error(),
+            #[cfg(feature = "grpc")]
+            InferenceError::Grpc(e) => e.is_transient(),
+            InferenceError::Empty | InferenceError::Unsupported(_) => false,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl fmt::Display for InferenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InferenceError::Http(e) => write!(f, "HTTP error: {}", e),
+            InferenceError::Status(status) => write!(f, "service returned {}", status),
+            #[cfg(feature = "grpc")]
+            InferenceError::Grpc(e) => write!(f, "{}", e),
+            InferenceError::Empty => write!(f, "service returned no outputs"),
+            InferenceError::Unsupported(what) => write!(f, "{} is not supported by this build", what),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl std::error::Error for InferenceError {}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl From<reqwest::Error> for InferenceError {
+    fn from(e: reqwest::Error) -> Self { InferenceError::Http(e) }
+}
+
+#[cfg(all(feature = "grpc", not(target_arch = "wasm32")))]
+impl From<super::grpc_client::GrpcError> for InferenceError {
+    fn from(e: super::grpc_client::GrpcError) -> Self { InferenceError::Grpc(e) }
+}
+
+/// Client shared by every brain, so connections to the service are pooled
+#[cfg(not(target_arch = "wasm32"))]
+fn http_client() -> &'static Client {
+    static CLIENT: OnceLock<Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        Client::builder()
+            .connect_timeout(Duration::from_secs(2))
+            .timeout(Duration::from_secs(5))
+            .build()
+            .unwrap_or_else(|_| Client::new())
+    })
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn post_infer(endpoint: &str, req: &InferenceRequest) -> Result<InferenceResponse, InferenceError> {
+    let response = http_client().post(endpoint).json(req).send()?;
+    if !response.status().is_success() {
+        return Err(InferenceError::Status(response.status()));
+    }
+    Ok(response.json()?)
+}
+
+impl Brain for NeatBrain {
+    fn think(&mut self, view: &WorldView, inputs: &[f32]) -> Action {
+        // Choose inference path: Python service, then ONNX backends or CPU
+        #[cfg(not(target_arch = "wasm32"))]
+        let remote = if self.url.is_empty() {
+            None
+        } else {
+            match self.remote_infer(inputs) {
+                Ok(outputs) => Some(outputs),
+                Err(e) => {
+                    // stop calling a service that is down for the rest of this brain's life
+                    tracing::warn!(url = %self.url, error = %e, "inference service failed, falling back to CPU");
+                    REMOTE_FALLBACKS.fetch_add(1, Ordering::Relaxed);
+                    self.url.clear();
+                    None
+                }
+            }
+        };
+        #[cfg(target_arch = "wasm32")]
+        let remote: Option<Vec<f32>> = None;
+        let outputs = match remote {
+            Some(outputs) => outputs,
+            None => self.timed_forward(inputs),
+        };
+        // If we get at least 3 outputs: [vx, vy, fire_score], plus the weapon
+        // selector with `Config::weapon_select`
         if outputs.len() >= 3 {
             let vx = outputs[0];
             let vy = outputs[1];
@@ -130,6 +319,9 @@ impl Brain for NeatBrain {
                     if dist < min_dist { min_dist = dist; }
                 }
                 if min_dist <= view.attack_range {
+                    if let Some(&select) = outputs.get(3) {
+                        return Action::FireSlot { slot: slot_for_output(select) };
+                    }
                     return Action::Fire { weapon: Weapon::Laser { damage: 1.0, range: view.attack_range } };
                 } else {
                     return Action::Thrust(thrust);
@@ -139,4 +331,44 @@ impl Brain for NeatBrain {
         }
         Action::Idle
     }
+
+    fn reset(&mut self) {
+        self.state.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neat::config::EvolutionConfig;
+
+    #[test]
+    fn unreachable_service_falls_back_to_cpu() {
+        let mut genome = Genome::new();
+        genome.initialize(&Config::default(), &EvolutionConfig::default(), &mut rand::thread_rng());
+        let inputs = vec![0.25; genome.input_size()];
+        let (positions, teams, healths, shields) = (vec![Vec2 { x: 0.0, y: 0.0 }], vec![0], vec![100.0], vec![0.0]);
+        let view = WorldView {
+            self_idx: 0,
+            self_pos: positions[0],
+            self_team: 0,
+            self_health: 100.0,
+            self_shield: 0.0,
+            positions: &positions,
+            teams: &teams,
+            healths: &healths,
+            shields: &shields,
+            wreck_positions: &[],
+            wreck_pools: &[],
+            world_width: 100.0,
+            world_height: 100.0,
+            attack_range: 10.0,
+            sep_range: 5.0,
+        };
+        // nothing listens on the discard port
+        let mut remote = NeatBrain::new(genome.clone(), 1, "http://127.0.0.1:9".to_string());
+        let mut local = NeatBrain::new(genome, 1, String::new());
+        assert_eq!(format!("{:?}", remote.think(&view, &inputs)), format!("{:?}", local.think(&view, &inputs)));
+        assert!(remote.url.is_empty());
+    }
 }
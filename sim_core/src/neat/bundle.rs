@@ -0,0 +1,287 @@
+//! Self-describing champion bundles: the genome together with the sensor and
+//! action layout it was trained on, the full `Config` and `EvolutionConfig`,
+//! and where it came from. `neat_train train` writes them as its champion
+//! files; loaders check the layout against the sim they are about to play in
+//! instead of letting a mismatched genome panic inside `feed_forward`.
+//!
+//! The genome sits under `genome` and the free-form training record under
+//! `metadata`, so a bundle is also a `{ metadata, genome }` champion file.
+//!
+//! Versions (`version`, absent in version 1):
+//! 1. original layout, trained under the legacy motion model
+//! 2. trained under the inertial motion model unless `legacy_motion` is set
+//!
+//! Plain JSON champion files predate bundles and the inertial model, so they
+//! only load under a config with `legacy_motion` set.
+
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::config::Config;
+use super::champion::{self, ChampionError};
+use super::config::EvolutionConfig;
+use super::genome::{Genome, NodeType};
+use super::schema::migrate_genome;
+
+/// Value of a bundle's `format` field
+pub const BUNDLE_FORMAT: &str = "neat-champion-bundle";
+
+/// Bundle layout this build writes
+pub const BUNDLE_VERSION: u32 = 2;
+
+/// Network outputs, in order, of the default `Config::action_layout`
+pub const ACTIONS: [&str; 3] = ["vx", "vy", "fire"];
+
+/// One block of sensor inputs: `slots` repetitions of `features`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SensorGroup {
+    pub group: String,
+    pub slots: usize,
+    pub features: Vec<String>,
+}
+
+/// Sensor inputs `sim_cfg` produces, in `Simulation::scan` order
+pub fn sensor_groups(sim_cfg: &Config) -> Vec<SensorGroup> {
+    sim_cfg.sensor_layout().into_iter().map(|(group, slots, features)| SensorGroup {
+        group: group.to_string(),
+        slots,
+        features: features.iter().map(|f| f.to_string()).collect(),
+    }).collect()
+}
+
+/// Where a champion came from
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Provenance {
+    pub run_id: Option<String>,
+    pub generation: usize,
+    pub fitness: f32,
+    pub fitness_naive: f32,
+    /// `%Y%m%d_%H%M%S` UTC
+    pub created: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ChampionBundle {
+    pub format: String,
+    #[serde(default)]
+    pub version: u32,
+    pub sensors: Vec<SensorGroup>,
+    pub actions: Vec<String>,
+    pub sim_config: Config,
+    pub evolution_config: EvolutionConfig,
+    pub provenance: Provenance,
+    /// Anything else the trainer recorded (instrumentation, CLI options)
+    #[serde(default)]
+    pub metadata: Value,
+    pub genome: Genome,
+}
+
+#[derive(Debug)]
+pub enum BundleError {
+    /// The file could not be read as a champion at all
+    Champion(ChampionError),
+    /// Trained on different sensors than `sim_cfg` produces
+    SensorMismatch { trained: Vec<SensorGroup>, configured: Vec<SensorGroup> },
+    /// A plain genome whose input or output count does not fit `sim_cfg`
+    ShapeMismatch { inputs: usize, outputs: usize, sensor_len: usize, action_len: usize },
+    /// Outputs other than the config's `action_layout`
+    ActionMismatch { trained: Vec<String>, configured: Vec<String> },
+    /// Trained under the other motion model than `sim_cfg.legacy_motion` selects
+    MotionMismatch { trained_legacy: bool },
+}
+
+impl fmt::Display for BundleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BundleError::Champion(e) => write!(f, "{}", e),
+            BundleError::SensorMismatch { trained, configured } => {
+                write!(f, "champion was trained on different sensors than the config produces")?;
+                for (t, c) in trained.iter().zip(configured) {
+                    if t != c {
+                        return write!(f, " ({}: {} x {:?} trained, {} x {:?} configured)",
+                            t.group, t.slots, t.features, c.slots, c.features);
+                    }
+                }
+                write!(f, " ({} sensor groups trained, {} configured)", trained.len(), configured.len())
+            }
+            BundleError::ShapeMismatch { inputs, outputs, sensor_len, action_len } => write!(
+                f, "genome has {} inputs and {} outputs; the config needs {} and {}",
+                inputs, outputs, sensor_len, action_len,
+            ),
+            BundleError::ActionMismatch { trained, configured } => {
+                write!(f, "champion outputs {:?}; brains expect {:?}", trained, configured)
+            }
+            BundleError::MotionMismatch { trained_legacy: true } => {
+                write!(f, "champion was trained under the legacy motion model; play it with legacy_motion set")
+            }
+            BundleError::MotionMismatch { trained_legacy: false } => {
+                write!(f, "champion was trained under the inertial motion model; play it with legacy_motion off")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BundleError {}
+
+impl From<ChampionError> for BundleError {
+    fn from(e: ChampionError) -> Self { BundleError::Champion(e) }
+}
+
+impl From<serde_json::Error> for BundleError {
+    fn from(e: serde_json::Error) -> Self { BundleError::Champion(ChampionError::Json(e)) }
+}
+
+impl ChampionBundle {
+    /// Bundle `genome` with the layout of `sim_cfg`
+    pub fn new(genome: Genome, sim_cfg: &Config, evo_cfg: &EvolutionConfig, provenance: Provenance, metadata: Value) -> Self {
+        ChampionBundle {
+            format: BUNDLE_FORMAT.to_string(),
+            version: BUNDLE_VERSION,
+            sensors: sensor_groups(sim_cfg),
+            actions: sim_cfg.action_layout().iter().map(|a| a.to_string()).collect(),
+            sim_config: sim_cfg.clone(),
+            evolution_config: evo_cfg.clone(),
+            provenance,
+            metadata,
+            genome,
+        }
+    }
+
+    /// Parse a bundle from (optionally gzip-compressed) bytes; `Ok(None)` if
+    /// they hold some other kind of champion file
+    pub fn from_bytes(bytes: &[u8]) -> Result<Option<Self>, BundleError> {
+        let json = champion::decompress(bytes).map_err(ChampionError::Io)?;
+        let Ok(mut value) = serde_json::from_slice::<Value>(&json) else { return Ok(None) };
+        if value.get("format").and_then(Value::as_str) != Some(BUNDLE_FORMAT) {
+            return Ok(None);
+        }
+        migrate_bundle(&mut value);
+        if let Some(genome) = value.get_mut("genome") {
+            migrate_genome(genome).map_err(ChampionError::Schema)?;
+        }
+        let bundle: ChampionBundle = serde_json::from_value(value)?;
+        bundle.genome.validate(true).map_err(ChampionError::Invalid)?;
+        Ok(Some(bundle))
+    }
+
+    /// Ok if the champion reads the sensors `sim_cfg` produces and drives its actions
+    pub fn check(&self, sim_cfg: &Config) -> Result<(), BundleError> {
+        let configured = sensor_groups(sim_cfg);
+        // groups switched off (zero slots) contribute no inputs either way
+        let active = |gs: &[SensorGroup]| gs.iter().filter(|g| g.slots > 0).cloned().collect::<Vec<_>>();
+        if active(&self.sensors) != active(&configured) {
+            return Err(BundleError::SensorMismatch { trained: self.sensors.clone(), configured });
+        }
+        let configured = sim_cfg.action_layout();
+        if self.actions != configured {
+            return Err(BundleError::ActionMismatch {
+                trained: self.actions.clone(),
+                configured: configured.iter().map(|a| a.to_string()).collect(),
+            });
+        }
+        if self.sim_config.legacy_motion != sim_cfg.legacy_motion {
+            return Err(BundleError::MotionMismatch { trained_legacy: self.sim_config.legacy_motion });
+        }
+        check_shape(&self.genome, sim_cfg)
+    }
+}
+
+/// Upgrade a raw bundle in place to `BUNDLE_VERSION`: champions from before
+/// the inertial motion model keep the physics they were trained under
+fn migrate_bundle(bundle: &mut Value) {
+    let found = bundle.get("version").and_then(Value::as_u64).unwrap_or(1);
+    if found < 2 {
+        if let Some(cfg) = bundle.get_mut("sim_config").and_then(Value::as_object_mut) {
+            cfg.entry("legacy_motion").or_insert(Value::Bool(true));
+        }
+    }
+    bundle["version"] = Value::from(BUNDLE_VERSION);
+}
+
+/// Ok if `genome` takes `sim_cfg`'s sensor values and produces every action
+pub fn check_shape(genome: &Genome, sim_cfg: &Config) -> Result<(), BundleError> {
+    let count = |t: NodeType| genome.nodes.iter().filter(|n| n.node_type == t).count();
+    let (inputs, outputs) = (count(NodeType::Input), count(NodeType::Output));
+    let action_len = sim_cfg.action_layout().len();
+    if inputs != sim_cfg.sensor_len() || outputs != action_len {
+        return Err(BundleError::ShapeMismatch { inputs, outputs, sensor_len: sim_cfg.sensor_len(), action_len });
+    }
+    Ok(())
+}
+
+/// Whether the champion file `bytes` was trained under the legacy motion
+/// model: bundles record it, plain JSON champions predate the inertial one.
+/// False for ONNX models, which carry no config, and for anything that is
+/// not a champion file.
+pub fn trained_with_legacy_motion(bytes: &[u8]) -> bool {
+    match ChampionBundle::from_bytes(bytes) {
+        Ok(Some(bundle)) => bundle.sim_config.legacy_motion,
+        Ok(None) => {
+            let json = champion::decompress(bytes).is_ok_and(|j| serde_json::from_slice::<Value>(&j).is_ok());
+            json && champion::genome_from_bytes(bytes).is_ok()
+        }
+        Err(_) => false,
+    }
+}
+
+/// Load a champion of any kind for play under `sim_cfg`: bundles are checked
+/// against their recorded layout, other files by input and output count
+/// (plain JSON champions also need `legacy_motion`)
+pub fn genome_for_config(bytes: &[u8], sim_cfg: &Config) -> Result<Genome, BundleError> {
+    if let Some(bundle) = ChampionBundle::from_bytes(bytes)? {
+        bundle.check(sim_cfg)?;
+        return Ok(bundle.genome);
+    }
+    let genome = champion::genome_from_bytes(bytes)?;
+    check_shape(&genome, sim_cfg)?;
+    if !sim_cfg.legacy_motion && trained_with_legacy_motion(bytes) {
+        return Err(BundleError::MotionMismatch { trained_legacy: true });
+    }
+    Ok(genome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundle_round_trips_and_rejects_other_layouts() {
+        let (sim_cfg, evo_cfg) = (Config::default(), EvolutionConfig::default());
+        let mut genome = Genome::new();
+        genome.initialize(&sim_cfg, &evo_cfg, &mut rand::thread_rng());
+        let provenance = Provenance { generation: 4, fitness: 1.5, ..Default::default() };
+        let bundle = ChampionBundle::new(genome, &sim_cfg, &evo_cfg, provenance.clone(), Value::Null);
+        let bytes = serde_json::to_vec(&bundle).unwrap();
+
+        let loaded = ChampionBundle::from_bytes(&bytes).unwrap().unwrap();
+        assert_eq!(loaded.provenance, provenance);
+        assert!(!loaded.sim_config.legacy_motion);
+        // version 1 bundles predate the inertial motion model
+        let mut v1 = serde_json::to_value(&bundle).unwrap();
+        v1.as_object_mut().unwrap().remove("version");
+        v1["sim_config"].as_object_mut().unwrap().remove("legacy_motion");
+        let old = ChampionBundle::from_bytes(&serde_json::to_vec(&v1).unwrap()).unwrap().unwrap();
+        assert!(old.sim_config.legacy_motion);
+        assert_eq!(old.version, BUNDLE_VERSION);
+        assert!(genome_for_config(&bytes, &sim_cfg).is_ok());
+        // still readable as a plain `{ metadata, genome }` champion
+        assert!(champion::genome_from_bytes(&bytes).is_ok());
+
+        let wider = Config { nearest_k_enemies: sim_cfg.nearest_k_enemies * 2, ..Config::default() };
+        let err = genome_for_config(&bytes, &wider).unwrap_err();
+        assert!(matches!(err, BundleError::SensorMismatch { .. }));
+        assert!(err.to_string().contains("enemies"));
+
+        let bare = serde_json::to_vec(&bundle.genome).unwrap();
+        assert!(ChampionBundle::from_bytes(&bare).unwrap().is_none());
+        assert!(matches!(genome_for_config(&bare, &wider), Err(BundleError::ShapeMismatch { .. })));
+
+        // plain champion files play only under the legacy motion model
+        let legacy = Config { legacy_motion: true, ..Config::default() };
+        assert!(trained_with_legacy_motion(&bare) && !trained_with_legacy_motion(&bytes));
+        assert!(matches!(genome_for_config(&bare, &sim_cfg), Err(BundleError::MotionMismatch { trained_legacy: true })));
+        assert!(genome_for_config(&bare, &legacy).is_ok());
+        assert!(matches!(genome_for_config(&bytes, &legacy), Err(BundleError::MotionMismatch { trained_legacy: false })));
+    }
+}
@@ -0,0 +1,153 @@
+//! Loading champion genomes from the files `neat_train` writes.
+//!
+//! Accepted forms: a bare `Genome` JSON object, or the `{ "metadata": .., "genome": .. }`
+//! wrapper; either may be gzip-compressed. ONNX models (see `onnx_importer`)
+//! are accepted too.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::io::Read;
+use std::path::Path;
+use flate2::read::GzDecoder;
+use serde_json::Value;
+use super::genome::{Genome, GenomeError};
+use super::onnx_importer::{self, ImportError};
+use super::schema::{migrate_genome, SchemaError};
+
+/// Gzip stream magic bytes
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+#[derive(Debug)]
+pub enum ChampionError {
+    /// Reading the file or decompressing failed
+    Io(std::io::Error),
+    /// Content is not a genome (bare or wrapped)
+    Json(serde_json::Error),
+    /// Genome parsed but is structurally broken
+    Invalid(GenomeError),
+    /// Content looked like an ONNX model but could not be imported
+    Onnx(ImportError),
+    /// Genome layout cannot be migrated to this build's
+    Schema(SchemaError),
+}
+
+impl fmt::Display for ChampionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChampionError::Io(e) => write!(f, "champion read error: {}", e),
+            ChampionError::Json(e) => write!(f, "invalid champion JSON: {}", e),
+            ChampionError::Invalid(e) => write!(f, "invalid champion genome: {}", e),
+            ChampionError::Onnx(e) => write!(f, "invalid ONNX champion: {}", e),
+            ChampionError::Schema(e) => write!(f, "unsupported champion: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ChampionError {}
+
+impl From<std::io::Error> for ChampionError {
+    fn from(e: std::io::Error) -> Self { ChampionError::Io(e) }
+}
+
+impl From<serde_json::Error> for ChampionError {
+    fn from(e: serde_json::Error) -> Self { ChampionError::Json(e) }
+}
+
+impl From<GenomeError> for ChampionError {
+    fn from(e: GenomeError) -> Self { ChampionError::Invalid(e) }
+}
+
+impl From<ImportError> for ChampionError {
+    fn from(e: ImportError) -> Self { ChampionError::Onnx(e) }
+}
+
+impl From<SchemaError> for ChampionError {
+    fn from(e: SchemaError) -> Self { ChampionError::Schema(e) }
+}
+
+/// `bytes` gunzipped if they are a gzip stream, otherwise as-is
+pub fn decompress(bytes: &[u8]) -> std::io::Result<Cow<'_, [u8]>> {
+    if !bytes.starts_with(&GZIP_MAGIC) {
+        return Ok(Cow::Borrowed(bytes));
+    }
+    let mut buf = Vec::new();
+    GzDecoder::new(bytes).read_to_end(&mut buf)?;
+    Ok(Cow::Owned(buf))
+}
+
+/// Parse and validate a champion from raw (optionally gzip-compressed) bytes,
+/// migrating older genome layouts. Recurrent champions are accepted.
+pub fn genome_from_bytes(bytes: &[u8]) -> Result<Genome, ChampionError> {
+    let json = decompress(bytes)?;
+    let mut value: Value = match serde_json::from_slice(&json) {
+        Ok(v) => v,
+        // not JSON: maybe an ONNX model
+        Err(e) => return match onnx_importer::genome_from_onnx(&json) {
+            Err(ImportError::Decode(_)) => Err(e.into()),
+            other => Ok(other?),
+        },
+    };
+    // unwrap { metadata, genome } files
+    if let Some(inner) = value.get_mut("genome") {
+        value = inner.take();
+    }
+    migrate_genome(&mut value)?;
+    let genome: Genome = serde_json::from_value(value)?;
+    genome.validate(true)?;
+    Ok(genome)
+}
+
+/// Read and parse a champion file (plain, `.gz`, or `.onnx`)
+pub fn load_genome<P: AsRef<Path>>(path: P) -> Result<Genome, ChampionError> {
+    genome_from_bytes(&std::fs::read(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{write::GzEncoder, Compression};
+    use std::io::Write;
+
+    fn sample() -> Genome {
+        let mut g = Genome::new();
+        g.fitness = 3.5;
+        g
+    }
+
+    #[test]
+    fn loads_bare_wrapped_and_gzipped() {
+        let bare = serde_json::to_vec(&sample()).unwrap();
+        assert_eq!(genome_from_bytes(&bare).unwrap().fitness, 3.5);
+
+        let wrapped = serde_json::to_vec(&serde_json::json!({
+            "metadata": { "generation": 7 },
+            "genome": sample(),
+        })).unwrap();
+        assert_eq!(genome_from_bytes(&wrapped).unwrap().fitness, 3.5);
+
+        let mut enc = GzEncoder::new(Vec::new(), Compression::default());
+        enc.write_all(&wrapped).unwrap();
+        let gz = enc.finish().unwrap();
+        assert_eq!(genome_from_bytes(&gz).unwrap().fitness, 3.5);
+    }
+
+    #[test]
+    fn accepts_onnx_models() {
+        let mut genome = sample();
+        genome.initialize(&crate::config::Config::default(), &Default::default(), &mut rand::thread_rng());
+        let bytes = crate::neat::onnx_exporter::export_genome(&genome).unwrap();
+        let imported = genome_from_bytes(&bytes).unwrap();
+        let x = vec![0.5; genome.input_size()];
+        assert!((imported.feed_forward(&x)[0] - genome.feed_forward(&x)[0]).abs() < 1e-6);
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        assert!(matches!(genome_from_bytes(b"not json"), Err(ChampionError::Json(_))));
+        assert!(matches!(genome_from_bytes(&[0x1f, 0x8b, 0, 1]), Err(ChampionError::Io(_))));
+        let mut dangling = sample();
+        dangling.conns.push(crate::neat::genome::ConnGene { in_node: 0, out_node: 1, weight: 1.0, enabled: true, innovation: 0 });
+        let bytes = serde_json::to_vec(&dangling).unwrap();
+        assert!(matches!(genome_from_bytes(&bytes), Err(ChampionError::Invalid(_))));
+    }
+}
@@ -0,0 +1,128 @@
+//! Two-population competitive co-evolution (host/parasite): a red and a blue
+//! population, each genome scored only against samples of the rival
+//! population, so neither side can settle into mirror-match specialists that
+//! merely beat copies of themselves.
+//!
+//! Each side keeps its own species, hall-of-fame, and novelty archive. A
+//! `hof_match_rate` fraction of opponents comes from the rival's
+//! hall-of-fame, so strategies that beat earlier rivals are not forgotten.
+
+use rand::seq::SliceRandom;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rayon::prelude::*;
+use serde::{Serialize, Deserialize};
+use crate::brain::Brain;
+use crate::config::Config;
+use super::brain::NeatBrain;
+use super::config::EvolutionConfig;
+use super::genome::Genome;
+use super::hyperneat;
+use super::population::{Population, Tally};
+use super::runner::run_match;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CoEvolution {
+    pub red: Population,
+    pub blue: Population,
+}
+
+/// Networks of one side, snapshotted as opponents for the other
+struct Rivals {
+    current: Vec<Genome>,
+    hof: Vec<Genome>,
+}
+
+impl Rivals {
+    fn of(pop: &Population, sim_cfg: &Config, evo_cfg: &EvolutionConfig) -> Self {
+        let nets = |gs: &[Genome]| gs.iter().map(|g| hyperneat::phenotype(g, sim_cfg, evo_cfg)).collect();
+        Rivals { current: nets(&pop.genomes), hof: nets(&pop.hof) }
+    }
+
+    fn pick(&self, rng: &mut StdRng, evo_cfg: &EvolutionConfig) -> &Genome {
+        if !self.hof.is_empty() && rng.gen_bool(evo_cfg.hof_match_rate.clamp(0.0, 1.0) as f64) {
+            self.hof.choose(rng).unwrap()
+        } else {
+            self.current.choose(rng).unwrap()
+        }
+    }
+}
+
+impl CoEvolution {
+    /// Red and blue populations of `evo_cfg.pop_size` genomes each
+    pub fn new(evo_cfg: &EvolutionConfig) -> Self {
+        CoEvolution { red: Population::new(evo_cfg), blue: Population::new(evo_cfg) }
+    }
+
+    /// Generations produced so far
+    pub fn generation(&self) -> usize {
+        self.red.generation
+    }
+
+    /// Score every genome against `coevo_opponents` rival teams, then rank
+    /// each side on its own
+    pub fn evaluate(&mut self, sim_cfg: &Config, evo_cfg: &EvolutionConfig) {
+        let (red_cfg, blue_cfg) = (evo_cfg.substream(0), evo_cfg.substream(1));
+        self.red.prepare(sim_cfg, &red_cfg);
+        self.blue.prepare(sim_cfg, &blue_cfg);
+        let red_rivals = Rivals::of(&self.blue, sim_cfg, evo_cfg);
+        let blue_rivals = Rivals::of(&self.red, sim_cfg, evo_cfg);
+        score(&mut self.red, &red_rivals, sim_cfg, &red_cfg);
+        score(&mut self.blue, &blue_rivals, sim_cfg, &blue_cfg);
+        self.red.rank(&red_cfg);
+        self.blue.rank(&blue_cfg);
+    }
+
+    /// Breed the next generation of both sides
+    pub fn reproduce(&mut self, evo_cfg: &EvolutionConfig) {
+        self.red.reproduce(&evo_cfg.substream(0));
+        self.blue.reproduce(&evo_cfg.substream(1));
+    }
+}
+
+/// Mean fitness of each genome of `pop` over matches where a team of its
+/// copies faces a team sampled from `rivals`
+fn score(pop: &mut Population, rivals: &Rivals, sim_cfg: &Config, evo_cfg: &EvolutionConfig) {
+    let mut rng = evo_cfg.rng(pop.generation, 0);
+    let seeds: Vec<u64> = (0..pop.genomes.len()).map(|_| rng.gen()).collect();
+    let team_size = evo_cfg.team_size.max(1);
+    let brain = |g: &Genome| -> Box<dyn Brain> { Box::new(NeatBrain::for_config(g.clone(), sim_cfg)) };
+    pop.genomes.par_iter_mut().zip(seeds).for_each(|(genome, seed)| {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let net = hyperneat::phenotype(genome, sim_cfg, evo_cfg);
+        let mut tally = Tally::default();
+        for _ in 0..evo_cfg.coevo_opponents {
+            let mut agents: Vec<(Box<dyn Brain>, u32)> = (0..team_size).map(|_| (brain(&net), 0)).collect();
+            for _ in 0..team_size {
+                agents.push((brain(rivals.pick(&mut rng, evo_cfg)), 1));
+            }
+            let stats = run_match(sim_cfg, evo_cfg, agents);
+            let fit = evo_cfg.fitness_fn.compute(&stats, evo_cfg) / team_size as f32;
+            tally.add(fit, &stats, evo_cfg);
+        }
+        genome.fitness = 0.0;
+        tally.apply(genome);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_side_keeps_its_own_hall_of_fame() {
+        let evo_cfg = EvolutionConfig {
+            pop_size: 3, team_size: 1, hof_size: 1, coevo_opponents: 2, max_ticks: 5, seed: Some(3),
+            ..Default::default()
+        };
+        let mut coevo = CoEvolution::new(&evo_cfg);
+        coevo.evaluate(&Config::default(), &evo_cfg);
+        for side in [&coevo.red, &coevo.blue] {
+            assert!(side.genomes.iter().all(|g| !g.behavior.is_empty()));
+            assert_eq!(side.hof.len(), 1);
+            assert_eq!(side.hof[0].id, side.genomes[0].id);
+        }
+        coevo.reproduce(&evo_cfg);
+        assert_eq!(coevo.generation(), 1);
+        assert_eq!(coevo.blue.generation, 1);
+    }
+}
@@ -1,7 +1,15 @@
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Serialize, Deserialize};
+use crate::ai::{Difficulty, NaiveAgent, NaiveBrain};
+use crate::brain::Brain;
+use crate::config::{Config, ConfigError};
+use crate::mapgen::MapGen;
+use super::fitness::{CustomFitness, FitnessExpr};
 use super::runner::MatchStats;
 
 /// NEAT training parameters and schedule
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct EvolutionConfig {
     pub pop_size: usize,
     pub num_teams: usize,
@@ -12,13 +20,62 @@ pub struct EvolutionConfig {
     pub map_height: u32,
     pub max_ticks: usize,
     pub early_exit: bool,
+    /// End a match early once one side's remaining health is below this
+    /// fraction of the other's (0 disables)
+    pub mercy_ratio: f32,
+    /// End a match early after this many ticks without any health lost (0 disables)
+    pub stall_ticks: usize,
     pub tournament_k: usize,
     pub hof_size: usize,
+    /// Fraction of evaluation matches played against last generation's hall-of-fame
     pub hof_match_rate: f32,
+    /// Play every evaluation match a second time from the other side (slots
+    /// and team ids swapped) and score the pair together
+    pub mirror_matches: bool,
+    /// How timed-out matches are decided (see `runner::MatchOutcome`)
+    pub tie_break: TieBreak,
+    /// Tie-break leads at or below this count as a draw
+    pub draw_margin: f32,
+    /// Evaluate every genome in each of these settings; empty plays the
+    /// run's own settings once
+    pub scenarios: Vec<Scenario>,
+    /// How fitness over `scenarios` is combined
+    pub scenario_aggregate: ScenarioAggregate,
     pub compatibility_threshold: f32,
+    /// Compatibility distance weight of excess genes
+    pub compat_excess_coeff: f32,
+    /// Compatibility distance weight of disjoint genes
+    pub compat_disjoint_coeff: f32,
+    /// Compatibility distance weight of the mean matching-gene weight difference
+    pub compat_weight_coeff: f32,
+    /// Divide fitness by species size when allocating offspring, so large species don't dominate
+    pub fitness_sharing: bool,
+    /// Generations a species may go without improving its best fitness before it is culled
+    pub species_stagnation_limit: usize,
     pub crossover_rate: f32,
+    /// Which parent's weight a gene present in both parents gets
+    pub crossover_matching: MatchingGenes,
+    /// Which parents contribute disjoint and excess genes
+    pub crossover_disjoint: DisjointGenes,
+    /// Probability a gene disabled in either parent is enabled in the child
+    /// (NEAT uses 0.25); None copies the inherited gene's flag as is
+    pub crossover_reenable_rate: Option<f32>,
     pub mutation_add_node_rate: f32,
     pub mutation_add_conn_rate: f32,
+    /// Let add-connection mutations create cycles (read as the previous tick's activation)
+    pub allow_recurrent: bool,
+    /// Per-connection probability of mutating its weight each generation
+    pub mutation_weight_rate: f32,
+    /// Std-dev of the gaussian perturbation added to a mutated weight
+    pub mutation_weight_sigma: f32,
+    /// Probability a mutated weight is replaced with a fresh uniform value instead of perturbed
+    pub mutation_weight_reset_rate: f32,
+    /// Probability per mutation of assigning a random activation to one hidden node
+    pub mutation_activation_rate: f32,
+    /// Probability per mutation of re-enabling one random disabled connection
+    pub mutation_enable_rate: f32,
+    /// Probability per mutation of disabling one random enabled connection
+    pub mutation_disable_rate: f32,
     /// Weight for health in fitness
     pub w_health: f32,
     /// Weight for damage in fitness
@@ -29,13 +86,169 @@ pub struct EvolutionConfig {
     pub w_salvage: f32,
     /// Weight for exploration (thrust) actions in fitness
     pub w_explore: f32,
+    /// Weight for hit rate (hits per shot) in fitness
+    pub w_accuracy: f32,
+    /// Weight for damage dealt per shot in fitness
+    pub w_damage_per_shot: f32,
+    /// Weight for the fraction of trigger pulls that went off in fitness
+    pub w_fire_efficiency: f32,
+    /// Penalty per point of damage dealt to teammates
+    pub w_friendly_fire: f32,
+    /// Penalty per tick of the longest run a subject agent stood still
+    pub w_idle: f32,
     /// Weight for time-to-win bonus (only for time-based fitness)
     pub time_bonus_weight: f32,
     pub fitness_fn: FitnessFn,
+    /// How parents are ranked for reproduction
+    pub selection: SelectionStrategy,
+    /// How a genome maps to the network that drives an agent
+    pub encoding: Encoding,
+    /// Hidden nodes in the HyperNEAT substrate
+    pub substrate_hidden: usize,
+    /// CPPN outputs with magnitude at or below this express no substrate link
+    pub cppn_weight_threshold: f32,
+    /// Magnitude of a substrate weight for a saturated CPPN output
+    pub cppn_max_weight: f32,
+    /// Nearest neighbours averaged for a behavior's novelty
+    pub novelty_k: usize,
+    /// Novelty above which a behavior enters the archive
+    pub novelty_threshold: f32,
+    /// Maximum archived behaviors (oldest evicted first)
+    pub novelty_archive_size: usize,
+    /// Scale of the novelty term (`Novelty` and `Hybrid` fitness)
+    pub novelty_weight: f32,
+    /// Sub-populations evolved side by side (see `islands`); `pop_size` is per island
+    pub islands: usize,
+    /// Generations between migrations of each island's best genomes to the next island
+    pub migration_interval: usize,
+    /// Genomes each island sends per migration
+    pub migrants: usize,
+    /// Opponents from the rival population each genome meets per generation
+    /// when co-evolving (see `coevolution`)
+    pub coevo_opponents: usize,
+    /// Scripted opponent (a `scripted::NAMES` entry) that each genome's
+    /// `fitness_naive` baseline is measured against
+    pub baseline: String,
+    /// Strength of the `naive` baseline (ramped by the curriculum)
+    pub naive_difficulty: Difficulty,
+    /// Seed for every random choice of a training run (initial weights,
+    /// matchups, match simulations, selection, mutation); None draws from entropy
+    pub seed: Option<u64>,
+    /// Record a per-tick state hash in `MatchStats` for replay verification
+    pub record_state_hashes: bool,
+}
+
+/// How genomes are ranked for selection
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SelectionStrategy {
+    /// scalar `fitness_fn` score
+    Tournament,
+    /// Pareto rank then crowding distance over health, damage, and time-to-win
+    Nsga2,
+}
+
+/// Crossover handling of genes present in both parents
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MatchingGenes {
+    /// copy either parent's gene with equal probability
+    Random,
+    /// mean of the parents' weights
+    Average,
+}
+
+/// Crossover handling of genes present in only one parent
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DisjointGenes {
+    /// only the fitter parent's (the weaker parent's are discarded)
+    Fitter,
+    /// both parents' (original NEAT when fitness is equal)
+    Both,
+    /// both parents' when their fitness is equal, otherwise the fitter's
+    BothIfEqual,
+}
+
+/// Genome encoding
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Encoding {
+    /// genes are the network's nodes and connections
+    Direct,
+    /// genes are a CPPN that paints weights onto a sensor-shaped substrate (see `hyperneat`)
+    HyperNeat,
+}
+
+/// How a match that reaches `max_ticks` with both sides alive is settled
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TieBreak {
+    /// more remaining health wins
+    #[default]
+    Health,
+    /// more damage dealt wins
+    Damage,
+    /// more living agents wins, then more remaining health
+    Survivors,
+    /// every timeout is a draw
+    None,
+}
+
+/// How a genome's per-scenario fitnesses combine into its fitness
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScenarioAggregate {
+    /// mean over the scenarios
+    #[default]
+    Mean,
+    /// the worst scenario
+    Worst,
+}
+
+impl ScenarioAggregate {
+    pub fn combine(self, fitnesses: &[f32]) -> f32 {
+        match self {
+            _ if fitnesses.is_empty() => 0.0,
+            ScenarioAggregate::Mean => fitnesses.iter().sum::<f32>() / fitnesses.len() as f32,
+            ScenarioAggregate::Worst => fitnesses.iter().copied().fold(f32::INFINITY, f32::min),
+        }
+    }
+}
+
+/// One evaluation setting of a scenario suite; `None` keeps the run's value
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Scenario {
+    pub map_width: Option<u32>,
+    pub map_height: Option<u32>,
+    pub num_teams: Option<usize>,
+    pub team_size: Option<usize>,
+    /// Procedural obstacles, wrecks and spawn ring
+    pub map_gen: Option<MapGen>,
+    /// Seed for every match of the scenario (default: a fresh seed per match
+    /// from the generation RNG, so terrain differs between matches)
+    pub seed: Option<u64>,
+}
+
+impl Scenario {
+    /// `sim_cfg` and `evo_cfg` with this scenario's overrides applied
+    pub fn apply(&self, sim_cfg: &Config, evo_cfg: &EvolutionConfig) -> (Config, EvolutionConfig) {
+        let (mut sim_cfg, mut evo_cfg) = (sim_cfg.clone(), evo_cfg.clone());
+        evo_cfg.map_width = self.map_width.unwrap_or(evo_cfg.map_width);
+        evo_cfg.map_height = self.map_height.unwrap_or(evo_cfg.map_height);
+        evo_cfg.num_teams = self.num_teams.unwrap_or(evo_cfg.num_teams);
+        evo_cfg.team_size = self.team_size.unwrap_or(evo_cfg.team_size);
+        if let Some(m) = &self.map_gen {
+            sim_cfg.map_gen = m.clone();
+        }
+        (sim_cfg, evo_cfg)
+    }
 }
 
 /// How to compute fitness from match stats
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum FitnessFn {
     /// original: health + damage
     HealthPlusDamage,
@@ -47,6 +260,15 @@ pub enum FitnessFn {
     HealthDamageExplore,
     /// health + damage + time bonus + salvage + exploration
     HealthDamageTimeSalvageExplore,
+    /// behavioral novelty only (scored population-wide after matches)
+    Novelty,
+    /// health + damage + novelty bonus
+    Hybrid,
+    /// arithmetic expression over match stats and weights (see `fitness`)
+    Expr(FitnessExpr),
+    /// closure supplied in code; never read back from a config file
+    #[serde(skip_deserializing)]
+    Custom(CustomFitness),
 }
 
 impl Default for EvolutionConfig {
@@ -59,30 +281,135 @@ impl Default for EvolutionConfig {
             map_height: 1000,
             max_ticks: 1000,
             early_exit: true,
+            mercy_ratio: 0.0,
+            stall_ticks: 0,
             tournament_k: 5,
             hof_size: 5,
             hof_match_rate: 0.1,
+            mirror_matches: false,
+            tie_break: TieBreak::Health,
+            draw_margin: 1e-3,
+            scenarios: Vec::new(),
+            scenario_aggregate: ScenarioAggregate::Mean,
             compatibility_threshold: 3.0,
+            compat_excess_coeff: 1.0,
+            compat_disjoint_coeff: 1.0,
+            compat_weight_coeff: 0.4,
+            fitness_sharing: true,
+            species_stagnation_limit: 15,
             crossover_rate: 0.75,
+            crossover_matching: MatchingGenes::Random,
+            crossover_disjoint: DisjointGenes::Fitter,
+            crossover_reenable_rate: None,
             mutation_add_node_rate: 0.3,
             mutation_add_conn_rate: 0.5,
+            allow_recurrent: false,
+            mutation_weight_rate: 0.8,
+            mutation_weight_sigma: 0.1,
+            mutation_weight_reset_rate: 0.1,
+            mutation_enable_rate: 0.05,
+            mutation_activation_rate: 0.05,
+            mutation_disable_rate: 0.01,
             w_health: 1.0,
             w_damage: 1.0,
             w_kills: 0.5,
             w_salvage: 0.0,
             w_explore: 0.0,
+            w_accuracy: 0.0,
+            w_damage_per_shot: 0.0,
+            w_fire_efficiency: 0.0,
+            w_friendly_fire: 0.0,
+            w_idle: 0.0,
             time_bonus_weight: 0.1,
             fitness_fn: FitnessFn::HealthPlusDamage,
+            selection: SelectionStrategy::Tournament,
+            encoding: Encoding::Direct,
+            substrate_hidden: 8,
+            cppn_weight_threshold: 0.2,
+            cppn_max_weight: 3.0,
+            novelty_k: 15,
+            novelty_threshold: 0.1,
+            novelty_archive_size: 500,
+            novelty_weight: 1.0,
+            islands: 1,
+            migration_interval: 10,
+            migrants: 2,
+            coevo_opponents: 5,
+            baseline: "naive".to_string(),
+            naive_difficulty: Difficulty::default(),
+            seed: None,
+            record_state_hashes: false,
+        }
+    }
+}
+
+impl EvolutionConfig {
+    /// Check that every probability lies in [0, 1], so a bad rate fails
+    /// before training rather than panicking mid-generation
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let probabilities = [
+            ("hof_match_rate", Some(self.hof_match_rate)),
+            ("crossover_rate", Some(self.crossover_rate)),
+            ("crossover_reenable_rate", self.crossover_reenable_rate),
+            ("mutation_add_node_rate", Some(self.mutation_add_node_rate)),
+            ("mutation_add_conn_rate", Some(self.mutation_add_conn_rate)),
+            ("mutation_weight_rate", Some(self.mutation_weight_rate)),
+            ("mutation_weight_reset_rate", Some(self.mutation_weight_reset_rate)),
+            ("mutation_activation_rate", Some(self.mutation_activation_rate)),
+            ("mutation_enable_rate", Some(self.mutation_enable_rate)),
+            ("mutation_disable_rate", Some(self.mutation_disable_rate)),
+        ];
+        for (field, value) in probabilities {
+            if let Some(v) = value.filter(|v| !(0.0..=1.0).contains(v)) {
+                return Err(ConfigError::Invalid { field, reason: format!("must be within [0, 1], got {}", v) });
+            }
+        }
+        Ok(())
+    }
+
+    /// A fresh `baseline` opponent, `NaiveAgent` at `naive_difficulty` for
+    /// `naive` or an unknown name
+    pub fn baseline_brain(&self) -> Box<dyn Brain> {
+        match crate::scripted::by_name(&self.baseline) {
+            Some(brain) if !self.baseline.eq_ignore_ascii_case("naive") => brain,
+            _ => Box::new(NaiveBrain(NaiveAgent::with_difficulty(1.2, 0.8, self.naive_difficulty))),
+        }
+    }
+
+    /// RNG for one phase (`stream`) of `generation`: derived from `seed` so a
+    /// seeded run, or one resumed from a checkpoint, replays exactly
+    pub fn rng(&self, generation: usize, stream: u64) -> StdRng {
+        match self.seed {
+            Some(seed) => StdRng::seed_from_u64(
+                seed ^ (generation as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ stream.wrapping_mul(0xD1B5_4A32_D192_ED03),
+            ),
+            None => StdRng::from_entropy(),
+        }
+    }
+
+    /// Copy of these settings drawing from an independent set of RNG streams,
+    /// for runs that evolve several populations side by side
+    pub fn substream(&self, i: usize) -> EvolutionConfig {
+        EvolutionConfig {
+            seed: self.seed.map(|s| s.wrapping_add((i as u64).wrapping_mul(0xA076_1D64_78BD_642F))),
+            ..self.clone()
         }
     }
 }
 
 impl FitnessFn {
     pub fn compute(&self, stats: &MatchStats, evo_cfg: &EvolutionConfig) -> f32 {
-        // Base health, damage, kills
+        // Base health, damage, kills, how well the team spent its fire, less
+        // friendly fire and idling
+        let damage_per_shot = if stats.fire.shots > 0 { stats.total_damage_inflicted / stats.fire.shots as f32 } else { 0.0 };
         let hd = stats.subject_team_health * evo_cfg.w_health
             + stats.total_damage_inflicted * evo_cfg.w_damage
-            + stats.kills as f32 * evo_cfg.w_kills;
+            + stats.kills as f32 * evo_cfg.w_kills
+            + stats.fire.accuracy() * evo_cfg.w_accuracy
+            + damage_per_shot * evo_cfg.w_damage_per_shot
+            + stats.fire.efficiency() * evo_cfg.w_fire_efficiency
+            - stats.fire.friendly_damage * evo_cfg.w_friendly_fire
+            - stats.longest_idle as f32 * evo_cfg.w_idle;
         // Supplemental terms
         let salvage_term = stats.salvage_actions * evo_cfg.w_salvage;
         let explore_term = stats.exploration_actions * evo_cfg.w_explore;
@@ -97,6 +424,21 @@ impl FitnessFn {
             FitnessFn::HealthDamageSalvage => hd + salvage_term,
             FitnessFn::HealthDamageExplore => hd + explore_term,
             FitnessFn::HealthDamageTimeSalvageExplore => hd + salvage_term + explore_term + time_bonus,
+            // novelty is added by `Population::evaluate`, not per match
+            FitnessFn::Novelty => 0.0,
+            FitnessFn::Hybrid => hd,
+            FitnessFn::Expr(expr) => expr.eval(stats, evo_cfg),
+            FitnessFn::Custom(f) => f.compute(stats, evo_cfg),
         }
     }
+
+    /// Fitness computed by `f`
+    pub fn custom(f: impl Fn(&MatchStats, &EvolutionConfig) -> f32 + Send + Sync + 'static) -> Self {
+        FitnessFn::Custom(CustomFitness::new(f))
+    }
+
+    /// Whether fitness includes a population-level novelty term
+    pub fn uses_novelty(&self) -> bool {
+        matches!(self, FitnessFn::Novelty | FitnessFn::Hybrid)
+    }
 }
@@ -0,0 +1,141 @@
+//! Curriculum: an ordered list of training stages, each overriding some
+//! simulation or evolution settings, with a promotion criterion for moving
+//! on to the next. `Population::evaluate` plays under the current stage and
+//! checks for promotion afterwards, so the curriculum travels with checkpoints.
+
+use serde::{Serialize, Deserialize};
+use crate::ai::Difficulty;
+use crate::config::Config;
+use super::config::EvolutionConfig;
+use super::genome::Genome;
+
+/// Settings a stage replaces (None keeps the run's own value)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Overrides {
+    pub scan_max_dist: Option<f32>,
+    pub max_ticks: Option<usize>,
+    pub map_width: Option<u32>,
+    pub map_height: Option<u32>,
+    /// Strength of the `naive` baseline
+    pub naive: Option<Difficulty>,
+}
+
+/// When a stage is passed, judged on a freshly evaluated generation
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Promotion {
+    /// Mean fitness against the NaiveAgent baseline reaches the value
+    AvgNaive(f32),
+    /// Mean fitness reaches the value
+    AvgFitness(f32),
+    /// Best fitness reaches the value
+    BestFitness(f32),
+}
+
+impl Promotion {
+    fn met(&self, genomes: &[Genome]) -> bool {
+        let mean = |f: fn(&Genome) -> f32| genomes.iter().map(f).sum::<f32>() / genomes.len().max(1) as f32;
+        match *self {
+            Promotion::AvgNaive(t) => mean(|g| g.fitness_naive) >= t,
+            Promotion::AvgFitness(t) => mean(|g| g.fitness) >= t,
+            Promotion::BestFitness(t) => genomes.iter().any(|g| g.fitness >= t),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Stage {
+    pub overrides: Overrides,
+    /// Criterion for moving on; None (or the last stage) never promotes
+    pub promote: Option<Promotion>,
+    /// Generations to spend in the stage before promotion is considered
+    pub min_generations: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Curriculum {
+    pub stages: Vec<Stage>,
+    /// Index of the current stage
+    pub stage: usize,
+    /// Generation the current stage began at
+    pub entered_at: usize,
+}
+
+impl Curriculum {
+    pub fn new(stages: Vec<Stage>) -> Self {
+        Curriculum { stages, stage: 0, entered_at: 0 }
+    }
+
+    /// The classic difficulty ramp: `levels + 1` stages shrinking the sensor
+    /// range by 10% of `base_scan_max_dist` each while the naive baseline
+    /// strengthens from `Difficulty::EASY` to full, promoted after at least
+    /// `interval` generations once the mean naive fitness reaches `threshold`
+    pub fn difficulty_ramp(base_scan_max_dist: f32, levels: usize, interval: usize, threshold: f32) -> Self {
+        Curriculum::new((0..=levels).map(|level| Stage {
+            overrides: Overrides {
+                scan_max_dist: Some(base_scan_max_dist * (1.0 - level as f32 * 0.1)),
+                naive: Some(Difficulty::at(level, levels)),
+                ..Default::default()
+            },
+            promote: Some(Promotion::AvgNaive(threshold)),
+            min_generations: interval,
+        }).collect())
+    }
+
+    pub fn current(&self) -> Option<&Stage> {
+        self.stages.get(self.stage)
+    }
+
+    /// Write the current stage's overrides (and its index as the difficulty level) into the configs
+    pub fn apply(&self, sim_cfg: &mut Config, evo_cfg: &mut EvolutionConfig) {
+        let Some(stage) = self.current() else { return };
+        let o = &stage.overrides;
+        if let Some(v) = o.scan_max_dist { sim_cfg.scan_max_dist = v; }
+        if let Some(v) = o.max_ticks { evo_cfg.max_ticks = v; }
+        if let Some(v) = o.map_width { evo_cfg.map_width = v; }
+        if let Some(v) = o.map_height { evo_cfg.map_height = v; }
+        if let Some(v) = o.naive { evo_cfg.naive_difficulty = v; }
+        sim_cfg.difficulty_level = self.stage;
+    }
+
+    /// Advance a stage if `genomes`, evaluated in `generation`, meet the
+    /// current stage's criterion; returns whether it did
+    pub fn update(&mut self, generation: usize, genomes: &[Genome]) -> bool {
+        let Some(stage) = self.current() else { return false };
+        let ready = self.stage + 1 < self.stages.len()
+            && generation + 1 >= self.entered_at + stage.min_generations
+            && stage.promote.is_some_and(|p| p.met(genomes));
+        if ready {
+            self.stage += 1;
+            self.entered_at = generation + 1;
+        }
+        ready
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ramp_promotes_after_interval_and_threshold() {
+        let mut c = Curriculum::difficulty_ramp(100.0, 2, 3, 50.0);
+        let weak = vec![Genome { fitness_naive: 10.0, ..Genome::new() }];
+        let strong = vec![Genome { fitness_naive: 60.0, ..Genome::new() }];
+        assert!(!c.update(0, &strong));
+        assert!(!c.update(2, &weak));
+        assert!(c.update(2, &strong));
+        assert_eq!((c.stage, c.entered_at), (1, 3));
+        assert!(!c.update(4, &strong));
+        assert!(c.update(5, &strong));
+        // last stage never promotes
+        assert!(!c.update(100, &strong));
+        let (mut sim_cfg, mut evo_cfg) = (Config::default(), EvolutionConfig::default());
+        c.apply(&mut sim_cfg, &mut evo_cfg);
+        assert_eq!(sim_cfg.difficulty_level, 2);
+        assert!((sim_cfg.scan_max_dist - 80.0).abs() < 1e-4);
+        assert_eq!(evo_cfg.naive_difficulty, Difficulty::default());
+        let json = serde_json::to_string(&c).unwrap();
+        assert_eq!(serde_json::from_str::<Curriculum>(&json).unwrap(), c);
+    }
+}
@@ -0,0 +1,147 @@
+//! Live training dashboard: one full-screen text frame per generation with
+//! fitness curves, species counts, generation timing, and the current
+//! champion's behavior, replacing the scrolling per-generation log.
+//! Rendering is plain text; `neat_train train --dashboard` draws it.
+
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use super::genome::Genome;
+
+/// Unicode eighth blocks, lowest to highest
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Notable events kept on screen
+const EVENT_LINES: usize = 5;
+
+/// What one generation produced
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GenerationStats {
+    pub generation: usize,
+    pub best: f32,
+    pub avg: f32,
+    /// Best fitness against NaiveAgent
+    pub best_naive: f32,
+    pub species: usize,
+    /// Seconds spent evaluating
+    pub eval_secs: f32,
+    /// Seconds for the whole generation, including reproduction and snapshots
+    pub gen_secs: f32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Dashboard {
+    pub run_id: String,
+    pub history: Vec<GenerationStats>,
+    /// Champion summary lines
+    pub champion: Vec<String>,
+    pub events: VecDeque<String>,
+}
+
+impl Dashboard {
+    pub fn new(run_id: &str) -> Self {
+        Dashboard { run_id: run_id.to_string(), ..Default::default() }
+    }
+
+    pub fn push(&mut self, stats: GenerationStats) {
+        self.history.push(stats);
+    }
+
+    /// Record a notable event (curriculum promotion, stagnation recovery, ...)
+    pub fn event(&mut self, msg: String) {
+        if self.events.len() == EVENT_LINES {
+            self.events.pop_front();
+        }
+        self.events.push_back(msg);
+    }
+
+    /// Summarize the hall-of-fame leader: fitness, size, and match behavior
+    pub fn set_champion(&mut self, champ: &Genome) {
+        let enabled = champ.conns.iter().filter(|c| c.enabled).count();
+        let m = &champ.metrics;
+        self.champion = vec![
+            format!("fitness {:.2} (vs naive {:.2}), {} nodes, {} enabled conns",
+                    champ.fitness, champ.fitness_naive, champ.nodes.len(), enabled),
+            format!("damage {:.1}/match, loot {:.1}/match, survives {:.0} ticks, movement entropy {:.2}",
+                    m.damage, m.loot, m.survival_ticks, m.movement_entropy),
+        ];
+    }
+
+    /// The frame for a terminal `width` columns wide
+    pub fn render(&self, width: usize) -> String {
+        let curve_width = width.saturating_sub(24).clamp(10, 120);
+        let column = |f: fn(&GenerationStats) -> f32| -> Vec<f32> { self.history.iter().map(f).collect() };
+        let mut out = String::new();
+        let _ = writeln!(out, "NEAT training · {}", self.run_id);
+        let Some(last) = self.history.last() else {
+            out.push_str("waiting for the first generation…\n");
+            return out;
+        };
+        let total: f32 = self.history.iter().fold(0.0, |acc, s| acc + s.gen_secs);
+        let _ = writeln!(
+            out, "generation {} · {:.1}s elapsed · last {:.2}s (eval {:.2}s)\n",
+            last.generation, total, last.gen_secs, last.eval_secs,
+        );
+        let rows: [(&str, Vec<f32>, f32); 5] = [
+            ("best", column(|s| s.best), last.best),
+            ("avg", column(|s| s.avg), last.avg),
+            ("vs naive", column(|s| s.best_naive), last.best_naive),
+            ("species", column(|s| s.species as f32), last.species as f32),
+            ("gen secs", column(|s| s.gen_secs), last.gen_secs),
+        ];
+        for (label, values, now) in &rows {
+            let _ = writeln!(out, "{:>9} {} {:>10.2}", label, sparkline(values, curve_width), now);
+        }
+        out.push_str("\nchampion\n");
+        for line in &self.champion {
+            let _ = writeln!(out, "  {}", line);
+        }
+        if !self.events.is_empty() {
+            out.push_str("\nevents\n");
+            for e in &self.events {
+                let _ = writeln!(out, "  {}", e);
+            }
+        }
+        out
+    }
+}
+
+/// The last `width` values as bars scaled between their min and max
+pub fn sparkline(values: &[f32], width: usize) -> String {
+    let tail = &values[values.len().saturating_sub(width)..];
+    let (lo, hi) = tail.iter().fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+    tail.iter().map(|&v| {
+        let level = if hi > lo { (v - lo) / (hi - lo) * (BARS.len() - 1) as f32 } else { 0.0 };
+        BARS[(level.round() as usize).min(BARS.len() - 1)]
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sparkline_scales_and_keeps_the_latest() {
+        assert_eq!(sparkline(&[0.0, 1.0, 2.0], 10), "▁▅█");
+        assert_eq!(sparkline(&[5.0, 5.0], 10), "▁▁");
+        assert_eq!(sparkline(&[9.0, 0.0, 7.0], 2), "▁█");
+        assert_eq!(sparkline(&[], 4), "");
+    }
+
+    #[test]
+    fn frame_shows_history_champion_and_recent_events() {
+        let mut dash = Dashboard::new("run");
+        assert!(dash.render(80).contains("waiting"));
+        for g in 0..3 {
+            dash.push(GenerationStats { generation: g, best: g as f32, species: 2, gen_secs: 0.5, ..Default::default() });
+        }
+        dash.set_champion(&Genome::new());
+        for i in 0..EVENT_LINES + 2 {
+            dash.event(format!("event {}", i));
+        }
+        let frame = dash.render(80);
+        assert!(frame.contains("generation 2 · 1.5s elapsed"));
+        assert!(frame.contains("     best ▁▅█       2.00"));
+        assert!(frame.contains("fitness 0.00"));
+        assert!(!frame.contains("event 1\n") && frame.contains("event 6"));
+    }
+}
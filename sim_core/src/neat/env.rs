@@ -0,0 +1,264 @@
+//! Gym-style multi-agent environment over `Simulation`, for training RL
+//! policies (PPO, SAC, ...) against NEAT champions or scripted opponents.
+//!
+//! Team 0 is the learner team: its `team_size` agents take their actions
+//! from `step`, one `[vx, vy, fire]` vector each (the `Config::action_layout`
+//! a NEAT brain produces, with a trailing `weapon` under `weapon_select`),
+//! and observe the same sensor vector a `NeatBrain` would. Every other team is played by the opponent factory.
+//!
+//! Rewards are the per-tick change of the run's `fitness_fn` applied to the
+//! learner team's running match stats, shared by all learners, so an
+//! episode's rewards sum to the team's match fitness less its value at reset.
+
+use crate::brain::Brain;
+use crate::config::Config;
+use crate::domain::{Action, Vec2, Weapon, WorldView};
+use crate::ship::slot_for_output;
+use crate::{Simulation, AGENT_STRIDE, IDX_HEALTH, IDX_TEAM};
+use super::config::EvolutionConfig;
+use super::metrics::IdleStreaks;
+use super::runner::MatchStats;
+
+/// Team id of the agents `step` controls
+pub const LEARNER_TEAM: u32 = 0;
+
+/// Stand-in brain for learner agents; `step` always queues their commands
+struct Controlled;
+
+impl Brain for Controlled {
+    fn think(&mut self, _view: &WorldView, _inputs: &[f32]) -> Action {
+        Action::Idle
+    }
+}
+
+/// What happened in one `step`, beyond observations, rewards and dones
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepInfo {
+    pub tick: usize,
+    /// Learner team fitness so far under the run's `fitness_fn`
+    pub fitness: f32,
+    /// The episode hit `max_ticks` without being decided
+    pub truncated: bool,
+    /// Sole surviving team once the match is decided (None for a mutual wipe or while undecided)
+    pub winner: Option<u32>,
+}
+
+/// `step` results, one entry per learner agent in id order
+#[derive(Debug, Clone)]
+pub struct Step {
+    pub observations: Vec<Vec<f32>>,
+    pub rewards: Vec<f32>,
+    /// Agent dead or episode over
+    pub dones: Vec<bool>,
+    pub info: StepInfo,
+}
+
+pub struct MultiAgentEnv {
+    sim_cfg: Config,
+    evo_cfg: EvolutionConfig,
+    opponent: Box<dyn Fn() -> Box<dyn Brain>>,
+    sim: Simulation,
+    /// Learner agent ids
+    learners: Vec<usize>,
+    stats: MatchStats,
+    idle: IdleStreaks,
+    initial_opp_health: f32,
+    initial_opponents: usize,
+    fitness: f32,
+    over: bool,
+}
+
+impl MultiAgentEnv {
+    /// Learners against `evo_cfg.num_teams - 1` opponent teams of
+    /// `evo_cfg.team_size` brains built by `opponent`; call `reset` to begin
+    pub fn new(sim_cfg: Config, evo_cfg: EvolutionConfig, opponent: impl Fn() -> Box<dyn Brain> + 'static) -> Self {
+        let mut env = MultiAgentEnv {
+            sim: Simulation::empty(evo_cfg.map_width, evo_cfg.map_height),
+            sim_cfg,
+            evo_cfg,
+            opponent: Box::new(opponent),
+            learners: Vec::new(),
+            stats: MatchStats::default(),
+            idle: IdleStreaks::default(),
+            initial_opp_health: 0.0,
+            initial_opponents: 0,
+            fitness: 0.0,
+            over: true,
+        };
+        env.reset(0);
+        env
+    }
+
+    /// Learner agents per episode
+    pub fn num_agents(&self) -> usize {
+        self.evo_cfg.team_size
+    }
+
+    /// Length of each observation vector
+    pub fn observation_len(&self) -> usize {
+        self.sim_cfg.sensor_len()
+    }
+
+    /// Length of each action vector
+    pub fn action_len(&self) -> usize {
+        self.sim_cfg.action_layout().len()
+    }
+
+    /// The underlying simulation, e.g. for rendering
+    pub fn simulation(&self) -> &Simulation {
+        &self.sim
+    }
+
+    /// Start a new episode with the simulation RNG seeded from `seed`;
+    /// returns each learner's first observation
+    pub fn reset(&mut self, seed: u64) -> Vec<Vec<f32>> {
+        let (teams, size) = (self.evo_cfg.num_teams.max(2), self.evo_cfg.team_size);
+        let mut agents: Vec<(Box<dyn Brain>, u32)> = Vec::with_capacity(teams * size);
+        for team in 0..teams as u32 {
+            for _ in 0..size {
+                let brain: Box<dyn Brain> = if team == LEARNER_TEAM { Box::new(Controlled) } else { (self.opponent)() };
+                agents.push((brain, team));
+            }
+        }
+        self.sim = Simulation::with_brains(self.evo_cfg.map_width, self.evo_cfg.map_height, self.sim_cfg.clone(), agents);
+        self.sim.reseed(seed);
+        self.sim.generate_map(seed);
+        self.learners = (0..size).collect();
+        self.initial_opp_health = self.opponent_health();
+        self.initial_opponents = size * (teams - 1);
+        self.stats = MatchStats::default();
+        self.idle = IdleStreaks::default();
+        self.update_stats();
+        self.fitness = self.evo_cfg.fitness_fn.compute(&self.stats, &self.evo_cfg);
+        self.over = false;
+        self.observations()
+    }
+
+    /// Apply one `action_len` action vector per learner (missing ones idle)
+    /// and advance one tick. Fire above 0.5 shoots when an enemy is in range
+    /// and otherwise thrusts, as `NeatBrain` does. After the episode ends,
+    /// further steps change nothing and report every agent done.
+    pub fn step<A: AsRef<[f32]>>(&mut self, actions: &[A]) -> Step {
+        if !self.over {
+            for (k, &id) in self.learners.iter().enumerate() {
+                let action = actions.get(k).map_or(Action::Idle, |a| self.decode(id, a.as_ref()));
+                match action {
+                    Action::Thrust(_) => self.stats.exploration_actions += 1.0,
+                    Action::Loot => self.stats.salvage_actions += 1.0,
+                    _ => {}
+                }
+                self.sim.push_command(id, action);
+            }
+            self.sim.step();
+            self.stats.ticks += 1;
+            self.update_stats();
+            self.over = self.sim.is_decided() || self.stats.ticks >= self.evo_cfg.max_ticks;
+        }
+        let fitness = self.evo_cfg.fitness_fn.compute(&self.stats, &self.evo_cfg);
+        let reward = fitness - self.fitness;
+        self.fitness = fitness;
+        let alive = self.sim.team_alive_counts();
+        let decided = self.sim.is_decided();
+        Step {
+            observations: self.observations(),
+            rewards: vec![reward; self.learners.len()],
+            dones: self.learners.iter().map(|&id| self.over || self.health(id) <= 0.0).collect(),
+            info: StepInfo {
+                tick: self.stats.ticks,
+                fitness,
+                truncated: self.over && !decided,
+                winner: if decided { alive.iter().position(|&c| c > 0).map(|t| t as u32) } else { None },
+            },
+        }
+    }
+
+    fn health(&self, id: usize) -> f32 {
+        self.sim.agents_data[id * AGENT_STRIDE + IDX_HEALTH]
+    }
+
+    fn opponent_health(&self) -> f32 {
+        self.sim.agents_data.chunks(AGENT_STRIDE)
+            .filter(|a| a[IDX_TEAM] as u32 != LEARNER_TEAM)
+            .map(|a| a[IDX_HEALTH].max(0.0))
+            .sum()
+    }
+
+    /// Refresh the health, damage, kill, fire and idle terms from the simulation
+    fn update_stats(&mut self) {
+        self.idle.observe(&self.sim, LEARNER_TEAM);
+        self.stats.longest_idle = self.idle.longest();
+        self.stats.subject_team_health = self.learners.iter().map(|&id| self.health(id).max(0.0)).sum();
+        self.stats.total_damage_inflicted = self.initial_opp_health - self.opponent_health();
+        let opp_alive = self.sim.agents_data.chunks(AGENT_STRIDE)
+            .filter(|a| a[IDX_TEAM] as u32 != LEARNER_TEAM && a[IDX_HEALTH] > 0.0)
+            .count();
+        self.stats.kills = self.initial_opponents.saturating_sub(opp_alive);
+        self.stats.fire = self.sim.team_shots(LEARNER_TEAM);
+        if self.learners.iter().any(|&id| self.health(id) > 0.0) {
+            self.stats.survival_ticks = self.stats.ticks;
+        }
+    }
+
+    fn observations(&self) -> Vec<Vec<f32>> {
+        self.learners.iter()
+            .map(|&id| self.sim.scan(id, self.sim_cfg.scan_rays, self.sim_cfg.scan_max_dist))
+            .collect()
+    }
+
+    fn decode(&self, id: usize, a: &[f32]) -> Action {
+        let at = |i: usize| a.get(i).copied().unwrap_or(0.0);
+        let thrust = Action::Thrust(Vec2 { x: at(0), y: at(1) });
+        if at(2) <= 0.5 {
+            return thrust;
+        }
+        let range = self.sim.agent_attack_range(id);
+        let (positions, teams, healths, ..) = self.sim.build_global_view();
+        let me = positions[id];
+        let (w, h) = (self.sim.width as f32, self.sim.height as f32);
+        let in_range = (0..positions.len()).any(|j| {
+            teams[j] != LEARNER_TEAM && healths[j] > 0.0 && me.torus_delta(positions[j], w, h).length() <= range
+        });
+        if !in_range {
+            thrust
+        } else if self.sim_cfg.weapon_select {
+            Action::FireSlot { slot: slot_for_output(at(3)) }
+        } else {
+            Action::Fire { weapon: Weapon::Laser { damage: 1.0, range } }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::{NaiveAgent, NaiveBrain};
+
+    #[test]
+    fn episode_runs_to_done_and_rewards_sum_to_fitness() {
+        let evo_cfg = EvolutionConfig { max_ticks: 60, team_size: 2, ..EvolutionConfig::default() };
+        let mut env = MultiAgentEnv::new(Config::default(), evo_cfg, || Box::new(NaiveBrain(NaiveAgent::new(1.2, 0.8))));
+        let obs = env.reset(7);
+        assert_eq!(obs.len(), env.num_agents());
+        assert!(obs.iter().all(|o| o.len() == env.observation_len()));
+
+        let start = env.fitness;
+        let mut total = 0.0;
+        let mut last = None;
+        for _ in 0..100 {
+            let step = env.step(&[[1.0, 0.0, 1.0], [0.0, -1.0, 0.0]]);
+            total += step.rewards[0];
+            if step.dones.iter().all(|&d| d) {
+                last = Some(step);
+                break;
+            }
+        }
+        let last = last.expect("episode ends within max_ticks");
+        assert!(last.info.tick <= 60);
+        assert!(last.info.truncated || last.info.winner.is_some() || env.sim.is_decided());
+        assert!((start + total - last.info.fitness).abs() < 1e-2);
+        // finished episodes stay finished
+        let after = env.step::<[f32; 3]>(&[]);
+        assert_eq!(after.info.tick, last.info.tick);
+        assert_eq!(after.rewards, vec![0.0; 2]);
+    }
+}
@@ -0,0 +1,72 @@
+//! `NeatError`: one error type for the fallible NEAT paths (champion loading,
+//! ONNX export and import, experiment files, remote inference, replay
+//! recording), so callers can `?` across them instead of matching each
+//! module's own error.
+
+use std::fmt;
+use super::champion::ChampionError;
+use super::experiment::ExperimentError;
+use super::genome::GenomeError;
+use super::onnx_importer::ImportError;
+#[cfg(not(target_arch = "wasm32"))]
+use super::brain::InferenceError;
+
+#[derive(Debug)]
+pub enum NeatError {
+    Io(std::io::Error),
+    /// Serializing or parsing JSON failed
+    Json(serde_json::Error),
+    /// A genome is structurally unusable for the operation
+    Genome(GenomeError),
+    Champion(ChampionError),
+    Import(ImportError),
+    Experiment(ExperimentError),
+    #[cfg(not(target_arch = "wasm32"))]
+    Inference(InferenceError),
+}
+
+impl fmt::Display for NeatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NeatError::Io(e) => write!(f, "I/O error: {}", e),
+            NeatError::Json(e) => write!(f, "JSON error: {}", e),
+            NeatError::Genome(e) => write!(f, "invalid genome: {}", e),
+            NeatError::Champion(e) => write!(f, "{}", e),
+            NeatError::Import(e) => write!(f, "ONNX import failed: {}", e),
+            NeatError::Experiment(e) => write!(f, "{}", e),
+            #[cfg(not(target_arch = "wasm32"))]
+            NeatError::Inference(e) => write!(f, "inference failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for NeatError {}
+
+impl From<std::io::Error> for NeatError {
+    fn from(e: std::io::Error) -> Self { NeatError::Io(e) }
+}
+
+impl From<serde_json::Error> for NeatError {
+    fn from(e: serde_json::Error) -> Self { NeatError::Json(e) }
+}
+
+impl From<GenomeError> for NeatError {
+    fn from(e: GenomeError) -> Self { NeatError::Genome(e) }
+}
+
+impl From<ChampionError> for NeatError {
+    fn from(e: ChampionError) -> Self { NeatError::Champion(e) }
+}
+
+impl From<ImportError> for NeatError {
+    fn from(e: ImportError) -> Self { NeatError::Import(e) }
+}
+
+impl From<ExperimentError> for NeatError {
+    fn from(e: ExperimentError) -> Self { NeatError::Experiment(e) }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl From<InferenceError> for NeatError {
+    fn from(e: InferenceError) -> Self { NeatError::Inference(e) }
+}
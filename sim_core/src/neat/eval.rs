@@ -0,0 +1,170 @@
+//! Head-to-head evaluation of two genomes: seeded matches played twice with
+//! sides swapped (so neither genome profits from its team slot), summarized
+//! as win/draw/loss, damage, and a sign test on the decisive games.
+
+use crate::brain::Brain;
+use crate::config::Config;
+use super::brain::NeatBrain;
+use super::config::EvolutionConfig;
+use super::genome::Genome;
+pub use super::runner::BrainFactory;
+use super::runner::{run_matches_parallel, Lineup, MatchOutcome, MatchStats};
+
+/// One game from A's point of view
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Game {
+    pub seed: u64,
+    /// Whether A played the first team slot
+    pub a_first: bool,
+    /// Health each side has left at the end
+    pub a_health: f32,
+    pub b_health: f32,
+    /// Health each side removed from the other
+    pub a_damage: f32,
+    pub b_damage: f32,
+    /// Result from A's side, under the run's tie-break rules
+    pub outcome: MatchOutcome,
+}
+
+impl Game {
+    /// From `stats` scored for A's team
+    fn from_stats(seed: u64, a_first: bool, stats: &MatchStats, team_health: f32) -> Self {
+        Game {
+            seed,
+            a_first,
+            a_health: stats.subject_team_health,
+            b_health: team_health - stats.total_damage_inflicted,
+            a_damage: stats.total_damage_inflicted,
+            b_damage: team_health - stats.subject_team_health,
+            outcome: stats.outcome,
+        }
+    }
+
+    /// 1 if A won, -1 if B won, 0 for a draw
+    pub fn result(&self) -> i32 {
+        self.outcome.result()
+    }
+}
+
+/// Summary of a head-to-head series
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HeadToHead {
+    pub games: Vec<Game>,
+}
+
+impl HeadToHead {
+    /// Play every seed twice, once from each side, with `team_size` copies of
+    /// each genome per team
+    pub fn play(sim_cfg: &Config, evo_cfg: &EvolutionConfig, a: &Genome, b: &Genome, seeds: &[u64]) -> Self {
+        let brain_a = || -> Box<dyn Brain> { Box::new(NeatBrain::for_config(a.clone(), sim_cfg)) };
+        let brain_b = || -> Box<dyn Brain> { Box::new(NeatBrain::for_config(b.clone(), sim_cfg)) };
+        HeadToHead::play_brains(sim_cfg, evo_cfg, &brain_a, &brain_b, seeds)
+    }
+
+    /// `play` for any pair of controllers (e.g. a genome against NaiveAgent)
+    pub fn play_brains<'a>(sim_cfg: &Config, evo_cfg: &EvolutionConfig, a: BrainFactory<'a>, b: BrainFactory<'a>, seeds: &[u64]) -> Self {
+        // always two sides, whatever the training setup, so damage is measured against one team
+        let evo_cfg = &EvolutionConfig { num_teams: 2, ..evo_cfg.clone() };
+        let team_health = sim_cfg.health_max * evo_cfg.team_size as f32;
+        let side = |make, team| std::iter::repeat_n((make, team), evo_cfg.team_size);
+        let a_first = Lineup::new(side(a, 0).chain(side(b, 1)).collect());
+        // every seed with A first, then mirrored; both scored for A
+        let lineups = [a_first.clone(), a_first.mirrored()];
+        let stats = run_matches_parallel(sim_cfg, evo_cfg, &lineups, seeds);
+        let (a_first, b_first) = stats.split_at(seeds.len());
+        let games = seeds.iter().zip(a_first.iter().zip(b_first))
+            .flat_map(|(&seed, (x, y))| [
+                Game::from_stats(seed, true, x, team_health),
+                Game::from_stats(seed, false, y, team_health),
+            ])
+            .collect();
+        HeadToHead { games }
+    }
+
+    /// A's wins, draws, and losses
+    pub fn record(&self) -> (usize, usize, usize) {
+        let count = |r| self.games.iter().filter(|g| g.result() == r).count();
+        (count(1), count(0), count(-1))
+    }
+
+    /// A's share of the points: 1 per win and 0.5 per draw, over all games
+    /// (0.5 for an empty series)
+    pub fn score(&self) -> f32 {
+        let (wins, draws, _) = self.record();
+        if self.games.is_empty() {
+            return 0.5;
+        }
+        (wins as f32 + 0.5 * draws as f32) / self.games.len() as f32
+    }
+
+    /// Mean damage dealt per game by A and by B
+    pub fn mean_damage(&self) -> (f32, f32) {
+        let n = self.games.len().max(1) as f32;
+        (
+            self.games.iter().map(|g| g.a_damage).sum::<f32>() / n,
+            self.games.iter().map(|g| g.b_damage).sum::<f32>() / n,
+        )
+    }
+
+    /// Two-sided exact sign test p-value for "A and B are equally strong",
+    /// over decisive games (draws are ignored)
+    pub fn p_value(&self) -> f64 {
+        let (wins, _, losses) = self.record();
+        sign_test(wins, losses)
+    }
+}
+
+/// Two-sided exact binomial test of `wins` vs `losses` at p = 0.5
+pub fn sign_test(wins: usize, losses: usize) -> f64 {
+    let n = wins + losses;
+    if n == 0 {
+        return 1.0;
+    }
+    // P(X <= min) under Binomial(n, 0.5), summed in log space so large n does not underflow
+    let ln2n = n as f64 * std::f64::consts::LN_2;
+    let mut ln_choose = 0.0;
+    let mut tail = 0.0;
+    for k in 0..=wins.min(losses) {
+        if k > 0 {
+            ln_choose += ((n - k + 1) as f64).ln() - (k as f64).ln();
+        }
+        tail += (ln_choose - ln2n).exp();
+    }
+    (2.0 * tail).min(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sign_test_matches_binomial() {
+        assert_eq!(sign_test(0, 0), 1.0);
+        assert_eq!(sign_test(5, 5), 1.0);
+        // 2 * P(X <= 0 | n = 10) = 2 / 1024
+        assert!((sign_test(10, 0) - 2.0 / 1024.0).abs() < 1e-12);
+        // 2 * (1 + 10 + 45) / 1024
+        assert!((sign_test(2, 8) - 112.0 / 1024.0).abs() < 1e-12);
+        assert!(sign_test(900, 1100) < 1e-4);
+    }
+
+    #[test]
+    fn self_play_is_mirrored() {
+        let sim_cfg = Config::default();
+        let evo_cfg = EvolutionConfig { team_size: 1, max_ticks: 20, ..Default::default() };
+        let mut genome = Genome::new();
+        genome.initialize(&sim_cfg, &evo_cfg, &mut rand::thread_rng());
+        let h2h = HeadToHead::play(&sim_cfg, &evo_cfg, &genome, &genome, &[1, 2]);
+        assert_eq!(h2h.games.len(), 4);
+        // identical genomes: each seed's two games are the same match seen from either side
+        for pair in h2h.games.chunks(2) {
+            let (g, m) = (pair[0], pair[1]);
+            assert_eq!((g.seed, g.a_first, m.a_first), (m.seed, true, false));
+            assert_eq!((g.a_health, g.b_health), (m.b_health, m.a_health));
+            assert_eq!(g.result(), -m.result());
+        }
+        let (w, d, l) = h2h.record();
+        assert_eq!((w + d + l, w), (4, l));
+        assert_eq!(h2h.score(), 0.5);
+    }
+}
@@ -0,0 +1,145 @@
+//! Experiment files: one TOML document describing a training run, so it can
+//! be reproduced without a long command line.
+//!
+//! ```toml
+//! [train]        # `neat_train train` options, by flag name
+//! runs = 50
+//! [sim]          # `Config` fields
+//! scan_max_dist = 400.0
+//! [evolution]    # `EvolutionConfig` fields
+//! pop_size = 40
+//! fitness_fn = "health-damage-explore"
+//! ```
+//!
+//! Each section only lists what it changes: `[sim]` and `[evolution]` are laid
+//! over a base config, replacing the top-level fields they name.
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use serde::{de::DeserializeOwned, Serialize, Deserialize};
+use toml::{Table, Value};
+use crate::config::Config;
+use super::config::EvolutionConfig;
+
+/// Errors from loading, resolving, or saving an experiment file
+#[derive(Debug)]
+pub enum ExperimentError {
+    /// Reading or writing the file failed
+    Io(std::io::Error),
+    /// The file is not valid TOML, or a section does not fit its config
+    Parse(String),
+}
+
+impl fmt::Display for ExperimentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExperimentError::Io(e) => write!(f, "experiment io error: {}", e),
+            ExperimentError::Parse(msg) => write!(f, "experiment parse error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ExperimentError {}
+
+impl From<std::io::Error> for ExperimentError {
+    fn from(e: std::io::Error) -> Self { ExperimentError::Io(e) }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Experiment {
+    /// Options of the command running the experiment, keyed by flag name
+    pub train: Table,
+    /// Overrides of the simulation `Config`
+    pub sim: Table,
+    /// Overrides of the `EvolutionConfig`
+    pub evolution: Table,
+}
+
+impl Experiment {
+    pub fn from_toml_str(s: &str) -> Result<Self, ExperimentError> {
+        toml::from_str(s).map_err(|e| ExperimentError::Parse(e.to_string()))
+    }
+
+    pub fn from_toml_file<P: AsRef<Path>>(path: P) -> Result<Self, ExperimentError> {
+        Experiment::from_toml_str(&fs::read_to_string(path)?)
+    }
+
+    /// A complete description of a run: every field of both configs
+    pub fn resolved<T: Serialize>(train: &T, sim_cfg: &Config, evo_cfg: &EvolutionConfig) -> Result<Self, ExperimentError> {
+        Ok(Experiment { train: to_table(train)?, sim: to_table(sim_cfg)?, evolution: to_table(evo_cfg)? })
+    }
+
+    /// `base` with the fields named in `[sim]` replaced
+    pub fn sim_config(&self, base: &Config) -> Result<Config, ExperimentError> {
+        overlay(base, &self.sim)
+    }
+
+    /// `base` with the fields named in `[evolution]` replaced
+    pub fn evolution_config(&self, base: &EvolutionConfig) -> Result<EvolutionConfig, ExperimentError> {
+        let cfg: EvolutionConfig = overlay(base, &self.evolution)?;
+        if crate::scripted::by_name(&cfg.baseline).is_none() {
+            return Err(ExperimentError::Parse(format!(
+                "unknown baseline opponent {:?}; expected one of {}", cfg.baseline, crate::scripted::NAMES.join(", "),
+            )));
+        }
+        cfg.validate().map_err(|e| ExperimentError::Parse(e.to_string()))?;
+        Ok(cfg)
+    }
+
+    pub fn to_toml(&self) -> Result<String, ExperimentError> {
+        toml::to_string_pretty(self).map_err(|e| ExperimentError::Parse(e.to_string()))
+    }
+
+    pub fn to_toml_file<P: AsRef<Path>>(&self, path: P) -> Result<(), ExperimentError> {
+        fs::write(path, self.to_toml()?)?;
+        Ok(())
+    }
+}
+
+fn to_table<T: Serialize>(value: &T) -> Result<Table, ExperimentError> {
+    Table::try_from(value).map_err(|e| ExperimentError::Parse(e.to_string()))
+}
+
+fn overlay<T: Serialize + DeserializeOwned>(base: &T, fields: &Table) -> Result<T, ExperimentError> {
+    let mut merged = to_table(base)?;
+    merged.extend(fields.iter().map(|(k, v)| (k.clone(), v.clone())));
+    Value::Table(merged).try_into().map_err(|e: toml::de::Error| ExperimentError::Parse(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neat::config::FitnessFn;
+
+    #[test]
+    fn sections_override_only_named_fields() {
+        let exp = Experiment::from_toml_str(
+            "[train]\nruns = 3\n[sim]\nscan_max_dist = 123.0\n[evolution]\npop_size = 7\nfitness_fn = \"novelty\"\n",
+        ).unwrap();
+        assert_eq!(exp.train["runs"].as_integer(), Some(3));
+        let base = EvolutionConfig { tournament_k: 2, ..Default::default() };
+        let evo_cfg = exp.evolution_config(&base).unwrap();
+        assert_eq!((evo_cfg.pop_size, evo_cfg.tournament_k), (7, 2));
+        assert!(matches!(evo_cfg.fitness_fn, FitnessFn::Novelty));
+        let sim_cfg = exp.sim_config(&Config::default()).unwrap();
+        assert_eq!(sim_cfg.scan_max_dist, 123.0);
+        assert_eq!(sim_cfg.max_speed, Config::default().max_speed);
+
+        let text = Experiment::resolved(&exp.train, &sim_cfg, &evo_cfg).unwrap().to_toml().unwrap();
+        let back = Experiment::from_toml_str(&text).unwrap();
+        let evo_back = back.evolution_config(&EvolutionConfig::default()).unwrap();
+        assert_eq!((evo_back.pop_size, evo_back.tournament_k), (7, 2));
+        assert_eq!(back.sim_config(&Config::default()).unwrap().scan_max_dist, 123.0);
+    }
+
+    #[test]
+    fn mistyped_field_is_an_error() {
+        let exp = Experiment::from_toml_str("[evolution]\npop_size = \"many\"\n").unwrap();
+        assert!(matches!(exp.evolution_config(&EvolutionConfig::default()), Err(ExperimentError::Parse(_))));
+        let exp = Experiment::from_toml_str("[evolution]\nmutation_weight_rate = 1.5\n").unwrap();
+        let err = exp.evolution_config(&EvolutionConfig::default()).err().unwrap();
+        assert!(err.to_string().contains("mutation_weight_rate"), "{}", err);
+    }
+}
@@ -0,0 +1,337 @@
+//! Custom fitness functions, for trying new fitness ideas without adding a
+//! `FitnessFn` variant: either a closure supplied in code (`CustomFitness`)
+//! or an arithmetic expression over match stats and config weights read from
+//! the config file (`FitnessExpr`).
+//!
+//! ```toml
+//! [evolution]
+//! fitness_fn = { expr = "health + 2 * damage - 0.1 * longest_idle" }
+//! ```
+//!
+//! Expressions support numbers, `+ - * /`, unary minus, parentheses,
+//! `min(a, b)`, `max(a, b)` and `abs(a)`, and the names listed in
+//! `VARIABLES`. Dividing by zero gives 0 so fitness stays finite.
+
+use std::fmt;
+use std::sync::Arc;
+use serde::{Deserialize, Serialize, Serializer};
+use super::config::EvolutionConfig;
+use super::runner::{MatchOutcome, MatchStats};
+
+/// Names an expression can refer to
+pub const VARIABLES: &[&str] = &[
+    "health", "damage", "kills", "salvage", "explore", "ticks", "max_ticks", "survival_ticks",
+    "movement_entropy", "trigger_pulls", "shots", "hits", "accuracy", "fire_efficiency",
+    "damage_per_shot", "friendly_damage", "longest_idle", "won",
+    "w_health", "w_damage", "w_kills", "w_salvage", "w_explore", "time_bonus_weight",
+];
+
+fn variable(name: &str, stats: &MatchStats, evo_cfg: &EvolutionConfig) -> f32 {
+    match name {
+        "health" => stats.subject_team_health,
+        "damage" => stats.total_damage_inflicted,
+        "kills" => stats.kills as f32,
+        "salvage" => stats.salvage_actions,
+        "explore" => stats.exploration_actions,
+        "ticks" => stats.ticks as f32,
+        "max_ticks" => evo_cfg.max_ticks as f32,
+        "survival_ticks" => stats.survival_ticks as f32,
+        "movement_entropy" => stats.movement_entropy,
+        "trigger_pulls" => stats.fire.trigger_pulls as f32,
+        "shots" => stats.fire.shots as f32,
+        "hits" => stats.fire.hits as f32,
+        "accuracy" => stats.fire.accuracy(),
+        "fire_efficiency" => stats.fire.efficiency(),
+        "damage_per_shot" => divide(stats.total_damage_inflicted, stats.fire.shots as f32),
+        "friendly_damage" => stats.fire.friendly_damage,
+        "longest_idle" => stats.longest_idle as f32,
+        "won" => matches!(stats.outcome, MatchOutcome::WinA { .. }) as u8 as f32,
+        "w_health" => evo_cfg.w_health,
+        "w_damage" => evo_cfg.w_damage,
+        "w_kills" => evo_cfg.w_kills,
+        "w_salvage" => evo_cfg.w_salvage,
+        "w_explore" => evo_cfg.w_explore,
+        "time_bonus_weight" => evo_cfg.time_bonus_weight,
+        _ => 0.0,
+    }
+}
+
+fn divide(a: f32, b: f32) -> f32 {
+    if b == 0.0 { 0.0 } else { a / b }
+}
+
+/// Fitness closure supplied in code; serialized as a placeholder, so a
+/// config holding one cannot be read back
+#[derive(Clone)]
+pub struct CustomFitness(Arc<FitnessClosure>);
+
+type FitnessClosure = dyn Fn(&MatchStats, &EvolutionConfig) -> f32 + Send + Sync;
+
+impl CustomFitness {
+    pub fn new(f: impl Fn(&MatchStats, &EvolutionConfig) -> f32 + Send + Sync + 'static) -> Self {
+        CustomFitness(Arc::new(f))
+    }
+
+    pub fn compute(&self, stats: &MatchStats, evo_cfg: &EvolutionConfig) -> f32 {
+        (self.0)(stats, evo_cfg)
+    }
+}
+
+impl Serialize for CustomFitness {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str("closure")
+    }
+}
+
+/// Errors from parsing a fitness expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExprError {
+    /// Malformed input at byte offset `pos`
+    Syntax { pos: usize, msg: &'static str },
+    UnknownVariable(String),
+    UnknownFunction(String),
+    /// A function called with the wrong number of arguments
+    Arity { function: String, expected: usize, found: usize },
+}
+
+impl fmt::Display for ExprError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExprError::Syntax { pos, msg } => write!(f, "{} at position {}", msg, pos),
+            ExprError::UnknownVariable(name) => write!(f, "unknown variable `{}` (expected one of {})", name, VARIABLES.join(", ")),
+            ExprError::UnknownFunction(name) => write!(f, "unknown function `{}` (expected min, max or abs)", name),
+            ExprError::Arity { function, expected, found } => {
+                write!(f, "`{}` takes {} argument(s), got {}", function, expected, found)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExprError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Num(f32),
+    Var(&'static str),
+    Neg(Box<Node>),
+    Add(Box<Node>, Box<Node>),
+    Sub(Box<Node>, Box<Node>),
+    Mul(Box<Node>, Box<Node>),
+    Div(Box<Node>, Box<Node>),
+    Min(Box<Node>, Box<Node>),
+    Max(Box<Node>, Box<Node>),
+    Abs(Box<Node>),
+}
+
+impl Node {
+    fn eval(&self, stats: &MatchStats, evo_cfg: &EvolutionConfig) -> f32 {
+        let ev = |n: &Node| n.eval(stats, evo_cfg);
+        match self {
+            Node::Num(v) => *v,
+            Node::Var(name) => variable(name, stats, evo_cfg),
+            Node::Neg(a) => -ev(a),
+            Node::Add(a, b) => ev(a) + ev(b),
+            Node::Sub(a, b) => ev(a) - ev(b),
+            Node::Mul(a, b) => ev(a) * ev(b),
+            Node::Div(a, b) => divide(ev(a), ev(b)),
+            Node::Min(a, b) => ev(a).min(ev(b)),
+            Node::Max(a, b) => ev(a).max(ev(b)),
+            Node::Abs(a) => ev(a).abs(),
+        }
+    }
+}
+
+/// Fitness expression parsed from text; serializes back to its source
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct FitnessExpr {
+    source: String,
+    root: Node,
+}
+
+impl FitnessExpr {
+    pub fn parse(source: &str) -> Result<Self, ExprError> {
+        let mut parser = Parser { src: source.as_bytes(), pos: 0 };
+        let root = parser.expr()?;
+        parser.skip_ws();
+        if parser.pos < parser.src.len() {
+            return Err(parser.error("unexpected input"));
+        }
+        Ok(FitnessExpr { source: source.to_string(), root })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn eval(&self, stats: &MatchStats, evo_cfg: &EvolutionConfig) -> f32 {
+        self.root.eval(stats, evo_cfg)
+    }
+}
+
+impl TryFrom<String> for FitnessExpr {
+    type Error = ExprError;
+
+    fn try_from(source: String) -> Result<Self, ExprError> {
+        FitnessExpr::parse(&source)
+    }
+}
+
+impl From<FitnessExpr> for String {
+    fn from(expr: FitnessExpr) -> String {
+        expr.source
+    }
+}
+
+/// Recursive-descent parser: `expr := term (+|- term)*`,
+/// `term := unary (*|/ unary)*`, `unary := -unary | atom`
+struct Parser<'a> {
+    src: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, msg: &'static str) -> ExprError {
+        ExprError::Syntax { pos: self.pos, msg }
+    }
+
+    fn skip_ws(&mut self) {
+        while self.src.get(self.pos).is_some_and(|c| c.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    /// Consume `c` if it is the next non-space character
+    fn eat(&mut self, c: u8) -> bool {
+        self.skip_ws();
+        let found = self.src.get(self.pos) == Some(&c);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expr(&mut self) -> Result<Node, ExprError> {
+        let mut node = self.term()?;
+        loop {
+            if self.eat(b'+') {
+                node = Node::Add(Box::new(node), Box::new(self.term()?));
+            } else if self.eat(b'-') {
+                node = Node::Sub(Box::new(node), Box::new(self.term()?));
+            } else {
+                return Ok(node);
+            }
+        }
+    }
+
+    fn term(&mut self) -> Result<Node, ExprError> {
+        let mut node = self.unary()?;
+        loop {
+            if self.eat(b'*') {
+                node = Node::Mul(Box::new(node), Box::new(self.unary()?));
+            } else if self.eat(b'/') {
+                node = Node::Div(Box::new(node), Box::new(self.unary()?));
+            } else {
+                return Ok(node);
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<Node, ExprError> {
+        if self.eat(b'-') {
+            return Ok(Node::Neg(Box::new(self.unary()?)));
+        }
+        self.atom()
+    }
+
+    fn atom(&mut self) -> Result<Node, ExprError> {
+        if self.eat(b'(') {
+            let node = self.expr()?;
+            return if self.eat(b')') { Ok(node) } else { Err(self.error("expected `)`")) };
+        }
+        let start = self.pos;
+        match self.src.get(self.pos) {
+            Some(c) if c.is_ascii_digit() || *c == b'.' => {
+                while self.src.get(self.pos).is_some_and(|c| c.is_ascii_digit() || *c == b'.') {
+                    self.pos += 1;
+                }
+                let text = std::str::from_utf8(&self.src[start..self.pos]).unwrap_or_default();
+                text.parse().map(Node::Num).map_err(|_| ExprError::Syntax { pos: start, msg: "malformed number" })
+            }
+            Some(c) if c.is_ascii_alphabetic() || *c == b'_' => {
+                while self.src.get(self.pos).is_some_and(|c| c.is_ascii_alphanumeric() || *c == b'_') {
+                    self.pos += 1;
+                }
+                let name = std::str::from_utf8(&self.src[start..self.pos]).unwrap_or_default().to_string();
+                if self.eat(b'(') {
+                    self.call(name)
+                } else {
+                    VARIABLES.iter().find(|v| **v == name).copied().map(Node::Var).ok_or(ExprError::UnknownVariable(name))
+                }
+            }
+            Some(_) => Err(self.error("expected a number, name or `(`")),
+            None => Err(self.error("unexpected end of expression")),
+        }
+    }
+
+    /// Arguments and closing parenthesis of a call to `name`
+    fn call(&mut self, name: String) -> Result<Node, ExprError> {
+        let mut args = vec![self.expr()?];
+        while self.eat(b',') {
+            args.push(self.expr()?);
+        }
+        if !self.eat(b')') {
+            return Err(self.error("expected `)`"));
+        }
+        let expected = match name.as_str() {
+            "min" | "max" => 2,
+            "abs" => 1,
+            _ => return Err(ExprError::UnknownFunction(name)),
+        };
+        if args.len() != expected {
+            return Err(ExprError::Arity { function: name, expected, found: args.len() });
+        }
+        let mut args = args.into_iter().map(Box::new);
+        let mut next = || args.next().expect("arity checked");
+        Ok(match name.as_str() {
+            "min" => Node::Min(next(), next()),
+            "max" => Node::Max(next(), next()),
+            _ => Node::Abs(next()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expressions_follow_precedence_and_reject_unknown_names() {
+        let stats = MatchStats { subject_team_health: 10.0, total_damage_inflicted: 4.0, longest_idle: 20, ..MatchStats::default() };
+        let evo_cfg = EvolutionConfig::default();
+        let eval = |s: &str| FitnessExpr::parse(s).unwrap().eval(&stats, &evo_cfg);
+        assert_eq!(eval("health + 2 * damage - 0.1 * longest_idle"), 16.0);
+        assert_eq!(eval("-(health - damage) / 2"), -3.0);
+        assert_eq!(eval("max(health, abs(-30)) + min(1, 2) + damage / shots"), 31.0);
+
+        assert_eq!(FitnessExpr::parse("hp + 1"), Err(ExprError::UnknownVariable("hp".into())));
+        assert_eq!(FitnessExpr::parse("max(1)"), Err(ExprError::Arity { function: "max".into(), expected: 2, found: 1 }));
+        assert!(matches!(FitnessExpr::parse("health +"), Err(ExprError::Syntax { pos: 8, .. })));
+        assert!(matches!(FitnessExpr::parse("(health"), Err(ExprError::Syntax { .. })));
+    }
+
+    #[test]
+    fn config_files_and_code_supply_custom_fitness() {
+        use super::super::config::FitnessFn;
+        let stats = MatchStats { kills: 2, ..MatchStats::default() };
+        let evo_cfg: EvolutionConfig = toml::from_str("fitness_fn = { expr = \"3 * kills + 1\" }").unwrap();
+        assert_eq!(evo_cfg.fitness_fn.compute(&stats, &evo_cfg), 7.0);
+        let saved = toml::to_string(&evo_cfg).unwrap();
+        assert!(saved.contains("expr = \"3 * kills + 1\""));
+        assert!(toml::from_str::<EvolutionConfig>("fitness_fn = { expr = \"3 * kilns\" }").is_err());
+
+        let evo_cfg = EvolutionConfig { fitness_fn: FitnessFn::custom(|s, cfg| s.kills as f32 * cfg.w_kills), ..evo_cfg };
+        assert_eq!(evo_cfg.fitness_fn.compute(&stats, &evo_cfg), 1.0);
+        assert!(serde_json::to_string(&evo_cfg).is_ok());
+    }
+}
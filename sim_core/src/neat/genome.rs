@@ -1,7 +1,14 @@
 use crate::config::Config as SimConfig;
-use rand::{thread_rng, Rng, seq::SliceRandom};
+use crate::math;
+use rand::{Rng, seq::SliceRandom};
+use rand_distr::{Distribution, Normal};
 use std::collections::HashMap;
-use super::config::EvolutionConfig;
+use std::fmt;
+use super::config::{DisjointGenes, EvolutionConfig, MatchingGenes};
+use super::error::NeatError;
+use super::innovation::InnovationTracker;
+use super::schema::SCHEMA_VERSION;
+use super::metrics::BehaviorMetrics;
 use super::onnx_exporter;
 use serde::{Serialize, Deserialize};
 use prost::Message;
@@ -13,6 +20,39 @@ pub enum NodeType {
     Input,
     Hidden,
     Output,
+    /// Constant 1.0 source; its outgoing weights act as neuron biases
+    Bias,
+}
+
+/// Activation function applied to a node's weighted input sum
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Activation {
+    #[default]
+    Tanh,
+    Relu,
+    Sigmoid,
+    Sine,
+    /// exp(-x²)
+    Gaussian,
+    /// Pass-through; used for values layering carries forward, never mutated in
+    Identity,
+}
+
+impl Activation {
+    pub const ALL: [Activation; 5] = [
+        Activation::Tanh, Activation::Relu, Activation::Sigmoid, Activation::Sine, Activation::Gaussian,
+    ];
+
+    pub fn apply(self, x: f32) -> f32 {
+        match self {
+            Activation::Tanh => math::tanh(x),
+            Activation::Relu => x.max(0.0),
+            Activation::Sigmoid => 1.0 / (1.0 + math::exp(-x)),
+            Activation::Sine => math::sin(x),
+            Activation::Gaussian => math::exp(-x * x),
+            Activation::Identity => x,
+        }
+    }
 }
 
 /// A node in the network
@@ -20,6 +60,16 @@ pub enum NodeType {
 pub struct NodeGene {
     pub id: usize,
     pub node_type: NodeType,
+    /// Genomes saved before activation genes load as tanh
+    #[serde(default)]
+    pub activation: Activation,
+}
+
+impl NodeGene {
+    /// Node with the default (tanh) activation
+    pub fn new(id: usize, node_type: NodeType) -> Self {
+        NodeGene { id, node_type, activation: Activation::default() }
+    }
 }
 
 /// A connection with innovation number
@@ -32,45 +82,96 @@ pub struct ConnGene {
     pub innovation: usize,
 }
 
+/// Structural defect found by `Genome::validate`
+#[derive(Debug, Clone, PartialEq)]
+pub enum GenomeError {
+    /// Two nodes share an id
+    DuplicateNode(usize),
+    /// A connection references a node id the genome does not contain
+    DanglingNode { innovation: usize, node: usize },
+    /// Two connections share an innovation number
+    DuplicateInnovation(usize),
+    /// Enabled connections form a cycle through these node ids
+    Cycle(Vec<usize>),
+}
+
+impl fmt::Display for GenomeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenomeError::DuplicateNode(id) => write!(f, "duplicate node id {}", id),
+            GenomeError::DanglingNode { innovation, node } => {
+                write!(f, "connection {} references missing node {}", innovation, node)
+            }
+            GenomeError::DuplicateInnovation(innov) => write!(f, "duplicate innovation number {}", innov),
+            GenomeError::Cycle(ids) => write!(f, "cycle through nodes {:?} with recurrence disabled", ids),
+        }
+    }
+}
+
+impl std::error::Error for GenomeError {}
+
 /// A genome: lists of nodes & connections and its fitness
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Genome {
+    /// Layout version this genome was written with (see `schema`)
+    #[serde(default = "current_schema")]
+    pub schema_version: u32,
+    /// Unique id within a run, assigned by `Genealogy::register`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<usize>,
+    /// Ids of the genomes this one was bred from
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parents: Vec<usize>,
     pub nodes: Vec<NodeGene>,
     pub conns: Vec<ConnGene>,
     /// Accumulated fitness of this genome
     pub fitness: f32,
     /// Fitness against NaiveAgent baseline
     pub fitness_naive: f32,
+    /// Mean `MatchStats::behavior` from the last evaluation
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub behavior: Vec<f32>,
+    /// Mean per-objective scores from the last evaluation (see `nsga2::objectives`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub objectives: Vec<f32>,
+    /// Mean behavior metrics from the last evaluation
+    #[serde(skip)]
+    pub metrics: BehaviorMetrics,
 }
 
+fn current_schema() -> u32 { SCHEMA_VERSION }
+
 impl Genome {
     /// Create an initial minimal genome
     pub fn new() -> Self {
-        Genome { nodes: Vec::new(), conns: Vec::new(), fitness: 0.0, fitness_naive: 0.0 }
+        Genome {
+            schema_version: SCHEMA_VERSION, id: None, parents: Vec::new(), nodes: Vec::new(), conns: Vec::new(),
+            fitness: 0.0, fitness_naive: 0.0, behavior: Vec::new(), objectives: Vec::new(),
+            metrics: BehaviorMetrics::default(),
+        }
     }
 
     /// Initialize as minimal fully-connected network
-    pub fn initialize(&mut self, sim_cfg: &SimConfig, evo_cfg: &EvolutionConfig) {
+    pub fn initialize<R: Rng + ?Sized>(&mut self, sim_cfg: &SimConfig, evo_cfg: &EvolutionConfig, rng: &mut R) {
         // inputs: [self_hp, self_shield] + per-enemy (dx,dy,hp,shield) + per-ally (dx,dy,hp,shield) + per-wreck (dx,dy,pool)
-        let input_size = 2
-            + sim_cfg.nearest_k_enemies * 4
-            + sim_cfg.nearest_k_allies * 4
-            + sim_cfg.nearest_k_wrecks * 3;
-        let output_size = 3;
+        let input_size = sim_cfg.sensor_len();
+        let output_size = sim_cfg.action_layout().len();
         self.nodes.clear();
         self.conns.clear();
         // input nodes
         for i in 0..input_size {
-            self.nodes.push(NodeGene { id: i as usize, node_type: NodeType::Input });
+            self.nodes.push(NodeGene::new(i as usize, NodeType::Input));
         }
         // output nodes
         for j in 0..output_size {
-            self.nodes.push(NodeGene { id: input_size as usize + j as usize, node_type: NodeType::Output });
+            self.nodes.push(NodeGene::new(input_size as usize + j as usize, NodeType::Output));
         }
-        // full connect inputs→outputs
-        let mut rng = thread_rng();
+        // bias node
+        let bias_id = input_size + output_size;
+        self.nodes.push(NodeGene::new(bias_id, NodeType::Bias));
+        // full connect inputs and bias→outputs
         let mut innov = 0;
-        for in_node in 0..input_size {
+        for in_node in (0..input_size).chain(std::iter::once(bias_id)) {
             for out_node in input_size..(input_size + output_size) {
                 let w = rng.gen_range(-1.0..1.0);
                 self.conns.push(ConnGene { in_node: in_node as usize, out_node: out_node as usize, weight: w, enabled: true, innovation: innov });
@@ -79,24 +180,60 @@ impl Genome {
         }
     }
 
-    /// Mutate the genome by adding node or connection
-    pub fn mutate(&mut self, cfg: &EvolutionConfig) {
-        let mut rng = thread_rng();
+    /// Mutate the genome: perturb weights, then maybe add a node or connection.
+    /// Structural mutations take their innovation numbers and node ids from `tracker`.
+    pub fn mutate<R: Rng + ?Sized>(&mut self, cfg: &EvolutionConfig, tracker: &mut InnovationTracker, rng: &mut R) {
+        // Weight mutation: gaussian perturbation, occasionally a full reset
+        if let Ok(noise) = Normal::new(0.0, cfg.mutation_weight_sigma) {
+            for c in &mut self.conns {
+                if rng.gen_bool(cfg.mutation_weight_rate as f64) {
+                    if rng.gen_bool(cfg.mutation_weight_reset_rate as f64) {
+                        c.weight = rng.gen_range(-1.0..1.0);
+                    } else {
+                        c.weight += noise.sample(rng);
+                    }
+                }
+            }
+        }
+        // Toggle mutations: revive a disabled gene, or switch off an enabled one
+        if rng.gen_bool(cfg.mutation_enable_rate as f64) {
+            let disabled: Vec<usize> = (0..self.conns.len()).filter(|&i| !self.conns[i].enabled).collect();
+            if let Some(&i) = disabled.choose(rng) {
+                self.conns[i].enabled = true;
+            }
+        }
+        if rng.gen_bool(cfg.mutation_disable_rate as f64) {
+            let enabled: Vec<usize> = (0..self.conns.len()).filter(|&i| self.conns[i].enabled).collect();
+            if let Some(&i) = enabled.choose(rng) {
+                self.conns[i].enabled = false;
+            }
+        }
+        // Activation mutation: hidden nodes only, so outputs keep their [-1, 1] range
+        if rng.gen_bool(cfg.mutation_activation_rate as f64) {
+            let hidden: Vec<usize> = (0..self.nodes.len()).filter(|&i| self.nodes[i].node_type == NodeType::Hidden).collect();
+            if let Some(&i) = hidden.choose(rng) {
+                self.nodes[i].activation = *Activation::ALL.choose(rng).unwrap();
+            }
+        }
         // Add connection mutation
         if rng.gen_bool(cfg.mutation_add_conn_rate as f64) {
             for _ in 0..100 {
-                let in_gene = self.nodes.choose(&mut rng).unwrap();
-                let out_gene = self.nodes.choose(&mut rng).unwrap();
-                if in_gene.node_type == NodeType::Output
-                    || out_gene.node_type == NodeType::Input
-                    || in_gene.id == out_gene.id
+                let in_gene = self.nodes.choose(rng).unwrap();
+                let out_gene = self.nodes.choose(rng).unwrap();
+                if matches!(out_gene.node_type, NodeType::Input | NodeType::Bias) {
+                    continue;
+                }
+                if !cfg.allow_recurrent
+                    && (in_gene.node_type == NodeType::Output
+                        || in_gene.id == out_gene.id
+                        || self.reaches(out_gene.id, in_gene.id))
                 {
                     continue;
                 }
                 if self.conns.iter().any(|c| c.in_node == in_gene.id && c.out_node == out_gene.id) {
                     continue;
                 }
-                let innov = self.conns.len();
+                let innov = tracker.connection(in_gene.id, out_gene.id);
                 let weight = rng.gen_range(-1.0..1.0);
                 self.conns.push(ConnGene { in_node: in_gene.id, out_node: out_gene.id, weight, enabled: true, innovation: innov });
                 break;
@@ -107,29 +244,28 @@ impl Genome {
             // pick a random enabled connection to split
             let enabled_idxs: Vec<usize> = self.conns.iter().enumerate()
                 .filter_map(|(i, c)| if c.enabled { Some(i) } else { None }).collect();
-            if let Some(&idx) = enabled_idxs.choose(&mut rng) {
+            if let Some(&idx) = enabled_idxs.choose(rng) {
                 // clone and disable the connection
                 let old_conn = self.conns[idx].clone();
                 self.conns[idx].enabled = false;
-                // new hidden node
-                let new_id = self.nodes.iter().map(|n| n.id).max().unwrap() + 1;
-                self.nodes.push(NodeGene { id: new_id, node_type: NodeType::Hidden });
+                // new hidden node, shared with every genome making the same split
+                let split = tracker.split(self, old_conn.innovation, old_conn.in_node, old_conn.out_node);
+                let new_id = split.node_id;
+                self.nodes.push(NodeGene::new(new_id, NodeType::Hidden));
                 // split connection into two
-                let innov1 = self.conns.len();
-                self.conns.push(ConnGene { in_node: old_conn.in_node, out_node: new_id, weight: 1.0, enabled: true, innovation: innov1 });
-                let innov2 = self.conns.len();
-                self.conns.push(ConnGene { in_node: new_id, out_node: old_conn.out_node, weight: old_conn.weight, enabled: true, innovation: innov2 });
+                self.conns.push(ConnGene { in_node: old_conn.in_node, out_node: new_id, weight: 1.0, enabled: true, innovation: split.in_innovation });
+                self.conns.push(ConnGene { in_node: new_id, out_node: old_conn.out_node, weight: old_conn.weight, enabled: true, innovation: split.out_innovation });
             }
         }
     }
 
     /// Crossover two parents to produce a child
-    pub fn crossover(
+    pub fn crossover<R: Rng + ?Sized>(
         parent1: &Genome,
         parent2: &Genome,
         cfg: &EvolutionConfig,
+        rng: &mut R,
     ) -> Genome {
-        let mut rng = thread_rng();
         // Determine fitter and weaker parents
         let (fitter, weaker) = if parent1.fitness >= parent2.fitness {
             (parent1, parent2)
@@ -137,6 +273,8 @@ impl Genome {
             (parent2, parent1)
         };
         let mut child = Genome::new();
+        child.parents = fitter.id.into_iter().chain(weaker.id).collect();
+        child.parents.dedup();
         // Merge nodes
         let mut node_map: HashMap<usize, NodeGene> = HashMap::new();
         for n in &fitter.nodes {
@@ -146,33 +284,144 @@ impl Genome {
             node_map.entry(n.id).or_insert_with(|| n.clone());
         }
         child.nodes = node_map.values().cloned().collect();
+        // HashMap order varies between processes; keep offspring reproducible
+        child.nodes.sort_by_key(|n| n.id);
         // Merge connections by innovation
         let conn_map_f: HashMap<usize, &ConnGene> = fitter.conns.iter().map(|c| (c.innovation, c)).collect();
         let conn_map_w: HashMap<usize, &ConnGene> = weaker.conns.iter().map(|c| (c.innovation, c)).collect();
         let mut all_innovs: Vec<usize> = conn_map_f.keys().cloned().chain(conn_map_w.keys().cloned()).collect();
         all_innovs.sort_unstable();
         all_innovs.dedup();
+        let take_weaker = match cfg.crossover_disjoint {
+            DisjointGenes::Fitter => false,
+            DisjointGenes::Both => true,
+            DisjointGenes::BothIfEqual => fitter.fitness == weaker.fitness,
+        };
+        let mut weaker_only = Vec::new();
         for innov in all_innovs {
-            if let Some(&g1) = conn_map_f.get(&innov) {
-                if let Some(&g2) = conn_map_w.get(&innov) {
-                    // Matching gene: randomly choose
-                    if rng.gen_bool(0.5) {
-                        child.conns.push(g1.clone());
-                    } else {
-                        child.conns.push(g2.clone());
+            let gene = match (conn_map_f.get(&innov), conn_map_w.get(&innov)) {
+                (Some(&g1), Some(&g2)) => {
+                    let mut gene = match cfg.crossover_matching {
+                        MatchingGenes::Random => if rng.gen_bool(0.5) { g1.clone() } else { g2.clone() },
+                        MatchingGenes::Average => ConnGene { weight: (g1.weight + g2.weight) / 2.0, ..g1.clone() },
+                    };
+                    // NEAT's rule: a gene disabled in either parent is re-enabled only sometimes
+                    if let Some(rate) = cfg.crossover_reenable_rate {
+                        if !g1.enabled || !g2.enabled {
+                            gene.enabled = rng.gen_bool(rate.clamp(0.0, 1.0) as f64);
+                        }
                     }
-                } else {
-                    // Disjoint/excess from fitter
-                    child.conns.push(g1.clone());
+                    gene
+                }
+                // Disjoint/excess from fitter
+                (Some(&g1), None) => g1.clone(),
+                (None, Some(&g2)) => {
+                    if take_weaker {
+                        weaker_only.push(g2.clone());
+                    }
+                    continue;
                 }
+                (None, None) => continue,
+            };
+            child.conns.push(gene);
+        }
+        // Genes only in the weaker parent go last, skipping any that would
+        // close a cycle through the fitter parent's structure
+        for gene in weaker_only {
+            if cfg.allow_recurrent || !child.reaches(gene.out_node, gene.in_node) {
+                child.conns.push(gene);
             }
-            // Genes only in weaker parent are skipped
         }
         child
     }
 
-    /// Feed-forward evaluation given sensor inputs
+    /// Check structural integrity: unique node ids and innovations, connections
+    /// between existing nodes, and (unless `allow_recurrent`) no cycles among
+    /// enabled connections
+    pub fn validate(&self, allow_recurrent: bool) -> Result<(), GenomeError> {
+        let mut ids = std::collections::HashSet::new();
+        for n in &self.nodes {
+            if !ids.insert(n.id) {
+                return Err(GenomeError::DuplicateNode(n.id));
+            }
+        }
+        let mut innovs = std::collections::HashSet::new();
+        for c in &self.conns {
+            for node in [c.in_node, c.out_node] {
+                if !ids.contains(&node) {
+                    return Err(GenomeError::DanglingNode { innovation: c.innovation, node });
+                }
+            }
+            if !innovs.insert(c.innovation) {
+                return Err(GenomeError::DuplicateInnovation(c.innovation));
+            }
+        }
+        if !allow_recurrent {
+            if let Some(c) = self.conns.iter().find(|c| c.enabled && c.in_node == c.out_node) {
+                return Err(GenomeError::Cycle(vec![c.in_node]));
+            }
+            // nodes left unplaced by the topological sort lie on (or behind) a cycle
+            let order = self.eval_order();
+            let rank: HashMap<usize, usize> = order.iter().enumerate().map(|(i, &id)| (id, i)).collect();
+            let back: Vec<usize> = self.conns.iter()
+                .filter(|c| c.enabled && matches!((rank.get(&c.in_node), rank.get(&c.out_node)), (Some(a), Some(b)) if a >= b))
+                .flat_map(|c| [c.out_node, c.in_node])
+                .collect();
+            if !back.is_empty() {
+                let mut ids = back;
+                ids.sort_unstable();
+                ids.dedup();
+                return Err(GenomeError::Cycle(ids));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether `to` is reachable from `from` along connections (enabled or not,
+    /// since disabled genes can be re-enabled)
+    fn reaches(&self, from: usize, to: usize) -> bool {
+        let mut stack = vec![from];
+        let mut seen = std::collections::HashSet::new();
+        while let Some(n) = stack.pop() {
+            if n == to {
+                return true;
+            }
+            if seen.insert(n) {
+                stack.extend(self.conns.iter().filter(|c| c.in_node == n).map(|c| c.out_node));
+            }
+        }
+        false
+    }
+
+    /// Drop structure that cannot affect the outputs: disabled connections,
+    /// then hidden nodes with no enabled path to an output and their connections
+    pub fn prune(&mut self) {
+        self.conns.retain(|c| c.enabled);
+        let mut stack: Vec<usize> = self.nodes.iter()
+            .filter(|n| n.node_type == NodeType::Output)
+            .map(|n| n.id)
+            .collect();
+        let mut live: std::collections::HashSet<usize> = stack.iter().copied().collect();
+        while let Some(n) = stack.pop() {
+            for c in self.conns.iter().filter(|c| c.out_node == n) {
+                if live.insert(c.in_node) {
+                    stack.push(c.in_node);
+                }
+            }
+        }
+        self.nodes.retain(|n| n.node_type != NodeType::Hidden || live.contains(&n.id));
+        self.conns.retain(|c| live.contains(&c.out_node));
+    }
+
+    /// Stateless evaluation given sensor inputs (recurrent edges read zero)
     pub fn feed_forward(&self, inputs: &[f32]) -> Vec<f32> {
+        self.activate(inputs, &mut HashMap::new())
+    }
+
+    /// One network step. A connection whose source has not been evaluated yet
+    /// this step (a recurrent edge) reads that node's activation from `state`,
+    /// which is then replaced by this step's activations.
+    pub fn activate(&self, inputs: &[f32], state: &mut HashMap<usize, f32>) -> Vec<f32> {
         // map input node values
         let mut values: HashMap<usize, f32> = HashMap::new();
         let mut input_nodes: Vec<&NodeGene> = self.nodes.iter().filter(|n| n.node_type == NodeType::Input).collect();
@@ -181,39 +430,120 @@ impl Genome {
         for (n, &v) in input_nodes.iter().zip(inputs.iter()) {
             values.insert(n.id, v);
         }
-        // hidden nodes
-        let mut hidden_nodes: Vec<&NodeGene> = self.nodes.iter().filter(|n| n.node_type == NodeType::Hidden).collect();
-        hidden_nodes.sort_by_key(|n| n.id);
-        for n in hidden_nodes {
-            let sum: f32 = self.conns.iter().filter(|c| c.enabled && c.out_node == n.id)
-                .map(|c| values.get(&c.in_node).cloned().unwrap_or(0.0) * c.weight).sum();
-            values.insert(n.id, sum.tanh());
+        for n in self.nodes.iter().filter(|n| n.node_type == NodeType::Bias) {
+            values.insert(n.id, 1.0);
         }
-        // output nodes
-        let mut output_nodes: Vec<&NodeGene> = self.nodes.iter().filter(|n| n.node_type == NodeType::Output).collect();
-        output_nodes.sort_by_key(|n| n.id);
-        let mut outputs = Vec::new();
-        for n in output_nodes {
-            let sum: f32 = self.conns.iter().filter(|c| c.enabled && c.out_node == n.id)
-                .map(|c| values.get(&c.in_node).cloned().unwrap_or(0.0) * c.weight).sum();
-            outputs.push(sum.tanh());
+        // hidden and output nodes, dependencies first
+        for id in self.eval_order() {
+            let n = self.nodes.iter().find(|n| n.id == id).unwrap();
+            let sum: f32 = self.conns.iter().filter(|c| c.enabled && c.out_node == id)
+                .map(|c| Self::source(&values, state, c.in_node) * c.weight).sum();
+            values.insert(id, n.activation.apply(sum));
         }
+        let mut output_ids: Vec<usize> = self.nodes.iter().filter(|n| n.node_type == NodeType::Output).map(|n| n.id).collect();
+        output_ids.sort_unstable();
+        let outputs = output_ids.iter().map(|id| values[id]).collect();
+        *state = values;
         outputs
     }
 
-    /// Decompose into strictly-layered structure: input->hidden?->output
+    /// Current-step value of a node if evaluated, else its previous-step activation
+    fn source(values: &HashMap<usize, f32>, state: &HashMap<usize, f32>, id: usize) -> f32 {
+        values.get(&id).or_else(|| state.get(&id)).copied().unwrap_or(0.0)
+    }
+
+    /// Hidden and output node ids in topological order over enabled
+    /// connections (ties by id). Nodes on a cycle follow in id order; their
+    /// back edges read the previous step's activation.
+    fn eval_order(&self) -> Vec<usize> {
+        let computed: std::collections::BTreeSet<usize> = self.nodes.iter()
+            .filter(|n| matches!(n.node_type, NodeType::Hidden | NodeType::Output))
+            .map(|n| n.id).collect();
+        let edges: Vec<(usize, usize)> = self.conns.iter()
+            .filter(|c| c.enabled && c.in_node != c.out_node
+                && computed.contains(&c.in_node) && computed.contains(&c.out_node))
+            .map(|c| (c.in_node, c.out_node)).collect();
+        let mut indegree: HashMap<usize, usize> = computed.iter().map(|&id| (id, 0)).collect();
+        for &(_, to) in &edges {
+            *indegree.get_mut(&to).unwrap() += 1;
+        }
+        let mut ready: std::collections::BTreeSet<usize> = computed.iter().copied().filter(|id| indegree[id] == 0).collect();
+        let mut order = Vec::with_capacity(computed.len());
+        while let Some(id) = ready.pop_first() {
+            order.push(id);
+            for &(from, to) in &edges {
+                if from == id {
+                    let d = indegree.get_mut(&to).unwrap();
+                    *d -= 1;
+                    if *d == 0 {
+                        ready.insert(to);
+                    }
+                }
+            }
+        }
+        let placed: std::collections::HashSet<usize> = order.iter().copied().collect();
+        order.extend(computed.iter().copied().filter(|id| !placed.contains(id)));
+        order
+    }
+
+    /// Decompose into strictly-layered structure of any depth. A hidden node
+    /// sits one layer past its deepest source; outputs form the last layer.
+    /// Values consumed by a later layer are carried forward through identity
+    /// units. Recurrent edges are not representable and are dropped.
     pub fn layers(&self) -> Vec<Layer> {
-        // Collect node IDs by type
-        let mut input_ids = self.nodes.iter().filter(|n| n.node_type == NodeType::Input).map(|n| n.id).collect::<Vec<_>>();
-        let mut hidden_ids = self.nodes.iter().filter(|n| n.node_type == NodeType::Hidden).map(|n| n.id).collect::<Vec<_>>();
-        let mut output_ids = self.nodes.iter().filter(|n| n.node_type == NodeType::Output).map(|n| n.id).collect::<Vec<_>>();
-        input_ids.sort_unstable(); hidden_ids.sort_unstable(); output_ids.sort_unstable();
+        let ids_of = |t: NodeType| {
+            let mut ids = self.nodes.iter().filter(|n| n.node_type == t).map(|n| n.id).collect::<Vec<_>>();
+            ids.sort_unstable();
+            ids
+        };
+        let input_ids = ids_of(NodeType::Input);
+        let output_ids = ids_of(NodeType::Output);
+        let bias_ids = ids_of(NodeType::Bias);
+        // feed-forward edges only: from an input/bias, or from a node earlier in evaluation order
+        let order = self.eval_order();
+        let rank: HashMap<usize, usize> = order.iter().enumerate().map(|(i, &id)| (id, i)).collect();
+        let forward: Vec<&ConnGene> = self.conns.iter()
+            .filter(|c| c.enabled && match (rank.get(&c.in_node), rank.get(&c.out_node)) {
+                (None, _) => true,
+                (Some(a), Some(b)) => a < b,
+                (Some(_), None) => false,
+            })
+            .collect();
+        // depth: inputs/bias 0, computed nodes 1 + deepest source, outputs pinned to the last layer
+        let mut depth: HashMap<usize, usize> = HashMap::new();
+        for &id in &order {
+            let d = forward.iter().filter(|c| c.out_node == id)
+                .map(|c| depth.get(&c.in_node).copied().unwrap_or(0))
+                .max().unwrap_or(0) + 1;
+            depth.insert(id, d);
+        }
+        let last = order.iter().filter(|id| !output_ids.contains(id)).map(|id| depth[id]).max().unwrap_or(0) + 1;
+        for id in &output_ids {
+            depth.insert(*id, last);
+        }
+        let conns: Vec<ConnGene> = forward.iter().map(|&c| c.clone()).collect();
         let mut layers = Vec::new();
-        if !hidden_ids.is_empty() {
-            layers.push(Layer::new(&input_ids, &hidden_ids, &self.conns));
-            layers.push(Layer::new(&hidden_ids, &output_ids, &self.conns));
-        } else {
-            layers.push(Layer::new(&input_ids, &output_ids, &self.conns));
+        let mut live = input_ids;
+        for d in 1..=last {
+            let mut computed: Vec<usize> = order.iter().copied().filter(|id| depth[id] == d).collect();
+            computed.sort_unstable();
+            // carry live values still read by a deeper node
+            let carried: Vec<usize> = if d == last { Vec::new() } else {
+                live.iter().copied()
+                    .filter(|id| conns.iter().any(|c| c.in_node == *id && depth.get(&c.out_node).is_some_and(|&od| od > d)))
+                    .collect()
+            };
+            let outs: Vec<usize> = computed.iter().chain(&carried).copied().collect();
+            let mut layer = Layer::new(&live, &outs, &conns);
+            layer.add_biases(&bias_ids, &conns);
+            layer.activations = outs.iter()
+                .map(|id| self.nodes.iter().find(|n| n.id == *id).map(|n| n.activation).unwrap_or_default())
+                .collect();
+            for id in &carried {
+                layer.carry(*id);
+            }
+            layers.push(layer);
+            live = outs;
         }
         layers
     }
@@ -227,8 +557,8 @@ impl Genome {
         self.layers().last().map(|l| l.output_size()).unwrap_or(0)
     }
 
-    /// Export this genome to ONNX bytes
-    pub fn to_onnx(&self) -> Vec<u8> {
+    /// Export this genome to ONNX bytes (see `onnx_exporter::export_genome`)
+    pub fn to_onnx(&self) -> Result<Vec<u8>, NeatError> {
         onnx_exporter::export_genome(self)
     }
 } // end impl Genome
@@ -239,6 +569,7 @@ pub struct Layer {
     pub output_ids: Vec<usize>,
     pub weights: Vec<f32>,  // row-major [out_dim, in_dim]
     pub biases: Vec<f32>,   // len = out_dim
+    pub activations: Vec<Activation>, // len = out_dim
 }
 
 impl Layer {
@@ -247,7 +578,7 @@ impl Layer {
         let in_dim = input_ids.len();
         let out_dim = output_ids.len();
         let mut weights = vec![0.0f32; in_dim * out_dim];
-        let biases = vec![0.0f32; out_dim]; // NEAT has no bias nodes
+        let biases = vec![0.0f32; out_dim]; // filled from bias-node connections by `add_biases`
         for c in conns.iter().filter(|c| c.enabled
             && input_ids.contains(&c.in_node)
             && output_ids.contains(&c.out_node)) {
@@ -255,12 +586,36 @@ impl Layer {
             let j = input_ids.iter().position(|&id| id == c.in_node).unwrap();
             weights[i * in_dim + j] = c.weight;
         }
-        Layer { input_ids: input_ids.to_vec(), output_ids: output_ids.to_vec(), weights, biases }
+        let activations = vec![Activation::default(); out_dim];
+        Layer { input_ids: input_ids.to_vec(), output_ids: output_ids.to_vec(), weights, biases, activations }
+    }
+    /// Pass `id` through unchanged: unit weight from its input slot, identity activation
+    pub fn carry(&mut self, id: usize) {
+        let i = self.output_ids.iter().position(|&o| o == id).unwrap();
+        let j = self.input_ids.iter().position(|&n| n == id).unwrap();
+        let in_dim = self.input_ids.len();
+        self.weights[i * in_dim..(i + 1) * in_dim].fill(0.0);
+        self.weights[i * in_dim + j] = 1.0;
+        self.biases[i] = 0.0;
+        self.activations[i] = Activation::Identity;
+    }
+    /// Sum enabled connections from bias nodes into each output as its bias
+    pub fn add_biases(&mut self, bias_ids: &[usize], conns: &[ConnGene]) {
+        for c in conns.iter().filter(|c| c.enabled && bias_ids.contains(&c.in_node)) {
+            if let Some(i) = self.output_ids.iter().position(|&id| id == c.out_node) {
+                self.biases[i] += c.weight;
+            }
+        }
     }
     pub fn input_size(&self) -> usize { self.input_ids.len() }
     pub fn output_size(&self) -> usize { self.output_ids.len() }
+    /// Weights as a row-major `[in_dim, out_dim]` tensor, the layout `MatMul(x, W)` expects
     pub fn weight_bytes(&self) -> Vec<u8> {
-        self.weights.iter().flat_map(|f| f.to_le_bytes()).collect()
+        let in_dim = self.input_size();
+        (0..in_dim)
+            .flat_map(|j| (0..self.output_size()).map(move |i| self.weights[i * in_dim + j]))
+            .flat_map(|f| f.to_le_bytes())
+            .collect()
     }
     pub fn bias_bytes(&self) -> Vec<u8> {
         self.biases.iter().flat_map(|f| f.to_le_bytes()).collect()
@@ -270,38 +625,289 @@ impl Layer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
     use crate::config::Config as SimConfig;
     use crate::onnx_generated::onnx::ModelProto;
 
     #[test]
     fn test_mutate_add_connection_and_node() {
+        let mut rng = StdRng::seed_from_u64(1);
         let sim_cfg = SimConfig::default();
         let mut evo_cfg = EvolutionConfig::default();
         evo_cfg.mutation_add_conn_rate = 1.0;
         evo_cfg.mutation_add_node_rate = 1.0;
         let mut genome = Genome::new();
-        genome.initialize(&sim_cfg, &evo_cfg);
+        genome.initialize(&sim_cfg, &evo_cfg, &mut rng);
         let initial_nodes = genome.nodes.len();
         let initial_conns = genome.conns.len();
-        genome.mutate(&evo_cfg);
+        let mut tracker = InnovationTracker::new();
+        tracker.observe(&genome);
+        genome.mutate(&evo_cfg, &mut tracker, &mut rng);
         assert!(genome.nodes.len() > initial_nodes, "Node count did not increase");
         assert!(genome.conns.len() > initial_conns, "Conn count did not increase");
     }
 
+    #[test]
+    fn test_weight_mutation_rates() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let sim_cfg = SimConfig::default();
+        let mut evo_cfg = EvolutionConfig {
+            mutation_add_conn_rate: 0.0,
+            mutation_add_node_rate: 0.0,
+            mutation_weight_rate: 0.0,
+            ..Default::default()
+        };
+        let mut genome = Genome::new();
+        genome.initialize(&sim_cfg, &evo_cfg, &mut rng);
+        let before: Vec<f32> = genome.conns.iter().map(|c| c.weight).collect();
+        genome.mutate(&evo_cfg, &mut InnovationTracker::new(), &mut rng);
+        assert_eq!(before, genome.conns.iter().map(|c| c.weight).collect::<Vec<_>>());
+
+        evo_cfg.mutation_weight_rate = 1.0;
+        evo_cfg.mutation_weight_reset_rate = 0.0;
+        evo_cfg.mutation_weight_sigma = 0.5;
+        genome.mutate(&evo_cfg, &mut InnovationTracker::new(), &mut rng);
+        let changed = genome.conns.iter().zip(&before).filter(|(c, &w)| c.weight != w).count();
+        assert_eq!(changed, before.len());
+    }
+
+    #[test]
+    fn test_toggle_mutations() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let evo_cfg = EvolutionConfig {
+            mutation_add_conn_rate: 0.0,
+            mutation_add_node_rate: 0.0,
+            mutation_weight_rate: 0.0,
+            mutation_enable_rate: 1.0,
+            mutation_disable_rate: 0.0,
+            ..Default::default()
+        };
+        let mut genome = Genome::new();
+        genome.initialize(&SimConfig::default(), &evo_cfg, &mut rng);
+        genome.conns[0].enabled = false;
+        genome.mutate(&evo_cfg, &mut InnovationTracker::new(), &mut rng);
+        assert!(genome.conns.iter().all(|c| c.enabled));
+
+        let evo_cfg = EvolutionConfig { mutation_enable_rate: 0.0, mutation_disable_rate: 1.0, ..evo_cfg };
+        genome.mutate(&evo_cfg, &mut InnovationTracker::new(), &mut rng);
+        assert_eq!(genome.conns.iter().filter(|c| !c.enabled).count(), 1);
+    }
+
+    #[test]
+    fn test_same_split_shares_innovations() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let evo_cfg = EvolutionConfig {
+            mutation_add_conn_rate: 0.0,
+            mutation_add_node_rate: 1.0,
+            mutation_weight_rate: 0.0,
+            mutation_enable_rate: 0.0,
+            mutation_disable_rate: 0.0,
+            ..Default::default()
+        };
+        let mut base = Genome::new();
+        base.initialize(&SimConfig::default(), &evo_cfg, &mut rng);
+        // leave a single enabled connection so both genomes split the same one
+        for c in base.conns.iter_mut().skip(1) {
+            c.enabled = false;
+        }
+        let mut tracker = InnovationTracker::new();
+        tracker.observe(&base);
+        let (mut a, mut b) = (base.clone(), base.clone());
+        a.mutate(&evo_cfg, &mut tracker, &mut rng);
+        b.mutate(&evo_cfg, &mut tracker, &mut rng);
+        let innovs = |g: &Genome| g.conns[base.conns.len()..].iter().map(|c| c.innovation).collect::<Vec<_>>();
+        assert_eq!(innovs(&a), innovs(&b));
+        assert_eq!(a.nodes.last().unwrap().id, b.nodes.last().unwrap().id);
+        assert!(innovs(&a).iter().all(|&i| i >= base.conns.len()));
+    }
+
+    #[test]
+    fn test_recurrent_state_carries_across_steps() {
+        let genome = Genome {
+            nodes: vec![
+                NodeGene::new(0, NodeType::Input),
+                NodeGene::new(1, NodeType::Output),
+                NodeGene::new(2, NodeType::Hidden),
+            ],
+            conns: vec![
+                ConnGene { in_node: 0, out_node: 2, weight: 1.0, enabled: true, innovation: 0 },
+                ConnGene { in_node: 2, out_node: 2, weight: 1.0, enabled: true, innovation: 1 },
+                ConnGene { in_node: 2, out_node: 1, weight: 1.0, enabled: true, innovation: 2 },
+            ],
+            ..Genome::new()
+        };
+        let mut state = HashMap::new();
+        let first = genome.activate(&[0.5], &mut state);
+        let second = genome.activate(&[0.5], &mut state);
+        assert_eq!(first, genome.feed_forward(&[0.5]));
+        assert!(second[0] > first[0], "self-loop should accumulate memory");
+        state.clear();
+        assert_eq!(genome.activate(&[0.5], &mut state), first);
+    }
+
+    #[test]
+    fn test_add_connection_stays_acyclic_without_recurrence() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let evo_cfg = EvolutionConfig { mutation_add_conn_rate: 1.0, mutation_add_node_rate: 0.5, ..Default::default() };
+        let mut genome = Genome::new();
+        genome.initialize(&SimConfig::default(), &evo_cfg, &mut rng);
+        let mut tracker = InnovationTracker::new();
+        tracker.observe(&genome);
+        for _ in 0..50 {
+            genome.mutate(&evo_cfg, &mut tracker, &mut rng);
+        }
+        for c in &genome.conns {
+            assert!(c.in_node != c.out_node && !genome.reaches(c.out_node, c.in_node));
+        }
+    }
+
+    #[test]
+    fn test_bias_node_shifts_outputs_and_exports() {
+        let mut rng = StdRng::seed_from_u64(6);
+        let mut genome = Genome {
+            nodes: vec![
+                NodeGene::new(0, NodeType::Input),
+                NodeGene::new(1, NodeType::Output),
+                NodeGene::new(2, NodeType::Bias),
+            ],
+            conns: vec![
+                ConnGene { in_node: 0, out_node: 1, weight: 1.0, enabled: true, innovation: 0 },
+                ConnGene { in_node: 2, out_node: 1, weight: 0.5, enabled: true, innovation: 1 },
+            ],
+            ..Genome::new()
+        };
+        assert_eq!(genome.feed_forward(&[0.0]), vec![Activation::Tanh.apply(0.5)]);
+        let layers = genome.layers();
+        assert_eq!(layers[0].input_ids, vec![0]);
+        assert_eq!(layers[0].biases, vec![0.5]);
+        genome.initialize(&SimConfig::default(), &EvolutionConfig::default(), &mut rng);
+        let bias = genome.nodes.iter().find(|n| n.node_type == NodeType::Bias).unwrap().id;
+        assert_eq!(genome.conns.iter().filter(|c| c.in_node == bias).count(), 3);
+        assert_eq!(genome.input_size(), SimConfig::default().sensor_len());
+    }
+
+    #[test]
+    fn test_activation_genes_drive_evaluation() {
+        let mut genome = Genome {
+            nodes: vec![NodeGene::new(0, NodeType::Input), NodeGene::new(1, NodeType::Output)],
+            conns: vec![ConnGene { in_node: 0, out_node: 1, weight: 1.0, enabled: true, innovation: 0 }],
+            ..Genome::new()
+        };
+        genome.nodes[1].activation = Activation::Relu;
+        assert_eq!(genome.feed_forward(&[-2.0]), vec![0.0]);
+        genome.nodes[1].activation = Activation::Gaussian;
+        assert_eq!(genome.feed_forward(&[0.0]), vec![1.0]);
+        assert_eq!(genome.layers()[0].activations, vec![Activation::Gaussian]);
+        // genomes saved before activation genes default to tanh
+        let old: NodeGene = serde_json::from_str(r#"{"id":3,"node_type":"Hidden"}"#).unwrap();
+        assert_eq!(old.activation, Activation::Tanh);
+    }
+
+    /// Evaluate `layers()` densely, as the ONNX graph would
+    fn run_layers(layers: &[Layer], inputs: &[f32]) -> Vec<f32> {
+        let mut x = inputs.to_vec();
+        for l in layers {
+            x = (0..l.output_size()).map(|i| {
+                let sum: f32 = (0..l.input_size()).map(|j| l.weights[i * l.input_size() + j] * x[j]).sum();
+                l.activations[i].apply(sum + l.biases[i])
+            }).collect();
+        }
+        x
+    }
+
+    #[test]
+    fn test_deep_chain_evaluates_in_topological_order() {
+        // input 0 → h5 → h4 → output 1, skip 0 → 1, bias 2 → h4
+        let genome = Genome {
+            nodes: vec![
+                NodeGene::new(0, NodeType::Input),
+                NodeGene::new(1, NodeType::Output),
+                NodeGene::new(2, NodeType::Bias),
+                NodeGene::new(4, NodeType::Hidden),
+                NodeGene::new(5, NodeType::Hidden),
+            ],
+            conns: vec![
+                ConnGene { in_node: 0, out_node: 5, weight: 0.9, enabled: true, innovation: 0 },
+                ConnGene { in_node: 5, out_node: 4, weight: -1.3, enabled: true, innovation: 1 },
+                ConnGene { in_node: 4, out_node: 1, weight: 0.7, enabled: true, innovation: 2 },
+                ConnGene { in_node: 0, out_node: 1, weight: 0.4, enabled: true, innovation: 3 },
+                ConnGene { in_node: 2, out_node: 4, weight: 0.2, enabled: true, innovation: 4 },
+            ],
+            ..Genome::new()
+        };
+        let x = 0.6f32;
+        let h5 = (0.9 * x).tanh();
+        let h4 = (-1.3 * h5 + 0.2).tanh();
+        let expected = (0.7 * h4 + 0.4 * x).tanh();
+        let out = genome.feed_forward(&[x]);
+        assert!((out[0] - expected).abs() < 1e-6);
+        let layers = genome.layers();
+        assert_eq!(layers.len(), 3);
+        assert_eq!(layers[0].output_ids, vec![5, 0]);
+        assert_eq!(layers[2].output_ids, vec![1]);
+        assert!((run_layers(&layers, &[x])[0] - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_layers_match_feed_forward_after_mutation() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let evo_cfg = EvolutionConfig { mutation_add_conn_rate: 0.8, mutation_add_node_rate: 0.5, ..Default::default() };
+        let mut genome = Genome::new();
+        genome.initialize(&SimConfig::default(), &evo_cfg, &mut rng);
+        let mut tracker = InnovationTracker::new();
+        tracker.observe(&genome);
+        for _ in 0..30 {
+            genome.mutate(&evo_cfg, &mut tracker, &mut rng);
+        }
+        let inputs: Vec<f32> = (0..genome.input_size()).map(|i| (i as f32 * 0.37).sin()).collect();
+        let direct = genome.feed_forward(&inputs);
+        let layered = run_layers(&genome.layers(), &inputs);
+        for (a, b) in direct.iter().zip(&layered) {
+            assert!((a - b).abs() < 1e-4, "{} vs {}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_validate_reports_defects() {
+        let base = Genome {
+            nodes: vec![NodeGene::new(0, NodeType::Input), NodeGene::new(1, NodeType::Output), NodeGene::new(2, NodeType::Hidden)],
+            conns: vec![
+                ConnGene { in_node: 0, out_node: 2, weight: 1.0, enabled: true, innovation: 0 },
+                ConnGene { in_node: 2, out_node: 1, weight: 1.0, enabled: true, innovation: 1 },
+            ],
+            ..Genome::new()
+        };
+        assert_eq!(base.validate(false), Ok(()));
+
+        let mut g = base.clone();
+        g.conns[1].out_node = 9;
+        assert_eq!(g.validate(true), Err(GenomeError::DanglingNode { innovation: 1, node: 9 }));
+
+        let mut g = base.clone();
+        g.conns[1].innovation = 0;
+        assert_eq!(g.validate(true), Err(GenomeError::DuplicateInnovation(0)));
+
+        let mut g = base.clone();
+        g.conns.push(ConnGene { in_node: 1, out_node: 2, weight: 1.0, enabled: true, innovation: 2 });
+        assert!(matches!(g.validate(false), Err(GenomeError::Cycle(_))));
+        assert_eq!(g.validate(true), Ok(()));
+        g.conns[2].enabled = false;
+        assert_eq!(g.validate(false), Ok(()));
+    }
+
     #[test]
     fn test_layers_direct() {
         let genome = Genome {
             nodes: vec![
-                NodeGene { id: 0, node_type: NodeType::Input },
-                NodeGene { id: 1, node_type: NodeType::Input },
-                NodeGene { id: 2, node_type: NodeType::Output },
+                NodeGene::new(0, NodeType::Input),
+                NodeGene::new(1, NodeType::Input),
+                NodeGene::new(2, NodeType::Output),
             ],
             conns: vec![
                 ConnGene { in_node: 0, out_node: 2, weight: 1.23, enabled: true, innovation: 0 },
                 ConnGene { in_node: 1, out_node: 2, weight: 4.56, enabled: true, innovation: 1 },
             ],
-            fitness: 0.0,
-            fitness_naive: 0.0,
+            ..Genome::new()
         };
         let layers = genome.layers();
         assert_eq!(layers.len(), 1);
@@ -316,16 +922,15 @@ mod tests {
     fn test_layers_with_hidden() {
         let genome = Genome {
             nodes: vec![
-                NodeGene { id: 0, node_type: NodeType::Input },
-                NodeGene { id: 1, node_type: NodeType::Hidden },
-                NodeGene { id: 2, node_type: NodeType::Output },
+                NodeGene::new(0, NodeType::Input),
+                NodeGene::new(1, NodeType::Hidden),
+                NodeGene::new(2, NodeType::Output),
             ],
             conns: vec![
                 ConnGene { in_node: 0, out_node: 1, weight: 7.89, enabled: true, innovation: 0 },
                 ConnGene { in_node: 1, out_node: 2, weight: 0.12, enabled: true, innovation: 1 },
             ],
-            fitness: 0.0,
-            fitness_naive: 0.0,
+            ..Genome::new()
         };
         let layers = genome.layers();
         assert_eq!(layers.len(), 2);
@@ -341,13 +946,94 @@ mod tests {
         assert_eq!(l1.biases, vec![0.0]);
     }
 
+    #[test]
+    fn crossover_policies() {
+        let mut rng = StdRng::seed_from_u64(8);
+        let nodes = vec![
+            NodeGene::new(0, NodeType::Input),
+            NodeGene::new(1, NodeType::Input),
+            NodeGene::new(2, NodeType::Output),
+            NodeGene::new(3, NodeType::Hidden),
+        ];
+        let conn = |in_node, out_node, weight, enabled, innovation| ConnGene { in_node, out_node, weight, enabled, innovation };
+        let fitter = Genome {
+            nodes: nodes.clone(),
+            conns: vec![conn(0, 2, 1.0, false, 0), conn(0, 3, 1.0, true, 1), conn(3, 2, 1.0, true, 2)],
+            fitness: 2.0,
+            ..Genome::new()
+        };
+        let weaker = Genome {
+            nodes,
+            conns: vec![conn(0, 2, 3.0, true, 0), conn(1, 2, 1.0, true, 3), conn(2, 3, 1.0, true, 4)],
+            fitness: 1.0,
+            ..Genome::new()
+        };
+        let innovs = |g: &Genome| { let mut v: Vec<usize> = g.conns.iter().map(|c| c.innovation).collect(); v.sort(); v };
+
+        let child = Genome::crossover(&fitter, &weaker, &EvolutionConfig::default(), &mut rng);
+        assert_eq!(innovs(&child), vec![0, 1, 2]);
+
+        let cfg = EvolutionConfig {
+            crossover_matching: MatchingGenes::Average,
+            crossover_disjoint: DisjointGenes::Both,
+            crossover_reenable_rate: Some(1.0),
+            ..Default::default()
+        };
+        let child = Genome::crossover(&fitter, &weaker, &cfg, &mut rng);
+        // 2→3 would close a cycle with 3→2 and is dropped
+        assert_eq!(innovs(&child), vec![0, 1, 2, 3]);
+        let matching = child.conns.iter().find(|c| c.innovation == 0).unwrap();
+        assert_eq!(matching.weight, 2.0);
+        assert!(matching.enabled);
+        child.validate(false).unwrap();
+
+        let cfg = EvolutionConfig { crossover_disjoint: DisjointGenes::BothIfEqual, crossover_reenable_rate: Some(0.0), ..Default::default() };
+        let child = Genome::crossover(&fitter, &weaker, &cfg, &mut rng);
+        assert_eq!(innovs(&child), vec![0, 1, 2]);
+        assert!(!child.conns.iter().find(|c| c.innovation == 0).unwrap().enabled);
+    }
+
+    #[test]
+    fn prune_keeps_outputs_and_drops_dead_structure() {
+        let mut rng = StdRng::seed_from_u64(9);
+        let sim_cfg = SimConfig::default();
+        let evo_cfg = EvolutionConfig {
+            mutation_add_node_rate: 1.0,
+            mutation_add_conn_rate: 1.0,
+            mutation_disable_rate: 0.5,
+            ..Default::default()
+        };
+        let mut genome = Genome::new();
+        genome.initialize(&sim_cfg, &evo_cfg, &mut rng);
+        let mut tracker = InnovationTracker::new();
+        tracker.observe(&genome);
+        for _ in 0..30 {
+            genome.mutate(&evo_cfg, &mut tracker, &mut rng);
+        }
+        // a hidden node that only feeds itself
+        let dead = genome.nodes.iter().map(|n| n.id).max().unwrap() + 1;
+        genome.nodes.push(NodeGene::new(dead, NodeType::Hidden));
+        genome.conns.push(ConnGene { in_node: 0, out_node: dead, weight: 1.0, enabled: true, innovation: 10_000 });
+        let inputs: Vec<f32> = (0..sim_cfg.sensor_len()).map(|i| (i as f32 * 0.37).sin()).collect();
+        let before = genome.feed_forward(&inputs);
+        let mut pruned = genome.clone();
+        pruned.prune();
+        pruned.validate(false).unwrap();
+        assert_eq!(pruned.feed_forward(&inputs), before);
+        assert!(pruned.conns.iter().all(|c| c.enabled));
+        assert!(!pruned.nodes.iter().any(|n| n.id == dead));
+        assert_eq!(pruned.input_size(), genome.input_size());
+        assert_eq!(pruned.output_size(), genome.output_size());
+    }
+
     #[test]
     fn test_export_to_onnx_simple() {
+        let mut rng = StdRng::seed_from_u64(10);
         let mut genome = Genome::new();
         let sim_cfg = SimConfig::default();
         let evo_cfg = EvolutionConfig::default();
-        genome.initialize(&sim_cfg, &evo_cfg);
-        let bytes = genome.to_onnx();
+        genome.initialize(&sim_cfg, &evo_cfg, &mut rng);
+        let bytes = genome.to_onnx().unwrap();
         assert!(!bytes.is_empty(), "ONNX output should not be empty");
         let model = ModelProto::decode(bytes.as_slice()).unwrap();
         let graph = model.graph.expect("Graph missing");
@@ -0,0 +1,161 @@
+//! gRPC client for the inference service (feature `grpc`), speaking
+//! `proto/inference/inference.proto`. Calls block on a small shared tokio
+//! runtime so `NeatBrain::think` can stay synchronous; channels are cached
+//! per URL and reused by every brain.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tokio::runtime::Runtime;
+use tonic::transport::{Channel, Endpoint};
+
+/// Generated messages and client/server stubs
+pub mod proto {
+    tonic::include_proto!("inference");
+}
+
+use proto::inference_client::InferenceClient;
+use proto::InferRequest;
+
+#[derive(Debug)]
+pub enum GrpcError {
+    /// Invalid URL or connection failure
+    Transport(tonic::transport::Error),
+    /// The call failed or the service answered with an error status
+    Status(Box<tonic::Status>),
+    /// Output of unexpected shape
+    Shape(String),
+    /// The client's async runtime could not be started
+    Runtime(String),
+}
+
+impl GrpcError {
+    /// Worth retrying: connection failures and unavailable/overloaded services
+    pub fn is_transient(&self) -> bool {
+        match self {
+            GrpcError::Transport(_) => true,
+            GrpcError::Status(s) => matches!(
+                s.code(),
+                tonic::Code::Unavailable | tonic::Code::DeadlineExceeded | tonic::Code::ResourceExhausted
+            ),
+            GrpcError::Shape(_) | GrpcError::Runtime(_) => false,
+        }
+    }
+}
+
+impl fmt::Display for GrpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GrpcError::Transport(e) => write!(f, "gRPC transport error: {}", e),
+            GrpcError::Status(s) => write!(f, "gRPC call failed: {} ({})", s.message(), s.code()),
+            GrpcError::Shape(what) => write!(f, "unexpected gRPC output: {}", what),
+            GrpcError::Runtime(e) => write!(f, "cannot start gRPC runtime: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for GrpcError {}
+
+impl From<tonic::transport::Error> for GrpcError {
+    fn from(e: tonic::transport::Error) -> Self { GrpcError::Transport(e) }
+}
+
+impl From<tonic::Status> for GrpcError {
+    fn from(s: tonic::Status) -> Self { GrpcError::Status(Box::new(s)) }
+}
+
+fn runtime() -> Result<&'static Runtime, GrpcError> {
+    static RUNTIME: OnceLock<Result<Runtime, String>> = OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .enable_all()
+            .build()
+            .map_err(|e| e.to_string())
+    }).as_ref().map_err(|e| GrpcError::Runtime(e.clone()))
+}
+
+/// Lazily connecting channel to `url`, shared by all callers
+fn channel(url: &str) -> Result<Channel, GrpcError> {
+    static CHANNELS: OnceLock<Mutex<HashMap<String, Channel>>> = OnceLock::new();
+    let mut channels = CHANNELS.get_or_init(Default::default).lock().unwrap();
+    if let Some(ch) = channels.get(url) {
+        return Ok(ch.clone());
+    }
+    let endpoint = Endpoint::from_shared(url.to_string())?
+        .connect_timeout(Duration::from_secs(2))
+        .timeout(Duration::from_secs(5));
+    let _guard = runtime()?.enter();
+    let ch = endpoint.connect_lazy();
+    channels.insert(url.to_string(), ch.clone());
+    Ok(ch)
+}
+
+/// Run `rows` through the service at `url` (`Infer`, or `InferBatch` if
+/// `batched`); returns the output rows and the model time in milliseconds
+pub fn infer(url: &str, rows: &[Vec<f32>], batched: bool) -> Result<(Vec<Vec<f32>>, f32), GrpcError> {
+    let width = rows.first().map_or(0, |r| r.len());
+    let req = InferRequest { inputs: rows.concat(), width: width as u32 };
+    let mut client = InferenceClient::new(channel(url)?);
+    let resp = runtime()?.block_on(async move {
+        if batched { client.infer_batch(req).await } else { client.infer(req).await }
+    })?.into_inner();
+    let out_width = resp.width as usize;
+    if out_width == 0 || resp.outputs.len() != rows.len() * out_width {
+        return Err(GrpcError::Shape(format!(
+            "{} values of width {} for {} rows", resp.outputs.len(), out_width, rows.len()
+        )));
+    }
+    Ok((resp.outputs.chunks(out_width).map(|c| c.to_vec()).collect(), resp.duration_ms))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proto::inference_server::{Inference, InferenceServer};
+    use proto::InferResponse;
+    use tonic::{Request, Response, Status};
+
+    /// Doubles every input
+    struct Doubler;
+
+    #[tonic::async_trait]
+    impl Inference for Doubler {
+        async fn infer(&self, req: Request<InferRequest>) -> Result<Response<InferResponse>, Status> {
+            let req = req.into_inner();
+            Ok(Response::new(InferResponse {
+                outputs: req.inputs.iter().map(|x| x * 2.0).collect(),
+                width: req.width,
+                duration_ms: 0.5,
+            }))
+        }
+
+        async fn infer_batch(&self, req: Request<InferRequest>) -> Result<Response<InferResponse>, Status> {
+            self.infer(req).await
+        }
+    }
+
+    #[test]
+    fn round_trips_rows() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        runtime().unwrap().spawn(tonic::transport::Server::builder()
+            .add_service(InferenceServer::new(Doubler))
+            .serve(addr));
+        let url = format!("http://{}", addr);
+        let rows = vec![vec![1.0, 2.0], vec![3.0, 4.0]];
+        let mut result = infer(&url, &rows, false);
+        for _ in 0..20 {
+            if result.is_ok() { break; }
+            std::thread::sleep(Duration::from_millis(50));
+            result = infer(&url, &rows, false);
+        }
+        let (outputs, ms) = result.unwrap();
+        assert_eq!(outputs, vec![vec![2.0, 4.0], vec![6.0, 8.0]]);
+        assert_eq!(ms, 0.5);
+        assert!(infer(&url, &rows, true).is_ok());
+        assert!(infer("http://127.0.0.1:9", &rows, false).unwrap_err().is_transient());
+    }
+}
@@ -0,0 +1,202 @@
+//! HyperNEAT: an indirect encoding where the evolved genome is a CPPN
+//! (compositional pattern-producing network) that paints connection weights
+//! onto a fixed substrate laid out over the sensor geometry.
+//!
+//! The CPPN is an ordinary `Genome` (so mutation, crossover, and speciation
+//! apply unchanged) queried with the coordinates of a source and target
+//! substrate node: `[x1, y1, z1, x2, y2, z2] -> [weight, bias]`. Its size is
+//! independent of the sensor vector length, so adding nearest-K slots grows
+//! the substrate but not the search space.
+
+use crate::config::Config as SimConfig;
+use rand::Rng;
+use super::config::{Encoding, EvolutionConfig};
+use super::genome::{ConnGene, Genome, NodeGene, NodeType};
+
+/// CPPN inputs: source (x, y, z) then target (x, y, z)
+pub const CPPN_INPUTS: usize = 6;
+/// CPPN outputs: connection weight, target bias
+pub const CPPN_OUTPUTS: usize = 2;
+/// Evenly spaced coordinate of item `i` of `n` over [-1, 1] (0 when alone)
+fn spread(i: usize, n: usize) -> f32 {
+    if n <= 1 {
+        0.0
+    } else {
+        -1.0 + 2.0 * i as f32 / (n - 1) as f32
+    }
+}
+
+/// Node positions of the three substrate layers (z = -1, 0, 1)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Substrate {
+    pub inputs: Vec<[f32; 3]>,
+    pub hidden: Vec<[f32; 3]>,
+    pub outputs: Vec<[f32; 3]>,
+}
+
+impl Substrate {
+    /// Lay inputs out in `Simulation::scan` order: one row (y) per sensor
+    /// group of `SimConfig::sensor_layout`, with each group's slots and
+    /// their features spread along x. Nearby slots thus get nearby
+    /// coordinates and the CPPN can reuse one pattern for all of them.
+    pub fn for_sensors(sim_cfg: &SimConfig, hidden: usize) -> Self {
+        let groups: Vec<(usize, usize)> = sim_cfg.sensor_layout().iter()
+            .map(|(_, slots, features)| (*slots, features.len()))
+            .collect();
+        let outputs = sim_cfg.action_layout().len();
+        let mut inputs = Vec::with_capacity(sim_cfg.sensor_len());
+        for (g, &(slots, features)) in groups.iter().enumerate() {
+            let y = spread(g, groups.len());
+            for i in 0..slots * features {
+                inputs.push([spread(i, slots * features), y, -1.0]);
+            }
+        }
+        Substrate {
+            inputs,
+            hidden: (0..hidden).map(|i| [spread(i, hidden), 0.0, 0.0]).collect(),
+            outputs: (0..outputs).map(|i| [spread(i, outputs), 0.0, 1.0]).collect(),
+        }
+    }
+
+    /// Decode `cppn` into a direct-encoded network with the same node layout
+    /// as `Genome::initialize` (inputs, outputs, bias, then hidden). Links
+    /// whose CPPN weight magnitude is below `cppn_weight_threshold` are not
+    /// expressed; the rest are rescaled to ±`cppn_max_weight`.
+    pub fn build(&self, cppn: &Genome, evo_cfg: &EvolutionConfig) -> Genome {
+        let n_in = self.inputs.len();
+        let out_ids: Vec<usize> = (n_in..n_in + self.outputs.len()).collect();
+        let bias_id = n_in + self.outputs.len();
+        let hidden_ids: Vec<usize> = (bias_id + 1..bias_id + 1 + self.hidden.len()).collect();
+        let mut net = Genome {
+            id: cppn.id,
+            parents: cppn.parents.clone(),
+            fitness: cppn.fitness,
+            fitness_naive: cppn.fitness_naive,
+            behavior: cppn.behavior.clone(),
+            objectives: cppn.objectives.clone(),
+            metrics: cppn.metrics,
+            ..Genome::new()
+        };
+        net.nodes.extend((0..n_in).map(|id| NodeGene::new(id, NodeType::Input)));
+        net.nodes.extend(out_ids.iter().map(|&id| NodeGene::new(id, NodeType::Output)));
+        net.nodes.push(NodeGene::new(bias_id, NodeType::Bias));
+        net.nodes.extend(hidden_ids.iter().map(|&id| NodeGene::new(id, NodeType::Hidden)));
+
+        let inputs: Vec<(usize, [f32; 3])> = self.inputs.iter().copied().enumerate().collect();
+        let hidden: Vec<(usize, [f32; 3])> = hidden_ids.iter().copied().zip(self.hidden.iter().copied()).collect();
+        let outputs: Vec<(usize, [f32; 3])> = out_ids.iter().copied().zip(self.outputs.iter().copied()).collect();
+        let link = |net: &mut Genome, src: usize, dst: usize, value: f32| {
+            if let Some(weight) = express(value, evo_cfg) {
+                let innovation = net.conns.len();
+                net.conns.push(ConnGene { in_node: src, out_node: dst, weight, enabled: true, innovation });
+            }
+        };
+        for (targets, sources) in [(&hidden, &inputs), (&outputs, &hidden), (&outputs, &inputs)] {
+            for &(dst, b) in targets.iter() {
+                for &(src, a) in sources.iter() {
+                    link(&mut net, src, dst, query(cppn, a, b)[0]);
+                }
+            }
+        }
+        for &(dst, b) in hidden.iter().chain(&outputs) {
+            link(&mut net, bias_id, dst, query(cppn, b, b)[1]);
+        }
+        net
+    }
+}
+
+/// CPPN outputs for the link from `a` to `b`
+fn query(cppn: &Genome, a: [f32; 3], b: [f32; 3]) -> Vec<f32> {
+    let mut outputs = cppn.feed_forward(&[a[0], a[1], a[2], b[0], b[1], b[2]]);
+    outputs.resize(CPPN_OUTPUTS, 0.0);
+    outputs
+}
+
+/// Substrate weight for a raw CPPN output, or None below the expression threshold
+fn express(value: f32, evo_cfg: &EvolutionConfig) -> Option<f32> {
+    let t = evo_cfg.cppn_weight_threshold.clamp(0.0, 0.99);
+    let magnitude = value.abs().min(1.0);
+    if magnitude <= t {
+        return None;
+    }
+    Some(value.signum() * (magnitude - t) / (1.0 - t) * evo_cfg.cppn_max_weight)
+}
+
+/// Initialize `genome` as a minimal fully-connected CPPN (inputs, outputs,
+/// bias), laid out like `Genome::initialize`
+pub fn initialize_cppn<R: Rng + ?Sized>(genome: &mut Genome, rng: &mut R) {
+    let bias_id = CPPN_INPUTS + CPPN_OUTPUTS;
+    genome.nodes = (0..CPPN_INPUTS).map(|id| NodeGene::new(id, NodeType::Input))
+        .chain((CPPN_INPUTS..bias_id).map(|id| NodeGene::new(id, NodeType::Output)))
+        .chain(std::iter::once(NodeGene::new(bias_id, NodeType::Bias)))
+        .collect();
+    genome.conns.clear();
+    for in_node in (0..CPPN_INPUTS).chain(std::iter::once(bias_id)) {
+        for out_node in CPPN_INPUTS..bias_id {
+            let innovation = genome.conns.len();
+            genome.conns.push(ConnGene { in_node, out_node, weight: rng.gen_range(-1.0..1.0), enabled: true, innovation });
+        }
+    }
+}
+
+/// Initialize a fresh genome for the configured encoding
+pub fn initialize<R: Rng + ?Sized>(genome: &mut Genome, sim_cfg: &SimConfig, evo_cfg: &EvolutionConfig, rng: &mut R) {
+    match evo_cfg.encoding {
+        Encoding::Direct => genome.initialize(sim_cfg, evo_cfg, rng),
+        Encoding::HyperNeat => initialize_cppn(genome, rng),
+    }
+}
+
+/// The network a genome controls an agent with: the genome itself under
+/// direct encoding, its decoded substrate under HyperNEAT
+pub fn phenotype(genome: &Genome, sim_cfg: &SimConfig, evo_cfg: &EvolutionConfig) -> Genome {
+    match evo_cfg.encoding {
+        Encoding::Direct => genome.clone(),
+        Encoding::HyperNeat => Substrate::for_sensors(sim_cfg, evo_cfg.substrate_hidden).build(genome, evo_cfg),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn substrate_matches_sensor_layout() {
+        let sim_cfg = SimConfig::default();
+        let substrate = Substrate::for_sensors(&sim_cfg, 4);
+        assert_eq!(substrate.inputs.len(), sim_cfg.sensor_len());
+        assert_eq!(substrate.hidden.len(), 4);
+        assert_eq!(substrate.outputs.len(), sim_cfg.action_layout().len());
+        assert!(substrate.inputs.iter().all(|p| p[2] == -1.0 && p[0].abs() <= 1.0 && p[1].abs() <= 1.0));
+    }
+
+    #[test]
+    fn decoded_network_drives_the_sensor_vector() {
+        let sim_cfg = SimConfig::default();
+        let evo_cfg = EvolutionConfig {
+            encoding: Encoding::HyperNeat,
+            cppn_weight_threshold: 0.0,
+            ..Default::default()
+        };
+        let mut cppn = Genome::new();
+        initialize(&mut cppn, &sim_cfg, &evo_cfg, &mut rand::thread_rng());
+        assert_eq!(cppn.input_size(), CPPN_INPUTS);
+        let net = phenotype(&cppn, &sim_cfg, &evo_cfg);
+        net.validate(false).unwrap();
+        assert_eq!(net.input_size(), sim_cfg.sensor_len());
+        assert_eq!(net.output_size(), sim_cfg.action_layout().len());
+        assert_eq!(net.feed_forward(&vec![0.5; sim_cfg.sensor_len()]).len(), sim_cfg.action_layout().len());
+        // the CPPN stays the same size however many sensors there are
+        let wide = SimConfig { nearest_k_enemies: sim_cfg.nearest_k_enemies + 4, ..SimConfig::default() };
+        assert!(phenotype(&cppn, &wide, &evo_cfg).input_size() > net.input_size());
+    }
+
+    #[test]
+    fn weak_links_are_not_expressed() {
+        let evo_cfg = EvolutionConfig { cppn_weight_threshold: 0.2, cppn_max_weight: 2.0, ..Default::default() };
+        assert_eq!(express(0.1, &evo_cfg), None);
+        assert_eq!(express(-0.2, &evo_cfg), None);
+        assert!((express(0.6, &evo_cfg).unwrap() - 1.0).abs() < 1e-6);
+        assert!((express(-1.0, &evo_cfg).unwrap() + 2.0).abs() < 1e-6);
+    }
+}
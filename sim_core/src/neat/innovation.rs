@@ -0,0 +1,154 @@
+//! Population-wide innovation numbers.
+//!
+//! The same structural mutation gets the same number in every genome: a new
+//! connection is keyed by its (in_node, out_node) pair, and splitting a
+//! connection always yields the same hidden node id and pair of connection
+//! innovations. This is what lets crossover align genes by innovation.
+
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+use super::genome::Genome;
+
+/// Node id and the two connection innovations created by splitting a connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Split {
+    pub node_id: usize,
+    /// in_node → new node
+    pub in_innovation: usize,
+    /// new node → out_node
+    pub out_innovation: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InnovationTracker {
+    next_innovation: usize,
+    next_node_id: usize,
+    #[serde(with = "pair_keys")]
+    conns: HashMap<(usize, usize), usize>,
+    /// Splits keyed by the split connection's innovation (several if a genome
+    /// re-splits a connection it already split)
+    splits: HashMap<usize, Vec<Split>>,
+}
+
+impl InnovationTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a genome's existing connections and node ids
+    pub fn observe(&mut self, genome: &Genome) {
+        for c in &genome.conns {
+            self.conns.entry((c.in_node, c.out_node)).or_insert(c.innovation);
+            self.next_innovation = self.next_innovation.max(c.innovation + 1);
+        }
+        if let Some(max_id) = genome.nodes.iter().map(|n| n.id).max() {
+            self.next_node_id = self.next_node_id.max(max_id + 1);
+        }
+    }
+
+    /// Innovation number for a connection between two nodes
+    pub fn connection(&mut self, in_node: usize, out_node: usize) -> usize {
+        let next = &mut self.next_innovation;
+        *self.conns.entry((in_node, out_node)).or_insert_with(|| {
+            let id = *next;
+            *next += 1;
+            id
+        })
+    }
+
+    /// Split of connection `innovation` (from `in_node` to `out_node`) for
+    /// `genome`: reuses a split seen elsewhere unless the genome already
+    /// contains that hidden node
+    pub fn split(&mut self, genome: &Genome, innovation: usize, in_node: usize, out_node: usize) -> Split {
+        let existing = self.splits.get(&innovation).and_then(|splits| {
+            splits.iter().copied().find(|s| !genome.nodes.iter().any(|n| n.id == s.node_id))
+        });
+        if let Some(split) = existing {
+            return split;
+        }
+        // never hand out an id the genome already uses, even if it was not observed
+        let genome_next = genome.nodes.iter().map(|n| n.id + 1).max().unwrap_or(0);
+        let node_id = self.next_node_id.max(genome_next);
+        self.next_node_id = node_id + 1;
+        let split = Split {
+            node_id,
+            in_innovation: self.connection(in_node, node_id),
+            out_innovation: self.connection(node_id, out_node),
+        };
+        self.splits.entry(innovation).or_default().push(split);
+        split
+    }
+
+    /// Next unassigned innovation number
+    pub fn next_innovation(&self) -> usize {
+        self.next_innovation
+    }
+}
+
+/// JSON object keys must be strings, so (in, out) keyed maps are stored as
+/// a list of `[[in, out], innovation]` entries
+mod pair_keys {
+    use std::collections::HashMap;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(map: &HashMap<(usize, usize), usize>, s: S) -> Result<S::Ok, S::Error> {
+        let mut entries: Vec<_> = map.iter().collect();
+        entries.sort_unstable();
+        entries.serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<HashMap<(usize, usize), usize>, D::Error> {
+        Ok(Vec::<((usize, usize), usize)>::deserialize(d)?.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config as SimConfig;
+    use crate::neat::config::EvolutionConfig;
+    use crate::neat::genome::{NodeGene, NodeType};
+
+    fn seeded() -> (InnovationTracker, Genome) {
+        let mut g = Genome::new();
+        g.initialize(&SimConfig::default(), &EvolutionConfig::default(), &mut rand::thread_rng());
+        let mut t = InnovationTracker::new();
+        t.observe(&g);
+        (t, g)
+    }
+
+    #[test]
+    fn same_connection_same_number() {
+        let (mut t, g) = seeded();
+        let n = t.next_innovation();
+        let first = t.connection(0, 10_000);
+        assert_eq!(first, n);
+        assert_eq!(t.connection(0, 10_000), first);
+        // existing genes keep their numbers
+        assert_eq!(t.connection(g.conns[5].in_node, g.conns[5].out_node), g.conns[5].innovation);
+    }
+
+    #[test]
+    fn splits_are_shared_across_genomes_but_unique_within_one() {
+        let (mut t, mut a) = seeded();
+        let b = a.clone();
+        let c = a.conns[0].clone();
+        let s1 = t.split(&a, c.innovation, c.in_node, c.out_node);
+        let s2 = t.split(&b, c.innovation, c.in_node, c.out_node);
+        assert_eq!(s1, s2);
+        a.nodes.push(NodeGene::new(s1.node_id, NodeType::Hidden));
+        let s3 = t.split(&a, c.innovation, c.in_node, c.out_node);
+        assert_ne!(s3.node_id, s1.node_id);
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let (mut t, g) = seeded();
+        let c = g.conns[0].clone();
+        let split = t.split(&g, c.innovation, c.in_node, c.out_node);
+        let mut back: InnovationTracker = serde_json::from_str(&serde_json::to_string(&t).unwrap()).unwrap();
+        assert_eq!(back.next_innovation(), t.next_innovation());
+        assert_eq!(back.split(&g, c.innovation, c.in_node, c.out_node), split);
+        assert_eq!(back.connection(c.in_node, c.out_node), c.innovation);
+    }
+}
@@ -0,0 +1,145 @@
+//! Island model: several sub-populations evaluated on separate rayon tasks,
+//! exchanging their best genomes every `migration_interval` generations.
+//!
+//! Islands keep their own species, hall-of-fame, and novelty archive, but
+//! share one innovation tracker and genealogy so migrants' genes line up
+//! with the receiving island's and genome ids stay unique.
+
+use std::mem;
+use rayon::prelude::*;
+use serde::{Serialize, Deserialize};
+use crate::config::Config;
+use super::config::EvolutionConfig;
+use super::genome::Genome;
+use super::innovation::InnovationTracker;
+use super::lineage::Genealogy;
+use super::population::Population;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Archipelago {
+    pub islands: Vec<Population>,
+    pub innovations: InnovationTracker,
+    pub genealogy: Genealogy,
+    pub generation: usize,
+}
+
+impl Archipelago {
+    /// `evo_cfg.islands` populations of `evo_cfg.pop_size` genomes each
+    pub fn new(evo_cfg: &EvolutionConfig) -> Self {
+        Archipelago {
+            islands: (0..evo_cfg.islands.max(1)).map(|_| Population::new(evo_cfg)).collect(),
+            innovations: InnovationTracker::new(),
+            genealogy: Genealogy::new(),
+            generation: 0,
+        }
+    }
+
+    /// Settings for island `i`: its own RNG streams when seeded
+    pub fn island_cfg(evo_cfg: &EvolutionConfig, i: usize) -> EvolutionConfig {
+        evo_cfg.substream(i)
+    }
+
+    /// Run `f` on island `i` with the shared tracker and genealogy swapped in
+    fn with_shared(&mut self, i: usize, f: impl FnOnce(&mut Population)) {
+        let pop = &mut self.islands[i];
+        mem::swap(&mut pop.innovations, &mut self.innovations);
+        mem::swap(&mut pop.genealogy, &mut self.genealogy);
+        f(pop);
+        mem::swap(&mut pop.innovations, &mut self.innovations);
+        mem::swap(&mut pop.genealogy, &mut self.genealogy);
+    }
+
+    /// Evaluate every island in parallel, then migrate if one is due
+    pub fn evaluate(&mut self, sim_cfg: &Config, evo_cfg: &EvolutionConfig) {
+        // ids and genes are handed out from the shared state, one island at a time
+        for i in 0..self.islands.len() {
+            let cfg = Self::island_cfg(evo_cfg, i);
+            self.with_shared(i, |pop| pop.prepare(sim_cfg, &cfg));
+        }
+        self.islands.par_iter_mut().enumerate().for_each(|(i, pop)| {
+            pop.evaluate(sim_cfg, &Self::island_cfg(evo_cfg, i));
+        });
+        for g in self.islands.iter().flat_map(|p| &p.genomes) {
+            if let Some(id) = g.id {
+                self.genealogy.set_fitness(id, g.fitness);
+            }
+        }
+        let interval = evo_cfg.migration_interval.max(1);
+        if self.islands.len() > 1 && self.generation > 0 && self.generation.is_multiple_of(interval) {
+            self.migrate(evo_cfg);
+        }
+    }
+
+    /// Ring migration: each island's best `migrants` replace the worst genomes
+    /// of the next island. Expects genomes sorted best-first, as `evaluate` leaves them.
+    pub fn migrate(&mut self, evo_cfg: &EvolutionConfig) {
+        let n = self.islands.len();
+        let emigrants: Vec<Vec<Genome>> = self.islands.iter()
+            .map(|p| p.genomes.iter().take(evo_cfg.migrants).cloned().collect())
+            .collect();
+        for (i, migrants) in emigrants.into_iter().enumerate() {
+            let dest = &mut self.islands[(i + 1) % n];
+            let keep = dest.genomes.len().saturating_sub(migrants.len());
+            dest.genomes.truncate(keep);
+            dest.genomes.extend(migrants);
+            dest.genomes.sort_by(|a, b| b.fitness.total_cmp(&a.fitness));
+            dest.speciate(&Self::island_cfg(evo_cfg, (i + 1) % n));
+        }
+    }
+
+    /// Breed the next generation of every island
+    pub fn reproduce(&mut self, evo_cfg: &EvolutionConfig) {
+        for i in 0..self.islands.len() {
+            let cfg = Self::island_cfg(evo_cfg, i);
+            self.with_shared(i, |pop| pop.reproduce(&cfg));
+        }
+        self.generation += 1;
+    }
+
+    /// Best genome across all islands' halls of fame
+    pub fn champion(&self) -> Option<&Genome> {
+        self.islands.iter()
+            .flat_map(|p| &p.hof)
+            .max_by(|a, b| a.fitness.total_cmp(&b.fitness))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrants_replace_the_worst_of_the_next_island() {
+        let evo_cfg = EvolutionConfig { pop_size: 4, islands: 3, migrants: 1, ..Default::default() };
+        let mut arch = Archipelago::new(&evo_cfg);
+        for (i, pop) in arch.islands.iter_mut().enumerate() {
+            for (j, g) in pop.genomes.iter_mut().enumerate() {
+                g.initialize(&Config::default(), &evo_cfg, &mut rand::thread_rng());
+                g.fitness = (10 * (i + 1) - j) as f32;
+            }
+        }
+        arch.migrate(&evo_cfg);
+        let best = |i: usize| arch.islands[i].genomes[0].fitness;
+        assert_eq!(best(1), 20.0);
+        assert_eq!(best(2), 30.0);
+        // island 0 receives island 2's best and loses its worst
+        let fits: Vec<f32> = arch.islands[0].genomes.iter().map(|g| g.fitness).collect();
+        assert_eq!(fits, vec![30.0, 10.0, 9.0, 8.0]);
+    }
+
+    #[test]
+    fn islands_share_innovations_and_ids() {
+        let evo_cfg = EvolutionConfig { pop_size: 3, islands: 2, ..Default::default() };
+        let mut arch = Archipelago::new(&evo_cfg);
+        for i in 0..2 {
+            let cfg = Archipelago::island_cfg(&evo_cfg, i);
+            arch.with_shared(i, |pop| pop.prepare(&Config::default(), &cfg));
+        }
+        let mut ids: Vec<usize> = arch.islands.iter().flat_map(|p| &p.genomes).filter_map(|g| g.id).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), 6);
+        assert_eq!(arch.genealogy.len(), 6);
+        assert!(arch.islands.iter().all(|p| p.genealogy.is_empty()));
+    }
+}
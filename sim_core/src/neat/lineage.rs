@@ -0,0 +1,162 @@
+//! Genealogy: every genome a run produces, its parents, and the connection
+//! innovations it introduced, so a champion's ancestry can be traced back
+//! through the generations.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt::Write;
+use serde::{Serialize, Deserialize};
+use super::genome::Genome;
+
+/// One genome in the family tree
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    pub id: usize,
+    /// Parent ids (empty for founders and injected genomes)
+    pub parents: Vec<usize>,
+    /// Generation the genome was born in
+    pub generation: usize,
+    /// Latest evaluated fitness
+    pub fitness: f32,
+    /// Connection innovations present in neither parent
+    pub new_innovations: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Genealogy {
+    next_id: usize,
+    records: BTreeMap<usize, Record>,
+}
+
+impl Genealogy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    pub fn get(&self, id: usize) -> Option<&Record> {
+        self.records.get(&id)
+    }
+
+    /// Give `genome` a fresh id if it has none and record it as born in
+    /// `generation` from `parents`. Genomes that already have an id (elites
+    /// carried over) are left alone.
+    pub fn register(&mut self, genome: &mut Genome, generation: usize, parents: &[&Genome]) {
+        if genome.id.is_some() {
+            return;
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        genome.id = Some(id);
+        let inherited: HashSet<usize> = parents.iter()
+            .flat_map(|p| p.conns.iter().map(|c| c.innovation))
+            .collect();
+        let new_innovations = if parents.is_empty() {
+            Vec::new()
+        } else {
+            let mut innovs: Vec<usize> = genome.conns.iter()
+                .map(|c| c.innovation)
+                .filter(|i| !inherited.contains(i))
+                .collect();
+            innovs.sort_unstable();
+            innovs
+        };
+        self.records.insert(id, Record {
+            id,
+            parents: genome.parents.clone(),
+            generation,
+            fitness: genome.fitness,
+            new_innovations,
+        });
+    }
+
+    /// Update a recorded genome's fitness after evaluation
+    pub fn set_fitness(&mut self, id: usize, fitness: f32) {
+        if let Some(r) = self.records.get_mut(&id) {
+            r.fitness = fitness;
+        }
+    }
+
+    /// `id` and all its recorded ancestors, oldest first
+    pub fn ancestry(&self, id: usize) -> Vec<&Record> {
+        let mut seen = BTreeSet::new();
+        let mut stack = vec![id];
+        while let Some(n) = stack.pop() {
+            if self.records.contains_key(&n) && seen.insert(n) {
+                stack.extend(&self.records[&n].parents);
+            }
+        }
+        let mut out: Vec<&Record> = seen.iter().map(|n| &self.records[n]).collect();
+        out.sort_by_key(|r| (r.generation, r.id));
+        out
+    }
+
+    /// Graphviz DOT of the ancestry of `root` (the whole genealogy if None)
+    pub fn to_dot(&self, root: Option<usize>) -> String {
+        let records: Vec<&Record> = match root {
+            Some(id) => self.ancestry(id),
+            None => self.records.values().collect(),
+        };
+        let ids: HashSet<usize> = records.iter().map(|r| r.id).collect();
+        let mut dot = String::from("digraph genealogy {\n  rankdir=TB;\n  node [shape=box];\n");
+        for r in &records {
+            let _ = writeln!(
+                dot, "  g{} [label=\"#{}\\ngen {}\\nfit {:.2}\\n+{} innov\"{}];",
+                r.id, r.id, r.generation, r.fitness, r.new_innovations.len(),
+                if Some(r.id) == root { ", style=bold" } else { "" },
+            );
+        }
+        for r in &records {
+            for p in r.parents.iter().filter(|p| ids.contains(p)) {
+                let _ = writeln!(dot, "  g{} -> g{};", p, r.id);
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neat::genome::ConnGene;
+
+    fn with_innovs(innovs: &[usize]) -> Genome {
+        Genome {
+            conns: innovs.iter()
+                .map(|&innovation| ConnGene { in_node: 0, out_node: 1, weight: 1.0, enabled: true, innovation })
+                .collect(),
+            ..Genome::new()
+        }
+    }
+
+    #[test]
+    fn traces_ancestry_and_new_innovations() {
+        let mut tree = Genealogy::new();
+        let mut a = with_innovs(&[0, 1]);
+        let mut b = with_innovs(&[0, 2]);
+        tree.register(&mut a, 0, &[]);
+        tree.register(&mut b, 0, &[]);
+        let mut unrelated = with_innovs(&[0]);
+        tree.register(&mut unrelated, 0, &[]);
+        let mut child = Genome { parents: vec![a.id.unwrap(), b.id.unwrap()], ..with_innovs(&[0, 1, 2, 7]) };
+        tree.register(&mut child, 1, &[&a, &b]);
+        let child_id = child.id.unwrap();
+        // already registered: id is kept
+        tree.register(&mut child, 2, &[]);
+        assert_eq!(child.id, Some(child_id));
+        assert_eq!(tree.len(), 4);
+        assert_eq!(tree.get(child_id).unwrap().new_innovations, vec![7]);
+        let ancestry: Vec<usize> = tree.ancestry(child_id).iter().map(|r| r.id).collect();
+        assert_eq!(ancestry, vec![a.id.unwrap(), b.id.unwrap(), child_id]);
+        let dot = tree.to_dot(Some(child_id));
+        assert!(dot.contains(&format!("g{} -> g{};", a.id.unwrap(), child_id)));
+        assert!(!dot.contains(&format!("g{} ", unrelated.id.unwrap())));
+    }
+}
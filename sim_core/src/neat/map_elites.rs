@@ -0,0 +1,145 @@
+//! MAP-Elites: keep the fittest genome per cell of a behavior grid instead of
+//! a single converging population, yielding a diverse library of champions.
+//!
+//! Cells are keyed by three descriptors taken from `Genome::behavior`:
+//! aggression (damage dealt), looting, and mobility (thrust rate).
+
+use std::collections::BTreeMap;
+use rand::Rng;
+use rand::seq::IteratorRandom;
+use crate::config::Config;
+use super::config::EvolutionConfig;
+use super::genome::Genome;
+use super::hyperneat;
+use super::innovation::InnovationTracker;
+use super::population::Population;
+
+/// `Genome::behavior` components used as grid axes: damage, loot, thrust
+const DESCRIPTOR_INDICES: [usize; 3] = [2, 3, 4];
+
+/// Grid cell coordinates, one bin per descriptor
+pub type Cell = [usize; 3];
+
+/// One elite per behavior cell
+#[derive(Debug, Clone)]
+pub struct EliteArchive {
+    bins: usize,
+    cells: BTreeMap<Cell, Genome>,
+}
+
+impl EliteArchive {
+    pub fn new(bins: usize) -> Self {
+        EliteArchive { bins: bins.max(1), cells: BTreeMap::new() }
+    }
+
+    /// Cell for a behavior vector (descriptors clamped to [0, 1]); None if it is too short
+    pub fn cell_of(&self, behavior: &[f32]) -> Option<Cell> {
+        let mut cell = [0; 3];
+        for (c, &i) in cell.iter_mut().zip(&DESCRIPTOR_INDICES) {
+            let v = behavior.get(i)?.clamp(0.0, 1.0);
+            *c = ((v * self.bins as f32) as usize).min(self.bins - 1);
+        }
+        Some(cell)
+    }
+
+    /// Keep `genome` if its cell is empty or it beats the incumbent
+    pub fn insert(&mut self, genome: Genome) -> bool {
+        let Some(cell) = self.cell_of(&genome.behavior) else { return false };
+        match self.cells.get(&cell) {
+            Some(incumbent) if incumbent.fitness >= genome.fitness => false,
+            _ => {
+                self.cells.insert(cell, genome);
+                true
+            }
+        }
+    }
+
+    pub fn elites(&self) -> impl Iterator<Item = (&Cell, &Genome)> {
+        self.cells.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Fraction of grid cells holding an elite
+    pub fn coverage(&self) -> f32 {
+        self.cells.len() as f32 / self.bins.pow(3) as f32
+    }
+
+    /// Best elite overall
+    pub fn best(&self) -> Option<&Genome> {
+        self.cells.values().max_by(|a, b| a.fitness.total_cmp(&b.fitness))
+    }
+}
+
+/// MAP-Elites training state: the archive plus shared innovation numbers
+pub struct MapElites {
+    pub archive: EliteArchive,
+    innovations: InnovationTracker,
+    /// Completed `step`s; selects the RNG stream of seeded runs
+    iterations: usize,
+}
+
+impl MapElites {
+    pub fn new(bins: usize) -> Self {
+        MapElites { archive: EliteArchive::new(bins), innovations: InnovationTracker::new(), iterations: 0 }
+    }
+
+    /// One iteration: breed `pop_size` offspring from random elites (fresh
+    /// genomes while the archive is empty), evaluate them against each other,
+    /// and offer each to the archive. Returns how many were kept.
+    pub fn step(&mut self, sim_cfg: &Config, evo_cfg: &EvolutionConfig) -> usize {
+        let mut rng = evo_cfg.rng(self.iterations, 3);
+        let batch: Vec<Genome> = (0..evo_cfg.pop_size).map(|_| {
+            if self.archive.is_empty() {
+                let mut g = Genome::new();
+                hyperneat::initialize(&mut g, sim_cfg, evo_cfg, &mut rng);
+                return g;
+            }
+            let p1 = self.archive.cells.values().choose(&mut rng).unwrap();
+            let mut child = if rng.gen::<f32>() < evo_cfg.crossover_rate {
+                let p2 = self.archive.cells.values().choose(&mut rng).unwrap();
+                Genome::crossover(p1, p2, evo_cfg, &mut rng)
+            } else {
+                Genome { id: None, parents: p1.id.into_iter().collect(), ..p1.clone() }
+            };
+            self.innovations.observe(&child);
+            child.mutate(evo_cfg, &mut self.innovations, &mut rng);
+            child
+        }).collect();
+        let mut pop = Population::new(evo_cfg);
+        pop.genomes = batch;
+        pop.generation = self.iterations;
+        self.iterations += 1;
+        pop.evaluate(sim_cfg, evo_cfg);
+        pop.genomes.into_iter().map(|g| self.archive.insert(g)).filter(|&kept| kept).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with(behavior: Vec<f32>, fitness: f32) -> Genome {
+        Genome { behavior, fitness, ..Genome::new() }
+    }
+
+    #[test]
+    fn keeps_fittest_per_cell() {
+        let mut archive = EliteArchive::new(4);
+        assert_eq!(archive.cell_of(&[0.0, 0.0, 0.3, 1.0, 0.0]), Some([1, 3, 0]));
+        assert!(archive.insert(with(vec![0.0, 0.0, 0.3, 0.0, 0.0], 1.0)));
+        assert!(!archive.insert(with(vec![0.5, 0.5, 0.3, 0.0, 0.0], 0.5)));
+        assert!(archive.insert(with(vec![0.0, 0.0, 0.3, 0.0, 0.0], 2.0)));
+        assert!(archive.insert(with(vec![0.0, 0.0, 0.9, 0.0, 0.0], 0.1)));
+        assert!(!archive.insert(with(Vec::new(), 9.0)));
+        assert_eq!(archive.len(), 2);
+        assert_eq!(archive.best().unwrap().fitness, 2.0);
+        assert_eq!(archive.coverage(), 2.0 / 64.0);
+    }
+}
@@ -0,0 +1,204 @@
+//! Per-genome behavior metrics gathered during evaluation (damage, loot,
+//! survival, movement entropy), for analysing what a population actually
+//! does rather than its scalar fitness alone.
+
+use std::io::{self, Write};
+use serde::{Serialize, Deserialize};
+use crate::{Simulation, AGENT_STRIDE, IDX_HEALTH, IDX_TEAM, IDX_X, IDX_Y};
+use super::genome::Genome;
+use super::runner::MatchStats;
+
+/// Moves shorter than this per tick count as standing still
+const STILL_EPS: f32 = 1e-3;
+/// Heading bins: eight compass sectors plus "still"
+const HEADING_BINS: usize = 9;
+
+/// Match means of a genome's evaluation
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct BehaviorMetrics {
+    /// Opponent health removed per match
+    pub damage: f32,
+    /// Salvage actions per match
+    pub loot: f32,
+    /// Ticks per match with the subject team still alive
+    pub survival_ticks: f32,
+    /// Normalized entropy of the subject team's per-tick headings, in [0, 1]
+    /// (0 = always the same heading, 1 = uniform over directions and standing still)
+    pub movement_entropy: f32,
+}
+
+impl BehaviorMetrics {
+    pub const CSV_HEADER: &'static str =
+        "generation,genome_id,fitness,fitness_naive,avg_damage,avg_loot,avg_survival_ticks,movement_entropy";
+
+    pub fn from_stats(stats: &MatchStats) -> Self {
+        BehaviorMetrics {
+            damage: stats.total_damage_inflicted,
+            loot: stats.salvage_actions,
+            survival_ticks: stats.survival_ticks as f32,
+            movement_entropy: stats.movement_entropy,
+        }
+    }
+
+    pub fn add(&mut self, other: &BehaviorMetrics) {
+        self.damage += other.damage;
+        self.loot += other.loot;
+        self.survival_ticks += other.survival_ticks;
+        self.movement_entropy += other.movement_entropy;
+    }
+
+    pub fn scaled(self, k: f32) -> Self {
+        BehaviorMetrics {
+            damage: self.damage * k,
+            loot: self.loot * k,
+            survival_ticks: self.survival_ticks * k,
+            movement_entropy: self.movement_entropy * k,
+        }
+    }
+}
+
+/// Histogram of the subject team's movement directions over a match
+#[derive(Debug, Clone, Default)]
+pub struct HeadingHistogram {
+    prev: Vec<[f32; 2]>,
+    counts: [u32; HEADING_BINS],
+}
+
+impl HeadingHistogram {
+    /// Bin each living subject agent's move since the previous call
+    pub fn observe(&mut self, sim: &Simulation, subject_team: u32) {
+        let agents = sim.agents_data.chunks(AGENT_STRIDE);
+        let first = self.prev.is_empty();
+        if first {
+            self.prev = agents.clone().map(|a| [a[IDX_X], a[IDX_Y]]).collect();
+            return;
+        }
+        for (a, prev) in agents.zip(self.prev.iter_mut()) {
+            let pos = [a[IDX_X], a[IDX_Y]];
+            if a[IDX_TEAM] as u32 == subject_team && a[IDX_HEALTH] > 0.0 {
+                let (dx, dy) = (pos[0] - prev[0], pos[1] - prev[1]);
+                let bin = if dx.hypot(dy) < STILL_EPS {
+                    HEADING_BINS - 1
+                } else {
+                    let turn = dy.atan2(dx) / std::f32::consts::TAU + 0.5;
+                    ((turn * 8.0) as usize).min(7)
+                };
+                self.counts[bin] += 1;
+            }
+            *prev = pos;
+        }
+    }
+
+    /// Shannon entropy of the binned headings over its maximum (0 if nothing was observed)
+    pub fn entropy(&self) -> f32 {
+        let total: u32 = self.counts.iter().sum();
+        if total == 0 {
+            return 0.0;
+        }
+        let h: f32 = self.counts.iter()
+            .filter(|&&c| c > 0)
+            .map(|&c| {
+                let p = c as f32 / total as f32;
+                -p * p.ln()
+            })
+            .sum();
+        h / (HEADING_BINS as f32).ln()
+    }
+}
+
+/// Consecutive still ticks of each subject agent, tracking the longest run
+#[derive(Debug, Clone, Default)]
+pub struct IdleStreaks {
+    prev: Vec<[f32; 2]>,
+    current: Vec<usize>,
+    longest: usize,
+}
+
+impl IdleStreaks {
+    /// Extend the streak of each living subject agent that stood still since
+    /// the previous call, and end the streak of every other agent
+    pub fn observe(&mut self, sim: &Simulation, subject_team: u32) {
+        let agents = sim.agents_data.chunks(AGENT_STRIDE);
+        if self.prev.is_empty() {
+            self.prev = agents.clone().map(|a| [a[IDX_X], a[IDX_Y]]).collect();
+            self.current = vec![0; self.prev.len()];
+            return;
+        }
+        for ((a, prev), run) in agents.zip(self.prev.iter_mut()).zip(self.current.iter_mut()) {
+            let pos = [a[IDX_X], a[IDX_Y]];
+            let still = (pos[0] - prev[0]).hypot(pos[1] - prev[1]) < STILL_EPS;
+            if a[IDX_TEAM] as u32 == subject_team && a[IDX_HEALTH] > 0.0 && still {
+                *run += 1;
+                self.longest = self.longest.max(*run);
+            } else {
+                *run = 0;
+            }
+            *prev = pos;
+        }
+    }
+
+    /// Longest run of consecutive still ticks seen so far
+    pub fn longest(&self) -> usize {
+        self.longest
+    }
+}
+
+/// Write one CSV row per genome (header first) for `generation`
+pub fn write_csv<W: Write>(mut out: W, generation: usize, genomes: &[Genome]) -> io::Result<()> {
+    writeln!(out, "{}", BehaviorMetrics::CSV_HEADER)?;
+    for g in genomes {
+        let m = &g.metrics;
+        writeln!(
+            out, "{},{},{},{},{},{},{},{}",
+            generation, g.id.map_or(String::new(), |id| id.to_string()), g.fitness, g.fitness_naive,
+            m.damage, m.loot, m.survival_ticks, m.movement_entropy,
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entropy_is_normalized() {
+        let mut h = HeadingHistogram::default();
+        assert_eq!(h.entropy(), 0.0);
+        h.counts[3] = 10;
+        assert_eq!(h.entropy(), 0.0);
+        h.counts = [1; HEADING_BINS];
+        assert!((h.entropy() - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn idle_streaks_reset_on_movement_and_ignore_opponents() {
+        let mut sim = Simulation::empty(100, 100);
+        // a subject agent and an opponent, both parked
+        sim.agents_data = vec![10.0, 10.0, 0.0, 100.0, 0.0, 0.0, 50.0, 50.0, 1.0, 100.0, 0.0, 0.0];
+        let mut idle = IdleStreaks::default();
+        for _ in 0..4 {
+            idle.observe(&sim, 0);
+        }
+        sim.agents_data[IDX_X] += 1.0;
+        idle.observe(&sim, 0);
+        idle.observe(&sim, 0);
+        assert_eq!(idle.longest(), 3);
+    }
+
+    #[test]
+    fn csv_has_a_row_per_genome() {
+        let genomes = vec![
+            Genome { id: Some(4), fitness: 2.5, metrics: BehaviorMetrics { damage: 10.0, ..Default::default() }, ..Genome::new() },
+            Genome::new(),
+        ];
+        let mut buf = Vec::new();
+        write_csv(&mut buf, 7, &genomes).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], BehaviorMetrics::CSV_HEADER);
+        assert_eq!(lines[1], "7,4,2.5,0,10,0,0,0");
+        assert!(lines[2].starts_with("7,,"));
+    }
+}
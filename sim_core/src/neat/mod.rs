@@ -1,7 +1,49 @@
 /// NEAT evolution scaffolding
+#[cfg(not(target_arch = "wasm32"))]
+pub mod bench;
 pub mod brain;
+pub mod bundle;
+pub mod champion;
+pub mod coevolution;
 pub mod config;
+pub mod curriculum;
+#[cfg(feature = "dashboard")]
+pub mod dashboard;
+pub mod env;
+pub mod error;
+pub mod eval;
+pub mod experiment;
+pub mod fitness;
 pub mod genome;
+#[cfg(all(feature = "grpc", not(target_arch = "wasm32")))]
+pub mod grpc_client;
+pub mod hyperneat;
+pub mod innovation;
+pub mod islands;
+pub mod lineage;
+pub mod map_elites;
+pub mod metrics;
+pub mod novelty;
+pub mod nsga2;
 pub mod onnx_exporter;
+pub mod onnx_importer;
+#[cfg(all(feature = "onnxruntime", not(target_arch = "wasm32")))]
+pub mod onnx_runtime;
+#[cfg(feature = "tract")]
+pub mod onnx_tract;
 pub mod population;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod prometheus;
+pub mod report;
+#[cfg(all(feature = "db", not(target_arch = "wasm32")))]
+pub mod results_db;
 pub mod runner;
+pub mod schema;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod server;
+pub mod species;
+pub mod sweep;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod telemetry;
+pub mod tournament;
+pub mod validate;
@@ -0,0 +1,86 @@
+//! Novelty search: reward genomes for behaving unlike anything seen so far.
+//!
+//! Behaviors are the `MatchStats::behavior` vectors averaged over a genome's
+//! matches. Novelty is the mean distance to the k nearest behaviors among the
+//! current population and the archive of past novel behaviors.
+
+use std::collections::VecDeque;
+use serde::{Serialize, Deserialize};
+
+/// Length of a behavior characterization vector
+pub const BEHAVIOR_LEN: usize = 5;
+
+/// Bounded archive of behaviors that were novel when seen
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoveltyArchive {
+    behaviors: VecDeque<Vec<f32>>,
+    k: usize,
+    threshold: f32,
+    capacity: usize,
+}
+
+fn distance(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum::<f32>().sqrt()
+}
+
+impl NoveltyArchive {
+    pub fn new(k: usize, threshold: f32, capacity: usize) -> Self {
+        NoveltyArchive { behaviors: VecDeque::new(), k: k.max(1), threshold, capacity }
+    }
+
+    pub fn len(&self) -> usize {
+        self.behaviors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.behaviors.is_empty()
+    }
+
+    /// Novelty of `population[idx]` against the rest of the population and the archive
+    pub fn score(&self, population: &[Vec<f32>], idx: usize) -> f32 {
+        let b = &population[idx];
+        let mut dists: Vec<f32> = population.iter().enumerate()
+            .filter(|&(i, _)| i != idx)
+            .map(|(_, o)| distance(b, o))
+            .chain(self.behaviors.iter().map(|o| distance(b, o)))
+            .collect();
+        if dists.is_empty() {
+            return 0.0;
+        }
+        dists.sort_by(f32::total_cmp);
+        let k = self.k.min(dists.len());
+        dists[..k].iter().sum::<f32>() / k as f32
+    }
+
+    /// Score every behavior, then archive those above the novelty threshold
+    /// (oldest entries are evicted past capacity)
+    pub fn score_population(&mut self, population: &[Vec<f32>]) -> Vec<f32> {
+        let scores: Vec<f32> = (0..population.len()).map(|i| self.score(population, i)).collect();
+        for (b, &s) in population.iter().zip(&scores) {
+            if s > self.threshold && self.capacity > 0 {
+                if self.behaviors.len() == self.capacity {
+                    self.behaviors.pop_front();
+                }
+                self.behaviors.push_back(b.clone());
+            }
+        }
+        scores
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outliers_score_higher_and_get_archived() {
+        let mut archive = NoveltyArchive::new(2, 0.5, 10);
+        let pop = vec![vec![0.0, 0.0], vec![0.1, 0.0], vec![0.0, 0.1], vec![5.0, 5.0]];
+        let scores = archive.score_population(&pop);
+        assert!(scores[3] > scores[0] && scores[3] > scores[1]);
+        assert_eq!(archive.len(), 1);
+        // the archived outlier now counts as a zero-distance neighbour of a repeat
+        let again = archive.score(&[vec![5.0, 5.0], vec![0.0, 0.0]], 0);
+        assert!((again - distance(&[5.0, 5.0], &[0.0, 0.0]) / 2.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,117 @@
+//! NSGA-II multi-objective selection: rank genomes by Pareto front over
+//! health, damage, and time-to-win instead of a weighted sum, breaking ties
+//! within a front by crowding distance.
+
+use super::config::EvolutionConfig;
+use super::runner::MatchStats;
+
+/// Objectives (all maximized) for one match: remaining health, damage dealt,
+/// and ticks to spare if the subject survived
+pub fn objectives(stats: &MatchStats, evo_cfg: &EvolutionConfig) -> Vec<f32> {
+    let time = if stats.subject_team_health > 0.0 {
+        evo_cfg.max_ticks as f32 - stats.ticks as f32
+    } else {
+        0.0
+    };
+    vec![stats.subject_team_health, stats.total_damage_inflicted, time]
+}
+
+/// `a` is at least as good as `b` everywhere and strictly better somewhere
+pub fn dominates(a: &[f32], b: &[f32]) -> bool {
+    a.iter().zip(b).all(|(x, y)| x >= y) && a.iter().zip(b).any(|(x, y)| x > y)
+}
+
+/// Fast non-dominated sort: indices of `points` grouped by front, best first
+pub fn fronts(points: &[Vec<f32>]) -> Vec<Vec<usize>> {
+    let n = points.len();
+    let mut dominated: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut counts = vec![0usize; n];
+    for i in 0..n {
+        for j in 0..n {
+            if dominates(&points[i], &points[j]) {
+                dominated[i].push(j);
+            } else if dominates(&points[j], &points[i]) {
+                counts[i] += 1;
+            }
+        }
+    }
+    let mut fronts = Vec::new();
+    let mut current: Vec<usize> = (0..n).filter(|&i| counts[i] == 0).collect();
+    while !current.is_empty() {
+        let mut next = Vec::new();
+        for &i in &current {
+            for &j in &dominated[i] {
+                counts[j] -= 1;
+                if counts[j] == 0 {
+                    next.push(j);
+                }
+            }
+        }
+        fronts.push(current);
+        current = next;
+    }
+    fronts
+}
+
+/// Crowding distance of each member of `front` (same order); boundary points are infinite
+pub fn crowding(points: &[Vec<f32>], front: &[usize]) -> Vec<f32> {
+    let mut dist = vec![0.0f32; front.len()];
+    let dims = front.first().map_or(0, |&i| points[i].len());
+    let mut order: Vec<usize> = (0..front.len()).collect();
+    for m in 0..dims {
+        let value = |k: usize| points[front[k]].get(m).copied().unwrap_or(0.0);
+        order.sort_by(|&a, &b| value(a).total_cmp(&value(b)));
+        let (Some(&lo), Some(&hi)) = (order.first(), order.last()) else { continue };
+        dist[lo] = f32::INFINITY;
+        dist[hi] = f32::INFINITY;
+        let range = value(hi) - value(lo);
+        if range <= 0.0 {
+            continue;
+        }
+        for w in order.windows(3) {
+            dist[w[1]] += (value(w[2]) - value(w[0])) / range;
+        }
+    }
+    dist
+}
+
+/// Scalar scores preserving the crowded-comparison order: a better front
+/// always scores higher, and within a front more crowding distance scores
+/// higher (front count minus rank, plus a crowding term in [0, 1])
+pub fn crowded_scores(points: &[Vec<f32>]) -> Vec<f32> {
+    let fronts = fronts(points);
+    let mut scores = vec![0.0; points.len()];
+    for (rank, front) in fronts.iter().enumerate() {
+        let base = (fronts.len() - rank) as f32;
+        for (&i, c) in front.iter().zip(crowding(points, front)) {
+            let c = if c.is_finite() { c / (1.0 + c) } else { 1.0 };
+            scores[i] = base + c;
+        }
+    }
+    scores
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorts_into_pareto_fronts() {
+        let points = vec![
+            vec![1.0, 1.0],
+            vec![3.0, 1.0],
+            vec![1.0, 3.0],
+            vec![2.0, 2.0],
+            vec![0.0, 0.0],
+        ];
+        assert!(dominates(&points[3], &points[0]));
+        assert!(!dominates(&points[1], &points[2]));
+        assert_eq!(fronts(&points), vec![vec![1, 2, 3], vec![0], vec![4]]);
+        let c = crowding(&points, &[1, 2, 3]);
+        assert!(c[0].is_infinite() && c[1].is_infinite());
+        assert!((c[2] - 2.0).abs() < 1e-6);
+        let s = crowded_scores(&points);
+        assert!(s[3] > s[0] && s[0] > s[4]);
+        assert!(s[1] > s[3]);
+    }
+}
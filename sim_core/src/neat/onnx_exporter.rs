@@ -1,19 +1,72 @@
+use std::collections::HashMap;
 use prost::Message;
+use serde_json::json;
+use tracing::debug;
+use crate::config::Config;
 use crate::onnx_generated::onnx::{
     ModelProto, GraphProto, NodeProto, TensorProto, ValueInfoProto, TensorShapeProto,
-    TypeProto, OperatorSetIdProto,
+    TypeProto, OperatorSetIdProto, StringStringEntryProto,
 };
 use crate::onnx_generated::onnx::tensor_proto::DataType;
 use crate::onnx_generated::onnx::tensor_shape_proto::Dimension;
 use crate::onnx_generated::onnx::tensor_shape_proto::dimension::Value as DimValue;
 use crate::onnx_generated::onnx::type_proto::Tensor as TypeTensor;
 use crate::onnx_generated::onnx::type_proto::Value as TypeValue;
-use super::genome::Genome;
+use super::error::NeatError;
+use super::genome::{Activation, Genome};
 
-/// Convert a strictly feed-forward Genome into ONNX bytes
-pub fn export_genome(genome: &Genome) -> Vec<u8> {
+/// Convert a Genome into ONNX bytes. Any acyclic topology (hidden chains of
+/// any depth, skip connections) is exported exactly: `Genome::layers` levels
+/// it into dense layers, carrying skipped values through identity slots.
+/// Recurrent or malformed genomes are rejected, since the stateless graph
+/// would silently compute something else.
+pub fn export_genome(genome: &Genome) -> Result<Vec<u8>, NeatError> {
+    export_with_metadata(genome, None, None)
+}
+
+/// `export_genome` for a training champion: `metadata_props` also record
+/// the generation, the sensor layout the inputs follow, and the full sim config
+pub fn export_champion(genome: &Genome, generation: usize, sim_cfg: &Config) -> Result<Vec<u8>, NeatError> {
+    export_with_metadata(genome, Some(generation), Some(sim_cfg))
+}
+
+/// `metadata_props` of an ONNX model as a map
+pub fn read_metadata(bytes: &[u8]) -> Result<HashMap<String, String>, prost::DecodeError> {
+    Ok(ModelProto::decode(bytes)?.metadata_props.into_iter()
+        .filter_map(|p| Some((p.key?, p.value.unwrap_or_default())))
+        .collect())
+}
+
+/// Self-description written under `neat.*` keys
+fn metadata(genome: &Genome, generation: Option<usize>, sim_cfg: Option<&Config>) -> Result<Vec<StringStringEntryProto>, serde_json::Error> {
+    let mut props = vec![
+        ("neat.fitness", genome.fitness.to_string()),
+        ("neat.fitness_naive", genome.fitness_naive.to_string()),
+        ("neat.input_size", genome.input_size().to_string()),
+        ("neat.outputs", "vx,vy,fire".to_string()),
+    ];
+    if let Some(id) = genome.id {
+        props.push(("neat.genome_id", id.to_string()));
+    }
+    if let Some(generation) = generation {
+        props.push(("neat.generation", generation.to_string()));
+    }
+    if let Some(cfg) = sim_cfg {
+        let layout: Vec<_> = cfg.sensor_layout().into_iter()
+            .map(|(group, slots, features)| json!({ "group": group, "slots": slots, "features": features }))
+            .collect();
+        props.push(("neat.sensor_layout", serde_json::to_string(&layout)?));
+        props.push(("neat.sim_config", serde_json::to_string(cfg)?));
+    }
+    Ok(props.into_iter()
+        .map(|(k, v)| StringStringEntryProto { key: Some(k.to_string()), value: Some(v) })
+        .collect())
+}
+
+fn export_with_metadata(genome: &Genome, generation: Option<usize>, sim_cfg: Option<&Config>) -> Result<Vec<u8>, NeatError> {
+    genome.validate(false)?;
     // Debug: report uninitialized genome layers
-    println!("export_genome: genome.layers() = {}", genome.layers().len());
+    debug!(layers = genome.layers().len(), "exporting genome");
 
     // 1) Model header
     let mut model = ModelProto::default();
@@ -73,14 +126,41 @@ pub fn export_genome(genome: &Genome) -> Vec<u8> {
         add.op_type = Some("Add".to_string());
         graph.node.push(add);
 
-        // Relu node
-        let mut relu = NodeProto::default();
-        relu.input = vec![format!("pre{}", i)];
-        relu.output = vec![format!("act{}", i)];
-        relu.op_type = Some("Relu".to_string());
-        graph.node.push(relu);
+        // Activation: one op when the layer is uniform, else a masked sum of per-kind ops
+        // (kinds taken from the layer itself: carried values use Identity, which
+        // is not in `Activation::ALL`)
+        let act = format!("act{}", i);
+        let mut kinds: Vec<Activation> = Vec::new();
+        for &a in &layer.activations {
+            if !kinds.contains(&a) {
+                kinds.push(a);
+            }
+        }
+        if kinds.len() <= 1 {
+            let kind = kinds.first().copied().unwrap_or_default();
+            push_activation(&mut graph, kind, &format!("pre{}", i), &act);
+        } else {
+            let mut terms = Vec::new();
+            for (k, &kind) in kinds.iter().enumerate() {
+                let mask: Vec<f32> = layer.activations.iter().map(|a| if *a == kind { 1.0 } else { 0.0 }).collect();
+                let mask_name = format!("M{}_{}", i, k);
+                graph.initializer.push(TensorProto {
+                    name: Some(mask_name.clone()),
+                    data_type: Some(DataType::Float as i32),
+                    dims: vec![out_dim as i64],
+                    raw_data: Some(mask.iter().flat_map(|f| f.to_le_bytes()).collect()),
+                    ..Default::default()
+                });
+                let raw = format!("act{}_{}", i, k);
+                push_activation(&mut graph, kind, &format!("pre{}", i), &raw);
+                let term = format!("{}_masked", raw);
+                graph.node.push(op("Mul", vec![raw, mask_name], &term));
+                terms.push(term);
+            }
+            graph.node.push(op("Sum", terms, &act));
+        }
 
-        prev = format!("act{}", i);
+        prev = act;
     }
 
     // 5) Define output ValueInfo
@@ -98,7 +178,7 @@ pub fn export_genome(genome: &Genome) -> Vec<u8> {
     graph.output.push(output_info);
 
     // Debug: graph contents
-    println!("graph: nodes={}, initializers={}", graph.node.len(), graph.initializer.len());
+    debug!(nodes = graph.node.len(), initializers = graph.initializer.len(), "built ONNX graph");
 
     // 6) Attach opset and encode
     let mut opset = OperatorSetIdProto::default();
@@ -107,20 +187,209 @@ pub fn export_genome(genome: &Genome) -> Vec<u8> {
     model.opset_import.push(opset);
 
     model.graph = Some(graph);
-    model.encode_to_vec()
+    model.producer_name = Some("sim_core".to_string());
+    model.producer_version = Some(env!("CARGO_PKG_VERSION").to_string());
+    model.metadata_props = metadata(genome, generation, sim_cfg)?;
+    Ok(model.encode_to_vec())
+}
+
+/// Single-output node with the given op and inputs
+fn op(op_type: &str, input: Vec<String>, output: &str) -> NodeProto {
+    NodeProto {
+        input,
+        output: vec![output.to_string()],
+        op_type: Some(op_type.to_string()),
+        ..Default::default()
+    }
+}
+
+/// Append the nodes computing `activation(input)` into `output`
+fn push_activation(graph: &mut GraphProto, activation: Activation, input: &str, output: &str) {
+    let single = |op_type: &str| op(op_type, vec![input.to_string()], output);
+    match activation {
+        Activation::Tanh => graph.node.push(single("Tanh")),
+        Activation::Relu => graph.node.push(single("Relu")),
+        Activation::Sigmoid => graph.node.push(single("Sigmoid")),
+        Activation::Sine => graph.node.push(single("Sin")),
+        Activation::Identity => graph.node.push(single("Identity")),
+        Activation::Gaussian => {
+            // exp(-(x*x))
+            let sq = format!("{}_sq", output);
+            let neg = format!("{}_neg", output);
+            graph.node.push(op("Mul", vec![input.to_string(), input.to_string()], &sq));
+            graph.node.push(op("Neg", vec![sq.clone()], &neg));
+            graph.node.push(op("Exp", vec![neg], output));
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::neat::genome::GenomeError;
     use crate::neat::population::Population;
     use crate::onnx_generated::onnx::ModelProto;
     use prost::Message;
 
+    fn op_types(bytes: &[u8]) -> Vec<String> {
+        let graph = ModelProto::decode(bytes).unwrap().graph.unwrap();
+        graph.node.into_iter().filter_map(|n| n.op_type).collect()
+    }
+
+    #[test]
+    fn test_export_respects_activations() {
+        let mut genome = crate::neat::genome::Genome::new();
+        genome.initialize(&crate::config::Config::default(), &Default::default(), &mut rand::thread_rng());
+        let ops = op_types(&export_genome(&genome).unwrap());
+        assert!(ops.contains(&"Tanh".to_string()) && !ops.contains(&"Relu".to_string()));
+
+        genome.nodes.iter_mut().filter(|n| n.node_type == crate::neat::genome::NodeType::Output)
+            .take(1).for_each(|n| n.activation = Activation::Gaussian);
+        let ops = op_types(&export_genome(&genome).unwrap());
+        assert!(ops.contains(&"Exp".to_string()) && ops.contains(&"Sum".to_string()));
+    }
+
+    /// Evaluate an exported graph on one input row, reading every tensor
+    /// with its declared dims (a reference interpreter for the ops we emit)
+    fn run_graph(bytes: &[u8], x: &[f32]) -> Vec<f32> {
+        use std::collections::HashMap;
+        let graph = ModelProto::decode(bytes).unwrap().graph.unwrap();
+        let floats = |raw: &[u8]| raw.chunks(4).map(|b| f32::from_le_bytes(b.try_into().unwrap())).collect::<Vec<f32>>();
+        let mut dims: HashMap<String, Vec<i64>> = HashMap::new();
+        let mut vals: HashMap<String, Vec<f32>> = HashMap::new();
+        for t in &graph.initializer {
+            let name = t.name.clone().unwrap();
+            dims.insert(name.clone(), t.dims.clone());
+            vals.insert(name, floats(t.raw_data.as_deref().unwrap()));
+        }
+        vals.insert("X".to_string(), x.to_vec());
+        for node in &graph.node {
+            let arg = |k: usize| vals[&node.input[k]].clone();
+            let map = |f: fn(f32) -> f32| arg(0).into_iter().map(f).collect::<Vec<f32>>();
+            let zip = |f: fn(f32, f32) -> f32| {
+                let (a, b) = (arg(0), arg(1));
+                assert_eq!(a.len(), b.len(), "{} operands differ in length", node.input[0]);
+                a.iter().zip(&b).map(|(&a, &b)| f(a, b)).collect::<Vec<f32>>()
+            };
+            let out = match node.op_type.as_deref().unwrap() {
+                "MatMul" => {
+                    let (v, w) = (arg(0), arg(1));
+                    let d = &dims[&node.input[1]];
+                    let (rows, cols) = (d[0] as usize, d[1] as usize);
+                    assert_eq!(v.len(), rows, "MatMul inner dimension mismatch");
+                    (0..cols).map(|c| (0..rows).map(|r| v[r] * w[r * cols + c]).sum()).collect()
+                }
+                "Add" => zip(|a, b| a + b),
+                "Mul" => zip(|a, b| a * b),
+                "Sum" => (0..arg(0).len()).map(|i| node.input.iter().map(|n| vals[n][i]).sum()).collect(),
+                "Tanh" => map(f32::tanh),
+                "Relu" => map(|v| v.max(0.0)),
+                "Sigmoid" => map(|v| 1.0 / (1.0 + (-v).exp())),
+                "Sin" => map(f32::sin),
+                "Identity" => arg(0),
+                "Neg" => map(|v| -v),
+                "Exp" => map(f32::exp),
+                other => panic!("unexpected op {}", other),
+            };
+            vals.insert(node.output[0].clone(), out);
+        }
+        vals.remove(graph.output[0].name.as_deref().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_export_matches_feed_forward() {
+        use crate::neat::config::EvolutionConfig;
+        use crate::neat::genome::NodeType;
+        use crate::neat::innovation::InnovationTracker;
+        use rand::Rng;
+        let evo_cfg = EvolutionConfig { mutation_add_node_rate: 1.0, mutation_add_conn_rate: 1.0, ..Default::default() };
+        let mut rng = rand::thread_rng();
+        let mut genome = crate::neat::genome::Genome::new();
+        genome.initialize(&crate::config::Config::default(), &evo_cfg, &mut rng);
+        let mut tracker = InnovationTracker::new();
+        tracker.observe(&genome);
+        for _ in 0..4 {
+            genome.mutate(&evo_cfg, &mut tracker, &mut rng);
+        }
+        // non-zero biases and a mixed output layer
+        let bias = genome.nodes.iter().find(|n| n.node_type == NodeType::Bias).unwrap().id;
+        for c in genome.conns.iter_mut().filter(|c| c.in_node == bias) {
+            c.weight = rng.gen_range(-2.0..2.0);
+        }
+        genome.nodes.iter_mut().filter(|n| n.node_type == NodeType::Output)
+            .take(1).for_each(|n| n.activation = Activation::Sigmoid);
+        let bytes = export_genome(&genome).unwrap();
+        for _ in 0..5 {
+            let x: Vec<f32> = (0..genome.input_size()).map(|_| rng.gen_range(-1.0..1.0)).collect();
+            let expected = genome.feed_forward(&x);
+            let actual = run_graph(&bytes, &x);
+            assert_eq!(expected.len(), actual.len());
+            for (e, a) in expected.iter().zip(&actual) {
+                assert!((e - a).abs() < 1e-5, "feed_forward {:?} != onnx {:?}", expected, actual);
+            }
+        }
+    }
+
+    #[test]
+    fn test_export_deep_and_skip_connections() {
+        use crate::neat::genome::{ConnGene, NodeGene, NodeType};
+        // inputs 0, 1 → h5 → h4 → h6 → output 2, skips 0 → 2 and 1 → h6, bias 3 → h4
+        let conn = |in_node, out_node, weight, innovation| ConnGene { in_node, out_node, weight, enabled: true, innovation };
+        let mut genome = Genome {
+            nodes: vec![
+                NodeGene::new(0, NodeType::Input),
+                NodeGene::new(1, NodeType::Input),
+                NodeGene::new(2, NodeType::Output),
+                NodeGene::new(3, NodeType::Bias),
+                NodeGene::new(4, NodeType::Hidden),
+                NodeGene::new(5, NodeType::Hidden),
+                NodeGene::new(6, NodeType::Hidden),
+            ],
+            conns: vec![
+                conn(0, 5, 0.9, 0),
+                conn(5, 4, -1.3, 1),
+                conn(4, 6, 0.7, 2),
+                conn(6, 2, 1.1, 3),
+                conn(0, 2, 0.4, 4),
+                conn(1, 6, -0.8, 5),
+                conn(3, 4, 0.2, 6),
+            ],
+            ..Genome::new()
+        };
+        genome.nodes[5].activation = Activation::Relu;
+        let bytes = export_genome(&genome).unwrap();
+        for x in [[0.6, -0.3], [-1.0, 0.5], [0.0, 0.0]] {
+            let (expected, actual) = (genome.feed_forward(&x), run_graph(&bytes, &x));
+            assert!((expected[0] - actual[0]).abs() < 1e-6, "{:?} != {:?}", expected, actual);
+        }
+        // a cycle cannot be expressed by the stateless graph
+        genome.conns.push(conn(6, 5, 0.5, 7));
+        assert!(matches!(export_genome(&genome), Err(NeatError::Genome(GenomeError::Cycle(_)))));
+    }
+
+    #[test]
+    fn test_champion_metadata() {
+        let sim_cfg = crate::config::Config::default();
+        let mut genome = crate::neat::genome::Genome::new();
+        genome.initialize(&sim_cfg, &Default::default(), &mut rand::thread_rng());
+        genome.fitness = 12.5;
+        let props = read_metadata(&export_champion(&genome, 42, &sim_cfg).unwrap()).unwrap();
+        assert_eq!(props["neat.generation"], "42");
+        assert_eq!(props["neat.fitness"], "12.5");
+        assert_eq!(props["neat.input_size"], sim_cfg.sensor_len().to_string());
+        let layout: serde_json::Value = serde_json::from_str(&props["neat.sensor_layout"]).unwrap();
+        assert_eq!(layout[1]["slots"], sim_cfg.nearest_k_enemies);
+        let cfg: crate::config::Config = serde_json::from_str(&props["neat.sim_config"]).unwrap();
+        assert_eq!(cfg.sensor_len(), sim_cfg.sensor_len());
+        // a bare export says nothing about training context
+        let props = read_metadata(&export_genome(&genome).unwrap()).unwrap();
+        assert!(!props.contains_key("neat.generation") && !props.contains_key("neat.sim_config"));
+    }
+
     #[test]
     fn test_export_genome_valid() {
         let pop = Population::new(&Default::default());
-        let bytes = export_genome(&pop.genomes[0]);
+        let bytes = export_genome(&pop.genomes[0]).unwrap();
         let model = ModelProto::decode(&*bytes).expect("Failed to decode ONNX bytes");
         assert!(model.graph.is_some(), "Graph is missing");
         assert_eq!(model.opset_import.len(), 1, "Expected exactly one opset_import");
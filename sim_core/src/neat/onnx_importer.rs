@@ -0,0 +1,437 @@
+//! Import of simple MLP ONNX models (e.g. PyTorch distillations of champions)
+//! as a `Genome`, so they play as a `NeatBrain` in matches and tournaments.
+//!
+//! Supported graphs are one chain of dense layers, `MatMul` (+ `Add`) or
+//! `Gemm`, each optionally followed by Tanh, Relu, Sigmoid, Sin, or Identity,
+//! plus the Gaussian and masked mixed-activation patterns `export_genome`
+//! writes. Every unit becomes a node and every non-zero weight a connection.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use prost::Message;
+use crate::onnx_generated::onnx::{ModelProto, NodeProto, TensorProto};
+use crate::onnx_generated::onnx::tensor_proto::DataType;
+use super::genome::{Activation, ConnGene, Genome, GenomeError, NodeGene, NodeType};
+
+#[derive(Debug)]
+pub enum ImportError {
+    Io(std::io::Error),
+    /// Not a protobuf-encoded ONNX model
+    Decode(prost::DecodeError),
+    /// Valid ONNX using an op or pattern outside the supported MLP subset
+    Unsupported(String),
+    /// Inconsistent graph (missing tensors, mismatched shapes)
+    Malformed(String),
+    /// The resulting genome failed validation
+    Invalid(GenomeError),
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Io(e) => write!(f, "ONNX read error: {}", e),
+            ImportError::Decode(e) => write!(f, "invalid ONNX protobuf: {}", e),
+            ImportError::Unsupported(what) => write!(f, "unsupported ONNX graph: {}", what),
+            ImportError::Malformed(what) => write!(f, "malformed ONNX graph: {}", what),
+            ImportError::Invalid(e) => write!(f, "imported genome is invalid: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+impl From<std::io::Error> for ImportError {
+    fn from(e: std::io::Error) -> Self { ImportError::Io(e) }
+}
+
+impl From<prost::DecodeError> for ImportError {
+    fn from(e: prost::DecodeError) -> Self { ImportError::Decode(e) }
+}
+
+impl From<GenomeError> for ImportError {
+    fn from(e: GenomeError) -> Self { ImportError::Invalid(e) }
+}
+
+fn malformed<T>(what: impl Into<String>) -> Result<T, ImportError> {
+    Err(ImportError::Malformed(what.into()))
+}
+
+fn unsupported<T>(what: impl Into<String>) -> Result<T, ImportError> {
+    Err(ImportError::Unsupported(what.into()))
+}
+
+/// Affine map of one layer before its activation
+#[derive(Debug, Clone, PartialEq)]
+struct Dense {
+    in_dim: usize,
+    out_dim: usize,
+    /// Row-major `[out_dim, in_dim]`
+    weights: Vec<f32>,
+    biases: Vec<f32>,
+}
+
+/// What a graph tensor holds, in terms of the layer chain
+#[derive(Debug, Clone)]
+enum Value {
+    /// Output of committed layer `k` (None: the graph input)
+    Layer(Option<usize>),
+    Linear(Dense),
+    /// `x * x` and `-(x * x)` of a linear value (Gaussian in progress)
+    Square(Dense),
+    NegSquare(Dense),
+    /// Per-unit activations applied so far (None: masked out)
+    Activated(Dense, Vec<Option<Activation>>),
+}
+
+/// Parse ONNX bytes into a genome
+pub fn genome_from_onnx(bytes: &[u8]) -> Result<Genome, ImportError> {
+    let model = ModelProto::decode(bytes)?;
+    let Some(graph) = model.graph else { return malformed("model has no graph") };
+    let inits: HashMap<&str, &TensorProto> = graph.initializer.iter()
+        .filter_map(|t| t.name.as_deref().map(|n| (n, t)))
+        .collect();
+    let Some(input) = graph.input.iter().filter_map(|i| i.name.as_deref()).find(|n| !inits.contains_key(n)) else {
+        return malformed("no graph input");
+    };
+    let mut values: HashMap<String, Value> = HashMap::new();
+    values.insert(input.to_string(), Value::Layer(None));
+    let mut layers: Vec<(Dense, Vec<Activation>)> = Vec::new();
+    for node in &graph.node {
+        let out = step(node, &mut values, &inits, &mut layers)?;
+        let Some(name) = node.output.first() else { return malformed("node without output") };
+        values.insert(name.clone(), out);
+    }
+    let Some(output) = graph.output.first().and_then(|o| o.name.as_deref()) else {
+        return malformed("no graph output");
+    };
+    let Some(last) = values.remove(output) else { return malformed(format!("output {} is never computed", output)) };
+    if commit(last, &mut layers)?.is_none() {
+        return unsupported("graph output is its input");
+    }
+    let genome = build(&layers);
+    genome.validate(false)?;
+    Ok(genome)
+}
+
+/// Read and import an ONNX model file
+pub fn load_onnx<P: AsRef<Path>>(path: P) -> Result<Genome, ImportError> {
+    genome_from_onnx(&std::fs::read(path)?)
+}
+
+/// Float contents and dims of an initializer
+fn tensor<'a>(inits: &HashMap<&str, &'a TensorProto>, name: &str) -> Result<(Vec<f32>, &'a [i64]), ImportError> {
+    let Some(t) = inits.get(name) else { return malformed(format!("{} is not an initializer", name)) };
+    if t.data_type.unwrap_or(DataType::Float as i32) != DataType::Float as i32 {
+        return unsupported(format!("{} is not a float tensor", name));
+    }
+    let data = match &t.raw_data {
+        Some(raw) if !raw.is_empty() => raw.chunks_exact(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect(),
+        _ => t.float_data.clone(),
+    };
+    let expected: i64 = t.dims.iter().product();
+    if data.len() as i64 != expected {
+        return malformed(format!("{} has {} values for dims {:?}", name, data.len(), t.dims));
+    }
+    Ok((data, &t.dims))
+}
+
+/// Make `value` a committed layer output, returning its index (None for the graph input)
+fn commit(value: Value, layers: &mut Vec<(Dense, Vec<Activation>)>) -> Result<Option<usize>, ImportError> {
+    let (dense, acts) = match value {
+        Value::Layer(k) => return Ok(k),
+        Value::Linear(d) => {
+            let acts = vec![Activation::Identity; d.out_dim];
+            (d, acts)
+        }
+        Value::Activated(d, acts) => {
+            let Some(acts) = acts.into_iter().collect::<Option<Vec<_>>>() else {
+                return malformed("a layer's masked activations do not cover every unit");
+            };
+            (d, acts)
+        }
+        Value::Square(_) | Value::NegSquare(_) => return unsupported("squared values outside a Gaussian"),
+    };
+    layers.push((dense, acts));
+    Ok(Some(layers.len() - 1))
+}
+
+/// Start a dense layer reading `input`; it must continue the chain
+fn dense_input(value: Value, layers: &mut Vec<(Dense, Vec<Activation>)>, in_dim: usize) -> Result<(), ImportError> {
+    let k = commit(value, layers)?;
+    if k.map_or(!layers.is_empty(), |k| k + 1 != layers.len()) {
+        return unsupported("layers do not form a single chain");
+    }
+    if let Some(k) = k {
+        if layers[k].0.out_dim != in_dim {
+            return malformed(format!("layer {} has {} outputs but the next expects {}", k, layers[k].0.out_dim, in_dim));
+        }
+    }
+    Ok(())
+}
+
+/// Interpret one node
+fn step(
+    node: &NodeProto,
+    values: &mut HashMap<String, Value>,
+    inits: &HashMap<&str, &TensorProto>,
+    layers: &mut Vec<(Dense, Vec<Activation>)>,
+) -> Result<Value, ImportError> {
+    let op = node.op_type.as_deref().unwrap_or("");
+    let arg = |k: usize| -> Result<Value, ImportError> {
+        let Some(name) = node.input.get(k) else { return malformed(format!("{} is missing input {}", op, k)) };
+        match values.get(name) {
+            Some(v) => Ok(v.clone()),
+            None => malformed(format!("{} reads unknown tensor {}", op, name)),
+        }
+    };
+    let attr_f = |name: &str, default: f32| node.attribute.iter().find(|a| a.name.as_deref() == Some(name)).and_then(|a| a.f).unwrap_or(default);
+    let attr_i = |name: &str| node.attribute.iter().find(|a| a.name.as_deref() == Some(name)).and_then(|a| a.i).unwrap_or(0);
+    let activation = match op {
+        "Tanh" => Some(Activation::Tanh),
+        "Relu" => Some(Activation::Relu),
+        "Sigmoid" => Some(Activation::Sigmoid),
+        "Sin" => Some(Activation::Sine),
+        "Identity" => Some(Activation::Identity),
+        _ => None,
+    };
+    if let Some(act) = activation {
+        return match arg(0)? {
+            Value::Linear(d) => {
+                let n = d.out_dim;
+                Ok(Value::Activated(d, vec![Some(act); n]))
+            }
+            other if act == Activation::Identity => Ok(other),
+            _ => unsupported(format!("{} applied to an already activated value", op)),
+        };
+    }
+    match op {
+        "MatMul" => {
+            let (w, dims) = tensor(inits, &node.input[1])?;
+            let &[in_dim, out_dim] = dims else { return unsupported("MatMul weights must be 2-D") };
+            let (in_dim, out_dim) = (in_dim as usize, out_dim as usize);
+            dense_input(arg(0)?, layers, in_dim)?;
+            let weights = (0..out_dim).flat_map(|o| (0..in_dim).map(move |i| (i, o))).map(|(i, o)| w[i * out_dim + o]).collect();
+            Ok(Value::Linear(Dense { in_dim, out_dim, weights, biases: vec![0.0; out_dim] }))
+        }
+        "Gemm" => {
+            if attr_i("transA") != 0 {
+                return unsupported("Gemm with transA");
+            }
+            let (w, dims) = tensor(inits, &node.input[1])?;
+            let &[rows, cols] = dims else { return unsupported("Gemm weights must be 2-D") };
+            let (rows, cols) = (rows as usize, cols as usize);
+            let trans_b = attr_i("transB") != 0;
+            let (in_dim, out_dim) = if trans_b { (cols, rows) } else { (rows, cols) };
+            dense_input(arg(0)?, layers, in_dim)?;
+            let alpha = attr_f("alpha", 1.0);
+            let weights = (0..out_dim).flat_map(|o| (0..in_dim).map(move |i| (i, o)))
+                .map(|(i, o)| alpha * if trans_b { w[o * in_dim + i] } else { w[i * out_dim + o] })
+                .collect();
+            let biases = match node.input.get(2).filter(|n| !n.is_empty()) {
+                Some(name) => {
+                    let beta = attr_f("beta", 1.0);
+                    broadcast(tensor(inits, name)?.0, out_dim)?.into_iter().map(|b| beta * b).collect()
+                }
+                None => vec![0.0; out_dim],
+            };
+            Ok(Value::Linear(Dense { in_dim, out_dim, weights, biases }))
+        }
+        "Add" => {
+            let (linear, bias) = match (values.get(&node.input[0]), values.get(&node.input[1])) {
+                (Some(Value::Linear(d)), None) => (d.clone(), &node.input[1]),
+                (None, Some(Value::Linear(d))) => (d.clone(), &node.input[0]),
+                _ => return unsupported("Add other than a bias on a linear value"),
+            };
+            let b = broadcast(tensor(inits, bias)?.0, linear.out_dim)?;
+            let biases = linear.biases.iter().zip(b).map(|(a, b)| a + b).collect();
+            Ok(Value::Linear(Dense { biases, ..linear }))
+        }
+        "Mul" => match (arg(0)?, values.get(&node.input[1])) {
+            (Value::Linear(d), _) if node.input[0] == node.input[1] => Ok(Value::Square(d)),
+            (Value::Activated(d, acts), None) => {
+                let mask = broadcast(tensor(inits, &node.input[1])?.0, d.out_dim)?;
+                let mut masked = Vec::with_capacity(acts.len());
+                for (a, m) in acts.into_iter().zip(mask) {
+                    masked.push(match m {
+                        1.0 => a,
+                        0.0 => None,
+                        _ => return unsupported("Mul by a non-mask tensor"),
+                    });
+                }
+                Ok(Value::Activated(d, masked))
+            }
+            _ => unsupported("Mul other than a square or an activation mask"),
+        },
+        "Neg" => match arg(0)? {
+            Value::Square(d) => Ok(Value::NegSquare(d)),
+            _ => unsupported("Neg outside a Gaussian"),
+        },
+        "Exp" => match arg(0)? {
+            Value::NegSquare(d) => {
+                let n = d.out_dim;
+                Ok(Value::Activated(d, vec![Some(Activation::Gaussian); n]))
+            }
+            _ => unsupported("Exp outside a Gaussian"),
+        },
+        "Sum" => {
+            let mut parts = (0..node.input.len()).map(arg);
+            let Some(Value::Activated(d, mut acts)) = parts.next().transpose()? else {
+                return unsupported("Sum other than of masked activations");
+            };
+            for part in parts {
+                let Value::Activated(d2, acts2) = part? else { return unsupported("Sum other than of masked activations") };
+                if d2 != d {
+                    return unsupported("Sum across different layers");
+                }
+                for (a, b) in acts.iter_mut().zip(acts2) {
+                    match (*a, b) {
+                        (Some(_), Some(_)) => return malformed("overlapping activation masks"),
+                        (None, b) => *a = b,
+                        _ => {}
+                    }
+                }
+            }
+            Ok(Value::Activated(d, acts))
+        }
+        other => unsupported(format!("op {}", other)),
+    }
+}
+
+/// A bias-like tensor of `n` values (or one value for all)
+fn broadcast(v: Vec<f32>, n: usize) -> Result<Vec<f32>, ImportError> {
+    match v.len() {
+        len if len == n => Ok(v),
+        1 => Ok(vec![v[0]; n]),
+        len => malformed(format!("expected {} values, found {}", n, len)),
+    }
+}
+
+/// Genome with one node per unit, laid out like `Genome::initialize`
+/// (inputs, outputs, bias, then hidden)
+fn build(layers: &[(Dense, Vec<Activation>)]) -> Genome {
+    let n_in = layers[0].0.in_dim;
+    let n_out = layers[layers.len() - 1].0.out_dim;
+    let bias_id = n_in + n_out;
+    let mut genome = Genome::new();
+    genome.nodes.extend((0..n_in).map(|id| NodeGene::new(id, NodeType::Input)));
+    genome.nodes.extend((n_in..bias_id).map(|id| NodeGene::new(id, NodeType::Output)));
+    genome.nodes.push(NodeGene::new(bias_id, NodeType::Bias));
+    let mut prev: Vec<usize> = (0..n_in).collect();
+    let mut next_hidden = bias_id + 1;
+    for (k, (dense, acts)) in layers.iter().enumerate() {
+        let ids: Vec<usize> = if k + 1 == layers.len() {
+            (n_in..bias_id).collect()
+        } else {
+            let ids: Vec<usize> = (next_hidden..next_hidden + dense.out_dim).collect();
+            next_hidden += dense.out_dim;
+            genome.nodes.extend(ids.iter().map(|&id| NodeGene::new(id, NodeType::Hidden)));
+            ids
+        };
+        for (o, (&id, &act)) in ids.iter().zip(acts).enumerate() {
+            genome.nodes.iter_mut().find(|n| n.id == id).unwrap().activation = act;
+            let sources = prev.iter().enumerate().map(|(i, &src)| (src, dense.weights[o * dense.in_dim + i]));
+            for (src, weight) in sources.chain(std::iter::once((bias_id, dense.biases[o]))) {
+                if weight != 0.0 {
+                    let innovation = genome.conns.len();
+                    genome.conns.push(ConnGene { in_node: src, out_node: id, weight, enabled: true, innovation });
+                }
+            }
+        }
+        prev = ids;
+    }
+    genome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neat::config::EvolutionConfig;
+    use crate::neat::innovation::InnovationTracker;
+    use crate::neat::onnx_exporter::export_genome;
+    use crate::onnx_generated::onnx::{AttributeProto, GraphProto, ValueInfoProto};
+    use rand::Rng;
+
+    #[test]
+    fn round_trips_exported_genomes() {
+        let evo_cfg = EvolutionConfig { mutation_add_node_rate: 1.0, mutation_add_conn_rate: 1.0, ..Default::default() };
+        let mut rng = rand::thread_rng();
+        let mut genome = Genome::new();
+        genome.initialize(&crate::config::Config::default(), &evo_cfg, &mut rng);
+        let mut tracker = InnovationTracker::new();
+        tracker.observe(&genome);
+        for _ in 0..4 {
+            genome.mutate(&evo_cfg, &mut tracker, &mut rng);
+        }
+        genome.nodes.iter_mut().filter(|n| n.node_type == NodeType::Output)
+            .take(1).for_each(|n| n.activation = Activation::Gaussian);
+        let imported = genome_from_onnx(&export_genome(&genome).unwrap()).unwrap();
+        assert_eq!(imported.input_size(), genome.input_size());
+        assert_eq!(imported.output_size(), genome.output_size());
+        for _ in 0..5 {
+            let x: Vec<f32> = (0..genome.input_size()).map(|_| rng.gen_range(-1.0..1.0)).collect();
+            let (a, b) = (genome.feed_forward(&x), imported.feed_forward(&x));
+            for (a, b) in a.iter().zip(&b) {
+                assert!((a - b).abs() < 1e-5, "{} vs {}", a, b);
+            }
+        }
+    }
+
+    fn init(name: &str, dims: Vec<i64>, data: Vec<f32>) -> TensorProto {
+        TensorProto { name: Some(name.into()), dims, data_type: Some(DataType::Float as i32), float_data: data, ..Default::default() }
+    }
+
+    fn node(op: &str, input: &[&str], output: &str, attribute: Vec<AttributeProto>) -> NodeProto {
+        NodeProto {
+            op_type: Some(op.into()),
+            input: input.iter().map(|s| s.to_string()).collect(),
+            output: vec![output.into()],
+            attribute,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn imports_pytorch_style_gemm() {
+        let trans_b = AttributeProto { name: Some("transB".into()), i: Some(1), ..Default::default() };
+        let io = |name: &str| ValueInfoProto { name: Some(name.into()), ..Default::default() };
+        // Linear(2, 2) → Relu → Linear(2, 1) → Tanh, weights stored [out, in]
+        let graph = GraphProto {
+            input: vec![io("x")],
+            output: vec![io("y")],
+            initializer: vec![
+                init("w1", vec![2, 2], vec![1.0, -1.0, 0.5, 2.0]),
+                init("b1", vec![2], vec![0.1, -0.2]),
+                init("w2", vec![1, 2], vec![1.5, -0.5]),
+                init("b2", vec![1], vec![0.3]),
+            ],
+            node: vec![
+                node("Gemm", &["x", "w1", "b1"], "h", vec![trans_b.clone()]),
+                node("Relu", &["h"], "a", vec![]),
+                node("Gemm", &["a", "w2", "b2"], "z", vec![trans_b]),
+                node("Tanh", &["z"], "y", vec![]),
+            ],
+            ..Default::default()
+        };
+        let model = ModelProto { graph: Some(graph), ..Default::default() };
+        let genome = genome_from_onnx(&model.encode_to_vec()).unwrap();
+        let x = [0.4f32, -0.7];
+        let h = [(0.4 + 0.7 + 0.1f32).max(0.0), (0.2 - 1.4 - 0.2f32).max(0.0)];
+        let expected = (1.5 * h[0] - 0.5 * h[1] + 0.3).tanh();
+        assert!((genome.feed_forward(&x)[0] - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn rejects_unsupported_ops() {
+        let io = |name: &str| ValueInfoProto { name: Some(name.into()), ..Default::default() };
+        let graph = GraphProto {
+            input: vec![io("x")],
+            output: vec![io("y")],
+            node: vec![node("Conv", &["x"], "y", vec![])],
+            ..Default::default()
+        };
+        let model = ModelProto { graph: Some(graph), ..Default::default() };
+        assert!(matches!(genome_from_onnx(&model.encode_to_vec()), Err(ImportError::Unsupported(_))));
+        assert!(matches!(genome_from_onnx(b"not onnx"), Err(ImportError::Decode(_))));
+    }
+}
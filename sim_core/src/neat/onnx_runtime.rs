@@ -0,0 +1,87 @@
+//! onnxruntime inference for `NeatBrain` (feature `onnxruntime`): the genome
+//! is exported once per brain and every forward pass runs in an ORT session,
+//! on the CUDA provider when requested and available.
+//!
+//! The runtime library is loaded when the first session is built, from the
+//! path in `ORT_DYLIB_PATH` (or the system library path).
+
+use std::fmt;
+use ort::execution_providers::CUDAExecutionProvider;
+use ort::session::Session;
+use ort::value::Tensor;
+use super::error::NeatError;
+use super::genome::Genome;
+use super::onnx_exporter::export_genome;
+
+#[derive(Debug)]
+pub enum OrtError {
+    /// The genome cannot be expressed as an ONNX graph (e.g. it is recurrent)
+    Export(NeatError),
+    /// Building the session or running it failed
+    Runtime(ort::Error),
+    /// The session returned an output of unexpected shape
+    Shape(String),
+}
+
+impl fmt::Display for OrtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrtError::Export(e) => write!(f, "genome not exportable to ONNX: {}", e),
+            OrtError::Runtime(e) => write!(f, "onnxruntime error: {}", e),
+            OrtError::Shape(what) => write!(f, "unexpected onnxruntime output: {}", what),
+        }
+    }
+}
+
+impl std::error::Error for OrtError {}
+
+impl From<NeatError> for OrtError {
+    fn from(e: NeatError) -> Self { OrtError::Export(e) }
+}
+
+impl From<ort::Error> for OrtError {
+    fn from(e: ort::Error) -> Self { OrtError::Runtime(e) }
+}
+
+/// An exported genome loaded into an onnxruntime session
+pub struct OrtModel {
+    session: Session,
+    input: String,
+    in_dim: usize,
+    out_dim: usize,
+}
+
+impl OrtModel {
+    /// Export `genome` and build a session for it
+    pub fn from_genome(genome: &Genome, gpu: bool) -> Result<Self, OrtError> {
+        let bytes = export_genome(genome)?;
+        let mut builder = Session::builder()?;
+        if gpu {
+            builder = builder.with_execution_providers([CUDAExecutionProvider::default().build()])?;
+        }
+        let session = builder.commit_from_memory(&bytes)?;
+        let input = session.inputs[0].name.clone();
+        Ok(OrtModel { session, input, in_dim: genome.input_size(), out_dim: genome.output_size() })
+    }
+
+    /// Forward pass over a batch of input rows
+    pub fn run(&mut self, batch: &[Vec<f32>]) -> Result<Vec<Vec<f32>>, OrtError> {
+        if batch.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut flat = Vec::with_capacity(batch.len() * self.in_dim);
+        for row in batch {
+            if row.len() != self.in_dim {
+                return Err(OrtError::Shape(format!("input row of {} values, model takes {}", row.len(), self.in_dim)));
+            }
+            flat.extend_from_slice(row);
+        }
+        let tensor = Tensor::from_array(([batch.len(), self.in_dim], flat))?;
+        let outputs = self.session.run(ort::inputs![self.input.as_str() => tensor])?;
+        let (_, data) = outputs[0].try_extract_tensor::<f32>()?;
+        if data.len() != batch.len() * self.out_dim {
+            return Err(OrtError::Shape(format!("{} values for {} rows of {}", data.len(), batch.len(), self.out_dim)));
+        }
+        Ok(data.chunks(self.out_dim).map(|c| c.to_vec()).collect())
+    }
+}
@@ -0,0 +1,124 @@
+//! Pure-Rust ONNX inference with tract (feature `tract`): runs exported
+//! models without onnxruntime or the Python service, in wasm32 builds too.
+
+use std::fmt;
+use std::io::Cursor;
+use tract_onnx::prelude::{tvec, Framework, InferenceModelExt, IntoTensor, TDim, TypedModel, TypedRunnableModel};
+use tract_onnx::tract_core::ndarray::Array2;
+use tract_onnx::tract_hir::internal::DimLike;
+use super::error::NeatError;
+use super::genome::Genome;
+use super::onnx_exporter::export_genome;
+
+#[derive(Debug)]
+pub enum TractError {
+    /// The genome cannot be expressed as an ONNX graph (e.g. it is recurrent)
+    Export(NeatError),
+    /// Loading, optimizing, or running the model failed
+    Runtime(tract_onnx::prelude::TractError),
+    /// Input or output of unexpected shape
+    Shape(String),
+}
+
+impl fmt::Display for TractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TractError::Export(e) => write!(f, "genome not exportable to ONNX: {}", e),
+            TractError::Runtime(e) => write!(f, "tract error: {}", e),
+            TractError::Shape(what) => write!(f, "unexpected shape: {}", what),
+        }
+    }
+}
+
+impl std::error::Error for TractError {}
+
+impl From<NeatError> for TractError {
+    fn from(e: NeatError) -> Self { TractError::Export(e) }
+}
+
+impl From<tract_onnx::prelude::TractError> for TractError {
+    fn from(e: tract_onnx::prelude::TractError) -> Self { TractError::Runtime(e) }
+}
+
+/// An ONNX model optimized into a runnable tract plan
+pub struct TractModel {
+    plan: TypedRunnableModel<TypedModel>,
+    in_dim: usize,
+    out_dim: usize,
+}
+
+impl TractModel {
+    /// Load a `[batch, inputs] -> [batch, outputs]` model from ONNX bytes
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, TractError> {
+        let model = tract_onnx::onnx().model_for_read(&mut Cursor::new(bytes))?.into_optimized()?;
+        let dim = |shape: &[TDim], what: &str| -> Result<usize, TractError> {
+            match shape {
+                [_, d] => d.to_usize().map_err(TractError::from),
+                _ => Err(TractError::Shape(format!("{} of rank {}, expected [batch, n]", what, shape.len()))),
+            }
+        };
+        let in_dim = dim(&model.input_fact(0)?.shape.to_tvec(), "input")?;
+        let out_dim = dim(&model.output_fact(0)?.shape.to_tvec(), "output")?;
+        Ok(TractModel { plan: model.into_runnable()?, in_dim, out_dim })
+    }
+
+    /// Export `genome` and load it
+    pub fn from_genome(genome: &Genome) -> Result<Self, TractError> {
+        TractModel::from_bytes(&export_genome(genome)?)
+    }
+
+    /// Forward pass over a batch of input rows
+    pub fn run(&self, batch: &[Vec<f32>]) -> Result<Vec<Vec<f32>>, TractError> {
+        if batch.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut flat = Vec::with_capacity(batch.len() * self.in_dim);
+        for row in batch {
+            if row.len() != self.in_dim {
+                return Err(TractError::Shape(format!("input row of {} values, model takes {}", row.len(), self.in_dim)));
+            }
+            flat.extend_from_slice(row);
+        }
+        let input = Array2::from_shape_vec((batch.len(), self.in_dim), flat)
+            .map_err(|e| TractError::Shape(e.to_string()))?;
+        let outputs = self.plan.run(tvec!(input.into_tensor().into()))?;
+        let data = outputs[0].as_slice::<f32>()?;
+        if data.len() != batch.len() * self.out_dim {
+            return Err(TractError::Shape(format!("{} values for {} rows of {}", data.len(), batch.len(), self.out_dim)));
+        }
+        Ok(data.chunks(self.out_dim).map(|c| c.to_vec()).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::neat::config::EvolutionConfig;
+    use crate::neat::innovation::InnovationTracker;
+
+    #[test]
+    fn matches_feed_forward() {
+        let mut rng = rand::thread_rng();
+        let evo_cfg = EvolutionConfig { mutation_add_node_rate: 1.0, mutation_add_conn_rate: 1.0, ..Default::default() };
+        let mut genome = Genome::new();
+        genome.initialize(&Config::default(), &evo_cfg, &mut rng);
+        let mut tracker = InnovationTracker::new();
+        tracker.observe(&genome);
+        for _ in 0..4 {
+            genome.mutate(&evo_cfg, &mut tracker, &mut rng);
+        }
+        let model = TractModel::from_genome(&genome).unwrap();
+        let batch: Vec<Vec<f32>> = (0..4)
+            .map(|k| (0..genome.input_size()).map(|i| ((i + k) as f32 * 0.37).sin()).collect())
+            .collect();
+        let rows = model.run(&batch).unwrap();
+        assert_eq!(rows.len(), batch.len());
+        for (x, row) in batch.iter().zip(&rows) {
+            for (e, a) in genome.feed_forward(x).iter().zip(row) {
+                assert!((e - a).abs() < 1e-5, "{} != {}", e, a);
+            }
+        }
+        assert!(matches!(model.run(&[vec![0.0]]), Err(TractError::Shape(_))));
+    }
+}
@@ -1,19 +1,98 @@
+use std::fmt;
+use std::path::Path;
 use crate::config::Config;
 use crate::brain::Brain;
-use super::config::EvolutionConfig;
-use super::genome::Genome;
-use super::runner::run_match;
+use super::config::{EvolutionConfig, Scenario, SelectionStrategy};
+use super::curriculum::Curriculum;
+use super::genome::{Genome, GenomeError};
+use super::hyperneat;
+use super::innovation::InnovationTracker;
+use super::lineage::Genealogy;
+use super::metrics::BehaviorMetrics;
+use super::novelty::NoveltyArchive;
+use super::nsga2;
+use super::runner::MatchStats;
+use super::schema::{migrate_genome, SchemaError};
+use super::species::{self, Species};
+use super::runner::{run_lineups_parallel, run_matches_parallel, BrainFactory, Lineup};
 use super::brain::NeatBrain;
-use crate::ai::{NaiveAgent, NaiveBrain};
 use rand::seq::SliceRandom;
 use rand::prelude::IteratorRandom;
-use rand::thread_rng;
-use rayon::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Serialize, Deserialize};
+
+/// Brain factory playing `genome` under `sim_cfg`
+fn genome_brain<'a>(genome: &'a Genome, sim_cfg: &'a Config) -> impl Fn() -> Box<dyn Brain> + Sync + 'a {
+    move || Box::new(NeatBrain::for_config(genome.clone(), sim_cfg))
+}
+
+/// One seed per match, fixing its terrain and simulation RNG: `pinned` when
+/// the scenario sets one, otherwise fresh draws from the generation RNG
+fn match_seeds(count: usize, pinned: Option<u64>, rng: &mut StdRng) -> Vec<u64> {
+    (0..count).map(|_| pinned.unwrap_or_else(|| rng.gen())).collect()
+}
+
+/// Failure saving or loading a population checkpoint
+#[derive(Debug)]
+pub enum PopulationError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    /// A checkpointed genome is structurally broken
+    Invalid(GenomeError),
+    /// A checkpointed genome's layout cannot be migrated
+    Schema(SchemaError),
+}
+
+impl fmt::Display for PopulationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PopulationError::Io(e) => write!(f, "checkpoint I/O error: {}", e),
+            PopulationError::Json(e) => write!(f, "invalid checkpoint JSON: {}", e),
+            PopulationError::Invalid(e) => write!(f, "invalid checkpoint genome: {}", e),
+            PopulationError::Schema(e) => write!(f, "unsupported checkpoint genome: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for PopulationError {}
+
+impl From<std::io::Error> for PopulationError {
+    fn from(e: std::io::Error) -> Self { PopulationError::Io(e) }
+}
+
+impl From<serde_json::Error> for PopulationError {
+    fn from(e: serde_json::Error) -> Self { PopulationError::Json(e) }
+}
+
+impl From<GenomeError> for PopulationError {
+    fn from(e: GenomeError) -> Self { PopulationError::Invalid(e) }
+}
+
+impl From<SchemaError> for PopulationError {
+    fn from(e: SchemaError) -> Self { PopulationError::Schema(e) }
+}
 
 /// A population of genomes and a hall-of-fame
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Population {
     pub genomes: Vec<Genome>,
     pub hof: Vec<Genome>,
+    /// Innovation numbers shared by every genome's structural mutations
+    pub innovations: InnovationTracker,
+    /// Species of the current generation (member indices refer to `genomes`)
+    pub species: Vec<Species>,
+    next_species_id: usize,
+    /// Past novel behaviors (used by `Novelty`/`Hybrid` fitness)
+    pub novelty: NoveltyArchive,
+    /// Generations produced so far (advanced by `reproduce`)
+    #[serde(default)]
+    pub generation: usize,
+    /// Every genome of the run with its parents
+    #[serde(default)]
+    pub genealogy: Genealogy,
+    /// Training stages `evaluate` plays under, if any
+    #[serde(default)]
+    pub curriculum: Option<Curriculum>,
 }
 
 impl Population {
@@ -22,150 +101,481 @@ impl Population {
         let genomes = (0..evo_cfg.pop_size)
             .map(|_| Genome::new())
             .collect();
-        Population { genomes, hof: Vec::new() }
+        Population {
+            genomes,
+            hof: Vec::new(),
+            innovations: InnovationTracker::new(),
+            species: Vec::new(),
+            next_species_id: 0,
+            novelty: NoveltyArchive::new(evo_cfg.novelty_k, evo_cfg.novelty_threshold, evo_cfg.novelty_archive_size),
+            generation: 0,
+            genealogy: Genealogy::new(),
+            curriculum: None,
+        }
     }
 
-    /// Evaluate each genome's fitness by running matches
-    pub fn evaluate(&mut self, sim_cfg: &Config, evo_cfg: &EvolutionConfig) {
-        // Initialize genomes & reset fitness
+    /// Write a checkpoint (genomes, hall-of-fame, innovation and species
+    /// state, generation) as JSON
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), PopulationError> {
+        std::fs::write(path, serde_json::to_vec(self)?)?;
+        Ok(())
+    }
+
+    /// Read a checkpoint written by `save`; every genome is migrated to the
+    /// current layout and validated
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, PopulationError> {
+        let mut value: serde_json::Value = serde_json::from_slice(&std::fs::read(path)?)?;
+        for list in ["genomes", "hof"] {
+            if let Some(genomes) = value.get_mut(list).and_then(serde_json::Value::as_array_mut) {
+                for g in genomes {
+                    migrate_genome(g)?;
+                }
+            }
+        }
+        if let Some(species) = value.get_mut("species").and_then(serde_json::Value::as_array_mut) {
+            for s in species.iter_mut().filter_map(|s| s.get_mut("representative")) {
+                migrate_genome(s)?;
+            }
+        }
+        let pop: Population = serde_json::from_value(value)?;
+        for g in pop.genomes.iter().chain(&pop.hof) {
+            g.validate(true)?;
+        }
+        Ok(pop)
+    }
+
+    /// Build any uninitialized genomes and register every genome's genes
+    /// and genealogy record (done by `evaluate`)
+    pub fn prepare(&mut self, sim_cfg: &Config, evo_cfg: &EvolutionConfig) {
+        let mut rng = evo_cfg.rng(self.generation, 4);
         for genome in &mut self.genomes {
             if genome.nodes.is_empty() {
-                genome.initialize(sim_cfg, evo_cfg);
+                hyperneat::initialize(genome, sim_cfg, evo_cfg, &mut rng);
             }
+            self.innovations.observe(genome);
+            // founders and injected genomes enter the genealogy without parents
+            self.genealogy.register(genome, self.generation, &[]);
+        }
+    }
+
+    /// Evaluate each genome's fitness by running matches (under the current
+    /// curriculum stage, which may then be promoted)
+    #[tracing::instrument(skip_all, fields(generation = self.generation))]
+    pub fn evaluate(&mut self, sim_cfg: &Config, evo_cfg: &EvolutionConfig) {
+        let (mut sim_cfg, mut evo_cfg) = (sim_cfg.clone(), evo_cfg.clone());
+        if let Some(c) = &self.curriculum {
+            c.apply(&mut sim_cfg, &mut evo_cfg);
+        }
+        let (sim_cfg, evo_cfg) = (&sim_cfg, &evo_cfg);
+        let mut rng = evo_cfg.rng(self.generation, 0);
+        // Initialize genomes & reset fitness
+        self.prepare(sim_cfg, evo_cfg);
+        for genome in &mut self.genomes {
             genome.fitness = 0.0;
         }
-        // Snapshot for opponent sampling
-        let snapshot = self.genomes.clone();
-        // Team-based or 1v1 evaluation
-        let n = snapshot.len();
-        let mut fitness_acc = vec![0.0; n];
-        let mut counts = vec![0; n];
-        // Helper to build agents for two teams
-        let make_agents = |team_a: &[usize], team_b: &[usize]| -> Vec<(Box<dyn Brain>, u32)> {
-            let mut v = Vec::new();
-            for &i in team_a {
-                v.push((Box::new(NeatBrain::new(
-                    snapshot[i].clone(), sim_cfg.batch_size,
-                    sim_cfg.python_service_url.clone().unwrap_or_default(),
-                )) as Box<dyn Brain>, 0));
+        // Every genome plays each scenario of the suite (or the run's own
+        // settings once); its fitness combines the per-scenario means, the
+        // other stats average over all matches
+        let suite = if evo_cfg.scenarios.is_empty() { vec![Scenario::default()] } else { evo_cfg.scenarios.clone() };
+        let n = self.genomes.len();
+        let mut totals = vec![Tally::default(); n];
+        let mut fitnesses: Vec<Vec<f32>> = vec![Vec::new(); n];
+        for scenario in &suite {
+            let (sim_k, evo_k) = scenario.apply(sim_cfg, evo_cfg);
+            for (i, tally) in self.play_scenario(&sim_k, &evo_k, scenario.seed, &mut rng).into_iter().enumerate() {
+                if let Some(f) = tally.mean_fitness() {
+                    fitnesses[i].push(f);
+                }
+                totals[i].merge(tally);
             }
-            for &j in team_b {
-                v.push((Box::new(NeatBrain::new(
-                    snapshot[j].clone(), sim_cfg.batch_size,
-                    sim_cfg.python_service_url.clone().unwrap_or_default(),
-                )) as Box<dyn Brain>, 1));
+        }
+        for ((genome, tally), scores) in self.genomes.iter_mut().zip(totals).zip(&fitnesses) {
+            tally.apply(genome);
+            if !scores.is_empty() {
+                genome.fitness = evo_cfg.scenario_aggregate.combine(scores);
+            }
+        }
+        if evo_cfg.team_size == 1 {
+            // scripted baseline evaluation (NaiveAgent unless `baseline` names another)
+            let snapshot: Vec<Genome> = self.genomes.iter()
+                .map(|g| hyperneat::phenotype(g, sim_cfg, evo_cfg))
+                .collect();
+            let nets: Vec<_> = snapshot.iter().map(|g| genome_brain(g, sim_cfg)).collect();
+            let baseline = || evo_cfg.baseline_brain();
+            let sides = if evo_cfg.mirror_matches { 2 } else { 1 };
+            let lineups: Vec<Lineup> = nets.iter()
+                .map(|net| Lineup::new(vec![(net as BrainFactory, 0), (&baseline as BrainFactory, 1)]))
+                .flat_map(|l| if sides == 2 { vec![l.mirrored(), l] } else { vec![l] })
+                .collect();
+            let results = run_matches_parallel(sim_cfg, evo_cfg, &lineups, &[0]);
+            for (genome, pair) in self.genomes.iter_mut().zip(results.chunks(sides)) {
+                genome.fitness_naive = pair.iter().map(|s| evo_cfg.fitness_fn.compute(s, evo_cfg)).sum::<f32>() / sides as f32;
             }
-            v
+        }
+        self.rank(evo_cfg);
+        if let Some(c) = &mut self.curriculum {
+            c.update(self.generation, &self.genomes);
+        }
+    }
+
+    /// Tallies of every genome's matches in one scenario, drawn from `rng`
+    /// and played with simulation seed `match_seed`
+    fn play_scenario(&self, sim_cfg: &Config, evo_cfg: &EvolutionConfig, pinned_seed: Option<u64>, rng: &mut StdRng) -> Vec<Tally> {
+        // Networks the genomes express, snapshotted for opponent sampling
+        let snapshot: Vec<Genome> = self.genomes.iter()
+            .map(|g| hyperneat::phenotype(g, sim_cfg, evo_cfg))
+            .collect();
+        // Last generation's hall-of-fame, played as fixed opponents in a
+        // `hof_match_rate` fraction of matches; only the subject side is scored
+        let hof_nets: Vec<Genome> = self.hof.iter()
+            .map(|g| hyperneat::phenotype(g, sim_cfg, evo_cfg))
+            .collect();
+        let use_hof = |rng: &mut StdRng| {
+            !hof_nets.is_empty() && rng.gen_bool(evo_cfg.hof_match_rate.clamp(0.0, 1.0) as f64)
+        };
+        let nets: Vec<_> = snapshot.iter().map(|g| genome_brain(g, sim_cfg)).collect();
+        let hofs: Vec<_> = hof_nets.iter().map(|g| genome_brain(g, sim_cfg)).collect();
+        let n = snapshot.len();
+        // Every match is drawn up front (so sampling stays sequential and
+        // seeded), played in parallel, then tallied in order so float sums
+        // do not depend on thread scheduling. Each lineup's team 0 is scored;
+        // with `mirror_matches` it is also played from the other side.
+        let mut lineups: Vec<Lineup> = Vec::new();
+        let mut subjects: Vec<Vec<usize>> = Vec::new();
+        let members = &nets;
+        let team = move |ids: &[usize], team: u32| -> Vec<(BrainFactory, u32)> {
+            ids.iter().map(|&i| (&members[i] as BrainFactory, team)).collect()
         };
         if evo_cfg.team_size > 1 {
-            // Parallel multi-team match evaluation
+            // multi-team matches between random teams, each played from both sides
             let matches_per_gen = evo_cfg.pop_size * evo_cfg.tournament_k;
-            let (fitness_acc_res, counts_res) = (0..matches_per_gen)
-                .into_par_iter()
-                .map(|_| {
-                    let mut local_rng = thread_rng();
-                    let ids = (0..n).choose_multiple(&mut local_rng, evo_cfg.team_size * evo_cfg.num_teams);
-                    let (team_a, team_b) = ids.split_at(evo_cfg.team_size);
-                    let stats_a = run_match(sim_cfg, evo_cfg, make_agents(team_a, team_b));
-                    let fit_a = evo_cfg.fitness_fn.compute(&stats_a, evo_cfg) / (evo_cfg.team_size as f32);
-                    let stats_b = run_match(sim_cfg, evo_cfg, make_agents(team_b, team_a));
-                    let fit_b = evo_cfg.fitness_fn.compute(&stats_b, evo_cfg) / (evo_cfg.team_size as f32);
-                    let mut acc = vec![0.0; n];
-                    let mut cnt = vec![0; n];
-                    for &i in team_a { acc[i] += fit_a; cnt[i] += 1; }
-                    for &j in team_b { acc[j] += fit_b; cnt[j] += 1; }
-                    (acc, cnt)
-                })
-                .reduce(
-                    || (vec![0.0; n], vec![0; n]),
-                    |(mut acc1, mut cnt1), (acc2, cnt2)| {
-                        for idx in 0..n {
-                            acc1[idx] += acc2[idx];
-                            cnt1[idx] += cnt2[idx];
-                        }
-                        (acc1, cnt1)
+            let match_seeds: Vec<u64> = (0..matches_per_gen).map(|_| rng.gen()).collect();
+            for seed in match_seeds {
+                let mut local_rng = StdRng::seed_from_u64(seed);
+                if use_hof(&mut local_rng) {
+                    let team_a = (0..n).choose_multiple(&mut local_rng, evo_cfg.team_size);
+                    let mut lineup = team(&team_a, 0);
+                    for _ in 0..evo_cfg.team_size {
+                        lineup.push((hofs.choose(&mut local_rng).unwrap(), 1));
                     }
-                );
-            for i in 0..n {
-                if counts_res[i] > 0 {
-                    self.genomes[i].fitness = fitness_acc_res[i] / (counts_res[i] as f32);
+                    lineups.push(Lineup::new(lineup));
+                    subjects.push(team_a);
+                    continue;
                 }
+                let ids = (0..n).choose_multiple(&mut local_rng, evo_cfg.team_size * evo_cfg.num_teams);
+                let (team_a, team_b) = ids.split_at(evo_cfg.team_size);
+                lineups.push(Lineup::new([team(team_a, 0), team(team_b, 1)].concat()));
+                subjects.push(team_a.to_vec());
+                lineups.push(Lineup::new([team(team_b, 0), team(team_a, 1)].concat()));
+                subjects.push(team_b.to_vec());
             }
         } else {
-            // fall back to 1v1 evaluate & naive baseline
-            // Round-robin evaluation using Rayon
-            self.genomes.par_iter_mut().enumerate().for_each(|(i, genome)| {
-                for j in 0..n {
-                    if i == j {
-                        continue;
-                    }
-                    let mut agents: Vec<(Box<dyn Brain>, u32)> = Vec::new();
-                    // subject agent
-                    agents.push((Box::new(NeatBrain::new(
-                        genome.clone(),
-                        sim_cfg.batch_size,
-                        sim_cfg.python_service_url.clone().unwrap_or_default(),
-                    )) as Box<dyn Brain>, 0));
-                    // opponent agent
-                    agents.push((Box::new(NeatBrain::new(
-                        snapshot[j].clone(),
-                        sim_cfg.batch_size,
-                        sim_cfg.python_service_url.clone().unwrap_or_default(),
-                    )) as Box<dyn Brain>, 1));
-                    let stats = run_match(sim_cfg, evo_cfg, agents);
-                    let fit = evo_cfg.fitness_fn.compute(&stats, &evo_cfg);
-                    genome.fitness += fit;
+            // 1v1 round-robin, each opponent swapped for a hall-of-famer at `hof_match_rate`
+            let seeds: Vec<u64> = (0..n).map(|_| rng.gen()).collect();
+            for (i, seed) in seeds.into_iter().enumerate() {
+                let mut rng = StdRng::seed_from_u64(seed);
+                for j in (0..n).filter(|&j| j != i) {
+                    let opponent = if use_hof(&mut rng) { hofs.choose(&mut rng).unwrap() } else { &nets[j] };
+                    lineups.push(Lineup::new(vec![(&nets[i], 0), (opponent, 1)]));
+                    subjects.push(vec![i]);
                 }
-                // normalize fitness
-                genome.fitness /= (n - 1) as f32;
-            });
-            // NaiveAgent baseline evaluation
-            for genome in &mut self.genomes {
-                let naive = NaiveBrain(NaiveAgent::new(1.2, 0.8));
-                let mut agents: Vec<(Box<dyn Brain>, u32)> = Vec::new();
-                // subject
-                agents.push((Box::new(NeatBrain::new(
-                    genome.clone(),
-                    sim_cfg.batch_size,
-                    sim_cfg.python_service_url.clone().unwrap_or_default(),
-                )) as Box<dyn Brain>, 0));
-                // naive opponent
-                agents.push((Box::new(naive) as Box<dyn Brain>, 1));
-                let stats = run_match(sim_cfg, evo_cfg, agents);
-                genome.fitness_naive = evo_cfg.fitness_fn.compute(&stats, &evo_cfg);
+            }
+        }
+        let mut seeds = match_seeds(lineups.len(), pinned_seed, rng);
+        if evo_cfg.mirror_matches {
+            // both sides of a pairing play the same terrain
+            lineups = lineups.iter().flat_map(|l| [l.clone(), l.mirrored()]).collect();
+            subjects = subjects.into_iter().flat_map(|ids| [ids.clone(), ids]).collect();
+            seeds = seeds.into_iter().flat_map(|s| [s, s]).collect();
+        }
+        let results = run_lineups_parallel(sim_cfg, evo_cfg, &lineups, &seeds);
+        let mut tallies = vec![Tally::default(); n];
+        for (stats, ids) in results.iter().zip(&subjects) {
+            let fit = evo_cfg.fitness_fn.compute(stats, evo_cfg) / ids.len() as f32;
+            for &i in ids {
+                tallies[i].add(fit, stats, evo_cfg);
+            }
+        }
+        tallies
+    }
+
+    /// Once every genome has its match fitness: add novelty, apply Pareto
+    /// ranking, sort best-first, and refresh the hall-of-fame and species
+    pub fn rank(&mut self, evo_cfg: &EvolutionConfig) {
+        // novelty relative to the rest of the population and the archive
+        if evo_cfg.fitness_fn.uses_novelty() {
+            let behaviors: Vec<Vec<f32>> = self.genomes.iter().map(|g| g.behavior.clone()).collect();
+            let scores = self.novelty.score_population(&behaviors);
+            for (g, nov) in self.genomes.iter_mut().zip(scores) {
+                g.fitness += evo_cfg.novelty_weight * nov;
+            }
+        }
+        // Pareto ranking replaces the weighted sum
+        if evo_cfg.selection == SelectionStrategy::Nsga2 {
+            let points: Vec<Vec<f32>> = self.genomes.iter().map(|g| g.objectives.clone()).collect();
+            for (g, score) in self.genomes.iter_mut().zip(nsga2::crowded_scores(&points)) {
+                g.fitness = score;
+            }
+        }
+        for g in &self.genomes {
+            if let Some(id) = g.id {
+                self.genealogy.set_fitness(id, g.fitness);
             }
         }
         // update hall-of-fame
-        self.genomes.sort_by(|a, b| b.fitness.partial_cmp(&a.fitness).unwrap());
+        self.genomes.sort_by(|a, b| b.fitness.total_cmp(&a.fitness));
         self.hof = self.genomes.iter().take(evo_cfg.hof_size).cloned().collect();
+        self.speciate(evo_cfg);
+        for s in &mut self.species {
+            s.update_stagnation(&self.genomes);
+        }
+    }
+
+    /// Assign the current genomes to species by compatibility distance
+    pub fn speciate(&mut self, evo_cfg: &EvolutionConfig) {
+        let mut rng = evo_cfg.rng(self.generation, 2);
+        species::speciate(&mut self.species, &mut self.next_species_id, &self.genomes, evo_cfg, &mut rng);
+    }
+
+    /// Per-species summary lines for the generation log: id, size, best and adjusted fitness, stagnation
+    pub fn species_summary(&self, evo_cfg: &EvolutionConfig) -> Vec<String> {
+        self.species.iter().map(|s| {
+            let best = s.members.iter().map(|&i| self.genomes[i].fitness).fold(f32::NEG_INFINITY, f32::max);
+            format!(
+                "  Species {}: size = {}, best = {:.2}, adjusted = {:.2}, stagnant = {}",
+                s.id, s.members.len(), best, s.adjusted_fitness(&self.genomes, 0.0, evo_cfg.fitness_sharing),
+                s.stagnant_gens,
+            )
+        }).collect()
     }
 
     /// Produce next generation via speciation, selection, crossover, and mutation
+    #[tracing::instrument(skip_all, fields(generation = self.generation))]
     pub fn reproduce(&mut self, evo_cfg: &EvolutionConfig) {
+        // genomes may have been injected or loaded since `evaluate`; register their genes
+        for g in &self.genomes {
+            self.innovations.observe(g);
+        }
+        // species must describe the current genomes (they may have been replaced since `evaluate`)
+        if self.species.iter().map(|s| s.members.len()).sum::<usize>() != self.genomes.len() {
+            self.speciate(evo_cfg);
+        }
+        // Stagnant species get no offspring; their quota goes to the rest
+        let champion = (0..self.genomes.len()).max_by(|&a, &b| self.genomes[a].fitness.total_cmp(&self.genomes[b].fitness));
+        species::cull_stagnant(&mut self.species, champion, evo_cfg.species_stagnation_limit);
         // Elitism: carry over top genomes from hall-of-fame
         let mut next_gen: Vec<Genome> = Vec::with_capacity(evo_cfg.pop_size);
         for g in &self.hof {
             next_gen.push(g.clone());
         }
-        let mut rng = thread_rng();
-        // Generate offspring until population is full
-        while next_gen.len() < evo_cfg.pop_size {
-            // Tournament selection for parents
-            let mut p1 = self.genomes.choose(&mut rng).unwrap();
-            for _ in 1..evo_cfg.tournament_k {
-                let cand = self.genomes.choose(&mut rng).unwrap();
-                if cand.fitness > p1.fitness { p1 = cand; }
-            }
-            let mut p2 = self.genomes.choose(&mut rng).unwrap();
-            for _ in 1..evo_cfg.tournament_k {
-                let cand = self.genomes.choose(&mut rng).unwrap();
-                if cand.fitness > p2.fitness { p2 = cand; }
+        let mut rng = evo_cfg.rng(self.generation, 1);
+        // Offspring quota per species, proportional to its (min-shifted) adjusted fitness
+        let min_fit = self.genomes.iter().map(|g| g.fitness).fold(f32::INFINITY, f32::min);
+        let scores: Vec<f32> = self.species.iter()
+            .map(|s| s.adjusted_fitness(&self.genomes, min_fit, evo_cfg.fitness_sharing))
+            .collect();
+        let quotas = species::allocate_offspring(&scores, evo_cfg.pop_size.saturating_sub(next_gen.len()));
+        for (s, &quota) in self.species.iter().zip(&quotas) {
+            // Tournament selection for parents within the species
+            let pick = |rng: &mut StdRng| {
+                let mut best = &self.genomes[*s.members.choose(rng).unwrap()];
+                for _ in 1..evo_cfg.tournament_k {
+                    let cand = &self.genomes[*s.members.choose(rng).unwrap()];
+                    if cand.fitness > best.fitness { best = cand; }
+                }
+                best
+            };
+            for _ in 0..quota {
+                let p1 = pick(&mut rng);
+                let p2 = pick(&mut rng);
+                // Crossover and mutate to produce child
+                let mut child = Genome::crossover(p1, p2, evo_cfg, &mut rng);
+                child.mutate(evo_cfg, &mut self.innovations, &mut rng);
+                self.genealogy.register(&mut child, self.generation + 1, &[p1, p2]);
+                debug_assert!(
+                    child.validate(evo_cfg.allow_recurrent).is_ok(),
+                    "invalid offspring: {}", child.validate(evo_cfg.allow_recurrent).unwrap_err(),
+                );
+                next_gen.push(child);
             }
-            // Crossover and mutate to produce child
-            let mut child = Genome::crossover(p1, p2, evo_cfg);
-            child.mutate(evo_cfg);
-            next_gen.push(child);
         }
         self.genomes = next_gen;
+        self.generation += 1;
+        // member indices no longer apply; `evaluate` re-speciates
+        for s in &mut self.species {
+            s.members.clear();
+        }
+    }
+}
+
+/// Per-genome sums over its evaluation matches
+#[derive(Clone, Default)]
+pub(super) struct Tally {
+    fitness: f32,
+    count: usize,
+    behavior: Vec<f32>,
+    objectives: Vec<f32>,
+    metrics: BehaviorMetrics,
+}
+
+/// Element-wise `acc += v`, growing `acc` as needed
+fn add_into(acc: &mut Vec<f32>, v: &[f32]) {
+    if acc.len() < v.len() {
+        acc.resize(v.len(), 0.0);
+    }
+    for (a, x) in acc.iter_mut().zip(v) {
+        *a += x;
+    }
+}
+
+impl Tally {
+    pub(super) fn add(&mut self, fitness: f32, stats: &MatchStats, evo_cfg: &EvolutionConfig) {
+        self.fitness += fitness;
+        self.count += 1;
+        add_into(&mut self.behavior, &stats.behavior);
+        add_into(&mut self.objectives, &nsga2::objectives(stats, evo_cfg));
+        self.metrics.add(&BehaviorMetrics::from_stats(stats));
+    }
+
+    /// Add another tally's sums to this one
+    pub(super) fn merge(&mut self, other: Tally) {
+        self.fitness += other.fitness;
+        self.count += other.count;
+        add_into(&mut self.behavior, &other.behavior);
+        add_into(&mut self.objectives, &other.objectives);
+        self.metrics.add(&other.metrics);
+    }
+
+    /// Mean match fitness, if any match was played
+    pub(super) fn mean_fitness(&self) -> Option<f32> {
+        (self.count > 0).then(|| self.fitness / self.count as f32)
+    }
+
+    /// Store match means on the genome (left untouched if it played no match)
+    pub(super) fn apply(self, genome: &mut Genome) {
+        if self.count == 0 {
+            return;
+        }
+        let n = self.count as f32;
+        genome.fitness = self.fitness / n;
+        genome.behavior = self.behavior.iter().map(|b| b / n).collect();
+        genome.objectives = self.objectives.iter().map(|o| o / n).collect();
+        genome.metrics = self.metrics.scaled(1.0 / n);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reproduce_fills_population_from_species() {
+        let evo_cfg = EvolutionConfig { pop_size: 12, hof_size: 2, ..Default::default() };
+        let mut pop = Population::new(&evo_cfg);
+        for (i, g) in pop.genomes.iter_mut().enumerate() {
+            g.initialize(&Config::default(), &evo_cfg, &mut rand::thread_rng());
+            g.fitness = i as f32;
+        }
+        pop.hof = pop.genomes[..2].to_vec();
+        pop.speciate(&evo_cfg);
+        assert!(!pop.species.is_empty());
+        assert_eq!(pop.species.iter().map(|s| s.members.len()).sum::<usize>(), 12);
+        pop.reproduce(&evo_cfg);
+        assert_eq!(pop.genomes.len(), 12);
+        assert_eq!(pop.generation, 1);
+    }
+
+    #[test]
+    fn scenario_suite_combines_per_scenario_fitness() {
+        use super::super::config::{Scenario, ScenarioAggregate};
+        let base = EvolutionConfig { pop_size: 3, team_size: 1, hof_size: 1, max_ticks: 5, ..Default::default() };
+        let suite = vec![
+            Scenario { map_width: Some(120), ..Default::default() },
+            Scenario { map_height: Some(90), seed: Some(7), ..Default::default() },
+        ];
+        let play = |scenarios: Vec<Scenario>, scenario_aggregate| {
+            let evo_cfg = EvolutionConfig { scenarios, scenario_aggregate, ..base.clone() };
+            let mut pop = Population::new(&evo_cfg);
+            for g in &mut pop.genomes {
+                g.initialize(&Config::default(), &evo_cfg, &mut StdRng::seed_from_u64(1));
+            }
+            pop.evaluate(&Config::default(), &evo_cfg);
+            pop.genomes.iter().map(|g| g.fitness).collect::<Vec<f32>>()
+        };
+        let mean = play(suite.clone(), ScenarioAggregate::Mean);
+        let worst = play(suite, ScenarioAggregate::Worst);
+        assert!(mean.iter().zip(&worst).all(|(m, w)| w <= &(m + 1e-4)));
+        assert_eq!(ScenarioAggregate::Worst.combine(&[2.0, -1.0]), -1.0);
+        assert_eq!(ScenarioAggregate::Mean.combine(&[2.0, -1.0]), 0.5);
+    }
+
+    #[test]
+    fn matches_in_a_generation_get_their_own_terrain() {
+        use crate::mapgen::MapGen;
+        use crate::Simulation;
+        let sim_cfg = Config { map_gen: MapGen { obstacle_clusters: 2, ..Default::default() }, ..Config::default() };
+        let seeds = match_seeds(2, None, &mut StdRng::seed_from_u64(3));
+        let obstacles = |seed: u64| {
+            let agents: Vec<(Box<dyn Brain>, u32)> = vec![(Box::new(crate::ai::NaiveBrain(crate::ai::NaiveAgent::new(1.2, 0.8))), 0)];
+            let mut sim = Simulation::with_brains(200, 200, sim_cfg.clone(), agents);
+            sim.generate_map(seed);
+            sim.wells_data()
+        };
+        assert_ne!(obstacles(seeds[0]), obstacles(seeds[1]));
+        assert_eq!(match_seeds(2, Some(5), &mut StdRng::seed_from_u64(3)), vec![5, 5]);
+    }
+
+    #[test]
+    fn hof_opponents_do_not_score_hof() {
+        let evo_cfg = EvolutionConfig {
+            pop_size: 3, team_size: 1, hof_size: 2, hof_match_rate: 1.0, max_ticks: 5,
+            ..Default::default()
+        };
+        let mut pop = Population::new(&evo_cfg);
+        let mut champ = Genome::new();
+        champ.initialize(&Config::default(), &evo_cfg, &mut rand::thread_rng());
+        champ.fitness = 123.0;
+        pop.hof = vec![champ.clone(), champ];
+        pop.evaluate(&Config::default(), &evo_cfg);
+        // the new hall-of-fame comes from this generation's genomes only
+        assert!(pop.genomes.iter().chain(&pop.hof).all(|g| g.fitness != 123.0));
+    }
+
+    #[test]
+    fn seeded_runs_are_reproducible() {
+        let evo_cfg = EvolutionConfig {
+            pop_size: 3, team_size: 1, hof_size: 1, max_ticks: 5, seed: Some(7),
+            ..Default::default()
+        };
+        let run = || {
+            let mut pop = Population::new(&evo_cfg);
+            for _ in 0..2 {
+                pop.evaluate(&Config::default(), &evo_cfg);
+                pop.reproduce(&evo_cfg);
+            }
+            serde_json::to_string(&pop.genomes).unwrap()
+        };
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    fn checkpoint_round_trip() {
+        let evo_cfg = EvolutionConfig { pop_size: 6, hof_size: 2, ..Default::default() };
+        let mut pop = Population::new(&evo_cfg);
+        for (i, g) in pop.genomes.iter_mut().enumerate() {
+            g.initialize(&Config::default(), &evo_cfg, &mut rand::thread_rng());
+            g.fitness = i as f32;
+        }
+        pop.hof = pop.genomes[..2].to_vec();
+        pop.reproduce(&evo_cfg);
+        let path = std::env::temp_dir().join(format!("population_checkpoint_{}.json", std::process::id()));
+        pop.save(&path).unwrap();
+        let back = Population::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(back.generation, 1);
+        assert_eq!(back.genomes.len(), pop.genomes.len());
+        assert_eq!(back.hof.len(), 2);
+        assert_eq!(back.species.len(), pop.species.len());
+        assert_eq!(back.innovations.next_innovation(), pop.innovations.next_innovation());
     }
 }
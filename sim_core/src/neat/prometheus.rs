@@ -0,0 +1,186 @@
+//! Prometheus metrics for long unattended runs: latency histograms fed from
+//! the inference, physics, and match hot paths plus per-generation progress,
+//! rendered in the text exposition format on `/metrics`
+//! (`neat_train train --metrics-addr`, and always on `neat_train serve`).
+//!
+//! Nothing is recorded until `enable` is called, so runs without an endpoint
+//! pay only a relaxed load per observation.
+
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::Instant;
+use axum::http::header;
+use axum::routing::get;
+use axum::Router;
+
+/// Histogram upper bounds, in seconds
+const BUCKETS: [f64; 14] = [1e-6, 5e-6, 1e-5, 5e-5, 1e-4, 5e-4, 1e-3, 5e-3, 1e-2, 5e-2, 0.1, 0.5, 1.0, 5.0];
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static STARTED: OnceLock<Instant> = OnceLock::new();
+
+/// One network forward pass (local brains and `serve` rows)
+pub static INFER_SECONDS: Histogram = Histogram::new();
+/// One simulation step
+pub static TICK_SECONDS: Histogram = Histogram::new();
+/// One whole match
+pub static MATCH_SECONDS: Histogram = Histogram::new();
+/// One training generation, evaluation through reproduction
+pub static GENERATION_SECONDS: Histogram = Histogram::new();
+/// Latest generation finished
+pub static GENERATION: Gauge = Gauge::new();
+/// Best fitness of the latest generation
+pub static BEST_FITNESS: Gauge = Gauge::new();
+
+/// Start recording
+pub fn enable() {
+    STARTED.get_or_init(Instant::now);
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Latency distribution with fixed `BUCKETS`
+pub struct Histogram {
+    /// Observations per bucket (not cumulative; the last slot is above every bound)
+    buckets: [AtomicU64; BUCKETS.len() + 1],
+    count: AtomicU64,
+    sum_ns: AtomicU64,
+}
+
+impl Histogram {
+    pub const fn new() -> Self {
+        Histogram {
+            buckets: [const { AtomicU64::new(0) }; BUCKETS.len() + 1],
+            count: AtomicU64::new(0),
+            sum_ns: AtomicU64::new(0),
+        }
+    }
+
+    pub fn observe_ns(&self, ns: u64) {
+        if !enabled() {
+            return;
+        }
+        let secs = ns as f64 * 1e-9;
+        let i = BUCKETS.iter().position(|&b| secs <= b).unwrap_or(BUCKETS.len());
+        self.buckets[i].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_ns.fetch_add(ns, Ordering::Relaxed);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    fn render(&self, name: &str, help: &str, out: &mut String) {
+        let _ = writeln!(out, "# HELP {} {}\n# TYPE {} histogram", name, help, name);
+        let mut cumulative = 0;
+        for (bound, bucket) in BUCKETS.iter().zip(&self.buckets) {
+            cumulative += bucket.load(Ordering::Relaxed);
+            let _ = writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, bound, cumulative);
+        }
+        let count = self.count();
+        let _ = writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", name, count);
+        let _ = writeln!(out, "{}_sum {}", name, self.sum_ns.load(Ordering::Relaxed) as f64 * 1e-9);
+        let _ = writeln!(out, "{}_count {}", name, count);
+    }
+}
+
+impl Default for Histogram {
+    fn default() -> Self { Histogram::new() }
+}
+
+/// A value that can go up and down (stored as `f64` bits)
+pub struct Gauge(AtomicU64);
+
+impl Gauge {
+    pub const fn new() -> Self {
+        Gauge(AtomicU64::new(0))
+    }
+
+    pub fn set(&self, value: f64) {
+        self.0.store(value.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> f64 {
+        f64::from_bits(self.0.load(Ordering::Relaxed))
+    }
+}
+
+impl Default for Gauge {
+    fn default() -> Self { Gauge::new() }
+}
+
+fn gauge(name: &str, help: &str, value: f64, out: &mut String) {
+    let _ = writeln!(out, "# HELP {} {}\n# TYPE {} gauge\n{} {}", name, help, name, name, value);
+}
+
+/// Every metric in the text exposition format
+pub fn render() -> String {
+    let mut out = String::new();
+    let uptime = STARTED.get().map_or(0.0, |t| t.elapsed().as_secs_f64());
+    let per_sec = |n: u64| if uptime > 0.0 { n as f64 / uptime } else { 0.0 };
+    gauge("neat_uptime_seconds", "Seconds since metrics recording started", uptime, &mut out);
+    gauge("neat_generation", "Latest training generation finished", GENERATION.get(), &mut out);
+    gauge("neat_best_fitness", "Best fitness of the latest generation", BEST_FITNESS.get(), &mut out);
+    gauge("neat_generations_per_second", "Generations finished per second of uptime", per_sec(GENERATION_SECONDS.count()), &mut out);
+    gauge("neat_matches_per_second", "Matches played per second of uptime", per_sec(MATCH_SECONDS.count()), &mut out);
+    GENERATION_SECONDS.render("neat_generation_duration_seconds", "Time per training generation", &mut out);
+    MATCH_SECONDS.render("neat_match_duration_seconds", "Time per match", &mut out);
+    TICK_SECONDS.render("neat_physics_tick_seconds", "Time per simulation step", &mut out);
+    INFER_SECONDS.render("neat_infer_duration_seconds", "Time per network forward pass", &mut out);
+    out
+}
+
+/// `/metrics` route
+pub fn router() -> Router {
+    Router::new().route("/metrics", get(|| async {
+        ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], render())
+    }))
+}
+
+/// Enable recording and serve `/metrics` on `addr` from a background thread
+pub fn spawn(addr: SocketAddr) -> std::io::Result<()> {
+    let listener = std::net::TcpListener::bind(addr)?;
+    listener.set_nonblocking(true)?;
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+    let listener = {
+        let _guard = runtime.enter();
+        tokio::net::TcpListener::from_std(listener)?
+    };
+    enable();
+    std::thread::spawn(move || {
+        runtime.block_on(async move {
+            let _ = axum::serve(listener, router()).await;
+        });
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_buckets_are_cumulative() {
+        enable();
+        let h = Histogram::new();
+        for ns in [500, 2_000, 2_000_000, 10_000_000_000] {
+            h.observe_ns(ns);
+        }
+        let mut out = String::new();
+        h.render("x_seconds", "test", &mut out);
+        assert!(out.contains("# TYPE x_seconds histogram"));
+        assert!(out.contains("x_seconds_bucket{le=\"0.000001\"} 1\n"));
+        assert!(out.contains("x_seconds_bucket{le=\"0.000005\"} 2\n"));
+        assert!(out.contains("x_seconds_bucket{le=\"0.005\"} 3\n"));
+        assert!(out.contains("x_seconds_bucket{le=\"5\"} 3\n"));
+        assert!(out.contains("x_seconds_bucket{le=\"+Inf\"} 4\n"));
+        assert!(out.contains("x_seconds_count 4\n"));
+        assert!(render().contains("# TYPE neat_matches_per_second gauge"));
+    }
+}
@@ -0,0 +1,254 @@
+//! Tournament results for sharing: per-champion aggregate stats, the
+//! head-to-head score matrix, and CSV, Markdown, and HTML renderings of both.
+
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use super::eval::HeadToHead;
+use super::tournament::Outcome;
+
+/// One participant's totals over every game it played
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entrant {
+    pub name: String,
+    pub elo: f32,
+    /// 95% interval of the rating
+    pub elo_low: f32,
+    pub elo_high: f32,
+    pub games: usize,
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+    /// Health removed from the opponents, per game
+    pub damage_dealt: f32,
+    /// Health lost to the opponents, per game
+    pub damage_taken: f32,
+}
+
+impl Entrant {
+    /// Share of the points: 1 per win and 0.5 per draw
+    pub fn score(&self) -> f32 {
+        (self.wins as f32 + 0.5 * self.draws as f32) / self.games.max(1) as f32
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    /// Best rating first
+    pub entrants: Vec<Entrant>,
+    /// Row entrant's score against the column entrant over all their games
+    /// (None if they never met), both in `entrants` order
+    pub matrix: Vec<Vec<Option<f32>>>,
+}
+
+impl Report {
+    /// Gather the results of a tournament over `names`, rated `ratings`
+    /// with `intervals` (all indexed by player)
+    pub fn new(names: &[String], outcomes: &[Outcome<HeadToHead>], ratings: &[f32], intervals: &[(f32, f32)]) -> Self {
+        let n = names.len();
+        let mut entrants: Vec<Entrant> = (0..n).map(|p| Entrant {
+            name: names[p].clone(),
+            elo: ratings[p],
+            elo_low: intervals[p].0,
+            elo_high: intervals[p].1,
+            games: 0, wins: 0, draws: 0, losses: 0,
+            damage_dealt: 0.0,
+            damage_taken: 0.0,
+        }).collect();
+        // points and games of row against column
+        let mut points = vec![vec![0.0f32; n]; n];
+        let mut games = vec![vec![0usize; n]; n];
+        for o in outcomes {
+            let (wins, draws, losses) = o.result.record();
+            let played = o.result.games.len();
+            let dealt = o.result.games.iter().fold(0.0, |acc, g| acc + g.a_damage);
+            let taken = o.result.games.iter().fold(0.0, |acc, g| acc + g.b_damage);
+            for (p, w, l, d_out, d_in) in [(o.a, wins, losses, dealt, taken), (o.b, losses, wins, taken, dealt)] {
+                let e = &mut entrants[p];
+                e.games += played;
+                e.wins += w;
+                e.draws += draws;
+                e.losses += l;
+                e.damage_dealt += d_out;
+                e.damage_taken += d_in;
+            }
+            points[o.a][o.b] += wins as f32 + 0.5 * draws as f32;
+            points[o.b][o.a] += losses as f32 + 0.5 * draws as f32;
+            games[o.a][o.b] += played;
+            games[o.b][o.a] += played;
+        }
+        for e in &mut entrants {
+            e.damage_dealt /= e.games.max(1) as f32;
+            e.damage_taken /= e.games.max(1) as f32;
+        }
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by(|&x, &y| entrants[y].elo.total_cmp(&entrants[x].elo));
+        let matrix = order.iter().map(|&r| {
+            order.iter().map(|&c| (games[r][c] > 0).then(|| points[r][c] / games[r][c] as f32)).collect()
+        }).collect();
+        let entrants = order.into_iter().map(|p| entrants[p].clone()).collect();
+        Report { entrants, matrix }
+    }
+
+    /// Aggregate stats, one row per entrant in rank order
+    pub fn write_stats_csv<W: Write>(&self, mut out: W) -> io::Result<()> {
+        writeln!(out, "rank,name,elo,elo_low,elo_high,games,wins,draws,losses,score,damage_dealt,damage_taken")?;
+        for (i, e) in self.entrants.iter().enumerate() {
+            writeln!(
+                out, "{},{},{:.1},{:.1},{:.1},{},{},{},{},{:.3},{:.2},{:.2}",
+                i + 1, e.name, e.elo, e.elo_low, e.elo_high, e.games, e.wins, e.draws, e.losses,
+                e.score(), e.damage_dealt, e.damage_taken,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// The score matrix with entrant names as row and column headers (empty cells never met)
+    pub fn write_matrix_csv<W: Write>(&self, mut out: W) -> io::Result<()> {
+        let names: Vec<&str> = self.entrants.iter().map(|e| e.name.as_str()).collect();
+        writeln!(out, ",{}", names.join(","))?;
+        for (name, row) in names.iter().zip(&self.matrix) {
+            let cells: Vec<String> = row.iter().map(|c| c.map_or(String::new(), |s| format!("{:.3}", s))).collect();
+            writeln!(out, "{},{}", name, cells.join(","))?;
+        }
+        Ok(())
+    }
+
+    /// Fixed-width score matrix with entrants labelled by rank (`.` never met)
+    pub fn matrix_table(&self) -> String {
+        let mut text = format!("{:>5}", "");
+        for i in 0..self.entrants.len() {
+            let _ = write!(text, " {:>5}", format!("#{}", i + 1));
+        }
+        for (i, row) in self.matrix.iter().enumerate() {
+            let _ = write!(text, "\n{:>5}", format!("#{}", i + 1));
+            for (j, cell) in row.iter().enumerate() {
+                let _ = match cell {
+                    _ if i == j => write!(text, " {:>5}", "-"),
+                    Some(s) => write!(text, " {:>5.2}", s),
+                    None => write!(text, " {:>5}", "."),
+                };
+            }
+        }
+        text
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::from("# Tournament report\n\n## Standings\n\n");
+        md.push_str("| # | Champion | Elo | 95% CI | Games | W-D-L | Score | Dmg dealt | Dmg taken |\n");
+        md.push_str("|---|---|---|---|---|---|---|---|---|\n");
+        for (i, e) in self.entrants.iter().enumerate() {
+            let _ = writeln!(
+                md, "| {} | {} | {:.1} | {:.1} – {:.1} | {} | {}-{}-{} | {:.3} | {:.1} | {:.1} |",
+                i + 1, e.name, e.elo, e.elo_low, e.elo_high, e.games, e.wins, e.draws, e.losses,
+                e.score(), e.damage_dealt, e.damage_taken,
+            );
+        }
+        md.push_str("\n## Head-to-head\n\nRow champion's score against the column champion (blank: never met).\n\n|   |");
+        for i in 0..self.entrants.len() {
+            let _ = write!(md, " #{} |", i + 1);
+        }
+        md.push_str("\n|---|");
+        md.push_str(&"---|".repeat(self.entrants.len()));
+        for (i, row) in self.matrix.iter().enumerate() {
+            let _ = write!(md, "\n| **#{}** |", i + 1);
+            for (j, cell) in row.iter().enumerate() {
+                let _ = match cell {
+                    _ if i == j => write!(md, " – |"),
+                    Some(s) => write!(md, " {:.2} |", s),
+                    None => write!(md, " |"),
+                };
+            }
+        }
+        md.push('\n');
+        md
+    }
+
+    /// Standalone page with the standings and a colour-coded matrix
+    pub fn to_html(&self) -> String {
+        let mut html = String::from(
+            "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>Tournament report</title>\n\
+             <style>body{font-family:sans-serif}table{border-collapse:collapse;margin-bottom:2em}\
+             td,th{border:1px solid #ccc;padding:2px 6px;text-align:right}td.name{text-align:left}</style>\n\
+             </head><body>\n<h1>Tournament report</h1>\n<h2>Standings</h2>\n<table>\n\
+             <tr><th>#</th><th>Champion</th><th>Elo</th><th>95% CI</th><th>Games</th><th>W-D-L</th>\
+             <th>Score</th><th>Dmg dealt</th><th>Dmg taken</th></tr>\n",
+        );
+        for (i, e) in self.entrants.iter().enumerate() {
+            let _ = writeln!(
+                html, "<tr><td>{}</td><td class=\"name\">{}</td><td>{:.1}</td><td>{:.1} – {:.1}</td><td>{}</td>\
+                       <td>{}-{}-{}</td><td>{:.3}</td><td>{:.1}</td><td>{:.1}</td></tr>",
+                i + 1, escape(&e.name), e.elo, e.elo_low, e.elo_high, e.games, e.wins, e.draws, e.losses,
+                e.score(), e.damage_dealt, e.damage_taken,
+            );
+        }
+        html.push_str("</table>\n<h2>Head-to-head</h2>\n<p>Row champion's score against the column champion.</p>\n<table>\n<tr><th></th>");
+        for i in 0..self.entrants.len() {
+            let _ = write!(html, "<th>#{}</th>", i + 1);
+        }
+        html.push_str("</tr>\n");
+        for (i, row) in self.matrix.iter().enumerate() {
+            let _ = write!(html, "<tr><th title=\"{}\">#{}</th>", escape(&self.entrants[i].name), i + 1);
+            for (j, cell) in row.iter().enumerate() {
+                let _ = match cell {
+                    _ if i == j => write!(html, "<td>–</td>"),
+                    // red for a loss through to green for a win
+                    Some(s) => write!(html, "<td style=\"background:hsl({:.0},70%,80%)\">{:.2}</td>", s * 120.0, s),
+                    None => write!(html, "<td></td>"),
+                };
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</table>\n</body></html>\n");
+        html
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neat::config::TieBreak;
+    use crate::neat::eval::Game;
+    use crate::neat::runner::{MatchOutcome, Side};
+
+    fn side(health: f32) -> Side {
+        Side { health, damage_dealt: 100.0 - health, alive: (health > 0.0) as usize }
+    }
+
+    fn game(a_health: f32, b_health: f32) -> Game {
+        Game { seed: 0, a_first: true, a_health, b_health, a_damage: 100.0 - b_health, b_damage: 100.0 - a_health,
+            outcome: MatchOutcome::decide(side(a_health), side(b_health), TieBreak::Health, 1e-3) }
+    }
+
+    #[test]
+    fn aggregates_and_matrix_agree() {
+        let names: Vec<String> = ["a", "b", "c"].iter().map(|s| s.to_string()).collect();
+        let outcomes = vec![
+            Outcome { a: 0, b: 1, result: HeadToHead { games: vec![game(50.0, 0.0), game(20.0, 20.0)] } },
+            Outcome { a: 1, b: 2, result: HeadToHead { games: vec![game(0.0, 10.0), game(0.0, 30.0)] } },
+        ];
+        let report = Report::new(&names, &outcomes, &[1210.0, 1190.0, 1200.0], &[(1200.0, 1220.0); 3]);
+        let order: Vec<&str> = report.entrants.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(order, ["a", "c", "b"]);
+        let b = &report.entrants[2];
+        assert_eq!((b.games, b.wins, b.draws, b.losses), (4, 0, 1, 3));
+        assert!((b.score() - 0.125).abs() < 1e-6);
+        assert!((b.damage_taken - (100.0 + 80.0 + 100.0 + 100.0) / 4.0).abs() < 1e-4);
+        // a vs b: one win and one draw
+        assert_eq!(report.matrix[0][2], Some(0.75));
+        assert_eq!(report.matrix[2][0], Some(0.25));
+        assert_eq!(report.matrix[0][1], None);
+
+        let mut csv = Vec::new();
+        report.write_matrix_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(csv.lines().next(), Some(",a,c,b"));
+        assert_eq!(csv.lines().nth(1), Some("a,,,0.750"));
+        assert!(report.to_markdown().contains("| 3 | b | 1190.0"));
+        assert!(report.to_html().contains("hsl(90,70%,80%)\">0.75"));
+        assert_eq!(report.matrix_table().lines().count(), 4);
+    }
+}
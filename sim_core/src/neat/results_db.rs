@@ -0,0 +1,220 @@
+//! Persistent results store (feature `db`): every game played by `tournament`
+//! or `eval` is recorded in sqlite with its seed, configs, and outcome, and
+//! each player's Elo rating is carried over between runs, so a new champion
+//! can be rated against the existing field without replaying it.
+
+use std::fmt;
+use std::path::Path;
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::config::Config;
+use super::config::EvolutionConfig;
+use super::eval::HeadToHead;
+use super::tournament::ELO_START;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS configs (
+    id INTEGER PRIMARY KEY,
+    sim TEXT NOT NULL,
+    evolution TEXT NOT NULL,
+    UNIQUE (sim, evolution)
+);
+CREATE TABLE IF NOT EXISTS games (
+    id INTEGER PRIMARY KEY,
+    recorded_at TEXT NOT NULL,
+    source TEXT NOT NULL,
+    player_a TEXT NOT NULL,
+    player_b TEXT NOT NULL,
+    seed INTEGER NOT NULL,
+    a_first INTEGER NOT NULL,
+    a_health REAL NOT NULL,
+    b_health REAL NOT NULL,
+    a_damage REAL NOT NULL,
+    b_damage REAL NOT NULL,
+    result INTEGER NOT NULL,
+    config_id INTEGER NOT NULL REFERENCES configs (id)
+);
+CREATE INDEX IF NOT EXISTS games_players ON games (player_a, player_b);
+CREATE TABLE IF NOT EXISTS ratings (
+    player TEXT PRIMARY KEY,
+    elo REAL NOT NULL,
+    games INTEGER NOT NULL,
+    updated_at TEXT NOT NULL
+);
+";
+
+#[derive(Debug)]
+pub enum DbError {
+    Sqlite(rusqlite::Error),
+    /// A config could not be serialized for storage
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for DbError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DbError::Sqlite(e) => write!(f, "results db error: {}", e),
+            DbError::Json(e) => write!(f, "results db config error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for DbError {}
+
+impl From<rusqlite::Error> for DbError {
+    fn from(e: rusqlite::Error) -> Self { DbError::Sqlite(e) }
+}
+
+impl From<serde_json::Error> for DbError {
+    fn from(e: serde_json::Error) -> Self { DbError::Json(e) }
+}
+
+/// A player's stored rating
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rating {
+    pub player: String,
+    pub elo: f32,
+    /// Games recorded for the player
+    pub games: usize,
+}
+
+/// A played pairing to record: both players' names and their games
+pub type Pairing<'a> = (&'a str, &'a str, &'a HeadToHead);
+
+pub struct ResultsDb {
+    conn: Connection,
+}
+
+impl ResultsDb {
+    /// Open (creating if needed) the database at `path`
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, DbError> {
+        ResultsDb::init(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self, DbError> {
+        ResultsDb::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, DbError> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(ResultsDb { conn })
+    }
+
+    /// Store every game of `pairings`, played under `sim_cfg`/`evo_cfg` by
+    /// `source` (e.g. "tournament"), and update both players' ratings after
+    /// each pairing by its aggregate score with factor `k`, starting from the
+    /// stored ratings. All or nothing: one transaction per call.
+    pub fn record<'a>(
+        &mut self,
+        source: &str,
+        sim_cfg: &Config,
+        evo_cfg: &EvolutionConfig,
+        k: f32,
+        pairings: impl IntoIterator<Item = Pairing<'a>>,
+    ) -> Result<(), DbError> {
+        let sim = serde_json::to_string(sim_cfg)?;
+        let evolution = serde_json::to_string(evo_cfg)?;
+        let now = Utc::now().to_rfc3339();
+        let tx = self.conn.transaction()?;
+        tx.execute("INSERT OR IGNORE INTO configs (sim, evolution) VALUES (?1, ?2)", params![sim, evolution])?;
+        let config_id: i64 = tx.query_row(
+            "SELECT id FROM configs WHERE sim = ?1 AND evolution = ?2",
+            params![sim, evolution],
+            |row| row.get(0),
+        )?;
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO games (recorded_at, source, player_a, player_b, seed, a_first,
+                    a_health, b_health, a_damage, b_damage, result, config_id)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            )?;
+            let mut rating = tx.prepare("SELECT elo, games FROM ratings WHERE player = ?1")?;
+            let mut upsert = tx.prepare(
+                "INSERT INTO ratings (player, elo, games, updated_at) VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT (player) DO UPDATE SET elo = ?2, games = ?3, updated_at = ?4",
+            )?;
+            for (a, b, h2h) in pairings {
+                for g in &h2h.games {
+                    insert.execute(params![
+                        now, source, a, b, g.seed as i64, g.a_first,
+                        g.a_health, g.b_health, g.a_damage, g.b_damage, g.result(), config_id,
+                    ])?;
+                }
+                let mut stored = |p: &str| -> rusqlite::Result<(f32, i64)> {
+                    Ok(rating.query_row(params![p], |row| Ok((row.get(0)?, row.get(1)?))).optional()?
+                        .unwrap_or((ELO_START, 0)))
+                };
+                let ((elo_a, games_a), (elo_b, games_b)) = (stored(a)?, stored(b)?);
+                let expected = 1.0 / (1.0 + 10f32.powf((elo_b - elo_a) / 400.0));
+                let delta = k * (h2h.score() - expected);
+                let played = h2h.games.len() as i64;
+                upsert.execute(params![a, elo_a + delta, games_a + played, now])?;
+                upsert.execute(params![b, elo_b - delta, games_b + played, now])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// `player`'s stored rating, if they have played
+    pub fn rating(&self, player: &str) -> Result<Option<Rating>, DbError> {
+        Ok(self.conn.query_row(
+            "SELECT player, elo, games FROM ratings WHERE player = ?1",
+            params![player],
+            |row| Ok(Rating { player: row.get(0)?, elo: row.get(1)?, games: row.get::<_, i64>(2)? as usize }),
+        ).optional()?)
+    }
+
+    /// Every stored rating, best first
+    pub fn ratings(&self) -> Result<Vec<Rating>, DbError> {
+        let mut stmt = self.conn.prepare("SELECT player, elo, games FROM ratings ORDER BY elo DESC, player")?;
+        let rows = stmt.query_map([], |row| {
+            Ok(Rating { player: row.get(0)?, elo: row.get(1)?, games: row.get::<_, i64>(2)? as usize })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Games recorded so far
+    pub fn game_count(&self) -> Result<usize, DbError> {
+        let n: i64 = self.conn.query_row("SELECT COUNT(*) FROM games", [], |row| row.get(0))?;
+        Ok(n as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neat::eval::Game;
+    use crate::neat::runner::MatchOutcome;
+
+    fn series(a_wins: bool) -> HeadToHead {
+        let (a_health, b_health) = if a_wins { (50.0, 0.0) } else { (0.0, 50.0) };
+        let game = |seed, a_first| Game {
+            seed, a_first, a_health, b_health, a_damage: 100.0 - b_health, b_damage: 100.0 - a_health,
+            outcome: if a_wins { MatchOutcome::WinA { margin: 50.0 } } else { MatchOutcome::WinB { margin: -50.0 } },
+        };
+        HeadToHead { games: vec![game(u64::MAX, true), game(u64::MAX, false)] }
+    }
+
+    #[test]
+    fn ratings_carry_over_between_runs() {
+        let (sim_cfg, evo_cfg) = (Config::default(), EvolutionConfig::default());
+        let mut db = ResultsDb::open_in_memory().unwrap();
+        let (win, loss) = (series(true), series(false));
+        db.record("tournament", &sim_cfg, &evo_cfg, 32.0, [("a", "b", &win)]).unwrap();
+        let a = db.rating("a").unwrap().unwrap();
+        assert_eq!((a.elo, a.games), (ELO_START + 16.0, 2));
+        assert_eq!(db.rating("c").unwrap(), None);
+
+        // a later run starts from the stored ratings rather than from scratch
+        db.record("eval", &sim_cfg, &evo_cfg, 32.0, [("c", "a", &loss), ("b", "c", &loss)]).unwrap();
+        let order: Vec<String> = db.ratings().unwrap().into_iter().map(|r| r.player).collect();
+        assert_eq!(order, ["a", "c", "b"]);
+        assert!(db.rating("a").unwrap().unwrap().elo > ELO_START + 16.0);
+        assert_eq!(db.game_count().unwrap(), 6);
+        let seed: i64 = db.conn.query_row("SELECT seed FROM games LIMIT 1", [], |row| row.get(0)).unwrap();
+        assert_eq!(seed as u64, u64::MAX);
+        let configs: i64 = db.conn.query_row("SELECT COUNT(*) FROM configs", [], |row| row.get(0)).unwrap();
+        assert_eq!(configs, 1);
+    }
+}
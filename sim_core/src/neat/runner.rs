@@ -1,12 +1,17 @@
 pub use super::config::EvolutionConfig;
-use crate::{Simulation, Config, AGENT_STRIDE, IDX_TEAM, IDX_HEALTH};
+use super::config::TieBreak;
+use crate::{Simulation, Config, AGENT_STRIDE, IDX_TEAM, IDX_HEALTH, IDX_X, IDX_Y};
 use crate::brain::Brain;
-use serde::Serialize;
+use crate::shots::ShotTally;
+use super::error::NeatError;
+use super::metrics::{HeadingHistogram, IdleStreaks};
+use crate::replay::ReplayFrame;
 use std::fs::File;
-use std::io::Write;
+use std::io::{BufWriter, Write};
 use std::path::Path;
 use std::time::Instant;
 use std::sync::atomic::{AtomicU64, Ordering};
+use rayon::prelude::*;
 
 /// Cumulative physics time and count for profiling
 pub static PHYS_TIME_NS: AtomicU64 = AtomicU64::new(0);
@@ -17,6 +22,7 @@ pub static MATCH_TIME_NS: AtomicU64 = AtomicU64::new(0);
 pub static MATCH_COUNT: AtomicU64 = AtomicU64::new(0);
 
 /// Raw stats collected from one match
+#[derive(Debug, Default)]
 pub struct MatchStats {
     pub ticks: usize,
     pub subject_team_health: f32,
@@ -27,6 +33,195 @@ pub struct MatchStats {
     pub salvage_actions: f32,
     /// Sum of exploration (thrust) actions over match
     pub exploration_actions: f32,
+    /// Subject team's trigger pulls, shots, hits and friendly-fire damage
+    pub fire: ShotTally,
+    /// Longest run of consecutive ticks a living subject agent stood still
+    pub longest_idle: usize,
+    /// Per-tick `Simulation::state_hash` (only when `record_state_hashes` is set)
+    pub state_hashes: Vec<u64>,
+    /// Behavior characterization for novelty search (see `behavior_of`)
+    pub behavior: Vec<f32>,
+    /// Ticks after which the subject team still had an agent alive
+    pub survival_ticks: usize,
+    /// Normalized entropy of the subject team's movement headings (see `HeadingHistogram`)
+    pub movement_entropy: f32,
+    /// Result for the subject team
+    pub outcome: MatchOutcome,
+}
+
+/// How a match ended for the subject team (A) against everyone else (B).
+/// `margin` is A's lead in the measure that decided it: remaining health,
+/// or for a tie-break the `TieBreak` measure (negative when B leads).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MatchOutcome {
+    WinA { margin: f32 },
+    WinB { margin: f32 },
+    Draw { margin: f32 },
+}
+
+impl Default for MatchOutcome {
+    fn default() -> Self { MatchOutcome::Draw { margin: 0.0 } }
+}
+
+/// One side's standing at the end of a match
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Side {
+    pub health: f32,
+    pub damage_dealt: f32,
+    pub alive: usize,
+}
+
+impl MatchOutcome {
+    /// A wipe decides the match outright; a timeout goes to `tie_break`, with
+    /// leads up to `draw_margin` drawn
+    pub fn decide(a: Side, b: Side, tie_break: TieBreak, draw_margin: f32) -> Self {
+        let health = a.health - b.health;
+        let margin = match (a.alive > 0, b.alive > 0) {
+            (true, false) => return MatchOutcome::WinA { margin: health },
+            (false, true) => return MatchOutcome::WinB { margin: health },
+            (false, false) => return MatchOutcome::Draw { margin: health },
+            (true, true) => match tie_break {
+                TieBreak::Health => health,
+                TieBreak::Damage => a.damage_dealt - b.damage_dealt,
+                TieBreak::Survivors if a.alive != b.alive => a.alive as f32 - b.alive as f32,
+                TieBreak::Survivors => health,
+                TieBreak::None => return MatchOutcome::Draw { margin: health },
+            },
+        };
+        if margin.abs() <= draw_margin {
+            MatchOutcome::Draw { margin }
+        } else if margin > 0.0 {
+            MatchOutcome::WinA { margin }
+        } else {
+            MatchOutcome::WinB { margin }
+        }
+    }
+
+    /// 1 if A won, -1 if B won, 0 for a draw
+    pub fn result(&self) -> i32 {
+        match self {
+            MatchOutcome::WinA { .. } => 1,
+            MatchOutcome::WinB { .. } => -1,
+            MatchOutcome::Draw { .. } => 0,
+        }
+    }
+}
+
+/// Mercy rule: watches a match for a lopsided health ratio or a stretch
+/// without damage (`mercy_ratio`, `stall_ticks`)
+struct Mercy {
+    ratio: f32,
+    stall_ticks: usize,
+    /// Lowest total health seen so far
+    low: f32,
+    quiet: usize,
+}
+
+impl Mercy {
+    fn new(evo_cfg: &EvolutionConfig) -> Self {
+        Mercy { ratio: evo_cfg.mercy_ratio, stall_ticks: evo_cfg.stall_ticks, low: f32::INFINITY, quiet: 0 }
+    }
+
+    /// Whether the match should end after a tick that left the sides at `a` and `b`
+    fn called(&mut self, a: Side, b: Side) -> bool {
+        let total = a.health + b.health;
+        if total < self.low {
+            self.low = total;
+            self.quiet = 0;
+        } else {
+            self.quiet += 1;
+        }
+        let (weak, strong) = (a.health.min(b.health), a.health.max(b.health));
+        (self.ratio > 0.0 && weak < self.ratio * strong)
+            || (self.stall_ticks > 0 && self.quiet >= self.stall_ticks)
+    }
+}
+
+/// Standings of the subject team and of everyone else
+fn sides(sim: &Simulation, subject_team: u32) -> (Side, Side) {
+    let (mut a, mut b) = (Side::default(), Side::default());
+    for ch in sim.agents_data.chunks(AGENT_STRIDE) {
+        let side = if ch[IDX_TEAM] as u32 == subject_team { &mut a } else { &mut b };
+        side.health += ch[IDX_HEALTH];
+        side.alive += (ch[IDX_HEALTH] > 0.0) as usize;
+    }
+    (a, b)
+}
+
+/// First divergence found when re-running a recorded match
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayMismatch {
+    /// Tick (1-based) at which the hashes diverged
+    pub tick: usize,
+    /// Recorded hash, None if the re-run lasted longer than the recording
+    pub expected: Option<u64>,
+    /// Re-run hash, None if the re-run ended before the recording did
+    pub actual: Option<u64>,
+}
+
+/// Builds a fresh brain for each agent of one side
+pub type BrainFactory<'a> = &'a (dyn Fn() -> Box<dyn Brain> + Sync);
+
+/// The agents of one match, as a brain factory and team per agent, and the
+/// team `MatchStats` describes
+#[derive(Clone)]
+pub struct Lineup<'a> {
+    pub agents: Vec<(BrainFactory<'a>, u32)>,
+    pub subject: u32,
+}
+
+impl<'a> Lineup<'a> {
+    /// Lineup scored for its first agent's team
+    pub fn new(agents: Vec<(BrainFactory<'a>, u32)>) -> Self {
+        let subject = agents.first().map_or(0, |a| a.1);
+        Lineup { agents, subject }
+    }
+
+    /// The same match from the other side: agent slots reversed and team ids
+    /// swapped end for end, still scored for the same brains
+    pub fn mirrored(&self) -> Self {
+        let last = self.agents.iter().map(|a| a.1).max().unwrap_or(0);
+        Lineup {
+            agents: self.agents.iter().rev().map(|&(make, team)| (make, last - team)).collect(),
+            subject: last - self.subject,
+        }
+    }
+}
+
+/// Play every lineup once per seed across the rayon pool. Results are in
+/// lineup-major order: `lineups[i]` under `seeds[s]` is at `i * seeds.len() + s`.
+pub fn run_matches_parallel(
+    sim_cfg: &Config,
+    evo_cfg: &EvolutionConfig,
+    lineups: &[Lineup],
+    seeds: &[u64],
+) -> Vec<MatchStats> {
+    (0..lineups.len() * seeds.len())
+        .into_par_iter()
+        .map(|k| {
+            let (lineup, seed) = (&lineups[k / seeds.len()], seeds[k % seeds.len()]);
+            let agents = lineup.agents.iter().map(|&(make, team)| (make(), team)).collect();
+            run_match_for(sim_cfg, evo_cfg, agents, seed, lineup.subject)
+        })
+        .collect()
+}
+
+/// Play `lineups[i]` under `seeds[i]` across the rayon pool, results in lineup order
+pub fn run_lineups_parallel(
+    sim_cfg: &Config,
+    evo_cfg: &EvolutionConfig,
+    lineups: &[Lineup],
+    seeds: &[u64],
+) -> Vec<MatchStats> {
+    assert_eq!(lineups.len(), seeds.len(), "one seed per lineup");
+    lineups
+        .par_iter()
+        .zip(seeds)
+        .map(|(lineup, &seed)| {
+            let agents = lineup.agents.iter().map(|&(make, team)| (make(), team)).collect();
+            run_match_for(sim_cfg, evo_cfg, agents, seed, lineup.subject)
+        })
+        .collect()
 }
 
 /// Run a single match, return raw statistics
@@ -35,10 +230,44 @@ pub fn run_match(
     evo_cfg: &EvolutionConfig,
     agents: Vec<(Box<dyn Brain>, u32)>,
 ) -> MatchStats {
+    run_match_seeded(sim_cfg, evo_cfg, agents, 0)
+}
+
+/// Run a single match with the simulation RNG seeded from `seed`
+pub fn run_match_seeded(
+    sim_cfg: &Config,
+    evo_cfg: &EvolutionConfig,
+    agents: Vec<(Box<dyn Brain>, u32)>,
+    seed: u64,
+) -> MatchStats {
+    let subject_team = agents[0].1;
+    run_match_for(sim_cfg, evo_cfg, agents, seed, subject_team)
+}
+
+/// `run_match_seeded` with stats for `subject_team` rather than the first agent's
+pub fn run_match_for(
+    sim_cfg: &Config,
+    evo_cfg: &EvolutionConfig,
+    agents: Vec<(Box<dyn Brain>, u32)>,
+    seed: u64,
+    subject_team: u32,
+) -> MatchStats {
+    play(sim_cfg, evo_cfg, agents, seed, subject_team, None)
+        .expect("a match without a recorder does no I/O")
+}
+
+/// The match loop behind `run_match_for` and `run_match_record`; when given a
+/// `recorder`, a `ReplayFrame` is written to it as one JSON line per tick
+fn play(
+    sim_cfg: &Config,
+    evo_cfg: &EvolutionConfig,
+    agents: Vec<(Box<dyn Brain>, u32)>,
+    seed: u64,
+    subject_team: u32,
+    mut recorder: Option<&mut dyn Write>,
+) -> Result<MatchStats, NeatError> {
     #[cfg(not(target_arch = "wasm32"))]
     let match_start = Instant::now();
-    // Determine subject team ID
-    let subject_team = agents[0].1;
     // Initialize simulation
     let mut sim = Simulation::with_brains(
         evo_cfg.map_width,
@@ -46,13 +275,22 @@ pub fn run_match(
         sim_cfg.clone(),
         agents,
     );
+    sim.reseed(seed);
+    sim.generate_map(seed);
     let n_agents = sim.agents_data.len() / AGENT_STRIDE;
     // Initial total opponent health
-    let initial_opponent_health = sim_cfg.health_max * ((evo_cfg.num_teams * evo_cfg.team_size - evo_cfg.team_size) as f32);
+    let initial_opp_health = sim_cfg.health_max * ((evo_cfg.num_teams * evo_cfg.team_size - evo_cfg.team_size) as f32);
+    let (initial_a, initial_b) = sides(&sim, subject_team);
     // Track salvage & exploration actions
     let mut total_salvage_actions: f32 = 0.0;
     let mut total_thrust_actions: f32 = 0.0;
-    let mut stats = MatchStats { ticks: 0, subject_team_health: 0.0, total_damage_inflicted: 0.0, kills: 0, salvage_actions: 0.0, exploration_actions: 0.0 };
+    let mut stats = MatchStats::default();
+    let mut headings = HeadingHistogram::default();
+    headings.observe(&sim, subject_team);
+    let mut idle = IdleStreaks::default();
+    idle.observe(&sim, subject_team);
+    let mut mercy = Mercy::new(evo_cfg);
+    mercy.called(initial_a, initial_b);
     for tick in 0..evo_cfg.max_ticks {
         // Profile simulation step (skip timing on wasm32)
         #[cfg(not(target_arch = "wasm32"))]
@@ -64,6 +302,7 @@ pub fn run_match(
             let phys_ns = phys_start.elapsed().as_nanos() as u64;
             PHYS_TIME_NS.fetch_add(phys_ns, Ordering::Relaxed);
             PHYS_COUNT.fetch_add(1, Ordering::Relaxed);
+            super::prometheus::TICK_SECONDS.observe_ns(phys_ns);
         }
         #[cfg(target_arch = "wasm32")]
         {
@@ -72,6 +311,18 @@ pub fn run_match(
             total_thrust_actions += sim.thrust_count as f32;
         }
         stats.ticks = tick + 1;
+        if evo_cfg.record_state_hashes {
+            stats.state_hashes.push(sim.state_hash());
+        }
+        if let Some(out) = recorder.as_mut() {
+            serde_json::to_writer(&mut **out, &ReplayFrame::capture(&sim))?;
+            out.write_all(b"\n")?;
+        }
+        headings.observe(&sim, subject_team);
+        idle.observe(&sim, subject_team);
+        if sim.agents_data.chunks(AGENT_STRIDE).any(|a| a[IDX_TEAM] as u32 == subject_team && a[IDX_HEALTH] > 0.0) {
+            stats.survival_ticks = stats.ticks;
+        }
         if evo_cfg.early_exit {
             // check if subject or opponents are done
             let mut subject_alive = false;
@@ -90,6 +341,10 @@ pub fn run_match(
                 break;
             }
         }
+        let (a, b) = sides(&sim, subject_team);
+        if mercy.called(a, b) {
+            break;
+        }
     }
     // Compute stats
     // subject team health
@@ -106,7 +361,7 @@ pub fn run_match(
         }
     }
     stats.subject_team_health = team_health;
-    stats.total_damage_inflicted = initial_opponent_health - opp_health;
+    stats.total_damage_inflicted = initial_opp_health - opp_health;
     // compute kill count: initial opponents minus remaining alive
     let mut opp_alive = 0;
     for i in 0..n_agents {
@@ -121,86 +376,220 @@ pub fn run_match(
     stats.kills = initial_opponents.saturating_sub(opp_alive);
     stats.salvage_actions = total_salvage_actions;
     stats.exploration_actions = total_thrust_actions;
+    stats.fire = sim.team_shots(subject_team);
+    stats.behavior = behavior_of(&sim, subject_team, &stats, initial_opp_health);
+    stats.movement_entropy = headings.entropy();
+    stats.longest_idle = idle.longest();
+    let (mut a, mut b) = sides(&sim, subject_team);
+    a.damage_dealt = initial_b.health - b.health;
+    b.damage_dealt = initial_a.health - a.health;
+    stats.outcome = MatchOutcome::decide(a, b, evo_cfg.tie_break, evo_cfg.draw_margin);
     #[cfg(not(target_arch = "wasm32"))]
     {
         let match_ns = match_start.elapsed().as_nanos() as u64;
         MATCH_TIME_NS.fetch_add(match_ns, Ordering::Relaxed);
         MATCH_COUNT.fetch_add(1, Ordering::Relaxed);
+        super::prometheus::MATCH_SECONDS.observe_ns(match_ns);
     }
-    stats
+    Ok(stats)
 }
 
-/// Record a JSONL replay of a match (one JSON frame per tick)
+/// Behavior characterization of the subject team, each component roughly in
+/// [0, 1]: mean final x and y as map fractions, damage dealt as a fraction of
+/// the opponents' starting health, and loot and thrust actions per agent-tick
+pub fn behavior_of(sim: &Simulation, subject_team: u32, stats: &MatchStats, initial_opp_health: f32) -> Vec<f32> {
+    let team: Vec<&[f32]> = sim.agents_data.chunks(AGENT_STRIDE)
+        .filter(|a| a[IDX_TEAM] as u32 == subject_team)
+        .collect();
+    let n = team.len().max(1) as f32;
+    let mean_x = team.iter().map(|a| a[IDX_X]).sum::<f32>() / n / sim.width as f32;
+    let mean_y = team.iter().map(|a| a[IDX_Y]).sum::<f32>() / n / sim.height as f32;
+    let agent_ticks = (sim.agent_count() * stats.ticks).max(1) as f32;
+    vec![
+        mean_x,
+        mean_y,
+        if initial_opp_health > 0.0 { stats.total_damage_inflicted / initial_opp_health } else { 0.0 },
+        stats.salvage_actions / agent_ticks,
+        stats.exploration_actions / agent_ticks,
+    ]
+}
+
+/// Re-run a match with the same seed and agents and confirm that every
+/// per-tick state hash matches the `expected` recording.
+pub fn verify_replay(
+    sim_cfg: &Config,
+    evo_cfg: &EvolutionConfig,
+    agents: Vec<(Box<dyn Brain>, u32)>,
+    seed: u64,
+    expected: &[u64],
+) -> Result<MatchStats, ReplayMismatch> {
+    let mut cfg = evo_cfg.clone();
+    cfg.record_state_hashes = true;
+    let stats = run_match_seeded(sim_cfg, &cfg, agents, seed);
+    let n = stats.state_hashes.len().max(expected.len());
+    for i in 0..n {
+        let e = expected.get(i).copied();
+        let a = stats.state_hashes.get(i).copied();
+        if e != a {
+            return Err(ReplayMismatch { tick: i + 1, expected: e, actual: a });
+        }
+    }
+    Ok(stats)
+}
+
+/// Record a JSONL replay of a seeded match (one JSON frame per tick)
 pub fn run_match_record<P: AsRef<Path>>(
     path: P,
     sim_cfg: &Config,
     evo_cfg: &EvolutionConfig,
     agents: Vec<(Box<dyn Brain>, u32)>,
-) -> MatchStats {
-    let mut file = File::create(path.as_ref()).expect("Failed to create replay file");
-    #[derive(Serialize)]
-    struct Frame {
-        tick: usize,
-        agents: Vec<f32>,
-        wrecks: Vec<f32>,
-    }
-    // Initialize simulation
+    seed: u64,
+) -> Result<MatchStats, NeatError> {
+    let mut file = BufWriter::new(File::create(path.as_ref())?);
     let subject_team = agents[0].1;
-    let mut sim = Simulation::with_brains(
-        evo_cfg.map_width,
-        evo_cfg.map_height,
-        sim_cfg.clone(),
-        agents,
-    );
-    let n_agents = sim.agents_data.len() / AGENT_STRIDE;
-    let initial_opp_health = sim_cfg.health_max * ((evo_cfg.num_teams * evo_cfg.team_size - evo_cfg.team_size) as f32);
-    // Track salvage & exploration actions
-    let mut total_salvage_actions: f32 = 0.0;
-    let mut total_thrust_actions: f32 = 0.0;
-    let mut stats = MatchStats { ticks: 0, subject_team_health: 0.0, total_damage_inflicted: 0.0, kills: 0, salvage_actions: 0.0, exploration_actions: 0.0 };
-    for tick in 0..evo_cfg.max_ticks {
-        sim.step();
-        total_salvage_actions += sim.loot_count as f32;
-        total_thrust_actions += sim.thrust_count as f32;
-        stats.ticks = tick + 1;
-        // dump frame
-        let frame = Frame { tick: stats.ticks, agents: sim.agents_data.clone(), wrecks: sim.wrecks_data.clone() };
-        serde_json::to_writer(&mut file, &frame).expect("Failed to write frame");
-        file.write_all(b"\n").expect("Failed to write newline");
-        // early exit
-        if evo_cfg.early_exit {
-            // check alive status
-            let alive = sim.agents_data.chunks(AGENT_STRIDE).enumerate().fold((false, false), |(s,a),(i,ch)| {
-                let team = ch[IDX_TEAM] as u32;
-                let health = ch[IDX_HEALTH];
-                (s || (team == subject_team && health > 0.0), a || (team != subject_team && health > 0.0))
-            });
-            if !alive.0 || !alive.1 { break; }
+    let stats = play(sim_cfg, evo_cfg, agents, seed, subject_team, Some(&mut file))?;
+    file.flush()?;
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::{NaiveAgent, NaiveBrain};
+
+    fn duel() -> Vec<(Box<dyn Brain>, u32)> {
+        vec![
+            (Box::new(NaiveBrain(NaiveAgent::new(1.2, 0.8))) as Box<dyn Brain>, 0),
+            (Box::new(NaiveBrain(NaiveAgent::new(1.2, 0.8))) as Box<dyn Brain>, 1),
+        ]
+    }
+
+    fn hashed_cfg() -> EvolutionConfig {
+        EvolutionConfig {
+            num_teams: 2,
+            team_size: 1,
+            max_ticks: 50,
+            record_state_hashes: true,
+            ..Default::default()
         }
     }
-    // final stats
-    let mut team_health = 0.0;
-    let mut opp_health = 0.0;
-    for chunk in sim.agents_data.chunks(AGENT_STRIDE) {
-        let team = chunk[IDX_TEAM] as u32;
-        let health = chunk[IDX_HEALTH];
-        if team == subject_team { team_health += health; } else { opp_health += health; }
+
+    #[test]
+    fn records_one_hash_per_tick() {
+        let stats = run_match_seeded(&Config::default(), &hashed_cfg(), duel(), 7);
+        assert_eq!(stats.state_hashes.len(), stats.ticks);
     }
-    stats.subject_team_health = team_health;
-    stats.total_damage_inflicted = initial_opp_health - opp_health;
-    // compute kill count: initial opponents minus remaining alive
-    let mut opp_alive = 0;
-    for i in 0..n_agents {
-        let base = i * AGENT_STRIDE;
-        let team = sim.agents_data[base + IDX_TEAM] as u32;
-        let health = sim.agents_data[base + IDX_HEALTH];
-        if team != subject_team && health > 0.0 {
-            opp_alive += 1;
-        }
+
+    #[test]
+    fn behavior_is_characterized() {
+        let stats = run_match_seeded(&Config::default(), &hashed_cfg(), duel(), 7);
+        assert_eq!(stats.behavior.len(), crate::neat::novelty::BEHAVIOR_LEN);
+        assert!(stats.behavior[..2].iter().all(|v| (0.0..=1.0).contains(v)));
+    }
+
+    #[test]
+    fn fire_terms_favor_accurate_careful_teams() {
+        let evo_cfg = EvolutionConfig { num_teams: 2, team_size: 1, map_width: 100, map_height: 100, max_ticks: 200, ..Default::default() };
+        let stats = run_match_seeded(&Config::default(), &evo_cfg, duel(), 7);
+        assert!(stats.fire.hits > 0 && stats.fire.hits <= stats.fire.shots && stats.fire.shots <= stats.fire.trigger_pulls);
+
+        let evo_cfg = EvolutionConfig { w_accuracy: 10.0, w_damage_per_shot: 1.0, w_fire_efficiency: 10.0, ..evo_cfg };
+        let shots = |trigger_pulls, shots, hits| MatchStats {
+            total_damage_inflicted: 40.0,
+            fire: ShotTally { trigger_pulls, shots, hits, friendly_damage: 0.0 },
+            ..MatchStats::default()
+        };
+        let aimed = evo_cfg.fitness_fn.compute(&shots(10, 10, 8), &evo_cfg);
+        let spammed = evo_cfg.fitness_fn.compute(&shots(100, 40, 8), &evo_cfg);
+        assert!((aimed - (40.0 + 8.0 + 4.0 + 10.0)).abs() < 1e-4);
+        assert!(spammed < aimed);
+
+        let evo_cfg = EvolutionConfig { w_friendly_fire: 1.0, w_idle: 0.1, ..evo_cfg };
+        let careless = MatchStats { longest_idle: 100, fire: ShotTally { friendly_damage: 5.0, ..shots(10, 10, 8).fire }, ..shots(10, 10, 8) };
+        assert!((evo_cfg.fitness_fn.compute(&careless, &evo_cfg) - (aimed - 5.0 - 10.0)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn verify_replay_accepts_identical_rerun() {
+        let sim_cfg = Config::default();
+        let evo_cfg = hashed_cfg();
+        let stats = run_match_seeded(&sim_cfg, &evo_cfg, duel(), 42);
+        let rerun = verify_replay(&sim_cfg, &evo_cfg, duel(), 42, &stats.state_hashes);
+        assert!(rerun.is_ok(), "rerun diverged: {:?}", rerun.err());
+    }
+
+    #[test]
+    fn verify_replay_reports_first_divergent_tick() {
+        let sim_cfg = Config::default();
+        let evo_cfg = hashed_cfg();
+        let mut hashes = run_match_seeded(&sim_cfg, &evo_cfg, duel(), 42).state_hashes;
+        hashes[3] ^= 1;
+        let err = verify_replay(&sim_cfg, &evo_cfg, duel(), 42, &hashes).unwrap_err();
+        assert_eq!(err.tick, 4);
+    }
+
+    #[test]
+    fn parallel_matches_match_sequential_runs() {
+        let (sim_cfg, evo_cfg) = (Config::default(), hashed_cfg());
+        let naive = || -> Box<dyn Brain> { Box::new(NaiveBrain(NaiveAgent::new(1.2, 0.8))) };
+        let duel_lineup = Lineup::new(vec![(&naive, 0), (&naive, 1)]);
+        let mirror = duel_lineup.mirrored();
+        assert_eq!((mirror.agents.iter().map(|a| a.1).collect::<Vec<_>>(), mirror.subject), (vec![0, 1], 1));
+        let stats = run_matches_parallel(&sim_cfg, &evo_cfg, &[duel_lineup, mirror], &[3, 9]);
+        assert_eq!(stats.len(), 4);
+        let sequential = run_match_seeded(&sim_cfg, &evo_cfg, duel(), 9);
+        assert_eq!(stats[1].state_hashes, sequential.state_hashes);
+        // same game, scored for the other team
+        assert_eq!(stats[3].state_hashes, sequential.state_hashes);
+    }
+
+    #[test]
+    fn mercy_rule_ends_lopsided_and_stalled_matches() {
+        let side = |health| Side { health, damage_dealt: 0.0, alive: 1 };
+        let cfg = EvolutionConfig { mercy_ratio: 0.25, stall_ticks: 3, ..EvolutionConfig::default() };
+        let mut mercy = Mercy::new(&cfg);
+        assert!(!mercy.called(side(100.0), side(100.0)));
+        assert!(!mercy.called(side(100.0), side(30.0)));
+        assert!(mercy.called(side(100.0), side(20.0)));
+
+        let mut mercy = Mercy::new(&cfg);
+        let calls: Vec<bool> = (0..4).map(|_| mercy.called(side(50.0), side(50.0))).collect();
+        assert_eq!(calls, [false, false, false, true]);
+        assert!(!Mercy::new(&EvolutionConfig::default()).called(side(100.0), side(1.0)));
+    }
+
+    #[test]
+    fn timeouts_follow_the_tie_break() {
+        let side = |health, damage_dealt, alive| Side { health, damage_dealt, alive };
+        let (a, b) = (side(80.0, 10.0, 1), side(60.0, 40.0, 2));
+        let decide = |t| MatchOutcome::decide(a, b, t, 1e-3);
+        assert_eq!(decide(TieBreak::Health), MatchOutcome::WinA { margin: 20.0 });
+        assert_eq!(decide(TieBreak::Damage), MatchOutcome::WinB { margin: -30.0 });
+        assert_eq!(decide(TieBreak::Survivors), MatchOutcome::WinB { margin: -1.0 });
+        assert_eq!(decide(TieBreak::None), MatchOutcome::Draw { margin: 20.0 });
+        assert_eq!(MatchOutcome::decide(a, b, TieBreak::Health, 25.0).result(), 0);
+        // a wipe is never a tie-break
+        assert_eq!(MatchOutcome::decide(side(5.0, 0.0, 1), side(0.0, 0.0, 0), TieBreak::None, 25.0).result(), 1);
+    }
+
+    #[test]
+    fn recording_to_unwritable_path_is_an_error() {
+        let path = std::env::temp_dir().join("no_such_dir").join("replay.jsonl");
+        let result = run_match_record(&path, &Config::default(), &hashed_cfg(), duel(), 7);
+        assert!(matches!(result, Err(NeatError::Io(_))));
+    }
+
+    #[test]
+    fn recorded_matches_keep_the_full_stats() {
+        let path = std::env::temp_dir().join(format!("replay_{}.jsonl", std::process::id()));
+        let recorded = run_match_record(&path, &Config::default(), &hashed_cfg(), duel(), 7).unwrap();
+        let frames = std::fs::read_to_string(&path).unwrap().lines().count();
+        std::fs::remove_file(&path).ok();
+        let plain = run_match_seeded(&Config::default(), &hashed_cfg(), duel(), 7);
+        assert_eq!(frames, recorded.ticks);
+        assert_eq!(recorded.state_hashes, plain.state_hashes);
+        assert_eq!(recorded.survival_ticks, plain.survival_ticks);
+        assert_eq!(recorded.longest_idle, plain.longest_idle);
+        assert_eq!(recorded.movement_entropy, plain.movement_entropy);
     }
-    let initial_opponents = n_agents.saturating_sub(evo_cfg.team_size as usize);
-    stats.kills = initial_opponents.saturating_sub(opp_alive);
-    stats.salvage_actions = total_salvage_actions;
-    stats.exploration_actions = total_thrust_actions;
-    stats
 }
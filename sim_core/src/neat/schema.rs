@@ -0,0 +1,103 @@
+//! Versioning of the serialized genome layout. Every genome is written with
+//! `schema_version`; loaders run `migrate_genome` on the raw JSON first, so
+//! champion files from before a layout change still load.
+//!
+//! Versions:
+//! 1. nodes, connections, and fitness
+//! 2. adds `fitness_naive`
+//! 3. adds bias nodes and per-node `activation` genes; first to record
+//!    `schema_version` (earlier files are recognized by their fields)
+
+use std::fmt;
+use serde_json::Value;
+
+/// Layout this build writes
+pub const SCHEMA_VERSION: u32 = 3;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaError {
+    /// Written by a newer build than this one
+    Newer { found: u32, supported: u32 },
+    /// Not a JSON object, so not a genome of any version
+    NotAnObject,
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaError::Newer { found, supported } => {
+                write!(f, "genome schema version {} is newer than this build supports ({})", found, supported)
+            }
+            SchemaError::NotAnObject => write!(f, "genome is not a JSON object"),
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+/// Layout version of a raw genome, from its `schema_version` or, for files
+/// written before it was recorded, from which fields are present
+pub fn detect_version(genome: &Value) -> u32 {
+    if let Some(v) = genome.get("schema_version").and_then(Value::as_u64) {
+        return v as u32;
+    }
+    if genome.get("fitness_naive").is_none() {
+        return 1;
+    }
+    let nodes = genome.get("nodes").and_then(Value::as_array);
+    if nodes.is_some_and(|ns| ns.iter().any(|n| n.get("activation").is_some())) { 3 } else { 2 }
+}
+
+/// Upgrade a raw genome in place to `SCHEMA_VERSION`; returns the version it had
+pub fn migrate_genome(genome: &mut Value) -> Result<u32, SchemaError> {
+    let found = detect_version(genome);
+    if found > SCHEMA_VERSION {
+        return Err(SchemaError::Newer { found, supported: SCHEMA_VERSION });
+    }
+    let obj = genome.as_object_mut().ok_or(SchemaError::NotAnObject)?;
+    if found < 2 {
+        obj.insert("fitness_naive".into(), Value::from(0.0));
+    }
+    if found < 3 {
+        // bias-less networks stay bias-less; every node gets the tanh they always used
+        if let Some(nodes) = obj.get_mut("nodes").and_then(Value::as_array_mut) {
+            for node in nodes.iter_mut().filter_map(Value::as_object_mut) {
+                node.entry("activation").or_insert_with(|| Value::from("Tanh"));
+            }
+        }
+    }
+    obj.insert("schema_version".into(), Value::from(SCHEMA_VERSION));
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::neat::genome::{Activation, Genome};
+
+    #[test]
+    fn migrates_each_older_layout() {
+        let v1 = json!({
+            "nodes": [{ "id": 0, "node_type": "Input" }, { "id": 1, "node_type": "Output" }],
+            "conns": [{ "in_node": 0, "out_node": 1, "weight": 0.5, "enabled": true, "innovation": 0 }],
+            "fitness": 2.0,
+        });
+        let mut v2 = v1.clone();
+        v2["fitness_naive"] = json!(1.5);
+        for (mut raw, version) in [(v1, 1), (v2, 2)] {
+            assert_eq!(migrate_genome(&mut raw), Ok(version));
+            let g: Genome = serde_json::from_value(raw).unwrap();
+            assert_eq!(g.schema_version, SCHEMA_VERSION);
+            assert!(g.nodes.iter().all(|n| n.activation == Activation::Tanh));
+            assert_eq!(g.feed_forward(&[1.0])[0], Activation::Tanh.apply(0.5));
+        }
+
+        let mut current = serde_json::to_value(Genome::new()).unwrap();
+        assert_eq!(current["schema_version"], json!(SCHEMA_VERSION));
+        assert_eq!(migrate_genome(&mut current), Ok(SCHEMA_VERSION));
+
+        let mut future = json!({ "schema_version": SCHEMA_VERSION + 1 });
+        assert!(matches!(migrate_genome(&mut future), Err(SchemaError::Newer { .. })));
+    }
+}
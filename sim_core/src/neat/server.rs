@@ -0,0 +1,115 @@
+//! Local inference server (`neat_train serve`): the `/infer` and
+//! `/infer_batch` JSON API of the Python ONNX service, answered by a loaded
+//! genome on the CPU, so remote-inference runs need no Python install.
+//! Prometheus metrics are served on `/metrics`.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Instant;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use rayon::prelude::*;
+use serde::{Serialize, Deserialize};
+use super::genome::Genome;
+use super::prometheus;
+
+#[derive(Deserialize)]
+pub struct InferenceRequest {
+    pub inputs: Vec<Vec<f32>>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct InferenceResponse {
+    pub outputs: Vec<Vec<f32>>,
+    /// Time spent in the network, in milliseconds
+    pub duration_ms: f32,
+}
+
+type Reply = Result<Json<InferenceResponse>, (StatusCode, String)>;
+
+/// Routes serving `genome`
+pub fn router(genome: Genome) -> Router {
+    Router::new()
+        .route("/health", get(|| async { Json(serde_json::json!({ "status": "ok" })) }))
+        .route("/infer", post(infer))
+        .route("/infer_batch", post(infer_batch))
+        .with_state(Arc::new(genome))
+        .merge(prometheus::router())
+}
+
+/// Serve `genome` on `addr` until the process is stopped
+pub async fn serve(genome: Genome, addr: SocketAddr) -> std::io::Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    prometheus::enable();
+    axum::serve(listener, router(genome)).await
+}
+
+/// Run each row through the network in order
+async fn infer(State(genome): State<Arc<Genome>>, Json(req): Json<InferenceRequest>) -> Reply {
+    run(genome, req.inputs, false).await
+}
+
+/// Same result as `/infer`; rows are spread over the rayon pool
+async fn infer_batch(State(genome): State<Arc<Genome>>, Json(req): Json<InferenceRequest>) -> Reply {
+    run(genome, req.inputs, true).await
+}
+
+async fn run(genome: Arc<Genome>, inputs: Vec<Vec<f32>>, parallel: bool) -> Reply {
+    let width = genome.input_size();
+    if let Some(row) = inputs.iter().find(|r| r.len() != width) {
+        return Err((StatusCode::BAD_REQUEST, format!("input row of {} values, model takes {}", row.len(), width)));
+    }
+    let (outputs, duration_ms) = tokio::task::spawn_blocking(move || {
+        let start = Instant::now();
+        let row = |x: &Vec<f32>| {
+            let row_start = Instant::now();
+            let out = genome.feed_forward(x);
+            prometheus::INFER_SECONDS.observe_ns(row_start.elapsed().as_nanos() as u64);
+            out
+        };
+        let outputs: Vec<Vec<f32>> = if parallel {
+            inputs.par_iter().map(row).collect()
+        } else {
+            inputs.iter().map(row).collect()
+        };
+        (outputs, start.elapsed().as_secs_f32() * 1000.0)
+    })
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Json(InferenceResponse { outputs, duration_ms }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::neat::config::EvolutionConfig;
+
+    #[test]
+    fn serves_genome_outputs() {
+        let mut genome = Genome::new();
+        genome.initialize(&Config::default(), &EvolutionConfig::default(), &mut rand::thread_rng());
+        let row = vec![0.5; genome.input_size()];
+        let expected = genome.feed_forward(&row);
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let listener = rt.block_on(tokio::net::TcpListener::bind("127.0.0.1:0")).unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        rt.spawn(async move { axum::serve(listener, router(genome)).await });
+        let client = reqwest::blocking::Client::new();
+        for endpoint in ["infer", "infer_batch"] {
+            let resp: InferenceResponse = client.post(format!("{}/{}", url, endpoint))
+                .json(&serde_json::json!({ "inputs": [row, row] }))
+                .send().unwrap()
+                .json().unwrap();
+            assert_eq!(resp.outputs, vec![expected.clone(), expected.clone()]);
+        }
+        let metrics = client.get(format!("{}/metrics", url)).send().unwrap().text().unwrap();
+        assert!(metrics.contains("# TYPE neat_infer_duration_seconds histogram"));
+        let bad = client.post(format!("{}/infer", url))
+            .json(&serde_json::json!({ "inputs": [[1.0]] }))
+            .send().unwrap();
+        assert_eq!(bad.status(), reqwest::StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,253 @@
+//! Speciation: grouping genomes by structural similarity so new topologies
+//! compete within their own niche instead of against the whole population.
+
+use std::collections::HashMap;
+use rand::Rng;
+use rand::seq::SliceRandom;
+use serde::{Serialize, Deserialize, Deserializer};
+use super::config::EvolutionConfig;
+use super::genome::Genome;
+
+/// Genomes compact enough that excess/disjoint counts are not normalized
+const SMALL_GENOME_CONNS: usize = 20;
+
+/// A niche of compatible genomes
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Species {
+    pub id: usize,
+    /// Genome new members are compared against (a member of the previous generation)
+    pub representative: Genome,
+    /// Indices into `Population::genomes`
+    pub members: Vec<usize>,
+    /// Best member fitness seen over the species' lifetime
+    #[serde(deserialize_with = "null_as_neg_infinity")]
+    pub best_fitness: f32,
+    /// Generations since `best_fitness` last improved
+    pub stagnant_gens: usize,
+}
+
+/// JSON has no infinities: a never-scored species saves its `-inf` best as null
+fn null_as_neg_infinity<'de, D: Deserializer<'de>>(d: D) -> Result<f32, D::Error> {
+    Ok(Option::<f32>::deserialize(d)?.unwrap_or(f32::NEG_INFINITY))
+}
+
+impl Species {
+    pub fn new(id: usize, representative: Genome) -> Self {
+        Species { id, representative, members: Vec::new(), best_fitness: f32::NEG_INFINITY, stagnant_gens: 0 }
+    }
+
+    /// Mean raw fitness of the members
+    pub fn mean_fitness(&self, genomes: &[Genome]) -> f32 {
+        if self.members.is_empty() {
+            return 0.0;
+        }
+        self.members.iter().map(|&i| genomes[i].fitness).sum::<f32>() / self.members.len() as f32
+    }
+
+    /// Record this generation's best member fitness, resetting or advancing the stagnation count
+    pub fn update_stagnation(&mut self, genomes: &[Genome]) {
+        let best = self.members.iter().map(|&i| genomes[i].fitness).fold(f32::NEG_INFINITY, f32::max);
+        if best > self.best_fitness {
+            self.best_fitness = best;
+            self.stagnant_gens = 0;
+        } else {
+            self.stagnant_gens += 1;
+        }
+    }
+
+    /// Species fitness used for offspring allocation: the sum of member
+    /// fitness (offset by `baseline`), each shared by the species size when
+    /// `sharing` is on
+    pub fn adjusted_fitness(&self, genomes: &[Genome], baseline: f32, sharing: bool) -> f32 {
+        let total: f32 = self.members.iter().map(|&i| genomes[i].fitness - baseline).sum();
+        if sharing && !self.members.is_empty() {
+            total / self.members.len() as f32
+        } else {
+            total
+        }
+    }
+}
+
+/// NEAT compatibility distance: `c1*E/N + c2*D/N + c3*W̄`, where E and D are
+/// the excess and disjoint gene counts, W̄ the mean weight difference of
+/// matching genes, and N the larger genome's size (1 for small genomes)
+pub fn compatibility_distance(a: &Genome, b: &Genome, cfg: &EvolutionConfig) -> f32 {
+    let ma: HashMap<usize, f32> = a.conns.iter().map(|c| (c.innovation, c.weight)).collect();
+    let mb: HashMap<usize, f32> = b.conns.iter().map(|c| (c.innovation, c.weight)).collect();
+    let max_a = ma.keys().max().copied();
+    let max_b = mb.keys().max().copied();
+    let (mut excess, mut disjoint, mut matching, mut weight_diff) = (0usize, 0usize, 0usize, 0.0f32);
+    let mut classify = |innov: usize, other_max: Option<usize>| {
+        if other_max.is_none_or(|m| innov > m) {
+            excess += 1;
+        } else {
+            disjoint += 1;
+        }
+    };
+    // walk genes in genome order so the weight sum is reproducible
+    for c in &a.conns {
+        match mb.get(&c.innovation) {
+            Some(wb) => {
+                matching += 1;
+                weight_diff += (c.weight - wb).abs();
+            }
+            None => classify(c.innovation, max_b),
+        }
+    }
+    for c in b.conns.iter().filter(|c| !ma.contains_key(&c.innovation)) {
+        classify(c.innovation, max_a);
+    }
+    let size = a.conns.len().max(b.conns.len());
+    let n = if size < SMALL_GENOME_CONNS { 1.0 } else { size as f32 };
+    let w = if matching > 0 { weight_diff / matching as f32 } else { 0.0 };
+    cfg.compat_excess_coeff * excess as f32 / n
+        + cfg.compat_disjoint_coeff * disjoint as f32 / n
+        + cfg.compat_weight_coeff * w
+}
+
+/// Assign every genome to the first existing species within
+/// `compatibility_threshold` of its representative, founding new species as
+/// needed. Species left empty are dropped; survivors take a random member
+/// as the representative for the next generation.
+pub fn speciate<R: Rng + ?Sized>(
+    species: &mut Vec<Species>,
+    next_id: &mut usize,
+    genomes: &[Genome],
+    cfg: &EvolutionConfig,
+    rng: &mut R,
+) {
+    for s in species.iter_mut() {
+        s.members.clear();
+    }
+    for (i, g) in genomes.iter().enumerate() {
+        match species.iter_mut()
+            .find(|s| compatibility_distance(g, &s.representative, cfg) < cfg.compatibility_threshold)
+        {
+            Some(s) => s.members.push(i),
+            None => {
+                let mut s = Species::new(*next_id, g.clone());
+                *next_id += 1;
+                s.members.push(i);
+                species.push(s);
+            }
+        }
+    }
+    species.retain(|s| !s.members.is_empty());
+    for s in species.iter_mut() {
+        let &rep = s.members.choose(rng).unwrap();
+        s.representative = genomes[rep].clone();
+    }
+}
+
+/// Drop species stagnant for at least `limit` generations, except the one
+/// holding the overall champion (`champion` index into `genomes`)
+pub fn cull_stagnant(species: &mut Vec<Species>, champion: Option<usize>, limit: usize) {
+    species.retain(|s| s.stagnant_gens < limit || champion.is_some_and(|c| s.members.contains(&c)));
+}
+
+/// Split `total` offspring across species proportionally to `scores`
+/// (largest remainder; negative scores count as zero, uniform when all are zero)
+pub fn allocate_offspring(scores: &[f32], total: usize) -> Vec<usize> {
+    if scores.is_empty() {
+        return Vec::new();
+    }
+    let scores: Vec<f32> = scores.iter().map(|s| s.max(0.0)).collect();
+    let sum: f32 = scores.iter().sum();
+    let shares: Vec<f32> = if sum > 0.0 {
+        scores.iter().map(|s| s / sum * total as f32).collect()
+    } else {
+        vec![total as f32 / scores.len() as f32; scores.len()]
+    };
+    let mut counts: Vec<usize> = shares.iter().map(|s| s.floor() as usize).collect();
+    let mut order: Vec<usize> = (0..scores.len()).collect();
+    order.sort_by(|&a, &b| (shares[b] - shares[b].floor()).total_cmp(&(shares[a] - shares[a].floor())));
+    let assigned: usize = counts.iter().sum();
+    for &i in order.iter().take(total - assigned) {
+        counts[i] += 1;
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neat::genome::{ConnGene, NodeGene, NodeType};
+
+    fn genome(innovs: &[(usize, f32)]) -> Genome {
+        let mut g = Genome::new();
+        g.nodes = vec![NodeGene::new(0, NodeType::Input), NodeGene::new(1, NodeType::Output)];
+        g.conns = innovs.iter()
+            .map(|&(innovation, weight)| ConnGene { in_node: 0, out_node: 1, weight, enabled: true, innovation })
+            .collect();
+        g
+    }
+
+    #[test]
+    fn distance_counts_excess_disjoint_and_weights() {
+        let cfg = EvolutionConfig::default();
+        let a = genome(&[(0, 1.0), (1, 0.0), (3, 0.0)]);
+        let b = genome(&[(0, 0.5), (2, 0.0), (4, 0.0), (5, 0.0)]);
+        // matching: 0 (diff 0.5); disjoint: 1, 2, 3; excess: 4, 5
+        let expected = cfg.compat_excess_coeff * 2.0 + cfg.compat_disjoint_coeff * 3.0 + cfg.compat_weight_coeff * 0.5;
+        assert!((compatibility_distance(&a, &b, &cfg) - expected).abs() < 1e-6);
+        assert_eq!(compatibility_distance(&a, &a, &cfg), 0.0);
+    }
+
+    #[test]
+    fn speciate_splits_distant_genomes() {
+        let cfg = EvolutionConfig::default();
+        let genomes = vec![
+            genome(&[(0, 0.1), (1, 0.2)]),
+            genome(&[(0, 0.2), (1, 0.1)]),
+            genome(&[(5, 0.0), (6, 0.0), (7, 0.0), (8, 0.0)]),
+        ];
+        let (mut species, mut next) = (Vec::new(), 0);
+        let mut rng = rand::thread_rng();
+        speciate(&mut species, &mut next, &genomes, &cfg, &mut rng);
+        assert_eq!(species.len(), 2);
+        assert_eq!(species[0].members, vec![0, 1]);
+        assert_eq!(species[1].members, vec![2]);
+        // species persist across calls
+        speciate(&mut species, &mut next, &genomes, &cfg, &mut rng);
+        assert_eq!(species.iter().map(|s| s.id).collect::<Vec<_>>(), vec![0, 1]);
+    }
+
+    #[test]
+    fn sharing_divides_by_species_size() {
+        let mut genomes = vec![genome(&[]), genome(&[]), genome(&[])];
+        for (g, f) in genomes.iter_mut().zip([2.0, 4.0, 6.0]) {
+            g.fitness = f;
+        }
+        let mut s = Species::new(0, genomes[0].clone());
+        s.members = vec![0, 1, 2];
+        assert_eq!(s.adjusted_fitness(&genomes, 0.0, false), 12.0);
+        assert_eq!(s.adjusted_fitness(&genomes, 0.0, true), 4.0);
+        assert_eq!(s.adjusted_fitness(&genomes, 2.0, true), 2.0);
+    }
+
+    #[test]
+    fn stagnant_species_are_culled_except_champion() {
+        let mut genomes = vec![genome(&[]), genome(&[])];
+        genomes[0].fitness = 5.0;
+        genomes[1].fitness = 1.0;
+        let mut species: Vec<Species> = (0..2).map(|i| {
+            let mut s = Species::new(i, genomes[i].clone());
+            s.members = vec![i];
+            s
+        }).collect();
+        for _ in 0..4 {
+            species.iter_mut().for_each(|s| s.update_stagnation(&genomes));
+        }
+        assert_eq!(species[0].stagnant_gens, 3);
+        cull_stagnant(&mut species, Some(0), 3);
+        assert_eq!(species.len(), 1);
+        assert_eq!(species[0].id, 0);
+    }
+
+    #[test]
+    fn allocation_sums_to_total() {
+        assert_eq!(allocate_offspring(&[3.0, 1.0], 8), vec![6, 2]);
+        assert_eq!(allocate_offspring(&[1.0, 1.0, 1.0], 10).iter().sum::<usize>(), 10);
+        assert_eq!(allocate_offspring(&[0.0, 0.0], 5).iter().sum::<usize>(), 5);
+    }
+}
@@ -0,0 +1,301 @@
+//! Hyperparameter sweeps: a grid or random search over `EvolutionConfig`
+//! fields. Every configuration trains for a short budget, then its champion
+//! is scored against NaiveAgent under the base settings, so trials that
+//! change the fitness weights are still ranked on one yardstick.
+//!
+//! ```toml
+//! search = "random"
+//! trials = 12
+//! generations = 5
+//! [evolution]                 # base settings, as in an experiment file
+//! pop_size = 10
+//! max_ticks = 200
+//! [params]
+//! pop_size = [10, 20]
+//! mutation_weight_sigma = { min = 0.05, max = 0.5 }
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, Write};
+use std::path::Path;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use rayon::prelude::*;
+use serde::{Serialize, Deserialize};
+use toml::{Table, Value};
+use crate::brain::Brain;
+use crate::config::Config;
+use super::brain::NeatBrain;
+use super::config::EvolutionConfig;
+use super::experiment::{Experiment, ExperimentError};
+use super::genome::Genome;
+use super::hyperneat;
+use super::population::Population;
+use super::runner::run_match_seeded;
+
+#[derive(Debug)]
+pub enum SweepError {
+    Io(io::Error),
+    /// The spec is not valid TOML, or a configuration does not fit `EvolutionConfig`
+    Parse(String),
+    /// The spec cannot be searched as written
+    Invalid(String),
+}
+
+impl fmt::Display for SweepError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SweepError::Io(e) => write!(f, "sweep io error: {}", e),
+            SweepError::Parse(msg) => write!(f, "sweep parse error: {}", msg),
+            SweepError::Invalid(msg) => write!(f, "invalid sweep: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for SweepError {}
+
+impl From<io::Error> for SweepError {
+    fn from(e: io::Error) -> Self { SweepError::Io(e) }
+}
+
+impl From<ExperimentError> for SweepError {
+    fn from(e: ExperimentError) -> Self {
+        match e {
+            ExperimentError::Io(e) => SweepError::Io(e),
+            ExperimentError::Parse(msg) => SweepError::Parse(msg),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Search {
+    /// every combination of the listed values
+    #[default]
+    Grid,
+    /// `trials` independent draws
+    Random,
+}
+
+/// Values a swept field may take
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Space {
+    Values(Vec<Value>),
+    /// Uniform over `[min, max]`, integers if both bounds are (random search only)
+    Range { min: Value, max: Value },
+}
+
+impl Space {
+    fn sample(&self, name: &str, rng: &mut StdRng) -> Result<Value, SweepError> {
+        match self {
+            Space::Values(values) => values.choose(rng).cloned()
+                .ok_or_else(|| SweepError::Invalid(format!("`{}` has no values", name))),
+            Space::Range { min: Value::Integer(lo), max: Value::Integer(hi) } if lo <= hi => {
+                Ok(Value::Integer(rng.gen_range(*lo..=*hi)))
+            }
+            Space::Range { min, max } => match (as_float(min), as_float(max)) {
+                (Some(lo), Some(hi)) if lo <= hi => Ok(Value::Float(rng.gen_range(lo..=hi))),
+                _ => Err(SweepError::Invalid(format!("`{}` needs numeric bounds with min <= max", name))),
+            },
+        }
+    }
+}
+
+fn as_float(v: &Value) -> Option<f64> {
+    v.as_float().or_else(|| v.as_integer().map(|i| i as f64))
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SweepSpec {
+    pub search: Search,
+    /// Configurations drawn by a random search
+    pub trials: usize,
+    /// Generations each configuration trains for
+    pub generations: usize,
+    /// Seeded matches against NaiveAgent scoring each champion
+    pub eval_matches: usize,
+    /// Seed of the random search and of every trial's run
+    pub seed: u64,
+    /// Base `Config` overrides
+    pub sim: Table,
+    /// Base `EvolutionConfig` overrides
+    pub evolution: Table,
+    /// Swept `EvolutionConfig` fields
+    pub params: BTreeMap<String, Space>,
+}
+
+impl Default for SweepSpec {
+    fn default() -> Self {
+        SweepSpec {
+            search: Search::Grid,
+            trials: 10,
+            generations: 5,
+            eval_matches: 10,
+            seed: 0,
+            sim: Table::new(),
+            evolution: Table::new(),
+            params: BTreeMap::new(),
+        }
+    }
+}
+
+/// One trained configuration
+#[derive(Debug, Clone)]
+pub struct Trial {
+    /// The swept fields' values
+    pub params: Table,
+    /// Champion's fitness in its own (possibly re-weighted) training
+    pub best_fitness: f32,
+    /// Champion's mean base-config fitness against NaiveAgent
+    pub score: f32,
+}
+
+impl SweepSpec {
+    pub fn from_toml_str(s: &str) -> Result<Self, SweepError> {
+        toml::from_str(s).map_err(|e| SweepError::Parse(e.to_string()))
+    }
+
+    pub fn from_toml_file<P: AsRef<Path>>(path: P) -> Result<Self, SweepError> {
+        SweepSpec::from_toml_str(&std::fs::read_to_string(path)?)
+    }
+
+    /// Base settings every configuration starts from
+    pub fn base(&self) -> Result<(Config, EvolutionConfig), SweepError> {
+        let exp = Experiment { sim: self.sim.clone(), evolution: self.evolution.clone(), ..Default::default() };
+        let sim_cfg = exp.sim_config(&Config::default())?;
+        let evo_cfg = exp.evolution_config(&EvolutionConfig::default())?;
+        Ok((sim_cfg, EvolutionConfig { seed: Some(self.seed), ..evo_cfg }))
+    }
+
+    /// The swept fields' values for each configuration to try
+    pub fn configurations(&self) -> Result<Vec<Table>, SweepError> {
+        match self.search {
+            Search::Grid => {
+                let mut configs = vec![Table::new()];
+                for (name, space) in &self.params {
+                    let Space::Values(values) = space else {
+                        return Err(SweepError::Invalid(format!("`{}` is a range; grid search needs a list", name)));
+                    };
+                    configs = configs.iter()
+                        .flat_map(|c| values.iter().map(move |v| {
+                            let mut c = c.clone();
+                            c.insert(name.clone(), v.clone());
+                            c
+                        }))
+                        .collect();
+                }
+                Ok(configs)
+            }
+            Search::Random => {
+                let mut rng = StdRng::seed_from_u64(self.seed);
+                (0..self.trials)
+                    .map(|_| self.params.iter().map(|(name, space)| Ok((name.clone(), space.sample(name, &mut rng)?))).collect())
+                    .collect()
+            }
+        }
+    }
+
+    /// Train every configuration in parallel; trials come back best score first
+    pub fn run(&self) -> Result<Vec<Trial>, SweepError> {
+        let (sim_cfg, base) = self.base()?;
+        let runs = self.configurations()?.into_iter()
+            .map(|params| {
+                let exp = Experiment { evolution: params.clone(), ..Default::default() };
+                Ok((exp.evolution_config(&base)?, params))
+            })
+            .collect::<Result<Vec<_>, SweepError>>()?;
+        let mut trials: Vec<Trial> = runs.into_par_iter().map(|(evo_cfg, params)| {
+            let champ = train(&sim_cfg, &evo_cfg, self.generations);
+            let score = score(&sim_cfg, &base, &champ, self.eval_matches);
+            Trial { params, best_fitness: champ.fitness, score }
+        }).collect();
+        trials.sort_by(|a, b| b.score.total_cmp(&a.score));
+        Ok(trials)
+    }
+}
+
+/// Evolve a fresh population for `generations` and return its champion network
+pub fn train(sim_cfg: &Config, evo_cfg: &EvolutionConfig, generations: usize) -> Genome {
+    let mut pop = Population::new(evo_cfg);
+    for gen in 0..generations.max(1) {
+        pop.evaluate(sim_cfg, evo_cfg);
+        if gen + 1 < generations {
+            pop.reproduce(evo_cfg);
+        }
+    }
+    hyperneat::phenotype(&pop.hof[0], sim_cfg, evo_cfg)
+}
+
+/// Mean per-agent fitness (under `evo_cfg`) of a team of `champ` against an
+/// equal team of the `baseline` opponent over `matches` seeded matches
+pub fn score(sim_cfg: &Config, evo_cfg: &EvolutionConfig, champ: &Genome, matches: usize) -> f32 {
+    let team_size = evo_cfg.team_size.max(1);
+    let fits: Vec<f32> = (0..matches as u64).into_par_iter().map(|seed| {
+        let mut agents: Vec<(Box<dyn Brain>, u32)> = (0..team_size)
+            .map(|_| (Box::new(NeatBrain::for_config(champ.clone(), sim_cfg)) as Box<dyn Brain>, 0))
+            .collect();
+        for _ in 0..team_size {
+            agents.push((evo_cfg.baseline_brain(), 1));
+        }
+        let stats = run_match_seeded(sim_cfg, evo_cfg, agents, seed);
+        evo_cfg.fitness_fn.compute(&stats, evo_cfg) / team_size as f32
+    }).collect();
+    // summed in seed order, so the score does not depend on thread scheduling
+    fits.iter().fold(0.0, |acc, f| acc + f) / matches.max(1) as f32
+}
+
+/// One CSV row per trial, ranked, with a column per swept field
+pub fn write_csv<W: Write>(mut out: W, trials: &[Trial]) -> io::Result<()> {
+    let names: Vec<&String> = trials.first().map_or(Vec::new(), |t| t.params.keys().collect());
+    let header: Vec<&str> = names.iter().map(|n| n.as_str()).collect();
+    writeln!(out, "rank,{},best_fitness,score", header.join(","))?;
+    for (i, t) in trials.iter().enumerate() {
+        let values: Vec<String> = names.iter().map(|n| t.params[n.as_str()].to_string()).collect();
+        writeln!(out, "{},{},{},{}", i + 1, values.join(","), t.best_fitness, t.score)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_is_a_cartesian_product_and_random_stays_in_range() {
+        let mut spec = SweepSpec::from_toml_str(
+            "[params]\npop_size = [3, 4]\nw_damage = [0.5, 1.0, 2.0]\n",
+        ).unwrap();
+        let grid = spec.configurations().unwrap();
+        assert_eq!(grid.len(), 6);
+        assert_eq!(grid[5]["pop_size"].as_integer(), Some(4));
+        assert_eq!(grid[5]["w_damage"].as_float(), Some(2.0));
+
+        spec.params.insert("mutation_weight_sigma".into(), Space::Range { min: Value::Float(0.1), max: Value::Float(0.2) });
+        assert!(matches!(spec.configurations(), Err(SweepError::Invalid(_))));
+        spec.search = Search::Random;
+        spec.trials = 20;
+        let draws = spec.configurations().unwrap();
+        assert_eq!(draws.len(), 20);
+        assert!(draws.iter().all(|c| (0.1..=0.2).contains(&c["mutation_weight_sigma"].as_float().unwrap())));
+        assert_eq!(spec.configurations().unwrap(), draws);
+    }
+
+    #[test]
+    fn trials_are_ranked_by_score() {
+        let spec = SweepSpec::from_toml_str(
+            "generations = 1\neval_matches = 2\n\
+             [evolution]\npop_size = 3\nteam_size = 1\nmax_ticks = 5\n\
+             [params]\nw_damage = [0.0, 1.0]\n",
+        ).unwrap();
+        let trials = spec.run().unwrap();
+        assert_eq!(trials.len(), 2);
+        assert!(trials[0].score >= trials[1].score);
+        let mut buf = Vec::new();
+        write_csv(&mut buf, &trials).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        assert_eq!(text.lines().next(), Some("rank,w_damage,best_fitness,score"));
+        assert_eq!(text.lines().count(), 3);
+    }
+}
@@ -0,0 +1,388 @@
+//! Structured diagnostics: snapshots of the profiling counters, the
+//! per-generation record `neat_train train` appends to
+//! `metrics/generations.jsonl`, and a `tracing` subscriber writing events
+//! (and, in JSON mode, span timings) as text or JSON lines.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use tracing::field::{Field, Visit};
+use tracing::level_filters::LevelFilter;
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+use super::brain::{HTTP_TIME_NS, INFER_COUNT, INFER_TIME_NS, REMOTE_INFER_NS};
+use super::runner::{MATCH_COUNT, MATCH_TIME_NS, PHYS_COUNT, PHYS_TIME_NS};
+
+/// Profiling counters accumulated since the last `take`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Counters {
+    pub sim_ticks: u64,
+    pub sim_ns: u64,
+    pub matches: u64,
+    pub match_ns: u64,
+    pub infer_calls: u64,
+    pub infer_ns: u64,
+    pub http_ns: u64,
+    pub remote_infer_ns: u64,
+}
+
+impl Counters {
+    /// Read and reset the global counters
+    pub fn take() -> Self {
+        let take = |c: &AtomicU64| c.swap(0, Ordering::Relaxed);
+        Counters {
+            sim_ticks: take(&PHYS_COUNT),
+            sim_ns: take(&PHYS_TIME_NS),
+            matches: take(&MATCH_COUNT),
+            match_ns: take(&MATCH_TIME_NS),
+            infer_calls: take(&INFER_COUNT),
+            infer_ns: take(&INFER_TIME_NS),
+            http_ns: take(&HTTP_TIME_NS),
+            remote_infer_ns: take(&REMOTE_INFER_NS),
+        }
+    }
+
+    pub fn add(&mut self, other: &Counters) {
+        self.sim_ticks += other.sim_ticks;
+        self.sim_ns += other.sim_ns;
+        self.matches += other.matches;
+        self.match_ns += other.match_ns;
+        self.infer_calls += other.infer_calls;
+        self.infer_ns += other.infer_ns;
+        self.http_ns += other.http_ns;
+        self.remote_infer_ns += other.remote_infer_ns;
+    }
+
+    /// Mean simulation step time in µs (0 with no steps)
+    pub fn sim_avg_us(&self) -> f64 {
+        mean(self.sim_ns, self.sim_ticks) / 1e3
+    }
+
+    /// Mean match time in ms
+    pub fn match_avg_ms(&self) -> f64 {
+        mean(self.match_ns, self.matches) / 1e6
+    }
+
+    /// Mean inference time in µs
+    pub fn infer_avg_us(&self) -> f64 {
+        mean(self.infer_ns, self.infer_calls) / 1e3
+    }
+}
+
+fn mean(total_ns: u64, count: u64) -> f64 {
+    if count == 0 { 0.0 } else { total_ns as f64 / count as f64 }
+}
+
+/// One line of `metrics/generations.jsonl`
+#[derive(Debug, Clone, Serialize)]
+pub struct GenerationRecord {
+    pub generation: usize,
+    /// Seconds since training started
+    pub elapsed_s: f32,
+    pub eval_s: f32,
+    pub best: f32,
+    pub avg: f32,
+    pub best_naive: f32,
+    pub avg_naive: f32,
+    pub species: usize,
+    #[serde(flatten)]
+    pub counters: Counters,
+}
+
+impl GenerationRecord {
+    pub fn write_line<W: Write>(&self, mut out: W) -> io::Result<()> {
+        writeln!(out, "{}", serde_json::to_string(self)?)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// `LEVEL span{field=..}: message key=value`
+    Text,
+    /// One JSON object per event, plus one per closed span with its duration
+    Json,
+}
+
+struct SpanData {
+    name: &'static str,
+    target: &'static str,
+    fields: Map<String, Value>,
+    opened: Instant,
+    refs: usize,
+}
+
+thread_local! {
+    /// Spans entered on this thread, innermost last
+    static STACK: RefCell<Vec<u64>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Writes every enabled event as one line. Events from other crates are
+/// only logged at WARN and above.
+pub struct LogSubscriber {
+    format: LogFormat,
+    level: Level,
+    out: Mutex<Box<dyn Write + Send>>,
+    spans: Mutex<HashMap<u64, SpanData>>,
+    next_id: AtomicU64,
+}
+
+impl LogSubscriber {
+    pub fn new(format: LogFormat, level: Level, out: Box<dyn Write + Send>) -> Self {
+        LogSubscriber { format, level, out: Mutex::new(out), spans: Mutex::new(HashMap::new()), next_id: AtomicU64::new(1) }
+    }
+
+    /// Install as the process-wide subscriber
+    pub fn init(self) -> Result<(), tracing::subscriber::SetGlobalDefaultError> {
+        tracing::subscriber::set_global_default(self)
+    }
+
+    fn write(&self, line: &str) {
+        let mut out = self.out.lock().unwrap();
+        let _ = writeln!(out, "{}", line);
+        let _ = out.flush();
+    }
+
+    /// The spans entered on this thread, outermost first
+    fn scope(&self) -> Vec<(&'static str, Map<String, Value>)> {
+        let spans = self.spans.lock().unwrap();
+        STACK.with(|s| s.borrow().iter()
+            .filter_map(|id| spans.get(id).map(|d| (d.name, d.fields.clone())))
+            .collect())
+    }
+
+    fn format_line(&self, level: &Level, target: &str, mut fields: Map<String, Value>, scope: &[(&str, Map<String, Value>)]) -> String {
+        let message = match fields.remove("message") {
+            Some(Value::String(s)) => s,
+            Some(v) => v.to_string(),
+            None => String::new(),
+        };
+        match self.format {
+            LogFormat::Json => {
+                let mut line = Map::new();
+                line.insert("ts".into(), Value::String(Utc::now().to_rfc3339()));
+                line.insert("level".into(), Value::String(level.to_string()));
+                line.insert("target".into(), Value::String(target.to_string()));
+                line.insert("message".into(), Value::String(message));
+                line.extend(fields);
+                if !scope.is_empty() {
+                    let spans = scope.iter().map(|(name, f)| {
+                        let mut span = f.clone();
+                        span.insert("name".into(), Value::String(name.to_string()));
+                        Value::Object(span)
+                    }).collect();
+                    line.insert("spans".into(), Value::Array(spans));
+                }
+                Value::Object(line).to_string()
+            }
+            LogFormat::Text => {
+                let mut line = format!("{} {:>5} ", Utc::now().format("%H:%M:%S%.3f"), level);
+                for (name, f) in scope {
+                    let _ = write!(line, "{}{}:", name, text_fields(f, true));
+                }
+                if !scope.is_empty() {
+                    line.push(' ');
+                }
+                line.push_str(&message);
+                line.push_str(&text_fields(&fields, false));
+                line
+            }
+        }
+    }
+}
+
+/// `{a=1 b=2}` for spans, ` a=1 b=2` after a message
+fn text_fields(fields: &Map<String, Value>, braced: bool) -> String {
+    if fields.is_empty() {
+        return String::new();
+    }
+    let pairs: Vec<String> = fields.iter().map(|(k, v)| match v {
+        Value::String(s) => format!("{}={}", k, s),
+        Value::Number(n) if n.is_f64() => format!("{}={:.3}", k, n.as_f64().unwrap_or_default()),
+        v => format!("{}={}", k, v),
+    }).collect();
+    if braced { format!("{{{}}}", pairs.join(" ")) } else { format!(" {}", pairs.join(" ")) }
+}
+
+/// Collects a span's or event's fields as JSON values
+struct Fields<'a>(&'a mut Map<String, Value>);
+
+impl Visit for Fields<'_> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        let v = serde_json::Number::from_f64(value).map_or(Value::Null, Value::Number);
+        self.0.insert(field.name().into(), v);
+    }
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().into(), value.into());
+    }
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().into(), value.into());
+    }
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.insert(field.name().into(), format!("{:?}", value).into());
+    }
+}
+
+impl Subscriber for LogSubscriber {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        let ours = metadata.target().starts_with("sim_core") || metadata.target().starts_with("neat_train");
+        *metadata.level() <= self.level && (ours || *metadata.level() <= Level::WARN)
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        Some(LevelFilter::from_level(self.level))
+    }
+
+    fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+        let mut fields = Map::new();
+        attrs.record(&mut Fields(&mut fields));
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let data = SpanData {
+            name: attrs.metadata().name(),
+            target: attrs.metadata().target(),
+            fields,
+            opened: Instant::now(),
+            refs: 1,
+        };
+        self.spans.lock().unwrap().insert(id, data);
+        Id::from_u64(id)
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        if let Some(data) = self.spans.lock().unwrap().get_mut(&span.into_u64()) {
+            values.record(&mut Fields(&mut data.fields));
+        }
+    }
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Map::new();
+        event.record(&mut Fields(&mut fields));
+        let meta = event.metadata();
+        let line = self.format_line(meta.level(), meta.target(), fields, &self.scope());
+        self.write(&line);
+    }
+
+    fn enter(&self, span: &Id) {
+        STACK.with(|s| s.borrow_mut().push(span.into_u64()));
+    }
+
+    fn exit(&self, span: &Id) {
+        STACK.with(|s| {
+            let mut stack = s.borrow_mut();
+            if let Some(pos) = stack.iter().rposition(|&id| id == span.into_u64()) {
+                stack.remove(pos);
+            }
+        });
+    }
+
+    fn clone_span(&self, id: &Id) -> Id {
+        if let Some(data) = self.spans.lock().unwrap().get_mut(&id.into_u64()) {
+            data.refs += 1;
+        }
+        id.clone()
+    }
+
+    fn try_close(&self, id: Id) -> bool {
+        let closed = {
+            let mut spans = self.spans.lock().unwrap();
+            match spans.get_mut(&id.into_u64()) {
+                Some(data) if data.refs > 1 => {
+                    data.refs -= 1;
+                    return false;
+                }
+                Some(_) => spans.remove(&id.into_u64()),
+                None => return false,
+            }
+        };
+        // span durations are only worth a line in machine-readable logs
+        if let (Some(data), LogFormat::Json) = (closed, self.format) {
+            let mut fields = data.fields;
+            fields.insert("message".into(), Value::String(format!("{} closed", data.name)));
+            fields.insert("span".into(), Value::String(data.name.to_string()));
+            fields.insert("elapsed_ms".into(), (data.opened.elapsed().as_secs_f64() * 1e3).into());
+            let line = self.format_line(&Level::INFO, data.target, fields, &self.scope());
+            self.write(&line);
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// Shared buffer the subscriber writes into
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    }
+
+    fn capture(format: LogFormat, f: impl FnOnce()) -> Vec<String> {
+        let buf = Buffer::default();
+        let subscriber = LogSubscriber::new(format, Level::INFO, Box::new(buf.clone()));
+        tracing::subscriber::with_default(subscriber, f);
+        let text = String::from_utf8(buf.0.lock().unwrap().clone()).unwrap();
+        text.lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn json_lines_carry_fields_spans_and_durations() {
+        let lines = capture(LogFormat::Json, || {
+            let _gen = tracing::info_span!("generation", gen = 3).entered();
+            tracing::info!(best = 1.5, species = 2u64, "evaluated");
+            tracing::debug!("filtered out");
+        });
+        assert_eq!(lines.len(), 2);
+        let event: Value = serde_json::from_str(&lines[0]).unwrap();
+        assert_eq!(event["message"], "evaluated");
+        assert_eq!(event["level"], "INFO");
+        assert_eq!(event["best"], 1.5);
+        assert_eq!(event["spans"][0]["name"], "generation");
+        assert_eq!(event["spans"][0]["gen"], 3);
+        let closed: Value = serde_json::from_str(&lines[1]).unwrap();
+        assert_eq!(closed["span"], "generation");
+        assert!(closed["elapsed_ms"].as_f64().unwrap() >= 0.0);
+    }
+
+    #[test]
+    fn text_lines_and_counter_snapshots() {
+        let lines = capture(LogFormat::Text, || {
+            let _gen = tracing::info_span!("generation", gen = 3).entered();
+            tracing::warn!(url = "http://x", ms = 1.5, "fell back");
+        });
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].ends_with(" WARN generation{gen=3}: fell back ms=1.500 url=http://x"), "{}", lines[0]);
+
+        let record = GenerationRecord {
+            generation: 1, elapsed_s: 0.0, eval_s: 0.0, best: 0.0, avg: 0.0, best_naive: 0.0, avg_naive: 0.0,
+            species: 1, counters: Counters { sim_ticks: 4, sim_ns: 8000, ..Default::default() },
+        };
+        assert_eq!(record.counters.sim_avg_us(), 2.0);
+        assert_eq!(record.counters.match_avg_ms(), 0.0);
+        let mut buf = Vec::new();
+        record.write_line(&mut buf).unwrap();
+        let line: Value = serde_json::from_slice(&buf).unwrap();
+        assert_eq!((line["generation"].as_u64(), line["sim_ticks"].as_u64()), (Some(1), Some(4)));
+    }
+}
@@ -0,0 +1,263 @@
+//! Tournament schedules: which champions meet, round by round. Games within
+//! a round are independent and played in parallel; later rounds are paired
+//! from earlier results (Swiss standings, elimination brackets), so large
+//! fields finish in far fewer games than a round-robin.
+
+use std::collections::HashSet;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rayon::prelude::*;
+use super::eval::HeadToHead;
+
+/// Rating every player starts from
+pub const ELO_START: f32 = 1200.0;
+
+/// What a pairing produced, reduced to `a`'s share of the points (1 win, 0.5 draw, 0 loss)
+pub trait Scored {
+    fn score(&self) -> f32;
+}
+
+impl Scored for f32 {
+    fn score(&self) -> f32 { *self }
+}
+
+impl Scored for HeadToHead {
+    fn score(&self) -> f32 { HeadToHead::score(self) }
+}
+
+/// A played pairing
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outcome<R = f32> {
+    pub a: usize,
+    pub b: usize,
+    pub result: R,
+}
+
+impl<R: Scored> Outcome<R> {
+    pub fn score(&self) -> f32 {
+        self.result.score()
+    }
+
+    /// The player who lost; a draw counts against `b` (in a bracket, the lower seed)
+    pub fn loser(&self) -> usize {
+        if self.score() >= 0.5 { self.b } else { self.a }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// every pair meets once
+    RoundRobin,
+    /// `rounds` rounds, each pairing players on similar points who have not met yet
+    Swiss { rounds: usize },
+    /// knockout in seed order; a player is out after losing `lives` games
+    /// (1 for single, 2 for double elimination)
+    Elimination { lives: usize },
+}
+
+impl Format {
+    /// Swiss rounds needed to separate a single leader among `n` players
+    pub fn swiss_rounds(n: usize) -> usize {
+        (n.max(2) as f64).log2().ceil() as usize
+    }
+
+    /// Games played for `n` players (at most, for multi-life elimination)
+    pub fn games(&self, n: usize) -> usize {
+        match *self {
+            Format::RoundRobin => n * n.saturating_sub(1) / 2,
+            Format::Swiss { rounds } => rounds * (n / 2),
+            Format::Elimination { lives } => n.saturating_sub(1) * lives + lives.saturating_sub(1),
+        }
+    }
+
+    /// Run the schedule over players `0..n`, where `play(a, b)` plays `a`
+    /// against `b`; outcomes come back in the order they were played
+    pub fn run<R, F>(&self, n: usize, play: F) -> Vec<Outcome<R>>
+    where
+        R: Scored + Send,
+        F: Fn(usize, usize) -> R + Sync,
+    {
+        match *self {
+            Format::RoundRobin => {
+                let pairs: Vec<(usize, usize)> = (0..n).flat_map(|a| (a + 1..n).map(move |b| (a, b))).collect();
+                play_round(&pairs, &play)
+            }
+            Format::Swiss { rounds } => swiss(n, rounds, &play),
+            Format::Elimination { lives } => elimination(n, lives.max(1), &play),
+        }
+    }
+}
+
+fn play_round<R: Send, F: Fn(usize, usize) -> R + Sync>(pairs: &[(usize, usize)], play: &F) -> Vec<Outcome<R>> {
+    pairs.par_iter().map(|&(a, b)| Outcome { a, b, result: play(a, b) }).collect()
+}
+
+fn swiss<R, F>(n: usize, rounds: usize, play: &F) -> Vec<Outcome<R>>
+where
+    R: Scored + Send,
+    F: Fn(usize, usize) -> R + Sync,
+{
+    let mut points = vec![0.0f32; n];
+    let mut had_bye = vec![false; n];
+    let mut met: HashSet<(usize, usize)> = HashSet::new();
+    let mut outcomes = Vec::new();
+    for _ in 0..rounds {
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by(|&x, &y| points[y].total_cmp(&points[x]).then(x.cmp(&y)));
+        // the lowest-standing player yet to sit out takes the bye, worth a win
+        if n % 2 == 1 {
+            let pos = order.iter().rposition(|&p| !had_bye[p]).unwrap_or(n - 1);
+            let p = order.remove(pos);
+            had_bye[p] = true;
+            points[p] += 1.0;
+        }
+        // each player in standing order meets the next one they have not played
+        let mut pairs = Vec::new();
+        while !order.is_empty() {
+            let a = order.remove(0);
+            let j = order.iter().position(|&b| !met.contains(&(a.min(b), a.max(b)))).unwrap_or(0);
+            let b = order.remove(j);
+            met.insert((a.min(b), a.max(b)));
+            pairs.push((a, b));
+        }
+        let round = play_round(&pairs, play);
+        for o in &round {
+            points[o.a] += o.score();
+            points[o.b] += 1.0 - o.score();
+        }
+        outcomes.extend(round);
+    }
+    outcomes
+}
+
+fn elimination<R, F>(n: usize, lives: usize, play: &F) -> Vec<Outcome<R>>
+where
+    R: Scored + Send,
+    F: Fn(usize, usize) -> R + Sync,
+{
+    let mut losses = vec![0usize; n];
+    let mut outcomes = Vec::new();
+    loop {
+        // one bracket per loss count, each in seed order
+        let brackets: Vec<Vec<usize>> = (0..lives)
+            .map(|l| (0..n).filter(|&p| losses[p] == l).collect::<Vec<_>>())
+            .filter(|b| !b.is_empty())
+            .collect();
+        let mut pairs = Vec::new();
+        if brackets.iter().all(|b| b.len() < 2) {
+            // a single player left per bracket: the final, between the two fewest-loss players
+            match brackets.concat()[..] {
+                [a, b, ..] => pairs.push((a, b)),
+                _ => break,
+            }
+        } else {
+            for bracket in &brackets {
+                // top seed takes the bye; the rest pair top against bottom
+                let rest = &bracket[bracket.len() % 2..];
+                pairs.extend((0..rest.len() / 2).map(|i| (rest[i], rest[rest.len() - 1 - i])));
+            }
+        }
+        let round = play_round(&pairs, play);
+        for o in &round {
+            losses[o.loser()] += 1;
+        }
+        outcomes.extend(round);
+    }
+    outcomes
+}
+
+/// Elo ratings after applying `outcomes` in order, each a single update by
+/// its (possibly fractional) score
+pub fn elo<R: Scored>(n: usize, outcomes: &[Outcome<R>], k: f32) -> Vec<f32> {
+    let mut ratings = vec![ELO_START; n];
+    for o in outcomes {
+        let expected = 1.0 / (1.0 + 10f32.powf((ratings[o.b] - ratings[o.a]) / 400.0));
+        let delta = k * (o.score() - expected);
+        ratings[o.a] += delta;
+        ratings[o.b] -= delta;
+    }
+    ratings
+}
+
+/// 95% bootstrap interval of each `elo` rating: the outcomes are resampled
+/// with replacement (kept in play order) `resamples` times
+pub fn elo_intervals<R: Scored>(n: usize, outcomes: &[Outcome<R>], k: f32, resamples: usize, seed: u64) -> Vec<(f32, f32)> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut samples: Vec<Vec<f32>> = vec![Vec::with_capacity(resamples); n];
+    for _ in 0..resamples {
+        let mut picks: Vec<usize> = (0..outcomes.len()).map(|_| rng.gen_range(0..outcomes.len())).collect();
+        picks.sort_unstable();
+        let resampled: Vec<Outcome> = picks.iter()
+            .map(|&i| Outcome { a: outcomes[i].a, b: outcomes[i].b, result: outcomes[i].score() })
+            .collect();
+        for (s, r) in samples.iter_mut().zip(elo(n, &resampled, k)) {
+            s.push(r);
+        }
+    }
+    samples.into_iter().map(|mut s| {
+        if s.is_empty() {
+            return (ELO_START, ELO_START);
+        }
+        s.sort_by(f32::total_cmp);
+        let at = |q: f32| s[((s.len() - 1) as f32 * q).round() as usize];
+        (at(0.025), at(0.975))
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The lower index always wins
+    fn by_seed(a: usize, b: usize) -> f32 {
+        if a < b { 1.0 } else { 0.0 }
+    }
+
+    #[test]
+    fn schedules_play_the_expected_games() {
+        let rr = Format::RoundRobin.run(5, by_seed);
+        assert_eq!(rr.len(), Format::RoundRobin.games(5));
+
+        let swiss = Format::Swiss { rounds: 3 }.run(7, by_seed);
+        assert_eq!(swiss.len(), Format::Swiss { rounds: 3 }.games(7));
+        let pairs: HashSet<(usize, usize)> = swiss.iter().map(|o| (o.a.min(o.b), o.a.max(o.b))).collect();
+        assert_eq!(pairs.len(), swiss.len(), "no rematches");
+
+        for lives in [1, 2] {
+            let games = Format::Elimination { lives }.run(6, by_seed);
+            assert!(games.len() <= Format::Elimination { lives }.games(6));
+            let mut losses = [0; 6];
+            for o in &games {
+                losses[o.loser()] += 1;
+            }
+            // the top seed goes through unbeaten, everyone else is knocked out
+            assert_eq!(losses[0], 0);
+            assert!(losses[1..].iter().all(|&l| l == lives));
+        }
+    }
+
+    #[test]
+    fn elo_favors_the_stronger_player_within_its_interval() {
+        let outcomes = Format::RoundRobin.run(4, |a, b| if a < b { 0.75 } else { 0.25 });
+        let ratings = elo(4, &outcomes, 32.0);
+        assert!(ratings.windows(2).all(|w| w[0] > w[1]));
+        assert!((ratings.iter().sum::<f32>() - 4.0 * ELO_START).abs() < 1e-3);
+        let intervals = elo_intervals(4, &outcomes, 32.0, 200, 1);
+        for (r, (lo, hi)) in ratings.iter().zip(&intervals) {
+            assert!(lo <= hi);
+            assert!(*lo - 1e-3 <= *r && *r <= *hi + 1e-3);
+        }
+        assert_eq!(elo_intervals::<f32>(4, &[], 32.0, 10, 1), vec![(ELO_START, ELO_START); 4]);
+    }
+
+    #[test]
+    fn double_elimination_resets_a_lost_final() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        // seed 0 wins the first meeting, then loses both finals to the losers'-bracket player
+        let played = AtomicUsize::new(0);
+        let games = Format::Elimination { lives: 2 }.run(2, |_, _| {
+            if played.fetch_add(1, Ordering::Relaxed) == 0 { 1.0 } else { 0.0 }
+        });
+        let losers: Vec<usize> = games.iter().map(Outcome::loser).collect();
+        assert_eq!(losers, vec![1, 0, 0]);
+    }
+}
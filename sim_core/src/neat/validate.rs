@@ -0,0 +1,232 @@
+//! Champion file checks behind `neat_train validate`: the genome's structure,
+//! its sensor and output counts against the sim config it will play under,
+//! and whether the file still records how it was trained.
+
+use std::fmt;
+use serde_json::Value;
+use crate::config::Config;
+use super::bundle::{trained_with_legacy_motion, BundleError, ChampionBundle};
+use super::champion::{self, ChampionError};
+use super::genome::{Genome, GenomeError, NodeType};
+use super::onnx_exporter;
+use super::schema::{self, SCHEMA_VERSION};
+
+/// Keys `neat_train train` writes under a wrapped champion's `metadata`
+const METADATA_KEYS: [&str; 5] = ["timestamp", "generation", "config", "simulation_config", "evolution_config"];
+
+/// Keys `neat_train export` writes into an ONNX champion
+const ONNX_KEYS: [&str; 4] = ["neat.generation", "neat.input_size", "neat.sensor_layout", "neat.sim_config"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The champion cannot be played under the config
+    Error,
+    /// Playable, but something is missing or suspicious
+    Warning,
+}
+
+/// One problem, phrased with what to do about it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Finding {
+    fn error(message: String) -> Self { Finding { severity: Severity::Error, message } }
+    fn warning(message: String) -> Self { Finding { severity: Severity::Warning, message } }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{}: {}", label, self.message)
+    }
+}
+
+/// True if any finding stops the champion from playing
+pub fn has_errors(findings: &[Finding]) -> bool {
+    findings.iter().any(|f| f.severity == Severity::Error)
+}
+
+/// Everything wrong with the champion file contents `bytes` when played under `sim_cfg`
+pub fn check_bytes(bytes: &[u8], sim_cfg: &Config) -> Vec<Finding> {
+    let genome = match champion::genome_from_bytes(bytes) {
+        Ok(g) => g,
+        Err(e) => return vec![Finding::error(load_advice(&e))],
+    };
+    let mut findings = check_genome(&genome, sim_cfg);
+    match ChampionBundle::from_bytes(bytes) {
+        Ok(Some(bundle)) => {
+            if let Err(e @ (BundleError::SensorMismatch { .. } | BundleError::MotionMismatch { .. })) = bundle.check(sim_cfg) {
+                findings.push(Finding::error(format!("{}; play it under its bundled sim_config", e)));
+            }
+        }
+        Ok(None) if !sim_cfg.legacy_motion && trained_with_legacy_motion(bytes) => {
+            findings.push(Finding::error(BundleError::MotionMismatch { trained_legacy: true }.to_string()));
+        }
+        Ok(None) => {}
+        Err(e) => findings.push(Finding::error(format!("malformed champion bundle: {}", e))),
+    }
+    findings.extend(check_metadata(bytes, sim_cfg));
+    findings
+}
+
+/// Structural and shape problems of `genome` under `sim_cfg`
+pub fn check_genome(genome: &Genome, sim_cfg: &Config) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    if let Err(e) = genome.validate(true) {
+        findings.push(Finding::error(format!("{}; the genome is corrupt, take it again from its run directory", e)));
+    } else if let Err(GenomeError::Cycle(ids)) = genome.validate(false) {
+        findings.push(Finding::warning(format!(
+            "recurrent connections through nodes {:?}: it plays, but `export` cannot convert it to ONNX", ids,
+        )));
+    }
+    let count = |t: NodeType| genome.nodes.iter().filter(|n| n.node_type == t).count();
+    let (inputs, outputs) = (count(NodeType::Input), count(NodeType::Output));
+    if inputs != sim_cfg.sensor_len() {
+        findings.push(Finding::error(format!(
+            "genome takes {} inputs but the config produces {} sensor values \
+             (nearest_k_enemies {}, nearest_k_allies {}, nearest_k_wrecks {}, class_sensors {}); \
+             check against the config it was trained with (--config <run dir>/experiment.toml)",
+            inputs, sim_cfg.sensor_len(), sim_cfg.nearest_k_enemies, sim_cfg.nearest_k_allies,
+            sim_cfg.nearest_k_wrecks, sim_cfg.class_sensors,
+        )));
+    }
+    let actions = sim_cfg.action_layout();
+    if outputs != actions.len() {
+        findings.push(Finding::error(format!(
+            "genome has {} outputs; brains need {} ({})", outputs, actions.len(), actions.join(", "),
+        )));
+    }
+    if let Some(c) = genome.conns.iter().find(|c| !c.weight.is_finite()) {
+        findings.push(Finding::error(format!(
+            "connection {} has weight {}; the run diverged, use an earlier generation's champion", c.innovation, c.weight,
+        )));
+    }
+    if !genome.conns.iter().any(|c| c.enabled) {
+        findings.push(Finding::warning("no enabled connections: every output is constant".to_string()));
+    }
+    findings
+}
+
+/// What the file records about its training, and whether it matches `sim_cfg`
+fn check_metadata(bytes: &[u8], sim_cfg: &Config) -> Vec<Finding> {
+    let Ok(raw) = champion::decompress(bytes) else { return Vec::new() };
+    let value: Value = match serde_json::from_slice(&raw) {
+        Ok(v) => v,
+        Err(_) => return check_onnx_metadata(&raw, sim_cfg),
+    };
+    let mut findings = Vec::new();
+    let version = schema::detect_version(value.get("genome").unwrap_or(&value));
+    if version < SCHEMA_VERSION {
+        findings.push(Finding::warning(format!(
+            "older genome layout (schema version {}), migrated to version {} on load", version, SCHEMA_VERSION,
+        )));
+    }
+    let Some(meta) = value.get("metadata") else {
+        findings.push(Finding::warning(
+            "no metadata, so its generation and training settings are unknown".to_string(),
+        ));
+        return findings;
+    };
+    let missing: Vec<&str> = METADATA_KEYS.iter().copied().filter(|k| meta.get(k).is_none()).collect();
+    if !missing.is_empty() {
+        findings.push(Finding::warning(format!("metadata is missing {}", missing.join(", "))));
+    }
+    if let Some(trained) = meta.get("simulation_config") {
+        let fields = [
+            ("nearest_k_enemies", sim_cfg.nearest_k_enemies),
+            ("nearest_k_allies", sim_cfg.nearest_k_allies),
+            ("nearest_k_wrecks", sim_cfg.nearest_k_wrecks),
+        ];
+        for (field, now) in fields {
+            if let Some(then) = trained.get(field).and_then(Value::as_u64) {
+                if then as usize != now {
+                    findings.push(Finding::warning(format!(
+                        "trained with {} = {} but the config has {}; sensors are read in a different order", field, then, now,
+                    )));
+                }
+            }
+        }
+    }
+    findings
+}
+
+fn check_onnx_metadata(model: &[u8], sim_cfg: &Config) -> Vec<Finding> {
+    let Ok(props) = onnx_exporter::read_metadata(model) else { return Vec::new() };
+    let mut findings = Vec::new();
+    let missing: Vec<&str> = ONNX_KEYS.iter().copied().filter(|k| !props.contains_key(*k)).collect();
+    if !missing.is_empty() {
+        findings.push(Finding::warning(format!(
+            "ONNX metadata is missing {}; re-export with `neat_train export` to record it", missing.join(", "),
+        )));
+    }
+    if let Some(trained) = props.get("neat.sim_config").and_then(|s| serde_json::from_str::<Config>(s).ok()) {
+        if trained.sensor_layout() != sim_cfg.sensor_layout() {
+            findings.push(Finding::warning(
+                "exported with a different sensor layout than the config; sensors are read in a different order".to_string(),
+            ));
+        }
+    }
+    findings
+}
+
+/// A load failure with the likely fix
+fn load_advice(e: &ChampionError) -> String {
+    let hint = match e {
+        ChampionError::Io(_) => "check the path, or that the .gz file is complete",
+        ChampionError::Json(_) => "expected a genome or a { metadata, genome } file written by `neat_train train`",
+        ChampionError::Invalid(_) => "the genome is corrupt, take it again from its run directory",
+        ChampionError::Onnx(_) => "only models written by `neat_train export` or plain Gemm/MatMul stacks import",
+        ChampionError::Schema(_) => "it was written by a newer neat_train; load it with that build",
+    };
+    format!("{}; {}", e, hint)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neat::config::EvolutionConfig;
+
+    fn champion(sim_cfg: &Config) -> Genome {
+        let mut g = Genome::new();
+        g.initialize(sim_cfg, &EvolutionConfig::default(), &mut rand::thread_rng());
+        g
+    }
+
+    #[test]
+    fn flags_shape_mismatch_and_missing_metadata() {
+        // plain champion files predate the inertial motion model
+        let sim_cfg = Config { legacy_motion: true, ..Config::default() };
+        let genome = champion(&sim_cfg);
+        let wrapped = serde_json::to_vec(&serde_json::json!({
+            "metadata": {
+                "timestamp": "20250101_000000", "generation": 3, "config": {}, "evolution_config": {},
+                "simulation_config": { "nearest_k_enemies": sim_cfg.nearest_k_enemies },
+            },
+            "genome": genome,
+        })).unwrap();
+        assert!(check_bytes(&wrapped, &sim_cfg).is_empty());
+        assert!(check_bytes(&wrapped, &Config::default())[0].message.contains("legacy motion"));
+
+        let bare = serde_json::to_vec(&genome).unwrap();
+        let findings = check_bytes(&bare, &sim_cfg);
+        assert_eq!(findings.len(), 1);
+        assert!(!has_errors(&findings) && findings[0].message.contains("no metadata"));
+
+        let wider = Config { nearest_k_enemies: sim_cfg.nearest_k_enemies + 1, ..sim_cfg.clone() };
+        let findings = check_bytes(&wrapped, &wider);
+        assert!(has_errors(&findings));
+        assert!(findings[0].message.contains("experiment.toml"));
+        assert!(findings.iter().any(|f| f.message.contains("trained with nearest_k_enemies")));
+
+        let mut diverged = genome.clone();
+        diverged.conns[0].weight = f32::NAN;
+        assert!(has_errors(&check_genome(&diverged, &sim_cfg)));
+        assert!(check_bytes(b"{}", &sim_cfg)[0].to_string().starts_with("error: invalid champion JSON"));
+    }
+}
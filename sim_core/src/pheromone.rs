@@ -0,0 +1,112 @@
+//! Stigmergy layer: a coarse per-team grid over the map. Every living agent
+//! deposits on its cell each tick, all cells decay, and agents sense their
+//! own team's trail in their cell and the four around it (see
+//! `Config::pheromone_cell`).
+
+use serde::{Deserialize, Serialize};
+use crate::domain::Vec2;
+
+/// Values sensed per agent: own cell, then north, east, south, west
+pub const SENSED_CELLS: usize = 5;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PheromoneGrid {
+    cols: usize,
+    rows: usize,
+    cell: f32,
+    /// One `cols * rows` row-major layer per team id
+    layers: Vec<Vec<f32>>,
+}
+
+impl PheromoneGrid {
+    /// Grid of `cell`-sized squares covering a `width` x `height` map
+    pub fn new(width: u32, height: u32, cell: f32) -> Self {
+        let cell = cell.max(1.0);
+        PheromoneGrid {
+            cols: ((width as f32 / cell).ceil() as usize).max(1),
+            rows: ((height as f32 / cell).ceil() as usize).max(1),
+            cell,
+            layers: Vec::new(),
+        }
+    }
+
+    pub fn cols(&self) -> usize { self.cols }
+    pub fn rows(&self) -> usize { self.rows }
+    pub fn cell_size(&self) -> f32 { self.cell }
+
+    /// Cell containing `pos`, wrapping positions outside the map
+    fn cell_of(&self, pos: Vec2) -> (usize, usize) {
+        let c = (pos.x / self.cell).floor() as i64;
+        let r = (pos.y / self.cell).floor() as i64;
+        (c.rem_euclid(self.cols as i64) as usize, r.rem_euclid(self.rows as i64) as usize)
+    }
+
+    fn index(&self, col: usize, row: usize) -> usize {
+        row * self.cols + col
+    }
+
+    /// Add `amount` to `team`'s layer at `pos`
+    pub fn deposit(&mut self, team: u32, pos: Vec2, amount: f32) {
+        let (c, r) = self.cell_of(pos);
+        let i = self.index(c, r);
+        let len = self.cols * self.rows;
+        let t = team as usize;
+        if self.layers.len() <= t {
+            self.layers.resize(t + 1, Vec::new());
+        }
+        let layer = &mut self.layers[t];
+        if layer.is_empty() {
+            layer.resize(len, 0.0);
+        }
+        layer[i] += amount;
+    }
+
+    /// Scale every cell by `keep` (the fraction that survives a tick)
+    pub fn decay(&mut self, keep: f32) {
+        for v in self.layers.iter_mut().flatten() {
+            *v *= keep;
+        }
+    }
+
+    /// `team`'s values at `pos`'s cell and its four neighbours (wrapping at the edges)
+    pub fn sense(&self, team: u32, pos: Vec2) -> [f32; SENSED_CELLS] {
+        let Some(layer) = self.layers.get(team as usize).filter(|l| !l.is_empty()) else {
+            return [0.0; SENSED_CELLS];
+        };
+        let (c, r) = self.cell_of(pos);
+        let (up, down) = ((r + self.rows - 1) % self.rows, (r + 1) % self.rows);
+        let (left, right) = ((c + self.cols - 1) % self.cols, (c + 1) % self.cols);
+        [(c, r), (c, up), (right, r), (c, down), (left, r)].map(|(c, r)| layer[self.index(c, r)])
+    }
+
+    /// `team`'s whole layer, row-major (empty until the team first deposits)
+    pub fn layer(&self, team: u32) -> &[f32] {
+        self.layers.get(team as usize).map_or(&[], |l| &l[..])
+    }
+
+    pub(crate) fn hash_into(&self, h: &mut crate::StateHasher) {
+        h.word(self.layers.len() as u32);
+        for layer in &self.layers {
+            h.floats(layer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deposits_decay_and_wrap() {
+        let mut grid = PheromoneGrid::new(100, 50, 10.0);
+        assert_eq!((grid.cols(), grid.rows()), (10, 5));
+        grid.deposit(1, Vec2 { x: 95.0, y: 5.0 }, 2.0);
+        grid.decay(0.5);
+        // the east neighbour of column 0 wraps; column 9 is its west
+        assert_eq!(grid.sense(1, Vec2 { x: 5.0, y: 5.0 }), [0.0, 0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(grid.sense(1, Vec2 { x: 95.0, y: -45.0 })[0], 1.0);
+        assert_eq!(grid.sense(0, Vec2 { x: 95.0, y: 5.0 }), [0.0; SENSED_CELLS]);
+        assert_eq!(grid.layer(1).len(), 50);
+        assert!(grid.layer(3).is_empty());
+    }
+}
@@ -0,0 +1,175 @@
+//! Offline replay rendering (`neat_train render`): draws replay frames with
+//! tiny-skia and writes them as PNG files or an animated GIF, for reviewing
+//! matches without the web front-end.
+
+use std::fmt;
+use std::io::{self, Write};
+use std::path::Path;
+use tiny_skia::{Color, FillRule, Paint, PathBuilder, Pixmap, Stroke, Transform};
+use crate::replay::ReplayFrame;
+use crate::domain::team_color;
+use crate::{AGENT_STRIDE, IDX_HEALTH, IDX_SHIELD, IDX_TEAM, IDX_X, IDX_Y, WRECK_STRIDE};
+
+/// Agent dot radius in pixels
+const AGENT_RADIUS: f32 = 4.0;
+const WRECK_RADIUS: f32 = 3.0;
+const BULLET_RADIUS: f32 = 1.5;
+
+#[derive(Debug)]
+pub enum RenderError {
+    Io(io::Error),
+    /// Image or GIF encoding failed
+    Encode(String),
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenderError::Io(e) => write!(f, "I/O error: {}", e),
+            RenderError::Encode(e) => write!(f, "encoding failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for RenderError {}
+
+impl From<io::Error> for RenderError {
+    fn from(e: io::Error) -> Self { RenderError::Io(e) }
+}
+
+impl From<gif::EncodingError> for RenderError {
+    fn from(e: gif::EncodingError) -> Self { RenderError::Encode(e.to_string()) }
+}
+
+/// World-to-image mapping
+#[derive(Debug, Clone, Copy)]
+pub struct Canvas {
+    /// World size the replay was recorded in
+    pub world_width: f32,
+    pub world_height: f32,
+    /// Pixels per world unit
+    pub scale: f32,
+}
+
+impl Canvas {
+    /// Canvas fitting a `world_width` x `world_height` world into `max_side` pixels
+    pub fn fit(world_width: u32, world_height: u32, max_side: u32) -> Self {
+        let scale = max_side as f32 / world_width.max(world_height).max(1) as f32;
+        Canvas { world_width: world_width as f32, world_height: world_height as f32, scale }
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (
+            ((self.world_width * self.scale).round() as u32).max(1),
+            ((self.world_height * self.scale).round() as u32).max(1),
+        )
+    }
+
+    /// Draw one frame: wrecks, laser hits, blasts, bullets, then living agents
+    /// (team-colored, with a ring while shielded)
+    pub fn draw(&self, frame: &ReplayFrame) -> Pixmap {
+        let (w, h) = self.size();
+        let mut pixmap = Pixmap::new(w, h).expect("non-zero canvas");
+        pixmap.fill(Color::from_rgba8(16, 16, 24, 255));
+        let s = self.scale;
+        for wreck in frame.wrecks.chunks(WRECK_STRIDE) {
+            fill_circle(&mut pixmap, wreck[0] * s, wreck[1] * s, WRECK_RADIUS, [110, 110, 110]);
+        }
+        let mut lasers = PathBuilder::new();
+        for seg in frame.hits.chunks(4).filter(|c| c.len() == 4) {
+            lasers.move_to(seg[0] * s, seg[1] * s);
+            lasers.line_to(seg[2] * s, seg[3] * s);
+        }
+        if let Some(path) = lasers.finish() {
+            let stroke = Stroke { width: 1.0, ..Default::default() };
+            pixmap.stroke_path(&path, &paint([255, 230, 120]), &stroke, Transform::identity(), None);
+        }
+        for blast in frame.effects.chunks(3).filter(|c| c.len() == 3) {
+            if let Some(ring) = PathBuilder::from_circle(blast[0] * s, blast[1] * s, blast[2] * s) {
+                let stroke = Stroke { width: 1.0, ..Default::default() };
+                pixmap.stroke_path(&ring, &paint([255, 160, 0]), &stroke, Transform::identity(), None);
+            }
+        }
+        for bullet in frame.bullets.chunks(4) {
+            fill_circle(&mut pixmap, bullet[0] * s, bullet[1] * s, BULLET_RADIUS, [240, 240, 240]);
+        }
+        for agent in frame.agents.chunks(AGENT_STRIDE).filter(|a| a[IDX_HEALTH] > 0.0) {
+            let (x, y) = (agent[IDX_X] * s, agent[IDX_Y] * s);
+            fill_circle(&mut pixmap, x, y, AGENT_RADIUS, team_color(agent[IDX_TEAM] as u32));
+            if agent[IDX_SHIELD] > 0.0 {
+                if let Some(ring) = PathBuilder::from_circle(x, y, AGENT_RADIUS + 2.0) {
+                    let stroke = Stroke { width: 1.0, ..Default::default() };
+                    pixmap.stroke_path(&ring, &paint([120, 200, 255]), &stroke, Transform::identity(), None);
+                }
+            }
+        }
+        pixmap
+    }
+
+    /// Write `frames` as `frame_00000.png`, ... into `dir`; returns the count
+    pub fn write_pngs<P: AsRef<Path>>(&self, frames: &[ReplayFrame], dir: P) -> Result<usize, RenderError> {
+        std::fs::create_dir_all(dir.as_ref())?;
+        for (i, frame) in frames.iter().enumerate() {
+            let path = dir.as_ref().join(format!("frame_{:05}.png", i));
+            self.draw(frame).save_png(&path).map_err(|e| RenderError::Encode(e.to_string()))?;
+        }
+        Ok(frames.len())
+    }
+
+    /// Write `frames` as a looping GIF, `delay_cs` hundredths of a second apart
+    pub fn write_gif<W: Write>(&self, frames: &[ReplayFrame], out: W, delay_cs: u16) -> Result<(), RenderError> {
+        let (w, h) = self.size();
+        let (w, h) = (w.min(u16::MAX as u32) as u16, h.min(u16::MAX as u32) as u16);
+        let mut encoder = gif::Encoder::new(out, w, h, &[])?;
+        encoder.set_repeat(gif::Repeat::Infinite)?;
+        for frame in frames {
+            // the background is opaque, so premultiplied RGBA is plain RGBA
+            let mut rgba = self.draw(frame).take();
+            let mut gif_frame = gif::Frame::from_rgba_speed(w, h, &mut rgba, 10);
+            gif_frame.delay = delay_cs;
+            encoder.write_frame(&gif_frame)?;
+        }
+        Ok(())
+    }
+}
+
+fn paint(rgb: [u8; 3]) -> Paint<'static> {
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(rgb[0], rgb[1], rgb[2], 255);
+    paint.anti_alias = true;
+    paint
+}
+
+fn fill_circle(pixmap: &mut Pixmap, x: f32, y: f32, r: f32, rgb: [u8; 3]) {
+    if let Some(circle) = PathBuilder::from_circle(x, y, r) {
+        pixmap.fill_path(&circle, &paint(rgb), FillRule::Winding, Transform::identity(), None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draws_agents_in_team_color() {
+        let frame = ReplayFrame {
+            tick: 1,
+            agents: vec![50.0, 50.0, 1.0, 100.0, 0.0, 0.0, 20.0, 20.0, 0.0, 0.0, 0.0, 0.0],
+            wrecks: vec![20.0, 20.0, 30.0],
+            bullets: Vec::new(),
+            hits: vec![0.0, 90.0, 99.0, 90.0],
+            effects: Vec::new(),
+        };
+        let canvas = Canvas::fit(100, 100, 200);
+        assert_eq!(canvas.size(), (200, 200));
+        let pixmap = canvas.draw(&frame);
+        let px = pixmap.pixel(100, 100).unwrap();
+        assert_eq!([px.red(), px.green(), px.blue()], team_color(1));
+        // the dead agent is drawn as its wreck
+        let px = pixmap.pixel(40, 40).unwrap();
+        assert_eq!([px.red(), px.green(), px.blue()], [110, 110, 110]);
+        let mut gif = Vec::new();
+        canvas.write_gif(&[frame.clone(), frame], &mut gif, 5).unwrap();
+        assert!(gif.starts_with(b"GIF89a"));
+    }
+}
@@ -0,0 +1,258 @@
+//! Replay frames shared by native recording (`run_match_record`) and the
+//! in-browser recorder. A replay is JSONL: one `ReplayFrame` per tick.
+//! `analyze` turns a replay back into health curves and event timelines.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use crate::{Simulation, AGENT_STRIDE, IDX_HEALTH, IDX_TEAM};
+
+/// Snapshot of the flat state buffers after a tick
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayFrame {
+    pub tick: usize,
+    /// `agents_data` copy: [x,y,team,health,shield,last_hit,...]
+    pub agents: Vec<f32>,
+    /// `wrecks_data` copy: [x,y,pool,...]
+    pub wrecks: Vec<f32>,
+    /// `bullets_data` copy: [x,y,damage,ttl,...] (absent in older replays)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bullets: Vec<f32>,
+    /// This tick's laser segments: [x1,y1,x2,y2,...] (absent in older replays)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hits: Vec<f32>,
+    /// This tick's blasts: [x,y,radius,...] (absent in older replays)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub effects: Vec<f32>,
+}
+
+impl ReplayFrame {
+    pub fn capture(sim: &Simulation) -> Self {
+        ReplayFrame {
+            tick: sim.tick_count as usize,
+            agents: sim.agents_data.clone(),
+            wrecks: sim.wrecks_data.clone(),
+            bullets: sim.bullets_data.clone(),
+            hits: sim.hits_data.clone(),
+            effects: sim.effects_data.clone(),
+        }
+    }
+}
+
+/// In-memory frame buffer, filled by `Simulation::step` while recording
+#[derive(Debug, Default, Clone)]
+pub struct ReplayRecorder {
+    frames: Vec<ReplayFrame>,
+}
+
+impl ReplayRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, sim: &Simulation) {
+        self.frames.push(ReplayFrame::capture(sim));
+    }
+
+    pub fn frames(&self) -> &[ReplayFrame] {
+        &self.frames
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Serialize as JSONL, the same format `run_match_record` writes
+    pub fn to_jsonl(&self) -> String {
+        let mut out = String::new();
+        for frame in &self.frames {
+            out.push_str(&serde_json::to_string(frame).expect("frame serializes"));
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Parse a JSONL replay (blank lines ignored)
+pub fn parse_jsonl(text: &str) -> Result<Vec<ReplayFrame>, serde_json::Error> {
+    text.lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(serde_json::from_str)
+        .collect()
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    /// A line that is not a `ReplayFrame`
+    Parse(serde_json::Error),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(e) => write!(f, "cannot read replay: {}", e),
+            ReplayError::Parse(e) => write!(f, "invalid replay frame: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<io::Error> for ReplayError {
+    fn from(e: io::Error) -> Self { ReplayError::Io(e) }
+}
+
+impl From<serde_json::Error> for ReplayError {
+    fn from(e: serde_json::Error) -> Self { ReplayError::Parse(e) }
+}
+
+/// Read a JSONL replay file, optionally gzip-compressed
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<ReplayFrame>, ReplayError> {
+    let bytes = std::fs::read(path)?;
+    let mut text = String::new();
+    if bytes.starts_with(&[0x1f, 0x8b]) {
+        GzDecoder::new(&bytes[..]).read_to_string(&mut text)?;
+    } else {
+        text = String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    }
+    Ok(parse_jsonl(&text)?)
+}
+
+/// An agent's health reaching zero
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Kill {
+    pub tick: usize,
+    pub agent: usize,
+    pub team: u32,
+}
+
+/// A living agent's health rising between frames (only looting heals)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Loot {
+    pub tick: usize,
+    pub agent: usize,
+    pub team: u32,
+    /// Net health gained that tick
+    pub amount: f32,
+}
+
+/// What happened over a replay
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReplaySummary {
+    /// Tick of each frame
+    pub ticks: Vec<usize>,
+    /// Total health of each team at each frame
+    pub health: BTreeMap<u32, Vec<f32>>,
+    pub kills: Vec<Kill>,
+    pub loot: Vec<Loot>,
+}
+
+impl ReplaySummary {
+    /// Agents alive per team in the last frame
+    pub fn survivors(&self, frames: &[ReplayFrame]) -> BTreeMap<u32, usize> {
+        let mut alive: BTreeMap<u32, usize> = self.health.keys().map(|&t| (t, 0)).collect();
+        if let Some(last) = frames.last() {
+            for a in last.agents.chunks(AGENT_STRIDE).filter(|a| a[IDX_HEALTH] > 0.0) {
+                *alive.entry(a[IDX_TEAM] as u32).or_default() += 1;
+            }
+        }
+        alive
+    }
+
+    /// Per-tick CSV: team health columns, then kills and loot that tick
+    pub fn write_csv<W: Write>(&self, mut out: W) -> io::Result<()> {
+        let teams: Vec<String> = self.health.keys().map(|t| format!("health_team{}", t)).collect();
+        writeln!(out, "tick,{},kills,loot", teams.join(","))?;
+        for (i, &tick) in self.ticks.iter().enumerate() {
+            let health: Vec<String> = self.health.values().map(|h| h[i].to_string()).collect();
+            let kills = self.kills.iter().filter(|k| k.tick == tick).count();
+            let loot = self.loot.iter().filter(|l| l.tick == tick).fold(0.0, |acc, l| acc + l.amount);
+            writeln!(out, "{},{},{},{}", tick, health.join(","), kills, loot)?;
+        }
+        Ok(())
+    }
+}
+
+/// Health curves, kills, and loot events of a replay
+pub fn analyze(frames: &[ReplayFrame]) -> ReplaySummary {
+    let mut summary = ReplaySummary::default();
+    for frame in frames {
+        for a in frame.agents.chunks(AGENT_STRIDE) {
+            summary.health.entry(a[IDX_TEAM] as u32).or_default();
+        }
+    }
+    let mut prev: Option<&ReplayFrame> = None;
+    for frame in frames {
+        summary.ticks.push(frame.tick);
+        for h in summary.health.values_mut() {
+            h.push(0.0);
+        }
+        for (id, a) in frame.agents.chunks(AGENT_STRIDE).enumerate() {
+            let (team, health) = (a[IDX_TEAM] as u32, a[IDX_HEALTH]);
+            if let Some(h) = summary.health.get_mut(&team) {
+                *h.last_mut().unwrap() += health.max(0.0);
+            }
+            let Some(before) = prev.and_then(|p| p.agents.chunks(AGENT_STRIDE).nth(id)) else { continue };
+            let was = before[IDX_HEALTH];
+            if was > 0.0 && health <= 0.0 {
+                summary.kills.push(Kill { tick: frame.tick, agent: id, team });
+            } else if was > 0.0 && health > was {
+                summary.loot.push(Loot { tick: frame.tick, agent: id, team, amount: health - was });
+            }
+        }
+        prev = Some(frame);
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recording_round_trips_through_jsonl() {
+        let mut sim = Simulation::new_seeded(100, 100, 1, 1, 1, 1, 3);
+        sim.start_recording();
+        sim.step_n(5, false);
+        let rec = sim.stop_recording().unwrap();
+        assert_eq!(rec.len(), 5);
+        assert_eq!(rec.frames()[4].tick, 5);
+        let parsed = parse_jsonl(&rec.to_jsonl()).unwrap();
+        assert_eq!(parsed, rec.frames());
+        assert!(sim.stop_recording().is_none());
+    }
+
+    #[test]
+    fn analysis_finds_kills_and_loot() {
+        let frame = |tick, healths: [f32; 3]| ReplayFrame {
+            tick,
+            agents: healths.iter().enumerate()
+                .flat_map(|(i, &h)| [0.0, 0.0, (i % 2) as f32, h, 0.0, 0.0])
+                .collect(),
+            wrecks: Vec::new(),
+            bullets: Vec::new(),
+            hits: Vec::new(),
+            effects: Vec::new(),
+        };
+        let frames = vec![frame(1, [50.0, 40.0, 30.0]), frame(2, [20.0, 45.0, 0.0]), frame(3, [20.0, 45.0, 0.0])];
+        let summary = analyze(&frames);
+        assert_eq!(summary.health[&0], vec![80.0, 20.0, 20.0]);
+        assert_eq!(summary.health[&1], vec![40.0, 45.0, 45.0]);
+        assert_eq!(summary.kills, vec![Kill { tick: 2, agent: 2, team: 0 }]);
+        assert_eq!(summary.loot, vec![Loot { tick: 2, agent: 1, team: 1, amount: 5.0 }]);
+        assert_eq!(summary.survivors(&frames), BTreeMap::from([(0, 1), (1, 1)]));
+        let mut csv = Vec::new();
+        summary.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(csv.lines().next(), Some("tick,health_team0,health_team1,kills,loot"));
+        assert_eq!(csv.lines().nth(2), Some("2,20,45,1,5"));
+    }
+}
@@ -0,0 +1,381 @@
+//! Brain scripted in [Rhai](https://rhai.rs), so opponents can be written
+//! and tuned without recompiling (feature `scripting`).
+//!
+//! A script defines one function, called once per tick:
+//!
+//! ```rhai
+//! // kite the nearest enemy at the edge of laser range
+//! fn think(view, inputs) {
+//!     if view.enemy.dist < 0.6 * view.range {
+//!         return thrust(-view.enemy.dx, -view.enemy.dy);
+//!     } else if view.enemy.dist <= view.range {
+//!         return fire();
+//!     }
+//!     thrust(view.enemy.dx, view.enemy.dy)
+//! }
+//! ```
+//!
+//! `view` is a map with the fields in `VIEW_FIELDS` (missing targets read as
+//! distance `f32::MAX`; Rhai's float comparisons misbehave on `inf`),
+//! `inputs` is the 0-based sensor array, and returning `thrust(x, y)`,
+//! `fire()`, `loot()`, `idle()` or `()` picks the action.
+//! Besides Rhai's own library the functions in `BUILTINS` are available.
+//! Each call is capped at `MAX_OPERATIONS`; runtime errors, including
+//! reading a field that does not exist, make the tick `Idle`.
+//!
+//! `ScriptBrain::load` watches its file in native builds and picks up edits
+//! while the simulation runs; a broken edit keeps the previous version.
+//! A `Program` compiled once can be shared by any number of brains.
+
+use std::fmt;
+use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::{Path, PathBuf};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::SystemTime;
+
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Map, Scope, AST};
+
+use crate::brain::Brain;
+use crate::config::Config;
+use crate::domain::{Action, Vec2, Weapon, WorldView};
+
+/// Laser damage of `fire()`, matching the built-in `NaiveAgent`
+const DAMAGE: f32 = 0.8;
+
+/// Rhai operations one `think` call may run before it is aborted
+pub const MAX_OPERATIONS: u64 = 50_000;
+
+/// Thinks between checks of a loaded script's modification time
+#[cfg(not(target_arch = "wasm32"))]
+const RELOAD_CHECK_INTERVAL: u32 = 30;
+
+/// Fields readable as `view.<name>`
+pub const VIEW_FIELDS: [&str; 24] = [
+    "hp", "shield", "x", "y", "team", "range", "sep_range", "width", "height",
+    "enemies", "allies", "wrecks",
+    "enemy.dx", "enemy.dy", "enemy.dist", "enemy.hp",
+    "ally.dx", "ally.dy", "ally.dist", "ally.hp",
+    "wreck.dx", "wreck.dy", "wreck.dist", "wreck.pool",
+];
+
+/// Functions registered on top of Rhai's standard library, besides the actions
+pub const BUILTINS: [&str; 2] = ["len", "clamp"];
+
+#[derive(Debug)]
+pub enum ScriptError {
+    #[cfg(not(target_arch = "wasm32"))]
+    Io(std::io::Error),
+    /// The source does not compile; `line` is 1-based
+    Parse { line: usize, message: String },
+    /// Evaluation failed (type error, unknown function or field)
+    Runtime(String),
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(not(target_arch = "wasm32"))]
+            ScriptError::Io(e) => write!(f, "could not read script: {}", e),
+            ScriptError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+            ScriptError::Runtime(message) => write!(f, "script error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl From<std::io::Error> for ScriptError {
+    fn from(e: std::io::Error) -> Self { ScriptError::Io(e) }
+}
+
+impl From<Box<EvalAltResult>> for ScriptError {
+    fn from(e: Box<EvalAltResult>) -> Self { ScriptError::Runtime(e.to_string()) }
+}
+
+/// Integers and floats both read as numbers, so `thrust(1, 0)` works
+fn num(v: Dynamic) -> Result<f32, Box<EvalAltResult>> {
+    match v.as_float() {
+        Ok(f) => Ok(f as f32),
+        Err(_) => v.as_int().map(|i| i as f32).map_err(|t| format!("expected a number, got {}", t).into()),
+    }
+}
+
+fn engine() -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_max_operations(MAX_OPERATIONS)
+        .set_fail_on_invalid_map_property(true)
+        .register_type_with_name::<Action>("Action")
+        .register_fn("len", |x: Dynamic, y: Dynamic| Ok::<_, Box<EvalAltResult>>(num(x)?.hypot(num(y)?) as f64))
+        .register_fn("clamp", |v: Dynamic, lo: Dynamic, hi: Dynamic| {
+            Ok::<_, Box<EvalAltResult>>(num(v)?.max(num(lo)?).min(num(hi)?) as f64)
+        })
+        .register_fn("thrust", |x: Dynamic, y: Dynamic| {
+            Ok::<_, Box<EvalAltResult>>(Action::Thrust(Vec2 { x: num(x)?, y: num(y)? }))
+        })
+        // range is filled in from the view once `think` returns
+        .register_fn("fire", || Action::Fire { weapon: Weapon::Laser { damage: DAMAGE, range: 0.0 } })
+        .register_fn("loot", || Action::Loot)
+        .register_fn("idle", || Action::Idle);
+    engine
+}
+
+/// Nearest living agent on (`ally`) or off the own team: offset and health
+fn nearest(view: &WorldView, cfg: &Config, ally: bool) -> Option<(Vec2, f32)> {
+    (0..view.positions.len())
+        .filter(|&j| j != view.self_idx && view.healths[j] > 0.0 && (view.teams[j] == view.self_team) == ally)
+        .map(|j| (view.delta(view.positions[j], cfg), view.healths[j]))
+        .min_by(|a, b| a.0.length().total_cmp(&b.0.length()))
+}
+
+/// Nearest wreck with loot left: offset and pool
+fn nearest_wreck(view: &WorldView, cfg: &Config) -> Option<(Vec2, f32)> {
+    (0..view.wreck_positions.len())
+        .filter(|&w| view.wreck_pools[w] > 0.0)
+        .map(|w| (view.delta(view.wreck_positions[w], cfg), view.wreck_pools[w]))
+        .min_by(|a, b| a.0.length().total_cmp(&b.0.length()))
+}
+
+fn view_field(view: &WorldView, field: &str) -> f32 {
+    let cfg = Config::default();
+    let target = |t: Option<(Vec2, f32)>, part: &str| match (t, part) {
+        (Some((d, _)), "dx") => d.x,
+        (Some((d, _)), "dy") => d.y,
+        (Some((d, _)), "dist") => d.length(),
+        (Some((_, v)), _) => v,
+        (None, "dist") => f32::MAX,
+        (None, _) => 0.0,
+    };
+    let living = |ally: bool| {
+        (0..view.positions.len())
+            .filter(|&j| j != view.self_idx && view.healths[j] > 0.0 && (view.teams[j] == view.self_team) == ally)
+            .count() as f32
+    };
+    match field.split_once('.') {
+        Some(("enemy", part)) => target(nearest(view, &cfg, false), part),
+        Some(("ally", part)) => target(nearest(view, &cfg, true), part),
+        Some((_, part)) => target(nearest_wreck(view, &cfg), part),
+        None => match field {
+            "hp" => view.self_health,
+            "shield" => view.self_shield,
+            "x" => view.self_pos.x,
+            "y" => view.self_pos.y,
+            "team" => view.self_team as f32,
+            "range" => view.attack_range,
+            "sep_range" => view.sep_range,
+            "width" => view.world_width,
+            "height" => view.world_height,
+            "enemies" => living(false),
+            "allies" => living(true),
+            _ => view.wreck_pools.iter().filter(|&&p| p > 0.0).count() as f32,
+        },
+    }
+}
+
+/// `view` as the script sees it: flat fields plus one map per target
+fn view_map(view: &WorldView) -> Map {
+    let mut map = Map::new();
+    for field in VIEW_FIELDS {
+        let value = Dynamic::from_float(view_field(view, field) as f64);
+        match field.split_once('.') {
+            Some((target, part)) => {
+                let entry = map.entry(target.into()).or_insert_with(|| Map::new().into());
+                if let Some(mut sub) = entry.write_lock::<Map>() {
+                    sub.insert(part.into(), value);
+                }
+            }
+            None => {
+                map.insert(field.into(), value);
+            }
+        }
+    }
+    map
+}
+
+/// A compiled script defining `think(view, inputs)`
+pub struct Program {
+    engine: Engine,
+    ast: AST,
+}
+
+impl Program {
+    pub fn parse(source: &str) -> Result<Self, ScriptError> {
+        let engine = engine();
+        let ast = engine.compile(source).map_err(|e| ScriptError::Parse {
+            line: e.position().line().unwrap_or(1),
+            message: e.err_type().to_string(),
+        })?;
+        if !ast.iter_functions().any(|f| f.name == "think" && f.params.len() == 2) {
+            return Err(ScriptError::Parse { line: 1, message: "no think(view, inputs) function".into() });
+        }
+        Ok(Program { engine, ast })
+    }
+
+    /// Read and compile the script at `path`
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, ScriptError> {
+        Program::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Run `think` for one agent
+    pub fn think(&self, view: &WorldView, inputs: &[f32]) -> Result<Action, ScriptError> {
+        let inputs: Array = inputs.iter().map(|&v| Dynamic::from_float(v as f64)).collect();
+        let result: Dynamic = self.engine.call_fn(&mut Scope::new(), &self.ast, "think", (view_map(view), inputs))?;
+        if result.is_unit() {
+            return Ok(Action::Idle);
+        }
+        match result.try_cast::<Action>() {
+            Some(Action::Fire { weapon: Weapon::Laser { damage, .. } }) => {
+                Ok(Action::Fire { weapon: Weapon::Laser { damage, range: view.attack_range } })
+            }
+            Some(action) => Ok(action),
+            None => Err(ScriptError::Runtime("think returned something other than an action".into())),
+        }
+    }
+}
+
+pub struct ScriptBrain {
+    program: Arc<Program>,
+    /// Runtime error from the latest think, if any
+    pub last_error: Option<String>,
+    #[cfg(not(target_arch = "wasm32"))]
+    watch: Option<Watch>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+struct Watch {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    countdown: u32,
+}
+
+impl ScriptBrain {
+    pub fn from_source(source: &str) -> Result<Self, ScriptError> {
+        Ok(ScriptBrain::from_program(Arc::new(Program::parse(source)?)))
+    }
+
+    /// Run an already compiled script (never reloaded)
+    pub fn from_program(program: Arc<Program>) -> Self {
+        ScriptBrain {
+            program,
+            last_error: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            watch: None,
+        }
+    }
+
+    /// Load the script at `path`, reloading it whenever the file changes
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ScriptError> {
+        let path = path.as_ref().to_path_buf();
+        let modified = std::fs::metadata(&path)?.modified().ok();
+        let mut brain = ScriptBrain::from_program(Arc::new(Program::read(&path)?));
+        brain.watch = Some(Watch { path, modified, countdown: RELOAD_CHECK_INTERVAL });
+        Ok(brain)
+    }
+
+    /// Re-parse the watched file if its modification time moved
+    #[cfg(not(target_arch = "wasm32"))]
+    fn poll_reload(&mut self) {
+        let Some(watch) = &mut self.watch else { return };
+        watch.countdown = watch.countdown.saturating_sub(1);
+        if watch.countdown > 0 {
+            return;
+        }
+        watch.countdown = RELOAD_CHECK_INTERVAL;
+        let modified = std::fs::metadata(&watch.path).and_then(|m| m.modified()).ok();
+        if modified.is_none() || modified == watch.modified {
+            return;
+        }
+        watch.modified = modified;
+        match Program::read(&watch.path) {
+            Ok(program) => {
+                tracing::info!(path = %watch.path.display(), "reloaded script");
+                self.program = Arc::new(program);
+            }
+            Err(e) => tracing::warn!(path = %watch.path.display(), error = %e, "script reload failed, keeping the previous version"),
+        }
+    }
+}
+
+impl Brain for ScriptBrain {
+    fn think(&mut self, view: &WorldView, inputs: &[f32]) -> Action {
+        #[cfg(not(target_arch = "wasm32"))]
+        self.poll_reload();
+        match self.program.think(view, inputs) {
+            Ok(action) => {
+                self.last_error = None;
+                action
+            }
+            Err(e) => {
+                self.last_error = Some(e.to_string());
+                Action::Idle
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KITER: &str = "
+        // kite the nearest enemy at the edge of laser range
+        fn think(view, inputs) {
+            let e = view.enemy.dist;
+            if e < 0.6 * view.range {
+                return thrust(-view.enemy.dx, -view.enemy.dy);
+            } else if e <= view.range && inputs.len() > 0 {
+                return fire();
+            }
+            thrust(view.enemy.dx, view.enemy.dy)
+        }
+    ";
+
+    #[test]
+    fn script_thinks_and_reports_errors() {
+        let positions = [Vec2 { x: 100.0, y: 100.0 }, Vec2 { x: 110.0, y: 100.0 }];
+        let (teams, healths, shields) = ([0, 1], [100.0, 100.0], [0.0, 0.0]);
+        let view = WorldView {
+            self_idx: 0,
+            self_pos: positions[0],
+            self_team: 0,
+            self_health: 100.0,
+            self_shield: 0.0,
+            positions: &positions,
+            teams: &teams,
+            healths: &healths,
+            shields: &shields,
+            wreck_positions: &[],
+            wreck_pools: &[],
+            world_width: 400.0,
+            world_height: 400.0,
+            attack_range: 50.0,
+            sep_range: 10.0,
+        };
+        let mut brain = ScriptBrain::from_source(KITER).unwrap();
+        assert!(matches!(brain.think(&view, &[1.0]), Action::Thrust(v) if v.x < 0.0));
+        let shared = Arc::new(Program::parse(KITER).unwrap());
+        let (mut one, mut two) = (ScriptBrain::from_program(shared.clone()), ScriptBrain::from_program(shared));
+        assert!(matches!((one.think(&view, &[1.0]), two.think(&view, &[1.0])), (Action::Thrust(_), Action::Thrust(_))));
+
+        let far = Program::parse("fn think(v, i) { if v.enemy.dist > 5 { return fire(); } }").unwrap();
+        assert!(matches!(far.think(&view, &[]), Ok(Action::Fire { weapon: Weapon::Laser { range, .. } }) if range == 50.0));
+        let nothing = Program::parse("fn think(v, i) { if i.len() < 3 { return; } loot() }").unwrap();
+        assert!(matches!(nothing.think(&view, &[0.0]), Ok(Action::Idle)));
+        let no_target = Program::parse("fn think(v, i) { if v.wreck.dist > v.width { idle() } else { loot() } }").unwrap();
+        assert!(matches!(no_target.think(&view, &[]), Ok(Action::Idle)));
+
+        let err = Program::parse("fn think(view, inputs) {\n  return fire(;\n}").err().expect("does not compile");
+        assert!(err.to_string().starts_with("line 2"), "{}", err);
+        assert!(Program::parse("fn act(view, inputs) { fire() }").is_err());
+
+        for broken in ["view.speed", "shoot()", "view.hp + fire()", "loop {}"] {
+            let mut bad = ScriptBrain::from_source(&format!("fn think(view, inputs) {{ {} }}", broken)).unwrap();
+            assert!(matches!(bad.think(&view, &[]), Action::Idle), "{}", broken);
+            assert!(bad.last_error.is_some(), "{}", broken);
+        }
+    }
+}
@@ -0,0 +1,183 @@
+//! Hand-written opponents with distinct strategies, as baselines stronger
+//! and more varied than `NaiveAgent`. `by_name` resolves the names the CLI
+//! and `EvolutionConfig::baseline` use.
+
+use crate::ai::{NaiveAgent, NaiveBrain};
+use crate::brain::Brain;
+use crate::config::Config;
+use crate::domain::{Action, Vec2, Weapon, WorldView};
+
+/// Thrust magnitude, matching the built-in `NaiveAgent`
+const SPEED: f32 = 1.2;
+/// Laser damage, matching the built-in `NaiveAgent`
+const DAMAGE: f32 = 0.8;
+
+/// Every registered opponent name
+pub const NAMES: [&str; 5] = ["naive", "kiter", "rusher", "camper", "looter"];
+
+/// The scripted opponent registered as `name` (case-insensitive)
+pub fn by_name(name: &str) -> Option<Box<dyn Brain>> {
+    let brain: Box<dyn Brain> = match name.to_ascii_lowercase().as_str() {
+        "naive" => Box::new(NaiveBrain(NaiveAgent::new(SPEED, DAMAGE))),
+        "kiter" => Box::new(Kiter),
+        "rusher" => Box::new(Rusher),
+        "camper" => Box::new(Camper { home: None }),
+        "looter" => Box::new(Looter),
+        _ => return None,
+    };
+    Some(brain)
+}
+
+/// Nearest living enemy and the offset to it
+fn nearest_enemy(view: &WorldView, cfg: &Config) -> Option<(usize, Vec2)> {
+    (0..view.positions.len())
+        .filter(|&j| j != view.self_idx && view.healths[j] > 0.0 && view.teams[j] != view.self_team)
+        .map(|j| (j, view.delta(view.positions[j], cfg)))
+        .min_by(|a, b| a.1.length().total_cmp(&b.1.length()))
+}
+
+fn toward(delta: Vec2) -> Action {
+    let d = delta.normalize();
+    Action::Thrust(Vec2 { x: d.x * SPEED, y: d.y * SPEED })
+}
+
+fn away(delta: Vec2) -> Action {
+    toward(Vec2 { x: -delta.x, y: -delta.y })
+}
+
+fn fire(view: &WorldView) -> Action {
+    Action::Fire { weapon: Weapon::Laser { damage: DAMAGE, range: view.attack_range } }
+}
+
+/// Holds the nearest enemy near the edge of laser range: fires from there,
+/// backs off when it closes in
+pub struct Kiter;
+
+impl Brain for Kiter {
+    fn think(&mut self, view: &WorldView, _inputs: &[f32]) -> Action {
+        let cfg = Config::default();
+        let Some((_, delta)) = nearest_enemy(view, &cfg) else { return Action::Idle };
+        let dist = delta.length();
+        if dist < 0.6 * view.attack_range {
+            away(delta)
+        } else if dist <= view.attack_range {
+            fire(view)
+        } else {
+            toward(delta)
+        }
+    }
+}
+
+/// Charges the nearest enemy and fires whenever it is in range, never retreating
+pub struct Rusher;
+
+impl Brain for Rusher {
+    fn think(&mut self, view: &WorldView, _inputs: &[f32]) -> Action {
+        let cfg = Config::default();
+        match nearest_enemy(view, &cfg) {
+            Some((_, delta)) if delta.length() <= view.attack_range => fire(view),
+            Some((_, delta)) => toward(delta),
+            None => Action::Idle,
+        }
+    }
+}
+
+/// Holds its spawn point and fires at whatever comes into range
+pub struct Camper {
+    home: Option<Vec2>,
+}
+
+impl Brain for Camper {
+    fn think(&mut self, view: &WorldView, _inputs: &[f32]) -> Action {
+        let cfg = Config::default();
+        let home = *self.home.get_or_insert(view.self_pos);
+        if let Some((_, delta)) = nearest_enemy(view, &cfg) {
+            if delta.length() <= view.attack_range {
+                return fire(view);
+            }
+        }
+        let back = view.delta(home, &cfg);
+        if back.length() > cfg.sep_range { toward(back) } else { Action::Idle }
+    }
+
+    fn reset(&mut self) {
+        self.home = None;
+    }
+}
+
+/// Heads for the richest reachable wreck and loots it, fighting only when
+/// cornered and avoiding enemies while there is nothing to loot
+pub struct Looter;
+
+impl Brain for Looter {
+    fn think(&mut self, view: &WorldView, _inputs: &[f32]) -> Action {
+        let cfg = Config::default();
+        let enemy = nearest_enemy(view, &cfg);
+        if let Some((_, delta)) = enemy {
+            if delta.length() <= 0.5 * view.attack_range {
+                return fire(view);
+            }
+        }
+        // richest pool per unit of distance
+        let wreck = (0..view.wreck_positions.len())
+            .filter(|&w| view.wreck_pools[w] > 0.0)
+            .map(|w| (w, view.delta(view.wreck_positions[w], &cfg)))
+            .max_by(|a, b| {
+                let value = |&(w, d): &(usize, Vec2)| view.wreck_pools[w] / (d.length() + 1.0);
+                value(a).total_cmp(&value(b))
+            });
+        match (wreck, enemy) {
+            (Some((_, delta)), _) if delta.length() <= cfg.loot_range => Action::Loot,
+            (Some((_, delta)), _) => toward(delta),
+            (None, Some((_, delta))) if delta.length() <= view.attack_range => fire(view),
+            (None, Some((_, delta))) => away(delta),
+            (None, None) => Action::Idle,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Simulation;
+
+    #[test]
+    fn every_name_resolves_and_plays() {
+        for name in NAMES {
+            assert!(by_name(name).is_some(), "{}", name);
+        }
+        assert!(by_name("Kiter").is_some());
+        assert!(by_name("sniper").is_none());
+
+        let agents: Vec<(Box<dyn Brain>, u32)> = NAMES.iter().enumerate()
+            .map(|(i, n)| (by_name(n).unwrap(), (i % 2) as u32))
+            .collect();
+        let mut sim = Simulation::with_brains(400, 400, Config::default(), agents);
+        sim.step_n(50, true);
+    }
+
+    #[test]
+    fn kiter_backs_off_when_closed_in() {
+        let positions = [Vec2 { x: 100.0, y: 100.0 }, Vec2 { x: 110.0, y: 100.0 }];
+        let (teams, healths, shields) = ([0, 1], [100.0, 100.0], [0.0, 0.0]);
+        let view = WorldView {
+            self_idx: 0,
+            self_pos: positions[0],
+            self_team: 0,
+            self_health: 100.0,
+            self_shield: 0.0,
+            positions: &positions,
+            teams: &teams,
+            healths: &healths,
+            shields: &shields,
+            wreck_positions: &[],
+            wreck_pools: &[],
+            world_width: 400.0,
+            world_height: 400.0,
+            attack_range: 50.0,
+            sep_range: 10.0,
+        };
+        assert!(matches!(Kiter.think(&view, &[]), Action::Thrust(v) if v.x < 0.0));
+        assert!(matches!(Rusher.think(&view, &[]), Action::Fire { .. }));
+    }
+}
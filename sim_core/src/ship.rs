@@ -0,0 +1,236 @@
+//! Ship classes: per-agent hull, shield, speed, and weapon loadout.
+//!
+//! Agents spawned without a class follow the global `Config` (plus any team
+//! overrides); agents with a class use the class stats instead. A loadout
+//! holds up to `MAX_SLOTS` weapon slots, each with its own cooldown and
+//! ammo, tracked per agent by `SlotState`.
+
+use serde::{Deserialize, Serialize};
+use crate::config::Config;
+use crate::domain::Weapon;
+use crate::status::Status;
+
+/// Laser damage per shot for agents without a class loadout
+pub const DEFAULT_LASER_DAMAGE: f32 = 0.8;
+/// Loadout slots an agent can select between; later entries are never fired
+pub const MAX_SLOTS: usize = 3;
+
+/// Loadout slot picked by a `weapon` network output: [0, 1] split evenly
+/// over `MAX_SLOTS`, values outside clamped
+pub fn slot_for_output(x: f32) -> usize {
+    ((x.clamp(0.0, 1.0) * MAX_SLOTS as f32) as usize).min(MAX_SLOTS - 1)
+}
+
+/// One loadout entry: a weapon and its firing limits
+#[derive(Debug, Clone)]
+pub struct WeaponSlot {
+    pub weapon: Weapon,
+    /// Ticks after a shot before the slot can fire again (0 = every tick)
+    pub cooldown: u32,
+    /// Shots available (None = unlimited)
+    pub ammo: Option<u32>,
+}
+
+impl WeaponSlot {
+    /// Slot without cooldown or ammo limit
+    pub fn new(weapon: Weapon) -> Self {
+        WeaponSlot { weapon, cooldown: 0, ammo: None }
+    }
+
+    pub fn with_cooldown(mut self, ticks: u32) -> Self {
+        self.cooldown = ticks;
+        self
+    }
+
+    pub fn with_ammo(mut self, shots: u32) -> Self {
+        self.ammo = Some(shots);
+        self
+    }
+}
+
+/// Per-agent firing state of each loadout slot; all zero means every slot
+/// is ready and full, so agents without state need none
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SlotState {
+    /// Tick each slot can fire again
+    ready_at: [u32; MAX_SLOTS],
+    /// Shots fired from each slot
+    spent: [u32; MAX_SLOTS],
+}
+
+impl SlotState {
+    /// Shots left in `slot` (None = unlimited)
+    pub fn ammo_left(&self, index: usize, slot: &WeaponSlot) -> Option<u32> {
+        slot.ammo.map(|a| a.saturating_sub(self.spent[index]))
+    }
+
+    /// Whether `slot` (at `index`) is off cooldown and has ammo at `tick`
+    pub fn ready(&self, index: usize, slot: &WeaponSlot, tick: u32) -> bool {
+        tick >= self.ready_at[index] && self.ammo_left(index, slot) != Some(0)
+    }
+
+    /// Record a shot from `slot` at `tick`
+    pub fn fire(&mut self, index: usize, slot: &WeaponSlot, tick: u32) {
+        self.ready_at[index] = tick + 1 + slot.cooldown;
+        self.spent[index] += 1;
+    }
+
+    pub(crate) fn hash_into(&self, h: &mut crate::StateHasher) {
+        for w in self.ready_at.iter().chain(&self.spent) {
+            h.word(*w);
+        }
+    }
+}
+
+/// Stat block and weapon loadout assigned to an agent at spawn
+#[derive(Debug, Clone)]
+pub struct ShipClass {
+    /// Display name (e.g. "tank")
+    pub name: String,
+    /// Maximum (and spawn) health
+    pub hull: f32,
+    /// Maximum (and spawn) shield
+    pub shield: f32,
+    /// Maximum speed per tick
+    pub speed: f32,
+    /// Weapon slots this ship may fire; the first entry is the default
+    pub slots: Vec<WeaponSlot>,
+}
+
+impl ShipClass {
+    /// Class whose weapons fire without cooldown or ammo limits
+    pub fn new(name: &str, hull: f32, shield: f32, speed: f32, weapons: Vec<Weapon>) -> Self {
+        ShipClass::with_slots(name, hull, shield, speed, weapons.into_iter().map(WeaponSlot::new).collect())
+    }
+
+    pub fn with_slots(name: &str, hull: f32, shield: f32, speed: f32, slots: Vec<WeaponSlot>) -> Self {
+        ShipClass { name: name.to_string(), hull, shield, speed, slots }
+    }
+
+    /// Class matching the global config (with team overrides) for `team`
+    pub fn from_config(cfg: &Config, team: u32) -> Self {
+        ShipClass::new(
+            "standard",
+            cfg.team_health_max(team),
+            cfg.max_shield,
+            cfg.team_max_speed(team),
+            vec![Weapon::Laser { damage: DEFAULT_LASER_DAMAGE, range: cfg.team_attack_range(team) }],
+        )
+    }
+
+    /// Slow, heavily armored, short-ranged
+    pub fn tank() -> Self {
+        ShipClass::new("tank", 200.0, 75.0, 0.025, vec![Weapon::Laser { damage: 1.2, range: 35.0 }])
+    }
+
+    /// Fast, fragile, long-ranged
+    pub fn skirmisher() -> Self {
+        ShipClass::new("skirmisher", 60.0, 25.0, 0.07, vec![Weapon::Laser { damage: 0.6, range: 60.0 }])
+    }
+
+    /// Sturdy area denial: flak bursts that punish tight formations
+    pub fn flak() -> Self {
+        ShipClass::new("flak", 120.0, 50.0, 0.035, vec![Weapon::Burst { damage: 2.0, radius: 15.0, range: 40.0 }])
+    }
+
+    /// Mixed loadout: a free laser, a cooldown-bound flak burst with limited
+    /// shells, and a few slow-recharging EMP pulses
+    pub fn gunship() -> Self {
+        ShipClass::with_slots("gunship", 100.0, 50.0, 0.04, vec![
+            WeaponSlot::new(Weapon::Laser { damage: 0.8, range: 50.0 }),
+            WeaponSlot::new(Weapon::Burst { damage: 3.0, radius: 12.0, range: 40.0 }).with_cooldown(30).with_ammo(8),
+            WeaponSlot::new(Weapon::Emp { status: Status::Slowed, ticks: 60, range: 35.0 }).with_cooldown(90).with_ammo(3),
+        ])
+    }
+
+    /// Weapons in slot order
+    pub fn weapons(&self) -> impl Iterator<Item = &Weapon> {
+        self.slots.iter().map(|s| &s.weapon)
+    }
+
+    /// Range of the first laser in the loadout, if any
+    pub fn laser_range(&self) -> Option<f32> {
+        self.weapons().find_map(|w| match w {
+            Weapon::Laser { range, .. } => Some(*range),
+            _ => None,
+        })
+    }
+
+    /// Map a requested weapon onto a selectable slot: the first slot of the
+    /// same kind wins, otherwise the default (first) one. `None` for an empty loadout.
+    pub fn resolve_slot(&self, requested: &Weapon) -> Option<usize> {
+        let selectable = self.slots.len().min(MAX_SLOTS);
+        self.slots[..selectable].iter()
+            .position(|s| std::mem::discriminant(&s.weapon) == std::mem::discriminant(requested))
+            .or(if selectable > 0 { Some(0) } else { None })
+    }
+
+    /// Like `resolve_slot`, returning the slot's weapon
+    pub fn resolve_weapon(&self, requested: &Weapon) -> Option<Weapon> {
+        self.resolve_slot(requested).map(|i| self.slots[i].weapon.clone())
+    }
+
+    /// Slot picked by a `FireSlot` action: `slot` clamped to the selectable
+    /// slots, `None` for an empty loadout
+    pub fn select_slot(&self, slot: usize) -> Option<usize> {
+        let selectable = self.slots.len().min(MAX_SLOTS);
+        if selectable == 0 { None } else { Some(slot.min(selectable - 1)) }
+    }
+
+    /// Normalized stats appended to sensors when `Config::class_sensors` is set:
+    /// [speed, hull, shield, laser range] relative to the global config
+    pub fn sensor_features(&self, cfg: &Config) -> [f32; 4] {
+        [
+            self.speed / cfg.max_speed,
+            self.hull / cfg.health_max,
+            self.shield / cfg.max_shield,
+            self.laser_range().unwrap_or(0.0) / cfg.attack_range,
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_prefers_same_kind_then_default() {
+        let class = ShipClass::new("mixed", 100.0, 50.0, 0.04, vec![
+            Weapon::Laser { damage: 2.0, range: 20.0 },
+            Weapon::Missile { damage: 9.0, speed: 1.0, ttl: 30 },
+        ]);
+        match class.resolve_weapon(&Weapon::Missile { damage: 1.0, speed: 1.0, ttl: 1 }) {
+            Some(Weapon::Missile { damage, .. }) => assert_eq!(damage, 9.0),
+            other => panic!("unexpected {:?}", other),
+        }
+        let lasers_only = ShipClass::tank();
+        match lasers_only.resolve_weapon(&Weapon::Missile { damage: 1.0, speed: 1.0, ttl: 1 }) {
+            Some(Weapon::Laser { range, .. }) => assert_eq!(range, 35.0),
+            other => panic!("unexpected {:?}", other),
+        }
+        let unarmed = ShipClass::new("unarmed", 1.0, 1.0, 1.0, vec![]);
+        assert!(unarmed.resolve_weapon(&Weapon::Laser { damage: 1.0, range: 1.0 }).is_none());
+    }
+
+    #[test]
+    fn slots_enforce_cooldown_and_ammo() {
+        let slot = WeaponSlot::new(Weapon::Laser { damage: 1.0, range: 1.0 }).with_cooldown(2).with_ammo(2);
+        let mut state = SlotState::default();
+        assert!(state.ready(1, &slot, 0));
+        state.fire(1, &slot, 0);
+        assert!(!state.ready(1, &slot, 2));
+        assert!(state.ready(1, &slot, 3) && state.ready(0, &slot, 0));
+        state.fire(1, &slot, 3);
+        assert_eq!(state.ammo_left(1, &slot), Some(0));
+        assert!(!state.ready(1, &slot, 100));
+        assert_eq!(ShipClass::gunship().select_slot(7), Some(2));
+        assert_eq!([slot_for_output(-1.0), slot_for_output(0.5), slot_for_output(1.0)], [0, 1, 2]);
+    }
+
+    #[test]
+    fn from_config_matches_globals() {
+        let cfg = Config::default();
+        let class = ShipClass::from_config(&cfg, 0);
+        assert_eq!(class.sensor_features(&cfg), [1.0, 1.0, 1.0, 1.0]);
+    }
+}
@@ -0,0 +1,61 @@
+//! Per-agent gunnery record: how often an agent pulled the trigger, how many
+//! of those pulls went off, how many struck an enemy, and how much damage
+//! spilled onto teammates. The combat phase keeps it up to date; fitness uses
+//! it to reward accurate, economical fire over spamming the trigger.
+//! Missiles are left out: their bullets carry no shooter to credit hits to.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ShotTally {
+    /// Fire commands for a carried weapon, including ones blocked by a
+    /// cooldown or an empty slot
+    pub trigger_pulls: u32,
+    /// Pulls that went off (spending a round or the weapon's cooldown),
+    /// whether or not anything was in reach
+    pub shots: u32,
+    /// Shots that damaged or disabled an enemy
+    pub hits: u32,
+    /// Damage the agent's shots did to its own team
+    pub friendly_damage: f32,
+}
+
+impl ShotTally {
+    pub fn add(&mut self, other: &ShotTally) {
+        self.trigger_pulls += other.trigger_pulls;
+        self.shots += other.shots;
+        self.hits += other.hits;
+        self.friendly_damage += other.friendly_damage;
+    }
+
+    /// Hits per shot (0 without shots)
+    pub fn accuracy(&self) -> f32 {
+        ratio(self.hits, self.shots)
+    }
+
+    /// Fraction of trigger pulls that went off rather than being wasted on a
+    /// cooldown or an empty slot (0 without pulls)
+    pub fn efficiency(&self) -> f32 {
+        ratio(self.shots, self.trigger_pulls)
+    }
+}
+
+fn ratio(num: u32, den: u32) -> f32 {
+    if den == 0 { 0.0 } else { num as f32 / den as f32 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ratios_are_zero_without_fire() {
+        assert_eq!(ShotTally::default().accuracy(), 0.0);
+        assert_eq!(ShotTally::default().efficiency(), 0.0);
+        let mut t = ShotTally { trigger_pulls: 8, shots: 4, hits: 1, friendly_damage: 0.0 };
+        t.add(&ShotTally { trigger_pulls: 2, shots: 1, hits: 1, friendly_damage: 3.0 });
+        assert_eq!(t.accuracy(), 0.4);
+        assert_eq!(t.efficiency(), 0.5);
+        assert_eq!(t.friendly_damage, 3.0);
+    }
+}
@@ -0,0 +1,214 @@
+//! World-state snapshots for pause/rewind.
+//!
+//! A snapshot captures the flat buffers, tick, and counters; brains, ship
+//! classes, and the RNG are not part of it, so restoring requires the same
+//! set of agents the snapshot was taken with. Brains keep their internal
+//! state across a restore, so recurrent `NeatBrain`s continue from their
+//! current memory rather than the snapshot's.
+
+use std::collections::VecDeque;
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use crate::{Simulation, AGENT_STRIDE};
+use crate::domain::Vec2;
+use crate::facing::Facing;
+use crate::gravity::GravityWell;
+use crate::pheromone::PheromoneGrid;
+use crate::ship::SlotState;
+use crate::shots::ShotTally;
+use crate::status::StatusSet;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub tick: u32,
+    pub agents: Vec<f32>,
+    pub bullets: Vec<f32>,
+    pub wrecks: Vec<f32>,
+    pub hits: Vec<f32>,
+    /// [thrust, fire, idle, loot] counts of the captured tick
+    pub counters: [u32; 4],
+    /// Timed statuses per agent id (absent in older snapshots)
+    #[serde(default)]
+    pub statuses: Vec<StatusSet>,
+    /// Heading and rear shield per agent id (absent in older snapshots)
+    #[serde(default)]
+    pub facings: Vec<Facing>,
+    /// Loadout cooldowns and ammo per agent id (absent in older snapshots)
+    #[serde(default)]
+    pub slot_states: Vec<SlotState>,
+    /// Velocity per agent id (absent in older snapshots)
+    #[serde(default)]
+    pub velocities: Vec<Vec2>,
+    /// Gunnery record per agent id (absent in older snapshots)
+    #[serde(default)]
+    pub shot_tallies: Vec<ShotTally>,
+    /// Stigmergy grid, when active (absent in older snapshots)
+    #[serde(default)]
+    pub pheromones: Option<PheromoneGrid>,
+    /// Generated obstacles (absent in older snapshots)
+    #[serde(default)]
+    pub obstacles: Vec<GravityWell>,
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// Snapshot was taken with a different number of agents
+    AgentCount { expected: usize, found: usize },
+    /// No retained snapshot at or before the requested tick
+    NoSnapshot(u32),
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::AgentCount { expected, found } => {
+                write!(f, "snapshot has {} agents, simulation has {}", found, expected)
+            }
+            SnapshotError::NoSnapshot(tick) => write!(f, "no snapshot at or before tick {}", tick),
+            SnapshotError::Json(e) => write!(f, "invalid snapshot JSON: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl From<serde_json::Error> for SnapshotError {
+    fn from(e: serde_json::Error) -> Self { SnapshotError::Json(e) }
+}
+
+impl Snapshot {
+    pub fn capture(sim: &Simulation) -> Self {
+        Snapshot {
+            tick: sim.tick_count,
+            agents: sim.agents_data.clone(),
+            bullets: sim.bullets_data.clone(),
+            wrecks: sim.wrecks_data.clone(),
+            hits: sim.hits_data.clone(),
+            counters: [sim.thrust_count, sim.fire_count, sim.idle_count, sim.loot_count],
+            statuses: sim.statuses.clone(),
+            facings: sim.facings.clone(),
+            slot_states: sim.slot_states.clone(),
+            velocities: sim.velocities.clone(),
+            shot_tallies: sim.shot_tallies.clone(),
+            pheromones: sim.pheromones.clone(),
+            obstacles: sim.obstacles.clone(),
+        }
+    }
+
+    pub fn agent_count(&self) -> usize {
+        self.agents.len() / AGENT_STRIDE
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("snapshot serializes")
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, SnapshotError> {
+        Ok(serde_json::from_str(text)?)
+    }
+}
+
+/// Bounded ring of automatic snapshots, one every `interval` ticks
+#[derive(Debug, Clone)]
+pub struct SnapshotRing {
+    capacity: usize,
+    interval: u32,
+    snaps: VecDeque<Snapshot>,
+}
+
+impl SnapshotRing {
+    pub fn new(capacity: usize, interval: u32) -> Self {
+        SnapshotRing { capacity: capacity.max(1), interval: interval.max(1), snaps: VecDeque::new() }
+    }
+
+    /// Record `sim` if its tick falls on the interval, evicting the oldest when full
+    pub fn observe(&mut self, sim: &Simulation) {
+        if !sim.tick_count.is_multiple_of(self.interval) {
+            return;
+        }
+        if self.snaps.len() == self.capacity {
+            self.snaps.pop_front();
+        }
+        self.snaps.push_back(Snapshot::capture(sim));
+    }
+
+    /// Ticks of retained snapshots, oldest first
+    pub fn ticks(&self) -> Vec<u32> {
+        self.snaps.iter().map(|s| s.tick).collect()
+    }
+
+    /// Latest snapshot at or before `tick`; newer ones are discarded.
+    /// Leaves the ring untouched when there is none.
+    pub fn rewind_to(&mut self, tick: u32) -> Option<Snapshot> {
+        if !self.snaps.iter().any(|s| s.tick <= tick) {
+            return None;
+        }
+        while self.snaps.back().is_some_and(|s| s.tick > tick) {
+            self.snaps.pop_back();
+        }
+        self.snaps.back().cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.snaps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snaps.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restore_reproduces_state_and_future() {
+        let mut sim = Simulation::new_seeded(100, 100, 2, 2, 2, 2, 5);
+        sim.step_n(10, false);
+        let snap = sim.snapshot();
+        sim.step_n(10, false);
+        let later = sim.state_hash();
+        sim.restore(&Snapshot::from_json(&snap.to_json()).unwrap()).unwrap();
+        assert_eq!(sim.tick_count(), 10);
+        sim.step_n(10, false);
+        assert_eq!(sim.state_hash(), later);
+    }
+
+    #[test]
+    fn restore_rewinds_pheromone_trails() {
+        let mut sim = Simulation::new_seeded(100, 100, 2, 2, 0, 0, 3);
+        sim.config.pheromone_cell = 10.0;
+        sim.step_n(5, false);
+        let snap = sim.snapshot();
+        let trail = sim.pheromones().unwrap().layer(0).to_vec();
+        let (rays, dist) = (sim.config.scan_rays, sim.config.scan_max_dist);
+        let sensed = sim.scan(0, rays, dist);
+        sim.step_n(10, false);
+        assert_ne!(sim.pheromones().unwrap().layer(0), &trail[..]);
+        sim.restore(&Snapshot::from_json(&snap.to_json()).unwrap()).unwrap();
+        assert_eq!(sim.pheromones().unwrap().layer(0), &trail[..]);
+        assert_eq!(sim.scan(0, rays, dist), sensed);
+    }
+
+    #[test]
+    fn restore_rejects_agent_mismatch() {
+        let snap = Simulation::new(100, 100, 1, 1, 0, 0).snapshot();
+        let mut sim = Simulation::new(100, 100, 2, 1, 0, 0);
+        assert!(matches!(sim.restore(&snap), Err(SnapshotError::AgentCount { .. })));
+    }
+
+    #[test]
+    fn ring_is_bounded_and_rewinds() {
+        let mut sim = Simulation::new_seeded(100, 100, 1, 1, 1, 1, 2);
+        sim.enable_auto_snapshots(3, 5);
+        sim.step_n(30, false);
+        assert_eq!(sim.snapshot_ticks(), vec![20, 25, 30]);
+        sim.rewind_to(27).unwrap();
+        assert_eq!(sim.tick_count(), 25);
+        assert_eq!(sim.snapshot_ticks(), vec![20, 25]);
+        assert!(matches!(sim.rewind_to(3), Err(SnapshotError::NoSnapshot(3))));
+        assert_eq!(sim.snapshot_ticks(), vec![20, 25]);
+    }
+}
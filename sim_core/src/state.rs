@@ -0,0 +1,160 @@
+//! Structured, read-only view of the simulation state for front ends.
+//!
+//! Decodes the flat stride buffers into named records so JS/TS code does not
+//! have to know the buffer layouts. Field names serialize in camelCase; the
+//! TypeScript definitions in `wasm_bindings.rs` mirror these structs.
+
+use serde::Serialize;
+use crate::{Simulation, AGENT_STRIDE, EFFECT_STRIDE, WRECK_STRIDE};
+use crate::{IDX_X, IDX_Y, IDX_TEAM, IDX_HEALTH, IDX_SHIELD, IDX_LAST_HIT};
+use crate::{IDX_WRECK_X, IDX_WRECK_Y, IDX_WRECK_POOL};
+
+/// Floats per bullet record: [x, y, damage, ttl]
+const BULLET_STRIDE: usize = 4;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentView {
+    pub id: usize,
+    pub x: f32,
+    pub y: f32,
+    pub team: u32,
+    pub health: f32,
+    pub shield: f32,
+    pub last_hit: u32,
+    pub alive: bool,
+    /// Ship class name, if the agent has one
+    pub class: Option<String>,
+    /// Unit vector of the agent's last thrust
+    pub heading: [f32; 2],
+    /// Rear shield (directional shields only; 0 otherwise)
+    pub rear_shield: f32,
+    /// Active statuses ("slowed", "weakened", "disrupted")
+    pub status: Vec<&'static str>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WreckView {
+    pub x: f32,
+    pub y: f32,
+    pub pool: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BulletView {
+    pub x: f32,
+    pub y: f32,
+    pub damage: f32,
+    pub ttl: f32,
+}
+
+/// Hitscan segment from shooter to target
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HitView {
+    pub x1: f32,
+    pub y1: f32,
+    pub x2: f32,
+    pub y2: f32,
+}
+
+/// Area-of-effect blast
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EffectView {
+    pub x: f32,
+    pub y: f32,
+    pub radius: f32,
+}
+
+/// Action counts for the last tick
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Counters {
+    pub thrust: u32,
+    pub fire: u32,
+    pub idle: u32,
+    pub loot: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimState {
+    pub tick: u32,
+    pub width: u32,
+    pub height: u32,
+    pub agents: Vec<AgentView>,
+    pub wrecks: Vec<WreckView>,
+    pub bullets: Vec<BulletView>,
+    pub hits: Vec<HitView>,
+    pub effects: Vec<EffectView>,
+    pub counters: Counters,
+}
+
+impl SimState {
+    pub fn capture(sim: &Simulation) -> Self {
+        let agents = sim.agents_data.chunks(AGENT_STRIDE).enumerate()
+            .map(|(id, a)| AgentView {
+                id,
+                x: a[IDX_X],
+                y: a[IDX_Y],
+                team: a[IDX_TEAM] as u32,
+                health: a[IDX_HEALTH],
+                shield: a[IDX_SHIELD],
+                last_hit: a[IDX_LAST_HIT] as u32,
+                alive: a[IDX_HEALTH] > 0.0,
+                class: sim.agent_class(id).map(|c| c.name.clone()),
+                heading: [sim.agent_facing(id).heading.x, sim.agent_facing(id).heading.y],
+                rear_shield: if sim.config.directional_shields {
+                    sim.agent_facing(id).rear_shield(sim.agent_max_shield(id))
+                } else {
+                    0.0
+                },
+                status: sim.agent_status(id).names(),
+            })
+            .collect();
+        let wrecks = sim.wrecks_data.chunks(WRECK_STRIDE)
+            .map(|w| WreckView { x: w[IDX_WRECK_X], y: w[IDX_WRECK_Y], pool: w[IDX_WRECK_POOL] })
+            .collect();
+        let bullets = sim.bullets_data.chunks(BULLET_STRIDE)
+            .map(|b| BulletView { x: b[0], y: b[1], damage: b[2], ttl: b[3] })
+            .collect();
+        let hits = sim.hits_data.chunks(4)
+            .map(|h| HitView { x1: h[0], y1: h[1], x2: h[2], y2: h[3] })
+            .collect();
+        let effects = sim.effects_data.chunks(EFFECT_STRIDE)
+            .map(|e| EffectView { x: e[0], y: e[1], radius: e[2] })
+            .collect();
+        SimState {
+            tick: sim.tick_count,
+            width: sim.width,
+            height: sim.height,
+            agents,
+            wrecks,
+            bullets,
+            hits,
+            effects,
+            counters: Counters {
+                thrust: sim.thrust_count,
+                fire: sim.fire_count,
+                idle: sim.idle_count,
+                loot: sim.loot_count,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capture_decodes_flat_buffers() {
+        let mut sim = Simulation::new_seeded(100, 100, 2, 0, 0, 2, 1);
+        sim.step();
+        let state = SimState::capture(&sim);
+        assert_eq!(state.tick, 1);
+        assert_eq!(state.agents.len(), sim.agent_count());
+        assert_eq!(state.agents[3].team, 3);
+        assert_eq!(state.agents[3].x, sim.agents_data[3 * AGENT_STRIDE + IDX_X]);
+        let json = serde_json::to_value(&state).unwrap();
+        assert!(json["agents"][0].get("lastHit").is_some());
+    }
+}
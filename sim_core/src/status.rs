@@ -0,0 +1,109 @@
+//! Timed status effects: a bitset of active statuses per agent with a
+//! countdown for each. Special weapons (`Weapon::Emp`) apply them; the
+//! movement, combat and shield-regen phases consult them, and they tick
+//! down once per simulation step.
+
+use serde::{Deserialize, Serialize};
+
+/// Max-speed multiplier while `Slowed`
+pub const SLOW_FACTOR: f32 = 0.5;
+/// Outgoing-damage multiplier while `Weakened`
+pub const WEAKEN_FACTOR: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    /// Max speed scaled by `SLOW_FACTOR`
+    Slowed,
+    /// Weapon damage scaled by `WEAKEN_FACTOR`
+    Weakened,
+    /// No shield regeneration
+    Disrupted,
+}
+
+impl Status {
+    /// Every status, in bit order
+    pub const ALL: [Status; 3] = [Status::Slowed, Status::Weakened, Status::Disrupted];
+
+    pub fn bit(self) -> u8 {
+        1 << self as u8
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Status::Slowed => "slowed",
+            Status::Weakened => "weakened",
+            Status::Disrupted => "disrupted",
+        }
+    }
+}
+
+/// One agent's active statuses and their remaining ticks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct StatusSet {
+    bits: u8,
+    timers: [u32; Status::ALL.len()],
+}
+
+impl StatusSet {
+    /// Inflict `status` for `ticks` ticks; a longer running timer is kept
+    pub fn apply(&mut self, status: Status, ticks: u32) {
+        if ticks == 0 {
+            return;
+        }
+        let t = &mut self.timers[status as usize];
+        *t = (*t).max(ticks);
+        self.bits |= status.bit();
+    }
+
+    pub fn has(&self, status: Status) -> bool {
+        self.bits & status.bit() != 0
+    }
+
+    /// Active statuses as a bitset of `Status::bit`s
+    pub fn bits(&self) -> u8 {
+        self.bits
+    }
+
+    /// Ticks `status` still has to run
+    pub fn remaining(&self, status: Status) -> u32 {
+        self.timers[status as usize]
+    }
+
+    /// Count every timer down one tick, clearing the ones that run out
+    pub fn tick(&mut self) {
+        for status in Status::ALL {
+            let t = &mut self.timers[status as usize];
+            if *t > 0 {
+                *t -= 1;
+                if *t == 0 {
+                    self.bits &= !status.bit();
+                }
+            }
+        }
+    }
+
+    /// Names of the active statuses
+    pub fn names(&self) -> Vec<&'static str> {
+        Status::ALL.iter().filter(|s| self.has(**s)).map(|s| s.name()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statuses_expire_independently() {
+        let mut set = StatusSet::default();
+        set.apply(Status::Slowed, 2);
+        set.apply(Status::Disrupted, 1);
+        set.apply(Status::Slowed, 1);
+        assert_eq!(set.names(), ["slowed", "disrupted"]);
+        set.tick();
+        assert!(set.has(Status::Slowed) && !set.has(Status::Disrupted));
+        assert_eq!(set.remaining(Status::Slowed), 1);
+        set.tick();
+        assert_eq!(set.bits(), 0);
+    }
+}
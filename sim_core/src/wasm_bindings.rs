@@ -1,11 +1,14 @@
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsValue;
 use js_sys::Float32Array;
-use crate::Simulation;
-use crate::config::DistanceMode;
+use crate::{Simulation, SimState, Action, Vec2};
+use wasm_bindgen::JsCast;
+use crate::config::{DistanceMode, Falloff};
 use serde_json;
 use crate::neat::genome::Genome;
 use crate::neat::brain::NeatBrain;
+use crate::neat::bundle::{genome_for_config, trained_with_legacy_motion, BundleError};
+use crate::js_brain::JsBrain;
 
 /// WebAssembly bindings for Simulation
 #[wasm_bindgen]
@@ -21,17 +24,92 @@ impl WasmSimulation {
         WasmSimulation { inner: Simulation::new(width, height, orange, yellow, green, blue) }
     }
 
+    /// Reproducible constructor: the same seed yields the same match in any browser
+    #[wasm_bindgen(js_name = newSeeded)]
+    pub fn new_seeded(width: u32, height: u32, orange: u32, yellow: u32, green: u32, blue: u32, seed: u32) -> WasmSimulation {
+        WasmSimulation { inner: Simulation::new_seeded(width, height, orange, yellow, green, blue, seed as u64) }
+    }
+
+    /// Seed this simulation was built with (random unless given explicitly)
+    pub fn seed(&self) -> u32 {
+        self.inner.seed() as u32
+    }
+
     /// Step the simulation one tick
     pub fn step(&mut self) {
         self.inner.step();
     }
 
+    /// Run up to `n` ticks in one call (e.g. several ticks per animation frame).
+    /// With `stop_when_decided`, stops once one team remains. `on_tick(tick)` is
+    /// called after each tick if given; returning `false` stops the batch.
+    /// Hits from the whole batch are kept. Returns the number of ticks run.
+    #[wasm_bindgen(js_name = stepN)]
+    pub fn step_n(&mut self, n: u32, stop_when_decided: Option<bool>, on_tick: Option<js_sys::Function>) -> u32 {
+        let stop = stop_when_decided.unwrap_or(false);
+        match on_tick {
+            None => self.inner.step_n(n, stop),
+            Some(cb) => self.inner.step_n_with(n, stop, |sim| {
+                cb.call1(&JsValue::NULL, &JsValue::from(sim.tick_count()))
+                    .map(|ret| ret.as_bool() != Some(false))
+                    .unwrap_or(false)
+            }),
+        }
+    }
+
+    /// Feed `dt_ms` of real time (e.g. the delta between animation frames) and
+    /// run the whole ticks it covers at the config's `tick_rate_hz`. Returns
+    /// the interpolation alpha: draw `prevAgentsData()` lerped toward
+    /// `agents_data()` by it. `tickCount()` tells how many ticks ran.
+    pub fn advance(&mut self, dt_ms: f32) -> f32 {
+        self.inner.advance(dt_ms).alpha
+    }
+
+    /// Agent flat data as of the tick before the last one `advance` ran
+    #[wasm_bindgen(js_name = prevAgentsData)]
+    pub fn prev_agents_data(&self) -> Float32Array {
+        Float32Array::from(self.inner.prev_agents_data())
+    }
+
+    /// True once at most one team has living agents
+    #[wasm_bindgen(js_name = isDecided)]
+    pub fn is_decided(&self) -> bool {
+        self.inner.is_decided()
+    }
+
     /// Get agent flat data: [x,y,team,health,shield,last_hit,...]
     pub fn agents_data(&self) -> Float32Array {
         let vec = self.inner.agents_data.clone();
         Float32Array::from(&vec[..])
     }
 
+    /// Get bullet flat data: [x,y,damage,ttl,...]
+    pub fn bullets_data(&self) -> Float32Array {
+        Float32Array::from(&self.inner.bullets_data[..])
+    }
+
+    /// Get this tick's hitscan segments: [x1,y1,x2,y2,...]
+    pub fn hits_data(&self) -> Float32Array {
+        Float32Array::from(&self.inner.hits_data[..])
+    }
+
+    /// Get this tick's blasts: [x,y,radius,...]
+    pub fn effects_data(&self) -> Float32Array {
+        Float32Array::from(self.inner.effects_data())
+    }
+
+    /// Lay out a procedural map from the config's `map_gen` knobs and `seed`
+    #[wasm_bindgen(js_name = generateMap)]
+    pub fn generate_map(&mut self, seed: u32) {
+        self.inner.generate_map(seed as u64);
+    }
+
+    /// Get gravity wells: [x,y,strength,radius,...]
+    #[wasm_bindgen(js_name = wellsData)]
+    pub fn wells_data(&self) -> Float32Array {
+        Float32Array::from(&self.inner.wells_data()[..])
+    }
+
     /// Get wreck flat data: [x,y,pool,...]
     pub fn wrecks_data(&self) -> Float32Array {
         let vec = self.inner.wrecks_data.clone();
@@ -65,6 +143,18 @@ impl WasmSimulation {
         self.inner.config.distance_mode = dm;
     }
 
+    /// Sets laser damage falloff: "none", "linear" or "quadratic"
+    #[wasm_bindgen(js_name = setLaserFalloff)]
+    pub fn set_laser_falloff(&mut self, mode: &str) -> Result<(), JsValue> {
+        self.inner.config.laser_falloff = match mode {
+            "none" => Falloff::None,
+            "linear" => Falloff::Linear,
+            "quadratic" => Falloff::Quadratic,
+            other => return Err(JsValue::from_str(&format!("unknown falloff `{}`", other))),
+        };
+        Ok(())
+    }
+
     // Expose memory pointers and stats
     #[wasm_bindgen(js_name = agentsPtr)]
     pub fn agents_ptr(&self) -> *const f32 {
@@ -76,6 +166,12 @@ impl WasmSimulation {
         self.inner.agents_data.len()
     }
 
+    /// Pointer to bullets_data buffer (4 floats per bullet)
+    #[wasm_bindgen(js_name = bulletsPtr)]
+    pub fn bullets_ptr(&self) -> *const f32 {
+        self.inner.bullets_data.as_ptr()
+    }
+
     #[wasm_bindgen(js_name = bulletsLen)]
     pub fn bullets_len(&self) -> usize {
         self.inner.bullets_data.len()
@@ -148,6 +244,415 @@ impl WasmSimulation {
     }
 }
 
+// N-team support
+#[wasm_bindgen]
+impl WasmSimulation {
+    /// Free-for-all with `counts[t]` naive agents on team t
+    #[wasm_bindgen(js_name = newTeams)]
+    pub fn new_teams(width: u32, height: u32, counts: Vec<u32>) -> WasmSimulation {
+        WasmSimulation { inner: Simulation::new_teams(width, height, &counts) }
+    }
+
+    /// Seeded free-for-all with `counts[t]` naive agents on team t
+    #[wasm_bindgen(js_name = newTeamsSeeded)]
+    pub fn new_teams_seeded(width: u32, height: u32, counts: Vec<u32>, seed: u32) -> WasmSimulation {
+        WasmSimulation { inner: Simulation::new_teams_seeded(width, height, &counts, seed as u64) }
+    }
+
+    /// One past the highest team id present
+    #[wasm_bindgen(js_name = teamCount)]
+    pub fn team_count(&self) -> u32 {
+        self.inner.team_count()
+    }
+
+    /// Living agents per team id
+    #[wasm_bindgen(js_name = teamAliveCounts)]
+    pub fn team_alive_counts(&self) -> Vec<u32> {
+        self.inner.team_alive_counts()
+    }
+
+    /// `#rrggbb` display color for a team id
+    #[wasm_bindgen(js_name = teamColor)]
+    pub fn team_color(team: u32) -> String {
+        crate::team_color_hex(team)
+    }
+}
+
+/// Convert a config validation error into a JS exception value
+fn config_err(e: crate::ConfigError) -> JsValue {
+    JsValue::from_str(&e.to_string())
+}
+
+// Runtime tuning setters (throw on invalid values)
+#[wasm_bindgen]
+impl WasmSimulation {
+    #[wasm_bindgen(js_name = setAttackRange)]
+    pub fn set_attack_range(&mut self, range: f32) -> Result<(), JsValue> {
+        self.inner.set_attack_range(range).map_err(config_err)
+    }
+
+    #[wasm_bindgen(js_name = setMaxSpeed)]
+    pub fn set_max_speed(&mut self, speed: f32) -> Result<(), JsValue> {
+        self.inner.set_max_speed(speed).map_err(config_err)
+    }
+
+    #[wasm_bindgen(js_name = setFriction)]
+    pub fn set_friction(&mut self, friction: f32) -> Result<(), JsValue> {
+        self.inner.set_friction(friction).map_err(config_err)
+    }
+
+    #[wasm_bindgen(js_name = setSeparation)]
+    pub fn set_separation(&mut self, range: f32, strength: f32) -> Result<(), JsValue> {
+        self.inner.set_separation(range, strength).map_err(config_err)
+    }
+
+    #[wasm_bindgen(js_name = setShieldParams)]
+    pub fn set_shield_params(&mut self, max_shield: f32, regen_delay: u32, regen_rate: f32) -> Result<(), JsValue> {
+        self.inner.set_shield_params(max_shield, regen_delay, regen_rate).map_err(config_err)
+    }
+
+    #[wasm_bindgen(js_name = setLootParams)]
+    pub fn set_loot_params(&mut self, range: f32, fixed: f32, fraction: f32) -> Result<(), JsValue> {
+        self.inner.set_loot_params(range, fixed, fraction).map_err(config_err)
+    }
+
+    /// Cell size 0 turns the pheromone grid off
+    #[wasm_bindgen(js_name = setPheromoneParams)]
+    pub fn set_pheromone_params(&mut self, cell: f32, deposit: f32, decay: f32) -> Result<(), JsValue> {
+        self.inner.set_pheromone_params(cell, deposit, decay).map_err(config_err)
+    }
+}
+
+// Pheromone grid for rendering (all zero-sized while the grid is off)
+#[wasm_bindgen]
+impl WasmSimulation {
+    #[wasm_bindgen(js_name = pheromoneCols)]
+    pub fn pheromone_cols(&self) -> usize {
+        self.inner.pheromones().map_or(0, |g| g.cols())
+    }
+
+    #[wasm_bindgen(js_name = pheromoneRows)]
+    pub fn pheromone_rows(&self) -> usize {
+        self.inner.pheromones().map_or(0, |g| g.rows())
+    }
+
+    #[wasm_bindgen(js_name = pheromoneCellSize)]
+    pub fn pheromone_cell_size(&self) -> f32 {
+        self.inner.pheromones().map_or(0.0, |g| g.cell_size())
+    }
+
+    /// `team`'s trail strength per cell, row-major (empty until it deposits)
+    #[wasm_bindgen(js_name = pheromoneLayer)]
+    pub fn pheromone_layer(&self, team: u32) -> Float32Array {
+        Float32Array::from(self.inner.pheromones().map_or(&[][..], |g| g.layer(team)))
+    }
+}
+
+// Commands from JavaScript (override the agent's brain on the next step)
+#[wasm_bindgen]
+impl WasmSimulation {
+    /// Queue an action for `actor_id` from JSON, e.g. `{"type":"thrust","x":0,"y":1}`,
+    /// `{"type":"fire","weapon":{"kind":"laser","damage":1,"range":50}}`, `{"type":"loot"}`
+    #[wasm_bindgen(js_name = pushCommand)]
+    pub fn push_command(&mut self, actor_id: usize, action_json: &str) -> Result<(), JsValue> {
+        let action: Action = serde_json::from_str(action_json)
+            .map_err(|e| JsValue::from_str(&format!("invalid action JSON: {}", e)))?;
+        self.queue(actor_id, action)
+    }
+
+    /// Queue a thrust of (dx, dy) for `actor_id`
+    pub fn thrust(&mut self, actor_id: usize, dx: f32, dy: f32) -> Result<(), JsValue> {
+        self.queue(actor_id, Action::Thrust(Vec2 { x: dx, y: dy }))
+    }
+
+    /// Queue a shot with the agent's default weapon
+    pub fn fire(&mut self, actor_id: usize) -> Result<(), JsValue> {
+        self.check_actor(actor_id)?;
+        let weapon = self.inner.default_weapon(actor_id);
+        self.queue(actor_id, Action::Fire { weapon })
+    }
+
+    /// Queue a shot from loadout slot `slot` (clamped to the loadout)
+    #[wasm_bindgen(js_name = fireSlot)]
+    pub fn fire_slot(&mut self, actor_id: usize, slot: usize) -> Result<(), JsValue> {
+        self.queue(actor_id, Action::FireSlot { slot })
+    }
+
+    /// Queue a loot attempt for `actor_id`
+    pub fn loot(&mut self, actor_id: usize) -> Result<(), JsValue> {
+        self.queue(actor_id, Action::Loot)
+    }
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const SIM_STATE_TS: &'static str = r#"
+export interface AgentView {
+  id: number;
+  x: number;
+  y: number;
+  team: number;
+  health: number;
+  shield: number;
+  lastHit: number;
+  alive: boolean;
+  class: string | null;
+  heading: [number, number];
+  rearShield: number;
+  status: string[];
+}
+
+export interface WreckView { x: number; y: number; pool: number; }
+
+export interface BulletView { x: number; y: number; damage: number; ttl: number; }
+
+export interface HitView { x1: number; y1: number; x2: number; y2: number; }
+
+export interface EffectView { x: number; y: number; radius: number; }
+
+export interface Counters { thrust: number; fire: number; idle: number; loot: number; }
+
+export interface SimState {
+  tick: number;
+  width: number;
+  height: number;
+  agents: AgentView[];
+  wrecks: WreckView[];
+  bullets: BulletView[];
+  hits: HitView[];
+  effects: EffectView[];
+  counters: Counters;
+}
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "SimState")]
+    pub type SimStateJs;
+}
+
+// Structured state export
+#[wasm_bindgen]
+impl WasmSimulation {
+    /// Decoded agents, wrecks, bullets, hits, blasts, and counters as a plain JS object
+    pub fn state(&self) -> Result<SimStateJs, JsValue> {
+        let state = SimState::capture(&self.inner);
+        serde_wasm_bindgen::to_value(&state)
+            .map(|v| v.unchecked_into())
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+}
+
+// Replay recording (JSONL, same format as native `run_match_record`)
+#[wasm_bindgen]
+impl WasmSimulation {
+    /// Start buffering a frame per tick; restarts any recording in progress
+    #[wasm_bindgen(js_name = startRecording)]
+    pub fn start_recording(&mut self) {
+        self.inner.start_recording();
+    }
+
+    /// Stop recording and return the replay as JSONL text ("" if not recording)
+    #[wasm_bindgen(js_name = stopRecording)]
+    pub fn stop_recording(&mut self) -> String {
+        self.inner.stop_recording().map(|r| r.to_jsonl()).unwrap_or_default()
+    }
+
+    #[wasm_bindgen(js_name = isRecording)]
+    pub fn is_recording(&self) -> bool {
+        self.inner.recording().is_some()
+    }
+
+    /// Frames buffered so far
+    #[wasm_bindgen(js_name = recordedFrames)]
+    pub fn recorded_frames(&self) -> usize {
+        self.inner.recording().map_or(0, |r| r.len())
+    }
+
+    /// Current recording as JSONL without stopping
+    #[wasm_bindgen(js_name = replayJsonl)]
+    pub fn replay_jsonl(&self) -> String {
+        self.inner.recording().map(|r| r.to_jsonl()).unwrap_or_default()
+    }
+}
+
+fn snapshot_err(e: crate::snapshot::SnapshotError) -> JsValue {
+    JsValue::from_str(&e.to_string())
+}
+
+// Snapshot / rewind (world state only; brains keep their state)
+#[wasm_bindgen]
+impl WasmSimulation {
+    /// Current world state as a JSON snapshot
+    pub fn snapshot(&self) -> String {
+        self.inner.snapshot().to_json()
+    }
+
+    /// Restore a JSON snapshot taken from this simulation's agents
+    pub fn restore(&mut self, snapshot_json: &str) -> Result<(), JsValue> {
+        let snap = crate::snapshot::Snapshot::from_json(snapshot_json).map_err(snapshot_err)?;
+        self.inner.restore(&snap).map_err(snapshot_err)
+    }
+
+    /// Keep the last `capacity` snapshots, one every `interval` ticks (e.g. 60 × 10 ticks)
+    #[wasm_bindgen(js_name = enableAutoSnapshots)]
+    pub fn enable_auto_snapshots(&mut self, capacity: usize, interval: u32) {
+        self.inner.enable_auto_snapshots(capacity, interval);
+    }
+
+    #[wasm_bindgen(js_name = disableAutoSnapshots)]
+    pub fn disable_auto_snapshots(&mut self) {
+        self.inner.disable_auto_snapshots();
+    }
+
+    /// Ticks available for the rewind slider, oldest first
+    #[wasm_bindgen(js_name = snapshotTicks)]
+    pub fn snapshot_ticks(&self) -> Vec<u32> {
+        self.inner.snapshot_ticks()
+    }
+
+    /// Rewind to the latest snapshot at or before `tick`; returns the restored tick
+    #[wasm_bindgen(js_name = rewindTo)]
+    pub fn rewind_to(&mut self, tick: u32) -> Result<u32, JsValue> {
+        self.inner.rewind_to(tick).map_err(snapshot_err)
+    }
+
+    #[wasm_bindgen(js_name = tickCount)]
+    pub fn tick_count(&self) -> u32 {
+        self.inner.tick_count()
+    }
+}
+
+// JavaScript-defined brains: callback(inputs: Float32Array, view) -> action object
+#[wasm_bindgen]
+impl WasmSimulation {
+    /// Replace agent `agent_id`'s brain with a JS callback
+    #[wasm_bindgen(js_name = setJsBrain)]
+    pub fn set_js_brain(&mut self, agent_id: usize, callback: js_sys::Function) -> Result<(), JsValue> {
+        self.check_actor(agent_id)?;
+        if agent_id >= self.inner.agents_impl.len() {
+            return Err(JsValue::from_str(&format!("agent {} has no brain slot", agent_id)));
+        }
+        self.inner.agents_impl[agent_id] = Box::new(JsBrain::new(callback));
+        Ok(())
+    }
+
+    /// Spawn a new agent driven by a JS callback; returns its id
+    #[wasm_bindgen(js_name = spawnJsAgent)]
+    pub fn spawn_js_agent(&mut self, x: f32, y: f32, team: u32, callback: js_sys::Function) -> usize {
+        self.inner.spawn_agent(Vec2 { x, y }, team, Box::new(JsBrain::new(callback)))
+    }
+}
+
+impl WasmSimulation {
+    fn check_actor(&self, actor_id: usize) -> Result<(), JsValue> {
+        if actor_id >= self.inner.agent_count() {
+            return Err(JsValue::from_str(&format!(
+                "agent id {} out of range ({} agents)", actor_id, self.inner.agent_count()
+            )));
+        }
+        Ok(())
+    }
+
+    fn queue(&mut self, actor_id: usize, action: Action) -> Result<(), JsValue> {
+        self.check_actor(actor_id)?;
+        self.inner.push_command(actor_id, action);
+        Ok(())
+    }
+}
+
+/// Getter/setter pairs for config fields; setters validate and throw on bad values
+macro_rules! config_accessors {
+    ($($field:ident: $ty:ty => $get:ident / $get_js:literal, $set:ident / $set_js:literal;)*) => {
+        #[wasm_bindgen]
+        impl WasmSimulation {
+            $(
+                #[wasm_bindgen(js_name = $get_js)]
+                pub fn $get(&self) -> $ty {
+                    self.inner.config.$field
+                }
+
+                #[wasm_bindgen(js_name = $set_js)]
+                pub fn $set(&mut self, value: $ty) -> Result<(), JsValue> {
+                    self.inner.update_config(|c| c.$field = value).map_err(config_err)
+                }
+            )*
+        }
+    };
+}
+
+// maxShield/attackRange/sepRange/healthMax/lootInitRatio getters and the
+// attackRange/maxSpeed/friction setters are defined above
+config_accessors! {
+    sep_strength: f32 => sep_strength / "sepStrength", set_sep_strength / "setSepStrength";
+    view_range: f32 => view_range / "viewRange", set_view_range / "setViewRange";
+    shield_regen_delay: u32 => shield_regen_delay / "shieldRegenDelay", set_shield_regen_delay / "setShieldRegenDelay";
+    shield_regen_rate: f32 => shield_regen_rate / "shieldRegenRate", set_shield_regen_rate / "setShieldRegenRate";
+    loot_range: f32 => loot_range / "lootRange", set_loot_range / "setLootRange";
+    loot_fixed: f32 => loot_fixed / "lootFixed", set_loot_fixed / "setLootFixed";
+    loot_fraction: f32 => loot_fraction / "lootFraction", set_loot_fraction / "setLootFraction";
+    health_flee_ratio: f32 => health_flee_ratio / "healthFleeRatio", set_health_flee_ratio / "setHealthFleeRatio";
+    health_engage_ratio: f32 => health_engage_ratio / "healthEngageRatio", set_health_engage_ratio / "setHealthEngageRatio";
+    scan_max_dist: f32 => scan_max_dist / "scanMaxDist", set_scan_max_dist / "setScanMaxDist";
+    nearest_k_enemies: usize => nearest_k_enemies / "nearestKEnemies", set_nearest_k_enemies / "setNearestKEnemies";
+    nearest_k_allies: usize => nearest_k_allies / "nearestKAllies", set_nearest_k_allies / "setNearestKAllies";
+    nearest_k_wrecks: usize => nearest_k_wrecks / "nearestKWrecks", set_nearest_k_wrecks / "setNearestKWrecks";
+    use_tract: bool => use_tract / "useTract", set_use_tract / "setUseTract";
+    agent_radius: f32 => agent_radius / "agentRadius", set_agent_radius / "setAgentRadius";
+    bullet_radius: f32 => bullet_radius / "bulletRadius", set_bullet_radius / "setBulletRadius";
+    laser_falloff_floor: f32 => laser_falloff_floor / "laserFalloffFloor", set_laser_falloff_floor / "setLaserFalloffFloor";
+    tick_rate_hz: f32 => tick_rate_hz / "tickRateHz", set_tick_rate_hz / "setTickRateHz";
+    max_accel: f32 => max_accel / "maxAccel", set_max_accel / "setMaxAccel";
+    legacy_motion: bool => legacy_motion / "legacyMotion", set_legacy_motion / "setLegacyMotion";
+}
+
+// Accessors completing pairs that predate the macro
+#[wasm_bindgen]
+impl WasmSimulation {
+    pub fn friction(&self) -> f32 {
+        self.inner.config.friction
+    }
+
+    #[wasm_bindgen(js_name = maxSpeed)]
+    pub fn max_speed(&self) -> f32 {
+        self.inner.config.max_speed
+    }
+
+    #[wasm_bindgen(js_name = setSepRange)]
+    pub fn set_sep_range(&mut self, value: f32) -> Result<(), JsValue> {
+        self.inner.update_config(|c| c.sep_range = value).map_err(config_err)
+    }
+
+    #[wasm_bindgen(js_name = setMaxShield)]
+    pub fn set_max_shield(&mut self, value: f32) -> Result<(), JsValue> {
+        self.inner.update_config(|c| c.max_shield = value).map_err(config_err)
+    }
+
+    #[wasm_bindgen(js_name = setHealthMax)]
+    pub fn set_health_max(&mut self, value: f32) -> Result<(), JsValue> {
+        self.inner.update_config(|c| c.health_max = value).map_err(config_err)
+    }
+
+    #[wasm_bindgen(js_name = setLootInitRatio)]
+    pub fn set_loot_init_ratio(&mut self, value: f32) -> Result<(), JsValue> {
+        self.inner.update_config(|c| c.loot_init_ratio = value).map_err(config_err)
+    }
+
+    /// Full config as pretty JSON (for debugging panels)
+    #[wasm_bindgen(js_name = configJson)]
+    pub fn config_json(&self) -> String {
+        self.inner.config_json()
+    }
+
+    /// Replace the whole config from JSON; missing fields take defaults. Throws on invalid config.
+    /// Changing nearest_k sizes changes the sensor length, which NEAT brains depend on.
+    #[wasm_bindgen(js_name = setConfigJson)]
+    pub fn set_config_json(&mut self, json: &str) -> Result<(), JsValue> {
+        let cfg: crate::Config = serde_json::from_str(json)
+            .map_err(|e| JsValue::from_str(&format!("invalid config JSON: {}", e)))?;
+        self.inner.set_config(cfg).map_err(config_err)
+    }
+}
+
 // Enable better panic messages in WASM
 use console_error_panic_hook;
 
@@ -165,28 +670,93 @@ impl WasmSimulation {
         WasmSimulation { inner: Simulation::new_nn_vs_naive(width, height, orange, yellow, green, blue) }
     }
 
-    /// Head-to-head Champion JSON vs Naive duel constructor
+    /// Head-to-head Champion JSON vs Naive duel constructor; throws on invalid
+    /// JSON or a champion trained on other sensors
     #[wasm_bindgen(static_method_of = WasmSimulation, js_name = new_champ_vs_naive)]
-    pub fn new_champ_vs_naive(width: u32, height: u32, orange: u32, yellow: u32, green: u32, blue: u32, genome_json: &str) -> WasmSimulation {
-        // parse genome
-        let genome: Genome = serde_json::from_str(genome_json).expect("Invalid genome JSON");
-        // build default sim with stub NN vs naive
+    pub fn new_champ_vs_naive(width: u32, height: u32, orange: u32, yellow: u32, green: u32, blue: u32, genome_json: &str) -> Result<WasmSimulation, JsValue> {
         let mut ws = WasmSimulation::new(width, height, orange, yellow, green, blue);
-        let batch = 1;
-        let url = String::new();
-        // replace stub NN agents at TL quadrant
-        let start1 = 0;
-        let end1 = orange as usize;
-        for i in start1..end1 {
-            ws.inner.agents_impl[i] = Box::new(NeatBrain::new(genome.clone(), batch, url.clone()));
+        ws.adopt_motion_model(genome_json.as_bytes())?;
+        let genome = ws.load_champion(genome_json.as_bytes())?;
+        ws.install_genome(&genome, &[0, 3], [orange, yellow, green, blue]);
+        Ok(ws)
+    }
+
+    /// Seeded Champion JSON vs Naive duel constructor; throws on invalid JSON
+    /// or a champion trained on other sensors
+    #[wasm_bindgen(js_name = newChampVsNaiveSeeded)]
+    pub fn new_champ_vs_naive_seeded(width: u32, height: u32, orange: u32, yellow: u32, green: u32, blue: u32, genome_json: &str, seed: u32) -> Result<WasmSimulation, JsValue> {
+        let mut ws = WasmSimulation::new_seeded(width, height, orange, yellow, green, blue, seed);
+        ws.adopt_motion_model(genome_json.as_bytes())?;
+        let genome = ws.load_champion(genome_json.as_bytes())?;
+        ws.install_genome(&genome, &[0, 3], [orange, yellow, green, blue]);
+        Ok(ws)
+    }
+
+    /// Champion vs Naive from champion file bytes (bundle, bare, or
+    /// `{metadata, genome}` JSON, optionally gzip-compressed); throws on
+    /// invalid input or a sensor layout this sim does not produce
+    #[wasm_bindgen(js_name = loadChampVsNaive)]
+    pub fn load_champ_vs_naive(width: u32, height: u32, orange: u32, yellow: u32, green: u32, blue: u32, champion: &[u8], seed: Option<u32>) -> Result<WasmSimulation, JsValue> {
+        let mut ws = WasmSimulation::create(width, height, [orange, yellow, green, blue], seed);
+        ws.adopt_motion_model(champion)?;
+        let genome = ws.load_champion(champion)?;
+        ws.install_genome(&genome, &[0, 3], [orange, yellow, green, blue]);
+        Ok(ws)
+    }
+
+    /// Champion A (TL & BR quadrants) vs champion B (TR & BL) from champion file bytes
+    #[wasm_bindgen(js_name = loadChampVsChamp)]
+    pub fn load_champ_vs_champ(width: u32, height: u32, orange: u32, yellow: u32, green: u32, blue: u32, champion_a: &[u8], champion_b: &[u8], seed: Option<u32>) -> Result<WasmSimulation, JsValue> {
+        let counts = [orange, yellow, green, blue];
+        let mut ws = WasmSimulation::create(width, height, counts, seed);
+        // champion B must fly under the same motion model as A
+        ws.adopt_motion_model(champion_a)?;
+        let a = ws.load_champion(champion_a)?;
+        let b = ws.load_champion(champion_b)?;
+        ws.install_genome(&a, &[0, 3], counts);
+        ws.install_genome(&b, &[1, 2], counts);
+        Ok(ws)
+    }
+}
+
+/// Convert a champion load error into a JS exception value
+fn champion_err(e: BundleError) -> JsValue {
+    JsValue::from_str(&e.to_string())
+}
+
+impl WasmSimulation {
+    /// Quadrant sim, seeded if a seed is given
+    fn create(width: u32, height: u32, counts: [u32; 4], seed: Option<u32>) -> WasmSimulation {
+        let [o, y, g, b] = counts;
+        match seed {
+            Some(seed) => WasmSimulation::new_seeded(width, height, o, y, g, b, seed),
+            None => WasmSimulation::new(width, height, o, y, g, b),
+        }
+    }
+
+    /// Switch to the legacy motion model if the champion was trained under it
+    fn adopt_motion_model(&mut self, bytes: &[u8]) -> Result<(), JsValue> {
+        if !trained_with_legacy_motion(bytes) {
+            return Ok(());
         }
-        // replace stub NN agents at BR quadrant
-        let skip = (orange + yellow + green) as usize;
-        let start2 = skip;
-        let end2 = start2 + blue as usize;
-        for i in start2..end2 {
-            ws.inner.agents_impl[i] = Box::new(NeatBrain::new(genome.clone(), batch, url.clone()));
+        self.inner.update_config(|c| c.legacy_motion = true).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Champion from file bytes, checked against this sim's sensor layout and motion model
+    fn load_champion(&self, bytes: &[u8]) -> Result<Genome, JsValue> {
+        genome_for_config(bytes, &self.inner.config).map_err(champion_err)
+    }
+
+    /// Give every agent in the listed quadrants (0=TL,1=TR,2=BL,3=BR) a NEAT
+    /// brain, using tract inference if the build and config enable it
+    fn install_genome(&mut self, genome: &Genome, quadrants: &[usize], counts: [u32; 4]) {
+        let cfg = crate::config::Config { python_service_url: None, batch_size: 1, ..self.inner.config.clone() };
+        for &q in quadrants {
+            let start: u32 = counts[..q].iter().sum();
+            let end = start + counts[q];
+            for i in start as usize..end as usize {
+                self.inner.agents_impl[i] = Box::new(NeatBrain::for_config(genome.clone(), &cfg));
+            }
         }
-        ws
     }
 }